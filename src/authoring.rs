@@ -0,0 +1,306 @@
+//! Drafting `.jumble` configuration with the client's own model.
+//!
+//! When the MCP client advertises the `sampling` capability, the
+//! `author_project_config` tool gathers the README and manifest files of a
+//! directory, asks the client to run a `sampling/createMessage` request, and
+//! turns the reply into draft `project.toml` / `conventions.toml` files. The
+//! drafts are only written to disk once the caller confirms them.
+
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+use crate::config::{ProjectConfig, ProjectConventions};
+use crate::migrate::{self, ConfigKind};
+use crate::paths;
+
+/// Manifest files whose contents help the model infer name, language and commands.
+const MANIFEST_FILES: &[&str] = &[
+    "Cargo.toml",
+    "package.json",
+    "pyproject.toml",
+    "setup.py",
+    "go.mod",
+    "composer.json",
+    "pom.xml",
+    "build.gradle",
+    "build.gradle.kts",
+    "Makefile",
+    "justfile",
+];
+
+/// README candidates, in order of preference.
+const README_FILES: &[&str] = &["README.md", "README", "README.rst", "readme.md"];
+
+/// Per-file cap so large READMEs don't blow the sampling budget.
+const MAX_FILE_CHARS: usize = 8_000;
+
+/// A drafted set of config files awaiting confirmation.
+#[derive(Debug, Clone)]
+pub struct AuthorDraft {
+    pub project_toml: String,
+    pub conventions_toml: Option<String>,
+}
+
+/// Build the `sampling/createMessage` params for drafting config in `directory`.
+pub fn sampling_request_params(directory: &Path) -> Result<Value, String> {
+    if !directory.is_dir() {
+        return Err(format!(
+            "Directory '{}' does not exist",
+            directory.display()
+        ));
+    }
+
+    let sources = collect_sources(directory);
+    if sources.is_empty() {
+        return Err(format!(
+            "No README or manifest files found in '{}' to draft from",
+            directory.display()
+        ));
+    }
+
+    let mut prompt = format!(
+        "Draft Jumble context files for the project described below.\n\n\
+         Reply with exactly two fenced ```toml blocks:\n\
+         1. `{}` with a [project] table (name, description, language, \
+         version, repository), [commands], [entry_points] and 3-5 [concepts.*] entries \
+         (each with `files` and `summary`).\n\
+         2. `{}` with [conventions] and [gotchas] tables.\n\n\
+         Only use information that can be inferred from these files. Keep descriptions \
+         and summaries to one sentence.\n",
+        paths::config_file("project.toml"),
+        paths::config_file("conventions.toml")
+    );
+
+    for (name, content) in &sources {
+        prompt.push_str(&format!("\n--- {} ---\n{}\n", name, content));
+    }
+
+    Ok(json!({
+        "messages": [{
+            "role": "user",
+            "content": {
+                "type": "text",
+                "text": prompt
+            }
+        }],
+        "systemPrompt": "You write concise, valid TOML configuration for the Jumble MCP server. Follow https://github.com/velvet-tiger/jumble/blob/main/AUTHORING.md.",
+        "includeContext": "none",
+        "maxTokens": 2000
+    }))
+}
+
+/// Turn a `sampling/createMessage` result into a validated draft.
+pub fn draft_from_sampling_result(result: &Value) -> Result<AuthorDraft, String> {
+    let text = result
+        .get("content")
+        .and_then(|c| c.get("text"))
+        .and_then(|t| t.as_str())
+        .ok_or("Sampling result did not contain text content")?;
+
+    let blocks = extract_toml_blocks(text);
+
+    let project_toml = blocks
+        .iter()
+        .find(|b| b.contains("[project]"))
+        .cloned()
+        .ok_or("Model reply did not contain a project.toml block with a [project] table")?;
+    toml::from_str::<ProjectConfig>(&project_toml)
+        .map_err(|e| format!("Drafted project.toml is not valid: {}", e))?;
+
+    let conventions_toml = blocks
+        .iter()
+        .find(|b| b.contains("[conventions]") || b.contains("[gotchas]"))
+        .cloned();
+    if let Some(conventions) = &conventions_toml {
+        toml::from_str::<ProjectConventions>(conventions)
+            .map_err(|e| format!("Drafted conventions.toml is not valid: {}", e))?;
+    }
+
+    Ok(AuthorDraft {
+        project_toml,
+        conventions_toml,
+    })
+}
+
+/// Render a draft for review before the caller confirms it.
+pub fn format_draft(directory: &Path, draft: &AuthorDraft) -> String {
+    let mut output = format!("# Draft Jumble config for {}\n\n", directory.display());
+    output.push_str(&format!(
+        "## {}\n\n```toml\n",
+        paths::config_file("project.toml")
+    ));
+    output.push_str(draft.project_toml.trim_end());
+    output.push_str("\n```\n");
+    if let Some(conventions) = &draft.conventions_toml {
+        output.push_str(&format!(
+            "\n## {}\n\n```toml\n",
+            paths::config_file("conventions.toml")
+        ));
+        output.push_str(conventions.trim_end());
+        output.push_str("\n```\n");
+    }
+    output.push_str(
+        "\nReview the draft with the user, then call author_project_config again with \
+         `confirm: true` to write these files.",
+    );
+    output
+}
+
+/// Write a confirmed draft into `<directory>/.jumble/`.
+///
+/// Existing files are left untouched unless `overwrite` is set.
+pub fn write_draft(
+    directory: &Path,
+    draft: &AuthorDraft,
+    overwrite: bool,
+) -> Result<Vec<PathBuf>, String> {
+    let jumble_dir = directory.join(paths::config_dir());
+    let mut targets = vec![(
+        jumble_dir.join("project.toml"),
+        migrate::upgrade_source(ConfigKind::Project, &draft.project_toml)?,
+    )];
+    if let Some(conventions) = &draft.conventions_toml {
+        targets.push((
+            jumble_dir.join("conventions.toml"),
+            migrate::upgrade_source(ConfigKind::Conventions, conventions)?,
+        ));
+    }
+
+    if !overwrite {
+        if let Some((existing, _)) = targets.iter().find(|(path, _)| path.exists()) {
+            return Err(format!(
+                "{} already exists. Pass `overwrite: true` to replace it.",
+                existing.display()
+            ));
+        }
+    }
+
+    std::fs::create_dir_all(&jumble_dir)
+        .map_err(|e| format!("Failed to create {}: {}", jumble_dir.display(), e))?;
+
+    let mut written = Vec::new();
+    for (path, content) in targets {
+        let mut content = content.trim_end().to_string();
+        content.push('\n');
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+fn collect_sources(directory: &Path) -> Vec<(String, String)> {
+    let mut sources = Vec::new();
+
+    if let Some((name, content)) = README_FILES
+        .iter()
+        .find_map(|name| read_capped(&directory.join(name)).map(|c| (name.to_string(), c)))
+    {
+        sources.push((name, content));
+    }
+
+    for name in MANIFEST_FILES {
+        if let Some(content) = read_capped(&directory.join(name)) {
+            sources.push((name.to_string(), content));
+        }
+    }
+
+    sources
+}
+
+fn read_capped(path: &Path) -> Option<String> {
+    let content = crate::text::read_text(path).ok()?;
+    if content.chars().count() > MAX_FILE_CHARS {
+        let truncated: String = content.chars().take(MAX_FILE_CHARS).collect();
+        Some(format!("{}\n[truncated]", truncated))
+    } else {
+        Some(content)
+    }
+}
+
+/// Extract the bodies of fenced code blocks tagged `toml` (or untagged).
+fn extract_toml_blocks(text: &str) -> Vec<String> {
+    let mut blocks = Vec::new();
+    let mut current: Option<String> = None;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        match current.as_mut() {
+            None => {
+                if let Some(tag) = trimmed.strip_prefix("```") {
+                    let tag = tag.trim();
+                    if tag.is_empty() || tag.eq_ignore_ascii_case("toml") {
+                        current = Some(String::new());
+                    }
+                }
+            }
+            Some(block) => {
+                if trimmed.starts_with("```") {
+                    blocks.push(current.take().unwrap_or_default());
+                } else {
+                    block.push_str(line);
+                    block.push('\n');
+                }
+            }
+        }
+    }
+
+    blocks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const REPLY: &str = "Here you go:\n\n```toml\n[project]\nname = \"demo\"\ndescription = \"Demo app\"\nlanguage = \"rust\"\n```\n\n```toml\n[conventions]\nerrors = \"Use anyhow\"\n\n[gotchas]\nstdout = \"Never print to stdout\"\n```\n";
+
+    #[test]
+    fn test_sampling_request_includes_readme_and_manifest() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("README.md"), "# Demo\nA demo app").unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), "[package]\nname = \"demo\"").unwrap();
+
+        let params = sampling_request_params(temp.path()).unwrap();
+        let text = params["messages"][0]["content"]["text"].as_str().unwrap();
+        assert!(text.contains("--- README.md ---"));
+        assert!(text.contains("--- Cargo.toml ---"));
+        assert!(params["maxTokens"].as_u64().is_some());
+    }
+
+    #[test]
+    fn test_sampling_request_requires_sources() {
+        let temp = TempDir::new().unwrap();
+        assert!(sampling_request_params(temp.path()).is_err());
+    }
+
+    #[test]
+    fn test_draft_from_sampling_result() {
+        let result = json!({"role": "assistant", "content": {"type": "text", "text": REPLY}});
+        let draft = draft_from_sampling_result(&result).unwrap();
+        assert!(draft.project_toml.contains("name = \"demo\""));
+        assert!(draft.conventions_toml.unwrap().contains("[gotchas]"));
+    }
+
+    #[test]
+    fn test_draft_rejects_invalid_project_toml() {
+        let text = "```toml\n[project]\nname = \"missing description\"\n```";
+        let result = json!({"content": {"type": "text", "text": text}});
+        let err = draft_from_sampling_result(&result).unwrap_err();
+        assert!(err.contains("not valid"));
+    }
+
+    #[test]
+    fn test_write_draft_refuses_to_overwrite() {
+        let temp = TempDir::new().unwrap();
+        let result = json!({"content": {"type": "text", "text": REPLY}});
+        let draft = draft_from_sampling_result(&result).unwrap();
+
+        let written = write_draft(temp.path(), &draft, false).unwrap();
+        assert_eq!(written.len(), 2);
+        assert!(temp.path().join(".jumble/project.toml").is_file());
+
+        assert!(write_draft(temp.path(), &draft, false).is_err());
+        assert!(write_draft(temp.path(), &draft, true).is_ok());
+    }
+}
@@ -0,0 +1,387 @@
+//! Commands a project's `[commands]` table leaves out: targets discovered in
+//! its Makefile, justfile and package.json scripts, and default commands
+//! inferred from its language or build manifest.
+//!
+//! Discovered and inferred commands are always reported as such, so agents
+//! can tell them apart from commands someone actually wrote down.
+
+use serde_json::Value;
+use std::path::Path;
+
+use crate::config::ProjectConfig;
+use crate::text;
+
+/// Commands filled in for a project, and what they were inferred from.
+#[derive(Debug, PartialEq)]
+pub struct InferredCommands {
+    /// `language = "rust"` or the manifest file name, for labelling.
+    pub source: String,
+    /// `(kind, command)` pairs, sorted by kind.
+    pub commands: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Toolchain {
+    Rust,
+    Go,
+    Node,
+    Python,
+}
+
+/// Manifests checked, in order, when no language is declared.
+const MANIFESTS: &[(&str, Toolchain)] = &[
+    ("Cargo.toml", Toolchain::Rust),
+    ("go.mod", Toolchain::Go),
+    ("package.json", Toolchain::Node),
+    ("pyproject.toml", Toolchain::Python),
+];
+
+impl Toolchain {
+    fn from_language(language: &str) -> Option<Self> {
+        match language.trim().to_lowercase().as_str() {
+            "rust" => Some(Self::Rust),
+            "go" | "golang" => Some(Self::Go),
+            "javascript" | "typescript" | "node" | "nodejs" => Some(Self::Node),
+            "python" => Some(Self::Python),
+            _ => None,
+        }
+    }
+
+    /// `language` value written into scaffolded project files.
+    fn language(self, root: &Path) -> &'static str {
+        match self {
+            Self::Rust => "rust",
+            Self::Go => "go",
+            Self::Node if root.join("tsconfig.json").is_file() => "typescript",
+            Self::Node => "javascript",
+            Self::Python => "python",
+        }
+    }
+
+    fn defaults(self, root: &Path) -> Vec<(&'static str, String)> {
+        match self {
+            Self::Rust => vec![
+                ("build", "cargo build".to_string()),
+                ("test", "cargo test".to_string()),
+                ("lint", "cargo clippy --all-targets".to_string()),
+            ],
+            Self::Go => vec![
+                ("build", "go build ./...".to_string()),
+                ("test", "go test ./...".to_string()),
+                ("lint", "go vet ./...".to_string()),
+            ],
+            Self::Node => node_scripts(root),
+            Self::Python => vec![("test", "python -m pytest".to_string())],
+        }
+    }
+}
+
+/// Commands for the standard kinds missing from `config.commands`, or
+/// `None` when the toolchain is unknown or nothing is missing.
+pub fn infer(root: &Path, config: &ProjectConfig) -> Option<InferredCommands> {
+    let (toolchain, source) = match &config.project.language {
+        Some(language) => (
+            Toolchain::from_language(language)?,
+            format!("language = \"{}\"", language),
+        ),
+        None => MANIFESTS
+            .iter()
+            .find(|(file, _)| root.join(file).is_file())
+            .map(|(file, toolchain)| (*toolchain, file.to_string()))?,
+    };
+
+    let mut commands: Vec<(String, String)> = toolchain
+        .defaults(root)
+        .into_iter()
+        .filter(|(kind, _)| !config.commands.contains_key(*kind))
+        .map(|(kind, command)| (kind.to_string(), command))
+        .collect();
+    if commands.is_empty() {
+        return None;
+    }
+    commands.sort();
+    Some(InferredCommands { source, commands })
+}
+
+/// The language of the first manifest found in `root`, or `None` when
+/// there is no recognizable manifest.
+pub fn detect_language(root: &Path) -> Option<&'static str> {
+    MANIFESTS
+        .iter()
+        .find(|(file, _)| root.join(file).is_file())
+        .map(|(_, toolchain)| toolchain.language(root))
+}
+
+/// `build`, `test` and `lint` scripts from package.json, run with the
+/// package manager whose lockfile is present.
+fn node_scripts(root: &Path) -> Vec<(&'static str, String)> {
+    let Some(manifest) = package_json(root) else {
+        return Vec::new();
+    };
+    let runner = node_runner(root);
+
+    ["build", "test", "lint"]
+        .into_iter()
+        .filter(|kind| manifest["scripts"].get(kind).is_some())
+        .map(|kind| (kind, format!("{} run {}", runner, kind)))
+        .collect()
+}
+
+fn package_json(root: &Path) -> Option<Value> {
+    let content = text::read_text(&root.join("package.json")).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn node_runner(root: &Path) -> &'static str {
+    if root.join("pnpm-lock.yaml").is_file() {
+        "pnpm"
+    } else if root.join("yarn.lock").is_file() {
+        "yarn"
+    } else {
+        "npm"
+    }
+}
+
+/// Commands found in one task runner file.
+#[derive(Debug, PartialEq)]
+pub struct DiscoveredCommands {
+    /// File name, such as `Makefile`.
+    pub source: String,
+    /// `(name, command)` pairs, in file order.
+    pub commands: Vec<(String, String)>,
+}
+
+const MAKEFILES: &[&str] = &["GNUmakefile", "Makefile", "makefile"];
+const JUSTFILES: &[&str] = &["justfile", "Justfile", ".justfile"];
+
+/// Makefile targets, justfile recipes and package.json scripts, in that
+/// order. Names already in `[commands]`, or found in an earlier file, are
+/// left out.
+pub fn discover(root: &Path, config: &ProjectConfig) -> Vec<DiscoveredCommands> {
+    let mut found: Vec<DiscoveredCommands> = Vec::new();
+    let mut add = |source: &str, names: Vec<String>, command: &dyn Fn(&str) -> String| {
+        let commands: Vec<(String, String)> = names
+            .into_iter()
+            .filter(|name| {
+                !config.commands.contains_key(name)
+                    && !found
+                        .iter()
+                        .any(|d| d.commands.iter().any(|(n, _)| n == name))
+            })
+            .map(|name| {
+                let cmd = command(&name);
+                (name, cmd)
+            })
+            .collect();
+        if !commands.is_empty() {
+            found.push(DiscoveredCommands {
+                source: source.to_string(),
+                commands,
+            });
+        }
+    };
+
+    let read_first = |names: &[&'static str]| {
+        names.iter().find_map(|name| {
+            let content = text::read_text(&root.join(name)).ok()?;
+            Some((*name, content))
+        })
+    };
+    if let Some((file, content)) = read_first(MAKEFILES) {
+        add(file, make_targets(&content), &|t| format!("make {}", t));
+    }
+    if let Some((file, content)) = read_first(JUSTFILES) {
+        add(file, just_recipes(&content), &|r| format!("just {}", r));
+    }
+    if let Some(manifest) = package_json(root) {
+        let runner = node_runner(root);
+        add("package.json", npm_scripts(&manifest), &|s| {
+            format!("{} run {}", runner, s)
+        });
+    }
+    found
+}
+
+/// Explicit targets, skipping special (`.PHONY`), pattern (`%.o`) and
+/// variable-based targets, and variable assignments such as `CC := gcc`.
+fn make_targets(content: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    for line in content.lines() {
+        if line.starts_with([' ', '\t', '#']) {
+            continue;
+        }
+        let Some((names, rest)) = line.split_once(':') else {
+            continue;
+        };
+        if rest.starts_with('=') || names.contains('=') {
+            continue;
+        }
+        for name in names.split_whitespace() {
+            let valid = !name.starts_with('.')
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+            if valid && !targets.iter().any(|t| t == name) {
+                targets.push(name.to_string());
+            }
+        }
+    }
+    targets
+}
+
+/// Public recipes: names starting with `_` and those marked `[private]`
+/// are skipped, as `just --list` does.
+fn just_recipes(content: &str) -> Vec<String> {
+    let mut recipes = Vec::new();
+    let mut private = false;
+    for line in content.lines() {
+        if line.starts_with([' ', '\t', '#']) || line.trim().is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            private |= line.contains("private");
+            continue;
+        }
+        let is_private = std::mem::take(&mut private);
+        let Some((head, rest)) = line.split_once(':') else {
+            continue;
+        };
+        if rest.starts_with('=') {
+            continue;
+        }
+        let mut words = head.split_whitespace();
+        let Some(name) = words.next().map(|w| w.trim_start_matches('@')) else {
+            continue;
+        };
+        if matches!(name, "set" | "alias" | "export" | "import" | "mod") {
+            continue;
+        }
+        let valid = name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+        if valid && !name.is_empty() && !name.starts_with('_') && !is_private {
+            recipes.push(name.to_string());
+        }
+    }
+    recipes
+}
+
+/// Script names, without `pre`/`post` hooks of other scripts.
+fn npm_scripts(manifest: &Value) -> Vec<String> {
+    let Some(scripts) = manifest.get("scripts").and_then(|s| s.as_object()) else {
+        return Vec::new();
+    };
+    let is_hook = |name: &str| {
+        ["pre", "post"].iter().any(|prefix| {
+            name.strip_prefix(prefix)
+                .is_some_and(|rest| scripts.contains_key(rest))
+        })
+    };
+    scripts
+        .keys()
+        .filter(|name| !is_hook(name))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn config(toml: &str) -> ProjectConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_infers_missing_rust_commands_from_manifest() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("Cargo.toml"), "[package]\n").unwrap();
+        let config = config(
+            "[project]\nname = \"a\"\ndescription = \"A\"\n[commands]\ntest = \"cargo nextest run\"\n",
+        );
+
+        let inferred = infer(temp.path(), &config).unwrap();
+        assert_eq!(inferred.source, "Cargo.toml");
+        assert_eq!(
+            inferred.commands,
+            vec![
+                ("build".to_string(), "cargo build".to_string()),
+                ("lint".to_string(), "cargo clippy --all-targets".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_declared_language_wins_over_manifests() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("Cargo.toml"), "[package]\n").unwrap();
+        fs::write(
+            temp.path().join("package.json"),
+            r#"{"scripts": {"test": "vitest", "dev": "vite"}}"#,
+        )
+        .unwrap();
+        fs::write(temp.path().join("pnpm-lock.yaml"), "").unwrap();
+
+        let node =
+            config("[project]\nname = \"a\"\ndescription = \"A\"\nlanguage = \"TypeScript\"\n");
+        let inferred = infer(temp.path(), &node).unwrap();
+        assert_eq!(inferred.source, "language = \"TypeScript\"");
+        assert_eq!(
+            inferred.commands,
+            vec![("test".to_string(), "pnpm run test".to_string())]
+        );
+
+        let cobol = config("[project]\nname = \"a\"\ndescription = \"A\"\nlanguage = \"cobol\"\n");
+        assert!(infer(temp.path(), &cobol).is_none());
+    }
+
+    #[test]
+    fn test_discover_reads_task_runner_files() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("Makefile"),
+            "CC := gcc\n.PHONY: build test\n\nbuild: deps\n\tcargo build\n\ntest lint:\n\tcargo test\n%.o: %.c\n\t$(CC) -c $<\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("justfile"),
+            "set shell := [\"bash\", \"-c\"]\nversion := \"1\"\n\n# Run it\nrun *args:\n    cargo run {{args}}\n\n[private]\nhelper:\n    true\n\n_hidden:\n    true\n\n@test:\n    cargo test\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("package.json"),
+            r#"{"scripts": {"dev": "vite", "predev": "gen", "test": "vitest"}}"#,
+        )
+        .unwrap();
+        fs::write(temp.path().join("yarn.lock"), "").unwrap();
+        let config = config(
+            "[project]\nname = \"a\"\ndescription = \"A\"\n[commands]\nlint = \"cargo clippy\"\n",
+        );
+
+        let pairs = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+            pairs
+                .iter()
+                .map(|(n, c)| (n.to_string(), c.to_string()))
+                .collect()
+        };
+        assert_eq!(
+            discover(temp.path(), &config),
+            vec![
+                DiscoveredCommands {
+                    source: "Makefile".to_string(),
+                    commands: pairs(&[("build", "make build"), ("test", "make test")]),
+                },
+                DiscoveredCommands {
+                    source: "justfile".to_string(),
+                    commands: pairs(&[("run", "just run")]),
+                },
+                DiscoveredCommands {
+                    source: "package.json".to_string(),
+                    commands: pairs(&[("dev", "yarn run dev")]),
+                },
+            ]
+        );
+    }
+}
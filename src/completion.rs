@@ -0,0 +1,348 @@
+//! `completion/complete`: suggest argument values from the live workspace,
+//! so clients can offer project, concept, topic and command names instead
+//! of letting a typo fail the call.
+//!
+//! Values depend on the argument's name, not on what is being completed:
+//! `project` completes project names, `concept` the project's concepts,
+//! `topic` its skills or docs, and `command_type` its commands, including
+//! those only defined in a profile. `context.arguments.project` narrows the
+//! last three to one project; without it they cover every project.
+//!
+//! References may be the spec's `ref/prompt` and `ref/resource` (the
+//! templates in `resources/templates/list`), or `ref/tool` with a tool name,
+//! which jumble accepts so clients can complete tool arguments too. The tool
+//! or template decides whether `topic` means skills, docs or message topics.
+//!
+//! Values starting with what was typed come first, then values containing
+//! it. When nothing does, values within a couple of edits are offered, so
+//! `aip` still suggests `api`.
+
+use serde_json::{json, Value};
+use std::collections::{BTreeSet, HashMap};
+
+use crate::protocol::{self, JsonRpcError};
+use crate::resources;
+use crate::tools::ProjectData;
+
+/// Most values one response carries, as the spec allows.
+pub const MAX_VALUES: usize = 100;
+
+/// Most edits a value may be from the input to be offered as a correction.
+const MAX_TYPO_DISTANCE: usize = 2;
+
+/// What a `topic` argument names.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Topics {
+    Skills,
+    Docs,
+    Messages,
+    /// Skills and docs, when the reference doesn't say which.
+    Any,
+}
+
+fn invalid_params(message: &str) -> JsonRpcError {
+    JsonRpcError {
+        code: protocol::INVALID_PARAMS,
+        message: message.to_string(),
+        data: None,
+    }
+}
+
+/// Which topics a reference's `topic` argument takes.
+fn topics_for(reference: &Value) -> Result<Topics, JsonRpcError> {
+    let field = |key: &str| reference.get(key).and_then(|v| v.as_str());
+    match field("type") {
+        Some("ref/tool") => Ok(match field("name") {
+            Some("get_skill" | "run_skill_script") => Topics::Skills,
+            Some("get_docs") => Topics::Docs,
+            Some("get_messaging_info") => Topics::Messages,
+            _ => Topics::Any,
+        }),
+        Some("ref/resource") => match field("uri") {
+            Some(resources::DOC_TEMPLATE) => Ok(Topics::Docs),
+            Some(resources::SKILL_TEMPLATE) => Ok(Topics::Skills),
+            Some(uri) => Err(invalid_params(&format!(
+                "Unknown resource template '{}'",
+                uri
+            ))),
+            None => Err(invalid_params("ref/resource must include a string 'uri'")),
+        },
+        Some("ref/prompt") => Ok(Topics::Any),
+        _ => Err(invalid_params(
+            "'ref' must have a type of ref/prompt, ref/resource or ref/tool",
+        )),
+    }
+}
+
+/// Every value `argument` can take, sorted, within `project` when given.
+fn candidates(
+    argument: &str,
+    topics: Topics,
+    project: Option<&str>,
+    projects: &HashMap<String, ProjectData>,
+) -> BTreeSet<String> {
+    if argument == "project" {
+        return projects.keys().cloned().collect();
+    }
+    let scoped = projects
+        .iter()
+        .filter(|(name, _)| project.is_none_or(|project| *name == project))
+        .map(|(_, data)| data);
+    let mut values = BTreeSet::new();
+    for (_, config, skills, _, docs, _) in scoped {
+        match argument {
+            "concept" => values.extend(config.concepts.keys().cloned()),
+            "command_type" => {
+                values.extend(config.commands.keys().cloned());
+                for profile in config.profiles.values() {
+                    values.extend(profile.commands.keys().cloned());
+                }
+            }
+            "topic" => {
+                if matches!(topics, Topics::Skills | Topics::Any) {
+                    values.extend(skills.skills.keys().cloned());
+                }
+                if matches!(topics, Topics::Docs | Topics::Any) {
+                    values.extend(docs.docs.keys().cloned());
+                }
+                if let (Topics::Messages, Some(messaging)) = (topics, &config.messaging) {
+                    values.extend(messaging.topics.keys().cloned());
+                }
+            }
+            _ => {}
+        }
+    }
+    values
+}
+
+/// Levenshtein distance between `a` and `b`, by character.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = previous[j] + usize::from(ca != *cb);
+            current.push(substitute.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// `candidates` matching `typed`, best first.
+fn rank(candidates: BTreeSet<String>, typed: &str) -> Vec<String> {
+    let typed = typed.to_lowercase();
+    let (mut prefixed, mut containing) = (Vec::new(), Vec::new());
+    for candidate in &candidates {
+        let lower = candidate.to_lowercase();
+        if lower.starts_with(&typed) {
+            prefixed.push(candidate.clone());
+        } else if lower.contains(&typed) {
+            containing.push(candidate.clone());
+        }
+    }
+    prefixed.append(&mut containing);
+    if !prefixed.is_empty() {
+        return prefixed;
+    }
+    let mut close: Vec<(usize, String)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&candidate.to_lowercase(), &typed), candidate))
+        .filter(|(distance, _)| *distance <= MAX_TYPO_DISTANCE)
+        .collect();
+    close.sort();
+    close.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+/// Build the `completion/complete` result for `params`.
+pub fn complete(
+    params: &Value,
+    projects: &HashMap<String, ProjectData>,
+) -> Result<Value, JsonRpcError> {
+    let reference = params
+        .get("ref")
+        .filter(|r| r.is_object())
+        .ok_or_else(|| invalid_params("completion/complete params must include a 'ref' object"))?;
+    let topics = topics_for(reference)?;
+    let argument = &params["argument"];
+    let name = argument["name"].as_str().ok_or_else(|| {
+        invalid_params("completion/complete params must include an 'argument' with a string 'name'")
+    })?;
+    let typed = argument["value"].as_str().unwrap_or_default();
+    let project = params
+        .pointer("/context/arguments/project")
+        .and_then(|v| v.as_str())
+        .filter(|project| projects.contains_key(*project));
+
+    let values = rank(candidates(name, topics, project, projects), typed);
+    let total = values.len();
+    let values: Vec<String> = values.into_iter().take(MAX_VALUES).collect();
+    Ok(json!({
+        "completion": {
+            "values": values,
+            "total": total,
+            "hasMore": total > MAX_VALUES
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{DocEntry, ProjectConfig, ProjectConventions, ProjectDocs, ProjectSkills};
+    use crate::memory;
+    use tempfile::TempDir;
+
+    fn project(temp: &TempDir, name: &str, toml: &str, docs: &[&str]) -> (String, ProjectData) {
+        let root = temp.path().join(name);
+        std::fs::create_dir_all(&root).unwrap();
+        let config: ProjectConfig = toml::from_str(toml).unwrap();
+        let docs = ProjectDocs {
+            docs: docs
+                .iter()
+                .map(|topic| {
+                    let entry = DocEntry {
+                        path: format!("docs/{}.md", topic),
+                        summary: String::new(),
+                    };
+                    (topic.to_string(), entry)
+                })
+                .collect(),
+        };
+        let db = memory::open_or_create_memory_db(&root).unwrap();
+        let data = (
+            root,
+            config,
+            ProjectSkills::default(),
+            ProjectConventions::default(),
+            docs,
+            db,
+        );
+        (name.to_string(), data)
+    }
+
+    fn projects(temp: &TempDir) -> HashMap<String, ProjectData> {
+        HashMap::from([
+            project(
+                temp,
+                "api",
+                r#"
+[project]
+name = "api"
+description = "API"
+
+[commands]
+build = "cargo build"
+test = "cargo test"
+
+[profiles.ci.commands]
+lint = "cargo clippy"
+
+[concepts.auth]
+files = ["src/auth.rs"]
+summary = "Login"
+
+[concepts.billing]
+files = ["src/billing.rs"]
+summary = "Invoices"
+"#,
+                &["setup", "deploy"],
+            ),
+            project(
+                temp,
+                "web",
+                "[project]\nname = \"web\"\ndescription = \"Web\"\n\n[commands]\ndev = \"npm run dev\"\n",
+                &[],
+            ),
+            project(
+                temp,
+                "apigw",
+                "[project]\nname = \"apigw\"\ndescription = \"Gateway\"\n",
+                &[],
+            ),
+        ])
+    }
+
+    fn values(result: Value) -> Vec<String> {
+        serde_json::from_value(result["completion"]["values"].clone()).unwrap()
+    }
+
+    #[test]
+    fn test_complete_arguments_from_workspace() {
+        let temp = TempDir::new().unwrap();
+        let projects = projects(&temp);
+        let tool = |name: &str, argument: &str, value: &str, project: Option<&str>| {
+            let mut params = json!({
+                "ref": {"type": "ref/tool", "name": name},
+                "argument": {"name": argument, "value": value}
+            });
+            if let Some(project) = project {
+                params["context"] = json!({"arguments": {"project": project}});
+            }
+            values(complete(&params, &projects).unwrap())
+        };
+
+        assert_eq!(
+            tool("get_project_info", "project", "ap", None),
+            ["api", "apigw"]
+        );
+        assert_eq!(
+            tool("get_project_info", "project", "", None),
+            ["api", "apigw", "web"]
+        );
+        // Typos are corrected when nothing matches as typed.
+        assert_eq!(tool("get_project_info", "project", "aip", None), ["api"]);
+        assert_eq!(
+            tool("get_architecture", "concept", "", Some("api")),
+            ["auth", "billing"]
+        );
+        assert_eq!(
+            tool("get_architecture", "concept", "ILL", Some("api")),
+            ["billing"]
+        );
+        assert_eq!(
+            tool("get_commands", "command_type", "", Some("api")),
+            ["build", "lint", "test"]
+        );
+        assert_eq!(
+            tool("get_commands", "command_type", "", None),
+            ["build", "dev", "lint", "test"]
+        );
+        assert_eq!(
+            tool("get_docs", "topic", "", Some("api")),
+            ["deploy", "setup"]
+        );
+        assert!(tool("get_skill", "topic", "", Some("api")).is_empty());
+        assert!(tool("get_commands", "profile", "", Some("api")).is_empty());
+
+        let params = json!({
+            "ref": {"type": "ref/resource", "uri": resources::DOC_TEMPLATE},
+            "argument": {"name": "topic", "value": "s"},
+            "context": {"arguments": {"project": "api"}}
+        });
+        let result = complete(&params, &projects).unwrap();
+        assert_eq!(result["completion"]["values"], json!(["setup"]));
+        assert_eq!(result["completion"]["total"], 1);
+        assert_eq!(result["completion"]["hasMore"], false);
+
+        let bad = json!({
+            "ref": {"type": "ref/resource", "uri": "jumble://{x}"},
+            "argument": {"name": "topic", "value": ""}
+        });
+        assert_eq!(
+            complete(&bad, &projects).unwrap_err().code,
+            protocol::INVALID_PARAMS
+        );
+        let bad = json!({"ref": {"type": "ref/tool", "name": "get_docs"}});
+        assert!(complete(&bad, &projects).is_err());
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("api", "api"), 0);
+        assert_eq!(edit_distance("aip", "api"), 2);
+        assert_eq!(edit_distance("web", "webs"), 1);
+        assert_eq!(edit_distance("", "api"), 3);
+    }
+}
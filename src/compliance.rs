@@ -0,0 +1,186 @@
+//! License detection for `get_compliance_info`.
+//!
+//! A project's license is read from license files at its root and from the
+//! `license` field of `Cargo.toml`, `package.json` or `pyproject.toml`.
+//! License texts are identified by a few distinctive phrases; anything else
+//! is reported as unrecognized rather than guessed.
+
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Base names (case-insensitive, any extension) of license files.
+const LICENSE_FILE_STEMS: &[&str] = &["license", "licence", "copying", "unlicense"];
+
+/// `(SPDX identifier, phrases that must all appear)`, most specific first.
+const LICENSE_PATTERNS: &[(&str, &[&str])] = &[
+    ("AGPL-3.0", &["gnu affero general public license"]),
+    (
+        "LGPL-3.0",
+        &["gnu lesser general public license", "version 3"],
+    ),
+    (
+        "LGPL-2.1",
+        &["gnu lesser general public license", "version 2.1"],
+    ),
+    ("GPL-3.0", &["gnu general public license", "version 3"]),
+    ("GPL-2.0", &["gnu general public license", "version 2"]),
+    ("MPL-2.0", &["mozilla public license", "2.0"]),
+    ("Apache-2.0", &["apache license", "version 2.0"]),
+    (
+        "BSD-3-Clause",
+        &[
+            "redistribution and use in source and binary forms",
+            "neither the name",
+        ],
+    ),
+    (
+        "BSD-2-Clause",
+        &["redistribution and use in source and binary forms"],
+    ),
+    (
+        "ISC",
+        &["permission to use, copy, modify, and/or distribute"],
+    ),
+    ("MIT", &["permission is hereby granted, free of charge"]),
+    (
+        "Unlicense",
+        &["free and unencumbered software released into the public domain"],
+    ),
+];
+
+/// Where a license was found and what it is.
+#[derive(Debug, PartialEq)]
+pub struct DetectedLicense {
+    /// File name relative to the project root.
+    pub source: String,
+    /// SPDX identifier or expression, if recognized.
+    pub license: Option<String>,
+}
+
+/// Licenses declared by license files and manifests at `root`, in file name
+/// order.
+pub fn detect(root: &Path) -> Vec<DetectedLicense> {
+    let mut names: Vec<String> = fs::read_dir(root)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+                .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+
+    let mut found = Vec::new();
+    for name in names {
+        let path = root.join(&name);
+        if is_license_file(&name) {
+            let license = fs::read_to_string(&path)
+                .ok()
+                .and_then(|text| identify(&text).map(str::to_string));
+            found.push(DetectedLicense {
+                source: name,
+                license,
+            });
+        } else if let Some(license) = manifest_license(&name, &path) {
+            found.push(DetectedLicense {
+                source: name,
+                license: Some(license),
+            });
+        }
+    }
+    found
+}
+
+fn is_license_file(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    let stem = lower.split(['.', '-', '_']).next().unwrap_or(&lower);
+    LICENSE_FILE_STEMS.contains(&stem)
+}
+
+/// SPDX identifier of a license text, if one of the known licenses.
+fn identify(text: &str) -> Option<&'static str> {
+    let text = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    LICENSE_PATTERNS
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|p| text.contains(p)))
+        .map(|(id, _)| *id)
+}
+
+fn manifest_license(name: &str, path: &Path) -> Option<String> {
+    let content = match name {
+        "Cargo.toml" | "package.json" | "pyproject.toml" => fs::read_to_string(path).ok()?,
+        _ => return None,
+    };
+    let license = match name {
+        "Cargo.toml" => toml::from_str::<toml::Value>(&content)
+            .ok()?
+            .get("package")?
+            .get("license")?
+            .as_str()?
+            .to_string(),
+        "package.json" => serde_json::from_str::<Value>(&content)
+            .ok()?
+            .get("license")?
+            .as_str()?
+            .to_string(),
+        _ => {
+            // PEP 621 allows `license = "MIT"` or `license = { text = "MIT" }`.
+            let project = toml::from_str::<toml::Value>(&content).ok()?;
+            let license = project.get("project")?.get("license")?;
+            license
+                .as_str()
+                .or_else(|| license.get("text").and_then(|t| t.as_str()))?
+                .to_string()
+        }
+    };
+    Some(license)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_reads_license_files_and_manifests() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("LICENSE-MIT"),
+            "MIT License\n\nPermission is hereby granted, free of\ncharge, to any person",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("LICENSE-APACHE"),
+            "Apache License\nVersion 2.0, January 2004",
+        )
+        .unwrap();
+        fs::write(temp.path().join("COPYING.txt"), "All rights reserved.").unwrap();
+        fs::write(
+            temp.path().join("Cargo.toml"),
+            "[package]\nname = \"x\"\nlicense = \"MIT OR Apache-2.0\"\n",
+        )
+        .unwrap();
+        fs::write(temp.path().join("README.md"), "MIT").unwrap();
+
+        let found = detect(temp.path());
+        let summary: Vec<(&str, Option<&str>)> = found
+            .iter()
+            .map(|l| (l.source.as_str(), l.license.as_deref()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("COPYING.txt", None),
+                ("Cargo.toml", Some("MIT OR Apache-2.0")),
+                ("LICENSE-APACHE", Some("Apache-2.0")),
+                ("LICENSE-MIT", Some("MIT")),
+            ]
+        );
+    }
+}
@@ -13,6 +13,9 @@ pub struct ProjectConfig {
     pub project: ProjectInfo,
     #[serde(default)]
     pub commands: HashMap<String, String>,
+    /// Alternate command sets, e.g. for CI or containers, by profile name.
+    #[serde(default)]
+    pub profiles: HashMap<String, CommandProfile>,
     #[serde(default)]
     pub entry_points: HashMap<String, String>,
     #[serde(default)]
@@ -23,6 +26,43 @@ pub struct ProjectConfig {
     pub api: Option<ApiInfo>,
     #[serde(default)]
     pub concepts: HashMap<String, Concept>,
+    /// Issue tracker for this project; overrides the workspace's.
+    #[serde(default)]
+    pub tracker: Option<TrackerConfig>,
+    /// Licensing policy for this project; overrides the workspace's.
+    #[serde(default)]
+    pub compliance: Option<ComplianceConfig>,
+    /// How changes are contributed; overrides the workspace's.
+    #[serde(default)]
+    pub workflow: Option<WorkflowConfig>,
+    #[serde(default)]
+    pub data: Option<DataConfig>,
+    #[serde(default)]
+    pub messaging: Option<MessagingConfig>,
+    #[serde(default)]
+    pub review: Option<ReviewConfig>,
+    #[serde(default)]
+    pub test_map: Option<TestMap>,
+    #[serde(default)]
+    pub performance: Option<PerformanceConfig>,
+    /// Flaky or failing tests everyone already knows about, by name.
+    #[serde(default)]
+    pub known_issues: HashMap<String, KnownIssue>,
+    /// Dotted keys set by `.jumble/local.toml`, filled in when loading.
+    #[serde(skip)]
+    pub local_overrides: Vec<String>,
+}
+
+/// Commands that replace `[commands]` entries when a profile is selected.
+///
+/// ```toml
+/// [profiles.docker.commands]
+/// test = "docker compose run --rm app cargo test"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct CommandProfile {
+    #[serde(default)]
+    pub commands: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -35,6 +75,13 @@ pub struct ProjectInfo {
     pub version: Option<String>,
     #[serde(default)]
     pub repository: Option<String>,
+    /// Free-form labels such as `backend`, used to filter the workspace
+    /// overview.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Let `get_code_owners` fall back to `git blame` without CODEOWNERS.
+    #[serde(default)]
+    pub infer_code_owners: bool,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -61,12 +108,316 @@ pub struct ApiInfo {
     pub base_url: Option<String>,
     #[serde(default)]
     pub endpoints: Vec<String>,
+    /// Worked requests by name, for `get_api_examples`.
+    #[serde(default)]
+    pub examples: HashMap<String, ApiExample>,
+}
+
+/// A request showing how to call the API, with its auth headers and payload.
+///
+/// ```toml
+/// [api.examples.create-user]
+/// endpoint = "POST /users"
+/// summary = "Needs a service token"
+/// snippet = """
+/// curl -X POST "$API_URL/users" -H "Authorization: Bearer $TOKEN" -d '{"email": "a@b.c"}'
+/// """
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ApiExample {
+    /// Endpoint it calls, e.g. `POST /users`.
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// The example inline, typically a curl command.
+    #[serde(default)]
+    pub snippet: Option<String>,
+    /// File holding the example, relative to the project.
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+/// Issue tracker behind ticket IDs such as `PAY-123`.
+///
+/// ```toml
+/// [tracker]
+/// type = "jira"
+/// base_url = "https://acme.atlassian.net"
+/// project_key = "PAY"
+/// branch_format = "feature/{ticket}-{description}"
+/// commit_format = "{ticket}: {summary}"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TrackerConfig {
+    #[serde(rename = "type")]
+    pub kind: TrackerKind,
+    /// Site root for Jira and Linear; repository URL for GitHub and GitLab.
+    pub base_url: String,
+    /// Default key for bare numbers, e.g. `PAY` turns `123` into `PAY-123`.
+    #[serde(default)]
+    pub project_key: Option<String>,
+    /// Branch name pattern; `{ticket}` is replaced with the ticket reference.
+    #[serde(default)]
+    pub branch_format: Option<String>,
+    /// Commit subject pattern; `{ticket}` is replaced with the ticket reference.
+    #[serde(default)]
+    pub commit_format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TrackerKind {
+    Jira,
+    Linear,
+    Github,
+    Gitlab,
+}
+
+/// Datasets, notebooks and pipelines of a data project, where the useful
+/// context is not in source modules.
+///
+/// ```toml
+/// [data]
+/// notebooks = ["notebooks"]
+/// pipelines = ["dags"]
+/// schema_registries = ["https://schemas.acme.internal"]
+///
+/// [data.datasets.orders]
+/// location = "s3://acme-lake/orders/"
+/// format = "parquet"
+/// schema = "schemas/orders.avsc"
+/// summary = "One row per order, partitioned by day"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DataConfig {
+    #[serde(default)]
+    pub datasets: HashMap<String, Dataset>,
+    /// Directories holding Jupyter notebooks, relative to the project.
+    #[serde(default)]
+    pub notebooks: Vec<String>,
+    /// Pipeline or DAG definition files and directories.
+    #[serde(default)]
+    pub pipelines: Vec<String>,
+    /// Schema registry URLs or directories.
+    #[serde(default)]
+    pub schema_registries: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Dataset {
+    /// Path, bucket URL or table name.
+    pub location: String,
+    pub summary: String,
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Schema file or registry subject.
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+/// Queues, topics and webhooks a project publishes to or consumes, for
+/// services whose interactions are asynchronous rather than REST calls.
+/// Topics are usually described once, by the project that owns them; other
+/// projects only list them under `publishes` or `subscribes`.
+///
+/// ```toml
+/// [messaging]
+/// broker = "kafka"
+/// publishes = ["orders.created"]
+/// subscribes = ["payments.settled"]
+///
+/// [messaging.topics."orders.created"]
+/// kind = "topic"
+/// summary = "Emitted once an order is committed"
+/// schema = "schemas/order_created.avsc"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct MessagingConfig {
+    /// Broker or transport, e.g. `kafka`, `rabbitmq` or `sqs`.
+    #[serde(default)]
+    pub broker: Option<String>,
+    #[serde(default)]
+    pub publishes: Vec<String>,
+    #[serde(default)]
+    pub subscribes: Vec<String>,
+    #[serde(default)]
+    pub topics: HashMap<String, MessageTopic>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MessageTopic {
+    pub summary: String,
+    /// `topic`, `queue` or `webhook`.
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Event schema file (relative to the project) or registry subject.
+    #[serde(default)]
+    pub schema: Option<String>,
+}
+
+/// The team's contribution process, so agents don't assume GitHub flow.
+///
+/// ```toml
+/// [workflow]
+/// branching = "Trunk-based: short-lived branches off main, merged within a day"
+/// base_branch = "main"
+/// pr_size = "Under 400 changed lines; split refactors from behaviour changes"
+/// required_approvals = 1
+/// merge_strategy = "squash"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct WorkflowConfig {
+    /// Branching model, e.g. trunk-based or git-flow, and how it's used.
+    #[serde(default)]
+    pub branching: Option<String>,
+    /// Branch pull requests target.
+    #[serde(default)]
+    pub base_branch: Option<String>,
+    /// How big a pull request should be.
+    #[serde(default)]
+    pub pr_size: Option<String>,
+    #[serde(default)]
+    pub required_approvals: Option<u32>,
+    /// Who must review what, beyond the approval count.
+    #[serde(default)]
+    pub review: Option<String>,
+    /// `squash`, `rebase` or `merge`, or a description.
+    #[serde(default)]
+    pub merge_strategy: Option<String>,
+    /// Anything else about opening or landing a change.
+    #[serde(default)]
+    pub notes: Vec<String>,
+}
+
+/// What a change must pass before merge, for `get_review_checklist`.
+///
+/// ```toml
+/// [review]
+/// required_checks = ["ci / test", "ci / clippy", "license-scan"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ReviewConfig {
+    /// CI status checks, by the name the forge shows.
+    #[serde(default)]
+    pub required_checks: Vec<String>,
+}
+
+/// Which tests cover which sources, for `get_tests_for`. Sources it doesn't
+/// mention are matched to tests by file name.
+///
+/// ```toml
+/// [test_map]
+/// run = "pytest {files}"
+///
+/// [test_map.paths]
+/// "src/billing/" = ["tests/billing/", "tests/test_invoices.py"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TestMap {
+    /// Command that runs only the given tests; `{files}` is replaced with
+    /// them, shell-quoted and space-separated.
+    #[serde(default)]
+    pub run: Option<String>,
+    /// Source patterns (gitignore syntax) to the tests that cover them.
+    #[serde(default)]
+    pub paths: HashMap<String, Vec<String>>,
+}
+
+/// How to measure a performance-sensitive change and what it must stay
+/// within.
+///
+/// ```toml
+/// [performance.benchmarks]
+/// api = "cargo bench --bench api"
+///
+/// [performance.budgets]
+/// "p99 GET /invoices" = "150 ms"
+///
+/// [performance.profiling]
+/// cpu = "cargo flamegraph --bin server -- --bench"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PerformanceConfig {
+    /// Benchmark commands by name.
+    #[serde(default)]
+    pub benchmarks: HashMap<String, String>,
+    /// Limits by metric, e.g. a latency percentile or bundle size.
+    #[serde(default)]
+    pub budgets: HashMap<String, String>,
+    /// How to profile, by kind (cpu, memory, ...): a command or instructions.
+    #[serde(default)]
+    pub profiling: HashMap<String, String>,
+    /// Where to find baselines or dashboards to compare against.
+    #[serde(default)]
+    pub baseline: Option<String>,
+}
+
+/// A test or behaviour known to fail, so agents don't debug it again.
+///
+/// ```toml
+/// [known_issues.checkout_timeout]
+/// test = "tests/e2e/checkout.spec.ts"
+/// status = "flaky"
+/// summary = "Times out on CI runners under load"
+/// ticket = "PAY-812"
+/// workaround = "Re-run the job; it passes locally"
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct KnownIssue {
+    pub summary: String,
+    /// Test file or test name affected.
+    #[serde(default)]
+    pub test: Option<String>,
+    #[serde(default)]
+    pub status: KnownIssueStatus,
+    /// Tracking ticket ID, linked through `[tracker]`.
+    #[serde(default)]
+    pub ticket: Option<String>,
+    #[serde(default)]
+    pub workaround: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KnownIssueStatus {
+    /// Passes and fails without code changes.
+    #[default]
+    Flaky,
+    /// Fails every time.
+    Failing,
+}
+
+/// Licensing policy agents must respect when adding dependencies.
+///
+/// ```toml
+/// [compliance]
+/// allowed_licenses = ["MIT", "Apache-2.0", "BSD-3-Clause"]
+/// denied_licenses = ["GPL-3.0", "AGPL-3.0"]
+/// export_restrictions = "Contains cryptography; EAR 5D002. No new crypto libraries."
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ComplianceConfig {
+    /// SPDX identifiers dependencies may use.
+    #[serde(default)]
+    pub allowed_licenses: Vec<String>,
+    /// SPDX identifiers dependencies must not use.
+    #[serde(default)]
+    pub denied_licenses: Vec<String>,
+    #[serde(default)]
+    pub export_restrictions: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Concept {
     pub files: Vec<String>,
     pub summary: String,
+    /// Listed before other concepts, and in the workspace overview.
+    #[serde(default)]
+    pub pinned: bool,
+    /// Skill topics for tasks in this area, e.g. `add-endpoint`.
+    #[serde(default)]
+    pub prompts: Vec<String>,
 }
 
 /// Optional YAML frontmatter for a skill file.
@@ -77,6 +428,12 @@ pub struct Concept {
 /// name: explaining-code
 /// description: Explains code with visual diagrams and analogies
 /// tags: [explain, diagram, analogy]
+/// applies_to:
+///   languages: [rust]
+///   files: ["src/**/*.rs"]
+/// roles:
+///   system: "## Context"
+///   user: "## Task"
 /// ---
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct SkillFrontmatter {
@@ -86,6 +443,49 @@ pub struct SkillFrontmatter {
     pub description: Option<String>,
     #[serde(default)]
     pub tags: Vec<String>,
+    /// Maps a message role to the body line that starts it. Used to split the
+    /// skill into MCP prompt messages when `get_skill` is called with
+    /// `format: "messages"`.
+    #[serde(default)]
+    pub roles: HashMap<String, String>,
+    /// When the skill is relevant; `list_skills` hides it otherwise.
+    #[serde(default)]
+    pub applies_to: SkillApplicability,
+}
+
+/// Split a Markdown file into its YAML frontmatter and the body after it.
+/// Frontmatter is recognized only when the first line is `---`, and runs to
+/// the next line that is only `---`; lines may end in `\n` or `\r\n`.
+pub fn split_frontmatter(content: &str) -> (Option<&str>, &str) {
+    let Some(rest) = content
+        .strip_prefix("---\n")
+        .or_else(|| content.strip_prefix("---\r\n"))
+    else {
+        return (None, content);
+    };
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end_matches(['\r', '\n']) == "---" {
+            return (Some(&rest[..offset]), &rest[offset + line.len()..]);
+        }
+        offset += line.len();
+    }
+    (None, content)
+}
+
+/// Conditions under which a skill applies. An empty list doesn't restrict
+/// anything, and a condition is only checked when the caller knows the value.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SkillApplicability {
+    /// Project languages, compared case-insensitively.
+    #[serde(default)]
+    pub languages: Vec<String>,
+    /// Task types, e.g. `bugfix` or `migration`.
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Gitignore-style globs for the file being worked on.
+    #[serde(default)]
+    pub files: Vec<String>,
 }
 
 /// Cached metadata for a single skill file.
@@ -129,6 +529,8 @@ pub struct ProjectDocs {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DocEntry {
     pub path: String,
+    /// Derived from the doc's first heading and paragraph when omitted.
+    #[serde(default)]
     pub summary: String,
 }
 
@@ -138,12 +540,333 @@ pub struct DocEntry {
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct WorkspaceConfig {
+    /// URL of a TOML file with organization-wide conventions to build on;
+    /// see `remote.rs` for what it may contain.
+    #[serde(default)]
+    pub extends_url: Option<String>,
+    /// Hex SHA-256 the file at `extends_url` must have.
+    #[serde(default)]
+    pub extends_sha256: Option<String>,
     #[serde(default)]
     pub workspace: WorkspaceInfo,
     #[serde(default)]
     pub conventions: HashMap<String, String>,
     #[serde(default)]
     pub gotchas: HashMap<String, String>,
+    /// Extra MCP tools declared under `[tools.<name>]`.
+    #[serde(default)]
+    pub tools: HashMap<String, CustomTool>,
+    /// Stdio MCP servers to spawn and proxy, declared under `[servers.<name>]`.
+    #[serde(default)]
+    pub servers: HashMap<String, ProxiedServerConfig>,
+    /// Words treated as interchangeable when searching, declared as
+    /// `auth = ["login", "oauth"]` under `[synonyms]`.
+    #[serde(default)]
+    pub synonyms: HashMap<String, Vec<String>>,
+    /// Issue tracker shared by projects without their own `[tracker]`.
+    #[serde(default)]
+    pub tracker: Option<TrackerConfig>,
+    /// Licensing policy shared by projects without their own `[compliance]`.
+    #[serde(default)]
+    pub compliance: Option<ComplianceConfig>,
+    /// Contribution process shared by projects without their own `[workflow]`.
+    #[serde(default)]
+    pub workflow: Option<WorkflowConfig>,
+    /// Suggested follow-up calls appended to tool results; off when absent.
+    #[serde(default)]
+    pub next_steps: Option<NextStepsConfig>,
+    /// Which external dependencies projects may add.
+    #[serde(default)]
+    pub dependency_policy: DependencyPolicy,
+    /// Local ports and URLs of services, by service name.
+    #[serde(default)]
+    pub dev_ports: HashMap<String, DevEndpoint>,
+    /// Named sets of projects, such as a domain or team's services.
+    #[serde(default)]
+    pub groups: HashMap<String, ProjectGroup>,
+    /// Projects this workspace depends on that aren't checked out locally,
+    /// declared under `[external.<name>]`.
+    #[serde(default)]
+    pub external: HashMap<String, ExternalProject>,
+    /// How tool output is written, for hosts that render Markdown differently.
+    #[serde(default)]
+    pub output: OutputConfig,
+    /// Authoring rules checked by `jumble lint --style`.
+    #[serde(default)]
+    pub lint: LintConfig,
+    /// Usage guidance sent to clients when they connect.
+    #[serde(default)]
+    pub instructions: InstructionsConfig,
+    /// Other workspaces by name, with their root directories (relative to
+    /// this one or absolute). `related_projects` names their projects as
+    /// `workspace:project`.
+    #[serde(default)]
+    pub workspaces: HashMap<String, String>,
+    /// Projects found in `workspaces`, keyed by `workspace:project`, filled
+    /// in when loading.
+    #[serde(skip)]
+    pub linked_projects: HashMap<String, LinkedProject>,
+    /// Dotted keys set by `.jumble/local.toml`, filled in when loading.
+    #[serde(skip)]
+    pub local_overrides: Vec<String>,
+}
+
+/// A project in another workspace registered under `[workspaces]`.
+#[derive(Debug, Clone)]
+pub struct LinkedProject {
+    pub path: PathBuf,
+    pub description: String,
+}
+
+/// A dependency with no local directory, described so agents know what it
+/// is and where to read about it. Shown in `list_projects` and the workspace
+/// overview, and usable in `related_projects`.
+///
+/// ```toml
+/// [external.payments-sdk]
+/// description = "Client for the payments platform team's API"
+/// repository = "https://github.com/acme/payments-sdk"
+/// docs = "https://docs.acme.dev/payments-sdk"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ExternalProject {
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub repository: Option<String>,
+    #[serde(default)]
+    pub docs: Option<String>,
+}
+
+/// Follow-up suggestions after tool results.
+///
+/// ```toml
+/// [next_steps]                 # on, with jumble's default suggestions
+///
+/// [next_steps.tools]
+/// get_project_info = ["get_commands", "get_skill"]
+/// list_projects = []           # no footer for this tool
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct NextStepsConfig {
+    /// Follow-up tools by tool name, replacing the defaults.
+    #[serde(default)]
+    pub tools: HashMap<String, Vec<String>>,
+}
+
+/// Which `jumble lint --style` rules run, and whether they fail the lint.
+/// Every rule has a default level; a rule named in more than one list takes
+/// the last of `allow`, `warn`, `deny`.
+///
+/// ```toml
+/// [lint]
+/// max_description_length = 120
+/// allow = ["command-executables"]   # not checked
+/// deny = ["description-length"]     # reported and fails the lint
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct LintConfig {
+    /// Longest project description, in characters, `description-length` accepts.
+    pub max_description_length: usize,
+    pub allow: Vec<String>,
+    /// Rules reported without failing the lint.
+    pub warn: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self {
+            max_description_length: 200,
+            allow: Vec::new(),
+            warn: Vec::new(),
+            deny: Vec::new(),
+        }
+    }
+}
+
+/// Guidance returned as `instructions` from `initialize`, which hosts
+/// typically add to the agent's system prompt.
+///
+/// ```toml
+/// [instructions]
+/// text = "Payments code is PCI scoped; read get_conventions(\"payments\") first."
+/// include_default = false  # drop the built-in "start with get_workspace_overview" text
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct InstructionsConfig {
+    pub text: Option<String>,
+    pub include_default: bool,
+}
+
+impl Default for InstructionsConfig {
+    fn default() -> Self {
+        Self {
+            text: None,
+            include_default: true,
+        }
+    }
+}
+
+/// How tool results are written.
+///
+/// ```toml
+/// [output]
+/// heading_level = 2     # top-level headings become `##`
+/// bullet = "*"          # "-" (default), "*" or "+"
+/// paths = "relative"    # "absolute" (default), "relative" or "uri"
+/// emoji = false         # drop emoji, including those in .jumble files
+///
+/// [output.clients."Claude Desktop"]
+/// emoji = true          # for the client whose clientInfo.name matches
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct OutputConfig {
+    /// Level of the outermost headings, 1 to 6; deeper ones move with them.
+    pub heading_level: u8,
+    pub bullet: BulletStyle,
+    pub paths: PathStyle,
+    pub emoji: bool,
+    /// Settings for one client, by the name it sends in `clientInfo`
+    /// (case-insensitive), over the ones above.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub clients: HashMap<String, ClientOutputConfig>,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            heading_level: 1,
+            bullet: BulletStyle::Dash,
+            paths: PathStyle::Absolute,
+            emoji: true,
+            clients: HashMap::new(),
+        }
+    }
+}
+
+impl OutputConfig {
+    /// The style for `client`: these settings with its `[output.clients]`
+    /// entry applied.
+    pub fn for_client(&self, client: Option<&str>) -> OutputConfig {
+        let mut style = OutputConfig {
+            clients: HashMap::new(),
+            ..self.clone()
+        };
+        let overrides = client.and_then(|client| {
+            self.clients
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(client))
+                .map(|(_, overrides)| overrides)
+        });
+        if let Some(overrides) = overrides {
+            style.heading_level = overrides.heading_level.unwrap_or(style.heading_level);
+            style.bullet = overrides.bullet.unwrap_or(style.bullet);
+            style.paths = overrides.paths.unwrap_or(style.paths);
+            style.emoji = overrides.emoji.unwrap_or(style.emoji);
+        }
+        style
+    }
+}
+
+/// `[output]` settings for one client; unset ones keep the workspace's.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct ClientOutputConfig {
+    pub heading_level: Option<u8>,
+    pub bullet: Option<BulletStyle>,
+    pub paths: Option<PathStyle>,
+    pub emoji: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum BulletStyle {
+    #[default]
+    #[serde(rename = "-")]
+    Dash,
+    #[serde(rename = "*")]
+    Asterisk,
+    #[serde(rename = "+")]
+    Plus,
+}
+
+impl BulletStyle {
+    pub fn marker(self) -> char {
+        match self {
+            BulletStyle::Dash => '-',
+            BulletStyle::Asterisk => '*',
+            BulletStyle::Plus => '+',
+        }
+    }
+}
+
+/// How file paths in tool output are written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PathStyle {
+    #[default]
+    Absolute,
+    /// Relative to the project a call names, or else the workspace root.
+    Relative,
+    /// `file://` URIs.
+    Uri,
+}
+
+/// Projects that belong together, e.g. a domain owned by one team.
+///
+/// ```toml
+/// [groups.payments]
+/// description = "Everything that moves money"
+/// projects = ["billing", "invoicing", "ledger"]
+///
+/// [groups.payments.conventions]
+/// money = "Amounts are integer minor units"
+///
+/// [groups.payments.docs.pci]
+/// path = "docs/payments/pci.md"
+/// summary = "PCI scope and handling card data"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ProjectGroup {
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Member project names.
+    #[serde(default)]
+    pub projects: Vec<String>,
+    /// Conventions shared by every member, under the member's own.
+    #[serde(default)]
+    pub conventions: HashMap<String, String>,
+    #[serde(default)]
+    pub gotchas: HashMap<String, String>,
+    /// Docs shared by every member, with paths relative to the workspace root.
+    #[serde(default)]
+    pub docs: HashMap<String, DocEntry>,
+}
+
+/// Where a service listens during local development.
+///
+/// ```toml
+/// [dev_ports.api]
+/// port = 8080
+/// project = "billing-api"
+///
+/// [dev_ports.keycloak]
+/// url = "http://localhost:8180/realms/dev"
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DevEndpoint {
+    /// Defaults to the port in `url`.
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Project that runs the service, if it is one of the workspace's.
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -152,6 +875,83 @@ pub struct WorkspaceInfo {
     pub name: Option<String>,
     #[serde(default)]
     pub description: Option<String>,
+    /// Language tag for the headings jumble writes in tool output, e.g. `ja`.
+    #[serde(default)]
+    pub output_language: Option<String>,
+    /// Projects listed first, in this order, wherever projects are listed.
+    #[serde(default)]
+    pub pinned_projects: Vec<String>,
+    /// `infer_code_owners` for every project.
+    #[serde(default)]
+    pub infer_code_owners: bool,
+}
+
+/// Workspace rules for adding external dependencies.
+///
+/// ```toml
+/// [dependency_policy]
+/// allowed = ["serde", "tokio", "reqwest"]
+///
+/// [dependency_policy.denied]
+/// openssl = "Use rustls; openssl breaks the musl build"
+///
+/// [dependency_policy.groups]
+/// http = ["reqwest", "ureq", "hyper"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DependencyPolicy {
+    /// Pre-approved dependencies; when set, anything else needs approval.
+    #[serde(default)]
+    pub allowed: Vec<String>,
+    /// Forbidden dependencies and why.
+    #[serde(default)]
+    pub denied: HashMap<String, String>,
+    /// Interchangeable dependencies, so a proposal can point at the one the
+    /// workspace already uses.
+    #[serde(default)]
+    pub groups: HashMap<String, Vec<String>>,
+}
+
+/// A workspace-defined tool that runs a command with substituted arguments.
+///
+/// ```toml
+/// [tools.deploy_status]
+/// description = "Show deployment status for an environment"
+/// command = "./scripts/status.sh {{env}}"
+/// args = { env = "Environment name (staging, prod)" }
+/// ```
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CustomTool {
+    pub description: String,
+    /// Command template, run from the workspace root without a shell.
+    /// Each `{{name}}` placeholder becomes a required string argument.
+    pub command: String,
+    /// Optional descriptions for the placeholders.
+    #[serde(default)]
+    pub args: HashMap<String, String>,
+    /// Override the default 30 second timeout.
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// A stdio MCP server that jumble spawns and proxies. Its tools are exposed
+/// as `<name>.<tool>`.
+///
+/// ```toml
+/// [servers.gh]
+/// command = "github-mcp-server stdio"
+/// env = { GITHUB_PERSONAL_ACCESS_TOKEN = "${GITHUB_TOKEN}" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProxiedServerConfig {
+    /// Command line, run from the workspace root without a shell.
+    pub command: String,
+    /// Extra environment variables; `${VAR}` expands from jumble's environment.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Per-request timeout (default 30 seconds).
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
 }
 
 // ============================================================================
@@ -160,24 +960,52 @@ pub struct WorkspaceInfo {
 
 /// Global configuration loaded from `~/.jumble/jumble.toml` (or the
 /// platform-specific equivalent of the user's home directory). This file is
-/// created on startup if it does not exist and currently reserves a single
-/// top-level `[jumble]` table for future options.
+/// created on startup if it does not exist and holds a single top-level
+/// `[jumble]` table.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct JumbleConfig {
     #[serde(default)]
     pub jumble: JumbleSection,
 }
 
-/// Placeholder for future Jumble-wide options under the `[jumble]` table.
-/// At the moment this is intentionally empty but ensures we always have a
-/// well-typed location for future configuration.
+/// Jumble-wide options under the `[jumble]` table.
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
-pub struct JumbleSection {}
+pub struct JumbleSection {
+    /// Token for `get_repo_activity` on GitHub; `GITHUB_TOKEN` wins.
+    #[serde(default)]
+    pub github_token: Option<String>,
+    /// Token for `get_repo_activity` on GitLab; `GITLAB_TOKEN` wins.
+    #[serde(default)]
+    pub gitlab_token: Option<String>,
+    /// Self-hosted GitHub Enterprise hosts, besides `github.com`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub github_hosts: Vec<String>,
+    /// Self-hosted GitLab hosts, besides `gitlab.com`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gitlab_hosts: Vec<String>,
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_split_frontmatter_accepts_crlf() {
+        let (yaml, body) = split_frontmatter("---\nname: a\n---\nBody\n");
+        assert_eq!(yaml, Some("name: a\n"));
+        assert_eq!(body, "Body\n");
+
+        let (yaml, body) = split_frontmatter("---\r\nname: a\r\n---\r\nBody\r\n");
+        assert_eq!(yaml, Some("name: a\r\n"));
+        assert_eq!(body, "Body\r\n");
+
+        assert_eq!(
+            split_frontmatter("---\nname: a\n"),
+            (None, "---\nname: a\n")
+        );
+        assert_eq!(split_frontmatter("# Title\n"), (None, "# Title\n"));
+    }
+
     #[test]
     fn test_parse_minimal_project_config() {
         let toml_str = r#"
@@ -267,6 +1095,9 @@ mod tests {
 
             [gotchas]
             async_deadlock = "Avoid holding locks across await points"
+
+            [synonyms]
+            auth = ["login", "oauth", "session"]
         "#;
 
         let config: WorkspaceConfig = toml::from_str(toml_str).unwrap();
@@ -274,6 +1105,40 @@ mod tests {
         assert_eq!(config.conventions.len(), 2);
         assert_eq!(config.gotchas.len(), 1);
         assert!(config.gotchas.contains_key("async_deadlock"));
+        assert_eq!(config.synonyms["auth"], vec!["login", "oauth", "session"]);
+    }
+
+    #[test]
+    fn test_parse_workspace_custom_tools() {
+        let toml_str = r#"
+            [tools.deploy_status]
+            description = "Show deployment status"
+            command = "./scripts/status.sh {{env}}"
+            args = { env = "Environment name" }
+        "#;
+
+        let config: WorkspaceConfig = toml::from_str(toml_str).unwrap();
+        let tool = config.tools.get("deploy_status").unwrap();
+        assert_eq!(tool.command, "./scripts/status.sh {{env}}");
+        assert_eq!(tool.args.get("env"), Some(&"Environment name".to_string()));
+        assert!(tool.timeout_secs.is_none());
+    }
+
+    #[test]
+    fn test_parse_workspace_proxied_servers() {
+        let toml_str = r#"
+            [servers.gh]
+            command = "github-mcp-server stdio"
+            env = { TOKEN = "${GITHUB_TOKEN}" }
+        "#;
+
+        let config: WorkspaceConfig = toml::from_str(toml_str).unwrap();
+        let server = config.servers.get("gh").unwrap();
+        assert_eq!(server.command, "github-mcp-server stdio");
+        assert_eq!(
+            server.env.get("TOKEN"),
+            Some(&"${GITHUB_TOKEN}".to_string())
+        );
     }
 
     #[test]
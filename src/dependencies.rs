@@ -0,0 +1,195 @@
+//! External dependencies of a project, for `check_dependency`.
+//!
+//! A project's dependencies are those declared under `[dependencies]
+//! external` plus the ones listed in its manifests (`Cargo.toml`,
+//! `package.json`, `pyproject.toml`, `requirements.txt`, `go.mod`). Names
+//! compare case-insensitively with `-` and `_` treated alike, as crates.io
+//! and PyPI do.
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use crate::config::{DependencyPolicy, ProjectConfig};
+
+const CARGO_TABLES: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+const NPM_FIELDS: &[&str] = &["dependencies", "devDependencies", "peerDependencies"];
+
+/// Canonical form of a dependency name for comparisons.
+pub fn normalize(name: &str) -> String {
+    name.trim().to_lowercase().replace('_', "-")
+}
+
+/// Declared and manifest dependencies of the project at `root`, normalized.
+pub fn project_dependencies(root: &Path, config: &ProjectConfig) -> BTreeSet<String> {
+    let mut deps: BTreeSet<String> = config
+        .dependencies
+        .external
+        .iter()
+        .map(|d| normalize(d))
+        .collect();
+    deps.extend(manifest_dependencies(root).iter().map(|d| normalize(d)));
+    deps
+}
+
+fn manifest_dependencies(root: &Path) -> Vec<String> {
+    let read = |name: &str| fs::read_to_string(root.join(name)).ok();
+    let mut deps = Vec::new();
+
+    if let Some(manifest) = read("Cargo.toml").and_then(|c| c.parse::<toml::Table>().ok()) {
+        for table in CARGO_TABLES {
+            if let Some(entries) = manifest.get(*table).and_then(|t| t.as_table()) {
+                deps.extend(entries.keys().cloned());
+            }
+        }
+    }
+
+    if let Some(package) = read("package.json").and_then(|c| serde_json::from_str::<Value>(&c).ok())
+    {
+        for field in NPM_FIELDS {
+            if let Some(entries) = package.get(*field).and_then(|f| f.as_object()) {
+                deps.extend(entries.keys().cloned());
+            }
+        }
+    }
+
+    if let Some(pyproject) = read("pyproject.toml").and_then(|c| c.parse::<toml::Table>().ok()) {
+        let requirements = pyproject
+            .get("project")
+            .and_then(|p| p.get("dependencies"))
+            .and_then(|d| d.as_array());
+        for requirement in requirements.into_iter().flatten() {
+            if let Some(name) = requirement.as_str().and_then(requirement_name) {
+                deps.push(name);
+            }
+        }
+    }
+
+    if let Some(requirements) = read("requirements.txt") {
+        deps.extend(requirements.lines().filter_map(requirement_name));
+    }
+
+    if let Some(go_mod) = read("go.mod") {
+        deps.extend(go_requirements(&go_mod));
+    }
+    deps
+}
+
+/// Package name of a PEP 508 requirement such as `httpx[http2]>=0.27`.
+fn requirement_name(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with(['#', '-']) {
+        return None;
+    }
+    let end = line
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(line.len());
+    (end > 0).then(|| line[..end].to_string())
+}
+
+/// Module paths from `require` lines and blocks.
+fn go_requirements(go_mod: &str) -> Vec<String> {
+    let mut modules = Vec::new();
+    let mut in_block = false;
+    for line in go_mod.lines().map(str::trim) {
+        let spec = if in_block {
+            if line.starts_with(')') {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if line.starts_with("require (") {
+            in_block = true;
+            continue;
+        } else if let Some(spec) = line.strip_prefix("require ") {
+            spec
+        } else {
+            continue;
+        };
+        if let Some(module) = spec
+            .split_whitespace()
+            .next()
+            .filter(|m| !m.starts_with("//"))
+        {
+            modules.push(module.to_string());
+        }
+    }
+    modules
+}
+
+/// Other members of every `[dependency_policy.groups]` entry containing
+/// `name`, normalized.
+pub fn alternatives(policy: &DependencyPolicy, name: &str) -> BTreeSet<String> {
+    let name = normalize(name);
+    policy
+        .groups
+        .values()
+        .filter(|members| members.iter().any(|m| normalize(m) == name))
+        .flatten()
+        .map(|m| normalize(m))
+        .filter(|m| *m != name)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_project_dependencies_reads_manifests() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join("Cargo.toml"),
+            "[package]\nname = \"x\"\n\n[dependencies]\nserde_json = \"1\"\n\n[dev-dependencies]\ntempfile = \"3\"\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("package.json"),
+            r#"{"dependencies": {"axios": "^1"}, "devDependencies": {"vitest": "^2"}}"#,
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("requirements.txt"),
+            "# pinned\nhttpx[http2]>=0.27\n-r dev.txt\nRequests==2.32\n",
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("go.mod"),
+            "module x\n\nrequire github.com/spf13/cobra v1.8.0\n\nrequire (\n\tgolang.org/x/sync v0.7.0 // indirect\n)\n",
+        )
+        .unwrap();
+        let config: ProjectConfig = toml::from_str(
+            "[project]\nname = \"x\"\ndescription = \"\"\n\n[dependencies]\nexternal = [\"Tokio\"]\n",
+        )
+        .unwrap();
+
+        let deps: Vec<String> = project_dependencies(temp.path(), &config)
+            .into_iter()
+            .collect();
+        assert_eq!(
+            deps,
+            [
+                "axios",
+                "github.com/spf13/cobra",
+                "golang.org/x/sync",
+                "httpx",
+                "requests",
+                "serde-json",
+                "tempfile",
+                "tokio",
+                "vitest",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_alternatives_come_from_groups() {
+        let policy: DependencyPolicy =
+            toml::from_str("[groups]\nhttp = [\"reqwest\", \"ureq\", \"hyper\"]\n").unwrap();
+        let others: Vec<String> = alternatives(&policy, "UReq").into_iter().collect();
+        assert_eq!(others, ["hyper", "reqwest"]);
+        assert!(alternatives(&policy, "serde").is_empty());
+    }
+}
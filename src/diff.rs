@@ -0,0 +1,366 @@
+//! `jumble diff-context <from> <to>` and the `get_context_diff` tool: what
+//! changed in `.jumble` files between two git refs, for reviewing pull
+//! requests that touch context.
+//!
+//! TOML files are compared section by section (`[commands]`, `[concepts]`,
+//! ...), naming the keys added, removed or changed, so a reviewer sees "removed
+//! command `lint`" instead of a line diff. Other files, such as skills and
+//! docs, are reported as added, removed or modified with line counts.
+//! `local.toml` and `.jumble/cache/` are never committed and are ignored.
+
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::exec;
+use crate::local;
+use crate::paths;
+
+/// Values longer than this are named but not shown.
+const MAX_VALUE_LEN: usize = 60;
+
+/// Most output kept from one git command; a context file larger than this
+/// is reported as an error rather than compared truncated.
+const MAX_GIT_OUTPUT_BYTES: usize = 4 * 1024 * 1024;
+
+/// Section name for top-level keys that aren't tables.
+const TOP_LEVEL: &str = "top level";
+
+#[derive(Debug, Default)]
+struct SectionChange {
+    section: String,
+    added: Vec<String>,
+    removed: Vec<String>,
+    /// Keys with their old and new values, or `None` when too long to show.
+    changed: Vec<(String, Option<(String, String)>)>,
+}
+
+impl SectionChange {
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Run git in `root` through the exec sandbox, returning its output.
+fn git(root: &Path, args: &[&str]) -> Result<String, String> {
+    let argv: Vec<String> = std::iter::once("git")
+        .chain(args.iter().copied())
+        .map(str::to_string)
+        .collect();
+    let policy = exec::ExecPolicy {
+        max_output_bytes: MAX_GIT_OUTPUT_BYTES,
+        ..exec::ExecPolicy::default()
+    };
+    let output = exec::run(&argv, root, &policy)?;
+    if output.timed_out {
+        return Err(format!("git {} timed out", args.join(" ")));
+    }
+    if !output.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            output.stderr.trim()
+        ));
+    }
+    if output.truncated {
+        return Err(format!(
+            "git {} printed more than {} bytes",
+            args.join(" "),
+            MAX_GIT_OUTPUT_BYTES
+        ));
+    }
+    Ok(output.stdout)
+}
+
+/// Whether a path listed by git is committed context.
+fn is_context_file(path: &str) -> bool {
+    let parts: Vec<&str> = path.split('/').collect();
+    let dir: Vec<String> = paths::config_dir()
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    let Some(index) = parts.windows(dir.len()).position(|window| window == dir) else {
+        return false;
+    };
+    let rest = &parts[index + dir.len()..];
+    !rest.is_empty() && rest[0] != "cache" && rest != [local::LOCAL_FILE]
+}
+
+/// Context files at `rev`, relative to `root`.
+fn context_files(root: &Path, rev: &str) -> Result<BTreeSet<String>, String> {
+    Ok(
+        git(root, &["ls-tree", "-r", "--name-only", rev, "--", "."])?
+            .lines()
+            .filter(|path| is_context_file(path))
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+fn show(root: &Path, rev: &str, path: &str) -> Result<String, String> {
+    git(root, &["show", &format!("{}:./{}", rev, path)])
+}
+
+/// `value` for display, or `None` when it is too long.
+fn display(value: &toml::Value) -> Option<String> {
+    let text = match value {
+        toml::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    (text.len() <= MAX_VALUE_LEN && !text.contains('\n')).then_some(text)
+}
+
+/// Compare two parsed TOML files section by section.
+fn compare_tables(old: &toml::Table, new: &toml::Table) -> Vec<SectionChange> {
+    let mut sections = Vec::new();
+    let mut top = SectionChange {
+        section: TOP_LEVEL.to_string(),
+        ..SectionChange::default()
+    };
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for key in keys {
+        match (old.get(key), new.get(key)) {
+            (Some(toml::Value::Table(old)), Some(toml::Value::Table(new))) => {
+                sections.push(compare_section(key, old, new));
+            }
+            (Some(toml::Value::Table(old)), None) => {
+                sections.push(compare_section(key, old, &toml::Table::new()));
+            }
+            (None, Some(toml::Value::Table(new))) => {
+                sections.push(compare_section(key, &toml::Table::new(), new));
+            }
+            (None, Some(_)) => top.added.push(key.clone()),
+            (Some(_), None) => top.removed.push(key.clone()),
+            (Some(old), Some(new)) if old != new => top
+                .changed
+                .push((key.clone(), display(old).zip(display(new)))),
+            _ => {}
+        }
+    }
+    sections.retain(|section| !section.is_empty());
+    if !top.is_empty() {
+        sections.insert(0, top);
+    }
+    sections
+}
+
+fn compare_section(section: &str, old: &toml::Table, new: &toml::Table) -> SectionChange {
+    let mut change = SectionChange {
+        section: section.to_string(),
+        ..SectionChange::default()
+    };
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    for key in keys {
+        match (old.get(key), new.get(key)) {
+            (None, Some(_)) => change.added.push(key.clone()),
+            (Some(_), None) => change.removed.push(key.clone()),
+            (Some(old), Some(new)) if old != new => change
+                .changed
+                .push((key.clone(), display(old).zip(display(new)))),
+            _ => {}
+        }
+    }
+    change
+}
+
+fn render_section(change: &SectionChange) -> String {
+    let names = |keys: &[String]| {
+        keys.iter()
+            .map(|k| format!("`{}`", k))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let mut parts = Vec::new();
+    if !change.added.is_empty() {
+        parts.push(format!("added {}", names(&change.added)));
+    }
+    if !change.removed.is_empty() {
+        parts.push(format!("removed {}", names(&change.removed)));
+    }
+    if !change.changed.is_empty() {
+        let changed: Vec<String> = change
+            .changed
+            .iter()
+            .map(|(key, values)| match values {
+                Some((old, new)) => format!("`{}` (`{}` → `{}`)", key, old, new),
+                None => format!("`{}`", key),
+            })
+            .collect();
+        parts.push(format!("changed {}", changed.join(", ")));
+    }
+    format!("- **{}**: {}\n", change.section, parts.join("; "))
+}
+
+/// Markdown for one file present at either ref.
+fn render_file(path: &str, old: Option<&str>, new: Option<&str>) -> Option<String> {
+    let status = match (old, new) {
+        (None, Some(_)) => " (added)",
+        (Some(_), None) => " (removed)",
+        (Some(old), Some(new)) if old == new => return None,
+        _ => "",
+    };
+    let mut out = format!("## `{}`{}\n\n", path, status);
+
+    if path.ends_with(".toml") {
+        let parse = |content: Option<&str>| -> Result<toml::Table, String> {
+            content
+                .unwrap_or_default()
+                .parse()
+                .map_err(|e: toml::de::Error| e.message().to_string())
+        };
+        match (parse(old), parse(new)) {
+            (Ok(old), Ok(new)) => {
+                let sections = compare_tables(&old, &new);
+                if sections.is_empty() {
+                    out.push_str("Formatting or comments only.\n");
+                }
+                for section in &sections {
+                    out.push_str(&render_section(section));
+                }
+            }
+            (_, Err(e)) | (Err(e), _) => {
+                out.push_str(&format!("Can't be parsed as TOML: {}\n", e));
+            }
+        }
+    } else {
+        let diff = similar::TextDiff::from_lines(old.unwrap_or_default(), new.unwrap_or_default());
+        let (mut added, mut removed) = (0, 0);
+        for change in diff.iter_all_changes() {
+            match change.tag() {
+                similar::ChangeTag::Insert => added += 1,
+                similar::ChangeTag::Delete => removed += 1,
+                similar::ChangeTag::Equal => {}
+            }
+        }
+        out.push_str(&format!("{} line(s) added, {} removed.\n", added, removed));
+    }
+    Some(out)
+}
+
+/// Summarize how `.jumble` files under `root` changed from `from` to `to`.
+pub fn context_diff(root: &Path, from: &str, to: &str) -> Result<String, String> {
+    if !cfg!(feature = "exec") {
+        return Err(exec::UNAVAILABLE.to_string());
+    }
+    // Refs are passed to git as arguments, so one starting with `-` would be
+    // read as an option.
+    if let Some(rev) = [from, to].into_iter().find(|rev| rev.starts_with('-')) {
+        return Err(format!("Unknown git ref '{}'", rev));
+    }
+    git(root, &["rev-parse", "--git-dir"])
+        .map_err(|_| format!("{} is not in a git repository", root.display()))?;
+    for rev in [from, to] {
+        git(
+            root,
+            &[
+                "rev-parse",
+                "--verify",
+                "--quiet",
+                &format!("{}^{{commit}}", rev),
+            ],
+        )
+        .map_err(|_| format!("Unknown git ref '{}'", rev))?;
+    }
+    let old_files = context_files(root, from)?;
+    let new_files = context_files(root, to)?;
+
+    let mut files = Vec::new();
+    for path in old_files.union(&new_files) {
+        let old = match old_files.contains(path) {
+            true => Some(show(root, from, path)?),
+            false => None,
+        };
+        let new = match new_files.contains(path) {
+            true => Some(show(root, to, path)?),
+            false => None,
+        };
+        files.extend(render_file(path, old.as_deref(), new.as_deref()));
+    }
+
+    let mut output = format!("# Context Changes: `{}` → `{}`\n\n", from, to);
+    if files.is_empty() {
+        output.push_str("No `.jumble` files changed.\n");
+        return Ok(output);
+    }
+    output.push_str(&format!("{} file(s) changed.\n", files.len()));
+    for file in files {
+        output.push('\n');
+        output.push_str(&file);
+    }
+    Ok(output)
+}
+
+/// `jumble diff-context <from> <to>`.
+pub fn run_diff_context(root: &Path, from: &str, to: &str) -> Result<()> {
+    let output = context_diff(root, from, to).map_err(anyhow::Error::msg)?;
+    print!("{}", output);
+    Ok(())
+}
+
+#[cfg(all(test, feature = "exec"))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_context_diff_between_commits() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let run = |args: &[&str]| {
+            git(
+                root,
+                &[
+                    &["-c", "user.name=t", "-c", "user.email=t@example.com"],
+                    args,
+                ]
+                .concat(),
+            )
+            .unwrap()
+        };
+        let jumble = root.join("api/.jumble");
+        fs::create_dir_all(jumble.join("skills")).unwrap();
+        fs::write(
+            jumble.join("project.toml"),
+            "[project]\nname = \"api\"\ndescription = \"API\"\n\n[commands]\nfmt = \"cargo fmt\"\ntest = \"cargo test\"\n",
+        )
+        .unwrap();
+        fs::write(jumble.join("skills/deploy.md"), "Deploy\n").unwrap();
+        fs::write(root.join("README.md"), "Readme\n").unwrap();
+        run(&["init", "--quiet"]);
+        run(&["add", "."]);
+        run(&["commit", "--quiet", "-m", "one"]);
+
+        fs::write(
+            jumble.join("project.toml"),
+            "[project]\nname = \"api\"\ndescription = \"API\"\n\n[commands]\nlint = \"cargo clippy\"\ntest = \"cargo nextest run\"\n\n[concepts.billing]\nfiles = [\"src/billing.rs\"]\nsummary = \"Invoices\"\n",
+        )
+        .unwrap();
+        fs::write(jumble.join("skills/deploy.md"), "Deploy\nThen verify\n").unwrap();
+        fs::write(root.join("README.md"), "Changed\n").unwrap();
+        fs::write(jumble.join("local.toml"), "[commands]\ntest = \"x\"\n").unwrap();
+        run(&["add", "."]);
+        run(&["commit", "--quiet", "-m", "two"]);
+
+        let output = context_diff(root, "HEAD~1", "HEAD").unwrap();
+        assert_eq!(
+            output,
+            "# Context Changes: `HEAD~1` → `HEAD`\n\n2 file(s) changed.\n\n\
+             ## `api/.jumble/project.toml`\n\n\
+             - **commands**: added `lint`; removed `fmt`; changed `test` (`cargo test` → `cargo nextest run`)\n\
+             - **concepts**: added `billing`\n\n\
+             ## `api/.jumble/skills/deploy.md`\n\n1 line(s) added, 0 removed.\n"
+        );
+
+        let output = context_diff(root, "HEAD", "HEAD").unwrap();
+        assert!(output.ends_with("No `.jumble` files changed.\n"));
+        assert_eq!(
+            context_diff(root, "HEAD", "nope").unwrap_err(),
+            "Unknown git ref 'nope'"
+        );
+        assert_eq!(
+            context_diff(root, "--output=/tmp/x", "HEAD").unwrap_err(),
+            "Unknown git ref '--output=/tmp/x'"
+        );
+    }
+}
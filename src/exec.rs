@@ -0,0 +1,425 @@
+//! Running external commands on behalf of MCP clients.
+//!
+//! Everything jumble executes goes through [`run`], which applies the same
+//! sandbox rules regardless of where the command came from:
+//!
+//! - no shell: the command template is split into argv before arguments are
+//!   substituted, so argument values can never inject extra commands;
+//! - the working directory is the workspace root for custom tools and the
+//!   project directory for skill scripts, and relative program paths are
+//!   resolved against it;
+//! - the environment is cleared except for a small allow-list;
+//! - stdin is closed, output is capped, and the process is killed on timeout.
+//!
+//! Builds without the `exec` feature refuse to run anything.
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Environment variables passed through to child processes.
+const ENV_ALLOW_LIST: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USER",
+    "LANG",
+    "LC_ALL",
+    "TERM",
+    "TMPDIR",
+    "SYSTEMROOT",
+    "USERPROFILE",
+];
+
+/// Why builds without the `exec` feature run nothing.
+pub const UNAVAILABLE: &str = "this build has no `exec` feature, so it can't run commands";
+
+/// Limits applied to a single command.
+#[derive(Debug, Clone)]
+pub struct ExecPolicy {
+    pub timeout: Duration,
+    /// Bytes kept from each of stdout and stderr.
+    pub max_output_bytes: usize,
+    /// Kills the command once set, e.g. when the client cancels the request.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl Default for ExecPolicy {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            max_output_bytes: 64 * 1024,
+            cancel: None,
+        }
+    }
+}
+
+/// What a finished (or killed) command produced.
+#[derive(Debug)]
+pub struct ExecOutput {
+    pub argv: Vec<String>,
+    /// `None` when the process was killed or terminated by a signal.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub timed_out: bool,
+    pub truncated: bool,
+}
+
+impl ExecOutput {
+    pub fn success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Split a command template into words, honouring single and double quotes.
+pub fn split_command(command: &str) -> Result<Vec<String>, String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+
+    for c in command.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if quote.is_some() {
+        return Err(format!("Unterminated quote in command: {}", command));
+    }
+    if in_word {
+        words.push(current);
+    }
+    if words.is_empty() {
+        return Err("Command is empty".to_string());
+    }
+    Ok(words)
+}
+
+/// Names of the `{{placeholder}}`s in a template, in order of first use.
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim().to_string();
+        if !name.is_empty() && !names.contains(&name) {
+            names.push(name);
+        }
+        rest = &after[end + 2..];
+    }
+    names
+}
+
+/// Split `template` into argv and substitute `{{name}}` in each word.
+pub fn render_argv(template: &str, args: &HashMap<String, String>) -> Result<Vec<String>, String> {
+    let mut argv = split_command(template)?;
+    for word in &mut argv {
+        for name in placeholders(word) {
+            let value = args
+                .get(&name)
+                .ok_or_else(|| format!("Missing '{}' argument", name))?;
+            if value.chars().any(|c| c.is_control()) {
+                return Err(format!("Argument '{}' contains control characters", name));
+            }
+            *word = replace_placeholder(word, &name, value);
+        }
+    }
+    Ok(argv)
+}
+
+fn replace_placeholder(word: &str, name: &str, value: &str) -> String {
+    let mut output = String::new();
+    let mut rest = word;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) if after[..end].trim() == name => {
+                output.push_str(&rest[..start]);
+                output.push_str(value);
+                rest = &after[end + 2..];
+            }
+            _ => {
+                output.push_str(&rest[..start + 2]);
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Build a `Command` for `argv` with the sandbox's working directory and
+/// environment rules applied. `extra_env` is added on top of the allow-list.
+pub fn sandboxed_command(
+    argv: &[String],
+    cwd: &Path,
+    extra_env: &HashMap<String, String>,
+) -> Result<Command, String> {
+    if !cfg!(feature = "exec") {
+        return Err(UNAVAILABLE.to_string());
+    }
+    let (program, args) = argv.split_first().ok_or("Command is empty")?;
+
+    let program_path = PathBuf::from(program);
+    let program_path = if program_path.is_relative() && program_path.components().count() > 1 {
+        cwd.join(program_path)
+    } else {
+        program_path
+    };
+
+    let mut command = Command::new(&program_path);
+    command.args(args).current_dir(cwd).env_clear();
+    for key in ENV_ALLOW_LIST {
+        if let Some(value) = std::env::var_os(key) {
+            command.env(key, value);
+        }
+    }
+    command.envs(extra_env);
+    Ok(command)
+}
+
+/// Replace `${NAME}` with the value of jumble's own environment variable
+/// `NAME` (empty when unset), so config files can reference secrets without
+/// containing them.
+pub fn expand_env(value: &str) -> String {
+    expand_env_with(value, |name| std::env::var(name).ok())
+}
+
+/// [`expand_env`], reading variables through `lookup`.
+fn expand_env_with(value: &str, lookup: impl Fn(&str) -> Option<String>) -> String {
+    let mut output = String::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else { break };
+        output.push_str(&rest[..start]);
+        output.push_str(&lookup(&after[..end]).unwrap_or_default());
+        rest = &after[end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Run `argv` in `cwd` under `policy`. A cancelled command is killed and
+/// reported as an error.
+pub fn run(argv: &[String], cwd: &Path, policy: &ExecPolicy) -> Result<ExecOutput, String> {
+    run_with_env(argv, cwd, &HashMap::new(), policy)
+}
+
+/// [`run`], with `extra_env` added on top of the allow-list.
+pub fn run_with_env(
+    argv: &[String],
+    cwd: &Path,
+    extra_env: &HashMap<String, String>,
+    policy: &ExecPolicy,
+) -> Result<ExecOutput, String> {
+    let program = argv.first().ok_or("Command is empty")?;
+    let mut command = sandboxed_command(argv, cwd, extra_env)?;
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to run '{}': {}", program, e))?;
+
+    let limit = policy.max_output_bytes;
+    let stdout = child.stdout.take().map(|pipe| capture(pipe, limit));
+    let stderr = child.stderr.take().map(|pipe| capture(pipe, limit));
+
+    let started = Instant::now();
+    let mut timed_out = false;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Some(status),
+            Ok(None)
+                if policy
+                    .cancel
+                    .as_ref()
+                    .is_some_and(|c| c.load(Ordering::Relaxed)) =>
+            {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("'{}' was cancelled", program));
+            }
+            Ok(None) if started.elapsed() >= policy.timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                timed_out = true;
+                break None;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(10)),
+            Err(e) => return Err(format!("Failed to wait for '{}': {}", program, e)),
+        }
+    };
+
+    let (stdout, stdout_truncated) = stdout.map(join_capture).unwrap_or_default();
+    let (stderr, stderr_truncated) = stderr.map(join_capture).unwrap_or_default();
+
+    Ok(ExecOutput {
+        argv: argv.to_vec(),
+        exit_code: status.and_then(|s| s.code()),
+        stdout,
+        stderr,
+        timed_out,
+        truncated: stdout_truncated || stderr_truncated,
+    })
+}
+
+type Capture = std::thread::JoinHandle<(Vec<u8>, bool)>;
+
+/// Drain a pipe on a thread, keeping at most `limit` bytes. The pipe is read
+/// to the end so the child never blocks on a full buffer.
+fn capture<R: Read + Send + 'static>(mut pipe: R, limit: usize) -> Capture {
+    std::thread::spawn(move || {
+        let mut kept = Vec::new();
+        let mut truncated = false;
+        let mut buf = [0u8; 8192];
+        loop {
+            match pipe.read(&mut buf) {
+                Ok(0) | Err(_) => break,
+                Ok(n) => {
+                    let room = limit.saturating_sub(kept.len());
+                    kept.extend_from_slice(&buf[..n.min(room)]);
+                    truncated |= n > room;
+                }
+            }
+        }
+        (kept, truncated)
+    })
+}
+
+fn join_capture(handle: Capture) -> (String, bool) {
+    let (bytes, truncated) = handle.join().unwrap_or_default();
+    (String::from_utf8_lossy(&bytes).into_owned(), truncated)
+}
+
+/// Render command output as markdown for a tool result.
+pub fn format_output(title: &str, output: &ExecOutput) -> String {
+    let mut text = format!("# {}\n\n`{}`\n\n", title, output.argv.join(" "));
+    match (output.timed_out, output.exit_code) {
+        (true, _) => text.push_str("**Timed out** (process killed)\n"),
+        (false, Some(code)) => text.push_str(&format!("**Exit code:** {}\n", code)),
+        (false, None) => text.push_str("**Terminated by signal**\n"),
+    }
+    if !output.stdout.trim().is_empty() {
+        text.push_str(&format!(
+            "\n## stdout\n\n```\n{}\n```\n",
+            output.stdout.trim_end()
+        ));
+    }
+    if !output.stderr.trim().is_empty() {
+        text.push_str(&format!(
+            "\n## stderr\n\n```\n{}\n```\n",
+            output.stderr.trim_end()
+        ));
+    }
+    if output.truncated {
+        text.push_str("\n*Output truncated.*\n");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command_honours_quotes() {
+        assert_eq!(
+            split_command(r#"./status.sh "two words" 'x y' plain"#).unwrap(),
+            vec!["./status.sh", "two words", "x y", "plain"]
+        );
+        assert!(split_command("echo \"open").is_err());
+        assert!(split_command("   ").is_err());
+    }
+
+    #[test]
+    fn test_placeholders_in_order() {
+        assert_eq!(
+            placeholders("deploy {{env}} --tag={{ tag }} {{env}}"),
+            vec!["env", "tag"]
+        );
+    }
+
+    #[test]
+    fn test_render_argv_cannot_inject_words() {
+        let mut args = HashMap::new();
+        args.insert("env".to_string(), "prod; rm -rf /".to_string());
+        let argv = render_argv("./status.sh --env={{env}}", &args).unwrap();
+        assert_eq!(argv, vec!["./status.sh", "--env=prod; rm -rf /"]);
+
+        assert!(render_argv("./status.sh {{missing}}", &args).is_err());
+    }
+
+    #[test]
+    fn test_expand_env() {
+        let env = HashMap::from([("TOKEN".to_string(), "secret".to_string())]);
+        let expand = |value| expand_env_with(value, |name| env.get(name).cloned());
+        assert_eq!(expand("Bearer ${TOKEN}"), "Bearer secret");
+        assert_eq!(expand("${UNSET}"), "");
+        assert_eq!(expand("${TOKEN}:${TOKEN}"), "secret:secret");
+        assert_eq!(expand("${unterminated"), "${unterminated");
+        assert_eq!(expand("plain"), "plain");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[cfg(feature = "exec")]
+    fn test_run_captures_output_and_exit_code() {
+        let cwd = std::env::temp_dir();
+        let argv = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "echo hi; exit 3".to_string(),
+        ];
+        let output = run(&argv, &cwd, &ExecPolicy::default()).unwrap();
+        assert_eq!(output.exit_code, Some(3));
+        assert_eq!(output.stdout.trim(), "hi");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[cfg(feature = "exec")]
+    fn test_run_kills_on_timeout() {
+        let cwd = std::env::temp_dir();
+        let policy = ExecPolicy {
+            timeout: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let output = run(&["sleep".to_string(), "5".to_string()], &cwd, &policy).unwrap();
+        assert!(output.timed_out);
+        assert!(!output.success());
+
+        let policy = ExecPolicy {
+            cancel: Some(Arc::new(AtomicBool::new(true))),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        let err = run(&["sleep".to_string(), "5".to_string()], &cwd, &policy).unwrap_err();
+        assert_eq!(err, "'sleep' was cancelled");
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,134 @@
+//! Cached listings of project files, used when curated concepts don't cover
+//! a query.
+
+use ignore::WalkBuilder;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// Files listed per project, at most. Keeps huge monorepos from stalling a
+/// tool call.
+const MAX_FILES: usize = 50_000;
+
+/// Per-project file listings, built on first use. Lives in the server's
+/// discovery state, so a reload starts with an empty cache.
+#[derive(Default)]
+pub struct FileIndex {
+    listings: Mutex<HashMap<PathBuf, Arc<Vec<String>>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl FileIndex {
+    /// Files under `root` as `/`-separated relative paths.
+    pub fn files(&self, root: &Path) -> Arc<Vec<String>> {
+        if let Some(files) = self.lock().get(root) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Arc::clone(files);
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        // Walk without the lock; two concurrent misses just list twice.
+        let files = Arc::new(list_files(root));
+        self.lock()
+            .entry(root.to_path_buf())
+            .or_insert(files)
+            .clone()
+    }
+
+    /// Lookups answered from the cache and lookups that listed files.
+    pub fn stats(&self) -> (u64, u64) {
+        (
+            self.hits.load(Ordering::Relaxed),
+            self.misses.load(Ordering::Relaxed),
+        )
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<PathBuf, Arc<Vec<String>>>> {
+        self.listings.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// List files under `root`, honouring `.gitignore` and skipping hidden
+/// entries such as `.git` and `.jumble`.
+pub fn list_files(root: &Path) -> Vec<String> {
+    let mut files: Vec<String> = WalkBuilder::new(root)
+        .build()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|e| {
+            let relative = e.path().strip_prefix(root).ok()?;
+            let parts: Vec<&str> = relative
+                .components()
+                .map(|c| c.as_os_str().to_str())
+                .collect::<Option<_>>()?;
+            Some(parts.join("/"))
+        })
+        .take(MAX_FILES)
+        .collect();
+    files.sort();
+    files
+}
+
+/// Files whose base name contains `query` (case-insensitive).
+pub fn match_basenames<'a>(files: &'a [String], query: &str) -> Vec<&'a str> {
+    let query = query.to_lowercase();
+    files
+        .iter()
+        .map(String::as_str)
+        .filter(|file| {
+            let name = file.rsplit('/').next().unwrap_or(file);
+            name.to_lowercase().contains(&query)
+        })
+        .collect()
+}
+
+/// Whether a relative `file` matches any of `patterns`, read as gitignore
+/// lines, or lies under a directory one of them matches.
+pub fn matches_globs(patterns: &[String], file: &str) -> bool {
+    let mut builder = ignore::gitignore::GitignoreBuilder::new("");
+    for pattern in patterns {
+        let _ = builder.add_line(None, pattern);
+    }
+    builder.build().is_ok_and(|matcher| {
+        matcher
+            .matched_path_or_any_parents(file.trim_start_matches("./"), false)
+            .is_ignore()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_listing_honours_gitignore_and_matches_basenames() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        // The ignore crate only applies .gitignore inside a git repository.
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::create_dir_all(root.join("src/billing")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("src/billing/invoice_service.rs"), "").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("target/invoice_service.o"), "").unwrap();
+
+        let index = FileIndex::default();
+        let files = index.files(root);
+        assert_eq!(
+            *files,
+            vec!["src/billing/invoice_service.rs", "src/main.rs"]
+        );
+        assert!(Arc::ptr_eq(&files, &index.files(root)));
+        assert_eq!(index.stats(), (1, 1));
+
+        assert_eq!(
+            match_basenames(&files, "Invoice"),
+            vec!["src/billing/invoice_service.rs"]
+        );
+        assert!(match_basenames(&files, "billing").is_empty());
+    }
+}
@@ -0,0 +1,462 @@
+//! Live repository metadata from GitHub or GitLab for projects with a
+//! `repository` URL.
+//!
+//! Responses are cached per project in `.jumble/cache/repo_activity.json` for
+//! [`CACHE_TTL_MINUTES`], so agents calling the tool every session don't hit
+//! the forge's rate limits. A stale cache is still served when the forge
+//! can't be reached.
+//!
+//! The three GET requests per forge go through the blocking `ureq` client the
+//! `network` feature already uses for `extends_url`, rather than a forge SDK
+//! such as octocrab: that would pull in an async runtime for a GitHub-only
+//! client, and GitLab would still need requests of its own.
+//!
+//! Tokens are only sent to `github.com`, `gitlab.com` and the self-hosted
+//! hosts listed as `github_hosts`/`gitlab_hosts` under `[jumble]`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+#[cfg(any(feature = "network", test))]
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::JumbleConfig;
+use crate::paths;
+use crate::remote;
+
+/// How long a cached response is served without asking the forge again.
+pub const CACHE_TTL_MINUTES: i64 = 15;
+
+/// Releases listed, newest first.
+#[cfg(any(feature = "network", test))]
+const MAX_RELEASES: usize = 5;
+
+/// Seconds before a forge request is abandoned.
+#[cfg(feature = "network")]
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// A repository on a supported forge, parsed from `project.repository`.
+#[derive(Debug, PartialEq)]
+pub enum Forge {
+    GitHub {
+        api: String,
+        owner: String,
+        repo: String,
+    },
+    GitLab {
+        api: String,
+        /// `group/subgroup/project`.
+        path: String,
+    },
+}
+
+impl Forge {
+    /// Parse `https://host/owner/repo(.git)`, `git@host:owner/repo.git` and
+    /// similar URLs on `github.com`, `gitlab.com` or a host `config` lists.
+    pub fn parse(repository: &str, config: Option<&JumbleConfig>) -> Option<Self> {
+        let url = repository.trim().trim_end_matches('/');
+        let rest = match url.split_once("://") {
+            Some((_, rest)) => rest,
+            None => url.strip_prefix("git@")?,
+        };
+        let (host, path) = rest.split_once(['/', ':'])?;
+        let host = host.rsplit('@').next().unwrap_or(host);
+        let path = path.trim_end_matches(".git").trim_matches('/');
+
+        let listed = |hosts: fn(&JumbleConfig) -> &Vec<String>| {
+            config.is_some_and(|c| hosts(c).iter().any(|h| h.eq_ignore_ascii_case(host)))
+        };
+        if host == "github.com" || listed(|c| &c.jumble.github_hosts) {
+            let (owner, repo) = path.split_once('/')?;
+            if repo.is_empty() || repo.contains('/') {
+                return None;
+            }
+            let api = if host == "github.com" {
+                "https://api.github.com".to_string()
+            } else {
+                format!("https://{}/api/v3", host)
+            };
+            Some(Self::GitHub {
+                api,
+                owner: owner.to_string(),
+                repo: repo.to_string(),
+            })
+        } else if (host == "gitlab.com" || listed(|c| &c.jumble.gitlab_hosts)) && path.contains('/')
+        {
+            Some(Self::GitLab {
+                api: format!("https://{}/api/v4", host),
+                path: path.to_string(),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::GitHub { .. } => "GitHub",
+            Self::GitLab { .. } => "GitLab",
+        }
+    }
+
+    /// Token from `GITHUB_TOKEN`/`GH_TOKEN` or `GITLAB_TOKEN`, then from
+    /// `github_token`/`gitlab_token` under `[jumble]` in the global config.
+    pub fn token(&self, config: Option<&JumbleConfig>) -> Option<String> {
+        let (vars, configured): (&[&str], _) = match self {
+            Self::GitHub { .. } => (
+                &["GITHUB_TOKEN", "GH_TOKEN"],
+                config.and_then(|c| c.jumble.github_token.clone()),
+            ),
+            Self::GitLab { .. } => (
+                &["GITLAB_TOKEN"],
+                config.and_then(|c| c.jumble.gitlab_token.clone()),
+            ),
+        };
+        vars.iter()
+            .find_map(|var| std::env::var(var).ok())
+            .or(configured)
+            .filter(|token| !token.trim().is_empty())
+    }
+}
+
+/// Current state of a repository.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepoActivity {
+    pub default_branch: String,
+    /// Open pull requests (GitHub) or merge requests (GitLab).
+    pub open_pull_requests: u64,
+    pub releases: Vec<Release>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Release {
+    pub tag: String,
+    pub name: Option<String>,
+    pub published_at: Option<String>,
+    pub url: Option<String>,
+}
+
+/// What is stored in the cache file.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedActivity {
+    repository: String,
+    /// RFC 3339 time of the request.
+    fetched_at: String,
+    activity: RepoActivity,
+}
+
+/// Activity and when it was fetched, plus a note when it came from cache.
+#[derive(Debug)]
+pub struct Fetched {
+    pub activity: RepoActivity,
+    pub fetched_at: String,
+    pub note: Option<String>,
+}
+
+fn cache_path(project_root: &Path) -> PathBuf {
+    project_root
+        .join(paths::config_dir())
+        .join("cache")
+        .join("repo_activity.json")
+}
+
+fn read_cache(project_root: &Path, repository: &str) -> Option<CachedActivity> {
+    let content = fs::read_to_string(cache_path(project_root)).ok()?;
+    let cached: CachedActivity = serde_json::from_str(&content).ok()?;
+    (cached.repository == repository).then_some(cached)
+}
+
+fn write_cache(project_root: &Path, cached: &CachedActivity) -> Result<(), String> {
+    let path = cache_path(project_root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(cached).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Activity for `repository`, from a fresh cache entry unless `refresh` is
+/// set, otherwise from the forge.
+pub fn activity(
+    project_root: &Path,
+    repository: &str,
+    config: Option<&JumbleConfig>,
+    refresh: bool,
+) -> Result<Fetched, String> {
+    let forge = Forge::parse(repository, config).ok_or_else(|| {
+        format!(
+            "'{}' is not a GitHub or GitLab repository URL. List self-hosted forges as github_hosts or gitlab_hosts under [jumble] in ~/.jumble/jumble.toml.",
+            repository
+        )
+    })?;
+    let cached = read_cache(project_root, repository);
+    if let Some(cached) = &cached {
+        let age = DateTime::parse_from_rfc3339(&cached.fetched_at)
+            .map(|fetched_at| Utc::now() - fetched_at.with_timezone(&Utc));
+        if !refresh && age.is_ok_and(|age| age < Duration::minutes(CACHE_TTL_MINUTES)) {
+            return Ok(Fetched {
+                activity: cached.activity.clone(),
+                fetched_at: cached.fetched_at.clone(),
+                note: Some("cached; pass refresh: true to update".to_string()),
+            });
+        }
+    }
+
+    let token = forge.token(config).ok_or_else(|| {
+        let (var, key) = match forge {
+            Forge::GitHub { .. } => ("GITHUB_TOKEN", "github_token"),
+            Forge::GitLab { .. } => ("GITLAB_TOKEN", "gitlab_token"),
+        };
+        format!(
+            "No {} token configured. Set {} or {} under [jumble] in ~/.jumble/jumble.toml.",
+            forge.name(),
+            var,
+            key
+        )
+    })?;
+
+    match fetch(&forge, &token) {
+        Ok(activity) => {
+            let fresh = CachedActivity {
+                repository: repository.to_string(),
+                fetched_at: Utc::now().to_rfc3339(),
+                activity,
+            };
+            // A cache that can't be written only costs a refetch next time.
+            let _ = write_cache(project_root, &fresh);
+            Ok(Fetched {
+                activity: fresh.activity,
+                fetched_at: fresh.fetched_at,
+                note: None,
+            })
+        }
+        Err(e) => match cached {
+            Some(stale) => Ok(Fetched {
+                activity: stale.activity,
+                fetched_at: stale.fetched_at,
+                note: Some(format!(
+                    "stale cache; {} request failed: {}",
+                    forge.name(),
+                    e
+                )),
+            }),
+            None => Err(format!("{} request failed: {}", forge.name(), e)),
+        },
+    }
+}
+
+#[cfg(not(feature = "network"))]
+fn fetch(forge: &Forge, _token: &str) -> Result<RepoActivity, String> {
+    remote::check_network(&format!("calling the {} API", forge.name()))?;
+    Err("this build has no `network` feature".to_string())
+}
+
+#[cfg(feature = "network")]
+fn fetch(forge: &Forge, token: &str) -> Result<RepoActivity, String> {
+    remote::check_network(&format!("calling the {} API", forge.name()))?;
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS)))
+        .build()
+        .into();
+    let get = |url: &str| -> Result<(Value, Option<u64>), String> {
+        let request = agent
+            .get(url)
+            .header("User-Agent", concat!("jumble/", env!("CARGO_PKG_VERSION")));
+        let request = match forge {
+            Forge::GitHub { .. } => request
+                .header("Authorization", &format!("Bearer {}", token))
+                .header("Accept", "application/vnd.github+json"),
+            Forge::GitLab { .. } => request.header("PRIVATE-TOKEN", token),
+        };
+        let mut response = request.call().map_err(|e| e.to_string())?;
+        let total = response
+            .headers()
+            .get("x-total")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok());
+        let body = response
+            .body_mut()
+            .read_json::<Value>()
+            .map_err(|e| e.to_string())?;
+        Ok((body, total))
+    };
+
+    match forge {
+        Forge::GitHub { api, owner, repo } => {
+            let (repository, _) = get(&format!("{}/repos/{}/{}", api, owner, repo))?;
+            let (pulls, _) = get(&format!(
+                "{}/search/issues?q=repo:{}/{}+is:pr+is:open&per_page=1",
+                api, owner, repo
+            ))?;
+            let (releases, _) = get(&format!(
+                "{}/repos/{}/{}/releases?per_page={}",
+                api, owner, repo, MAX_RELEASES
+            ))?;
+            Ok(github_activity(
+                &repository,
+                pulls["total_count"].as_u64().unwrap_or(0),
+                &releases,
+            ))
+        }
+        Forge::GitLab { api, path } => {
+            let project = format!("{}/projects/{}", api, path.replace('/', "%2F"));
+            let (repository, _) = get(&project)?;
+            let (merge_requests, total) = get(&format!(
+                "{}/merge_requests?state=opened&per_page=100",
+                project
+            ))?;
+            let open = total.unwrap_or_else(|| {
+                merge_requests
+                    .as_array()
+                    .map_or(0, |list| list.len() as u64)
+            });
+            let (releases, _) = get(&format!("{}/releases?per_page={}", project, MAX_RELEASES))?;
+            Ok(gitlab_activity(&repository, open, &releases))
+        }
+    }
+}
+
+#[cfg(any(feature = "network", test))]
+fn string(value: &Value) -> Option<String> {
+    value.as_str().map(str::to_string)
+}
+
+#[cfg(any(feature = "network", test))]
+fn github_activity(repository: &Value, open_pull_requests: u64, releases: &Value) -> RepoActivity {
+    RepoActivity {
+        default_branch: string(&repository["default_branch"]).unwrap_or_default(),
+        open_pull_requests,
+        releases: releases
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(MAX_RELEASES)
+            .map(|release| Release {
+                tag: string(&release["tag_name"]).unwrap_or_default(),
+                name: string(&release["name"]).filter(|n| !n.is_empty()),
+                published_at: string(&release["published_at"]),
+                url: string(&release["html_url"]),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(any(feature = "network", test))]
+fn gitlab_activity(repository: &Value, open_merge_requests: u64, releases: &Value) -> RepoActivity {
+    RepoActivity {
+        default_branch: string(&repository["default_branch"]).unwrap_or_default(),
+        open_pull_requests: open_merge_requests,
+        releases: releases
+            .as_array()
+            .into_iter()
+            .flatten()
+            .take(MAX_RELEASES)
+            .map(|release| Release {
+                tag: string(&release["tag_name"]).unwrap_or_default(),
+                name: string(&release["name"]).filter(|n| !n.is_empty()),
+                published_at: string(&release["released_at"]),
+                url: string(&release["_links"]["self"]),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_repository_urls() {
+        let github = Forge::GitHub {
+            api: "https://api.github.com".to_string(),
+            owner: "acme".to_string(),
+            repo: "api".to_string(),
+        };
+        assert_eq!(
+            Forge::parse("https://github.com/acme/api", None),
+            Some(github)
+        );
+        assert!(matches!(
+            Forge::parse("git@github.com:acme/api.git", None),
+            Some(Forge::GitHub { repo, .. }) if repo == "api"
+        ));
+        assert_eq!(
+            Forge::parse("https://gitlab.com/acme/platform/api.git", None),
+            Some(Forge::GitLab {
+                api: "https://gitlab.com/api/v4".to_string(),
+                path: "acme/platform/api".to_string(),
+            })
+        );
+        assert_eq!(Forge::parse("https://bitbucket.org/acme/api", None), None);
+        assert_eq!(Forge::parse("https://github.com/acme", None), None);
+
+        // Self-hosted forges must be listed, so tokens don't go to any host
+        // with `github` in its name.
+        let enterprise = "https://github.example.com/acme/api/";
+        assert_eq!(Forge::parse(enterprise, None), None);
+        assert_eq!(Forge::parse("https://notgithub.com/acme/api", None), None);
+        let mut config = JumbleConfig::default();
+        config.jumble.github_hosts = vec!["github.example.com".to_string()];
+        config.jumble.gitlab_hosts = vec!["git.example.com".to_string()];
+        assert!(matches!(
+            Forge::parse(enterprise, Some(&config)),
+            Some(Forge::GitHub { api, .. }) if api == "https://github.example.com/api/v3"
+        ));
+        assert!(matches!(
+            Forge::parse("https://git.example.com/acme/api", Some(&config)),
+            Some(Forge::GitLab { api, .. }) if api == "https://git.example.com/api/v4"
+        ));
+    }
+
+    #[test]
+    fn test_activity_from_api_responses() {
+        let github = github_activity(
+            &json!({"default_branch": "main"}),
+            3,
+            &json!([{"tag_name": "v1.2.0", "name": "", "published_at": "2026-09-30T10:00:00Z", "html_url": "https://github.com/acme/api/releases/tag/v1.2.0"}]),
+        );
+        assert_eq!(github.default_branch, "main");
+        assert_eq!(github.releases[0].tag, "v1.2.0");
+        assert_eq!(github.releases[0].name, None);
+
+        let gitlab = gitlab_activity(
+            &json!({"default_branch": "develop"}),
+            2,
+            &json!([{"tag_name": "v3", "name": "Three", "released_at": "2026-10-01T00:00:00Z", "_links": {"self": "https://gitlab.com/acme/api/-/releases/v3"}}]),
+        );
+        assert_eq!(gitlab.open_pull_requests, 2);
+        assert_eq!(
+            gitlab.releases[0].url.as_deref(),
+            Some("https://gitlab.com/acme/api/-/releases/v3")
+        );
+    }
+
+    #[test]
+    fn test_fresh_cache_is_served_without_a_token() {
+        let temp = TempDir::new().unwrap();
+        let repository = "https://github.com/acme/api";
+        let activity = RepoActivity {
+            default_branch: "main".to_string(),
+            open_pull_requests: 4,
+            releases: Vec::new(),
+        };
+        write_cache(
+            temp.path(),
+            &CachedActivity {
+                repository: repository.to_string(),
+                fetched_at: Utc::now().to_rfc3339(),
+                activity: activity.clone(),
+            },
+        )
+        .unwrap();
+
+        let fetched = self::activity(temp.path(), repository, None, false).unwrap();
+        assert_eq!(fetched.activity, activity);
+        assert!(fetched.note.unwrap().starts_with("cached"));
+        assert!(read_cache(temp.path(), "https://github.com/acme/web").is_none());
+    }
+}
@@ -1,6 +1,10 @@
 //! Formatting helpers for output strings.
 
-use crate::config::{ApiInfo, Concept, Dependencies, RelatedProjects};
+use crate::commands::{DiscoveredCommands, InferredCommands};
+use crate::config::{
+    ApiInfo, Concept, Dependencies, LinkedProject, OutputConfig, PathStyle, RelatedProjects,
+};
+use crate::paths;
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -15,6 +19,36 @@ pub fn format_commands(commands: &HashMap<String, String>) -> String {
     output
 }
 
+/// Inferred commands, each labelled, with a note on where they came from.
+pub fn format_inferred_commands(inferred: &InferredCommands) -> String {
+    let mut output = String::new();
+    for (name, cmd) in &inferred.commands {
+        output.push_str(&format!("- **{}**: `{}` *(inferred)*\n", name, cmd));
+    }
+    output.push_str(&format!(
+        "\n*Inferred from {}. Add them to `[commands]` in project.toml to confirm or change them.*\n",
+        inferred.source
+    ));
+    output
+}
+
+/// Task runner commands, each labelled with the file it came from.
+pub fn format_discovered_commands(discovered: &[DiscoveredCommands]) -> String {
+    let mut output = String::new();
+    for found in discovered {
+        for (name, cmd) in &found.commands {
+            output.push_str(&format!(
+                "- **{}**: `{}` *(from {})*\n",
+                name, cmd, found.source
+            ));
+        }
+    }
+    output.push_str(
+        "\n*Auto-discovered from task runner files. Entries in `[commands]` take precedence.*\n",
+    );
+    output
+}
+
 pub fn format_entry_points(entry_points: &HashMap<String, String>) -> String {
     if entry_points.is_empty() {
         return "No entry points defined.".to_string();
@@ -47,18 +81,36 @@ pub fn format_dependencies(deps: &Dependencies) -> String {
     }
 }
 
-pub fn format_related_projects(related: &RelatedProjects) -> String {
+/// `related_projects` as Markdown. Names of the form `workspace:project`
+/// are looked up in `linked`, the projects of registered workspaces.
+pub fn format_related_projects(
+    related: &RelatedProjects,
+    linked: &HashMap<String, LinkedProject>,
+) -> String {
+    let entry = |name: &String| match linked.get(name) {
+        Some(project) => format!(
+            "- {}: {} ({})\n",
+            name,
+            project.description,
+            project.path.display()
+        ),
+        None if name.contains(':') => format!(
+            "- {} (not found: is its workspace registered under [workspaces]?)\n",
+            name
+        ),
+        None => format!("- {}\n", name),
+    };
     let mut output = String::new();
     if !related.upstream.is_empty() {
         output.push_str("**Upstream (this project depends on):**\n");
         for proj in &related.upstream {
-            output.push_str(&format!("- {}\n", proj));
+            output.push_str(&entry(proj));
         }
     }
     if !related.downstream.is_empty() {
         output.push_str("**Downstream (depends on this project):**\n");
         for proj in &related.downstream {
-            output.push_str(&format!("- {}\n", proj));
+            output.push_str(&entry(proj));
         }
     }
     if output.is_empty() {
@@ -84,6 +136,15 @@ pub fn format_api(api: &Option<ApiInfo>) -> String {
                     output.push_str(&format!("- {}\n", endpoint));
                 }
             }
+            if !api_info.examples.is_empty() {
+                let mut names: Vec<&String> = api_info.examples.keys().collect();
+                names.sort();
+                let names: Vec<&str> = names.into_iter().map(|n| n.as_str()).collect();
+                output.push_str(&format!(
+                    "**Examples:** {} (see `get_api_examples`)\n",
+                    names.join(", ")
+                ));
+            }
             if output.is_empty() {
                 "API section defined but empty.".to_string()
             } else {
@@ -97,7 +158,131 @@ pub fn format_api(api: &Option<ApiInfo>) -> String {
 pub fn format_concept(project_path: &Path, name: &str, concept: &Concept) -> String {
     let mut output = format!("## {}\n\n{}\n\n**Files:**\n", name, concept.summary);
     for file in &concept.files {
-        output.push_str(&format!("- {}/{}\n", project_path.display(), file));
+        output.push_str(&format_file_entry(project_path, file));
+    }
+    output
+}
+
+/// A file list entry: the display path, followed by a `file://` URI hosts
+/// can open unless `file` is a glob pattern.
+pub fn format_file_entry(project_path: &Path, file: &str) -> String {
+    let display = format!("{}/{}", project_path.display(), file);
+    if file.contains('*') {
+        return format!("- {}\n", display);
+    }
+    let uri = paths::file_uri(&paths::join_relative(project_path, file));
+    format!("- {} <{}>\n", display, uri)
+}
+
+/// Rewrite a tool's markdown by the workspace's `[output]` settings: shift
+/// headings, swap the bullet marker, rewrite paths under `base` in the chosen
+/// style and drop emoji. Code blocks are left as they are.
+pub fn apply_output_style(text: &str, style: &OutputConfig, base: &Path) -> String {
+    if *style == OutputConfig::default() {
+        return text.to_string();
+    }
+    let base = base.display().to_string();
+    let shift = usize::from(style.heading_level.clamp(1, 6)) - 1;
+    let mut in_fence = false;
+    let mut output = String::with_capacity(text.len());
+    for line in text.split_inclusive('\n') {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            output.push_str(line);
+            continue;
+        }
+        if in_fence {
+            output.push_str(line);
+            continue;
+        }
+        let mut line = line.to_string();
+        let hashes = line.bytes().take_while(|&b| b == b'#').count();
+        if shift > 0 && (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+            let level = (hashes + shift).min(6);
+            line = format!("{}{}", "#".repeat(level), &line[hashes..]);
+        }
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        if line[indent..].starts_with("- ") {
+            line.replace_range(indent..indent + 1, &style.bullet.marker().to_string());
+        }
+        if style.paths != PathStyle::Absolute {
+            line = rewrite_paths(&line, &base, style.paths);
+        }
+        if !style.emoji {
+            line = strip_emoji(&line);
+        }
+        output.push_str(&line);
+    }
+    output
+}
+
+/// Length of the path starting `text`, without trailing punctuation.
+fn path_len(text: &str) -> usize {
+    let end = text
+        .find(|c: char| c.is_whitespace() || "`<>()[]\"',;|".contains(c))
+        .unwrap_or(text.len());
+    text[..end].trim_end_matches(['.', ':']).len()
+}
+
+/// Rewrite `base` and the paths under it in `line` as relative paths or
+/// `file://` URIs. A URI following a path it duplicates is dropped, and
+/// existing URIs and glob patterns are left alone.
+fn rewrite_paths(line: &str, base: &str, style: PathStyle) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(at) = rest.find(base) {
+        output.push_str(&rest[..at]);
+        let from = &rest[at..];
+        let path = &from[..path_len(from).max(base.len())];
+        let tail = &path[base.len()..];
+        // Inside a longer path or URI, or a longer name like `/ws/api2`.
+        let embedded = output.ends_with(|c: char| c.is_alphanumeric() || "/._-".contains(c))
+            || !(tail.is_empty() || tail.starts_with('/'));
+        rest = &from[path.len()..];
+        match style {
+            _ if embedded => output.push_str(path),
+            PathStyle::Relative => match tail.strip_prefix('/') {
+                Some(relative) if !relative.is_empty() => output.push_str(relative),
+                _ => output.push('.'),
+            },
+            PathStyle::Uri if !path.contains('*') => {
+                let uri = paths::file_uri(Path::new(path));
+                if let Some(after) = rest.strip_prefix(&format!(" <{}>", uri)) {
+                    rest = after;
+                }
+                output.push_str(&uri);
+            }
+            _ => output.push_str(path),
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn is_emoji(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0xFE0F | 0x200D
+    )
+}
+
+/// Drop emoji from `line`, along with the space that followed one at the
+/// start of a word.
+fn strip_emoji(line: &str) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if !is_emoji(c) {
+            output.push(c);
+            continue;
+        }
+        while chars.peek().copied().is_some_and(is_emoji) {
+            chars.next();
+        }
+        let word_start = output.is_empty() || output.ends_with(' ');
+        if word_start && chars.peek() == Some(&' ') {
+            chars.next();
+        }
     }
     output
 }
@@ -105,6 +290,8 @@ pub fn format_concept(project_path: &Path, name: &str, concept: &Concept) -> Str
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ApiExample;
+    use std::path::PathBuf;
 
     #[test]
     fn test_format_commands_empty() {
@@ -177,7 +364,7 @@ mod tests {
     fn test_format_related_projects_empty() {
         let related = RelatedProjects::default();
         assert_eq!(
-            format_related_projects(&related),
+            format_related_projects(&related, &HashMap::new()),
             "No related projects defined."
         );
     }
@@ -185,13 +372,27 @@ mod tests {
     #[test]
     fn test_format_related_projects() {
         let related = RelatedProjects {
-            upstream: vec!["core-lib".to_string()],
+            upstream: vec![
+                "core-lib".to_string(),
+                "libs:http".to_string(),
+                "libs:gone".to_string(),
+            ],
             downstream: vec!["frontend".to_string()],
         };
+        let mut linked = HashMap::new();
+        linked.insert(
+            "libs:http".to_string(),
+            LinkedProject {
+                path: PathBuf::from("/src/libs/http"),
+                description: "HTTP client".to_string(),
+            },
+        );
 
-        let result = format_related_projects(&related);
+        let result = format_related_projects(&related, &linked);
         assert!(result.contains("Upstream"));
-        assert!(result.contains("core-lib"));
+        assert!(result.contains("- core-lib\n"));
+        assert!(result.contains("- libs:http: HTTP client (/src/libs/http)\n"));
+        assert!(result.contains("- libs:gone (not found: is its workspace registered"));
         assert!(result.contains("Downstream"));
         assert!(result.contains("frontend"));
     }
@@ -207,6 +408,7 @@ mod tests {
             openapi: Some("api.yaml".to_string()),
             base_url: Some("/api/v1".to_string()),
             endpoints: vec!["GET /users".to_string()],
+            examples: HashMap::from([("list-users".to_string(), ApiExample::default())]),
         });
 
         let result = format_api(&api);
@@ -215,6 +417,7 @@ mod tests {
         assert!(result.contains("Base URL"));
         assert!(result.contains("/api/v1"));
         assert!(result.contains("Endpoints"));
+        assert!(result.contains("**Examples:** list-users"));
     }
 
     #[test]
@@ -223,6 +426,7 @@ mod tests {
             openapi: None,
             base_url: None,
             endpoints: vec![],
+            examples: HashMap::new(),
         });
 
         assert_eq!(format_api(&api), "API section defined but empty.");
@@ -233,6 +437,8 @@ mod tests {
         let concept = Concept {
             files: vec!["src/auth.rs".to_string(), "src/jwt.rs".to_string()],
             summary: "Authentication module".to_string(),
+            pinned: false,
+            prompts: Vec::new(),
         };
         let path = Path::new("/project");
 
@@ -242,4 +448,57 @@ mod tests {
         assert!(result.contains("/project/src/auth.rs"));
         assert!(result.contains("/project/src/jwt.rs"));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_format_file_entry_adds_uri_except_for_globs() {
+        let path = Path::new("/project");
+        assert_eq!(
+            format_file_entry(path, "src/auth.rs"),
+            "- /project/src/auth.rs <file:///project/src/auth.rs>\n"
+        );
+        assert_eq!(
+            format_file_entry(path, "src/**/*.rs"),
+            "- /project/src/**/*.rs\n"
+        );
+    }
+
+    #[test]
+    fn test_apply_output_style() {
+        let root = Path::new("/ws");
+        let text = "# api\n\n## Files\n- /ws/api/src/main.rs <file:///ws/api/src/main.rs>\n  - 🚀 Ship it ✅\n\n```sh\n# not a heading\n- /ws/keep\n```\n---\n";
+        assert_eq!(
+            apply_output_style(text, &OutputConfig::default(), root),
+            text
+        );
+
+        let style = OutputConfig {
+            heading_level: 2,
+            bullet: crate::config::BulletStyle::Asterisk,
+            paths: PathStyle::Relative,
+            emoji: false,
+            ..OutputConfig::default()
+        };
+        assert_eq!(
+            apply_output_style(text, &style, root),
+            "## api\n\n### Files\n* api/src/main.rs <file:///ws/api/src/main.rs>\n  * Ship it \n\n```sh\n# not a heading\n- /ws/keep\n```\n---\n"
+        );
+    }
+
+    #[test]
+    fn test_path_styles() {
+        let text = "**Path:** /ws/api\n- /ws/api/src/main.rs <file:///ws/api/src/main.rs>\n- /ws/api/src/*.rs\n- /ws/api2/lib.rs\n- See `/ws/web/app.ts`.\n";
+        let style = |paths| OutputConfig {
+            paths,
+            ..OutputConfig::default()
+        };
+        assert_eq!(
+            apply_output_style(text, &style(PathStyle::Relative), Path::new("/ws/api")),
+            "**Path:** .\n- src/main.rs <file:///ws/api/src/main.rs>\n- src/*.rs\n- /ws/api2/lib.rs\n- See `/ws/web/app.ts`.\n"
+        );
+        assert_eq!(
+            apply_output_style(text, &style(PathStyle::Uri), Path::new("/ws")),
+            "**Path:** file:///ws/api\n- file:///ws/api/src/main.rs\n- /ws/api/src/*.rs\n- file:///ws/api2/lib.rs\n- See `file:///ws/web/app.ts`.\n"
+        );
+    }
 }
@@ -0,0 +1,146 @@
+//! How messages are delimited on stdio: one JSON value per line, as MCP
+//! specifies, or LSP-style `Content-Length` headers for hosts that frame
+//! messages that way (and may send bodies spanning several lines).
+
+use serde::Serialize;
+use std::io::{self, BufRead, Read, Write};
+
+use crate::limits::{self, LineRead};
+
+/// Longest header line accepted in LSP framing.
+const MAX_HEADER_BYTES: usize = 1024;
+
+/// Message framing for `--framing`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Framing {
+    /// Newline-delimited JSON.
+    #[default]
+    Lines,
+    /// `Content-Length: <bytes>` and a blank line before each message.
+    Lsp,
+}
+
+impl Framing {
+    /// Read one message into `buf`, never holding more than `max` bytes of
+    /// it. [`LineRead::Line`] means a complete message, whatever the framing.
+    pub fn read<R: BufRead>(
+        self,
+        reader: &mut R,
+        buf: &mut Vec<u8>,
+        max: usize,
+    ) -> io::Result<LineRead> {
+        match self {
+            Framing::Lines => limits::read_line_limited(reader, buf, max),
+            Framing::Lsp => read_content_length(reader, buf, max),
+        }
+    }
+
+    /// Write `message` as one framed JSON message, in a single `write_all`
+    /// so writers shared between threads can keep messages whole.
+    pub fn write(self, writer: &mut impl Write, message: &impl Serialize) -> io::Result<()> {
+        let json = serde_json::to_string(message)?;
+        let framed = match self {
+            Framing::Lines => format!("{}\n", json),
+            Framing::Lsp => format!("Content-Length: {}\r\n\r\n{}", json.len(), json),
+        };
+        writer.write_all(framed.as_bytes())
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Read one `Content-Length` framed message. Oversized bodies are read and
+/// discarded so the next call starts on the following message; a missing or
+/// malformed length can't be skipped past and is an error.
+fn read_content_length<R: BufRead>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max: usize,
+) -> io::Result<LineRead> {
+    let mut length = None;
+    let mut seen_header = false;
+    loop {
+        match limits::read_line_limited(reader, buf, MAX_HEADER_BYTES)? {
+            LineRead::Eof if !seen_header => return Ok(LineRead::Eof),
+            LineRead::Eof => return Err(invalid("Input ended inside message headers".into())),
+            LineRead::TooLong => return Err(invalid("Message header too long".into())),
+            // Blank lines between messages are tolerated.
+            LineRead::Line if buf.is_empty() && !seen_header => continue,
+            LineRead::Line if buf.is_empty() => break,
+            LineRead::Line => {
+                seen_header = true;
+                let header = String::from_utf8_lossy(buf);
+                if let Some((name, value)) = header.split_once(':') {
+                    if name.trim().eq_ignore_ascii_case("content-length") {
+                        length = Some(value.trim().parse::<usize>().map_err(|_| {
+                            invalid(format!("Invalid Content-Length: {}", value.trim()))
+                        })?);
+                    }
+                }
+            }
+        }
+    }
+    let length = length.ok_or_else(|| invalid("Missing Content-Length header".into()))?;
+    buf.clear();
+    if length > max {
+        io::copy(&mut reader.take(length as u64), &mut io::sink())?;
+        return Ok(LineRead::TooLong);
+    }
+    buf.resize(length, 0);
+    reader.read_exact(buf)?;
+    Ok(LineRead::Line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_lsp_framing_round_trips_multiline_bodies() {
+        let body = "{\n  \"jsonrpc\": \"2.0\",\n  \"id\": 1\n}";
+        let input = format!(
+            "Content-Length: {}\r\nContent-Type: application/vscode-jsonrpc; charset=utf-8\r\n\r\n{}\
+             \r\ncontent-length: 300\r\n\r\n{}Content-Length: 2\r\n\r\n{{}}",
+            body.len(),
+            body,
+            "x".repeat(300)
+        );
+        let mut reader = Cursor::new(input.into_bytes());
+        let mut buf = Vec::new();
+        let framing = Framing::Lsp;
+        assert_eq!(
+            framing.read(&mut reader, &mut buf, 100).unwrap(),
+            LineRead::Line
+        );
+        assert_eq!(buf, body.as_bytes());
+        assert_eq!(
+            framing.read(&mut reader, &mut buf, 100).unwrap(),
+            LineRead::TooLong
+        );
+        assert_eq!(
+            framing.read(&mut reader, &mut buf, 100).unwrap(),
+            LineRead::Line
+        );
+        assert_eq!(buf, b"{}");
+        assert_eq!(
+            framing.read(&mut reader, &mut buf, 100).unwrap(),
+            LineRead::Eof
+        );
+
+        let mut reader = Cursor::new(b"Content-Type: json\r\n\r\n{}".to_vec());
+        assert!(framing.read(&mut reader, &mut buf, 100).is_err());
+
+        let mut output = Vec::new();
+        framing.write(&mut output, &json!({"id": 1})).unwrap();
+        assert_eq!(output, b"Content-Length: 8\r\n\r\n{\"id\":1}");
+        output.clear();
+        Framing::Lines
+            .write(&mut output, &json!({"id": 1}))
+            .unwrap();
+        assert_eq!(output, b"{\"id\":1}\n");
+    }
+}
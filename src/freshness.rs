@@ -0,0 +1,307 @@
+//! `verify_context`: a quick check that what a project's `.jumble` files
+//! point at still exists, for agents starting a session in a repository
+//! whose context may have drifted.
+//!
+//! Concept files, entry points, indexed docs and the OpenAPI spec must exist
+//! on disk, and the program each command starts must resolve, either as a
+//! path in the project or on `PATH`. The probe is time-boxed: projects with
+//! many references are sampled evenly, and whatever is left when the budget
+//! runs out is reported as unchecked rather than slowing the session down.
+
+use std::ffi::OsStr;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::config::{ProjectConfig, ProjectDocs};
+use crate::exec;
+use crate::paths;
+
+/// Time a probe may take before it stops checking.
+pub const PROBE_BUDGET: Duration = Duration::from_secs(2);
+
+/// File references checked at most; larger projects are sampled.
+pub const MAX_FILE_SAMPLES: usize = 200;
+
+/// Words that start a command but are run by the shell, not found on `PATH`.
+const SHELL_BUILTINS: &[&str] = &[
+    ".", ":", "[", "cd", "echo", "exec", "exit", "export", "false", "set", "source", "test",
+    "true", "unset",
+];
+
+/// A reference that points at nothing.
+#[derive(Debug, PartialEq)]
+pub struct Stale {
+    /// The path, or the program a command starts.
+    pub target: String,
+    /// Where it is referenced, e.g. ``concept `auth` ``.
+    pub source: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Freshness {
+    pub files_checked: usize,
+    /// File references found, including any not sampled.
+    pub files_total: usize,
+    pub missing_files: Vec<Stale>,
+    pub commands_checked: usize,
+    pub commands_total: usize,
+    pub unresolved_commands: Vec<Stale>,
+    /// Whether the budget ran out before everything sampled was checked.
+    pub timed_out: bool,
+    pub elapsed: Duration,
+}
+
+/// Check `config`'s references under `project_dir`, resolving commands
+/// against `path_var` (the value of `PATH`), for at most `budget`.
+pub fn probe(
+    project_dir: &Path,
+    config: &ProjectConfig,
+    docs: &ProjectDocs,
+    path_var: &OsStr,
+    budget: Duration,
+) -> Freshness {
+    let started = Instant::now();
+    let mut report = Freshness::default();
+
+    let mut files = file_references(config, docs);
+    report.files_total = files.len();
+    let step = files.len().div_ceil(MAX_FILE_SAMPLES).max(1);
+    files = files.into_iter().step_by(step).collect();
+    for (file, source) in files {
+        if started.elapsed() >= budget {
+            report.timed_out = true;
+            break;
+        }
+        report.files_checked += 1;
+        if !paths::join_relative(project_dir, &file).exists() {
+            report.missing_files.push(Stale {
+                target: file,
+                source,
+            });
+        }
+    }
+
+    let commands = command_references(config);
+    report.commands_total = commands.len();
+    for (name, command) in commands {
+        if started.elapsed() >= budget {
+            report.timed_out = true;
+            break;
+        }
+        report.commands_checked += 1;
+        for program in programs(&command) {
+            if !resolves(&program, project_dir, path_var) {
+                report.unresolved_commands.push(Stale {
+                    target: program,
+                    source: format!("command `{}`", name),
+                });
+            }
+        }
+    }
+
+    report.elapsed = started.elapsed();
+    report
+}
+
+/// Every non-glob path the project refers to, with where it came from,
+/// sorted so sampling is stable.
+fn file_references(config: &ProjectConfig, docs: &ProjectDocs) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = Vec::new();
+    for (name, concept) in &config.concepts {
+        for file in &concept.files {
+            files.push((file.clone(), format!("concept `{}`", name)));
+        }
+    }
+    for (name, file) in &config.entry_points {
+        files.push((file.clone(), format!("entry point `{}`", name)));
+    }
+    for (name, doc) in &docs.docs {
+        files.push((doc.path.clone(), format!("doc `{}`", name)));
+    }
+    if let Some(openapi) = config.api.as_ref().and_then(|api| api.openapi.as_ref()) {
+        files.push((openapi.clone(), "`api.openapi`".to_string()));
+    }
+    files.retain(|(file, _)| !file.contains('*'));
+    files.sort();
+    files
+}
+
+/// Commands by name, profile commands as `<profile>.<name>`, sorted.
+fn command_references(config: &ProjectConfig) -> Vec<(String, String)> {
+    let mut commands: Vec<(String, String)> = config
+        .commands
+        .iter()
+        .map(|(name, command)| (name.clone(), command.clone()))
+        .collect();
+    for (profile, set) in &config.profiles {
+        for (name, command) in &set.commands {
+            commands.push((format!("{}.{}", profile, name), command.clone()));
+        }
+    }
+    commands.sort();
+    commands
+}
+
+/// The program each step of a shell command line starts: `cargo` and `npm`
+/// for `cargo build && RUST_LOG=debug npm test`. Builtins are left out.
+pub fn programs(command: &str) -> Vec<String> {
+    command
+        .split(['&', '|', ';'])
+        .filter_map(|step| exec::split_command(step).ok())
+        .filter_map(|words| words.into_iter().find(|word| !is_assignment(word)))
+        .filter(|program| !SHELL_BUILTINS.contains(&program.as_str()))
+        .collect()
+}
+
+/// Whether `word` is a leading `NAME=value` environment assignment.
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+/// Whether `program` names an executable: a path relative to the project,
+/// or a name found in one of `path_var`'s directories.
+pub fn resolves(program: &str, project_dir: &Path, path_var: &OsStr) -> bool {
+    if program.contains(['/', '\\']) {
+        return is_executable(&paths::join_relative(project_dir, program));
+    }
+    std::env::split_paths(path_var).any(|dir| {
+        if cfg!(windows) {
+            ["", ".exe", ".cmd", ".bat", ".com"]
+                .iter()
+                .any(|ext| dir.join(format!("{}{}", program, ext)).is_file())
+        } else {
+            is_executable(&dir.join(program))
+        }
+    })
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Render a probe as markdown.
+pub fn format_freshness(name: &str, report: &Freshness) -> String {
+    let mut output = format!(
+        "# Context Freshness: {}\n\nChecked {} of {} file reference(s) and {} of {} command(s) in {} ms.\n",
+        name,
+        report.files_checked,
+        report.files_total,
+        report.commands_checked,
+        report.commands_total,
+        report.elapsed.as_millis()
+    );
+    if report.files_checked < report.files_total && !report.timed_out {
+        output.push_str(&format!(
+            "File references were sampled: one in every {} was checked.\n",
+            report.files_total.div_ceil(MAX_FILE_SAMPLES)
+        ));
+    }
+    if report.timed_out {
+        output.push_str("Stopped when the time budget ran out; the rest was not checked.\n");
+    }
+
+    if report.missing_files.is_empty() && report.unresolved_commands.is_empty() {
+        output.push_str("\nEverything checked is still there.\n");
+        return output;
+    }
+    if !report.missing_files.is_empty() {
+        output.push_str("\n## Missing Files\n\n");
+        for stale in &report.missing_files {
+            output.push_str(&format!("- `{}` ({})\n", stale.target, stale.source));
+        }
+    }
+    if !report.unresolved_commands.is_empty() {
+        output.push_str("\n## Unresolved Commands\n\n");
+        for stale in &report.unresolved_commands {
+            output.push_str(&format!(
+                "- `{}` ({}) is not on PATH or in the project\n",
+                stale.target, stale.source
+            ));
+        }
+    }
+    output.push_str(
+        "\nTreat these parts of the context as out of date, and fix the `.jumble` files when you can.\n",
+    );
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::DocEntry;
+    use tempfile::TempDir;
+
+    #[test]
+    #[cfg(unix)]
+    fn test_probe_reports_missing_files_and_programs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = TempDir::new().unwrap();
+        let bin = temp.path().join("bin");
+        std::fs::create_dir_all(&bin).unwrap();
+        std::fs::write(bin.join("cargo"), "").unwrap();
+        std::fs::set_permissions(bin.join("cargo"), std::fs::Permissions::from_mode(0o755))
+            .unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/lib.rs"), "").unwrap();
+
+        let config: ProjectConfig = toml::from_str(
+            "[project]\nname = \"app\"\ndescription = \"App\"\n\n\
+             [commands]\nbuild = \"cargo build\"\nlint = \"cd web && FORCE_COLOR=1 eslint .\"\n\
+             deploy = \"./scripts/deploy.sh\"\n\n\
+             [entry_points]\nlib = \"src/lib.rs\"\n\n\
+             [concepts.auth]\nfiles = [\"src/lib.rs\", \"src/auth.rs\", \"src/**/*.rs\"]\nsummary = \"Auth\"\n",
+        )
+        .unwrap();
+        let mut docs = ProjectDocs::default();
+        docs.docs.insert(
+            "guide".to_string(),
+            DocEntry {
+                path: "docs/guide.md".to_string(),
+                summary: "Guide".to_string(),
+            },
+        );
+
+        let report = probe(temp.path(), &config, &docs, bin.as_os_str(), PROBE_BUDGET);
+        assert_eq!((report.files_checked, report.files_total), (4, 4));
+        assert_eq!(
+            report.missing_files,
+            vec![
+                Stale {
+                    target: "docs/guide.md".to_string(),
+                    source: "doc `guide`".to_string(),
+                },
+                Stale {
+                    target: "src/auth.rs".to_string(),
+                    source: "concept `auth`".to_string(),
+                },
+            ]
+        );
+        let unresolved: Vec<&str> = report
+            .unresolved_commands
+            .iter()
+            .map(|stale| stale.target.as_str())
+            .collect();
+        assert_eq!(unresolved, vec!["./scripts/deploy.sh", "eslint"]);
+
+        let text = format_freshness("app", &report);
+        assert!(text.contains("Checked 4 of 4 file reference(s) and 3 of 3 command(s)"));
+        assert!(text.contains("- `src/auth.rs` (concept `auth`)\n"));
+        assert!(text.contains("- `eslint` (command `lint`) is not on PATH"));
+
+        // Nothing is checked once the budget is spent.
+        let report = probe(temp.path(), &config, &docs, bin.as_os_str(), Duration::ZERO);
+        assert!(report.timed_out);
+        assert_eq!(report.files_checked + report.commands_checked, 0);
+    }
+}
@@ -0,0 +1,330 @@
+//! Rendering agent guide files from the live jumble configuration.
+//!
+//! Generated content sits between `<!-- jumble:begin <name> -->` and
+//! `<!-- jumble:end <name> -->` markers, so regenerating replaces only that
+//! block and leaves anything the user wrote around it alone.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::commands;
+use crate::config::WorkspaceConfig;
+use crate::server::Server;
+use crate::text;
+use crate::tools::{self, ProjectData};
+
+/// Marker name for the generated block in AGENTS.md.
+pub const AGENTS_MD: &str = "agents-md";
+
+fn begin_marker(name: &str) -> String {
+    format!("<!-- jumble:begin {} -->", name)
+}
+
+fn end_marker(name: &str) -> String {
+    format!("<!-- jumble:end {} -->", name)
+}
+
+/// Wrap `body` in the begin/end markers for `name`.
+pub fn marked_section(name: &str, body: &str) -> String {
+    format!(
+        "{}\n{}\n{}\n",
+        begin_marker(name),
+        body.trim_end(),
+        end_marker(name)
+    )
+}
+
+/// Whether `content` contains a complete marked block for `name`.
+pub fn has_marked_section(content: &str, name: &str) -> bool {
+    content
+        .find(&begin_marker(name))
+        .is_some_and(|start| content[start..].contains(&end_marker(name)))
+}
+
+/// Replace the marked block for `name` in `content`, or append one if the
+/// markers are missing.
+pub fn update_marked_section(content: &str, name: &str, body: &str) -> String {
+    let section = marked_section(name, body);
+    let begin = begin_marker(name);
+    let end = end_marker(name);
+
+    if let Some(start) = content.find(&begin) {
+        if let Some(end_offset) = content[start..].find(&end) {
+            let mut stop = start + end_offset + end.len();
+            if content[stop..].starts_with('\n') {
+                stop += 1;
+            }
+            return format!("{}{}{}", &content[..start], section, &content[stop..]);
+        }
+    }
+
+    let mut updated = content.to_string();
+    if !updated.is_empty() {
+        if !updated.ends_with('\n') {
+            updated.push('\n');
+        }
+        updated.push('\n');
+    }
+    updated.push_str(&section);
+    updated
+}
+
+/// Outcome of writing a generated file.
+#[derive(Debug, PartialEq)]
+pub enum WriteOutcome {
+    Created,
+    Updated,
+    Unchanged,
+}
+
+/// Content of a new file holding `heading` and the marked block for `name`.
+pub fn new_marked_file(heading: &str, name: &str, body: &str) -> String {
+    format!("{}\n\n{}", heading, marked_section(name, body))
+}
+
+/// Create `path` with `heading` and the marked block, or update the block in
+/// place if the file exists.
+pub fn write_marked_file(
+    path: &Path,
+    heading: &str,
+    name: &str,
+    body: &str,
+) -> Result<WriteOutcome> {
+    if !path.exists() {
+        let content = new_marked_file(heading, name, body);
+        fs::write(path, content).with_context(|| format!("Failed to create {}", path.display()))?;
+        return Ok(WriteOutcome::Created);
+    }
+
+    let existing =
+        text::read_text(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let updated = update_marked_section(&existing, name, body);
+    if updated == existing {
+        return Ok(WriteOutcome::Unchanged);
+    }
+    fs::write(path, updated).with_context(|| format!("Failed to update {}", path.display()))?;
+    Ok(WriteOutcome::Updated)
+}
+
+/// Render the jumble block of AGENTS.md from what is discovered under `root`.
+pub fn render_agents_md(root: &Path) -> Result<String> {
+    let (workspace, projects) = Server::discover_static(root)?;
+    Ok(agents_md_body(root, workspace.as_ref(), &projects))
+}
+
+fn agents_md_body(
+    root: &Path,
+    workspace: Option<&WorkspaceConfig>,
+    projects: &HashMap<String, ProjectData>,
+) -> String {
+    let mut out = String::from("## Using Jumble in This Project\n\n");
+    out.push_str(
+        "This section is generated by `jumble generate agents-md` from the `.jumble` files in this \
+         workspace. Edit those files and regenerate instead of editing it by hand.\n\n",
+    );
+    out.push_str("- **Start by calling `get_workspace_overview()`** from the Jumble MCP server.\n");
+    if root.join(".ai").join("constitution.md").is_file() {
+        out.push_str("- **Follow the project constitution** in `.ai/constitution.md`.\n");
+    }
+    out.push_str(
+        "- Use `get_commands(project, type)` instead of guessing build, test or lint commands.\n\
+         - Check `get_conventions(project)` and `get_workspace_conventions()` before writing code.\n",
+    );
+
+    if let Some(ws) = workspace {
+        if !ws.conventions.is_empty() || !ws.gotchas.is_empty() {
+            out.push_str("\n### Workspace Conventions\n\n");
+            push_sorted(&mut out, &ws.conventions);
+            push_sorted(&mut out, &ws.gotchas);
+        }
+    }
+
+    out.push_str("\n### Projects\n");
+    if projects.is_empty() {
+        out.push_str(&format!(
+            "\nNo projects found yet. Create `{}` to add one.\n",
+            crate::paths::config_file("project.toml")
+        ));
+    }
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort();
+    for name in names {
+        let (path, config, skills, conventions, _, _) = &projects[name];
+        out.push_str(&format!(
+            "\n#### {}\n\n{}\n\n",
+            name, config.project.description
+        ));
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let relative = if relative.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            relative.display().to_string().replace('\\', "/")
+        };
+        out.push_str(&format!("- Path: `{}`\n", relative));
+        if let Some(lang) = &config.project.language {
+            out.push_str(&format!("- Language: {}\n", lang));
+        }
+
+        let mut commands: Vec<(&String, &String)> = config.commands.iter().collect();
+        commands.sort();
+        for (kind, command) in commands {
+            out.push_str(&format!("- `{}`: `{}`\n", kind, command));
+        }
+        if let Some(inferred) = commands::infer(path, config) {
+            for (kind, command) in &inferred.commands {
+                out.push_str(&format!(
+                    "- `{}`: `{}` (inferred from {})\n",
+                    kind, command, inferred.source
+                ));
+            }
+        }
+
+        if !conventions.conventions.is_empty() || !conventions.gotchas.is_empty() {
+            out.push_str("\nConventions and gotchas:\n\n");
+            push_sorted(&mut out, &conventions.conventions);
+            push_sorted(&mut out, &conventions.gotchas);
+        }
+
+        if !skills.skills.is_empty() {
+            let mut skill_names: Vec<&str> = skills.skills.keys().map(|s| s.as_str()).collect();
+            skill_names.sort();
+            out.push_str(&format!("\nSkills: {}\n", skill_names.join(", ")));
+        }
+    }
+
+    out.push_str("\n### Available Jumble Tools\n\n");
+    for (tool_name, description) in tool_summaries(workspace) {
+        out.push_str(&format!("- `{}` - {}\n", tool_name, description));
+    }
+    out
+}
+
+fn push_sorted(out: &mut String, entries: &HashMap<String, String>) {
+    let mut entries: Vec<(&String, &String)> = entries.iter().collect();
+    entries.sort();
+    for (key, value) in entries {
+        out.push_str(&format!("- **{}**: {}\n", key, value));
+    }
+}
+
+/// Built-in and workspace-defined tools with the first sentence of their
+/// description.
+fn tool_summaries(workspace: Option<&WorkspaceConfig>) -> Vec<(String, String)> {
+    let mut summaries: Vec<(String, String)> = tools::tools_list()["tools"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .map(|tool| {
+            (
+                tool["name"].as_str().unwrap_or_default().to_string(),
+                first_sentence(&tool["description"]),
+            )
+        })
+        .collect();
+
+    if let Some(ws) = workspace {
+        let mut custom: Vec<(&String, _)> = ws.tools.iter().collect();
+        custom.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, tool) in custom {
+            if !tools::is_known_tool(name) {
+                summaries.push((name.clone(), tool.description.clone()));
+            }
+        }
+    }
+    summaries
+}
+
+fn first_sentence(description: &Value) -> String {
+    let text = description.as_str().unwrap_or_default();
+    match text.find(". ") {
+        Some(end) => text[..=end].to_string(),
+        None => text.to_string(),
+    }
+}
+
+/// `jumble generate agents-md`: update AGENTS.md at `root`, or print the
+/// generated block with `stdout`.
+pub fn run_agents_md(root: &Path, stdout: bool) -> Result<()> {
+    let body = render_agents_md(root)?;
+    if stdout {
+        print!("{}", marked_section(AGENTS_MD, &body));
+        return Ok(());
+    }
+
+    let path = root.join("AGENTS.md");
+    match write_marked_file(&path, "# AGENTS.md", AGENTS_MD, &body)? {
+        WriteOutcome::Created => println!("✓ Created AGENTS.md"),
+        WriteOutcome::Updated => println!("✓ Updated AGENTS.md"),
+        WriteOutcome::Unchanged => println!("✓ AGENTS.md is up to date"),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_update_marked_section_replaces_only_the_block() {
+        let original = format!(
+            "# Notes\n\nKeep me.\n\n{}After.\n",
+            marked_section("x", "old")
+        );
+        let updated = update_marked_section(&original, "x", "new");
+        assert_eq!(
+            updated,
+            "# Notes\n\nKeep me.\n\n<!-- jumble:begin x -->\nnew\n<!-- jumble:end x -->\nAfter.\n"
+        );
+        assert_eq!(update_marked_section(&updated, "x", "new"), updated);
+    }
+
+    #[test]
+    fn test_update_marked_section_appends_when_missing() {
+        let updated = update_marked_section("# Notes", "x", "body");
+        assert_eq!(
+            updated,
+            "# Notes\n\n<!-- jumble:begin x -->\nbody\n<!-- jumble:end x -->\n"
+        );
+    }
+
+    #[test]
+    fn test_agents_md_reflects_discovered_projects() {
+        let temp = TempDir::new().unwrap();
+        let jumble_dir = temp.path().join("api").join(".jumble");
+        fs::create_dir_all(&jumble_dir).unwrap();
+        fs::write(
+            jumble_dir.join("project.toml"),
+            "[project]\nname = \"api\"\ndescription = \"The API\"\n\n[commands]\ntest = \"cargo test\"\n",
+        )
+        .unwrap();
+        fs::write(
+            jumble_dir.join("conventions.toml"),
+            "[gotchas]\nmigrations = \"Never edit applied migrations\"\n",
+        )
+        .unwrap();
+
+        let agents = temp.path().join("AGENTS.md");
+        fs::write(&agents, "# Team notes\n\nHand-written.\n").unwrap();
+        run_agents_md(temp.path(), false).unwrap();
+
+        let content = fs::read_to_string(&agents).unwrap();
+        assert!(content.starts_with("# Team notes\n\nHand-written.\n"));
+        assert!(content.contains("#### api"));
+        assert!(content.contains("- Path: `api`"));
+        assert!(content.contains("- `test`: `cargo test`"));
+        assert!(content.contains("Never edit applied migrations"));
+        assert!(content.contains("- `get_context_health` - "));
+
+        let path = temp.path().join("AGENTS.md");
+        let body = render_agents_md(temp.path()).unwrap();
+        assert_eq!(
+            write_marked_file(&path, "# AGENTS.md", AGENTS_MD, &body).unwrap(),
+            WriteOutcome::Unchanged
+        );
+    }
+}
@@ -0,0 +1,214 @@
+//! Context health scoring: how completely a project's `.jumble` files
+//! describe it, as a checklist and a 0–100 score.
+
+use std::path::Path;
+
+use crate::config::{ProjectConfig, ProjectConventions, ProjectDocs};
+use crate::paths;
+
+/// Concepts a project needs before its architecture counts as described.
+pub const MIN_CONCEPTS: usize = 3;
+
+/// One checklist item.
+#[derive(Debug)]
+pub struct Check {
+    pub label: &'static str,
+    pub passed: bool,
+    /// What to do about a failed check, or what was found.
+    pub detail: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct ProjectHealth {
+    pub checks: Vec<Check>,
+    /// Concept files and doc paths that do not exist on disk.
+    pub dangling: Vec<String>,
+}
+
+impl ProjectHealth {
+    /// Percentage of checks passed, rounded down.
+    pub fn score(&self) -> u32 {
+        if self.checks.is_empty() {
+            return 0;
+        }
+        let passed = self.checks.iter().filter(|c| c.passed).count();
+        (passed * 100 / self.checks.len()) as u32
+    }
+}
+
+/// Score one project's context.
+pub fn assess(
+    root: &Path,
+    config: &ProjectConfig,
+    conventions: &ProjectConventions,
+    docs: &ProjectDocs,
+) -> ProjectHealth {
+    let mut dangling: Vec<String> = dangling_references(root, config, docs)
+        .into_iter()
+        .map(|(_, path)| path.to_string())
+        .collect();
+    dangling.sort();
+    dangling.dedup();
+
+    let concept_count = config.concepts.len();
+    let checks = vec![
+        Check {
+            label: "Has a description",
+            passed: !config.project.description.trim().is_empty(),
+            detail: None,
+        },
+        Check {
+            label: "Defines commands",
+            passed: !config.commands.is_empty(),
+            detail: config
+                .commands
+                .is_empty()
+                .then(|| "Add a [commands] table (build, test, lint, ...)".to_string()),
+        },
+        Check {
+            label: "Describes at least 3 concepts",
+            passed: concept_count >= MIN_CONCEPTS,
+            detail: Some(format!("{} defined", concept_count)),
+        },
+        Check {
+            label: "Has conventions or gotchas",
+            passed: !conventions.conventions.is_empty() || !conventions.gotchas.is_empty(),
+            detail: None,
+        },
+        Check {
+            label: "Has a docs index",
+            passed: !docs.docs.is_empty(),
+            detail: None,
+        },
+        Check {
+            label: "No dangling file references",
+            passed: dangling.is_empty(),
+            detail: (!dangling.is_empty()).then(|| format!("{} missing", dangling.len())),
+        },
+    ];
+
+    ProjectHealth { checks, dangling }
+}
+
+/// What lists a dangling reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Referrer<'a> {
+    /// A file or directory in the named concept's `files`.
+    Concept(&'a str),
+    /// The named doc's `path`.
+    Doc(&'a str),
+}
+
+/// Concept files and doc paths under `root` that point at nothing, with what
+/// lists them, concepts first and each sorted by name. Glob patterns are not
+/// checked.
+pub fn dangling_references<'a>(
+    root: &Path,
+    config: &'a ProjectConfig,
+    docs: &'a ProjectDocs,
+) -> Vec<(Referrer<'a>, &'a str)> {
+    let concept_files = config.concepts.iter().flat_map(|(name, concept)| {
+        concept
+            .files
+            .iter()
+            .filter(|file| !file.contains('*'))
+            .filter(|file| !paths::join_relative(root, file).exists())
+            .map(move |file| (Referrer::Concept(name), file.as_str()))
+    });
+    let doc_paths = docs
+        .docs
+        .iter()
+        .filter(|(_, doc)| !paths::join_relative(root, &doc.path).is_file())
+        .map(|(topic, doc)| (Referrer::Doc(topic), doc.path.as_str()));
+
+    let mut dangling: Vec<_> = concept_files.chain(doc_paths).collect();
+    dangling.sort();
+    dangling
+}
+
+/// Render a project's checklist as markdown.
+pub fn format_health(name: &str, health: &ProjectHealth) -> String {
+    let mut output = format!("## {}\n\n**Score:** {}/100\n\n", name, health.score());
+    for check in &health.checks {
+        let mark = if check.passed { "x" } else { " " };
+        output.push_str(&format!("- [{}] {}", mark, check.label));
+        if let Some(detail) = &check.detail {
+            output.push_str(&format!(" ({})", detail));
+        }
+        output.push('\n');
+    }
+    for file in &health.dangling {
+        output.push_str(&format!("  - missing: `{}`\n", file));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Concept, DocEntry};
+    use tempfile::TempDir;
+
+    fn config(toml: &str) -> ProjectConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_empty_project_scores_low() {
+        let temp = TempDir::new().unwrap();
+        let config = config("[project]\nname = \"bare\"\ndescription = \"Bare\"\n");
+        let health = assess(
+            temp.path(),
+            &config,
+            &ProjectConventions::default(),
+            &ProjectDocs::default(),
+        );
+        // Description and "no dangling files" pass; the rest fail.
+        assert_eq!(health.score(), 33);
+        let text = format_health("bare", &health);
+        assert!(text.contains("**Score:** 33/100"));
+        assert!(text.contains("- [ ] Defines commands"));
+        assert!(text.contains("- [x] Has a description"));
+    }
+
+    #[test]
+    fn test_complete_project_scores_full_and_flags_dangling_files() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(temp.path().join("README.md"), "").unwrap();
+
+        let mut config = config(
+            "[project]\nname = \"full\"\ndescription = \"Full\"\n[commands]\ntest = \"cargo test\"\n",
+        );
+        for name in ["a", "b", "c"] {
+            config.concepts.insert(
+                name.to_string(),
+                Concept {
+                    files: vec!["src/lib.rs".to_string(), "src/**/*.rs".to_string()],
+                    summary: name.to_string(),
+                    pinned: false,
+                    prompts: Vec::new(),
+                },
+            );
+        }
+        let mut conventions = ProjectConventions::default();
+        conventions.gotchas.insert("x".to_string(), "y".to_string());
+        let mut docs = ProjectDocs::default();
+        docs.docs.insert(
+            "readme".to_string(),
+            DocEntry {
+                path: "README.md".to_string(),
+                summary: "Readme".to_string(),
+            },
+        );
+
+        let health = assess(temp.path(), &config, &conventions, &docs);
+        assert_eq!(health.score(), 100);
+
+        config.concepts.get_mut("a").unwrap().files = vec!["src/moved.rs".to_string()];
+        let health = assess(temp.path(), &config, &conventions, &docs);
+        assert_eq!(health.dangling, vec!["src/moved.rs"]);
+        assert_eq!(health.score(), 83);
+    }
+}
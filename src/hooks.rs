@@ -0,0 +1,343 @@
+//! Git hooks configured through pre-commit, husky or lefthook, for
+//! `get_hooks`.
+//!
+//! Only configuration files are read; nothing is run. Hooks that look like
+//! they rewrite files (formatters, `--fix`) are flagged, since those change
+//! what an agent staged.
+
+use serde_yaml::Value;
+use std::path::Path;
+
+use crate::text;
+
+/// One check run by a hook manager.
+#[derive(Debug, PartialEq)]
+pub struct Hook {
+    /// Git hook name, e.g. `pre-commit` or `pre-push`.
+    pub stage: String,
+    pub name: String,
+    pub command: Option<String>,
+    /// Whether it appears to modify files rather than only check them.
+    pub fixes: bool,
+}
+
+/// A hook manager's configuration and the hooks it declares.
+#[derive(Debug, PartialEq)]
+pub struct HookConfig {
+    pub tool: &'static str,
+    /// Config file or directory, relative to the root it was found in.
+    pub source: String,
+    pub hooks: Vec<Hook>,
+}
+
+const PRE_COMMIT_CONFIG: &str = ".pre-commit-config.yaml";
+const LEFTHOOK_CONFIGS: &[&str] = &["lefthook.yml", ".lefthook.yml", "lefthook.yaml"];
+const LINT_STAGED_CONFIGS: &[&str] = &[".lintstagedrc", ".lintstagedrc.json", ".lintstagedrc.yaml"];
+
+/// Git hooks husky and lefthook may define.
+const GIT_HOOKS: &[&str] = &[
+    "pre-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "pre-merge-commit",
+    "pre-rebase",
+    "post-checkout",
+    "post-merge",
+    "pre-push",
+];
+
+/// Words suggesting a command rewrites files.
+const FIXING_WORDS: &[&str] = &[
+    "--fix",
+    "--write",
+    "fmt",
+    "format",
+    "prettier",
+    "black",
+    "isort",
+    "autopep8",
+    "end-of-file-fixer",
+    "trailing-whitespace",
+    "mixed-line-ending",
+];
+
+/// Hook configurations in `root`.
+pub fn detect(root: &Path) -> Vec<HookConfig> {
+    let mut configs = Vec::new();
+    if let Some(config) = pre_commit(root) {
+        configs.push(config);
+    }
+    if let Some(config) = husky(root) {
+        configs.push(config);
+    }
+    if let Some(config) = lefthook(root) {
+        configs.push(config);
+    }
+    configs
+}
+
+fn read_yaml(path: &Path) -> Option<Value> {
+    serde_yaml::from_str(&text::read_text(path).ok()?).ok()
+}
+
+fn fixes(text: &str) -> bool {
+    let text = text.to_lowercase();
+    !text.contains("--check")
+        && !text.contains("--diff")
+        && FIXING_WORDS.iter().any(|word| text.contains(word))
+}
+
+/// pre-commit's legacy stage names mapped to git hook names.
+fn stage_name(stage: &str) -> String {
+    match stage {
+        "commit" => "pre-commit",
+        "push" => "pre-push",
+        "merge-commit" => "pre-merge-commit",
+        other => other,
+    }
+    .to_string()
+}
+
+fn strings(value: Option<&Value>) -> Option<Vec<String>> {
+    value.and_then(|v| v.as_sequence()).map(|items| {
+        items
+            .iter()
+            .filter_map(|item| item.as_str().map(str::to_string))
+            .collect()
+    })
+}
+
+fn pre_commit(root: &Path) -> Option<HookConfig> {
+    let config = read_yaml(&root.join(PRE_COMMIT_CONFIG))?;
+    let default_stages =
+        strings(config.get("default_stages")).unwrap_or_else(|| vec!["pre-commit".to_string()]);
+
+    let mut hooks = Vec::new();
+    for repo in config.get("repos")?.as_sequence()? {
+        for hook in repo
+            .get("hooks")
+            .and_then(|h| h.as_sequence())
+            .into_iter()
+            .flatten()
+        {
+            let Some(id) = hook.get("id").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let command = hook
+                .get("entry")
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+            let args = strings(hook.get("args")).unwrap_or_default().join(" ");
+            let fixes = fixes(&format!(
+                "{} {} {}",
+                id,
+                command.as_deref().unwrap_or(""),
+                args
+            ));
+            for stage in strings(hook.get("stages")).unwrap_or_else(|| default_stages.clone()) {
+                hooks.push(Hook {
+                    stage: stage_name(&stage),
+                    name: id.to_string(),
+                    command: command.clone(),
+                    fixes,
+                });
+            }
+        }
+    }
+    Some(HookConfig {
+        tool: "pre-commit",
+        source: PRE_COMMIT_CONFIG.to_string(),
+        hooks,
+    })
+}
+
+fn husky(root: &Path) -> Option<HookConfig> {
+    let dir = root.join(".husky");
+    if !dir.is_dir() {
+        return None;
+    }
+    let mut hooks = Vec::new();
+    for stage in GIT_HOOKS {
+        let Ok(script) = text::read_text(&dir.join(stage)) else {
+            continue;
+        };
+        for line in script.lines().map(str::trim) {
+            // Skip the shebang, comments and husky's own loader line.
+            if line.is_empty() || line.starts_with('#') || line.contains("husky.sh") {
+                continue;
+            }
+            hooks.extend(command_hooks(root, stage, line, line));
+        }
+    }
+    Some(HookConfig {
+        tool: "husky",
+        source: ".husky/".to_string(),
+        hooks,
+    })
+}
+
+fn lefthook(root: &Path) -> Option<HookConfig> {
+    let (file, config) = LEFTHOOK_CONFIGS
+        .iter()
+        .find_map(|file| Some((*file, read_yaml(&root.join(file))?)))?;
+
+    let mut hooks = Vec::new();
+    for stage in GIT_HOOKS {
+        let Some(hook) = config.get(*stage) else {
+            continue;
+        };
+        if let Some(commands) = hook.get("commands").and_then(|c| c.as_mapping()) {
+            for (name, command) in commands {
+                let name = name.as_str().unwrap_or_default();
+                if let Some(run) = command.get("run").and_then(|r| r.as_str()) {
+                    hooks.extend(command_hooks(root, stage, name, run));
+                }
+            }
+        }
+        // Lefthook 1.10+ lists `jobs` instead of named `commands`.
+        for job in hook
+            .get("jobs")
+            .and_then(|j| j.as_sequence())
+            .into_iter()
+            .flatten()
+        {
+            if let Some(run) = job.get("run").and_then(|r| r.as_str()) {
+                let name = job.get("name").and_then(|n| n.as_str()).unwrap_or(run);
+                hooks.extend(command_hooks(root, stage, name, run));
+            }
+        }
+    }
+    Some(HookConfig {
+        tool: "lefthook",
+        source: file.to_string(),
+        hooks,
+    })
+}
+
+/// The hook for `command`, expanded into the lint-staged tasks it runs when
+/// it invokes lint-staged.
+fn command_hooks(root: &Path, stage: &str, name: &str, command: &str) -> Vec<Hook> {
+    if command.contains("lint-staged") {
+        if let Some(tasks) = lint_staged(root) {
+            return tasks
+                .into_iter()
+                .map(|(glob, commands)| Hook {
+                    stage: stage.to_string(),
+                    fixes: fixes(&commands),
+                    name: format!("lint-staged {}", glob),
+                    command: Some(commands),
+                })
+                .collect();
+        }
+    }
+    vec![Hook {
+        stage: stage.to_string(),
+        name: name.to_string(),
+        command: Some(command.to_string()),
+        fixes: fixes(command),
+    }]
+}
+
+/// `(glob, commands)` from lint-staged's config in package.json or an
+/// rc file.
+fn lint_staged(root: &Path) -> Option<Vec<(String, String)>> {
+    let config = LINT_STAGED_CONFIGS
+        .iter()
+        .find_map(|file| read_yaml(&root.join(file)))
+        .or_else(|| {
+            read_yaml(&root.join("package.json"))?
+                .get("lint-staged")
+                .cloned()
+        })?;
+    let tasks = config
+        .as_mapping()?
+        .iter()
+        .filter_map(|(glob, commands)| {
+            let commands = match commands {
+                Value::String(command) => command.clone(),
+                Value::Sequence(_) => strings(Some(commands))?.join(" && "),
+                _ => return None,
+            };
+            Some((glob.as_str()?.to_string(), commands))
+        })
+        .collect();
+    Some(tasks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_pre_commit_hooks_and_stages() {
+        let temp = TempDir::new().unwrap();
+        fs::write(
+            temp.path().join(PRE_COMMIT_CONFIG),
+            "repos:\n  - repo: https://github.com/psf/black\n    rev: 24.1.0\n    hooks:\n      - id: black\n  - repo: local\n    hooks:\n      - id: pytest\n        entry: pytest -q\n        stages: [push]\n      - id: ruff\n        entry: ruff check\n        args: [--fix]\n",
+        )
+        .unwrap();
+
+        let configs = detect(temp.path());
+        assert_eq!(configs.len(), 1);
+        let hooks: Vec<(&str, &str, bool)> = configs[0]
+            .hooks
+            .iter()
+            .map(|h| (h.stage.as_str(), h.name.as_str(), h.fixes))
+            .collect();
+        assert_eq!(
+            hooks,
+            [
+                ("pre-commit", "black", true),
+                ("pre-push", "pytest", false),
+                ("pre-commit", "ruff", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_husky_with_lint_staged_and_lefthook() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join(".husky/_")).unwrap();
+        fs::write(
+            temp.path().join(".husky/pre-commit"),
+            "#!/usr/bin/env sh\n. \"$(dirname -- \"$0\")/_/husky.sh\"\n\nnpx lint-staged\n",
+        )
+        .unwrap();
+        fs::write(temp.path().join(".husky/pre-push"), "npm test\n").unwrap();
+        fs::write(
+            temp.path().join("package.json"),
+            r#"{"lint-staged": {"*.ts": ["eslint --fix", "prettier --write"]}}"#,
+        )
+        .unwrap();
+        fs::write(
+            temp.path().join("lefthook.yml"),
+            "pre-push:\n  commands:\n    audit:\n      run: npm audit\n",
+        )
+        .unwrap();
+
+        let configs = detect(temp.path());
+        assert_eq!(configs[0].tool, "husky");
+        assert_eq!(
+            configs[0].hooks,
+            vec![
+                Hook {
+                    stage: "pre-commit".to_string(),
+                    name: "lint-staged *.ts".to_string(),
+                    command: Some("eslint --fix && prettier --write".to_string()),
+                    fixes: true,
+                },
+                Hook {
+                    stage: "pre-push".to_string(),
+                    name: "npm test".to_string(),
+                    command: Some("npm test".to_string()),
+                    fixes: false,
+                },
+            ]
+        );
+        assert_eq!(configs[1].tool, "lefthook");
+        assert_eq!(configs[1].hooks[0].name, "audit");
+    }
+}
@@ -0,0 +1,904 @@
+//! `jumble server --http <addr>`: the MCP streamable HTTP transport, so
+//! remote agents and web-based IDEs can share one jumble instance.
+//!
+//! Clients POST JSON-RPC messages to `/mcp`. A request is answered with its
+//! response as `application/json`; when the client accepts
+//! `text/event-stream` and the call queued notifications (such as log
+//! messages), the answer is a short SSE stream carrying those and then the
+//! response. Notifications and client responses get `202 Accepted`.
+//!
+//! `initialize` starts a session whose id comes back in `Mcp-Session-Id`;
+//! later requests must send it, and `DELETE /mcp` ends it (as does half an
+//! hour without requests). Each session is a [`Server::new_session`], so
+//! clients don't share subscriptions or tool history, but the workspace is
+//! discovered once and a reload reaches all. `--max-requests-per-second` and
+//! the reload throttle count every session's calls together. At most
+//! [`MAX_CONNECTIONS`] connections are served at once and
+//! [`MAX_SESSIONS`] sessions kept open; beyond either, clients get `503`.
+//! There is no standalone GET stream, so GET answers 405.
+//!
+//! With `--sse`, the older HTTP+SSE transport is served too, for clients
+//! that predate streamable HTTP: `GET /sse` opens an event stream whose first
+//! `endpoint` event names `/messages?sessionId=<id>`. Messages POSTed there
+//! get `202 Accepted`, and their responses and notifications are sent as
+//! `message` events on the stream. The session ends when the stream closes.
+//!
+//! Requests with an `Origin` header are refused unless it is a loopback
+//! origin or was passed with `--allow-origin`, which guards against DNS
+//! rebinding from web pages. With `--auth-token`, every request must also
+//! carry the token as `Authorization: Bearer <token>` (or, for browsers that
+//! can't set headers on `EventSource`, an `access_token` query parameter);
+//! others get `401` with a JSON-RPC error.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::limits::{self, LineRead};
+use crate::protocol::{self, IncomingMessage, JsonRpcError, JsonRpcResponse};
+use crate::server::Server;
+
+/// The endpoint clients talk to.
+pub const MCP_PATH: &str = "/mcp";
+
+/// The legacy SSE transport's event stream and message endpoints.
+pub const SSE_PATH: &str = "/sse";
+pub const MESSAGES_PATH: &str = "/messages";
+
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Seconds between keep-alive comments on an idle event stream.
+const KEEPALIVE_SECS: u64 = 15;
+
+/// Seconds to wait for a slow client to send its request.
+const READ_TIMEOUT_SECS: u64 = 30;
+
+/// Seconds a Streamable HTTP session may go unused before it is dropped.
+const SESSION_IDLE_SECS: u64 = 30 * 60;
+
+/// Longest request line or header line accepted.
+const MAX_HEAD_LINE_BYTES: usize = 8 * 1024;
+
+/// Most header lines accepted in one request.
+const MAX_HEADERS: usize = 100;
+
+/// Connections served at once; more are answered `503` and closed.
+const MAX_CONNECTIONS: usize = 256;
+
+/// Streamable HTTP and legacy SSE sessions open at once; a client starting
+/// another gets `503`.
+const MAX_SESSIONS: usize = 1000;
+
+/// One HTTP request, with header names lowercased.
+#[derive(Debug, Default)]
+pub struct HttpRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct HttpResponse {
+    pub status: &'static str,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    fn new(status: &'static str, content_type: &str, body: String) -> Self {
+        Self {
+            status,
+            headers: vec![("Content-Type", content_type.to_string())],
+            body,
+        }
+    }
+
+    pub fn text(status: &'static str, body: &str) -> Self {
+        Self::new(status, "text/plain", format!("{}\n", body))
+    }
+
+    fn json(status: &'static str, response: &JsonRpcResponse) -> Self {
+        Self::new(
+            status,
+            "application/json",
+            serde_json::to_string(response).unwrap_or_default(),
+        )
+    }
+
+    pub fn write_to(&self, stream: &mut impl Write) -> io::Result<()> {
+        write!(stream, "HTTP/1.1 {}\r\n", self.status)?;
+        for (name, value) in &self.headers {
+            write!(stream, "{}: {}\r\n", name, value)?;
+        }
+        write!(
+            stream,
+            "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.body.len(),
+            self.body
+        )?;
+        stream.flush()
+    }
+}
+
+/// One SSE event carrying a JSON-RPC message.
+fn message_event(message: &impl serde::Serialize) -> String {
+    format!(
+        "event: message\ndata: {}\n\n",
+        serde_json::to_string(message).unwrap_or_default()
+    )
+}
+
+/// The value of `name` in the query string of `path`.
+fn query_param<'a>(path: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = path.split_once('?')?;
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+/// Whether `origin` is a page served from this machine.
+fn is_loopback_origin(origin: &str) -> bool {
+    let host = origin
+        .split_once("://")
+        .map_or(origin, |(_, rest)| rest)
+        .split('/')
+        .next()
+        .unwrap_or("");
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    matches!(host, "localhost" | "127.0.0.1" | "::1")
+}
+
+/// A Streamable HTTP session: its server and when a request last used it.
+struct HttpSession {
+    server: Arc<Server>,
+    last_used: Instant,
+}
+
+/// A legacy SSE session: its server and the stream its messages go out on.
+struct StreamSession {
+    server: Arc<Server>,
+    events: Sender<String>,
+}
+
+/// Whether `request` has no `Origin` header, a loopback one or one in
+/// `allowed`.
+pub fn origin_allowed(request: &HttpRequest, allowed: &[String]) -> bool {
+    request
+        .header("origin")
+        .is_none_or(|origin| is_loopback_origin(origin) || allowed.iter().any(|o| o == origin))
+}
+
+/// Compare secrets without revealing through timing where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Check that `request` carries `token`, when one is required, as a bearer
+/// token or an `access_token` query parameter. Refusals are a `401` whose
+/// body is a JSON-RPC error, so MCP clients can show why.
+pub fn authorize(request: &HttpRequest, token: Option<&str>) -> Result<(), HttpResponse> {
+    let Some(token) = token else {
+        return Ok(());
+    };
+    let presented = request
+        .header("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or_else(|| query_param(&request.path, "access_token"));
+    if presented.is_some_and(|p| constant_time_eq(p.trim().as_bytes(), token.as_bytes())) {
+        return Ok(());
+    }
+    let mut response = HttpResponse::json(
+        "401 Unauthorized",
+        &JsonRpcResponse::error(
+            Some(serde_json::Value::Null),
+            JsonRpcError {
+                code: protocol::UNAUTHORIZED,
+                message: "Unauthorized: missing or invalid bearer token".to_string(),
+                data: None,
+            },
+        ),
+    );
+    response
+        .headers
+        .push(("WWW-Authenticate", "Bearer".to_string()));
+    Err(response)
+}
+
+/// Sessions of the HTTP transport, each a [`Server::new_session`] of one
+/// discovered workspace.
+pub struct HttpTransport {
+    server: Server,
+    allowed_origins: Vec<String>,
+    /// Token every request must present, if set.
+    auth_token: Option<String>,
+    sessions: Mutex<HashMap<String, HttpSession>>,
+    /// Sessions unused for this long are dropped.
+    idle_timeout: Duration,
+    /// Most sessions of either transport open at once.
+    max_sessions: usize,
+    /// Serve the legacy SSE transport as well.
+    legacy_sse: bool,
+    streams: Mutex<HashMap<String, StreamSession>>,
+}
+
+impl HttpTransport {
+    pub fn new(server: Server, allowed_origins: Vec<String>) -> Self {
+        Self {
+            server,
+            allowed_origins,
+            auth_token: None,
+            sessions: Mutex::default(),
+            idle_timeout: Duration::from_secs(SESSION_IDLE_SECS),
+            max_sessions: MAX_SESSIONS,
+            legacy_sse: false,
+            streams: Mutex::default(),
+        }
+    }
+
+    /// Also serve the legacy SSE transport on [`SSE_PATH`] and
+    /// [`MESSAGES_PATH`].
+    pub fn with_legacy_sse(mut self, enabled: bool) -> Self {
+        self.legacy_sse = enabled;
+        self
+    }
+
+    /// Require clients to present `token` (see [`authorize`]).
+    pub fn with_auth_token(mut self, token: Option<String>) -> Self {
+        self.auth_token = token;
+        self
+    }
+
+    fn streams(&self) -> std::sync::MutexGuard<'_, HashMap<String, StreamSession>> {
+        self.streams.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// The live sessions, after dropping those idle for longer than
+    /// `idle_timeout`. Clients that go away without `DELETE` leave theirs
+    /// behind, so this runs on every use.
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, HttpSession>> {
+        let mut sessions = self.sessions.lock().unwrap_or_else(PoisonError::into_inner);
+        sessions.retain(|_, session| session.last_used.elapsed() < self.idle_timeout);
+        sessions
+    }
+
+    /// A `503` when `max_sessions` are open, so clients can't grow the
+    /// session tables without bound.
+    fn check_session_capacity(&self) -> Result<(), HttpResponse> {
+        let open = self.sessions().len() + self.streams().len();
+        if open >= self.max_sessions {
+            return Err(HttpResponse::text(
+                "503 Service Unavailable",
+                "Too many sessions",
+            ));
+        }
+        Ok(())
+    }
+
+    /// An unguessable session id: 128 bits from the OS's random source.
+    fn new_session_id() -> io::Result<String> {
+        let mut bytes = [0u8; 16];
+        getrandom::fill(&mut bytes).map_err(|e| io::Error::other(e.to_string()))?;
+        Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+
+    /// A `403` for requests from origins that may not use the server, or a
+    /// `401` for ones without the auth token.
+    fn check_access(&self, request: &HttpRequest) -> Result<(), HttpResponse> {
+        if !origin_allowed(request, &self.allowed_origins) {
+            return Err(HttpResponse::text("403 Forbidden", "Origin not allowed"));
+        }
+        authorize(request, self.auth_token.as_deref())
+    }
+
+    /// Whether `request` opens a legacy SSE event stream, to be answered with
+    /// [`open_event_stream`](Self::open_event_stream) rather than `handle`.
+    pub fn is_event_stream_request(&self, request: &HttpRequest) -> bool {
+        self.legacy_sse
+            && request.method == "GET"
+            && request.path.split('?').next() == Some(SSE_PATH)
+    }
+
+    /// Start a legacy SSE session, returning its id and the events to send.
+    pub fn open_event_stream(
+        &self,
+        request: &HttpRequest,
+    ) -> Result<(String, Receiver<String>), HttpResponse> {
+        self.check_access(request)?;
+        self.check_session_capacity()?;
+        let id = Self::new_session_id().map_err(|e| {
+            HttpResponse::text(
+                "500 Internal Server Error",
+                &format!("Failed to start a session: {}", e),
+            )
+        })?;
+        let server = self.server.new_session();
+        let (events, receiver) = mpsc::channel();
+        let progress = events.clone();
+        server.set_notifier(Box::new(move |message| {
+            let _ = progress.send(message_event(message));
+        }));
+        let _ = events.send(format!(
+            "event: endpoint\ndata: {}?sessionId={}\n\n",
+            MESSAGES_PATH, id
+        ));
+        self.streams().insert(
+            id.clone(),
+            StreamSession {
+                server: Arc::new(server),
+                events,
+            },
+        );
+        Ok((id, receiver))
+    }
+
+    /// Forget a legacy SSE session whose stream has closed.
+    pub fn close_event_stream(&self, id: &str) {
+        self.streams().remove(id);
+    }
+
+    /// Answer one request.
+    pub fn handle(&self, request: &HttpRequest) -> HttpResponse {
+        let path = request.path.split('?').next();
+        let legacy = self.legacy_sse && path == Some(MESSAGES_PATH);
+        if path != Some(MCP_PATH) && !legacy {
+            return HttpResponse::text("404 Not Found", "Not found");
+        }
+        if let Err(response) = self.check_access(request) {
+            return response;
+        }
+        if legacy {
+            return match request.method.as_str() {
+                "POST" => self.handle_legacy_post(request),
+                _ => {
+                    let mut response =
+                        HttpResponse::text("405 Method Not Allowed", "Method not allowed");
+                    response.headers.push(("Allow", "POST".to_string()));
+                    response
+                }
+            };
+        }
+        match request.method.as_str() {
+            "POST" => self.handle_post(request),
+            "DELETE" => match request.header(SESSION_HEADER) {
+                Some(id) if self.sessions().remove(id).is_some() => {
+                    HttpResponse::text("200 OK", "Session ended")
+                }
+                Some(_) => HttpResponse::text("404 Not Found", "Unknown session"),
+                None => HttpResponse::text("400 Bad Request", "Missing Mcp-Session-Id header"),
+            },
+            _ => {
+                let mut response =
+                    HttpResponse::text("405 Method Not Allowed", "Method not allowed");
+                response.headers.push(("Allow", "POST, DELETE".to_string()));
+                response
+            }
+        }
+    }
+
+    /// The JSON-RPC message in a POST body, or the response refusing it.
+    fn parse_body(&self, request: &HttpRequest) -> Result<IncomingMessage, HttpResponse> {
+        let unknown_id = self
+            .server
+            .options
+            .strict
+            .then_some(serde_json::Value::Null);
+        if request.body.len() > self.server.options.max_request_bytes {
+            return Err(HttpResponse::json(
+                "413 Payload Too Large",
+                &JsonRpcResponse::error(
+                    unknown_id,
+                    JsonRpcError {
+                        code: protocol::REQUEST_TOO_LARGE,
+                        message: "Request too large".to_string(),
+                        data: Some(json!({ "maxBytes": self.server.options.max_request_bytes })),
+                    },
+                ),
+            ));
+        }
+        let parsed = std::str::from_utf8(&request.body)
+            .map_err(|_| JsonRpcError {
+                code: protocol::PARSE_ERROR,
+                message: "Parse error: request is not valid UTF-8".to_string(),
+                data: None,
+            })
+            .and_then(|body| protocol::parse_message(body, self.server.options.strict));
+        parsed.map_err(|error| {
+            HttpResponse::json(
+                "400 Bad Request",
+                &JsonRpcResponse::error(unknown_id, error),
+            )
+        })
+    }
+
+    /// A message for a legacy SSE session: answered on its event stream.
+    fn handle_legacy_post(&self, request: &HttpRequest) -> HttpResponse {
+        let Some(id) = query_param(&request.path, "sessionId") else {
+            return HttpResponse::text("400 Bad Request", "Missing sessionId parameter");
+        };
+        let Some((server, events)) = self
+            .streams()
+            .get(id)
+            .map(|session| (Arc::clone(&session.server), session.events.clone()))
+        else {
+            return HttpResponse::text("404 Not Found", "Unknown session");
+        };
+        let message = match self.parse_body(request) {
+            Ok(message) => message,
+            Err(response) => return response,
+        };
+        let response = server.handle_message(message);
+        let sent = server
+            .take_outgoing()
+            .iter()
+            .map(message_event)
+            .chain(response.as_ref().map(message_event))
+            .all(|event| events.send(event).is_ok());
+        if !sent {
+            self.close_event_stream(id);
+            return HttpResponse::text("404 Not Found", "Unknown session");
+        }
+        HttpResponse::new("202 Accepted", "text/plain", String::new())
+    }
+
+    fn handle_post(&self, request: &HttpRequest) -> HttpResponse {
+        let message = match self.parse_body(request) {
+            Ok(message) => message,
+            Err(response) => return response,
+        };
+
+        let initialize =
+            matches!(&message, IncomingMessage::Request(r) if r.method == "initialize");
+        let (session_id, server) = if initialize {
+            if let Err(response) = self.check_session_capacity() {
+                return response;
+            }
+            match Self::new_session_id() {
+                Ok(id) => (id, Arc::new(self.server.new_session())),
+                Err(e) => {
+                    return HttpResponse::text(
+                        "500 Internal Server Error",
+                        &format!("Failed to start a session: {}", e),
+                    )
+                }
+            }
+        } else {
+            let Some(id) = request.header(SESSION_HEADER) else {
+                return HttpResponse::text("400 Bad Request", "Missing Mcp-Session-Id header");
+            };
+            match self.sessions().get_mut(id) {
+                Some(session) => {
+                    session.last_used = Instant::now();
+                    (id.to_string(), Arc::clone(&session.server))
+                }
+                None => return HttpResponse::text("404 Not Found", "Unknown session"),
+            }
+        };
+
+        let Some(response) = server.handle_message(message) else {
+            return HttpResponse::new("202 Accepted", "text/plain", String::new());
+        };
+        if initialize {
+            if response.error.is_some() {
+                return HttpResponse::json("200 OK", &response);
+            }
+            let session = HttpSession {
+                server: Arc::clone(&server),
+                last_used: Instant::now(),
+            };
+            self.sessions().insert(session_id.clone(), session);
+        }
+
+        let accepts_stream = request
+            .header("accept")
+            .is_some_and(|accept| accept.contains("text/event-stream"));
+        // Without a stream, queued notifications wait for a later request;
+        // the server keeps only the newest `limits::MAX_QUEUED_MESSAGES`.
+        let outgoing = if accepts_stream {
+            server.take_outgoing()
+        } else {
+            Vec::new()
+        };
+        let mut http = if outgoing.is_empty() {
+            HttpResponse::json("200 OK", &response)
+        } else {
+            let mut body: String = outgoing.iter().map(message_event).collect();
+            body.push_str(&message_event(&response));
+            HttpResponse::new("200 OK", "text/event-stream", body)
+        };
+        http.headers.push(("Mcp-Session-Id", session_id));
+        http
+    }
+}
+
+/// Read the request line or a header line, refusing ones over
+/// [`MAX_HEAD_LINE_BYTES`]. The end of the stream reads as an empty line.
+fn read_head_line(reader: &mut impl BufRead, buf: &mut Vec<u8>) -> io::Result<String> {
+    match limits::read_line_limited(reader, buf, MAX_HEAD_LINE_BYTES)? {
+        LineRead::TooLong => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "header line too long",
+        )),
+        LineRead::Eof => Ok(String::new()),
+        LineRead::Line => Ok(String::from_utf8_lossy(buf).into_owned()),
+    }
+}
+
+/// Read one request from `stream`, refusing bodies over `max_body` bytes and
+/// heads with overlong or too many lines.
+pub fn read_request(stream: impl Read, max_body: usize) -> io::Result<HttpRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut buf = Vec::new();
+    let line = read_head_line(&mut reader, &mut buf)?;
+    let mut parts = line.split_whitespace();
+    let mut request = HttpRequest {
+        method: parts.next().unwrap_or("").to_string(),
+        path: parts.next().unwrap_or("").to_string(),
+        ..HttpRequest::default()
+    };
+    loop {
+        let header = read_head_line(&mut reader, &mut buf)?;
+        if header.trim().is_empty() {
+            break;
+        }
+        if request.headers.len() == MAX_HEADERS {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "too many header lines",
+            ));
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            request
+                .headers
+                .push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    let length: usize = request
+        .header("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    // One byte over the limit is enough for `handle` to refuse it.
+    let length = length.min(max_body + 1);
+    request.body = vec![0; length];
+    reader.read_exact(&mut request.body)?;
+    Ok(request)
+}
+
+/// Write `events` to a legacy SSE client until either side closes.
+fn write_event_stream(stream: &mut TcpStream, events: Receiver<String>) -> io::Result<()> {
+    stream.write_all(
+        b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+    )?;
+    loop {
+        match events.recv_timeout(Duration::from_secs(KEEPALIVE_SECS)) {
+            Ok(event) => stream.write_all(event.as_bytes())?,
+            // Writing to a closed connection is how we notice it went away.
+            Err(RecvTimeoutError::Timeout) => stream.write_all(b": keep-alive\n\n")?,
+            Err(RecvTimeoutError::Disconnected) => return Ok(()),
+        }
+        stream.flush()?;
+    }
+}
+
+/// Frees a connection's place under [`MAX_CONNECTIONS`] when its thread ends.
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Serve the transport on `addr` until the process exits.
+pub fn serve(addr: &str, transport: HttpTransport) -> Result<()> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("Failed to listen on {}", addr))?;
+    let local = listener.local_addr()?;
+    eprintln!("Serving MCP over HTTP on http://{}{}", local, MCP_PATH);
+    if transport.legacy_sse {
+        eprintln!(
+            "Serving legacy MCP over SSE on http://{}{}",
+            local, SSE_PATH
+        );
+    }
+    let transport = Arc::new(transport);
+    let connections = Arc::new(AtomicUsize::new(0));
+    for mut stream in listener.incoming().flatten() {
+        if connections.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            connections.fetch_sub(1, Ordering::SeqCst);
+            let busy = HttpResponse::text("503 Service Unavailable", "Too many connections");
+            let _ = busy.write_to(&mut stream);
+            continue;
+        }
+        let transport = Arc::clone(&transport);
+        let connections = Arc::clone(&connections);
+        std::thread::spawn(move || {
+            let _slot = ConnectionSlot(connections);
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(READ_TIMEOUT_SECS)));
+            let response = match read_request(&stream, transport.server.options.max_request_bytes) {
+                Ok(request) if transport.is_event_stream_request(&request) => {
+                    match transport.open_event_stream(&request) {
+                        Ok((id, events)) => {
+                            let _ = write_event_stream(&mut stream, events);
+                            transport.close_event_stream(&id);
+                            return;
+                        }
+                        Err(response) => response,
+                    }
+                }
+                Ok(request) => transport.handle(&request),
+                Err(_) => HttpResponse::text("400 Bad Request", "Malformed request"),
+            };
+            let _ = response.write_to(&mut stream);
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::ServerOptions;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn test_workspace(temp: &TempDir) -> Server {
+        Server::with_options(temp.path().to_path_buf(), ServerOptions::default()).unwrap()
+    }
+
+    fn post(body: &str, session: Option<&str>, accept: &str) -> HttpRequest {
+        let mut headers = vec![("accept".to_string(), accept.to_string())];
+        if let Some(session) = session {
+            headers.push((SESSION_HEADER.to_string(), session.to_string()));
+        }
+        HttpRequest {
+            method: "POST".to_string(),
+            path: MCP_PATH.to_string(),
+            headers,
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn session_id(response: &HttpResponse) -> Option<String> {
+        response
+            .headers
+            .iter()
+            .find(|(name, _)| *name == "Mcp-Session-Id")
+            .map(|(_, value)| value.clone())
+    }
+
+    #[test]
+    fn test_sessions_over_http() {
+        let temp = TempDir::new().unwrap();
+        let jumble = temp.path().join("app/.jumble");
+        fs::create_dir_all(&jumble).unwrap();
+        fs::write(
+            jumble.join("project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"App\"\n",
+        )
+        .unwrap();
+        let transport = HttpTransport::new(test_workspace(&temp), vec![]);
+
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{}}}"#;
+        let response = transport.handle(&post(init, None, "application/json"));
+        assert_eq!(response.status, "200 OK");
+        assert!(response.body.contains("\"protocolVersion\":\"2025-06-18\""));
+        let id = session_id(&response).unwrap();
+
+        let initialized = r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#;
+        let response = transport.handle(&post(initialized, Some(&id), "application/json"));
+        assert!(response.status == "202 Accepted" || response.status == "200 OK");
+
+        let list = r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"list_projects","arguments":{}}}"#;
+        let response = transport.handle(&post(
+            list,
+            Some(&id),
+            "application/json, text/event-stream",
+        ));
+        assert_eq!(response.status, "200 OK");
+        assert!(response.body.contains("**app**"));
+
+        let response = transport.handle(&post(list, None, "application/json"));
+        assert_eq!(response.status, "400 Bad Request");
+        let response = transport.handle(&post(list, Some("nope"), "application/json"));
+        assert_eq!(response.status, "404 Not Found");
+
+        // Sessions are independent, but share the discovered workspace.
+        let other = transport.handle(&post(init, None, "application/json"));
+        let other = session_id(&other).unwrap();
+        assert_ne!(other, id);
+        fs::write(
+            jumble.join("project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"Renamed\"\n",
+        )
+        .unwrap();
+        let reload = r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"reload_workspace","arguments":{}}}"#;
+        transport.handle(&post(reload, Some(&other), "application/json"));
+        let response = transport.handle(&post(list, Some(&id), "application/json"));
+        assert!(response.body.contains("Renamed"));
+
+        let delete = HttpRequest {
+            method: "DELETE".to_string(),
+            path: MCP_PATH.to_string(),
+            headers: vec![(SESSION_HEADER.to_string(), id.clone())],
+            body: Vec::new(),
+        };
+        assert_eq!(transport.handle(&delete).status, "200 OK");
+        let response = transport.handle(&post(list, Some(&id), "application/json"));
+        assert_eq!(response.status, "404 Not Found");
+    }
+
+    #[test]
+    fn test_idle_sessions_expire() {
+        let temp = TempDir::new().unwrap();
+        let mut transport = HttpTransport::new(test_workspace(&temp), vec![]);
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{}}}"#;
+        let ping = r#"{"jsonrpc":"2.0","id":2,"method":"ping"}"#;
+        let id = session_id(&transport.handle(&post(init, None, "application/json"))).unwrap();
+        assert_eq!(id.len(), 32);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+        let response = transport.handle(&post(ping, Some(&id), "application/json"));
+        assert_eq!(response.status, "200 OK");
+
+        transport.idle_timeout = Duration::ZERO;
+        let response = transport.handle(&post(ping, Some(&id), "application/json"));
+        assert_eq!(response.status, "404 Not Found");
+        assert!(transport.sessions().is_empty());
+    }
+
+    #[test]
+    fn test_session_count_is_capped() {
+        let temp = TempDir::new().unwrap();
+        let mut transport = HttpTransport::new(test_workspace(&temp), vec![]).with_legacy_sse(true);
+        transport.max_sessions = 1;
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{}}}"#;
+        let id = session_id(&transport.handle(&post(init, None, "application/json"))).unwrap();
+
+        let response = transport.handle(&post(init, None, "application/json"));
+        assert_eq!(response.status, "503 Service Unavailable");
+        let get = HttpRequest {
+            method: "GET".to_string(),
+            path: SSE_PATH.to_string(),
+            ..HttpRequest::default()
+        };
+        assert_eq!(
+            transport.open_event_stream(&get).unwrap_err().status,
+            "503 Service Unavailable"
+        );
+
+        let delete = HttpRequest {
+            method: "DELETE".to_string(),
+            path: MCP_PATH.to_string(),
+            headers: vec![(SESSION_HEADER.to_string(), id)],
+            body: Vec::new(),
+        };
+        transport.handle(&delete);
+        let response = transport.handle(&post(init, None, "application/json"));
+        assert_eq!(response.status, "200 OK");
+    }
+
+    #[test]
+    fn test_read_request_limits_the_head() {
+        let request = read_request(
+            &b"POST /mcp HTTP/1.1\r\nContent-Length: 2\r\nAccept: */*\r\n\r\n{}"[..],
+            100,
+        )
+        .unwrap();
+        assert_eq!(
+            (request.method.as_str(), request.path.as_str()),
+            ("POST", "/mcp")
+        );
+        assert_eq!(request.header("accept"), Some("*/*"));
+        assert_eq!(request.body, b"{}");
+
+        let long = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(MAX_HEAD_LINE_BYTES));
+        assert!(read_request(long.as_bytes(), 100).is_err());
+        let many = format!(
+            "GET / HTTP/1.1\r\n{}\r\n",
+            "X-A: b\r\n".repeat(MAX_HEADERS + 1)
+        );
+        assert!(read_request(many.as_bytes(), 100).is_err());
+    }
+
+    #[test]
+    fn test_rejects_foreign_origins_and_other_methods() {
+        let temp = TempDir::new().unwrap();
+        let transport = HttpTransport::new(
+            test_workspace(&temp),
+            vec!["https://ide.example.com".to_string()],
+        );
+        let mut request = post("{}", None, "application/json");
+        request
+            .headers
+            .push(("origin".to_string(), "https://evil.example.com".to_string()));
+        assert_eq!(transport.handle(&request).status, "403 Forbidden");
+        request.headers[1].1 = "https://ide.example.com".to_string();
+        assert_eq!(transport.handle(&request).status, "400 Bad Request");
+
+        assert!(is_loopback_origin("http://localhost:3000"));
+        assert!(is_loopback_origin("http://[::1]:8080"));
+        assert!(!is_loopback_origin("http://localhost.example.com"));
+
+        let get = HttpRequest {
+            method: "GET".to_string(),
+            path: MCP_PATH.to_string(),
+            ..HttpRequest::default()
+        };
+        assert_eq!(transport.handle(&get).status, "405 Method Not Allowed");
+    }
+
+    #[test]
+    fn test_requires_the_auth_token() {
+        let temp = TempDir::new().unwrap();
+        let transport = HttpTransport::new(test_workspace(&temp), vec![])
+            .with_auth_token(Some("secret".to_string()));
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{}}}"#;
+        let mut request = post(init, None, "application/json");
+        let response = transport.handle(&request);
+        assert_eq!(response.status, "401 Unauthorized");
+        assert!(response.body.contains("\"code\":-32003"));
+
+        request
+            .headers
+            .push(("authorization".to_string(), "Bearer wrong".to_string()));
+        assert_eq!(transport.handle(&request).status, "401 Unauthorized");
+        request.headers[1].1 = "Bearer secret".to_string();
+        assert_eq!(transport.handle(&request).status, "200 OK");
+    }
+
+    #[test]
+    fn test_legacy_sse_sessions() {
+        let temp = TempDir::new().unwrap();
+        let transport = HttpTransport::new(test_workspace(&temp), vec![]);
+        let get = HttpRequest {
+            method: "GET".to_string(),
+            path: SSE_PATH.to_string(),
+            ..HttpRequest::default()
+        };
+        assert!(!transport.is_event_stream_request(&get));
+
+        let transport = transport.with_legacy_sse(true);
+        assert!(transport.is_event_stream_request(&get));
+        let (id, events) = transport.open_event_stream(&get).unwrap();
+        assert_eq!(
+            events.try_recv().unwrap(),
+            format!("event: endpoint\ndata: /messages?sessionId={}\n\n", id)
+        );
+
+        let mut message = post(
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2024-11-05","capabilities":{}}}"#,
+            None,
+            "application/json",
+        );
+        message.path = format!("{}?sessionId={}", MESSAGES_PATH, id);
+        assert_eq!(transport.handle(&message).status, "202 Accepted");
+        let event = events.try_recv().unwrap();
+        assert!(event.starts_with("event: message\ndata: {\"jsonrpc\":\"2.0\",\"id\":1,\"result\""));
+        assert!(event.contains("\"protocolVersion\":\"2024-11-05\""));
+
+        transport.close_event_stream(&id);
+        assert_eq!(transport.handle(&message).status, "404 Not Found");
+        message.path = MESSAGES_PATH.to_string();
+        assert_eq!(transport.handle(&message).status, "400 Bad Request");
+    }
+}
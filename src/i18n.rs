@@ -0,0 +1,147 @@
+//! Translated headings for tool output.
+//!
+//! Only the headings and labels jumble writes itself are translated, picked
+//! by `output_language` under `[workspace]`. Config keys, tool names and the
+//! text teams write in their `.jumble` files are shown as written.
+
+use crate::config::WorkspaceConfig;
+
+/// Static output strings for one language. `{}` marks where a project or
+/// workspace name goes.
+pub struct Strings {
+    pub conventions_for: &'static str,
+    pub gotchas_for: &'static str,
+    pub workspace_conventions: &'static str,
+    pub workspace_gotchas: &'static str,
+    pub workspace_overview: &'static str,
+    pub root: &'static str,
+    pub projects: &'static str,
+    pub dependencies: &'static str,
+    pub depends_on: &'static str,
+    pub used_by: &'static str,
+}
+
+/// Languages with translations, as primary language subtags.
+pub const SUPPORTED: [&str; 5] = ["en", "de", "es", "fr", "ja"];
+
+const EN: Strings = Strings {
+    conventions_for: "Conventions for '{}'",
+    gotchas_for: "Gotchas for '{}'",
+    workspace_conventions: "{} Conventions",
+    workspace_gotchas: "{} Gotchas",
+    workspace_overview: "Workspace Overview",
+    root: "Root",
+    projects: "Projects",
+    dependencies: "Dependencies",
+    depends_on: "depends on",
+    used_by: "used by",
+};
+
+const DE: Strings = Strings {
+    conventions_for: "Konventionen für '{}'",
+    gotchas_for: "Fallstricke für '{}'",
+    workspace_conventions: "{}: Konventionen",
+    workspace_gotchas: "{}: Fallstricke",
+    workspace_overview: "Workspace-Übersicht",
+    root: "Stammverzeichnis",
+    projects: "Projekte",
+    dependencies: "Abhängigkeiten",
+    depends_on: "hängt ab von",
+    used_by: "verwendet von",
+};
+
+const ES: Strings = Strings {
+    conventions_for: "Convenciones de '{}'",
+    gotchas_for: "Advertencias de '{}'",
+    workspace_conventions: "{}: convenciones",
+    workspace_gotchas: "{}: advertencias",
+    workspace_overview: "Resumen del espacio de trabajo",
+    root: "Raíz",
+    projects: "Proyectos",
+    dependencies: "Dependencias",
+    depends_on: "depende de",
+    used_by: "usado por",
+};
+
+const FR: Strings = Strings {
+    conventions_for: "Conventions de '{}'",
+    gotchas_for: "Pièges de '{}'",
+    workspace_conventions: "{} : conventions",
+    workspace_gotchas: "{} : pièges",
+    workspace_overview: "Vue d'ensemble de l'espace de travail",
+    root: "Racine",
+    projects: "Projets",
+    dependencies: "Dépendances",
+    depends_on: "dépend de",
+    used_by: "utilisé par",
+};
+
+const JA: Strings = Strings {
+    conventions_for: "'{}' の規約",
+    gotchas_for: "'{}' の注意点",
+    workspace_conventions: "{} の規約",
+    workspace_gotchas: "{} の注意点",
+    workspace_overview: "ワークスペース概要",
+    root: "ルート",
+    projects: "プロジェクト",
+    dependencies: "依存関係",
+    depends_on: "依存先",
+    used_by: "利用元",
+};
+
+/// `ja` for `ja`, `ja-JP` or `JA_jp`.
+fn primary_subtag(language: &str) -> String {
+    language
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// Whether `language` has translations.
+pub fn is_supported(language: &str) -> bool {
+    SUPPORTED.contains(&primary_subtag(language).as_str())
+}
+
+/// Strings for a language tag such as `ja` or `pt-BR`. Unknown or missing
+/// languages get English.
+pub fn strings(language: Option<&str>) -> &'static Strings {
+    match primary_subtag(language.unwrap_or_default()).as_str() {
+        "de" => &DE,
+        "es" => &ES,
+        "fr" => &FR,
+        "ja" => &JA,
+        _ => &EN,
+    }
+}
+
+/// Strings for the workspace's `output_language`.
+pub fn for_workspace(workspace: &Option<WorkspaceConfig>) -> &'static Strings {
+    strings(
+        workspace
+            .as_ref()
+            .and_then(|ws| ws.workspace.output_language.as_deref()),
+    )
+}
+
+/// Fill the `{}` in `template` with `name`.
+pub fn fill(template: &str, name: &str) -> String {
+    template.replacen("{}", name, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strings_match_primary_subtag_and_fall_back_to_english() {
+        assert_eq!(strings(Some("ja")).projects, "プロジェクト");
+        assert_eq!(strings(Some("de-AT")).projects, "Projekte");
+        assert_eq!(strings(Some("FR_ca")).projects, "Projets");
+        assert_eq!(strings(Some("pt-BR")).projects, "Projects");
+        assert_eq!(strings(None).projects, "Projects");
+        assert!(is_supported("ja-JP"));
+        assert!(!is_supported("pt-BR"));
+        assert_eq!(fill(JA.conventions_for, "api"), "'api' の規約");
+    }
+}
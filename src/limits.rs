@@ -0,0 +1,162 @@
+//! Input limits that keep a misbehaving client from exhausting the server.
+
+use std::io::{self, BufRead};
+use std::time::{Duration, Instant};
+
+/// Default cap on a single JSON-RPC message, in bytes.
+pub const DEFAULT_MAX_REQUEST_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default number of requests a client may send per second.
+pub const DEFAULT_MAX_REQUESTS_PER_SECOND: u32 = 100;
+
+/// Server-initiated messages kept for a client that isn't collecting them,
+/// such as an HTTP session that never accepts an event stream. The oldest
+/// are dropped beyond this.
+pub const MAX_QUEUED_MESSAGES: usize = 1000;
+
+/// Minimum time between two rediscovery-triggering calls.
+pub const MIN_RELOAD_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Outcome of reading one newline-delimited message.
+#[derive(Debug, PartialEq)]
+pub enum LineRead {
+    /// End of input.
+    Eof,
+    /// A complete line (without the trailing newline) is in the buffer.
+    Line,
+    /// The line exceeded the limit; it was consumed and discarded.
+    TooLong,
+}
+
+/// Read one line into `buf`, never holding more than `max` bytes of it.
+///
+/// An oversized line is drained up to its newline so the next call starts on
+/// the following message.
+pub fn read_line_limited<R: BufRead>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max: usize,
+) -> io::Result<LineRead> {
+    buf.clear();
+    let mut too_long = false;
+    let mut read_any = false;
+
+    loop {
+        let available = reader.fill_buf()?;
+        if available.is_empty() {
+            return Ok(match (read_any, too_long) {
+                (false, _) => LineRead::Eof,
+                (true, true) => LineRead::TooLong,
+                (true, false) => LineRead::Line,
+            });
+        }
+        read_any = true;
+
+        let (chunk, found_newline) = match available.iter().position(|&b| b == b'\n') {
+            Some(i) => (&available[..i], true),
+            None => (available, false),
+        };
+        if !too_long {
+            if buf.len() + chunk.len() > max {
+                too_long = true;
+                buf.clear();
+            } else {
+                buf.extend_from_slice(chunk);
+            }
+        }
+
+        let consumed = chunk.len() + usize::from(found_newline);
+        reader.consume(consumed);
+        if found_newline {
+            if buf.last() == Some(&b'\r') {
+                buf.pop();
+            }
+            return Ok(if too_long {
+                LineRead::TooLong
+            } else {
+                LineRead::Line
+            });
+        }
+    }
+}
+
+/// Token bucket allowing `rate` requests per second with bursts up to `rate`.
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate: u32) -> Self {
+        Self {
+            rate: f64::from(rate),
+            tokens: f64::from(rate),
+            last: Instant::now(),
+        }
+    }
+
+    /// Take a token if one is available.
+    pub fn try_acquire(&mut self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&mut self, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.last = now;
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_line_limited_skips_oversized_lines() {
+        let input = format!("{}\n{{\"ok\":1}}\r\npartial", "x".repeat(100));
+        let mut reader = io::BufReader::with_capacity(16, Cursor::new(input));
+        let mut buf = Vec::new();
+
+        assert_eq!(
+            read_line_limited(&mut reader, &mut buf, 32).unwrap(),
+            LineRead::TooLong
+        );
+        assert!(buf.is_empty());
+
+        assert_eq!(
+            read_line_limited(&mut reader, &mut buf, 32).unwrap(),
+            LineRead::Line
+        );
+        assert_eq!(buf, b"{\"ok\":1}");
+
+        assert_eq!(
+            read_line_limited(&mut reader, &mut buf, 32).unwrap(),
+            LineRead::Line
+        );
+        assert_eq!(buf, b"partial");
+
+        assert_eq!(
+            read_line_limited(&mut reader, &mut buf, 32).unwrap(),
+            LineRead::Eof
+        );
+    }
+
+    #[test]
+    fn test_rate_limiter_refills_over_time() {
+        let mut limiter = RateLimiter::new(2);
+        let start = limiter.last;
+        assert!(limiter.try_acquire_at(start));
+        assert!(limiter.try_acquire_at(start));
+        assert!(!limiter.try_acquire_at(start));
+        assert!(limiter.try_acquire_at(start + Duration::from_millis(500)));
+    }
+}
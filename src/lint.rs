@@ -0,0 +1,685 @@
+//! `jumble lint`: find advice in `.jumble` files that contradicts itself,
+//! and context written against authoring best practices.
+//!
+//! With `--conventions`, conventions from `workspace.toml` and each
+//! project's `conventions.toml` are compared. Each convention is read for a
+//! few well-known topics (indentation, quotes, error handling) and given a
+//! style when it recommends exactly one, so "Use spaces, never tabs" is
+//! `spaces`. Two conventions conflict when they pick different styles for a
+//! topic, or share a key but say different things. Projects are compared with
+//! the workspace, and with each other only when they use the same language;
+//! a Go project using tabs next to a Python one using spaces is expected.
+//!
+//! With `--style`, each project is checked against the rules in `RULES`:
+//! short descriptions, no placeholder text, concepts pointing at files that
+//! exist and commands whose programs can be found. `[lint]` in
+//! `workspace.toml` turns rules off or changes whether they fail the lint.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use crate::config::LintConfig;
+use crate::files;
+use crate::freshness;
+use crate::paths;
+use crate::server::Server;
+
+/// Scope name used for conventions from `workspace.toml`.
+const WORKSPACE: &str = "workspace";
+
+/// Words that negate a style mentioned within the next two words.
+const NEGATIONS: &[&str] = &[
+    "not", "no", "never", "avoid", "dont", "instead", "without", "nor",
+];
+
+struct Topic {
+    name: &'static str,
+    /// A convention is about the topic if its key or text contains one.
+    triggers: &'static [&'static str],
+    /// `(style, phrases recommending it)`.
+    styles: &'static [(&'static str, &'static [&'static str])],
+}
+
+const TOPICS: &[Topic] = &[
+    Topic {
+        name: "indentation",
+        triggers: &[
+            "indent",
+            "indents",
+            "indentation",
+            "indented",
+            "tab",
+            "tabs",
+        ],
+        styles: &[("tabs", &["tab", "tabs"]), ("spaces", &["space", "spaces"])],
+    },
+    Topic {
+        name: "indentation width",
+        triggers: &["indent", "indents", "indentation", "indented"],
+        styles: &[
+            ("2 spaces", &["2 spaces", "2 space", "two spaces"]),
+            ("4 spaces", &["4 spaces", "4 space", "four spaces"]),
+        ],
+    },
+    Topic {
+        name: "quotes",
+        triggers: &["quote", "quotes", "quoted"],
+        styles: &[
+            (
+                "single quotes",
+                &["single quote", "single quotes", "single quoted"],
+            ),
+            (
+                "double quotes",
+                &["double quote", "double quotes", "double quoted"],
+            ),
+        ],
+    },
+    Topic {
+        name: "error handling",
+        triggers: &[
+            "error",
+            "errors",
+            "exception",
+            "exceptions",
+            "panic",
+            "panics",
+            "unwrap",
+        ],
+        styles: &[
+            (
+                "exceptions",
+                &[
+                    "throw",
+                    "throws",
+                    "raise",
+                    "raises",
+                    "exception",
+                    "exceptions",
+                ],
+            ),
+            (
+                "error values",
+                &[
+                    "result",
+                    "results",
+                    "return errors",
+                    "return an error",
+                    "error values",
+                ],
+            ),
+            ("panics", &["panic", "panics", "unwrap"]),
+        ],
+    },
+];
+
+/// A convention and where it came from.
+struct Source {
+    /// `workspace` or the project name.
+    scope: String,
+    language: Option<String>,
+    key: String,
+    words: Vec<String>,
+}
+
+/// Two conventions that disagree.
+#[derive(Debug, PartialEq)]
+pub struct Conflict {
+    /// Topic name, or `None` when the two share a key.
+    pub topic: Option<&'static str>,
+    /// `(scope, key, style)` for each side; style is set for topic conflicts.
+    pub sides: [(String, String, Option<&'static str>); 2],
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let [(scope_a, key_a, style_a), (scope_b, key_b, style_b)] = &self.sides;
+        match (self.topic, style_a, style_b) {
+            (Some(topic), Some(style_a), Some(style_b)) => write!(
+                f,
+                "{}: {} '{}' says {}, {} '{}' says {}",
+                topic, scope_a, key_a, style_a, scope_b, key_b, style_b
+            ),
+            _ => write!(
+                f,
+                "'{}' means different things in {} and {}",
+                key_a, scope_a, scope_b
+            ),
+        }
+    }
+}
+
+/// Conflicting conventions across the workspace at `root`.
+pub fn check_conventions(root: &Path) -> Result<Vec<Conflict>> {
+    let (workspace, projects) = Server::discover_static(root)?;
+
+    let mut sources = Vec::new();
+    if let Some(ws) = &workspace {
+        sources.extend(sorted_sources(WORKSPACE, None, &ws.conventions));
+    }
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort();
+    for name in names {
+        let (_, config, _, conventions, _, _) = &projects[name];
+        let language = config.project.language.as_deref().map(str::to_lowercase);
+        sources.extend(sorted_sources(name, language, &conventions.conventions));
+    }
+
+    let styles: Vec<Vec<Option<&'static str>>> = sources
+        .iter()
+        .map(|source| TOPICS.iter().map(|topic| style(topic, source)).collect())
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (i, a) in sources.iter().enumerate() {
+        for (j, b) in sources.iter().enumerate().skip(i + 1) {
+            if !comparable(a, b) {
+                continue;
+            }
+            let before = conflicts.len();
+            for (t, topic) in TOPICS.iter().enumerate() {
+                if let (Some(style_a), Some(style_b)) = (styles[i][t], styles[j][t]) {
+                    if style_a != style_b {
+                        conflicts.push(Conflict {
+                            topic: Some(topic.name),
+                            sides: [
+                                (a.scope.clone(), a.key.clone(), Some(style_a)),
+                                (b.scope.clone(), b.key.clone(), Some(style_b)),
+                            ],
+                        });
+                    }
+                }
+            }
+            let same_key = a.key.eq_ignore_ascii_case(&b.key);
+            if conflicts.len() == before && same_key && a.words != b.words {
+                conflicts.push(Conflict {
+                    topic: None,
+                    sides: [
+                        (a.scope.clone(), a.key.clone(), None),
+                        (b.scope.clone(), b.key.clone(), None),
+                    ],
+                });
+            }
+        }
+    }
+    Ok(conflicts)
+}
+
+fn sorted_sources(
+    scope: &str,
+    language: Option<String>,
+    conventions: &HashMap<String, String>,
+) -> Vec<Source> {
+    let mut entries: Vec<(&String, &String)> = conventions.iter().collect();
+    entries.sort();
+    entries
+        .into_iter()
+        .map(|(key, text)| Source {
+            scope: scope.to_string(),
+            language: language.clone(),
+            key: key.clone(),
+            words: words(text),
+        })
+        .collect()
+}
+
+/// Workspace conventions apply to every project; projects only overlap
+/// with others of the same language.
+fn comparable(a: &Source, b: &Source) -> bool {
+    if a.scope == b.scope {
+        return false;
+    }
+    if a.scope == WORKSPACE || b.scope == WORKSPACE {
+        return true;
+    }
+    a.language.is_some() && a.language == b.language
+}
+
+/// Lowercase words, with punctuation and apostrophes dropped.
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .replace('\'', "")
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|w| !w.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The one style `source` recommends for `topic`, if any.
+fn style(topic: &Topic, source: &Source) -> Option<&'static str> {
+    let mut words = words(&source.key);
+    words.extend(source.words.iter().cloned());
+    if !topic.triggers.iter().any(|t| words.iter().any(|w| w == t)) {
+        return None;
+    }
+    let recommended: Vec<&'static str> = topic
+        .styles
+        .iter()
+        .filter(|(_, phrases)| phrases.iter().any(|p| recommends(&source.words, p)))
+        .map(|(style, _)| *style)
+        .collect();
+    match recommended.as_slice() {
+        [style] => Some(style),
+        _ => None,
+    }
+}
+
+/// Whether `phrase` appears in `words` without a negation just before it.
+fn recommends(words: &[String], phrase: &str) -> bool {
+    let phrase: Vec<&str> = phrase.split(' ').collect();
+    words
+        .windows(phrase.len())
+        .enumerate()
+        .any(|(start, window)| {
+            window.iter().zip(&phrase).all(|(w, p)| w == p)
+                && !words[start.saturating_sub(2)..start]
+                    .iter()
+                    .any(|w| NEGATIONS.contains(&w.as_str()))
+        })
+}
+
+/// What a style rule does when it finds something.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+/// Style rules and their default levels.
+pub const RULES: &[(&str, Level)] = &[
+    ("description-length", Level::Warn),
+    ("placeholder-text", Level::Deny),
+    ("concept-files", Level::Deny),
+    ("command-executables", Level::Warn),
+];
+
+/// Text left over from a template or a first draft, compared after
+/// lowercasing and trimming trailing punctuation.
+const PLACEHOLDERS: &[&str] = &[
+    "todo",
+    "tbd",
+    "fixme",
+    "xxx",
+    "placeholder",
+    "lorem ipsum",
+    "description",
+    "a brief description of your project",
+    "a brief description of your workspace",
+];
+
+/// A style rule broken by a project's context.
+#[derive(Debug, PartialEq)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub level: Level,
+    /// `workspace` or the project name.
+    pub scope: String,
+    pub message: String,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} [{}]", self.scope, self.message, self.rule)
+    }
+}
+
+/// The level of every rule after `[lint]` is applied.
+fn rule_levels(config: &LintConfig) -> Result<HashMap<&'static str, Level>> {
+    let mut levels: HashMap<&'static str, Level> = RULES.iter().copied().collect();
+    for (names, level) in [
+        (&config.allow, Level::Allow),
+        (&config.warn, Level::Warn),
+        (&config.deny, Level::Deny),
+    ] {
+        for name in names {
+            let Some((rule, _)) = RULES.iter().find(|(rule, _)| rule == name) else {
+                let known: Vec<&str> = RULES.iter().map(|(rule, _)| *rule).collect();
+                anyhow::bail!(
+                    "Unknown lint rule '{}' in workspace.toml [lint]. Known rules: {}",
+                    name,
+                    known.join(", ")
+                );
+            };
+            levels.insert(rule, level);
+        }
+    }
+    Ok(levels)
+}
+
+/// Whether `text` is empty of content: a placeholder, or a note to fill it in
+/// later such as "TODO: describe the API".
+fn is_placeholder(text: &str) -> bool {
+    let text = text.trim().trim_end_matches(['.', ':', '!']).to_lowercase();
+    let first = text
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default();
+    text.chars().all(|c| !c.is_alphanumeric())
+        || PLACEHOLDERS.contains(&text.as_str())
+        || ["todo", "tbd", "fixme", "xxx"].contains(&first)
+}
+
+/// Style findings for the workspace at `root`, allowed rules left out.
+/// Commands are resolved against `path_var`, the value of `PATH`.
+pub fn check_style(root: &Path, path_var: &std::ffi::OsStr) -> Result<Vec<Finding>> {
+    let (workspace, projects) = Server::discover_static(root)?;
+    let lint = workspace
+        .as_ref()
+        .map(|ws| ws.lint.clone())
+        .unwrap_or_default();
+    let levels = rule_levels(&lint)?;
+
+    let mut findings = Vec::new();
+    let mut report = |rule: &'static str, scope: &str, message: String| {
+        let level = levels[rule];
+        if level != Level::Allow {
+            findings.push(Finding {
+                rule,
+                level,
+                scope: scope.to_string(),
+                message,
+            });
+        }
+    };
+
+    if let Some(ws) = &workspace {
+        if let Some(description) = &ws.workspace.description {
+            if is_placeholder(description) {
+                report(
+                    "placeholder-text",
+                    WORKSPACE,
+                    format!("description is a placeholder: '{}'", description),
+                );
+            }
+        }
+        for (kind, entries) in [("convention", &ws.conventions), ("gotcha", &ws.gotchas)] {
+            for (key, text) in sorted(entries) {
+                if is_placeholder(text) {
+                    report(
+                        "placeholder-text",
+                        WORKSPACE,
+                        format!("{} '{}' is a placeholder: '{}'", kind, key, text),
+                    );
+                }
+            }
+        }
+    }
+
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort();
+    for name in names {
+        let (project_dir, config, _, conventions, docs, _) = &projects[name];
+
+        let description = &config.project.description;
+        if is_placeholder(description) {
+            report(
+                "placeholder-text",
+                name,
+                format!("description is a placeholder: '{}'", description),
+            );
+        }
+        let length = description.chars().count();
+        if length > lint.max_description_length {
+            report(
+                "description-length",
+                name,
+                format!(
+                    "description is {} characters, more than {}; move the detail into concepts or docs",
+                    length, lint.max_description_length
+                ),
+            );
+        }
+
+        let mut summaries: Vec<(String, &String)> = Vec::new();
+        for (key, concept) in sorted(&config.concepts) {
+            summaries.push((format!("concept '{}' summary", key), &concept.summary));
+        }
+        for (key, doc) in sorted(&docs.docs) {
+            if !doc.summary.is_empty() {
+                summaries.push((format!("doc '{}' summary", key), &doc.summary));
+            }
+        }
+        for (key, text) in sorted(&conventions.conventions) {
+            summaries.push((format!("convention '{}'", key), text));
+        }
+        for (key, text) in sorted(&conventions.gotchas) {
+            summaries.push((format!("gotcha '{}'", key), text));
+        }
+        for (what, text) in summaries {
+            if is_placeholder(text) {
+                report(
+                    "placeholder-text",
+                    name,
+                    format!("{} is a placeholder: '{}'", what, text),
+                );
+            }
+        }
+
+        // Listed once, and only if a concept uses a glob.
+        let mut listing: Option<Vec<String>> = None;
+        for (key, concept) in sorted(&config.concepts) {
+            let exists = concept.files.iter().any(|file| {
+                if file.contains('*') {
+                    let listing = listing.get_or_insert_with(|| files::list_files(project_dir));
+                    let patterns = [file.clone()];
+                    listing.iter().any(|f| files::matches_globs(&patterns, f))
+                } else {
+                    paths::join_relative(project_dir, file).exists()
+                }
+            });
+            if !exists {
+                let message = if concept.files.is_empty() {
+                    format!("concept '{}' lists no files", key)
+                } else {
+                    format!("concept '{}' lists no file that exists", key)
+                };
+                report("concept-files", name, message);
+            }
+        }
+
+        for (key, command) in sorted(&config.commands) {
+            for program in freshness::programs(command) {
+                if !freshness::resolves(&program, project_dir, path_var) {
+                    report(
+                        "command-executables",
+                        name,
+                        format!(
+                            "command '{}' runs '{}', which is not on PATH or in the project",
+                            key, program
+                        ),
+                    );
+                }
+            }
+        }
+    }
+    Ok(findings)
+}
+
+/// Map entries sorted by key, so findings come out in a stable order.
+fn sorted<V>(map: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<(&String, &V)> = map.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+}
+
+/// `jumble lint [--conventions] [--style]`. With no check selected, every
+/// check runs. Fails when there are conflicts or `deny` findings.
+pub fn run_lint(root: &Path, conventions: bool, style: bool) -> Result<()> {
+    let all = !(conventions || style);
+    let mut problems = 0;
+    if conventions || all {
+        let conflicts = check_conventions(root)?;
+        if conflicts.is_empty() {
+            println!("✓ No conflicting conventions found");
+        }
+        for conflict in &conflicts {
+            println!("✗ {}", conflict);
+        }
+        problems += conflicts.len();
+    }
+    if style || all {
+        let path_var = std::env::var_os("PATH").unwrap_or_default();
+        let findings = check_style(root, &path_var)?;
+        if findings.is_empty() {
+            println!("✓ No style problems found");
+        }
+        for finding in &findings {
+            let marker = if finding.level == Level::Deny {
+                "✗"
+            } else {
+                "⚠"
+            };
+            println!("{} {}", marker, finding);
+        }
+        problems += findings.iter().filter(|f| f.level == Level::Deny).count();
+    }
+
+    if problems > 0 {
+        anyhow::bail!("{} problem(s) found", problems);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn project(root: &Path, name: &str, language: &str, conventions: &str) {
+        let dir = root.join(name).join(".jumble");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("project.toml"),
+            format!(
+                "[project]\nname = \"{}\"\ndescription = \"\"\nlanguage = \"{}\"\n",
+                name, language
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("conventions.toml"), conventions).unwrap();
+    }
+
+    #[test]
+    fn test_check_conventions_finds_contradictions() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join(".jumble")).unwrap();
+        fs::write(
+            root.join(".jumble/workspace.toml"),
+            "[conventions]\nindentation = \"Indent with 4 spaces, never tabs\"\n",
+        )
+        .unwrap();
+        project(
+            root,
+            "api",
+            "rust",
+            "[conventions]\nerrors = \"Return Result from fallible functions; never unwrap\"\nlogging = \"Use tracing\"\n",
+        );
+        project(
+            root,
+            "worker",
+            "rust",
+            "[conventions]\nerrors = \"Panic on invalid state, unwrap is fine\"\nlogging = \"Use log\"\nstyle = \"Indent with tabs\"\n",
+        );
+        project(
+            root,
+            "cli",
+            "go",
+            "[conventions]\nerrors = \"Wrap errors with context and return them\"\n",
+        );
+
+        let messages: Vec<String> = check_conventions(root)
+            .unwrap()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(
+            messages,
+            [
+                "indentation: workspace 'indentation' says spaces, worker 'style' says tabs",
+                "error handling: api 'errors' says error values, worker 'errors' says panics",
+                "'logging' means different things in api and worker",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_style_applies_rules_and_levels() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join(".jumble")).unwrap();
+        fs::write(
+            root.join(".jumble/workspace.toml"),
+            "[lint]\nmax_description_length = 20\ndeny = [\"description-length\"]\nallow = [\"command-executables\"]\n",
+        )
+        .unwrap();
+        let dir = root.join("api/.jumble");
+        fs::create_dir_all(&dir).unwrap();
+        fs::create_dir_all(root.join("api/src")).unwrap();
+        fs::write(root.join("api/src/lib.rs"), "").unwrap();
+        fs::write(
+            dir.join("project.toml"),
+            "[project]\nname = \"api\"\ndescription = \"The public API and everything behind it\"\n\n\
+             [commands]\nbuild = \"no-such-program build\"\n\n\
+             [concepts.core]\nfiles = [\"src/*.rs\"]\nsummary = \"Core types\"\n\n\
+             [concepts.auth]\nfiles = [\"src/auth.rs\"]\nsummary = \"TODO: describe\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("conventions.toml"),
+            "[conventions]\nnaming = \"TBD\"\nerrors = \"Return Result, not panics\"\n",
+        )
+        .unwrap();
+
+        let findings = check_style(root, std::ffi::OsStr::new("")).unwrap();
+        let messages: Vec<String> = findings.iter().map(|f| f.to_string()).collect();
+        assert_eq!(
+            messages,
+            [
+                "api: description is 39 characters, more than 20; move the detail into concepts or docs [description-length]",
+                "api: concept 'auth' summary is a placeholder: 'TODO: describe' [placeholder-text]",
+                "api: convention 'naming' is a placeholder: 'TBD' [placeholder-text]",
+                "api: concept 'auth' lists no file that exists [concept-files]",
+            ]
+        );
+        assert!(findings.iter().all(|f| f.level == Level::Deny));
+
+        fs::write(
+            root.join(".jumble/workspace.toml"),
+            "[lint]\nwarn = [\"no-todos\"]\n",
+        )
+        .unwrap();
+        let err = check_style(root, std::ffi::OsStr::new("")).unwrap_err();
+        assert!(err.to_string().starts_with("Unknown lint rule 'no-todos'"));
+    }
+
+    #[test]
+    fn test_is_placeholder() {
+        assert!(is_placeholder("TODO"));
+        assert!(is_placeholder("  tbd. "));
+        assert!(is_placeholder("FIXME: write this"));
+        assert!(is_placeholder("..."));
+        assert!(is_placeholder("A brief description of your project"));
+        assert!(!is_placeholder("Todoist sync service"));
+        assert!(!is_placeholder("Handles TODO lists"));
+    }
+
+    #[test]
+    fn test_style_needs_a_single_unnegated_recommendation() {
+        let topic = &TOPICS[0];
+        let source = |text: &str| Source {
+            scope: WORKSPACE.to_string(),
+            language: None,
+            key: "formatting".to_string(),
+            words: words(text),
+        };
+        assert_eq!(style(topic, &source("Use tabs, not spaces")), Some("tabs"));
+        assert_eq!(style(topic, &source("Don't use tabs")), None);
+        assert_eq!(style(topic, &source("Spaces around operators")), None);
+        assert_eq!(style(topic, &source("Tabs or spaces are both fine")), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! Machine-specific overrides from `.jumble/local.toml`.
+//!
+//! A `local.toml` next to `project.toml` or `workspace.toml` is laid over
+//! the committed file when it is loaded: tables merge key by key and any
+//! other value, arrays included, replaces the committed one. It is meant to
+//! stay out of version control, for paths, ports and other values that
+//! differ between developers. The dotted keys it set are kept so tool output
+//! can say where a value came from.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+use crate::migrate::{self, ConfigKind};
+use crate::text;
+
+/// File name of the overrides, inside a `.jumble` directory.
+pub const LOCAL_FILE: &str = "local.toml";
+
+/// Load the config at `path` with its sibling `local.toml` applied, and the
+/// keys the overrides set, sorted.
+pub fn load<T: DeserializeOwned>(kind: ConfigKind, path: &Path) -> Result<(T, Vec<String>)> {
+    let (table, overridden) = load_table(kind, path)?;
+    Ok((deserialize(table, path)?, overridden))
+}
+
+/// [`load`] without deserializing, for callers that merge in more tables.
+pub fn load_table(kind: ConfigKind, path: &Path) -> Result<(toml::Table, Vec<String>)> {
+    let mut table: toml::Table = migrate::load(kind, path)?;
+    let local_path = path.with_file_name(LOCAL_FILE);
+    let mut overridden = Vec::new();
+    if local_path.is_file() {
+        let content = text::read_text(&local_path)
+            .with_context(|| format!("Failed to read {}", local_path.display()))?;
+        let mut local: toml::Table = content
+            .parse()
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", local_path.display(), e))?;
+        // The committed file's version decides how it was migrated.
+        local.remove("config_version");
+        overlay(&mut table, local, "", &mut overridden);
+        overridden.sort();
+    }
+    Ok((table, overridden))
+}
+
+/// Deserialize a table loaded from `path` (and its overrides).
+pub fn deserialize<T: DeserializeOwned>(table: toml::Table, path: &Path) -> Result<T> {
+    toml::Value::Table(table).try_into().map_err(|e| {
+        let local_path = path.with_file_name(LOCAL_FILE);
+        if local_path.is_file() {
+            anyhow::anyhow!(
+                "Failed to apply {} to {}: {}",
+                local_path.display(),
+                path.display(),
+                e
+            )
+        } else {
+            anyhow::anyhow!("Failed to parse {}: {}", path.display(), e)
+        }
+    })
+}
+
+/// Lay `over` onto `base` the way `local.toml` is applied.
+pub fn merge(base: &mut toml::Table, over: toml::Table) {
+    overlay(base, over, "", &mut Vec::new());
+}
+
+fn overlay(base: &mut toml::Table, local: toml::Table, prefix: &str, keys: &mut Vec<String>) {
+    for (key, value) in local {
+        let path = format!("{}{}", prefix, key);
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base)), toml::Value::Table(local)) => {
+                overlay(base, local, &format!("{}.", path), keys);
+            }
+            (None, toml::Value::Table(local)) => {
+                let mut table = toml::Table::new();
+                overlay(&mut table, local, &format!("{}.", path), keys);
+                base.insert(key, toml::Value::Table(table));
+            }
+            (_, value) => {
+                base.insert(key, value);
+                keys.push(path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProjectConfig;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_overlays_local_values() {
+        let temp = TempDir::new().unwrap();
+        let path = temp.path().join("project.toml");
+        fs::write(
+            &path,
+            "[project]\nname = \"api\"\ndescription = \"API\"\n\n[commands]\nbuild = \"cargo build\"\ntest = \"cargo test\"\n",
+        )
+        .unwrap();
+
+        let (config, overridden): (ProjectConfig, _) = load(ConfigKind::Project, &path).unwrap();
+        assert_eq!(config.commands["test"], "cargo test");
+        assert!(overridden.is_empty());
+
+        fs::write(
+            temp.path().join(LOCAL_FILE),
+            "config_version = 9\n\n[commands]\ntest = \"cargo nextest run\"\n\n[api]\nbase_url = \"http://localhost:9090\"\n",
+        )
+        .unwrap();
+        let (config, overridden): (ProjectConfig, _) = load(ConfigKind::Project, &path).unwrap();
+        assert_eq!(config.commands["build"], "cargo build");
+        assert_eq!(config.commands["test"], "cargo nextest run");
+        assert_eq!(
+            config.api.unwrap().base_url.as_deref(),
+            Some("http://localhost:9090")
+        );
+        assert_eq!(overridden, ["api.base_url", "commands.test"]);
+
+        fs::write(temp.path().join(LOCAL_FILE), "commands = 3\n").unwrap();
+        let err = load::<ProjectConfig>(ConfigKind::Project, &path).unwrap_err();
+        assert!(err.to_string().contains("local.toml"));
+    }
+}
@@ -1,19 +1,71 @@
+mod authoring;
+mod commands;
+mod completion;
+mod compliance;
 mod config;
+mod dependencies;
+mod diff;
+mod exec;
+mod files;
+mod forge;
 mod format;
+mod framing;
+mod freshness;
+mod generate;
+mod health;
+mod hooks;
+#[cfg(feature = "http")]
+mod http;
+mod i18n;
+mod limits;
+mod lint;
+mod local;
 mod memory;
+mod metrics;
+mod migrate;
+mod owners;
+mod paths;
+mod plugins;
+mod ports;
+mod prompts;
 mod protocol;
+mod proxy;
+mod remote;
+#[cfg(feature = "repl")]
+mod repl;
+mod resources;
 mod server;
 mod setup;
+#[cfg(unix)]
+mod socket;
+mod state;
+mod stats;
+mod stream;
+mod style;
+mod template;
+mod testmap;
+mod text;
 mod tools;
+mod tracker;
+mod transcript;
+#[cfg(feature = "tui")]
+mod tui;
+mod validate;
+mod watch;
+#[cfg(feature = "http")]
+mod ws;
 
 use anyhow::{Context, Result};
 use clap::{Parser, Subcommand};
 use std::env;
-use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use protocol::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
-use server::Server;
+use framing::Framing;
+use server::{Server, ServerOptions};
+use setup::{OutputMode, Reporter};
+use transcript::Recorder;
 
 /// An MCP server that provides queryable, on-demand project context to LLMs
 #[derive(Parser, Debug)]
@@ -25,20 +77,253 @@ struct Args {
     /// Root directory to scan for .jumble/project.toml files (server mode only)
     #[arg(long, env = "JUMBLE_ROOT", global = true)]
     root: Option<PathBuf>,
+
+    /// Name of the per-project config directory, e.g. .context or .ai/jumble, for repositories that can't use .jumble
+    #[arg(long, env = "JUMBLE_CONFIG_DIR", global = true, value_name = "DIR")]
+    config_dir: Option<PathBuf>,
+
+    /// Enforce strict JSON-RPC 2.0 / MCP conformance (server mode only)
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Reject messages larger than this many bytes (server mode only)
+    #[arg(long, global = true, default_value_t = limits::DEFAULT_MAX_REQUEST_BYTES)]
+    max_request_bytes: usize,
+
+    /// Reject requests beyond this many per second (server mode only)
+    #[arg(
+        long,
+        global = true,
+        default_value_t = limits::DEFAULT_MAX_REQUESTS_PER_SECOND,
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    max_requests_per_second: u32,
+
+    /// Serve /healthz and Prometheus /metrics over HTTP on this address (server mode only)
+    #[arg(long, global = true, value_name = "ADDR")]
+    metrics_addr: Option<String>,
+
+    /// Append every request and its response, with credentials redacted, to this JSONL file (server mode only)
+    #[arg(long, global = true, value_name = "FILE")]
+    record: Option<PathBuf>,
+
+    /// Only load projects under this directory, relative to the root; workspace config still comes from the root (server mode only)
+    #[arg(long, global = true, value_name = "PATH")]
+    scope: Option<PathBuf>,
+
+    /// How messages are delimited on stdio: newline-delimited JSON, or LSP-style Content-Length headers (server mode only)
+    #[arg(long, global = true, value_enum, default_value_t = Framing::Lines)]
+    framing: Framing,
+
+    /// Never make network requests; remote conventions and repository activity come from cache only
+    #[arg(long, env = "JUMBLE_NO_NETWORK", global = true)]
+    no_network: bool,
+
+    /// Don't reload when .jumble files or indexed docs change (server mode only)
+    #[arg(long, global = true)]
+    no_watch: bool,
 }
 
 #[derive(Subcommand, Debug)]
 enum Commands {
     /// Run the MCP server (default if no subcommand specified)
-    Server,
+    #[command(alias = "serve")]
+    #[cfg_attr(
+        feature = "http",
+        command(group(clap::ArgGroup::new("network").args(["http", "ws"])))
+    )]
+    Server {
+        /// Serve the MCP streamable HTTP transport on this address instead of stdio
+        #[cfg(feature = "http")]
+        #[arg(long, value_name = "ADDR")]
+        http: Option<String>,
+
+        /// Serve JSON-RPC over WebSocket on this address instead of stdio
+        #[cfg(feature = "http")]
+        #[arg(long, value_name = "ADDR")]
+        ws: Option<String>,
+
+        /// Also accept browser requests from this origin (repeatable; loopback origins are always accepted)
+        #[cfg(feature = "http")]
+        #[arg(long, value_name = "ORIGIN", requires = "network")]
+        allow_origin: Vec<String>,
+
+        /// Require HTTP and WebSocket clients to send this bearer token
+        #[cfg(feature = "http")]
+        #[arg(
+            long,
+            env = "JUMBLE_AUTH_TOKEN",
+            value_name = "TOKEN",
+            hide_env_values = true
+        )]
+        auth_token: Option<String>,
+
+        /// Also serve the legacy HTTP+SSE transport on /sse for older clients
+        #[cfg(feature = "http")]
+        #[arg(long, requires = "http")]
+        sse: bool,
+
+        /// Listen on this unix socket instead of stdio, one session per connection
+        #[cfg(unix)]
+        #[arg(long, value_name = "PATH")]
+        #[cfg_attr(feature = "http", arg(conflicts_with = "network"))]
+        socket: Option<PathBuf>,
+    },
+
+    /// Relay stdio to a server started with `jumble server --socket`
+    #[cfg(unix)]
+    Connect {
+        /// Socket the server is listening on
+        #[arg(value_name = "PATH")]
+        socket: PathBuf,
+    },
 
     /// Initialize a new jumble project
-    Init,
+    Init {
+        /// Create .jumble/workspace.toml for a multi-project workspace instead
+        #[arg(long)]
+        workspace: bool,
+
+        /// With --workspace, also create .jumble/project.toml in each immediate
+        /// subdirectory that has a Cargo.toml, go.mod, package.json or pyproject.toml
+        #[arg(long, requires = "workspace")]
+        projects: bool,
+
+        /// Start from a template in this git repository (URL or local directory)
+        #[arg(
+            long,
+            value_name = "REPO",
+            conflicts_with = "workspace",
+            requires = "template"
+        )]
+        from: Option<String>,
+
+        /// Template directory to use from the --from repository
+        #[arg(long, value_name = "NAME", requires = "from")]
+        template: Option<String>,
+
+        /// Require the --from repository to be at this full commit hash
+        #[arg(long, value_name = "COMMIT", requires = "from")]
+        rev: Option<String>,
+
+        /// Template variable as KEY=VALUE; project_name defaults to the directory name
+        #[arg(long = "var", value_name = "KEY=VALUE", requires = "from")]
+        vars: Vec<String>,
+
+        /// Show the files that would be created as a diff, without writing them
+        #[arg(long)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Upgrade .jumble TOML files under the root to the current config_version
+    Migrate {
+        /// Report which files would change without writing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Render files from the live jumble configuration
+    Generate {
+        #[command(subcommand)]
+        target: GenerateCommands,
+    },
+
+    /// Re-render generated agent guide files (WARP.md, usage guides, AGENTS.md)
+    Sync {
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+
+    /// Check .jumble files for errors
+    Validate {
+        /// Also check doc paths, concept files and relative links in indexed docs
+        #[arg(long)]
+        links: bool,
+    },
+
+    /// Report contradictory advice and authoring problems in .jumble files
+    Lint {
+        /// Compare conventions between projects and the workspace
+        #[arg(long)]
+        conventions: bool,
+
+        /// Check descriptions, placeholders, concept files and commands against the rules in workspace.toml [lint]
+        #[arg(long)]
+        style: bool,
+    },
+
+    /// Summarize how .jumble files changed between two git refs
+    DiffContext {
+        /// Base ref, e.g. main
+        from: String,
+
+        /// Ref to compare with
+        to: String,
+    },
+
+    /// Call tools interactively against this workspace, with tab completion
+    #[cfg(feature = "repl")]
+    Repl,
+
+    /// Browse projects and their context in a terminal UI that follows file changes
+    #[cfg(feature = "tui")]
+    Tui,
+
+    /// Re-run a transcript written by --record and diff the responses
+    Replay {
+        /// Transcript file
+        file: PathBuf,
+    },
 
     /// Setup AI agent integrations
     Setup {
         #[command(subcommand)]
         agent: SetupCommands,
+
+        /// Show the files that would be created or changed as a diff, without writing them
+        #[arg(long, global = true)]
+        dry_run: bool,
+
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+}
+
+/// Output flags shared by `init`, `sync` and `setup`.
+#[derive(clap::Args, Debug)]
+struct OutputArgs {
+    /// Print a JSON report of created, updated and skipped files instead of text
+    #[arg(long, global = true, conflicts_with = "quiet")]
+    json: bool,
+
+    /// Print nothing except errors
+    #[arg(short, long, global = true)]
+    quiet: bool,
+}
+
+impl OutputArgs {
+    fn reporter(&self, command: &'static str) -> Reporter {
+        let mode = if self.json {
+            OutputMode::Json
+        } else if self.quiet {
+            OutputMode::Quiet
+        } else {
+            OutputMode::Text
+        };
+        Reporter::new(mode, command)
+    }
+}
+
+#[derive(Subcommand, Debug)]
+enum GenerateCommands {
+    /// Create or update the jumble section of AGENTS.md
+    AgentsMd {
+        /// Print the generated section instead of writing AGENTS.md
+        #[arg(long)]
+        stdout: bool,
     },
 }
 
@@ -80,6 +365,19 @@ enum SetupCommands {
     },
 }
 
+impl SetupCommands {
+    /// Command name reported in `--json` output.
+    fn command_name(&self) -> &'static str {
+        match self {
+            SetupCommands::Warp { .. } => "setup warp",
+            SetupCommands::Claude { .. } => "setup claude",
+            SetupCommands::Cursor { .. } => "setup cursor",
+            SetupCommands::Windsurf { .. } => "setup windsurf",
+            SetupCommands::Codex { .. } => "setup codex",
+        }
+    }
+}
+
 fn main() -> Result<()> {
     let args = Args::parse();
 
@@ -87,60 +385,163 @@ fn main() -> Result<()> {
         .root
         .or_else(|| env::var("JUMBLE_ROOT").ok().map(PathBuf::from))
         .unwrap_or_else(|| env::current_dir().unwrap_or_else(|_| PathBuf::from(".")));
+    if args.no_network {
+        remote::disable_network();
+    }
+    if let Some(dir) = &args.config_dir {
+        paths::set_config_dir(dir).map_err(anyhow::Error::msg)?;
+    }
+    let options = ServerOptions {
+        strict: args.strict,
+        max_request_bytes: args.max_request_bytes,
+        max_requests_per_second: args.max_requests_per_second,
+        scope: args.scope,
+    };
 
     match args.command {
-        Some(Commands::Server) | None => {
+        #[cfg(feature = "http")]
+        Some(Commands::Server {
+            http: Some(addr),
+            allow_origin,
+            auth_token,
+            sse,
+            ..
+        }) => {
+            let server = Server::with_options(root, options)?;
+            let transport = http::HttpTransport::new(server, allow_origin)
+                .with_auth_token(auth_token.filter(|t| !t.is_empty()))
+                .with_legacy_sse(sse);
+            http::serve(&addr, transport)
+        }
+        #[cfg(feature = "http")]
+        Some(Commands::Server {
+            ws: Some(addr),
+            allow_origin,
+            auth_token,
+            ..
+        }) => ws::serve(
+            &addr,
+            Server::with_options(root, options)?,
+            allow_origin,
+            auth_token.filter(|t| !t.is_empty()),
+        ),
+        #[cfg(unix)]
+        Some(Commands::Server {
+            socket: Some(path), ..
+        }) => {
+            let server = Server::with_options(root, options)?;
+            if let Some(hint) = server.startup_hint() {
+                eprintln!("{}", hint);
+            }
+            socket::serve(&path, server, args.framing, !args.no_watch)
+        }
+        #[cfg(unix)]
+        Some(Commands::Connect { socket }) => socket::connect(&socket),
+        Some(Commands::Server { .. }) | None => {
             // Run MCP server (default mode)
-            run_server(root)
+            run_server(
+                root,
+                options,
+                args.metrics_addr.as_deref(),
+                args.record.as_deref(),
+                args.framing,
+                !args.no_watch,
+            )
         }
-        Some(Commands::Init) => setup::setup_init(&root),
-        Some(Commands::Setup { agent }) => match agent {
-            SetupCommands::Warp { force } => setup::setup_warp(&root, force),
-            SetupCommands::Claude { global } => setup::setup_claude(&root, global),
-            SetupCommands::Cursor { global } => setup::setup_cursor(&root, global),
-            SetupCommands::Windsurf { global } => setup::setup_windsurf(&root, global),
-            SetupCommands::Codex { global } => setup::setup_codex(&root, global),
+        Some(Commands::Init {
+            workspace,
+            projects,
+            from,
+            template,
+            rev,
+            vars,
+            dry_run,
+            output,
+        }) => {
+            let mut report = output.reporter("init").with_dry_run(dry_run);
+            let result = if let (Some(from), Some(template)) = (&from, &template) {
+                template::run_init_from(&root, from, rev.as_deref(), template, &vars, &mut report)
+            } else if workspace {
+                setup::setup_init_workspace(&root, projects, &mut report)
+            } else {
+                setup::setup_init(&root, &mut report)
+            };
+            report.finish(result)
+        }
+        Some(Commands::Migrate { dry_run }) => migrate::run_migrate(&root, dry_run),
+        Some(Commands::Generate { target }) => match target {
+            GenerateCommands::AgentsMd { stdout } => generate::run_agents_md(&root, stdout),
         },
+        Some(Commands::Sync { output }) => {
+            let mut report = output.reporter("sync");
+            let result = setup::sync(&root, &mut report);
+            report.finish(result)
+        }
+        Some(Commands::Validate { links }) => validate::run_validate(&root, links),
+        Some(Commands::Lint { conventions, style }) => lint::run_lint(&root, conventions, style),
+        Some(Commands::DiffContext { from, to }) => diff::run_diff_context(&root, &from, &to),
+        Some(Commands::Replay { file }) => transcript::run_replay(&root, file),
+        #[cfg(feature = "repl")]
+        Some(Commands::Repl) => repl::run_repl(&root),
+        #[cfg(feature = "tui")]
+        Some(Commands::Tui) => tui::run_tui(&root),
+        Some(Commands::Setup {
+            agent,
+            dry_run,
+            output,
+        }) => {
+            let mut report = output.reporter(agent.command_name()).with_dry_run(dry_run);
+            let result = match agent {
+                SetupCommands::Warp { force } => setup::setup_warp(&root, force, &mut report),
+                SetupCommands::Claude { global } => setup::setup_claude(&root, global, &mut report),
+                SetupCommands::Cursor { global } => setup::setup_cursor(&root, global, &mut report),
+                SetupCommands::Windsurf { global } => {
+                    setup::setup_windsurf(&root, global, &mut report)
+                }
+                SetupCommands::Codex { global } => setup::setup_codex(&root, global, &mut report),
+            };
+            report.finish(result)
+        }
     }
 }
 
-fn run_server(root: PathBuf) -> Result<()> {
-    let mut server = Server::new(root)?;
-
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
-
-    for line in stdin.lock().lines() {
-        let line = line.context("Failed to read from stdin")?;
-        if line.is_empty() {
-            continue;
-        }
-
-        let request: JsonRpcRequest = match serde_json::from_str(&line) {
-            Ok(req) => req,
-            Err(e) => {
-                let error_response = JsonRpcResponse {
-                    jsonrpc: "2.0".to_string(),
-                    id: None,
-                    result: None,
-                    error: Some(JsonRpcError {
-                        code: -32700,
-                        message: format!("Parse error: {}", e),
-                        data: None,
-                    }),
-                };
-                let response_json = serde_json::to_string(&error_response)?;
-                writeln!(stdout, "{}", response_json)?;
-                stdout.flush()?;
-                continue;
+fn run_server(
+    root: PathBuf,
+    options: ServerOptions,
+    metrics_addr: Option<&str>,
+    record: Option<&Path>,
+    framing: Framing,
+    watch: bool,
+) -> Result<()> {
+    let server = Arc::new(Server::with_options(root, options)?);
+    if let Some(hint) = server.startup_hint() {
+        eprintln!("{}", hint);
+    }
+    server.set_notifier(Box::new(move |message| {
+        let mut stdout = io::stdout().lock();
+        let _ = framing.write(&mut stdout, message);
+        let _ = stdout.flush();
+    }));
+    if let Some(addr) = metrics_addr {
+        metrics::serve(addr, Arc::clone(&server))
+            .with_context(|| format!("Failed to serve metrics on {}", addr))?;
+    }
+    if watch {
+        watch::spawn(Arc::clone(&server), move |outgoing| {
+            let mut stdout = io::stdout().lock();
+            for message in outgoing {
+                let _ = framing.write(&mut stdout, &message);
             }
-        };
-
-        let response = server.handle_request(request);
-        let response_json = serde_json::to_string(&response)?;
-        writeln!(stdout, "{}", response_json)?;
-        stdout.flush()?;
+            let _ = stdout.flush();
+        });
     }
+    let mut recorder = record.map(Recorder::open).transpose()?;
 
-    Ok(())
+    stream::serve_stream(
+        io::BufReader::new(io::stdin()),
+        &mut io::stdout(),
+        &server,
+        framing,
+        recorder.as_mut(),
+    )
 }
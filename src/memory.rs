@@ -8,6 +8,8 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
 
+use crate::paths;
+
 /// A single memory entry with metadata.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemoryEntry {
@@ -22,12 +24,15 @@ pub struct MemoryEntry {
 /// Memory database type: a simple key-value store.
 pub type MemoryDb = HashMap<String, MemoryEntry>;
 
+/// File name of the memory database inside `.jumble`.
+pub const MEMORY_FILE: &str = "memory.ron";
+
 /// Type alias for the FileDatabase used in memory storage.
 pub type MemoryDatabase = FileDatabase<MemoryDb, Ron>;
 
 /// Opens or creates a memory database for a project.
 ///
-/// The database is stored at `<project_root>/.jumble/memory.json`.
+/// The database is stored at `<project_root>/.jumble/memory.ron`.
 /// If the file doesn't exist, it will be created with an empty HashMap.
 ///
 /// # Arguments
@@ -37,12 +42,12 @@ pub type MemoryDatabase = FileDatabase<MemoryDb, Ron>;
 /// * `Ok(MemoryDatabase)` - Successfully opened or created the database.
 /// * `Err(String)` - Failed to open/create the database.
 pub fn open_or_create_memory_db(project_root: &Path) -> Result<MemoryDatabase, String> {
-    let memory_path = project_root.join(".jumble/memory.ron");
+    let memory_path = project_root.join(paths::config_dir()).join(MEMORY_FILE);
 
-    // Ensure .jumble directory exists
+    // Ensure the config directory exists
     if let Some(parent) = memory_path.parent() {
         std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create .jumble directory: {}", e))?;
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
     }
 
     // Open or create the database
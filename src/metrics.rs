@@ -0,0 +1,285 @@
+//! Request metrics in the Prometheus text format, and a small HTTP listener
+//! serving them at `/metrics` next to a `/healthz` probe.
+//!
+//! Counters live for the whole process; a reload does not reset them. Tool
+//! and method labels are limited to names the server knows, so a client
+//! sending junk can't grow the label set without bound.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+use crate::server::Server;
+
+/// Histogram bucket upper bounds, in seconds.
+const BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Label used for methods and tools the server doesn't recognise.
+pub const OTHER: &str = "other";
+
+#[derive(Default)]
+struct Histogram {
+    /// Observations per bucket in [`BUCKETS`], not cumulative.
+    counts: [u64; BUCKETS.len()],
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let seconds = duration.as_secs_f64();
+        if let Some(i) = BUCKETS.iter().position(|le| seconds <= *le) {
+            self.counts[i] += 1;
+        }
+        self.count += 1;
+        self.sum += seconds;
+    }
+
+    fn render(&self, out: &mut String, name: &str, labels: &str) {
+        let sep = if labels.is_empty() { "" } else { "," };
+        let mut cumulative = 0;
+        for (le, count) in BUCKETS.iter().zip(self.counts) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "{}_bucket{{{}{}le=\"{}\"}} {}",
+                name, labels, sep, le, cumulative
+            );
+        }
+        let _ = writeln!(
+            out,
+            "{}_bucket{{{}{}le=\"+Inf\"}} {}",
+            name, labels, sep, self.count
+        );
+        let braces = |labels: &str| {
+            if labels.is_empty() {
+                String::new()
+            } else {
+                format!("{{{}}}", labels)
+            }
+        };
+        let _ = writeln!(out, "{}_sum{} {}", name, braces(labels), self.sum);
+        let _ = writeln!(out, "{}_count{} {}", name, braces(labels), self.count);
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    requests: BTreeMap<String, u64>,
+    tools: BTreeMap<String, Histogram>,
+    discovery: Histogram,
+    /// File index lookups from discoveries that have since been replaced.
+    cache_hits: u64,
+    cache_misses: u64,
+}
+
+/// Process-wide counters, shared by every request handler.
+#[derive(Default)]
+pub struct Metrics {
+    counters: Mutex<Counters>,
+}
+
+/// Values read from the server when metrics are rendered.
+pub struct Gauges {
+    pub projects: usize,
+    /// File index hits and misses since the last reload.
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+}
+
+impl Metrics {
+    fn lock(&self) -> MutexGuard<'_, Counters> {
+        self.counters.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn record_request(&self, method: &str) {
+        *self.lock().requests.entry(method.to_string()).or_default() += 1;
+    }
+
+    pub fn record_tool(&self, tool: &str, duration: Duration) {
+        self.lock()
+            .tools
+            .entry(tool.to_string())
+            .or_default()
+            .observe(duration);
+    }
+
+    pub fn record_discovery(&self, duration: Duration) {
+        self.lock().discovery.observe(duration);
+    }
+
+    /// Keep the file index counts of a discovery about to be replaced.
+    pub fn retire_cache(&self, hits: u64, misses: u64) {
+        let mut counters = self.lock();
+        counters.cache_hits += hits;
+        counters.cache_misses += misses;
+    }
+
+    /// All metrics in the Prometheus text exposition format.
+    pub fn render(&self, gauges: &Gauges) -> String {
+        let counters = self.lock();
+        let mut out = String::new();
+
+        out.push_str("# HELP jumble_requests_total JSON-RPC requests received, by method.\n");
+        out.push_str("# TYPE jumble_requests_total counter\n");
+        for (method, count) in &counters.requests {
+            let _ = writeln!(
+                out,
+                "jumble_requests_total{{method=\"{}\"}} {}",
+                method, count
+            );
+        }
+
+        out.push_str(
+            "# HELP jumble_tool_duration_seconds Time spent answering tools/call, by tool.\n",
+        );
+        out.push_str("# TYPE jumble_tool_duration_seconds histogram\n");
+        for (tool, histogram) in &counters.tools {
+            histogram.render(
+                &mut out,
+                "jumble_tool_duration_seconds",
+                &format!("tool=\"{}\"", tool),
+            );
+        }
+
+        out.push_str(
+            "# HELP jumble_discovery_duration_seconds Time spent discovering the workspace and projects.\n",
+        );
+        out.push_str("# TYPE jumble_discovery_duration_seconds histogram\n");
+        counters
+            .discovery
+            .render(&mut out, "jumble_discovery_duration_seconds", "");
+
+        let hits = counters.cache_hits + gauges.cache_hits;
+        let misses = counters.cache_misses + gauges.cache_misses;
+        out.push_str(
+            "# HELP jumble_file_index_lookups_total File listing lookups, by cache result.\n",
+        );
+        out.push_str("# TYPE jumble_file_index_lookups_total counter\n");
+        let _ = writeln!(
+            out,
+            "jumble_file_index_lookups_total{{result=\"hit\"}} {}",
+            hits
+        );
+        let _ = writeln!(
+            out,
+            "jumble_file_index_lookups_total{{result=\"miss\"}} {}",
+            misses
+        );
+        out.push_str(
+            "# HELP jumble_file_index_hit_ratio Share of file listing lookups served from cache.\n",
+        );
+        out.push_str("# TYPE jumble_file_index_hit_ratio gauge\n");
+        let ratio = if hits + misses == 0 {
+            0.0
+        } else {
+            hits as f64 / (hits + misses) as f64
+        };
+        let _ = writeln!(out, "jumble_file_index_hit_ratio {}", ratio);
+
+        out.push_str("# HELP jumble_projects Projects currently discovered.\n");
+        out.push_str("# TYPE jumble_projects gauge\n");
+        let _ = writeln!(out, "jumble_projects {}", gauges.projects);
+        out
+    }
+}
+
+/// Serve `/healthz` and `/metrics` for `server` on `addr` from a background
+/// thread. Binding errors are returned; errors on a connection only drop it.
+pub fn serve(addr: &str, server: Arc<Server>) -> std::io::Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    eprintln!(
+        "Serving /healthz and /metrics on http://{}",
+        listener.local_addr()?
+    );
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = respond(stream, &server);
+        }
+    });
+    Ok(())
+}
+
+fn respond(mut stream: TcpStream, server: &Server) -> std::io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // Headers are not needed; read them so the client sees a clean close.
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    let path = path.split('?').next().unwrap_or(path);
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/healthz") => ("200 OK", "application/json", server.health().to_string()),
+        ("GET", "/metrics") => ("200 OK", "text/plain; version=0.0.4", server.metrics_text()),
+        ("GET", _) => ("404 Not Found", "text/plain", "Not found\n".to_string()),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain",
+            "Method not allowed\n".to_string(),
+        ),
+    };
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_text() {
+        let metrics = Metrics::default();
+        metrics.record_request("tools/call");
+        metrics.record_request("tools/call");
+        metrics.record_request("initialize");
+        metrics.record_tool("get_commands", Duration::from_millis(3));
+        metrics.record_tool("get_commands", Duration::from_millis(300));
+        metrics.record_discovery(Duration::from_millis(40));
+        metrics.retire_cache(2, 1);
+
+        let text = metrics.render(&Gauges {
+            projects: 4,
+            cache_hits: 1,
+            cache_misses: 0,
+        });
+        for line in [
+            "jumble_requests_total{method=\"initialize\"} 1",
+            "jumble_requests_total{method=\"tools/call\"} 2",
+            "jumble_tool_duration_seconds_bucket{tool=\"get_commands\",le=\"0.001\"} 0",
+            "jumble_tool_duration_seconds_bucket{tool=\"get_commands\",le=\"0.005\"} 1",
+            "jumble_tool_duration_seconds_bucket{tool=\"get_commands\",le=\"0.5\"} 2",
+            "jumble_tool_duration_seconds_bucket{tool=\"get_commands\",le=\"+Inf\"} 2",
+            "jumble_tool_duration_seconds_count{tool=\"get_commands\"} 2",
+            "jumble_discovery_duration_seconds_bucket{le=\"0.05\"} 1",
+            "jumble_discovery_duration_seconds_count 1",
+            "jumble_file_index_lookups_total{result=\"hit\"} 3",
+            "jumble_file_index_hit_ratio 0.75",
+            "jumble_projects 4",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing {}\n{}",
+                line,
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,309 @@
+//! Versioned `.jumble` config files and the migrations between versions.
+//!
+//! Every TOML file jumble reads carries a top-level `config_version`; files
+//! without one are version 0. Loading runs the migrations in memory so older
+//! layouts keep working, and `jumble migrate` runs the same steps and writes
+//! the result back, preserving comments and formatting.
+
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use std::path::{Path, PathBuf};
+use toml_edit::DocumentMut;
+use walkdir::WalkDir;
+
+use crate::paths;
+use crate::text;
+
+/// The `config_version` written by this build of jumble.
+pub const CURRENT_CONFIG_VERSION: i64 = 1;
+
+/// The kinds of TOML file jumble reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigKind {
+    /// `.jumble/project.toml`
+    Project,
+    /// `.jumble/workspace.toml` at the workspace root
+    Workspace,
+    /// `.jumble/conventions.toml`
+    Conventions,
+    /// `.jumble/docs.toml`
+    Docs,
+    /// `~/.jumble/jumble.toml`
+    Global,
+}
+
+impl ConfigKind {
+    /// Map a file name inside a `.jumble` directory to its kind.
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        match name {
+            "project.toml" => Some(Self::Project),
+            "workspace.toml" => Some(Self::Workspace),
+            "conventions.toml" => Some(Self::Conventions),
+            "docs.toml" => Some(Self::Docs),
+            "jumble.toml" => Some(Self::Global),
+            _ => None,
+        }
+    }
+}
+
+/// One upgrade step, from `from` to `from + 1`.
+struct Migration {
+    from: i64,
+    description: &'static str,
+    apply: fn(ConfigKind, &mut DocumentMut),
+}
+
+/// All migrations, in order. The engine stamps `config_version` after each
+/// step, so a step only has to reshape the tables it cares about.
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 0,
+    description: "add config_version",
+    apply: |_, _| {},
+}];
+
+/// Read the `config_version` of a document (0 when absent).
+pub fn config_version(doc: &DocumentMut) -> Result<i64, String> {
+    match doc.get("config_version") {
+        None => Ok(0),
+        Some(item) => item
+            .as_integer()
+            .filter(|v| *v >= 0)
+            .ok_or_else(|| "config_version must be a non-negative integer".to_string()),
+    }
+}
+
+/// Upgrade `doc` to `CURRENT_CONFIG_VERSION` in place.
+///
+/// Returns the descriptions of the steps that ran, which is empty when the
+/// document was already current.
+pub fn migrate_document(
+    kind: ConfigKind,
+    doc: &mut DocumentMut,
+) -> Result<Vec<&'static str>, String> {
+    let mut version = config_version(doc)?;
+    if version > CURRENT_CONFIG_VERSION {
+        return Err(format!(
+            "config_version {} is newer than this jumble supports ({}); upgrade jumble",
+            version, CURRENT_CONFIG_VERSION
+        ));
+    }
+
+    let mut applied = Vec::new();
+    while version < CURRENT_CONFIG_VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|m| m.from == version)
+            .ok_or_else(|| format!("No migration from config_version {}", version))?;
+        (migration.apply)(kind, doc);
+        version += 1;
+        set_config_version(doc, version);
+        applied.push(migration.description);
+    }
+    Ok(applied)
+}
+
+/// Parse and migrate TOML source, then deserialize it.
+pub fn parse<T: DeserializeOwned>(kind: ConfigKind, content: &str) -> Result<T, String> {
+    let mut doc: DocumentMut = content.parse().map_err(|e| format!("{}", e))?;
+    migrate_document(kind, &mut doc)?;
+    toml::from_str(&doc.to_string()).map_err(|e| format!("{}", e))
+}
+
+/// Read, migrate and deserialize a config file.
+pub fn load<T: DeserializeOwned>(kind: ConfigKind, path: &Path) -> Result<T> {
+    let content =
+        text::read_text(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    parse(kind, &content).map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Return TOML source upgraded to the current version, for files jumble writes.
+pub fn upgrade_source(kind: ConfigKind, content: &str) -> Result<String, String> {
+    let mut doc: DocumentMut = content.parse().map_err(|e| format!("{}", e))?;
+    migrate_document(kind, &mut doc)?;
+    Ok(doc.to_string())
+}
+
+fn set_config_version(doc: &mut DocumentMut, version: i64) {
+    let had_key = doc.contains_key("config_version");
+    doc["config_version"] = toml_edit::value(version);
+    if !had_key {
+        // Keep the version at the very top rather than after other root keys.
+        doc.sort_values_by(|a, _, b, _| {
+            (b.get() == "config_version").cmp(&(a.get() == "config_version"))
+        });
+    }
+}
+
+/// Result of migrating one file on disk.
+#[derive(Debug)]
+pub struct FileMigration {
+    pub path: PathBuf,
+    pub from_version: i64,
+    pub applied: Vec<&'static str>,
+}
+
+/// Find every `.jumble/*.toml` under `root` and upgrade the ones that are
+/// out of date. Nothing is written when `dry_run` is set.
+pub fn migrate_tree(root: &Path, dry_run: bool) -> Result<Vec<FileMigration>> {
+    let mut migrated = Vec::new();
+
+    for entry in WalkDir::new(root)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        let kind = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if paths::is_in_jumble_dir(path) => ConfigKind::from_file_name(name),
+            _ => None,
+        };
+        let Some(kind) = kind else { continue };
+
+        let bytes =
+            std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let decoded = text::decode(&bytes);
+        if decoded.lossy {
+            // Writing back would bake the replacement characters into the file.
+            anyhow::bail!(
+                "{} is not valid UTF-8; fix its encoding before migrating",
+                path.display()
+            );
+        }
+        let content = decoded.text;
+        let mut doc: DocumentMut = content
+            .parse()
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let from_version =
+            config_version(&doc).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        let applied = migrate_document(kind, &mut doc)
+            .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        if applied.is_empty() {
+            continue;
+        }
+
+        if !dry_run {
+            std::fs::write(path, doc.to_string())
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+        migrated.push(FileMigration {
+            path: path.to_path_buf(),
+            from_version,
+            applied,
+        });
+    }
+
+    Ok(migrated)
+}
+
+/// `jumble migrate`: upgrade every config file under `root` and report.
+pub fn run_migrate(root: &Path, dry_run: bool) -> Result<()> {
+    let migrated = migrate_tree(root, dry_run)?;
+    if migrated.is_empty() {
+        println!(
+            "✓ All .jumble files are at config_version {}",
+            CURRENT_CONFIG_VERSION
+        );
+        return Ok(());
+    }
+
+    let verb = if dry_run { "Would migrate" } else { "Migrated" };
+    for file in &migrated {
+        let display = file.path.strip_prefix(root).unwrap_or(&file.path);
+        println!(
+            "✓ {} {} (config_version {} → {}): {}",
+            verb,
+            display.display(),
+            file.from_version,
+            CURRENT_CONFIG_VERSION,
+            file.applied.join("; ")
+        );
+    }
+    if dry_run {
+        println!("\nRun `jumble migrate` without --dry-run to write these changes.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ProjectConfig;
+    use tempfile::TempDir;
+
+    const UNVERSIONED: &str = "# My project\n[project]\nname = \"demo\"\ndescription = \"Demo\"\n";
+
+    #[test]
+    fn test_unversioned_file_is_migrated_in_memory() {
+        let config: ProjectConfig = parse(ConfigKind::Project, UNVERSIONED).unwrap();
+        assert_eq!(config.project.name, "demo");
+    }
+
+    #[test]
+    fn test_upgrade_stamps_version_at_top_and_keeps_comments() {
+        let upgraded = upgrade_source(ConfigKind::Project, UNVERSIONED).unwrap();
+        assert!(upgraded.starts_with("config_version = 1\n"));
+        assert!(upgraded.contains("# My project"));
+
+        // Already current: unchanged.
+        assert_eq!(
+            upgrade_source(ConfigKind::Project, &upgraded).unwrap(),
+            upgraded
+        );
+    }
+
+    #[test]
+    fn test_windows_authored_file_loads() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path().join(".jumble");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("project.toml");
+        std::fs::write(
+            &path,
+            b"\xEF\xBB\xBF[project]\r\nname = \"win\"\r\ndescription = \"Windows\"\r\n",
+        )
+        .unwrap();
+
+        let config: ProjectConfig = load(ConfigKind::Project, &path).unwrap();
+        assert_eq!(config.project.name, "win");
+        assert_eq!(migrate_tree(temp.path(), true).unwrap().len(), 1);
+
+        // Latin-1 bytes still load, but migrate refuses to rewrite them.
+        std::fs::write(
+            &path,
+            b"[project]\nname = \"win\"\ndescription = \"Caf\xE9\"\n",
+        )
+        .unwrap();
+        let config: ProjectConfig = load(ConfigKind::Project, &path).unwrap();
+        assert_eq!(config.project.description, "Caf\u{FFFD}");
+        assert!(migrate_tree(temp.path(), true).is_err());
+    }
+
+    #[test]
+    fn test_newer_config_version_is_rejected() {
+        let content = "config_version = 99\n[project]\nname = \"x\"\ndescription = \"y\"\n";
+        let err = parse::<ProjectConfig>(ConfigKind::Project, content).unwrap_err();
+        assert!(err.contains("newer"));
+    }
+
+    #[test]
+    fn test_migrate_tree_rewrites_outdated_files() {
+        let temp = TempDir::new().unwrap();
+        let jumble_dir = temp.path().join("app/.jumble");
+        std::fs::create_dir_all(&jumble_dir).unwrap();
+        std::fs::write(jumble_dir.join("project.toml"), UNVERSIONED).unwrap();
+        std::fs::write(jumble_dir.join("notes.toml"), "x = 1\n").unwrap();
+
+        let planned = migrate_tree(temp.path(), true).unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(
+            std::fs::read_to_string(jumble_dir.join("project.toml")).unwrap(),
+            UNVERSIONED
+        );
+
+        let migrated = migrate_tree(temp.path(), false).unwrap();
+        assert_eq!(migrated[0].from_version, 0);
+        let written = std::fs::read_to_string(jumble_dir.join("project.toml")).unwrap();
+        assert!(written.starts_with("config_version = 1"));
+        assert!(migrate_tree(temp.path(), false).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,241 @@
+//! Who owns a path, for `get_code_owners`.
+//!
+//! A CODEOWNERS file is authoritative when one exists. Otherwise, and only
+//! when `infer_code_owners` is enabled, owners are inferred by running
+//! `git blame` over the files under the path and counting the lines each
+//! author last touched, weighting recent changes.
+
+use ignore::gitignore::GitignoreBuilder;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::exec::{self, ExecPolicy};
+
+/// Where CODEOWNERS may live, relative to a repository or project root.
+const CODEOWNERS_LOCATIONS: &[&str] = &[
+    ".github/CODEOWNERS",
+    "CODEOWNERS",
+    "docs/CODEOWNERS",
+    ".gitlab/CODEOWNERS",
+];
+
+/// Files blamed per call, at most; blame costs a process per file.
+pub const MAX_BLAME_FILES: usize = 50;
+
+/// Lines changed within this many days count as recent.
+pub const RECENT_DAYS: i64 = 365;
+
+/// A CODEOWNERS file and the directory its patterns are relative to.
+pub struct CodeOwnersFile {
+    pub base: PathBuf,
+    pub path: PathBuf,
+}
+
+/// The first CODEOWNERS file found under any of `roots`, in order.
+pub fn find_codeowners(roots: &[&Path]) -> Option<CodeOwnersFile> {
+    roots.iter().find_map(|root| {
+        CODEOWNERS_LOCATIONS
+            .iter()
+            .map(|location| root.join(location))
+            .find(|path| path.is_file())
+            .map(|path| CodeOwnersFile {
+                base: root.to_path_buf(),
+                path,
+            })
+    })
+}
+
+/// The last rule in `content` matching `path` (under `base`), as
+/// `(pattern, owners)`. Like git, later rules override earlier ones.
+pub fn owners_for(
+    content: &str,
+    base: &Path,
+    path: &Path,
+    is_dir: bool,
+) -> Option<(String, Vec<String>)> {
+    let mut found = None;
+    for line in content.lines().map(str::trim) {
+        // GitLab section headers look like `[Docs]` or `^[Optional]`.
+        if line.is_empty() || line.starts_with(['#', '[', '^']) {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let Some(pattern) = parts.next() else {
+            continue;
+        };
+        let mut builder = GitignoreBuilder::new(base);
+        if builder.add_line(None, pattern).is_err() {
+            continue;
+        }
+        let Ok(matcher) = builder.build() else {
+            continue;
+        };
+        if matcher
+            .matched_path_or_any_parents(path, is_dir)
+            .is_ignore()
+        {
+            found = Some((pattern.to_string(), parts.map(str::to_string).collect()));
+        }
+    }
+    found
+}
+
+/// Lines last changed by one author.
+#[derive(Debug, Default, PartialEq)]
+pub struct AuthorLines {
+    pub name: String,
+    pub email: String,
+    pub lines: usize,
+    /// Lines changed within [`RECENT_DAYS`].
+    pub recent_lines: usize,
+    /// Unix time of the author's latest change among the blamed lines.
+    pub last_change: i64,
+}
+
+/// Blame `files` (relative to `root`) and total the lines per author, most
+/// recent activity first.
+pub fn blame(root: &Path, files: &[String], now: i64) -> Result<Vec<AuthorLines>, String> {
+    let policy = ExecPolicy {
+        timeout: Duration::from_secs(20),
+        max_output_bytes: 8 * 1024 * 1024,
+        ..Default::default()
+    };
+    let since = now - RECENT_DAYS * 24 * 60 * 60;
+    let mut authors: HashMap<String, AuthorLines> = HashMap::new();
+    for file in files {
+        let argv: Vec<String> = ["git", "blame", "--porcelain", "--", file]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let output = exec::run(&argv, root, &policy)?;
+        if !output.success() {
+            if output.stderr.contains("not a git repository") {
+                return Err("Not a git repository".to_string());
+            }
+            // Untracked or binary files have nothing to blame.
+            continue;
+        }
+        if !output.truncated {
+            add_porcelain(&output.stdout, since, &mut authors);
+        }
+    }
+
+    let mut authors: Vec<AuthorLines> = authors.into_values().collect();
+    authors.sort_by(|a, b| {
+        (b.recent_lines, b.lines, &a.name).cmp(&(a.recent_lines, a.lines, &b.name))
+    });
+    Ok(authors)
+}
+
+/// Add the lines of one `git blame --porcelain` output to `authors`, keyed
+/// by lowercased email.
+fn add_porcelain(output: &str, since: i64, authors: &mut HashMap<String, AuthorLines>) {
+    #[derive(Default)]
+    struct Commit {
+        name: String,
+        email: String,
+        time: i64,
+    }
+    let mut commits: HashMap<&str, Commit> = HashMap::new();
+    let mut current = "";
+
+    for line in output.lines() {
+        if line.starts_with('\t') {
+            let Some(commit) = commits.get(current) else {
+                continue;
+            };
+            // Uncommitted changes have an all-zero hash.
+            if current.bytes().all(|b| b == b'0') {
+                continue;
+            }
+            let entry = authors
+                .entry(commit.email.to_lowercase())
+                .or_insert_with(|| AuthorLines {
+                    name: commit.name.clone(),
+                    email: commit.email.clone(),
+                    ..AuthorLines::default()
+                });
+            entry.lines += 1;
+            if commit.time >= since {
+                entry.recent_lines += 1;
+            }
+            entry.last_change = entry.last_change.max(commit.time);
+            continue;
+        }
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "author" => commits.entry(current).or_default().name = value.to_string(),
+            "author-mail" => {
+                commits.entry(current).or_default().email =
+                    value.trim_matches(['<', '>']).to_string()
+            }
+            "author-time" => commits.entry(current).or_default().time = value.parse().unwrap_or(0),
+            _ if key.len() == 40 && key.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                current = key;
+                commits.entry(current).or_default();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owners_for_uses_the_last_matching_rule() {
+        let content = "\
+# Default owners
+* @acme/core
+[Docs]
+/src/api/ @acme/api @alice
+*.md @acme/docs
+";
+        let base = Path::new("/repo");
+        assert_eq!(
+            owners_for(content, base, &base.join("src/api/users.rs"), false),
+            Some((
+                "/src/api/".to_string(),
+                vec!["@acme/api".to_string(), "@alice".to_string()]
+            ))
+        );
+        assert_eq!(
+            owners_for(content, base, &base.join("src/api/README.md"), false)
+                .unwrap()
+                .1,
+            ["@acme/docs"]
+        );
+        assert_eq!(
+            owners_for(content, base, &base.join("src/lib.rs"), false)
+                .unwrap()
+                .0,
+            "*"
+        );
+    }
+
+    #[test]
+    fn test_add_porcelain_counts_lines_per_author() {
+        let a = "a".repeat(40);
+        let b = "b".repeat(40);
+        let zero = "0".repeat(40);
+        let output = format!(
+            "{a} 1 1 2\nauthor Ann\nauthor-mail <ann@example.com>\nauthor-time 2000\nfilename x.rs\n\tfn a() {{}}\n\
+             {a} 2 2\n\tfn b() {{}}\n\
+             {b} 3 3 1\nauthor Bob\nauthor-mail <BOB@example.com>\nauthor-time 100\nfilename x.rs\n\tfn c() {{}}\n\
+             {zero} 4 4 1\nauthor Not Committed Yet\nauthor-mail <not.committed.yet>\nauthor-time 3000\n\tfn d() {{}}\n"
+        );
+        let mut authors = HashMap::new();
+        add_porcelain(&output, 1000, &mut authors);
+
+        assert_eq!(authors.len(), 2);
+        let ann = &authors["ann@example.com"];
+        assert_eq!((ann.lines, ann.recent_lines, ann.last_change), (2, 2, 2000));
+        let bob = &authors["bob@example.com"];
+        assert_eq!(
+            (bob.name.as_str(), bob.lines, bob.recent_lines),
+            ("Bob", 1, 0)
+        );
+    }
+}
@@ -0,0 +1,237 @@
+//! Separator-agnostic path helpers.
+//!
+//! Discovery and config values have to work the same on Windows (including
+//! `\\?\` verbatim and UNC roots) as on Unix, so matching is done on path
+//! components and relative paths from config files may use either `/` or `\`.
+
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf, Prefix};
+use std::sync::OnceLock;
+
+/// Default name of the per-directory configuration folder, and the name of
+/// the one in the home directory that holds `jumble.toml`.
+pub const JUMBLE_DIR: &str = ".jumble";
+
+/// Set by `--config-dir`.
+static CONFIG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` (such as `.context` or `.ai/jumble`) instead of `.jumble` in
+/// every project and workspace for the rest of the process.
+pub fn set_config_dir(dir: &Path) -> Result<(), String> {
+    let is_relative = dir.components().all(|c| matches!(c, Component::Normal(_)));
+    if dir.as_os_str().is_empty() || !is_relative {
+        return Err(format!(
+            "--config-dir must be a relative directory name like .context or .ai/jumble, got '{}'",
+            dir.display()
+        ));
+    }
+    CONFIG_DIR
+        .set(dir.to_path_buf())
+        .map_err(|_| "The config directory was already set".to_string())
+}
+
+/// The per-directory configuration folder, relative to a project or
+/// workspace root: `.jumble` unless `--config-dir` says otherwise.
+pub fn config_dir() -> &'static Path {
+    CONFIG_DIR
+        .get()
+        .map_or(Path::new(JUMBLE_DIR), PathBuf::as_path)
+}
+
+/// `file` inside the config folder as messages name it, such as
+/// `.jumble/docs.toml`.
+pub fn config_file(file: &str) -> String {
+    format!("{}/{}", config_dir().display(), file)
+}
+
+/// Whether `path`'s parent directory is the config folder.
+pub fn is_in_jumble_dir(path: &Path) -> bool {
+    is_in_dir(path, config_dir())
+}
+
+/// Whether `path`'s parent directory ends with `dir`, compared by component.
+fn is_in_dir(path: &Path, dir: &Path) -> bool {
+    path.parent().is_some_and(|parent| parent.ends_with(dir))
+}
+
+/// Whether `path` is `<anything>/.jumble/<file_name>`.
+pub fn is_jumble_file(path: &Path, file_name: &str) -> bool {
+    path.file_name() == Some(OsStr::new(file_name)) && is_in_jumble_dir(path)
+}
+
+/// The project or workspace directory holding the config folder `dir`.
+pub fn owner_of_config_dir(dir: &Path) -> &Path {
+    let mut owner = dir;
+    for _ in config_dir().components() {
+        owner = owner.parent().unwrap_or(owner);
+    }
+    owner
+}
+
+/// Join a relative path written in a config file onto `base`, accepting both
+/// `/` and `\` as separators. Absolute values are returned as-is.
+pub fn join_relative(base: &Path, relative: &str) -> PathBuf {
+    if Path::new(relative).is_absolute() {
+        return PathBuf::from(relative);
+    }
+    let mut joined = base.to_path_buf();
+    for part in relative.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            part => joined.push(part),
+        }
+    }
+    joined
+}
+
+/// Whether `path` has extension `ext`, ignoring ASCII case (`SKILL.MD` is
+/// common on case-insensitive filesystems).
+pub fn has_extension(path: &Path, ext: &str) -> bool {
+    path.extension()
+        .and_then(OsStr::to_str)
+        .map(|e| e.eq_ignore_ascii_case(ext))
+        .unwrap_or(false)
+}
+
+/// `file://` URI for `path`. Relative paths are made absolute against the
+/// current directory and `.`/`..` are resolved without touching the disk,
+/// so the file does not have to exist. Windows drive, UNC and verbatim
+/// prefixes become `file:///C:/...` and `file://server/share/...`.
+pub fn file_uri(path: &Path) -> String {
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut host = String::new();
+    let mut segments: Vec<String> = Vec::new();
+    // Segments from the prefix (drive or share) that `..` must not pop.
+    let mut floor = 0;
+
+    for component in absolute.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                match prefix.kind() {
+                    Prefix::Disk(letter) | Prefix::VerbatimDisk(letter) => {
+                        segments.push(format!("{}:", letter as char));
+                    }
+                    Prefix::UNC(server, share) | Prefix::VerbatimUNC(server, share) => {
+                        host = server.to_string_lossy().into_owned();
+                        segments.push(percent_encode(&share.to_string_lossy()));
+                    }
+                    Prefix::Verbatim(name) | Prefix::DeviceNS(name) => {
+                        segments.push(percent_encode(&name.to_string_lossy()));
+                    }
+                }
+                floor = segments.len();
+            }
+            Component::RootDir | Component::CurDir => {}
+            Component::ParentDir => {
+                if segments.len() > floor {
+                    segments.pop();
+                }
+            }
+            Component::Normal(part) => segments.push(percent_encode(&part.to_string_lossy())),
+        }
+    }
+    format!("file://{}/{}", host, segments.join("/"))
+}
+
+/// Percent-encode one path segment, keeping the characters RFC 3986 allows
+/// in a segment unescaped.
+fn percent_encode(segment: &str) -> String {
+    let mut encoded = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' => encoded.push(byte as char),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+'
+            | b',' | b';' | b'=' | b':' | b'@' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_jumble_file_matches_components() {
+        let path = Path::new("repo").join(".jumble").join("project.toml");
+        assert!(is_jumble_file(&path, "project.toml"));
+        assert!(!is_jumble_file(&path, "workspace.toml"));
+
+        let decoy = Path::new("repo").join("x.jumble").join("project.toml");
+        assert!(!is_jumble_file(&decoy, "project.toml"));
+        assert!(!is_jumble_file(Path::new("project.toml"), "project.toml"));
+
+        let nested = Path::new("repo")
+            .join(".ai")
+            .join("jumble")
+            .join("project.toml");
+        assert!(is_in_dir(&nested, Path::new(".ai/jumble")));
+        assert!(!is_in_dir(&nested, Path::new(".jumble")));
+        assert!(!is_in_dir(&path, Path::new(".ai/jumble")));
+        assert!(set_config_dir(Path::new("../elsewhere")).is_err());
+        assert!(set_config_dir(Path::new("/abs")).is_err());
+    }
+
+    #[test]
+    fn test_join_relative_accepts_either_separator() {
+        let base = Path::new("repo");
+        let expected = base.join("docs").join("api").join("index.md");
+        assert_eq!(join_relative(base, "docs/api/index.md"), expected);
+        assert_eq!(join_relative(base, "docs\\api\\index.md"), expected);
+        assert_eq!(join_relative(base, "./docs\\api/index.md"), expected);
+    }
+
+    #[test]
+    fn test_has_extension_ignores_case() {
+        assert!(has_extension(Path::new("SKILL.MD"), "md"));
+        assert!(has_extension(Path::new("notes.md"), "md"));
+        assert!(!has_extension(Path::new("notes.txt"), "md"));
+        assert!(!has_extension(Path::new("md"), "md"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_uri_normalizes_and_escapes() {
+        assert_eq!(
+            file_uri(Path::new("/work/repo/./src/../docs/API guide#2.md")),
+            "file:///work/repo/docs/API%20guide%232.md"
+        );
+        assert_eq!(file_uri(Path::new("/work/é.md")), "file:///work/%C3%A9.md");
+
+        let relative = file_uri(Path::new("notes.md"));
+        assert!(relative.starts_with("file:///"), "{}", relative);
+        assert!(relative.ends_with("/notes.md"), "{}", relative);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn test_windows_verbatim_and_unc_paths() {
+        for path in [
+            r"\\?\C:\work\repo\.jumble\project.toml",
+            r"\\server\share\repo\.jumble\project.toml",
+            r"C:/work/repo/.jumble/project.toml",
+        ] {
+            assert!(is_jumble_file(Path::new(path), "project.toml"), "{}", path);
+        }
+
+        let base = Path::new(r"\\?\C:\work\repo");
+        assert_eq!(
+            join_relative(base, "docs/api.md"),
+            PathBuf::from(r"\\?\C:\work\repo\docs\api.md")
+        );
+        assert_eq!(
+            join_relative(base, r"D:\shared\api.md"),
+            PathBuf::from(r"D:\shared\api.md")
+        );
+
+        assert_eq!(
+            file_uri(Path::new(r"\\?\C:\work\repo\docs\api.md")),
+            "file:///C:/work/repo/docs/api.md"
+        );
+        assert_eq!(
+            file_uri(Path::new(r"\\server\share\repo\my doc.md")),
+            "file://server/share/repo/my%20doc.md"
+        );
+    }
+}
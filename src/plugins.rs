@@ -0,0 +1,362 @@
+//! Experimental WASM plugin host for third-party tool providers.
+//!
+//! Plugins are core WebAssembly modules (`.wasm`, or `.wat` text) placed in
+//! `<root>/.jumble/plugins/`. They run with no imports at all, so they cannot
+//! touch the filesystem, network or environment; each call gets a fresh
+//! instance with a fuel budget and a memory cap.
+//!
+//! A plugin exports:
+//!
+//! - `memory`
+//! - `jumble_alloc(len: i32) -> i32`: reserve `len` bytes for the host to write
+//! - `jumble_tools() -> i64`: a JSON array of MCP tool definitions
+//! - `jumble_call(ptr: i32, len: i32) -> i64`: handle
+//!   `{"name": ..., "arguments": {...}}` and return
+//!   `{"text": "...", "is_error": false}`
+//!
+//! Results are returned as `(ptr << 32) | len` of a UTF-8 buffer in `memory`.
+//!
+//! Without the `plugins` feature the host loads nothing and only warns when
+//! plugin files are present.
+
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+use crate::paths;
+
+/// Directory, inside the workspace's config folder, that plugins are loaded
+/// from.
+pub const PLUGIN_DIR: &str = "plugins";
+
+fn plugin_dir(root: &Path) -> PathBuf {
+    root.join(paths::config_dir()).join(PLUGIN_DIR)
+}
+
+fn plugin_files(root: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(plugin_dir(root))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| {
+                    p.extension()
+                        .map(|ext| ext == "wasm" || ext == "wat")
+                        .unwrap_or(false)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+#[cfg(feature = "plugins")]
+pub use host::PluginHost;
+
+#[cfg(not(feature = "plugins"))]
+pub use disabled::PluginHost;
+
+#[cfg(not(feature = "plugins"))]
+mod disabled {
+    use super::*;
+
+    /// Stand-in used when jumble is built without the `plugins` feature.
+    #[derive(Default)]
+    pub struct PluginHost;
+
+    /// Never constructed without the `plugins` feature.
+    pub enum PluginCall {}
+
+    impl PluginCall {
+        pub fn call(&self, _arguments: &Value) -> Result<String, String> {
+            match *self {}
+        }
+    }
+
+    impl PluginHost {
+        pub fn load(root: &Path) -> Self {
+            if !plugin_files(root).is_empty() {
+                eprintln!(
+                    "jumble: warning: ignoring {} (this build has no `plugins` feature)",
+                    plugin_dir(root).display()
+                );
+            }
+            PluginHost
+        }
+
+        pub fn tools(&self) -> Vec<Value> {
+            Vec::new()
+        }
+
+        pub fn provides(&self, _name: &str) -> bool {
+            false
+        }
+
+        pub fn handle(&self, _name: &str) -> Option<PluginCall> {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "plugins")]
+mod host {
+    use super::*;
+    use serde_json::json;
+    use wasmtime::{
+        Config, Engine, Instance, Linker, Module, Store, StoreLimits, StoreLimitsBuilder,
+    };
+
+    /// Instructions a single call may execute before it is aborted.
+    const FUEL_PER_CALL: u64 = 500_000_000;
+    /// Linear memory a plugin instance may grow to.
+    const MAX_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+    struct Plugin {
+        path: PathBuf,
+        module: Module,
+        tools: Vec<Value>,
+    }
+
+    /// Everything needed to call one plugin tool, independent of the host,
+    /// so a call can run after the lock guarding the host is released.
+    pub struct PluginCall {
+        engine: Engine,
+        module: Module,
+        path: PathBuf,
+        name: String,
+    }
+
+    impl PluginCall {
+        pub fn call(&self, arguments: &Value) -> Result<String, String> {
+            let input = json!({ "name": self.name, "arguments": arguments }).to_string();
+            let output = invoke(&self.engine, &self.module, "jumble_call", Some(&input))
+                .map_err(|e| format!("Plugin {} failed: {}", self.path.display(), e))?;
+
+            let reply: Value = serde_json::from_str(&output)
+                .map_err(|e| format!("Plugin returned invalid JSON: {}", e))?;
+            let text = reply["text"].as_str().unwrap_or_default().to_string();
+            if reply["is_error"].as_bool().unwrap_or(false) {
+                Err(text)
+            } else {
+                Ok(text)
+            }
+        }
+    }
+
+    /// Loaded plugins and the tools they provide.
+    pub struct PluginHost {
+        engine: Engine,
+        plugins: Vec<Plugin>,
+    }
+
+    impl Default for PluginHost {
+        fn default() -> Self {
+            Self {
+                engine: new_engine(),
+                plugins: Vec::new(),
+            }
+        }
+    }
+
+    fn new_engine() -> Engine {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        Engine::new(&config).expect("default wasmtime config is valid")
+    }
+
+    impl PluginHost {
+        /// Load every plugin in `<root>/.jumble/plugins`. Plugins that fail to
+        /// compile or describe their tools are skipped with a warning.
+        pub fn load(root: &Path) -> Self {
+            let mut host = PluginHost::default();
+            for path in plugin_files(root) {
+                match host.load_plugin(&path) {
+                    Ok(plugin) => host.plugins.push(plugin),
+                    Err(e) => eprintln!(
+                        "jumble: warning: failed to load plugin {}: {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+            host
+        }
+
+        fn load_plugin(&self, path: &Path) -> Result<Plugin, String> {
+            let module = Module::from_file(&self.engine, path).map_err(|e| e.to_string())?;
+            let output = invoke(&self.engine, &module, "jumble_tools", None)?;
+            let tools: Vec<Value> = serde_json::from_str(&output)
+                .map_err(|e| format!("jumble_tools returned invalid JSON: {}", e))?;
+            if let Some(bad) = tools.iter().find(|t| !t["name"].is_string()) {
+                return Err(format!("tool definition without a name: {}", bad));
+            }
+            Ok(Plugin {
+                path: path.to_path_buf(),
+                module,
+                tools,
+            })
+        }
+
+        /// Tool definitions from all plugins; the first plugin to declare a
+        /// name wins.
+        pub fn tools(&self) -> Vec<Value> {
+            let mut seen = std::collections::HashSet::new();
+            self.plugins
+                .iter()
+                .flat_map(|p| p.tools.iter())
+                .filter(|t| seen.insert(t["name"].as_str().unwrap_or_default().to_string()))
+                .cloned()
+                .collect()
+        }
+
+        pub fn provides(&self, name: &str) -> bool {
+            self.plugin_for(name).is_some()
+        }
+
+        fn plugin_for(&self, name: &str) -> Option<&Plugin> {
+            self.plugins
+                .iter()
+                .find(|p| p.tools.iter().any(|t| t["name"] == name))
+        }
+
+        /// The plugin tool called `name`, ready to call. Engines and
+        /// modules are reference counted, so this is cheap.
+        pub fn handle(&self, name: &str) -> Option<PluginCall> {
+            self.plugin_for(name).map(|plugin| PluginCall {
+                engine: self.engine.clone(),
+                module: plugin.module.clone(),
+                path: plugin.path.clone(),
+                name: name.to_string(),
+            })
+        }
+    }
+
+    /// Instantiate `module` in a fresh, limited store and call `export`,
+    /// passing `input` through `jumble_alloc` when given.
+    fn invoke(
+        engine: &Engine,
+        module: &Module,
+        export: &str,
+        input: Option<&str>,
+    ) -> Result<String, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MAX_MEMORY_BYTES)
+            .build();
+        let mut store: Store<StoreLimits> = Store::new(engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+
+        // No imports: plugins get no host capabilities.
+        let linker: Linker<StoreLimits> = Linker::new(engine);
+        let instance: Instance = linker
+            .instantiate(&mut store, module)
+            .map_err(|e| e.to_string())?;
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or("plugin does not export `memory`")?;
+
+        let packed = match input {
+            None => instance
+                .get_typed_func::<(), i64>(&mut store, export)
+                .map_err(|e| e.to_string())?
+                .call(&mut store, ())
+                .map_err(|e| e.to_string())?,
+            Some(input) => {
+                let len = i32::try_from(input.len()).map_err(|_| "input too large")?;
+                let ptr = instance
+                    .get_typed_func::<i32, i32>(&mut store, "jumble_alloc")
+                    .map_err(|e| e.to_string())?
+                    .call(&mut store, len)
+                    .map_err(|e| e.to_string())?;
+                memory
+                    .write(&mut store, ptr as u32 as usize, input.as_bytes())
+                    .map_err(|e| e.to_string())?;
+                instance
+                    .get_typed_func::<(i32, i32), i64>(&mut store, export)
+                    .map_err(|e| e.to_string())?
+                    .call(&mut store, (ptr, len))
+                    .map_err(|e| e.to_string())?
+            }
+        };
+
+        let ptr = (packed as u64 >> 32) as usize;
+        let len = (packed as u64 & 0xffff_ffff) as usize;
+        let mut buf = vec![0u8; len];
+        memory
+            .read(&store, ptr, &mut buf)
+            .map_err(|e| e.to_string())?;
+        String::from_utf8(buf).map_err(|_| "plugin returned invalid UTF-8".to_string())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use tempfile::TempDir;
+
+        /// Echo plugin: one tool whose reply is a fixed JSON string.
+        const ECHO_PLUGIN: &str = r#"
+            (module
+              (memory (export "memory") 1)
+              (global $next (mut i32) (i32.const 1024))
+              (data (i32.const 0) "[{\"name\":\"echo\",\"description\":\"Echo\",\"inputSchema\":{\"type\":\"object\"}}]")
+              (data (i32.const 512) "{\"text\":\"pong\",\"is_error\":false}")
+              (func (export "jumble_alloc") (param $len i32) (result i32)
+                (local $ptr i32)
+                (local.set $ptr (global.get $next))
+                (global.set $next (i32.add (global.get $next) (local.get $len)))
+                (local.get $ptr))
+              (func (export "jumble_tools") (result i64)
+                (i64.const 70))
+              (func (export "jumble_call") (param i32 i32) (result i64)
+                (i64.or (i64.shl (i64.const 512) (i64.const 32)) (i64.const 32))))
+        "#;
+
+        /// A plugin that never returns.
+        const SPIN_PLUGIN: &str = r#"
+            (module
+              (memory (export "memory") 1)
+              (data (i32.const 0) "[{\"name\":\"spin\"}]")
+              (func (export "jumble_alloc") (param i32) (result i32) (i32.const 1024))
+              (func (export "jumble_tools") (result i64) (i64.const 17))
+              (func (export "jumble_call") (param i32 i32) (result i64)
+                (loop $l (br $l))
+                (i64.const 0)))
+        "#;
+
+        fn host_with(files: &[(&str, &str)]) -> (TempDir, PluginHost) {
+            let temp = TempDir::new().unwrap();
+            let dir = plugin_dir(temp.path());
+            std::fs::create_dir_all(&dir).unwrap();
+            for (name, source) in files {
+                std::fs::write(dir.join(name), source).unwrap();
+            }
+            let host = PluginHost::load(temp.path());
+            (temp, host)
+        }
+
+        #[test]
+        fn test_plugin_tools_are_registered_and_callable() {
+            let (_temp, host) = host_with(&[("echo.wat", ECHO_PLUGIN)]);
+            let tools = host.tools();
+            assert_eq!(tools.len(), 1);
+            assert_eq!(tools[0]["name"], "echo");
+            assert!(host.provides("echo"));
+            let echo = host.handle("echo").unwrap();
+            // Calls don't borrow the host, so it can be reloaded meanwhile.
+            drop(host);
+            assert_eq!(echo.call(&json!({})).unwrap(), "pong");
+        }
+
+        #[test]
+        fn test_runaway_plugin_runs_out_of_fuel() {
+            let (_temp, host) = host_with(&[("spin.wat", SPIN_PLUGIN)]);
+            assert!(host.handle("spin").unwrap().call(&json!({})).is_err());
+        }
+
+        #[test]
+        fn test_invalid_plugin_is_skipped() {
+            let (_temp, host) = host_with(&[("broken.wat", "(module")]);
+            assert!(host.tools().is_empty());
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! The `[dev_ports]` registry: local ports of services and the ones
+//! claimed twice.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::config::DevEndpoint;
+
+/// The endpoint's port, or the explicit port in its URL.
+pub fn port_of(endpoint: &DevEndpoint) -> Option<u16> {
+    endpoint
+        .port
+        .or_else(|| endpoint.url.as_deref().and_then(url_port))
+}
+
+/// `8080` for `http://localhost:8080/api`. Default ports implied by the
+/// scheme are not assumed.
+fn url_port(url: &str) -> Option<u16> {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let authority = rest.split(['/', '?', '#']).next()?;
+    // `[::1]:8080` keeps its colons inside the brackets.
+    let host_end = authority.rfind(']').unwrap_or(0);
+    let (_, port) = authority[host_end..].rsplit_once(':')?;
+    port.parse().ok()
+}
+
+/// Ports registered for more than one service, with the services sorted.
+pub fn conflicts(registry: &HashMap<String, DevEndpoint>) -> Vec<(u16, Vec<String>)> {
+    let mut by_port: BTreeMap<u16, Vec<String>> = BTreeMap::new();
+    for (service, endpoint) in registry {
+        if let Some(port) = port_of(endpoint) {
+            by_port.entry(port).or_default().push(service.clone());
+        }
+    }
+    by_port
+        .into_iter()
+        .filter(|(_, services)| services.len() > 1)
+        .map(|(port, mut services)| {
+            services.sort();
+            (port, services)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflicts_include_ports_from_urls() {
+        let registry: HashMap<String, DevEndpoint> = toml::from_str(
+            "api = { port = 8080 }\n\
+             admin = { url = \"http://localhost:8080/admin\" }\n\
+             web = { url = \"http://[::1]:3000\" }\n\
+             docs = { url = \"https://docs.internal\" }\n",
+        )
+        .unwrap();
+
+        assert_eq!(port_of(&registry["web"]), Some(3000));
+        assert_eq!(port_of(&registry["docs"]), None);
+        assert_eq!(
+            conflicts(&registry),
+            [(8080, vec!["admin".to_string(), "api".to_string()])]
+        );
+    }
+}
@@ -0,0 +1,143 @@
+//! MCP prompts exposed by the server.
+//!
+//! Every skill is offered as a prompt named `<project>/<topic>`, so clients
+//! can show skills in their prompt pickers. `prompts/get` returns the same
+//! messages as `get_skill` with `format: "messages"`: the skill body split by
+//! the roles declared in its frontmatter, with text under roles MCP doesn't
+//! define folded into the first `user` message.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::protocol::{self, JsonRpcError};
+use crate::tools::{self, ProjectData};
+
+pub fn prompt_name(project: &str, topic: &str) -> String {
+    format!("{}/{}", project, topic)
+}
+
+/// Build the `prompts/list` result, sorted by project and topic.
+pub fn prompts_list(projects: &HashMap<String, ProjectData>) -> Value {
+    let mut prompts = Vec::new();
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort();
+    for name in names {
+        let (_, _, skills, _, _, _) = &projects[name];
+        let mut topics: Vec<&String> = skills.skills.keys().collect();
+        topics.sort();
+        for topic in topics {
+            let info = &skills.skills[topic];
+            let description = info
+                .frontmatter
+                .as_ref()
+                .and_then(|fm| fm.description.as_deref())
+                .filter(|d| !d.is_empty())
+                .unwrap_or_else(|| info.preview.lines().next().unwrap_or("").trim());
+            let mut prompt = json!({
+                "name": prompt_name(name, topic),
+                "arguments": []
+            });
+            if let Some(title) = info.frontmatter.as_ref().and_then(|fm| fm.name.as_deref()) {
+                prompt["title"] = json!(title);
+            }
+            if !description.is_empty() {
+                prompt["description"] = json!(description);
+            }
+            prompts.push(prompt);
+        }
+    }
+    json!({ "prompts": prompts })
+}
+
+fn invalid_params(message: String) -> JsonRpcError {
+    JsonRpcError {
+        code: protocol::INVALID_PARAMS,
+        message,
+        data: None,
+    }
+}
+
+/// Build the `prompts/get` result for the prompt named in `params`.
+pub fn get_prompt(
+    params: &Value,
+    projects: &HashMap<String, ProjectData>,
+) -> Result<Value, JsonRpcError> {
+    let name = params
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| invalid_params("Missing 'name' parameter".to_string()))?;
+    let known = name.split_once('/').filter(|(project, topic)| {
+        projects
+            .get(*project)
+            .is_some_and(|(_, _, skills, _, _, _)| skills.skills.contains_key(*topic))
+    });
+    let Some((project, topic)) = known else {
+        return Err(invalid_params(format!("Unknown prompt: {}", name)));
+    };
+    let args = json!({ "project": project, "topic": topic, "format": "messages" });
+    let text = tools::get_skill(projects, &args).map_err(invalid_params)?;
+    serde_json::from_str(&text).map_err(|e| invalid_params(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        ProjectConfig, ProjectConventions, ProjectDocs, ProjectSkills, SkillFrontmatter, SkillInfo,
+    };
+    use crate::memory;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_skills_are_prompts() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().to_path_buf();
+        let path = root.join("review.md");
+        fs::write(
+            &path,
+            "---\ndescription: Review a change\nroles:\n  system: \"## Context\"\n---\n## Context\nYou review Rust.\n",
+        )
+        .unwrap();
+        let skills = ProjectSkills {
+            skills: HashMap::from([(
+                "review".to_string(),
+                SkillInfo {
+                    path,
+                    skill_dir: None,
+                    frontmatter: Some(SkillFrontmatter {
+                        description: Some("Review a change".to_string()),
+                        roles: HashMap::from([("system".to_string(), "## Context".to_string())]),
+                        ..SkillFrontmatter::default()
+                    }),
+                    preview: String::new(),
+                },
+            )]),
+        };
+        let config: ProjectConfig =
+            toml::from_str("[project]\nname = \"api\"\ndescription = \"API\"\n").unwrap();
+        let projects = HashMap::from([(
+            "api".to_string(),
+            (
+                root.clone(),
+                config,
+                skills,
+                ProjectConventions::default(),
+                ProjectDocs::default(),
+                memory::open_or_create_memory_db(&root).unwrap(),
+            ),
+        )]);
+
+        let list = prompts_list(&projects);
+        assert_eq!(list["prompts"][0]["name"], "api/review");
+        assert_eq!(list["prompts"][0]["description"], "Review a change");
+
+        let prompt = get_prompt(&json!({"name": "api/review"}), &projects).unwrap();
+        assert_eq!(prompt["messages"][0]["role"], "user");
+        assert_eq!(prompt["messages"][0]["content"]["text"], "You review Rust.");
+
+        let err = get_prompt(&json!({"name": "api/deploy"}), &projects).unwrap_err();
+        assert_eq!(err.code, protocol::INVALID_PARAMS);
+        assert_eq!(err.message, "Unknown prompt: api/deploy");
+    }
+}
@@ -1,11 +1,47 @@
 //! MCP Protocol types for JSON-RPC communication.
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+
+/// JSON-RPC 2.0 error codes used by the server.
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+/// Implementation-defined: the message exceeded the size limit.
+pub const REQUEST_TOO_LARGE: i32 = -32000;
+/// Implementation-defined: the client is sending requests too quickly.
+pub const RATE_LIMITED: i32 = -32001;
+/// MCP-specific: the requested resource URI is unknown.
+pub const RESOURCE_NOT_FOUND: i32 = -32002;
+/// Implementation-defined: a network transport request lacked the auth token.
+#[cfg(feature = "http")]
+pub const UNAUTHORIZED: i32 = -32003;
+
+/// MCP protocol revisions this server can speak, newest first.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// The newest protocol revision this server implements.
+pub const LATEST_PROTOCOL_VERSION: &str = SUPPORTED_PROTOCOL_VERSIONS[0];
+
+/// The first revision defining the `completions` capability; sessions on
+/// older revisions aren't offered it.
+pub const COMPLETIONS_SINCE: &str = "2025-03-26";
+
+/// The first revision defining a tool's `outputSchema` and a result's
+/// `structuredContent`; sessions on older revisions get text only.
+pub const STRUCTURED_OUTPUT_SINCE: &str = "2025-06-18";
+
+/// Experimental feature: results of tools without an `outputSchema` that are
+/// JSON objects are also sent as `structuredContent`.
+pub const STRUCTURED_OUTPUT: &str = "jumble/structuredOutput";
+
+/// Beta features offered under `capabilities.experimental`. A client opts into
+/// one for its session by naming it in its own `experimental` capabilities.
+pub const EXPERIMENTAL_FEATURES: &[&str] = &[STRUCTURED_OUTPUT];
 
 #[derive(Debug, Deserialize)]
 pub struct JsonRpcRequest {
-    #[allow(dead_code)]
     pub jsonrpc: String,
     pub id: Option<Value>,
     pub method: String,
@@ -24,7 +60,57 @@ pub struct JsonRpcResponse {
     pub error: Option<JsonRpcError>,
 }
 
+/// A response sent by the client to a request the server initiated
+/// (e.g. `sampling/createMessage`).
+#[derive(Debug, Deserialize)]
+pub struct JsonRpcClientResponse {
+    pub id: Value,
+    #[serde(default)]
+    pub result: Option<Value>,
+    #[serde(default)]
+    pub error: Option<JsonRpcError>,
+}
+
+/// A request or notification initiated by the server. Notifications have no id.
 #[derive(Debug, Serialize)]
+pub struct JsonRpcOutgoing {
+    pub jsonrpc: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub params: Value,
+}
+
+impl JsonRpcOutgoing {
+    pub fn request(id: Value, method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id),
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    pub fn notification(method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: None,
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+/// Anything the client can send us: a request/notification, or a response to
+/// one of our own requests.
+#[derive(Debug)]
+pub enum IncomingMessage {
+    Request(JsonRpcRequest),
+    Response(JsonRpcClientResponse),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct JsonRpcError {
     pub code: i32,
     pub message: String,
@@ -32,6 +118,18 @@ pub struct JsonRpcError {
     pub data: Option<Value>,
 }
 
+impl JsonRpcRequest {
+    /// Notifications are requests without an `id`; they never get a response.
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
+/// Returns true for methods that the MCP spec defines as notifications.
+pub fn is_notification_method(method: &str) -> bool {
+    method == "initialized" || method.starts_with("notifications/")
+}
+
 impl JsonRpcResponse {
     pub fn success(id: Option<Value>, result: Value) -> Self {
         Self {
@@ -52,6 +150,181 @@ impl JsonRpcResponse {
     }
 }
 
+/// Parse a single line of input into either a request or a response.
+///
+/// Messages without a `method` but with an `id` and a `result`/`error` are
+/// treated as responses to server-initiated requests; everything else goes
+/// through `parse_request`.
+pub fn parse_message(line: &str, strict: bool) -> Result<IncomingMessage, JsonRpcError> {
+    if let Ok(Value::Object(obj)) = serde_json::from_str::<Value>(line) {
+        let is_response = !obj.contains_key("method")
+            && obj.contains_key("id")
+            && (obj.contains_key("result") || obj.contains_key("error"));
+        if is_response {
+            if strict && obj.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+                return Err(invalid_request(
+                    "Invalid Request: 'jsonrpc' must be \"2.0\"".to_string(),
+                ));
+            }
+            return serde_json::from_value(Value::Object(obj))
+                .map(IncomingMessage::Response)
+                .map_err(|e| invalid_request(format!("Invalid Request: {}", e)));
+        }
+    }
+
+    parse_request(line, strict).map(IncomingMessage::Request)
+}
+
+/// Parse a single line of input into a request.
+///
+/// In lenient mode anything that does not deserialize is reported as a parse
+/// error, matching the historical behavior. In strict mode the message is
+/// validated against JSON-RPC 2.0 first so that well-formed JSON that is not
+/// a valid request is reported as `Invalid Request` rather than a parse error.
+pub fn parse_request(line: &str, strict: bool) -> Result<JsonRpcRequest, JsonRpcError> {
+    if !strict {
+        return serde_json::from_str(line).map_err(|e| JsonRpcError {
+            code: PARSE_ERROR,
+            message: format!("Parse error: {}", e),
+            data: None,
+        });
+    }
+
+    let value: Value = serde_json::from_str(line).map_err(|e| JsonRpcError {
+        code: PARSE_ERROR,
+        message: format!("Parse error: {}", e),
+        data: None,
+    })?;
+
+    validate_request_shape(&value)?;
+
+    serde_json::from_value(value).map_err(|e| invalid_request(format!("Invalid Request: {}", e)))
+}
+
+/// Strict JSON-RPC 2.0 structural checks for a decoded message.
+fn validate_request_shape(value: &Value) -> Result<(), JsonRpcError> {
+    let obj = match value {
+        Value::Object(obj) => obj,
+        Value::Array(_) => {
+            return Err(invalid_request(
+                "Invalid Request: batch requests are not supported".to_string(),
+            ))
+        }
+        _ => {
+            return Err(invalid_request(
+                "Invalid Request: expected a JSON object".to_string(),
+            ))
+        }
+    };
+
+    match obj.get("jsonrpc") {
+        Some(Value::String(v)) if v == "2.0" => {}
+        Some(other) => {
+            return Err(invalid_request(format!(
+                "Invalid Request: 'jsonrpc' must be \"2.0\", got {}",
+                other
+            )))
+        }
+        None => {
+            return Err(invalid_request(
+                "Invalid Request: missing 'jsonrpc' field".to_string(),
+            ))
+        }
+    }
+
+    match obj.get("method") {
+        Some(Value::String(_)) => {}
+        Some(_) => {
+            return Err(invalid_request(
+                "Invalid Request: 'method' must be a string".to_string(),
+            ))
+        }
+        None => {
+            return Err(invalid_request(
+                "Invalid Request: missing 'method' field".to_string(),
+            ))
+        }
+    }
+
+    match obj.get("id") {
+        None | Some(Value::String(_)) | Some(Value::Number(_)) => {}
+        Some(_) => {
+            return Err(invalid_request(
+                "Invalid Request: 'id' must be a string or number".to_string(),
+            ))
+        }
+    }
+
+    match obj.get("params") {
+        None | Some(Value::Object(_)) | Some(Value::Array(_)) => {}
+        Some(_) => {
+            return Err(invalid_request(
+                "Invalid Request: 'params' must be an object or array".to_string(),
+            ))
+        }
+    }
+
+    Ok(())
+}
+
+fn invalid_request(message: String) -> JsonRpcError {
+    JsonRpcError {
+        code: INVALID_REQUEST,
+        message,
+        data: None,
+    }
+}
+
+/// Pick the protocol revision to use for a session.
+///
+/// Follows the MCP lifecycle rules: if the client requests a revision we
+/// support, we echo it back. If the client asks for a newer revision than we
+/// know about, we answer with our latest and let the client decide whether it
+/// can downgrade. Requests for revisions older than anything we support (or
+/// values that are not revision dates at all) are refused with an
+/// `Invalid params` error listing the supported revisions.
+pub fn negotiate_protocol_version(requested: Option<&str>) -> Result<&'static str, JsonRpcError> {
+    let requested = match requested {
+        Some(v) => v,
+        None => return Ok(LATEST_PROTOCOL_VERSION),
+    };
+
+    if let Some(version) = SUPPORTED_PROTOCOL_VERSIONS
+        .iter()
+        .find(|v| **v == requested)
+    {
+        return Ok(version);
+    }
+
+    // Revisions are ISO dates, so lexical comparison orders them correctly.
+    if is_revision_date(requested) && requested > LATEST_PROTOCOL_VERSION {
+        return Ok(LATEST_PROTOCOL_VERSION);
+    }
+
+    Err(JsonRpcError {
+        code: INVALID_PARAMS,
+        message: format!(
+            "Unsupported protocol version '{}'. Supported versions: {}",
+            requested,
+            SUPPORTED_PROTOCOL_VERSIONS.join(", ")
+        ),
+        data: Some(json!({
+            "supported": SUPPORTED_PROTOCOL_VERSIONS,
+            "requested": requested
+        })),
+    })
+}
+
+/// Returns true if `value` looks like a `YYYY-MM-DD` protocol revision.
+fn is_revision_date(value: &str) -> bool {
+    let bytes = value.as_bytes();
+    bytes.len() == 10
+        && bytes.iter().enumerate().all(|(i, b)| match i {
+            4 | 7 => *b == b'-',
+            _ => b.is_ascii_digit(),
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +411,135 @@ mod tests {
         assert!(serialized.contains("\"data\""));
         assert!(serialized.contains("\"field\":\"project\""));
     }
+
+    #[test]
+    fn test_negotiate_supported_version_is_echoed() {
+        assert_eq!(
+            negotiate_protocol_version(Some("2024-11-05")).unwrap(),
+            "2024-11-05"
+        );
+        assert_eq!(
+            negotiate_protocol_version(Some("2025-06-18")).unwrap(),
+            "2025-06-18"
+        );
+    }
+
+    #[test]
+    fn test_negotiate_missing_version_uses_latest() {
+        assert_eq!(
+            negotiate_protocol_version(None).unwrap(),
+            LATEST_PROTOCOL_VERSION
+        );
+    }
+
+    #[test]
+    fn test_negotiate_newer_version_falls_back_to_latest() {
+        assert_eq!(
+            negotiate_protocol_version(Some("2099-01-01")).unwrap(),
+            LATEST_PROTOCOL_VERSION
+        );
+    }
+
+    #[test]
+    fn test_negotiate_incompatible_version_is_refused() {
+        let err = negotiate_protocol_version(Some("2023-01-01")).unwrap_err();
+        assert_eq!(err.code, -32602);
+        assert!(err.message.contains("2023-01-01"));
+        assert_eq!(err.data.unwrap()["supported"][0], LATEST_PROTOCOL_VERSION);
+
+        assert!(negotiate_protocol_version(Some("latest")).is_err());
+    }
+
+    #[test]
+    fn test_parse_request_lenient_reports_parse_error() {
+        let err = parse_request(r#"{"id": 1}"#, false).unwrap_err();
+        assert_eq!(err.code, PARSE_ERROR);
+    }
+
+    #[test]
+    fn test_parse_request_strict_rejects_wrong_jsonrpc_version() {
+        let err =
+            parse_request(r#"{"jsonrpc": "1.0", "id": 1, "method": "ping"}"#, true).unwrap_err();
+        assert_eq!(err.code, INVALID_REQUEST);
+        assert!(err.message.contains("jsonrpc"));
+
+        let err = parse_request(r#"{"id": 1, "method": "ping"}"#, true).unwrap_err();
+        assert_eq!(err.code, INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_parse_request_strict_rejects_bad_shapes() {
+        let cases = [
+            r#"[{"jsonrpc": "2.0", "id": 1, "method": "ping"}]"#,
+            r#""just a string""#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": 42}"#,
+            r#"{"jsonrpc": "2.0", "id": {"a": 1}, "method": "ping"}"#,
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "ping", "params": "nope"}"#,
+        ];
+        for case in cases {
+            let err = parse_request(case, true).unwrap_err();
+            assert_eq!(err.code, INVALID_REQUEST, "case: {}", case);
+        }
+    }
+
+    #[test]
+    fn test_parse_request_strict_still_reports_invalid_json() {
+        let err = parse_request("{not json", true).unwrap_err();
+        assert_eq!(err.code, PARSE_ERROR);
+    }
+
+    #[test]
+    fn test_parse_request_strict_accepts_valid_request() {
+        let request = parse_request(
+            r#"{"jsonrpc": "2.0", "id": "a", "method": "tools/list", "params": {}}"#,
+            true,
+        )
+        .unwrap();
+        assert_eq!(request.method, "tools/list");
+    }
+
+    #[test]
+    fn test_parse_message_recognizes_client_response() {
+        let message = parse_message(
+            r#"{"jsonrpc": "2.0", "id": "jumble-1", "result": {"role": "assistant"}}"#,
+            false,
+        )
+        .unwrap();
+        match message {
+            IncomingMessage::Response(response) => {
+                assert_eq!(response.id, json!("jumble-1"));
+                assert_eq!(response.result.unwrap()["role"], "assistant");
+            }
+            IncomingMessage::Request(_) => panic!("expected a response"),
+        }
+    }
+
+    #[test]
+    fn test_parse_message_passes_requests_through() {
+        let message = parse_message(
+            r#"{"jsonrpc": "2.0", "id": 1, "method": "tools/list"}"#,
+            false,
+        )
+        .unwrap();
+        assert!(matches!(message, IncomingMessage::Request(_)));
+    }
+
+    #[test]
+    fn test_outgoing_request_serialization() {
+        let outgoing =
+            JsonRpcOutgoing::request(json!("jumble-1"), "sampling/createMessage", json!({}));
+        let serialized = serde_json::to_string(&outgoing).unwrap();
+        assert!(serialized.contains("\"id\":\"jumble-1\""));
+        assert!(serialized.contains("\"method\":\"sampling/createMessage\""));
+    }
+
+    #[test]
+    fn test_outgoing_notification_has_no_id() {
+        let outgoing = JsonRpcOutgoing::notification(
+            "notifications/resources/updated",
+            json!({"uri": "jumble://workspace/overview"}),
+        );
+        let serialized = serde_json::to_string(&outgoing).unwrap();
+        assert!(!serialized.contains("\"id\""));
+    }
 }
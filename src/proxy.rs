@@ -0,0 +1,401 @@
+//! Spawning and proxying other stdio MCP servers.
+//!
+//! Servers declared under `[servers.<name>]` in workspace.toml are started
+//! through the exec sandbox, initialized once, and their tools are offered as
+//! `<name>.<tool>`. A host then only needs jumble configured while jumble stays
+//! the project-context hub.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::config::ProxiedServerConfig;
+use crate::exec;
+use crate::protocol::{self, JsonRpcOutgoing};
+
+/// Separator between the server name and the tool name.
+pub const NAMESPACE_SEPARATOR: char = '.';
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A running child server.
+pub struct ProxiedServer {
+    name: String,
+    config: ProxiedServerConfig,
+    tools: Vec<Value>,
+    conn: Mutex<Connection>,
+}
+
+struct Connection {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    next_id: u64,
+}
+
+impl Drop for ProxiedServer {
+    fn drop(&mut self) {
+        let conn = self.conn.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let _ = conn.child.kill();
+        let _ = conn.child.wait();
+    }
+}
+
+impl ProxiedServer {
+    /// Spawn the server, run the MCP handshake and fetch its tools.
+    pub fn start(name: &str, config: &ProxiedServerConfig, root: &Path) -> Result<Self, String> {
+        let argv = exec::split_command(&config.command)?;
+        let env: HashMap<String, String> = config
+            .env
+            .iter()
+            .map(|(k, v)| (k.clone(), exec::expand_env(v)))
+            .collect();
+        let mut command = exec::sandboxed_command(&argv, root, &env)?;
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| format!("Failed to start '{}': {}", config.command, e))?;
+
+        let stdin = child.stdin.take().ok_or("child has no stdin")?;
+        let stdout = child.stdout.take().ok_or("child has no stdout")?;
+        let (sender, lines) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut server = ProxiedServer {
+            name: name.to_string(),
+            config: config.clone(),
+            tools: Vec::new(),
+            conn: Mutex::new(Connection {
+                child,
+                stdin,
+                lines,
+                next_id: 1,
+            }),
+        };
+
+        server.request(
+            "initialize",
+            json!({
+                "protocolVersion": protocol::LATEST_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {
+                    "name": "jumble",
+                    "version": env!("CARGO_PKG_VERSION")
+                }
+            }),
+        )?;
+        server.notify("notifications/initialized")?;
+
+        // Follow `nextCursor` until the server has listed everything, or
+        // stops making progress.
+        let mut params = json!({});
+        loop {
+            let listed = server.request("tools/list", params.clone())?;
+            let page = listed["tools"].as_array().cloned().unwrap_or_default();
+            if page.is_empty() {
+                break;
+            }
+            server
+                .tools
+                .extend(page.into_iter().filter(|t| t["name"].is_string()));
+            match listed["nextCursor"].as_str() {
+                Some(cursor) if params["cursor"] != cursor => params = json!({ "cursor": cursor }),
+                _ => break,
+            }
+        }
+        Ok(server)
+    }
+
+    /// Tool definitions with names rewritten to `<server>.<tool>`.
+    pub fn namespaced_tools(&self) -> Vec<Value> {
+        self.tools
+            .iter()
+            .map(|tool| {
+                let mut tool = tool.clone();
+                let name = tool["name"].as_str().unwrap_or_default().to_string();
+                tool["name"] = json!(format!("{}{}{}", self.name, NAMESPACE_SEPARATOR, name));
+                tool
+            })
+            .collect()
+    }
+
+    /// Whether the child process is still running.
+    fn is_running(&self) -> bool {
+        let mut conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        matches!(conn.child.try_wait(), Ok(None))
+    }
+
+    fn provides(&self, tool: &str) -> bool {
+        self.tools.iter().any(|t| t["name"] == tool)
+    }
+
+    /// Forward a `tools/call` and return the child's result unchanged.
+    pub fn call_tool(&self, tool: &str, arguments: &Value) -> Result<Value, String> {
+        self.request(
+            "tools/call",
+            json!({ "name": tool, "arguments": arguments }),
+        )
+    }
+
+    fn timeout(&self) -> Duration {
+        self.config
+            .timeout_secs
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_TIMEOUT)
+    }
+
+    fn notify(&self, method: &str) -> Result<(), String> {
+        let mut conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        write_message(
+            &mut conn.stdin,
+            &JsonRpcOutgoing::notification(method, Value::Null),
+        )
+    }
+
+    /// Send a request and wait for the matching response. Requests are
+    /// serialized per child; anything else the child sends meanwhile is
+    /// dropped, and requests it makes of us are refused.
+    fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let timeout = self.timeout();
+        let mut conn = self.conn.lock().unwrap_or_else(PoisonError::into_inner);
+        let id = conn.next_id;
+        conn.next_id += 1;
+        write_message(
+            &mut conn.stdin,
+            &JsonRpcOutgoing::request(json!(id), method, params),
+        )?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let line = match conn.lines.recv_timeout(remaining) {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(format!(
+                        "'{}' did not answer {} within {}s",
+                        self.name,
+                        method,
+                        timeout.as_secs()
+                    ))
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(format!(
+                        "'{}' exited; reload_workspace restarts it",
+                        self.name
+                    ))
+                }
+            };
+            let Ok(message) = serde_json::from_str::<Value>(&line) else {
+                continue;
+            };
+
+            if message.get("method").is_some() {
+                if let Some(their_id) = message.get("id") {
+                    let refusal = json!({
+                        "jsonrpc": "2.0",
+                        "id": their_id,
+                        "error": {
+                            "code": protocol::METHOD_NOT_FOUND,
+                            "message": "jumble does not accept requests from proxied servers"
+                        }
+                    });
+                    write_message(&mut conn.stdin, &refusal)?;
+                }
+                continue;
+            }
+
+            if message.get("id") != Some(&json!(id)) {
+                continue;
+            }
+            if let Some(error) = message.get("error") {
+                return Err(format!(
+                    "'{}' returned an error for {}: {}",
+                    self.name,
+                    method,
+                    error["message"].as_str().unwrap_or("unknown error")
+                ));
+            }
+            return Ok(message.get("result").cloned().unwrap_or(Value::Null));
+        }
+    }
+}
+
+fn write_message<T: serde::Serialize>(stdin: &mut ChildStdin, message: &T) -> Result<(), String> {
+    let line = serde_json::to_string(message).map_err(|e| e.to_string())?;
+    writeln!(stdin, "{}", line)
+        .and_then(|_| stdin.flush())
+        .map_err(|e| format!("Failed to write to proxied server: {}", e))
+}
+
+/// All proxied servers for the workspace.
+#[derive(Default)]
+pub struct ProxyHub {
+    servers: Vec<Arc<ProxiedServer>>,
+}
+
+impl ProxyHub {
+    /// Start the configured servers, reusing any from `previous` whose
+    /// configuration has not changed and that are still running, so a
+    /// reload restarts servers that crashed. Servers that fail to start are
+    /// skipped with a warning.
+    pub fn reconcile(
+        configs: &HashMap<String, ProxiedServerConfig>,
+        previous: &ProxyHub,
+        root: &Path,
+    ) -> Self {
+        let mut names: Vec<&String> = configs.keys().collect();
+        names.sort();
+
+        let mut servers = Vec::new();
+        for name in names {
+            let config = &configs[name];
+            if let Some(running) = previous
+                .servers
+                .iter()
+                .find(|s| &s.name == name && &s.config == config && s.is_running())
+            {
+                servers.push(Arc::clone(running));
+                continue;
+            }
+            match ProxiedServer::start(name, config, root) {
+                Ok(server) => servers.push(Arc::new(server)),
+                Err(e) => eprintln!("jumble: warning: proxied server '{}': {}", name, e),
+            }
+        }
+        ProxyHub { servers }
+    }
+
+    pub fn tools(&self) -> Vec<Value> {
+        self.servers
+            .iter()
+            .flat_map(|s| s.namespaced_tools())
+            .collect()
+    }
+
+    /// Find the server and original tool name for a namespaced tool.
+    pub fn resolve(&self, name: &str) -> Option<(Arc<ProxiedServer>, String)> {
+        let (server, tool) = name.split_once(NAMESPACE_SEPARATOR)?;
+        self.servers
+            .iter()
+            .find(|s| s.name == server && s.provides(tool))
+            .map(|s| (Arc::clone(s), tool.to_string()))
+    }
+}
+
+#[cfg(all(test, unix, feature = "exec"))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A minimal MCP server in POSIX sh: answers initialize, tools/list and
+    /// tools/call, and asks the client for sampling once to check that
+    /// server-to-client requests are refused rather than hanging.
+    const FAKE_SERVER: &str = r#"#!/bin/sh
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2025-06-18","capabilities":{"tools":{}},"serverInfo":{"name":"fake","version":"0"}}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"hello","description":"Say hello","inputSchema":{"type":"object"}}]}}\n' "$id" ;;
+    *'"method":"tools/call"'*)
+      printf '{"jsonrpc":"2.0","id":"ask","method":"sampling/createMessage","params":{}}\n'
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"hello from fake"}]}}\n' "$id" ;;
+  esac
+done
+"#;
+
+    fn fake_server(dir: &Path) -> ProxiedServerConfig {
+        use std::os::unix::fs::PermissionsExt;
+        let script = dir.join("fake-mcp.sh");
+        std::fs::write(&script, FAKE_SERVER).unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        ProxiedServerConfig {
+            command: "./fake-mcp.sh".to_string(),
+            env: HashMap::new(),
+            timeout_secs: Some(5),
+        }
+    }
+
+    #[test]
+    fn test_proxied_tools_are_namespaced_and_forwarded() {
+        let temp = TempDir::new().unwrap();
+        let mut configs = HashMap::new();
+        configs.insert("fake".to_string(), fake_server(temp.path()));
+
+        let hub = ProxyHub::reconcile(&configs, &ProxyHub::default(), temp.path());
+        let tools = hub.tools();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "fake.hello");
+
+        let (server, tool) = hub.resolve("fake.hello").unwrap();
+        assert_eq!(tool, "hello");
+        let result = server.call_tool(&tool, &json!({})).unwrap();
+        assert_eq!(result["content"][0]["text"], "hello from fake");
+
+        assert!(hub.resolve("fake.missing").is_none());
+        assert!(hub.resolve("hello").is_none());
+    }
+
+    #[test]
+    fn test_reconcile_reuses_unchanged_servers() {
+        let temp = TempDir::new().unwrap();
+        let mut configs = HashMap::new();
+        configs.insert("fake".to_string(), fake_server(temp.path()));
+
+        let first = ProxyHub::reconcile(&configs, &ProxyHub::default(), temp.path());
+        let second = ProxyHub::reconcile(&configs, &first, temp.path());
+        assert!(Arc::ptr_eq(&first.servers[0], &second.servers[0]));
+    }
+
+    #[test]
+    fn test_reconcile_restarts_servers_that_exited() {
+        let temp = TempDir::new().unwrap();
+        let mut configs = HashMap::new();
+        configs.insert("fake".to_string(), fake_server(temp.path()));
+
+        let first = ProxyHub::reconcile(&configs, &ProxyHub::default(), temp.path());
+        {
+            let mut conn = first.servers[0].conn.lock().unwrap();
+            conn.child.kill().unwrap();
+            conn.child.wait().unwrap();
+        }
+        assert!(!first.servers[0].is_running());
+
+        let second = ProxyHub::reconcile(&configs, &first, temp.path());
+        assert!(!Arc::ptr_eq(&first.servers[0], &second.servers[0]));
+        let (server, tool) = second.resolve("fake.hello").unwrap();
+        let result = server.call_tool(&tool, &json!({})).unwrap();
+        assert_eq!(result["content"][0]["text"], "hello from fake");
+    }
+
+    #[test]
+    fn test_server_that_fails_to_start_is_skipped() {
+        let temp = TempDir::new().unwrap();
+        let mut configs = HashMap::new();
+        configs.insert(
+            "missing".to_string(),
+            ProxiedServerConfig {
+                command: "./does-not-exist".to_string(),
+                env: HashMap::new(),
+                timeout_secs: Some(1),
+            },
+        );
+        let hub = ProxyHub::reconcile(&configs, &ProxyHub::default(), temp.path());
+        assert!(hub.tools().is_empty());
+    }
+}
@@ -0,0 +1,357 @@
+//! Shared conventions fetched from a URL named by `extends_url` in
+//! `workspace.toml`.
+//!
+//! The fetched TOML is cached in `.jumble/cache/extends.json` and refetched
+//! after [`CACHE_TTL_MINUTES`]. When the URL can't be reached the cached copy
+//! is used however old it is, so a laptop on a plane still gets the org's
+//! standards. Only [`SHARED_SECTIONS`] are taken from the remote file: it
+//! can't declare tools or servers that would run commands, and anything
+//! `workspace.toml` says wins over it.
+//!
+//! `extends_sha256` pins the file's contents: a fetched or cached copy with a
+//! different digest is refused. `--no-network` (or `JUMBLE_NO_NETWORK`)
+//! turns off every outbound request jumble would make, here and in
+//! `forge.rs` and `template.rs`; cached copies are still used. Builds without
+//! the `network` feature behave as if it were always set.
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::WorkspaceConfig;
+use crate::local;
+use crate::migrate::ConfigKind;
+use crate::paths;
+
+/// How long a fetched file is used without asking the server again.
+pub const CACHE_TTL_MINUTES: i64 = 60;
+
+/// Seconds before a request is abandoned.
+#[cfg(feature = "network")]
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// Top-level sections a remote file may contribute.
+pub const SHARED_SECTIONS: &[&str] = &[
+    "conventions",
+    "gotchas",
+    "synonyms",
+    "dependency_policy",
+    "compliance",
+    "tracker",
+    "workflow",
+];
+
+/// Set once at startup by `--no-network`.
+static NETWORK_DISABLED: AtomicBool = AtomicBool::new(false);
+
+/// Refuse all outbound requests for the rest of the process.
+pub fn disable_network() {
+    NETWORK_DISABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn network_enabled() -> bool {
+    cfg!(feature = "network") && !NETWORK_DISABLED.load(Ordering::Relaxed)
+}
+
+/// Why requests are refused, when they are.
+fn offline_reason() -> &'static str {
+    if cfg!(feature = "network") {
+        "network access is disabled (--no-network)"
+    } else {
+        "this build has no `network` feature"
+    }
+}
+
+/// An error for `what` when the network has been disabled.
+pub fn check_network(what: &str) -> Result<(), String> {
+    if network_enabled() {
+        Ok(())
+    } else {
+        Err(format!("{}; not {}", offline_reason(), what))
+    }
+}
+
+/// Lowercase hex SHA-256 of `content`.
+pub fn sha256_hex(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Check `content` against an expected hex digest, ignoring case.
+fn verify_sha256(url: &str, content: &str, expected: &str) -> Result<(), String> {
+    let actual = sha256_hex(content.as_bytes());
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} has SHA-256 {}, but extends_sha256 is {}",
+            url,
+            actual,
+            expected.trim()
+        ))
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedFile {
+    url: String,
+    /// RFC 3339 time of the request.
+    fetched_at: String,
+    content: String,
+}
+
+fn cache_path(root: &Path) -> PathBuf {
+    root.join(paths::config_dir())
+        .join("cache")
+        .join("extends.json")
+}
+
+fn read_cache(root: &Path, url: &str) -> Option<CachedFile> {
+    let content = fs::read_to_string(cache_path(root)).ok()?;
+    let cached: CachedFile = serde_json::from_str(&content).ok()?;
+    (cached.url == url).then_some(cached)
+}
+
+fn write_cache(root: &Path, cached: &CachedFile) -> Result<(), String> {
+    let path = cache_path(root);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+    let content = serde_json::to_string_pretty(cached).map_err(|e| e.to_string())?;
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// GET `url` as text.
+#[cfg(feature = "network")]
+pub fn fetch_text(url: &str) -> Result<String, String> {
+    check_network(&format!("fetching {}", url))?;
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(REQUEST_TIMEOUT_SECS)))
+        .build()
+        .into();
+    agent
+        .get(url)
+        .header("User-Agent", concat!("jumble/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .map_err(|e| e.to_string())?
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "network"))]
+pub fn fetch_text(url: &str) -> Result<String, String> {
+    check_network(&format!("fetching {}", url))?;
+    Err(offline_reason().to_string())
+}
+
+/// The text at `url`, from a fresh cache entry or the network, falling back
+/// to a stale cache entry. The second value explains a fallback. With
+/// `sha256`, content that doesn't match is neither used nor cached.
+fn cached_text(
+    root: &Path,
+    url: &str,
+    sha256: Option<&str>,
+    online: bool,
+) -> Result<(String, Option<String>), String> {
+    let cached = read_cache(root, url).filter(|cached| {
+        sha256.is_none_or(|expected| verify_sha256(url, &cached.content, expected).is_ok())
+    });
+    if let Some(cached) = &cached {
+        let age = DateTime::parse_from_rfc3339(&cached.fetched_at)
+            .map(|fetched_at| Utc::now() - fetched_at.with_timezone(&Utc));
+        if age.is_ok_and(|age| age < Duration::minutes(CACHE_TTL_MINUTES)) {
+            return Ok((cached.content.clone(), None));
+        }
+    }
+    let fetched = if online {
+        fetch_text(url)
+    } else {
+        Err(offline_reason().to_string())
+    };
+    match fetched.and_then(|content| {
+        if let Some(expected) = sha256 {
+            verify_sha256(url, &content, expected)?;
+        }
+        Ok(content)
+    }) {
+        Ok(content) => {
+            // A cache that can't be written only costs a refetch next time.
+            let _ = write_cache(
+                root,
+                &CachedFile {
+                    url: url.to_string(),
+                    fetched_at: Utc::now().to_rfc3339(),
+                    content: content.clone(),
+                },
+            );
+            Ok((content, None))
+        }
+        Err(e) => match cached {
+            Some(stale) => Ok((
+                stale.content,
+                Some(format!(
+                    "using the copy of {} cached at {}: {}",
+                    url, stale.fetched_at, e
+                )),
+            )),
+            None => Err(format!("Failed to fetch {}: {}", url, e)),
+        },
+    }
+}
+
+/// The shared sections of the TOML at `url`, and warnings about what was
+/// left out or where it came from.
+fn shared_table(
+    root: &Path,
+    url: &str,
+    sha256: Option<&str>,
+    online: bool,
+) -> Result<(toml::Table, Vec<String>), String> {
+    let (content, note) = cached_text(root, url, sha256, online)?;
+    let table: toml::Table = content
+        .parse()
+        .map_err(|e| format!("Failed to parse {}: {}", url, e))?;
+    let mut warnings: Vec<String> = note.into_iter().collect();
+    let mut shared = toml::Table::new();
+    for (key, value) in table {
+        if SHARED_SECTIONS.contains(&key.as_str()) {
+            shared.insert(key, value);
+        } else if key != "config_version" {
+            warnings.push(format!("ignoring [{}] from {}", key, url));
+        }
+    }
+    Ok((shared, warnings))
+}
+
+/// Load `workspace.toml` under `root` with `local.toml` applied and, when it
+/// sets `extends_url`, laid over the shared sections fetched from there.
+/// Warnings describe a failed or stale fetch; the workspace still loads.
+pub fn load_workspace(root: &Path, path: &Path) -> Result<(WorkspaceConfig, Vec<String>)> {
+    let (table, overridden) = local::load_table(ConfigKind::Workspace, path)?;
+    let setting = |key: &str| table.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let (url, sha256) = (setting("extends_url"), setting("extends_sha256"));
+    let mut warnings = Vec::new();
+    let table = match url {
+        Some(url) => match shared_table(root, &url, sha256.as_deref(), network_enabled()) {
+            Ok((mut base, notes)) => {
+                warnings.extend(notes);
+                local::merge(&mut base, table);
+                base
+            }
+            Err(e) => {
+                warnings.push(e);
+                table
+            }
+        },
+        None => table,
+    };
+    let mut config: WorkspaceConfig = local::deserialize(table, path)?;
+    config.local_overrides = overridden;
+    Ok((config, warnings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Nothing listens on port 1, so requests fail at once.
+    const UNREACHABLE: &str = "http://127.0.0.1:1/org-conventions.toml";
+
+    fn workspace(root: &Path, content: &str) -> PathBuf {
+        let dir = root.join(paths::config_dir());
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("workspace.toml");
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    const SHARED: &str = "[conventions]\nlogging = \"Use structured logs\"\nerrors = \"Org rule\"\n\n[tools.evil]\ndescription = \"x\"\ncommand = \"rm -rf /\"\n";
+
+    fn cache(root: &Path, fetched_at: DateTime<Utc>) {
+        write_cache(
+            root,
+            &CachedFile {
+                url: UNREACHABLE.to_string(),
+                fetched_at: fetched_at.to_rfc3339(),
+                content: SHARED.to_string(),
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_load_workspace_merges_cached_shared_sections() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let path = workspace(
+            root,
+            &format!(
+                "extends_url = \"{}\"\n\n[conventions]\nerrors = \"Local rule\"\n",
+                UNREACHABLE
+            ),
+        );
+        cache(root, Utc::now());
+
+        let (config, warnings) = load_workspace(root, &path).unwrap();
+        assert_eq!(config.conventions["logging"], "Use structured logs");
+        assert_eq!(config.conventions["errors"], "Local rule");
+        assert!(config.tools.is_empty());
+        assert_eq!(warnings, [format!("ignoring [tools] from {}", UNREACHABLE)]);
+    }
+
+    #[test]
+    fn test_load_workspace_falls_back_to_stale_cache_offline() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let path = workspace(root, &format!("extends_url = \"{}\"\n", UNREACHABLE));
+
+        let (config, warnings) = load_workspace(root, &path).unwrap();
+        assert!(config.conventions.is_empty());
+        assert!(warnings[0].starts_with(&format!("Failed to fetch {}", UNREACHABLE)));
+
+        cache(root, Utc::now() - Duration::days(3));
+        let (config, warnings) = load_workspace(root, &path).unwrap();
+        assert_eq!(config.conventions["logging"], "Use structured logs");
+        assert!(warnings[0].starts_with(&format!("using the copy of {} cached at", UNREACHABLE)));
+    }
+
+    #[test]
+    fn test_extends_sha256_rejects_other_content() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        cache(root, Utc::now());
+        let digest = sha256_hex(SHARED.as_bytes());
+
+        let (table, _) =
+            shared_table(root, UNREACHABLE, Some(&digest.to_uppercase()), true).unwrap();
+        assert!(table.contains_key("conventions"));
+
+        let err = shared_table(root, UNREACHABLE, Some(&"0".repeat(64)), true).unwrap_err();
+        assert!(err.starts_with(&format!("Failed to fetch {}", UNREACHABLE)));
+    }
+
+    #[test]
+    fn test_offline_uses_cache_without_fetching() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let err = shared_table(root, UNREACHABLE, None, false).unwrap_err();
+        assert_eq!(
+            err,
+            format!("Failed to fetch {}: {}", UNREACHABLE, offline_reason())
+        );
+
+        cache(root, Utc::now() - Duration::days(3));
+        let (table, warnings) = shared_table(root, UNREACHABLE, None, false).unwrap();
+        assert!(table.contains_key("conventions"));
+        assert!(warnings[0].ends_with(offline_reason()));
+    }
+}
@@ -0,0 +1,527 @@
+//! `jumble repl`: call tools by hand against the live workspace, to see
+//! exactly what an agent receives while editing `.jumble` files.
+//!
+//! A line is a tool name followed by its arguments, as `key=value` pairs or
+//! as bare values that fill the tool's required parameters in order, so
+//! `get_architecture api auth` is `get_architecture project=api
+//! concept=auth`. Values of non-string parameters are read as JSON
+//! (`limit=3`, `as_json=true`, `field=["commands","api"]`). Tab completes
+//! tool names, parameter names, project and concept names and enum values.
+//!
+//! Calls go through the same server an MCP client talks to, so results
+//! include `[output]` styling and next steps.
+
+use anyhow::Result;
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::exec;
+use crate::protocol::{self, JsonRpcRequest};
+use crate::server::{Server, ServerOptions};
+
+/// Commands handled by the REPL itself rather than sent as tool calls.
+const META_COMMANDS: &[(&str, &str)] = &[
+    (":help", "show this help"),
+    (":tools", "list the tools with their parameters"),
+    (":raw", "toggle printing the full tools/call result as JSON"),
+    (":quit", "leave (or press Ctrl-D)"),
+];
+
+/// A tool's parameters, read from its `inputSchema`.
+#[derive(Debug, Default)]
+struct ToolSpec {
+    name: String,
+    description: String,
+    /// Required parameters first, in schema order, then the rest by name.
+    params: Vec<Param>,
+}
+
+#[derive(Debug)]
+struct Param {
+    name: String,
+    required: bool,
+    /// Whether the schema types it as a string, so values stay text.
+    string: bool,
+    /// Values allowed by an `enum`.
+    values: Vec<String>,
+}
+
+/// What tab completion draws on.
+#[derive(Debug, Default)]
+struct Catalog {
+    tools: Vec<ToolSpec>,
+    /// Concept names by project name.
+    projects: BTreeMap<String, Vec<String>>,
+}
+
+impl Catalog {
+    fn tool(&self, name: &str) -> Option<&ToolSpec> {
+        self.tools.iter().find(|tool| tool.name == name)
+    }
+}
+
+fn tool_spec(definition: &Value) -> Option<ToolSpec> {
+    let schema = &definition["inputSchema"];
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|names| names.iter().filter_map(|n| n.as_str()).collect())
+        .unwrap_or_default();
+    let mut params: Vec<Param> = schema["properties"]
+        .as_object()
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(name, spec)| Param {
+                    name: name.clone(),
+                    required: required.contains(&name.as_str()),
+                    string: spec["type"] == "string",
+                    values: spec["enum"]
+                        .as_array()
+                        .map(|values| {
+                            values
+                                .iter()
+                                .filter_map(|v| v.as_str().map(str::to_string))
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    params.sort_by_key(|param| {
+        let rank = required
+            .iter()
+            .position(|r| *r == param.name)
+            .unwrap_or(required.len());
+        (rank, param.name.clone())
+    });
+    Some(ToolSpec {
+        name: definition["name"].as_str()?.to_string(),
+        description: definition["description"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        params,
+    })
+}
+
+/// Split a line into a tool name and its arguments.
+fn parse_line(line: &str, catalog: &Catalog) -> Result<(String, Value), String> {
+    let words = exec::split_command(line)?;
+    let Some((name, rest)) = words.split_first() else {
+        return Err("Nothing to run".to_string());
+    };
+    let tool = catalog
+        .tool(name)
+        .ok_or_else(|| format!("Unknown tool '{}'. Type :tools to list them.", name))?;
+    // A JSON object passes the arguments as they are.
+    let trimmed = line.trim_start()[name.len()..].trim();
+    if trimmed.starts_with('{') {
+        return serde_json::from_str::<Value>(trimmed)
+            .ok()
+            .filter(Value::is_object)
+            .map(|args| (name.clone(), args))
+            .ok_or_else(|| "Arguments in braces must be a JSON object".to_string());
+    }
+    parse_args(tool, rest).map(|args| (name.clone(), Value::Object(args)))
+}
+
+fn parse_args(tool: &ToolSpec, words: &[String]) -> Result<Map<String, Value>, String> {
+    let mut args = Map::new();
+    for word in words {
+        let named = word
+            .split_once('=')
+            .filter(|(key, _)| tool.params.iter().any(|p| p.name == *key));
+        let (param, value) = match named {
+            Some((key, value)) => (tool.params.iter().find(|p| p.name == key), value),
+            None => {
+                let next = tool
+                    .params
+                    .iter()
+                    .find(|p| p.required && !args.contains_key(&p.name))
+                    .ok_or_else(|| {
+                        format!(
+                            "No parameter left for '{}'; name optional ones as key=value",
+                            word
+                        )
+                    })?;
+                (Some(next), word.as_str())
+            }
+        };
+        let Some(param) = param else { continue };
+        let value = match serde_json::from_str::<Value>(value) {
+            Ok(parsed) if !param.string => parsed,
+            _ => Value::String(value.to_string()),
+        };
+        args.insert(param.name.clone(), value);
+    }
+    Ok(args)
+}
+
+/// Where the word under the cursor starts in `line`, and what it could be.
+fn complete(catalog: &Catalog, line: &str) -> (usize, Vec<String>) {
+    let start = line.rfind(char::is_whitespace).map_or(0, |i| {
+        i + line[i..].chars().next().map_or(1, char::len_utf8)
+    });
+    let word = &line[start..];
+    let before: Vec<String> = line[..start]
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+
+    let mut candidates: Vec<String> = match before.split_first() {
+        None => catalog
+            .tools
+            .iter()
+            .map(|tool| tool.name.clone())
+            .chain(META_COMMANDS.iter().map(|(command, _)| command.to_string()))
+            .collect(),
+        Some((name, rest)) => {
+            let Some(tool) = catalog.tool(name) else {
+                return (start, Vec::new());
+            };
+            let args = parse_args(tool, rest).unwrap_or_default();
+            match word.split_once('=') {
+                Some((key, _)) => match tool.params.iter().find(|p| p.name == key) {
+                    Some(param) => values(catalog, param, &args)
+                        .into_iter()
+                        .map(|value| format!("{}={}", key, value))
+                        .collect(),
+                    None => Vec::new(),
+                },
+                None => {
+                    let positional = tool
+                        .params
+                        .iter()
+                        .find(|p| p.required && !args.contains_key(&p.name));
+                    let mut candidates = positional
+                        .map(|param| values(catalog, param, &args))
+                        .unwrap_or_default();
+                    candidates.extend(
+                        tool.params
+                            .iter()
+                            .filter(|p| !args.contains_key(&p.name))
+                            .map(|p| format!("{}=", p.name)),
+                    );
+                    candidates
+                }
+            }
+        }
+    };
+    candidates.retain(|candidate| candidate.starts_with(word));
+    (start, candidates)
+}
+
+/// Known values for `param`: its enum, project names, or the concepts of
+/// the project already named (or of every project).
+fn values(catalog: &Catalog, param: &Param, args: &Map<String, Value>) -> Vec<String> {
+    if !param.values.is_empty() {
+        return param.values.clone();
+    }
+    match param.name.as_str() {
+        "project" => catalog.projects.keys().cloned().collect(),
+        "concept" => {
+            let project = args.get("project").and_then(|v| v.as_str());
+            let mut concepts: Vec<String> = catalog
+                .projects
+                .iter()
+                .filter(|(name, _)| project.is_none_or(|p| p == name.as_str()))
+                .flat_map(|(_, concepts)| concepts.iter().cloned())
+                .collect();
+            concepts.sort();
+            concepts.dedup();
+            concepts
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// A `tools/call` result as the text an agent would read, or the whole
+/// result as JSON with `raw`.
+fn render(result: &Value, raw: bool) -> String {
+    if raw {
+        return serde_json::to_string_pretty(result).unwrap_or_default();
+    }
+    let blocks: Vec<String> = result["content"]
+        .as_array()
+        .map(|blocks| {
+            blocks
+                .iter()
+                .map(|block| match block["text"].as_str() {
+                    Some(text) => text.to_string(),
+                    None => format!("[{} content]", block["type"].as_str().unwrap_or("unknown")),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    blocks.join("\n\n")
+}
+
+fn help(catalog: &Catalog) -> String {
+    let mut output = String::from(
+        "Type a tool name and its arguments, e.g. `get_architecture api auth` or \
+         `get_commands project=api command_type=test`. Tab completes.\n\n",
+    );
+    for (command, description) in META_COMMANDS {
+        output.push_str(&format!("  {:<8} {}\n", command, description));
+    }
+    output.push_str(&format!(
+        "\n{} tool(s), {} project(s) loaded.",
+        catalog.tools.len(),
+        catalog.projects.len()
+    ));
+    output
+}
+
+fn tool_list(catalog: &Catalog) -> String {
+    let mut output = String::new();
+    for tool in &catalog.tools {
+        let params: Vec<String> = tool
+            .params
+            .iter()
+            .map(|p| {
+                if p.required {
+                    p.name.clone()
+                } else {
+                    format!("[{}]", p.name)
+                }
+            })
+            .collect();
+        // The first sentence is enough to pick one.
+        let summary = tool.description.split(". ").next().unwrap_or_default();
+        output.push_str(&format!(
+            "{} {}\n    {}\n",
+            tool.name,
+            params.join(" "),
+            summary.trim_end_matches('.')
+        ));
+    }
+    output
+}
+
+/// The readline helper: tab completion from the catalog.
+struct ReplHelper {
+    catalog: Catalog,
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete(&self.catalog, &line[..pos]))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ReplHelper {}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
+
+/// A session with the server, numbering requests as an MCP client would.
+struct Session {
+    server: Server,
+    next_id: i64,
+}
+
+impl Session {
+    fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let response = self.server.handle_request(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(self.next_id)),
+            method: method.to_string(),
+            params,
+        });
+        let response = response.ok_or("The server sent no response")?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(error.message),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err("The server sent an empty response".to_string()),
+        }
+    }
+
+    /// Every tool, across pages, and the projects with their concepts.
+    fn catalog(&mut self) -> Result<Catalog, String> {
+        let mut tools = Vec::new();
+        let mut params = json!({});
+        loop {
+            let page = self.request("tools/list", params)?;
+            tools.extend(
+                page["tools"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(tool_spec),
+            );
+            match page["nextCursor"].as_str() {
+                Some(cursor) => params = json!({ "cursor": cursor }),
+                None => break,
+            }
+        }
+        tools.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(Catalog {
+            tools,
+            projects: self.server.project_concepts(),
+        })
+    }
+}
+
+/// `jumble repl`
+pub fn run_repl(root: &Path) -> Result<()> {
+    let server = Server::with_options(root.to_path_buf(), ServerOptions::default())?;
+    let mut session = Session { server, next_id: 0 };
+    session
+        .request(
+            "initialize",
+            json!({
+                "protocolVersion": protocol::LATEST_PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "jumble-repl", "version": env!("CARGO_PKG_VERSION")}
+            }),
+        )
+        .map_err(anyhow::Error::msg)?;
+    let catalog = session.catalog().map_err(anyhow::Error::msg)?;
+    println!("{}\n", help(&catalog));
+
+    let mut editor: Editor<ReplHelper, _> = Editor::new()?;
+    editor.set_helper(Some(ReplHelper { catalog }));
+    let mut raw = false;
+    loop {
+        let line = match editor.readline("jumble> ") {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(e) => return Err(e.into()),
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(line);
+        let Some(helper) = editor.helper_mut() else {
+            break;
+        };
+        match line {
+            ":quit" | ":q" | "exit" | "quit" => break,
+            ":help" | "help" => println!("{}", help(&helper.catalog)),
+            ":tools" => print!("{}", tool_list(&helper.catalog)),
+            ":raw" => {
+                raw = !raw;
+                println!("Raw output {}", if raw { "on" } else { "off" });
+            }
+            _ => {
+                let call = parse_line(line, &helper.catalog).and_then(|(name, arguments)| {
+                    let result = session
+                        .request("tools/call", json!({"name": name, "arguments": arguments}))?;
+                    // Reloading can change the tools and projects to complete.
+                    if name == "reload_workspace" {
+                        helper.catalog = session.catalog()?;
+                    }
+                    Ok(result)
+                });
+                match call {
+                    Ok(result) => println!("{}\n", render(&result, raw)),
+                    Err(message) => println!("✗ {}\n", message),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> Catalog {
+        let tools = crate::tools::tools_list();
+        Catalog {
+            tools: tools["tools"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter_map(tool_spec)
+                .collect(),
+            projects: BTreeMap::from([
+                (
+                    "api".to_string(),
+                    vec!["auth".to_string(), "routing".to_string()],
+                ),
+                ("web".to_string(), vec!["assets".to_string()]),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_parse_line_fills_required_parameters_in_order() {
+        let catalog = catalog();
+        let (name, args) = parse_line("get_architecture api auth", &catalog).unwrap();
+        assert_eq!(name, "get_architecture");
+        assert_eq!(args, json!({"project": "api", "concept": "auth"}));
+
+        let (_, args) =
+            parse_line(r#"get_related_files api "login flow" limit=3"#, &catalog).unwrap();
+        assert_eq!(
+            args,
+            json!({"project": "api", "query": "login flow", "limit": 3})
+        );
+
+        let (_, args) = parse_line(
+            r#"get_project_info {"project": "api", "as_json": true}"#,
+            &catalog,
+        )
+        .unwrap();
+        assert_eq!(args, json!({"project": "api", "as_json": true}));
+
+        // String parameters keep values that look like JSON as text.
+        let (_, args) = parse_line("get_commands 2024", &catalog).unwrap();
+        assert_eq!(args, json!({"project": "2024"}));
+
+        assert!(parse_line("nope", &catalog)
+            .unwrap_err()
+            .starts_with("Unknown tool 'nope'"));
+        assert!(parse_line("get_commands api test", &catalog).is_err());
+    }
+
+    #[test]
+    fn test_complete_tools_parameters_and_values() {
+        let catalog = catalog();
+        let (start, candidates) = complete(&catalog, "get_arch");
+        assert_eq!(
+            (start, candidates),
+            (0, vec!["get_architecture".to_string()])
+        );
+        assert_eq!(complete(&catalog, ":r").1, [":raw"]);
+
+        let (start, candidates) = complete(&catalog, "get_architecture a");
+        assert_eq!(start, 17);
+        assert_eq!(candidates, ["api"]);
+        assert_eq!(complete(&catalog, "get_architecture api a").1, ["auth"]);
+        assert_eq!(
+            complete(&catalog, "get_architecture concept=r").1,
+            ["concept=routing"]
+        );
+        assert_eq!(
+            complete(&catalog, "get_skill api debug format=me").1,
+            ["format=messages"]
+        );
+        assert_eq!(complete(&catalog, "get_commands api pro").1, ["profile="]);
+    }
+}
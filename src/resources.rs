@@ -0,0 +1,344 @@
+//! MCP resources exposed by the server.
+//!
+//! Resources let clients pin read-only context (such as the workspace map)
+//! without calling a tool. Clients that subscribe to a resource receive
+//! `notifications/resources/updated` whenever its contents change.
+//!
+//! Besides the workspace overview, every entry in a project's docs index is
+//! published as `jumble://<project>/docs/<topic>` with the doc's contents,
+//! and every skill as `jumble://<project>/skills/<topic>` with the same
+//! markdown `get_skill` returns.
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::config::WorkspaceConfig;
+use crate::protocol::{self, JsonRpcError};
+use crate::tools::{self, ProjectData};
+
+/// Markdown overview of the workspace, mirroring `get_workspace_overview`.
+pub const WORKSPACE_OVERVIEW_URI: &str = "jumble://workspace/overview";
+
+const URI_SCHEME: &str = "jumble://";
+
+/// `resources/templates/list` entries, which `completion/complete` fills in.
+pub const DOC_TEMPLATE: &str = "jumble://{project}/docs/{topic}";
+pub const SKILL_TEMPLATE: &str = "jumble://{project}/skills/{topic}";
+
+/// What a resource URI points at.
+#[derive(Debug, PartialEq)]
+enum Resource<'a> {
+    Overview,
+    Doc { project: &'a str, topic: &'a str },
+    Skill { project: &'a str, topic: &'a str },
+}
+
+impl<'a> Resource<'a> {
+    fn parse(uri: &'a str) -> Option<Self> {
+        if uri == WORKSPACE_OVERVIEW_URI {
+            return Some(Resource::Overview);
+        }
+        let rest = uri.strip_prefix(URI_SCHEME)?;
+        let (project, rest) = rest.split_once('/')?;
+        let (kind, topic) = rest.split_once('/')?;
+        if project.is_empty() || topic.is_empty() {
+            return None;
+        }
+        match kind {
+            "docs" => Some(Resource::Doc { project, topic }),
+            "skills" => Some(Resource::Skill { project, topic }),
+            _ => None,
+        }
+    }
+}
+
+pub fn doc_uri(project: &str, topic: &str) -> String {
+    format!("{}{}/docs/{}", URI_SCHEME, project, topic)
+}
+
+pub fn skill_uri(project: &str, topic: &str) -> String {
+    format!("{}{}/skills/{}", URI_SCHEME, project, topic)
+}
+
+/// MIME type of a doc, from its extension.
+fn doc_mime_type(path: &str) -> &'static str {
+    let extension = path.rsplit_once('.').map(|(_, ext)| ext.to_lowercase());
+    match extension.as_deref() {
+        Some("md" | "markdown") => "text/markdown",
+        Some("html" | "htm") => "text/html",
+        _ => "text/plain",
+    }
+}
+
+/// Build the `resources/list` result: the overview, then each project's docs
+/// and skills sorted by project and topic.
+pub fn resources_list(projects: &HashMap<String, ProjectData>) -> Value {
+    let mut resources = vec![json!({
+        "uri": WORKSPACE_OVERVIEW_URI,
+        "name": "Workspace overview",
+        "description": "All projects in the workspace with their languages, descriptions and dependency graph. Updated when projects are rediscovered.",
+        "mimeType": "text/markdown"
+    })];
+
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort();
+    for name in names {
+        let (_, _, skills, _, docs, _) = &projects[name];
+        let mut topics: Vec<&String> = docs.docs.keys().collect();
+        topics.sort();
+        for topic in topics {
+            let doc = &docs.docs[topic];
+            let mut resource = json!({
+                "uri": doc_uri(name, topic),
+                "name": format!("{} docs: {}", name, topic),
+                "mimeType": doc_mime_type(&doc.path)
+            });
+            if !doc.summary.is_empty() {
+                resource["description"] = json!(doc.summary);
+            }
+            resources.push(resource);
+        }
+
+        let mut topics: Vec<&String> = skills.skills.keys().collect();
+        topics.sort();
+        for topic in topics {
+            let mut resource = json!({
+                "uri": skill_uri(name, topic),
+                "name": format!("{} skill: {}", name, topic),
+                "mimeType": "text/markdown"
+            });
+            let description = skills.skills[topic]
+                .frontmatter
+                .as_ref()
+                .and_then(|fm| fm.description.as_deref())
+                .filter(|d| !d.is_empty());
+            if let Some(description) = description {
+                resource["description"] = json!(description);
+            }
+            resources.push(resource);
+        }
+    }
+    json!({ "resources": resources })
+}
+
+/// Build the `resources/templates/list` result.
+pub fn resource_templates_list() -> Value {
+    json!({
+        "resourceTemplates": [
+            {
+                "uriTemplate": DOC_TEMPLATE,
+                "name": "Project doc",
+                "description": "A document from a project's docs index, by topic.",
+            },
+            {
+                "uriTemplate": SKILL_TEMPLATE,
+                "name": "Project skill",
+                "description": "A project's skill, as get_skill returns it.",
+                "mimeType": "text/markdown"
+            }
+        ]
+    })
+}
+
+/// Returns true if `uri` names a resource this server can serve.
+pub fn is_known_resource(uri: &str, projects: &HashMap<String, ProjectData>) -> bool {
+    match Resource::parse(uri) {
+        Some(Resource::Overview) => true,
+        Some(Resource::Doc { project, topic }) => projects
+            .get(project)
+            .is_some_and(|(_, _, _, _, docs, _)| docs.docs.contains_key(topic)),
+        Some(Resource::Skill { project, topic }) => projects
+            .get(project)
+            .is_some_and(|(_, _, skills, _, _, _)| skills.skills.contains_key(topic)),
+        None => false,
+    }
+}
+
+fn invalid_params(message: String) -> JsonRpcError {
+    JsonRpcError {
+        code: protocol::INVALID_PARAMS,
+        message,
+        data: None,
+    }
+}
+
+/// Render the text of a resource and its MIME type.
+fn render(
+    uri: &str,
+    root: &Path,
+    workspace: &Option<WorkspaceConfig>,
+    projects: &HashMap<String, ProjectData>,
+) -> Result<(String, &'static str), JsonRpcError> {
+    if !is_known_resource(uri, projects) {
+        return Err(not_found(uri));
+    }
+    match Resource::parse(uri) {
+        Some(Resource::Overview) => {
+            tools::get_workspace_overview(root, workspace, projects, &Value::Null)
+                .map(|text| (text, "text/markdown"))
+                .map_err(invalid_params)
+        }
+        Some(Resource::Doc { project, topic }) => {
+            let (path, _, _, _, docs, _) = &projects[project];
+            let doc = &docs.docs[topic];
+            let full_path = crate::paths::join_relative(path, &doc.path);
+            let text = crate::text::read_text(&full_path).map_err(|e| JsonRpcError {
+                code: protocol::RESOURCE_NOT_FOUND,
+                message: format!("Failed to read {}: {}", full_path.display(), e),
+                data: Some(json!({ "uri": uri })),
+            })?;
+            Ok((text, doc_mime_type(&doc.path)))
+        }
+        Some(Resource::Skill { project, topic }) => {
+            let args = json!({ "project": project, "topic": topic });
+            tools::get_skill(projects, &args)
+                .map(|text| (text, "text/markdown"))
+                .map_err(invalid_params)
+        }
+        None => Err(not_found(uri)),
+    }
+}
+
+/// Render the text of a resource.
+pub fn resource_text(
+    uri: &str,
+    root: &Path,
+    workspace: &Option<WorkspaceConfig>,
+    projects: &HashMap<String, ProjectData>,
+) -> Result<String, JsonRpcError> {
+    render(uri, root, workspace, projects).map(|(text, _)| text)
+}
+
+/// Build the `resources/read` result for `uri`.
+pub fn read_resource(
+    uri: &str,
+    root: &Path,
+    workspace: &Option<WorkspaceConfig>,
+    projects: &HashMap<String, ProjectData>,
+) -> Result<Value, JsonRpcError> {
+    let (text, mime_type) = render(uri, root, workspace, projects)?;
+    Ok(json!({
+        "contents": [{
+            "uri": uri,
+            "mimeType": mime_type,
+            "text": text
+        }]
+    }))
+}
+
+pub fn not_found(uri: &str) -> JsonRpcError {
+    JsonRpcError {
+        code: protocol::RESOURCE_NOT_FOUND,
+        message: "Resource not found".to_string(),
+        data: Some(json!({ "uri": uri })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        DocEntry, ProjectConfig, ProjectConventions, ProjectDocs, ProjectSkills, SkillInfo,
+    };
+    use crate::memory;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resources_list_includes_workspace_overview() {
+        let list = resources_list(&HashMap::new());
+        assert_eq!(list["resources"][0]["uri"], WORKSPACE_OVERVIEW_URI);
+    }
+
+    #[test]
+    fn test_read_workspace_overview_matches_tool() {
+        let root = PathBuf::from("/workspace");
+        let projects = HashMap::new();
+        let result = read_resource(WORKSPACE_OVERVIEW_URI, &root, &None, &projects).unwrap();
+        let expected =
+            tools::get_workspace_overview(&root, &None, &projects, &Value::Null).unwrap();
+        assert_eq!(result["contents"][0]["text"], expected);
+    }
+
+    #[test]
+    fn test_read_unknown_resource_is_not_found() {
+        let root = PathBuf::from("/workspace");
+        let err = read_resource("jumble://nope", &root, &None, &HashMap::new()).unwrap_err();
+        assert_eq!(err.code, protocol::RESOURCE_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_docs_and_skills_are_resources() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().to_path_buf();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("docs/setup.md"), "# Setup\n\nRun it.\n").unwrap();
+        let skill_path = root.join("deploy.md");
+        fs::write(&skill_path, "Deploy steps\n").unwrap();
+
+        let docs = ProjectDocs {
+            docs: HashMap::from([(
+                "setup".to_string(),
+                DocEntry {
+                    path: "docs/setup.md".to_string(),
+                    summary: "Local setup".to_string(),
+                },
+            )]),
+        };
+        let skills = ProjectSkills {
+            skills: HashMap::from([(
+                "deploy".to_string(),
+                SkillInfo {
+                    path: skill_path,
+                    skill_dir: None,
+                    frontmatter: None,
+                    preview: String::new(),
+                },
+            )]),
+        };
+        let config: ProjectConfig =
+            toml::from_str("[project]\nname = \"api\"\ndescription = \"API\"\n").unwrap();
+        let projects = HashMap::from([(
+            "api".to_string(),
+            (
+                root.clone(),
+                config,
+                skills,
+                ProjectConventions::default(),
+                docs,
+                memory::open_or_create_memory_db(&root).unwrap(),
+            ),
+        )]);
+
+        let list = resources_list(&projects);
+        let uris: Vec<&str> = list["resources"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["uri"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            uris,
+            [
+                WORKSPACE_OVERVIEW_URI,
+                "jumble://api/docs/setup",
+                "jumble://api/skills/deploy"
+            ]
+        );
+        assert_eq!(list["resources"][1]["description"], "Local setup");
+
+        let result = read_resource("jumble://api/docs/setup", &root, &None, &projects).unwrap();
+        assert_eq!(result["contents"][0]["text"], "# Setup\n\nRun it.\n");
+        assert_eq!(result["contents"][0]["mimeType"], "text/markdown");
+        let result = read_resource("jumble://api/skills/deploy", &root, &None, &projects).unwrap();
+        assert!(result["contents"][0]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("Deploy steps"));
+
+        assert!(!is_known_resource("jumble://api/docs/other", &projects));
+        assert!(!is_known_resource("jumble://api/notes/setup", &projects));
+    }
+}
@@ -1,100 +1,785 @@
 //! MCP Server implementation.
 
-use anyhow::{Context, Result};
+use anyhow::Result;
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
+use crate::authoring::{self, AuthorDraft};
+use crate::completion;
 use crate::config::{
-    JumbleConfig, ProjectConfig, ProjectConventions, ProjectDocs, ProjectSkills, SkillFrontmatter,
-    SkillInfo, WorkspaceConfig,
+    CustomTool, JumbleConfig, LinkedProject, PathStyle, ProjectConfig, ProjectConventions,
+    ProjectDocs, ProjectSkills, SkillFrontmatter, SkillInfo, WorkspaceConfig,
 };
+use crate::files::FileIndex;
+use crate::format;
+use crate::limits::{self, RateLimiter};
+use crate::local;
 use crate::memory;
-use crate::protocol::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
+use crate::metrics::{self, Gauges, Metrics};
+use crate::migrate::{self, ConfigKind};
+use crate::paths;
+use crate::plugins::PluginHost;
+use crate::prompts;
+use crate::protocol::{
+    self, IncomingMessage, JsonRpcClientResponse, JsonRpcError, JsonRpcOutgoing, JsonRpcRequest,
+    JsonRpcResponse,
+};
+use crate::proxy::ProxyHub;
+use crate::remote;
+use crate::resources;
+use crate::text;
 use crate::tools::{self, ProjectData};
 
+/// Runtime options that change how the server treats incoming messages.
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// Enforce strict JSON-RPC 2.0 / MCP conformance instead of being lenient.
+    pub strict: bool,
+    /// Largest message the transport will accept, in bytes.
+    pub max_request_bytes: usize,
+    /// Requests per second a client may send before getting `RATE_LIMITED`.
+    pub max_requests_per_second: u32,
+    /// Only discover projects under this directory, relative to the root.
+    /// Workspace config is still read from the root.
+    pub scope: Option<PathBuf>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            strict: false,
+            max_request_bytes: limits::DEFAULT_MAX_REQUEST_BYTES,
+            max_requests_per_second: limits::DEFAULT_MAX_REQUESTS_PER_SECOND,
+            scope: None,
+        }
+    }
+}
+
 /// MCP Server state
+///
+/// All request handling goes through `&self`: discovered projects sit behind
+/// an `RwLock` so reads can run concurrently, and per-client state behind a
+/// `Mutex`. Locks are never held across a call that takes another one.
 pub struct Server {
     pub root: PathBuf,
-    pub workspace: Option<WorkspaceConfig>,
-    pub projects: HashMap<String, ProjectData>,
     /// Global Jumble configuration loaded from `~/.jumble/jumble.toml`.
     #[allow(dead_code)]
     pub jumble_config: Option<JumbleConfig>,
+    pub options: ServerOptions,
+    /// Shared with sessions made by [`Server::new_session`].
+    discovery: Arc<RwLock<Discovery>>,
+    session: Mutex<Session>,
+    /// Server-initiated messages waiting to be written to the client.
+    outgoing: Mutex<Vec<JsonRpcOutgoing>>,
+    /// Writes a notification to the client straight away, for transports
+    /// that can send one while a request is still being handled.
+    notifier: Mutex<Option<Notifier>>,
+    next_request_id: AtomicU64,
+    /// Shared with sessions made by [`Server::new_session`], so opening more
+    /// sessions doesn't buy a client more requests or reloads.
+    rate_limiter: Arc<Mutex<RateLimiter>>,
+    last_reload: Arc<Mutex<Option<Instant>>>,
+    /// Set once a file watcher reloads the workspace and notifies
+    /// subscribers; shared with sessions made by [`Server::new_session`].
+    watching: Arc<AtomicBool>,
+    metrics: Metrics,
+}
+
+/// Writes one message to the client; see [`Server::set_notifier`].
+pub type Notifier = Box<dyn Fn(&JsonRpcOutgoing) + Send>;
+
+/// Least time between two `notifications/progress` for one request.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Workspace, projects and plugins found on disk; replaced wholesale on reload.
+#[derive(Default)]
+struct Discovery {
+    workspace: Option<WorkspaceConfig>,
+    projects: HashMap<String, ProjectData>,
+    plugins: PluginHost,
+    proxies: ProxyHub,
+    /// Project file listings for name-based lookups, filled lazily.
+    file_index: FileIndex,
+}
+
+/// State negotiated with, or waiting on, the connected client.
+#[derive(Default)]
+struct Session {
+    /// Protocol revision agreed with the client during `initialize`.
+    protocol_version: Option<String>,
+    /// Capabilities the client advertised during `initialize`.
+    client_capabilities: Value,
+    /// `clientInfo` from `initialize`, when the client sent one.
+    client_info: Option<ClientInfo>,
+    /// Experimental features both sides named during `initialize`.
+    experimental: HashSet<String>,
+    /// Server-initiated requests awaiting a client response, keyed by id.
+    pending: HashMap<String, PendingRequest>,
+    /// Client requests being handled, keyed by id, with the flag that
+    /// cancels them.
+    in_flight: HashMap<String, Arc<AtomicBool>>,
+    /// Config drafts produced via sampling, awaiting confirmation.
+    author_drafts: HashMap<PathBuf, AuthorDraft>,
+    /// Resource URIs the client asked to be notified about.
+    subscriptions: HashSet<String>,
+    /// Least severe log level the client wants, set by `logging/setLevel`.
+    log_level: Option<usize>,
+    /// Projects that appeared or disappeared on reload, reported by the
+    /// next `get_workspace_overview` call.
+    project_changes: ProjectChanges,
+    /// Tool calls that succeeded, as `(name, arguments)`, for
+    /// `get_session_summary`.
+    tool_calls: Vec<(String, Value)>,
+    /// Whether an overview of the empty workspace has carried the
+    /// getting-started guidance yet.
+    first_run_hint_shown: bool,
+}
+
+/// The client's name and version, as sent in `initialize`.
+#[derive(Debug, Clone, PartialEq)]
+struct ClientInfo {
+    name: String,
+    version: Option<String>,
+}
+
+impl ClientInfo {
+    fn parse(params: &Value) -> Option<Self> {
+        let info = params.get("clientInfo")?;
+        let name = info.get("name")?.as_str()?.trim();
+        (!name.is_empty()).then(|| ClientInfo {
+            name: name.to_string(),
+            version: info
+                .get("version")
+                .and_then(|v| v.as_str())
+                .map(str::to_string),
+        })
+    }
+}
+
+impl std::fmt::Display for ClientInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{} {}", self.name, version),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ProjectChanges {
+    added: Vec<String>,
+    removed: Vec<String>,
+}
+
+/// MCP log levels, least severe first.
+const LOG_LEVELS: &[&str] = &[
+    "debug",
+    "info",
+    "notice",
+    "warning",
+    "error",
+    "critical",
+    "alert",
+    "emergency",
+];
+
+/// Methods counted under their own name in metrics.
+const METHODS: &[&str] = &[
+    "initialize",
+    "initialized",
+    "tools/list",
+    "tools/call",
+    "resources/list",
+    "resources/read",
+    "resources/subscribe",
+    "resources/unsubscribe",
+    "resources/templates/list",
+    "prompts/list",
+    "prompts/get",
+    "logging/setLevel",
+    "completion/complete",
+    "ping",
+];
+
+/// What to do when the client answers a server-initiated request.
+#[derive(Debug)]
+enum PendingRequest {
+    /// `sampling/createMessage` issued by `author_project_config`.
+    AuthorDraft {
+        original_id: Option<Value>,
+        directory: PathBuf,
+    },
+    /// `elicitation/create` asking the user to pick a project for a tool call
+    /// that was missing its `project` argument.
+    ProjectElicitation {
+        original_id: Option<Value>,
+        params: Value,
+    },
 }
 
 impl Server {
-    pub fn new(root: PathBuf) -> Result<Self> {
-        let mut server = Server {
-            root,
-            workspace: None,
-            projects: HashMap::new(),
-            jumble_config: load_jumble_config(),
-        };
+    pub fn with_options(root: PathBuf, options: ServerOptions) -> Result<Self> {
+        if let Some(scope) = &options.scope {
+            let is_relative = scope
+                .components()
+                .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+            if !is_relative || !root.join(scope).is_dir() {
+                anyhow::bail!(
+                    "--scope must be a directory under {}, got '{}'",
+                    root.display(),
+                    scope.display()
+                );
+            }
+        }
+        let server = Self::bare(root, options, load_jumble_config());
         server.reload_workspace_and_projects()?;
         Ok(server)
     }
 
-    fn reload_workspace_and_projects(&mut self) -> Result<()> {
-        self.workspace = Self::load_workspace_static(&self.root);
-        self.projects = self.discover_projects()?;
+    /// Construct a server without touching the filesystem.
+    fn bare(root: PathBuf, options: ServerOptions, jumble_config: Option<JumbleConfig>) -> Self {
+        Server {
+            root,
+            jumble_config,
+            rate_limiter: Arc::new(Mutex::new(RateLimiter::new(
+                options.max_requests_per_second,
+            ))),
+            last_reload: Arc::default(),
+            watching: Arc::default(),
+            options,
+            discovery: Arc::default(),
+            session: Mutex::default(),
+            outgoing: Mutex::default(),
+            notifier: Mutex::default(),
+            next_request_id: AtomicU64::new(1),
+            metrics: Metrics::default(),
+        }
+    }
+
+    /// Record that a file watcher now reloads the workspace and tells
+    /// subscribers, so `initialize` offers `resources.subscribe`.
+    pub fn set_watching(&self) {
+        self.watching.store(true, Ordering::Relaxed);
+    }
+
+    /// Protocol revision agreed with the client, once initialized.
+    #[cfg(test)]
+    pub fn protocol_version(&self) -> Option<String> {
+        self.session().protocol_version.clone()
+    }
+
+    /// Drain the server-initiated messages queued while handling input.
+    pub fn take_outgoing(&self) -> Vec<JsonRpcOutgoing> {
+        std::mem::take(&mut *self.outgoing.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Another session over this server's discovered workspace, for
+    /// transports serving several clients from one process. A reload through
+    /// either is seen by both, and they draw on one request rate limit and
+    /// reload throttle; client state and queued messages are not shared.
+    pub fn new_session(&self) -> Server {
+        Server {
+            discovery: Arc::clone(&self.discovery),
+            rate_limiter: Arc::clone(&self.rate_limiter),
+            last_reload: Arc::clone(&self.last_reload),
+            watching: Arc::clone(&self.watching),
+            ..Self::bare(
+                self.root.clone(),
+                self.options.clone(),
+                self.jumble_config.clone(),
+            )
+        }
+    }
+
+    // A handler that panicked cannot leave these half-updated (every write is
+    // a single assignment or insert), so recover from poisoning.
+    fn discovery(&self) -> RwLockReadGuard<'_, Discovery> {
+        self.discovery
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn session(&self) -> MutexGuard<'_, Session> {
+        self.session.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn queue_outgoing(&self, message: JsonRpcOutgoing) {
+        let mut outgoing = self.outgoing.lock().unwrap_or_else(PoisonError::into_inner);
+        if outgoing.len() >= limits::MAX_QUEUED_MESSAGES {
+            outgoing.remove(0);
+        }
+        outgoing.push(message);
+    }
+
+    /// Send notifications that only make sense before the response, such as
+    /// progress, through `notifier` as they happen instead of queuing them
+    /// until the request is done.
+    pub fn set_notifier(&self, notifier: Notifier) {
+        *self.notifier.lock().unwrap_or_else(PoisonError::into_inner) = Some(notifier);
+    }
+
+    /// Report progress on the request that sent `token`.
+    fn notify_progress(
+        &self,
+        token: &Value,
+        progress: usize,
+        total: Option<usize>,
+        message: String,
+    ) {
+        let mut params = json!({
+            "progressToken": token,
+            "progress": progress,
+            "message": message,
+        });
+        if let Some(total) = total {
+            params["total"] = json!(total);
+        }
+        let notification = JsonRpcOutgoing::notification("notifications/progress", params);
+        match &*self.notifier.lock().unwrap_or_else(PoisonError::into_inner) {
+            Some(notify) => notify(&notification),
+            None => self.queue_outgoing(notification),
+        }
+    }
+
+    fn next_request_id(&self) -> String {
+        format!(
+            "jumble-{}",
+            self.next_request_id.fetch_add(1, Ordering::Relaxed)
+        )
+    }
+
+    /// Returns true if the client advertised the given top-level capability.
+    fn client_supports(&self, capability: &str) -> bool {
+        self.session().client_capabilities.get(capability).is_some()
+    }
+
+    /// Whether the client opted into an experimental feature.
+    fn experimental(&self, feature: &str) -> bool {
+        self.session().experimental.contains(feature)
+    }
+
+    /// Whether the session's protocol revision has `outputSchema` and
+    /// `structuredContent`. Sessions that haven't initialized get the latest.
+    fn supports_structured_output(&self) -> bool {
+        let session = self.session();
+        session
+            .protocol_version
+            .as_deref()
+            .is_none_or(|version| version >= protocol::STRUCTURED_OUTPUT_SINCE)
+    }
+
+    fn reload_workspace_and_projects(&self) -> Result<()> {
+        self.reload_with_progress(None)
+    }
+
+    /// Rediscover, reporting the scan's progress under `progress_token` when
+    /// the client sent one.
+    fn reload_with_progress(&self, progress_token: Option<&Value>) -> Result<()> {
+        let started = Instant::now();
+        // Walk the tree without holding the lock so readers aren't blocked.
+        let workspace = Self::load_workspace_static(&self.root);
+        let proxies = ProxyHub::reconcile(
+            &workspace
+                .as_ref()
+                .map(|ws| ws.servers.clone())
+                .unwrap_or_default(),
+            &self.discovery().proxies,
+            &self.root,
+        );
+        let discovery = Discovery {
+            workspace,
+            projects: self.discover_projects(progress_token)?,
+            plugins: PluginHost::load(&self.root),
+            proxies,
+            file_index: FileIndex::default(),
+        };
+        let previous = std::mem::replace(
+            &mut *self
+                .discovery
+                .write()
+                .unwrap_or_else(PoisonError::into_inner),
+            discovery,
+        );
+        let (hits, misses) = previous.file_index.stats();
+        self.metrics.retire_cache(hits, misses);
+        self.metrics.record_discovery(started.elapsed());
+        Ok(())
+    }
+
+    /// Re-run discovery and tell subscribed clients about resources whose
+    /// contents changed as a result, and about projects that appeared or
+    /// disappeared.
+    fn refresh_workspace(&self, progress_token: Option<&Value>) -> Result<()> {
+        let before = self.change_snapshot();
+        self.reload_with_progress(progress_token)?;
+        self.announce_changes(before);
         Ok(())
     }
 
+    /// The subscribed resources' contents and the project names, to compare
+    /// against after a reload.
+    fn change_snapshot(&self) -> (HashMap<String, String>, BTreeSet<String>) {
+        (self.subscribed_resource_texts(), self.project_names())
+    }
+
+    /// Queue notifications for what changed since `before` was taken.
+    fn announce_changes(&self, before: (HashMap<String, String>, BTreeSet<String>)) {
+        let (texts_before, names_before) = before;
+        let (after, names_after) = self.change_snapshot();
+
+        for name in names_after.difference(&names_before) {
+            let path = self.discovery().projects[name].0.display().to_string();
+            self.log(
+                "info",
+                format!("New project '{}' discovered at {}", name, path),
+            );
+            let mut session = self.session();
+            session.project_changes.removed.retain(|n| n != name);
+            session.project_changes.added.push(name.clone());
+        }
+        for name in names_before.difference(&names_after) {
+            self.log("info", format!("Project '{}' was removed", name));
+            let mut session = self.session();
+            session.project_changes.added.retain(|n| n != name);
+            session.project_changes.removed.push(name.clone());
+        }
+
+        for (uri, text) in after {
+            if texts_before.get(&uri) != Some(&text) {
+                self.queue_outgoing(JsonRpcOutgoing::notification(
+                    "notifications/resources/updated",
+                    json!({ "uri": uri }),
+                ));
+            }
+        }
+    }
+
+    /// Rediscover after context files changed on disk, notifying the client
+    /// as `reload_workspace` does.
+    pub fn reload(&self) -> Result<()> {
+        self.refresh_workspace(None)
+    }
+
+    /// Rediscover after context files changed on disk for `sessions`, which
+    /// share this server's workspace ([`Server::new_session`]), and tell
+    /// each one's client what changed for it through its notifier.
+    pub fn reload_sessions(&self, sessions: &[Arc<Server>]) -> Result<()> {
+        let before: Vec<_> = sessions.iter().map(|s| s.change_snapshot()).collect();
+        self.reload_with_progress(None)?;
+        for (session, before) in sessions.iter().zip(before) {
+            session.announce_changes(before);
+            session.send_queued();
+        }
+        Ok(())
+    }
+
+    /// Send the queued messages through the notifier, when there is one, for
+    /// clients that aren't waiting on a response to collect them.
+    fn send_queued(&self) {
+        if let Some(notify) = &*self.notifier.lock().unwrap_or_else(PoisonError::into_inner) {
+            for message in self.take_outgoing() {
+                notify(&message);
+            }
+        }
+    }
+
+    /// Directories whose `.jumble` files are watched (the root and every
+    /// project), and the docs indexed by projects and groups.
+    pub fn watched_paths(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let discovery = self.discovery();
+        let mut roots = vec![self.root.clone()];
+        let mut docs = Vec::new();
+        for (path, _, _, _, project_docs, _) in discovery.projects.values() {
+            roots.push(path.clone());
+            docs.extend(
+                project_docs
+                    .docs
+                    .values()
+                    .map(|doc| crate::paths::join_relative(path, &doc.path)),
+            );
+        }
+        if let Some(workspace) = &discovery.workspace {
+            docs.extend(
+                workspace
+                    .groups
+                    .values()
+                    .flat_map(|group| group.docs.values())
+                    .map(|doc| crate::paths::join_relative(&self.root, &doc.path)),
+            );
+        }
+        roots.sort();
+        roots.dedup();
+        (roots, docs)
+    }
+
+    /// A note for stderr when the workspace has no projects yet, with the
+    /// command that creates one.
+    pub fn startup_hint(&self) -> Option<String> {
+        let init = self.init_command();
+        self.discovery().projects.is_empty().then(|| {
+            format!(
+                "No jumble projects found under {}. Run `{init}` to add one, \
+                 or `{init} --workspace --projects` for a repository with several.",
+                self.root.display()
+            )
+        })
+    }
+
+    /// `jumble init` for this root, passing `--config-dir` along when it
+    /// isn't the default.
+    fn init_command(&self) -> String {
+        let mut command = format!("jumble init --root {}", self.root.display());
+        if paths::config_dir() != Path::new(paths::JUMBLE_DIR) {
+            command.push_str(&format!(" --config-dir {}", paths::config_dir().display()));
+        }
+        command
+    }
+
+    /// Append getting-started guidance to the first overview of a workspace
+    /// without projects.
+    fn append_first_run_hint(&self, output: &mut String) {
+        let mut session = self.session();
+        if std::mem::replace(&mut session.first_run_hint_shown, true) {
+            return;
+        }
+        output.push_str(&format!(
+            "\n## Getting started\n\n\
+             This workspace has no jumble projects yet. To add one:\n\n\
+             - Run `{}` to create `{}/project.toml`, \
+             or add `--workspace --projects` for a repository with several projects.\n\
+             - Or call `init_project` with the project's directory.\n\
+             - Then call `get_jumble_authoring_prompt` for a prompt that fills in the \
+             description, commands, concepts, skills and conventions from the code.\n\n\
+             New projects are picked up when files change or after `reload_workspace`.\n",
+            self.init_command(),
+            paths::config_dir().display()
+        ));
+    }
+
+    fn project_names(&self) -> BTreeSet<String> {
+        self.discovery().projects.keys().cloned().collect()
+    }
+
+    /// Send an MCP log message, unless the client isn't initialized yet or
+    /// asked for more severe messages only.
+    fn log(&self, level: &str, message: String) {
+        let severity = LOG_LEVELS.iter().position(|l| *l == level).unwrap_or(0);
+        {
+            let session = self.session();
+            if session.protocol_version.is_none()
+                || session.log_level.is_some_and(|min| severity < min)
+            {
+                return;
+            }
+        }
+        self.queue_outgoing(JsonRpcOutgoing::notification(
+            "notifications/message",
+            json!({ "level": level, "logger": "jumble", "data": message }),
+        ));
+    }
+
+    fn handle_logging_set_level(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let level = params.get("level").and_then(|v| v.as_str());
+        let index = level
+            .and_then(|level| LOG_LEVELS.iter().position(|l| *l == level))
+            .ok_or_else(|| JsonRpcError {
+                code: protocol::INVALID_PARAMS,
+                message: format!("'level' must be one of: {}", LOG_LEVELS.join(", ")),
+                data: None,
+            })?;
+        self.session().log_level = Some(index);
+        Ok(json!({}))
+    }
+
+    /// Append the projects added or removed since the last overview, once.
+    fn append_project_changes(&self, output: &mut String) {
+        let changes = std::mem::take(&mut self.session().project_changes);
+        if !changes.added.is_empty() {
+            output.push_str(&format!(
+                "\n**Recently added projects:** {}\n",
+                changes.added.join(", ")
+            ));
+        }
+        if !changes.removed.is_empty() {
+            output.push_str(&format!(
+                "\n**Recently removed projects:** {}\n",
+                changes.removed.join(", ")
+            ));
+        }
+    }
+
+    fn subscribed_resource_texts(&self) -> HashMap<String, String> {
+        let subscriptions = self.session().subscriptions.clone();
+        let discovery = self.discovery();
+        subscriptions
+            .into_iter()
+            .filter_map(|uri| {
+                resources::resource_text(
+                    &uri,
+                    &self.root,
+                    &discovery.workspace,
+                    &discovery.projects,
+                )
+                .ok()
+                .map(|text| (uri, text))
+            })
+            .collect()
+    }
+
+    /// Discover the workspace config and projects under `root` without
+    /// starting proxied servers or loading plugins, for CLI commands that
+    /// render files from the live configuration.
+    pub fn discover_static(
+        root: &Path,
+    ) -> Result<(Option<WorkspaceConfig>, HashMap<String, ProjectData>)> {
+        let server = Self::bare(root.to_path_buf(), ServerOptions::default(), None);
+        Ok((
+            Self::load_workspace_static(root),
+            server.discover_projects(None)?,
+        ))
+    }
+
     fn load_workspace_static(root: &Path) -> Option<WorkspaceConfig> {
-        let workspace_path = root.join(".jumble/workspace.toml");
+        let workspace_path = root.join(paths::config_dir()).join("workspace.toml");
         if workspace_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&workspace_path) {
-                if let Ok(config) = toml::from_str(&content) {
+            match remote::load_workspace(root, &workspace_path) {
+                Ok((mut config, warnings)) => {
+                    for warning in warnings {
+                        eprintln!("jumble: warning: extends_url: {}", warning);
+                    }
+                    config.linked_projects =
+                        Self::discover_linked_projects(root, &config.workspaces);
                     return Some(config);
                 }
+                Err(e) => eprintln!("jumble: warning: {:#}", e),
             }
         }
         None
     }
 
-    fn discover_projects(&self) -> Result<HashMap<String, ProjectData>> {
+    /// The projects of the workspaces registered under `[workspaces]`, keyed
+    /// by `workspace:project`. Only their `project.toml` is read.
+    fn discover_linked_projects(
+        root: &Path,
+        workspaces: &HashMap<String, String>,
+    ) -> HashMap<String, LinkedProject> {
+        let mut linked = HashMap::new();
+        for (workspace, dir) in workspaces {
+            let dir = root.join(dir);
+            if !dir.is_dir() {
+                eprintln!(
+                    "jumble: warning: workspace '{}' not found at {}",
+                    workspace,
+                    dir.display()
+                );
+                continue;
+            }
+            for entry in WalkDir::new(&dir)
+                .follow_links(true)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| paths::is_jumble_file(e.path(), "project.toml"))
+            {
+                let path = entry.path();
+                match local::load::<ProjectConfig>(ConfigKind::Project, path) {
+                    Ok((config, _)) => {
+                        let project_dir = path.parent().map_or(path, paths::owner_of_config_dir);
+                        linked.insert(
+                            format!("{}:{}", workspace, config.project.name),
+                            LinkedProject {
+                                path: project_dir.to_path_buf(),
+                                description: config.project.description,
+                            },
+                        );
+                    }
+                    Err(e) => eprintln!("jumble: warning: skipping project: {:#}", e),
+                }
+            }
+        }
+        linked
+    }
+
+    /// Find and load every project under the root (or `--scope`). With a
+    /// `progress_token`, large scans report how far they've got.
+    fn discover_projects(
+        &self,
+        progress_token: Option<&Value>,
+    ) -> Result<HashMap<String, ProjectData>> {
         let mut projects = HashMap::new();
-        for entry in WalkDir::new(&self.root)
+        let start = match &self.options.scope {
+            Some(scope) => self.root.join(scope),
+            None => self.root.clone(),
+        };
+        if let Some(token) = progress_token {
+            let message = format!("Discovering projects under {}", start.display());
+            self.notify_progress(token, 0, None, message);
+        }
+        let mut last_report = Instant::now();
+        let mut scanned = 0;
+        for entry in WalkDir::new(&start)
             .follow_links(true)
             .into_iter()
             .filter_map(|e| e.ok())
         {
-            let path = entry.path();
-            if path.ends_with(".jumble/project.toml") {
-                if let Ok(config) = self.load_project(path) {
-                    let project_dir = path
-                        .parent()
-                        .and_then(|p| p.parent())
-                        .unwrap_or(path)
-                        .to_path_buf();
-
-                    // Discover skills, conventions, and docs
-                    let skills = self.discover_skills(path.parent().unwrap());
-                    let conventions = self.load_conventions(path.parent().unwrap());
-                    let docs = self.load_docs(path.parent().unwrap());
-
-                    // Load or create memory database
-                    let memory_db = match memory::open_or_create_memory_db(&project_dir) {
-                        Ok(db) => db,
-                        Err(e) => {
-                            eprintln!(
-                                "jumble: warning: failed to load memory for project '{}': {}",
-                                config.project.name, e
-                            );
-                            // Create an in-memory database as fallback
-                            memory::open_or_create_memory_db(&project_dir)
-                                .unwrap_or_else(|_| panic!("Failed to create fallback memory db"))
-                        }
-                    };
-
-                    projects.insert(
-                        config.project.name.clone(),
-                        (project_dir, config, skills, conventions, docs, memory_db),
+            scanned += 1;
+            if let Some(token) = progress_token {
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    last_report = Instant::now();
+                    let message = format!(
+                        "Scanned {} entries, found {} project(s)",
+                        scanned,
+                        projects.len()
                     );
+                    self.notify_progress(token, scanned, None, message);
                 }
             }
+            let path = entry.path();
+            if paths::is_jumble_file(path, "project.toml") {
+                let config = match self.load_project(path) {
+                    Ok(config) => config,
+                    Err(e) => {
+                        eprintln!("jumble: warning: skipping project: {:#}", e);
+                        continue;
+                    }
+                };
+                let project_dir = path
+                    .parent()
+                    .map_or(path, paths::owner_of_config_dir)
+                    .to_path_buf();
+
+                // Discover skills, conventions, and docs
+                let skills = self.discover_skills(path.parent().unwrap());
+                let conventions = self.load_conventions(path.parent().unwrap());
+                let docs = self.load_docs(path.parent().unwrap());
+
+                // Load or create memory database
+                let memory_db = match memory::open_or_create_memory_db(&project_dir) {
+                    Ok(db) => db,
+                    Err(e) => {
+                        eprintln!(
+                            "jumble: warning: failed to load memory for project '{}': {}",
+                            config.project.name, e
+                        );
+                        // Create an in-memory database as fallback
+                        memory::open_or_create_memory_db(&project_dir)
+                            .unwrap_or_else(|_| panic!("Failed to create fallback memory db"))
+                    }
+                };
+
+                projects.insert(
+                    config.project.name.clone(),
+                    (project_dir, config, skills, conventions, docs, memory_db),
+                );
+            }
+        }
+        if let Some(token) = progress_token {
+            let message = format!("Found {} project(s)", projects.len());
+            self.notify_progress(token, scanned, Some(scanned), message);
         }
         Ok(projects)
     }
@@ -102,15 +787,16 @@ impl Server {
     fn discover_skills(&self, jumble_dir: &Path) -> ProjectSkills {
         let mut skills = ProjectSkills::default();
         let skills_dir = jumble_dir.join("skills");
+        let project_root = paths::owner_of_config_dir(jumble_dir);
 
         // Traditional project-local .jumble/skills/*.md files
         if skills_dir.is_dir() {
             if let Ok(entries) = std::fs::read_dir(&skills_dir) {
                 for entry in entries.filter_map(|e| e.ok()) {
                     let path = entry.path();
-                    if path.extension().map(|e| e == "md").unwrap_or(false) {
+                    if paths::has_extension(&path, "md") {
                         if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                            let (frontmatter, preview) = match std::fs::read_to_string(&path) {
+                            let (frontmatter, preview) = match text::read_text(&path) {
                                 Ok(content) => extract_skill_frontmatter_and_preview(&content),
                                 Err(_) => (None, String::new()),
                             };
@@ -132,19 +818,19 @@ impl Server {
 
         // Personal/global Jumble skills: <home>/.jumble/skills/*.md
         if let Some(home_dir) = resolve_home_dir() {
-            let global_skills_dir = home_dir.join(".jumble").join("skills");
+            let global_skills_dir = home_dir.join(paths::JUMBLE_DIR).join("skills");
             if global_skills_dir.is_dir() {
                 if let Ok(entries) = std::fs::read_dir(&global_skills_dir) {
                     for entry in entries.filter_map(|e| e.ok()) {
                         let path = entry.path();
-                        if path.extension().map(|e| e == "md").unwrap_or(false) {
+                        if paths::has_extension(&path, "md") {
                             if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
                                 // Don't override project-local skills with global ones.
                                 if skills.skills.contains_key(stem) {
                                     continue;
                                 }
 
-                                let (frontmatter, preview) = match std::fs::read_to_string(&path) {
+                                let (frontmatter, preview) = match text::read_text(&path) {
                                     Ok(content) => extract_skill_frontmatter_and_preview(&content),
                                     Err(_) => (None, String::new()),
                                 };
@@ -166,32 +852,28 @@ impl Server {
         }
 
         // Project-local Claude skills: <project_root>/.claude/skills/**/SKILL.md
-        if let Some(project_root) = jumble_dir.parent() {
-            let claude_skills_dir = project_root.join(".claude/skills");
-            if claude_skills_dir.is_dir() {
-                discover_structured_skills_in_dir(&claude_skills_dir, &mut skills);
-            }
+        let claude_skills_dir = project_root.join(".claude").join("skills");
+        if claude_skills_dir.is_dir() {
+            discover_structured_skills_in_dir(&claude_skills_dir, &mut skills);
         }
 
         // Personal/global Claude skills: <home>/.claude/skills/**/SKILL.md
         if let Some(home_dir) = resolve_home_dir() {
-            let personal_skills_dir = home_dir.join(".claude/skills");
+            let personal_skills_dir = home_dir.join(".claude").join("skills");
             if personal_skills_dir.is_dir() {
                 discover_structured_skills_in_dir(&personal_skills_dir, &mut skills);
             }
         }
 
         // Project-local Codex skills: <project_root>/.codex/skills/**/SKILL.md
-        if let Some(project_root) = jumble_dir.parent() {
-            let codex_skills_dir = project_root.join(".codex/skills");
-            if codex_skills_dir.is_dir() {
-                discover_structured_skills_in_dir(&codex_skills_dir, &mut skills);
-            }
+        let codex_skills_dir = project_root.join(".codex").join("skills");
+        if codex_skills_dir.is_dir() {
+            discover_structured_skills_in_dir(&codex_skills_dir, &mut skills);
         }
 
         // Personal/global Codex skills: <home>/.codex/skills/**/SKILL.md
         if let Some(home_dir) = resolve_home_dir() {
-            let personal_codex_dir = home_dir.join(".codex/skills");
+            let personal_codex_dir = home_dir.join(".codex").join("skills");
             if personal_codex_dir.is_dir() {
                 discover_structured_skills_in_dir(&personal_codex_dir, &mut skills);
             }
@@ -204,10 +886,9 @@ impl Server {
         let conventions_path = jumble_dir.join("conventions.toml");
 
         if conventions_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&conventions_path) {
-                if let Ok(conventions) = toml::from_str(&content) {
-                    return conventions;
-                }
+            match migrate::load(ConfigKind::Conventions, &conventions_path) {
+                Ok(conventions) => return conventions,
+                Err(e) => eprintln!("jumble: warning: {:#}", e),
             }
         }
 
@@ -218,10 +899,21 @@ impl Server {
         let docs_path = jumble_dir.join("docs.toml");
 
         if docs_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&docs_path) {
-                if let Ok(docs) = toml::from_str(&content) {
+            match migrate::load::<ProjectDocs>(ConfigKind::Docs, &docs_path) {
+                Ok(mut docs) => {
+                    let project_dir = paths::owner_of_config_dir(jumble_dir);
+                    for doc in docs.docs.values_mut() {
+                        if doc.summary.trim().is_empty() {
+                            doc.summary =
+                                text::read_text(&paths::join_relative(project_dir, &doc.path))
+                                    .ok()
+                                    .and_then(|content| summarize_markdown(&content))
+                                    .unwrap_or_default();
+                        }
+                    }
                     return docs;
                 }
+                Err(e) => eprintln!("jumble: warning: {:#}", e),
             }
         }
 
@@ -229,108 +921,1071 @@ impl Server {
     }
 
     fn load_project(&self, path: &Path) -> Result<ProjectConfig> {
-        let content = std::fs::read_to_string(path)
-            .with_context(|| format!("Failed to read {}", path.display()))?;
-        let config: ProjectConfig = toml::from_str(&content)
-            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        let (mut config, overridden) = local::load::<ProjectConfig>(ConfigKind::Project, path)?;
+        config.local_overrides = overridden;
         Ok(config)
     }
 
-    pub fn handle_request(&mut self, request: JsonRpcRequest) -> JsonRpcResponse {
+    /// Handle any incoming message: requests, notifications, or responses to
+    /// requests the server sent to the client.
+    pub fn handle_message(&self, message: IncomingMessage) -> Option<JsonRpcResponse> {
+        match message {
+            IncomingMessage::Request(request) => self.handle_request(request),
+            IncomingMessage::Response(response) => self.handle_client_response(response),
+        }
+    }
+
+    /// Handle a single request. Returns `None` when no response should be
+    /// written: for notifications, for cancelled requests and for tool calls
+    /// that are waiting on the client (e.g. sampling).
+    pub fn handle_request(&self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        let method = request.method.as_str();
+        self.metrics.record_request(if METHODS.contains(&method) {
+            method
+        } else {
+            metrics::OTHER
+        });
+
+        // A supervisor's health check must not look like a hung server just
+        // because the client is busy.
+        let allowed = method == "ping"
+            || self
+                .rate_limiter
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .try_acquire();
+        if !allowed {
+            if request.is_notification() {
+                return None;
+            }
+            return Some(JsonRpcResponse::error(
+                request.id,
+                JsonRpcError {
+                    code: protocol::RATE_LIMITED,
+                    message: "Rate limit exceeded".to_string(),
+                    data: Some(json!({
+                        "maxRequestsPerSecond": self.options.max_requests_per_second
+                    })),
+                },
+            ));
+        }
+
+        if self.options.strict {
+            if let Err(error) = validate_strict_request(&request) {
+                if request.is_notification() {
+                    return None;
+                }
+                return Some(JsonRpcResponse::error(request.id, error));
+            }
+        }
+
+        // Requests can be cancelled while they run; see `handle_cancel`.
+        let cancel = request.id.as_ref().map(|id| {
+            let flag = Arc::new(AtomicBool::new(false));
+            self.session()
+                .in_flight
+                .insert(request_key(id), Arc::clone(&flag));
+            flag
+        });
         let result = match request.method.as_str() {
             "initialize" => self.handle_initialize(&request.params),
-            "initialized" => Ok(json!({})),
-            "tools/list" => self.handle_tools_list(),
-            "tools/call" => self.handle_tools_call(&request.params),
+            "ping" => Ok(json!({})),
+            "initialized" | "notifications/initialized" => Ok(json!({})),
+            "notifications/cancelled" | "$/cancelRequest" => {
+                self.handle_cancel(&request.params);
+                Ok(json!({}))
+            }
+            "tools/list" => self.handle_tools_list(&request.params),
+            "tools/call" if needs_sampling(&request.params) => {
+                match self.begin_author_sampling(request.id.clone(), &request.params) {
+                    Ok(()) => {
+                        self.finish_request(&request);
+                        return None;
+                    }
+                    Err(msg) => Ok(tool_result(Err(msg))),
+                }
+            }
+            "tools/call"
+                if !request.is_notification() && self.should_elicit_project(&request.params) =>
+            {
+                self.begin_project_elicitation(request.id.clone(), &request.params);
+                self.finish_request(&request);
+                return None;
+            }
+            "tools/call" => {
+                let started = Instant::now();
+                let result = self.handle_tools_call(&request.params, cancel.clone());
+                self.record_tool_latency(&request.params, started.elapsed());
+                if let Ok(value) = &result {
+                    self.record_tool_call(&request.params, value);
+                }
+                result
+            }
+            "resources/list" => Ok(resources::resources_list(&self.discovery().projects)),
+            "resources/templates/list" => Ok(resources::resource_templates_list()),
+            "prompts/list" => Ok(prompts::prompts_list(&self.discovery().projects)),
+            "prompts/get" => prompts::get_prompt(&request.params, &self.discovery().projects),
+            "resources/read" => self.handle_resources_read(&request.params),
+            "resources/subscribe" => self.handle_resources_subscribe(&request.params, true),
+            "resources/unsubscribe" => self.handle_resources_subscribe(&request.params, false),
+            "logging/setLevel" => self.handle_logging_set_level(&request.params),
+            "completion/complete" => {
+                completion::complete(&request.params, &self.discovery().projects)
+            }
             _ => Err(JsonRpcError {
-                code: -32601,
+                code: protocol::METHOD_NOT_FOUND,
                 message: format!("Method not found: {}", request.method),
                 data: None,
             }),
         };
 
-        match result {
+        // Notifications are never answered, whatever they were.
+        request.id.as_ref()?;
+        self.finish_request(&request);
+        if cancel.is_some_and(|flag| flag.load(Ordering::Relaxed)) {
+            return None;
+        }
+
+        Some(match result {
             Ok(value) => JsonRpcResponse::success(request.id, value),
             Err(error) => JsonRpcResponse::error(request.id, error),
-        }
+        })
     }
 
-    fn handle_initialize(&self, _params: &Value) -> Result<Value, JsonRpcError> {
-        Ok(json!({
-            "protocolVersion": "2024-11-05",
+    fn handle_initialize(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let requested = params.get("protocolVersion").and_then(|v| v.as_str());
+        let version = protocol::negotiate_protocol_version(requested)?;
+        let mut session = self.session();
+        session.protocol_version = Some(version.to_string());
+        session.client_capabilities = params.get("capabilities").cloned().unwrap_or(Value::Null);
+        session.client_info = ClientInfo::parse(params);
+        session.experimental = params
+            .pointer("/capabilities/experimental")
+            .and_then(|v| v.as_object())
+            .map(|requested| {
+                protocol::EXPERIMENTAL_FEATURES
+                    .iter()
+                    .filter(|feature| requested.contains_key(**feature))
+                    .map(|feature| feature.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let experimental: serde_json::Map<String, Value> = protocol::EXPERIMENTAL_FEATURES
+            .iter()
+            .map(|feature| (feature.to_string(), json!({})))
+            .collect();
+        drop(session);
+
+        let mut result = json!({
+            "protocolVersion": version,
             "capabilities": {
-                "tools": {}
+                "tools": {},
+                "resources": {
+                    "subscribe": self.watching.load(Ordering::Relaxed)
+                },
+                "prompts": {},
+                "logging": {},
+                "completions": {},
+                "experimental": experimental
             },
             "serverInfo": {
                 "name": "jumble",
                 "version": env!("CARGO_PKG_VERSION")
             }
-        }))
+        });
+        // Revisions are ISO dates, so they compare lexically.
+        if version < protocol::COMPLETIONS_SINCE {
+            if let Some(capabilities) = result["capabilities"].as_object_mut() {
+                capabilities.remove("completions");
+            }
+        }
+        if let Some(instructions) = server_instructions(self.discovery().workspace.as_ref()) {
+            result["instructions"] = json!(instructions);
+        }
+        Ok(result)
+    }
+
+    fn handle_resources_read(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let uri = resource_uri(params)?;
+        let discovery = self.discovery();
+        resources::read_resource(uri, &self.root, &discovery.workspace, &discovery.projects)
+    }
+
+    fn handle_resources_subscribe(
+        &self,
+        params: &Value,
+        subscribe: bool,
+    ) -> Result<Value, JsonRpcError> {
+        let uri = resource_uri(params)?;
+        if !resources::is_known_resource(uri, &self.discovery().projects) {
+            return Err(resources::not_found(uri));
+        }
+        let mut session = self.session();
+        if subscribe {
+            session.subscriptions.insert(uri.to_string());
+        } else {
+            session.subscriptions.remove(uri);
+        }
+        Ok(json!({}))
+    }
+
+    /// One page of tools, `PAGE_SIZE` at a time, starting from the
+    /// request's `cursor`.
+    fn handle_tools_list(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let mut list = tools::tools_list();
+        let supports_sampling = self.client_supports("sampling");
+        let structured_output = self.supports_structured_output();
+        if let Some(tools) = list["tools"].as_array_mut() {
+            if supports_sampling {
+                tools.push(tools::author_project_config_tool());
+            }
+            let custom = self.custom_tools();
+            tools.extend(
+                custom
+                    .iter()
+                    .map(|(name, tool)| tools::custom_tool_definition(name, tool)),
+            );
+            let discovery = self.discovery();
+            tools.extend(discovery.plugins.tools().into_iter().filter(|t| {
+                let name = t["name"].as_str().unwrap_or_default();
+                !tools::is_known_tool(name) && !custom.iter().any(|(c, _)| c == name)
+            }));
+            tools.extend(discovery.proxies.tools());
+            if !structured_output {
+                for tool in tools.iter_mut() {
+                    if let Some(tool) = tool.as_object_mut() {
+                        tool.remove("outputSchema");
+                    }
+                }
+            }
+        }
+        let all = list["tools"].as_array().cloned().unwrap_or_default();
+        let cursor = params.get("cursor").and_then(|v| v.as_str());
+        let page =
+            tools::page_of(all, cursor, tools::PAGE_SIZE).map_err(|message| JsonRpcError {
+                code: protocol::INVALID_PARAMS,
+                message,
+                data: None,
+            })?;
+        list["tools"] = json!(page.items);
+        if let Some(next) = page.next_cursor {
+            list["nextCursor"] = json!(next);
+        }
+        Ok(list)
+    }
+
+    /// Tools declared in workspace.toml, sorted by name. Built-in tools win
+    /// over a custom tool with the same name.
+    fn custom_tools(&self) -> Vec<(String, CustomTool)> {
+        let discovery = self.discovery();
+        let mut custom: Vec<(String, CustomTool)> = discovery
+            .workspace
+            .iter()
+            .flat_map(|ws| ws.tools.iter())
+            .filter(|(name, _)| !tools::is_known_tool(name))
+            .map(|(name, tool)| (name.clone(), tool.clone()))
+            .collect();
+        custom.sort_by(|a, b| a.0.cmp(&b.0));
+        custom
+    }
+
+    fn custom_tool(&self, name: &str) -> Option<CustomTool> {
+        if tools::is_known_tool(name) {
+            return None;
+        }
+        self.discovery()
+            .workspace
+            .as_ref()
+            .and_then(|ws| ws.tools.get(name).cloned())
+    }
+
+    /// Start drafting config through the client's model. The tools/call
+    /// response is sent once the client answers the sampling request.
+    fn begin_author_sampling(
+        &self,
+        original_id: Option<Value>,
+        params: &Value,
+    ) -> Result<(), String> {
+        if !self.client_supports("sampling") {
+            return Err(
+                "author_project_config requires a client that supports sampling".to_string(),
+            );
+        }
+
+        let directory = self.resolve_author_directory(params.get("arguments"))?;
+        let sampling_params = authoring::sampling_request_params(&directory)?;
+
+        let id = self.next_request_id();
+        self.session().pending.insert(
+            id.clone(),
+            PendingRequest::AuthorDraft {
+                original_id,
+                directory,
+            },
+        );
+        self.queue_outgoing(JsonRpcOutgoing::request(
+            json!(id),
+            "sampling/createMessage",
+            sampling_params,
+        ));
+        Ok(())
+    }
+
+    /// True when a tool that requires `project` was called without one and the
+    /// client can ask the user to pick from the discovered projects instead.
+    fn should_elicit_project(&self, params: &Value) -> bool {
+        let name = match params.get("name").and_then(|v| v.as_str()) {
+            Some(name) => name,
+            None => return false,
+        };
+        let has_project = params
+            .get("arguments")
+            .and_then(|a| a.get("project"))
+            .is_some();
+
+        !has_project
+            && !self.discovery().projects.is_empty()
+            && self.client_supports("elicitation")
+            && tools::requires_argument(name, "project")
+    }
+
+    fn begin_project_elicitation(&self, original_id: Option<Value>, params: &Value) {
+        let tool = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let mut names: Vec<String> = self.discovery().projects.keys().cloned().collect();
+        names.sort();
+
+        let id = self.next_request_id();
+        self.session().pending.insert(
+            id.clone(),
+            PendingRequest::ProjectElicitation {
+                original_id,
+                params: params.clone(),
+            },
+        );
+        self.queue_outgoing(JsonRpcOutgoing::request(
+            json!(id),
+            "elicitation/create",
+            json!({
+                "message": format!("Which project should `{}` use?", tool),
+                "requestedSchema": {
+                    "type": "object",
+                    "properties": {
+                        "project": {
+                            "type": "string",
+                            "title": "Project",
+                            "enum": names
+                        }
+                    },
+                    "required": ["project"]
+                }
+            }),
+        ));
+    }
+
+    /// Stop tracking `request` as in flight.
+    fn finish_request(&self, request: &JsonRpcRequest) {
+        if let Some(id) = &request.id {
+            self.session().in_flight.remove(&request_key(id));
+        }
+    }
+
+    /// Cancel a request, by `requestId` (`notifications/cancelled`) or `id`
+    /// (`$/cancelRequest`). A running one is stopped where it can be (custom
+    /// tools are killed) and gets no response; one waiting on the client is
+    /// abandoned, so the client's eventual answer is ignored. Unknown ids are
+    /// ignored: the request may have finished already.
+    fn handle_cancel(&self, params: &Value) {
+        let Some(id) = params.get("requestId").or_else(|| params.get("id")) else {
+            return;
+        };
+        let mut session = self.session();
+        if let Some(flag) = session.in_flight.get(&request_key(id)) {
+            flag.store(true, Ordering::Relaxed);
+        }
+        session.pending.retain(|_, pending| match pending {
+            PendingRequest::AuthorDraft { original_id, .. }
+            | PendingRequest::ProjectElicitation { original_id, .. } => {
+                original_id.as_ref() != Some(id)
+            }
+        });
     }
 
-    fn handle_tools_list(&self) -> Result<Value, JsonRpcError> {
-        Ok(tools::tools_list())
+    fn handle_client_response(&self, response: JsonRpcClientResponse) -> Option<JsonRpcResponse> {
+        let key = request_key(&response.id);
+
+        let pending = self.session().pending.remove(&key)?;
+        match pending {
+            PendingRequest::AuthorDraft {
+                original_id,
+                directory,
+            } => {
+                let outcome = match (response.result, response.error) {
+                    (_, Some(error)) => Err(format!("Sampling request failed: {}", error.message)),
+                    (Some(result), None) => {
+                        authoring::draft_from_sampling_result(&result).map(|draft| {
+                            let text = authoring::format_draft(&directory, &draft);
+                            self.session()
+                                .author_drafts
+                                .insert(directory.clone(), draft);
+                            text
+                        })
+                    }
+                    (None, None) => Err("Sampling response contained no result".to_string()),
+                };
+                Some(JsonRpcResponse::success(original_id, tool_result(outcome)))
+            }
+            PendingRequest::ProjectElicitation {
+                original_id,
+                mut params,
+            } => {
+                let result = response.result.unwrap_or(Value::Null);
+                let accepted = result.get("action").and_then(|v| v.as_str()) == Some("accept");
+                let project = result
+                    .get("content")
+                    .and_then(|c| c.get("project"))
+                    .and_then(|v| v.as_str());
+
+                match (accepted, project) {
+                    (true, Some(project)) => {
+                        if !params
+                            .get("arguments")
+                            .map(|a| a.is_object())
+                            .unwrap_or(false)
+                        {
+                            params["arguments"] = json!({});
+                        }
+                        params["arguments"]["project"] = json!(project);
+                        Some(match self.handle_tools_call(&params, None) {
+                            Ok(value) => JsonRpcResponse::success(original_id, value),
+                            Err(error) => JsonRpcResponse::error(original_id, error),
+                        })
+                    }
+                    _ => Some(JsonRpcResponse::success(
+                        original_id,
+                        tool_result(Err("Missing 'project' argument".to_string())),
+                    )),
+                }
+            }
+        }
     }
 
-    fn handle_tools_call(&mut self, params: &Value) -> Result<Value, JsonRpcError> {
+    /// Write a previously drafted config after the user confirmed it.
+    fn confirm_author_draft(&self, args: &Value) -> Result<String, String> {
+        let directory = self.resolve_author_directory(Some(args))?;
+        let overwrite = args
+            .get("overwrite")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let draft = self
+            .session()
+            .author_drafts
+            .get(&directory)
+            .cloned()
+            .ok_or_else(|| {
+                format!(
+                    "No draft found for '{}'. Call author_project_config without `confirm` first.",
+                    directory.display()
+                )
+            })?;
+
+        let written = authoring::write_draft(&directory, &draft, overwrite)?;
+        self.session().author_drafts.remove(&directory);
+
+        let mut output = String::from("Wrote drafted Jumble config:\n");
+        for path in written {
+            output.push_str(&format!("- {}\n", path.display()));
+        }
+        output.push_str("\nCall reload_workspace to pick up the new project.");
+        Ok(output)
+    }
+
+    /// Rediscovery walks the whole tree, so refuse back-to-back reloads.
+    fn throttle_reload(&self) -> Result<(), String> {
+        let mut last = self
+            .last_reload
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        if let Some(previous) = *last {
+            let elapsed = now.saturating_duration_since(previous);
+            if elapsed < limits::MIN_RELOAD_INTERVAL {
+                return Err(format!(
+                    "reload_workspace called too frequently. Retry in {} ms.",
+                    (limits::MIN_RELOAD_INTERVAL - elapsed).as_millis()
+                ));
+            }
+        }
+        *last = Some(now);
+        Ok(())
+    }
+
+    fn resolve_author_directory(&self, args: Option<&Value>) -> Result<PathBuf, String> {
+        let dir = args
+            .and_then(|a| a.get("directory"))
+            .and_then(|v| v.as_str())
+            .ok_or("Missing 'directory' argument")?;
+        // The client picks this path, so it must not lead out of the workspace.
+        let root = self
+            .root
+            .canonicalize()
+            .unwrap_or_else(|_| self.root.clone());
+        let directory = root
+            .join(dir)
+            .canonicalize()
+            .map_err(|_| format!("Directory '{}' does not exist", dir))?;
+        if !directory.starts_with(&root) {
+            return Err(format!("Directory '{}' is outside the workspace", dir));
+        }
+        Ok(directory)
+    }
+
+    /// Built-in, custom, plugin or proxied tool called `name`.
+    fn is_available_tool(&self, name: &str) -> bool {
+        tools::is_known_tool(name)
+            || self.custom_tool(name).is_some()
+            || self.discovery().plugins.provides(name)
+            || self.discovery().proxies.resolve(name).is_some()
+    }
+
+    fn record_tool_latency(&self, params: &Value, elapsed: std::time::Duration) {
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        let label = if self.is_available_tool(name) {
+            name
+        } else {
+            metrics::OTHER
+        };
+        self.metrics.record_tool(label, elapsed);
+    }
+
+    fn record_tool_call(&self, params: &Value, result: &Value) {
+        let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+        if result.get("isError") == Some(&Value::Bool(true)) || name == "get_session_summary" {
+            return;
+        }
+        let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
+        self.session()
+            .tool_calls
+            .push((name.to_string(), arguments));
+    }
+
+    /// Project names with their concept names, sorted, for `jumble repl`'s
+    /// tab completion.
+    #[cfg(feature = "repl")]
+    pub fn project_concepts(&self) -> std::collections::BTreeMap<String, Vec<String>> {
+        self.discovery()
+            .projects
+            .iter()
+            .map(|(name, (_, config, _, _, _, _))| {
+                let mut concepts: Vec<String> = config.concepts.keys().cloned().collect();
+                concepts.sort();
+                (name.clone(), concepts)
+            })
+            .collect()
+    }
+
+    /// The projects as a tree for `jumble tui`.
+    #[cfg(feature = "tui")]
+    pub fn outline(&self) -> Vec<crate::tui::Node> {
+        crate::tui::outline(&self.discovery().projects)
+    }
+
+    /// `/healthz` body: the server answers and knows how many projects it has.
+    pub fn health(&self) -> Value {
+        let mut health = json!({
+            "status": "ok",
+            "projects": self.discovery().projects.len(),
+        });
+        if let Some(scope) = &self.options.scope {
+            health["scope"] = json!(scope);
+        }
+        if let Some(client) = &self.session().client_info {
+            health["client"] = json!(client.to_string());
+        }
+        health
+    }
+
+    /// Request, tool latency, discovery and cache metrics for `/metrics`.
+    pub fn metrics_text(&self) -> String {
+        let gauges = {
+            let discovery = self.discovery();
+            let (cache_hits, cache_misses) = discovery.file_index.stats();
+            Gauges {
+                projects: discovery.projects.len(),
+                cache_hits,
+                cache_misses,
+            }
+        };
+        self.metrics.render(&gauges)
+    }
+
+    fn handle_tools_call(
+        &self,
+        params: &Value,
+        cancel: Option<Arc<AtomicBool>>,
+    ) -> Result<Value, JsonRpcError> {
         let name = params
             .get("name")
             .and_then(|v| v.as_str())
             .ok_or_else(|| JsonRpcError {
-                code: -32602,
+                code: protocol::INVALID_PARAMS,
                 message: "Missing 'name' parameter".to_string(),
                 data: None,
             })?;
 
+        // The MCP spec reports unknown tools as a protocol error; lenient mode
+        // keeps returning them as a tool result for older clients.
+        if self.options.strict && !self.is_available_tool(name) {
+            return Err(JsonRpcError {
+                code: protocol::INVALID_PARAMS,
+                message: format!("Unknown tool: {}", name),
+                data: None,
+            });
+        }
+
         let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
 
-        let result = match name {
-            "reload_workspace" => match self.reload_workspace_and_projects() {
-                Ok(()) => Ok("Workspace and projects reloaded from disk.".to_string()),
-                Err(e) => Err(format!("Failed to reload workspace: {}", e)),
+        // These take the discovery write lock or the session lock themselves.
+        match name {
+            "reload_workspace" => {
+                if let Err(e) = self.throttle_reload() {
+                    return Ok(tool_result(Err(e)));
+                }
+                let progress_token = params.get("_meta").and_then(|m| m.get("progressToken"));
+                return Ok(tool_result(match self.refresh_workspace(progress_token) {
+                    Ok(()) => Ok("Workspace and projects reloaded from disk.".to_string()),
+                    Err(e) => Err(format!("Failed to reload workspace: {}", e)),
+                }));
+            }
+            "author_project_config" => {
+                return Ok(tool_result(self.confirm_author_draft(&arguments)))
+            }
+            "get_session_summary" => {
+                let (calls, client) = {
+                    let session = self.session();
+                    let client = session.client_info.as_ref().map(ClientInfo::to_string);
+                    (session.tool_calls.clone(), client)
+                };
+                let discovery = self.discovery();
+                return Ok(tool_result(tools::get_session_summary(
+                    &calls,
+                    client.as_deref(),
+                    &discovery.projects,
+                    &discovery.workspace,
+                    &arguments,
+                )));
+            }
+            _ => {}
+        }
+
+        // Proxied servers answer with a full tools/call result of their own.
+        let proxied = self.discovery().proxies.resolve(name);
+        if let Some((server, tool)) = proxied {
+            return Ok(match server.call_tool(&tool, &arguments) {
+                Ok(result) => result,
+                Err(e) => tool_result(Err(e)),
+            });
+        }
+
+        // Skill scripts and custom tools run a process, so don't hold the
+        // discovery lock.
+        if name == "run_skill_script" {
+            let invocation = tools::skill_script_invocation(&self.discovery().projects, &arguments);
+            return Ok(tool_result(invocation.and_then(|(dir, argv)| {
+                tools::run_skill_script(&dir, &argv, cancel)
+            })));
+        }
+        if let Some(tool) = self.custom_tool(name) {
+            return Ok(tool_result(tools::run_custom_tool(
+                &self.root, name, &tool, &arguments, cancel,
+            )));
+        }
+
+        // Plugins run until their fuel is spent, so don't hold the discovery
+        // lock either. Built-in tools win over plugin tools of the same name.
+        let plugin = (!tools::is_known_tool(name))
+            .then(|| self.discovery().plugins.handle(name))
+            .flatten();
+        let plugin_result = plugin.map(|plugin| plugin.call(&arguments));
+        // These run git or call the forge, so they happen outside the lock.
+        let context_diff =
+            (name == "get_context_diff").then(|| tools::get_context_diff(&self.root, &arguments));
+        let code_owners = (name == "get_code_owners").then(|| {
+            let discovery = self.discovery();
+            tools::look_up_code_owners(
+                &self.root,
+                &discovery.projects,
+                &discovery.workspace,
+                &discovery.file_index,
+                &arguments,
+            )
+        });
+        let code_owners = code_owners.map(|lookup| lookup.and_then(tools::blame_code_owners));
+        let repo_activity = (name == "get_repo_activity").then(|| {
+            let target = tools::repo_activity_target(&self.discovery().projects, &arguments);
+            target.and_then(|target| {
+                tools::get_repo_activity(target, self.jumble_config.as_ref(), &arguments)
+            })
+        });
+
+        let path_style = match arguments.get("path_style") {
+            Some(value) => match serde_json::from_value::<PathStyle>(value.clone()) {
+                Ok(style) => Some(style),
+                Err(_) => {
+                    return Ok(tool_result(Err(format!(
+                        "Invalid path_style {}: use \"relative\", \"absolute\" or \"uri\"",
+                        value
+                    ))))
+                }
             },
-            "list_projects" => tools::list_projects(&self.projects),
-            "get_project_info" => tools::get_project_info(&self.projects, &arguments),
-            "get_commands" => tools::get_commands(&self.projects, &arguments),
-            "get_architecture" => tools::get_architecture(&self.projects, &arguments),
-            "get_related_files" => tools::get_related_files(&self.projects, &arguments),
-            "list_skills" => tools::list_skills(&self.projects, &arguments),
-            "get_skill" => tools::get_skill(&self.projects, &arguments),
-            "get_conventions" => tools::get_conventions(&self.projects, &arguments),
-            "get_docs" => tools::get_docs(&self.projects, &arguments),
-            "get_workspace_overview" => {
-                tools::get_workspace_overview(&self.root, &self.workspace, &self.projects)
+            None => None,
+        };
+
+        let client = self.session().client_info.as_ref().map(|c| c.name.clone());
+        let structured_output = self.supports_structured_output();
+        let discovery = self.discovery();
+        let projects = &discovery.projects;
+        let mut structured = None;
+        let mut result = match name {
+            "list_projects" => {
+                tools::list_projects_structured(projects, &discovery.workspace, &arguments).map(
+                    |(text, value)| {
+                        structured = Some(value);
+                        text
+                    },
+                )
+            }
+            "list_groups" => tools::list_groups(projects, &discovery.workspace),
+            "get_project_info" => {
+                tools::get_project_info(projects, &discovery.workspace, &arguments)
+            }
+            "get_commands" => {
+                tools::get_commands_structured(projects, &arguments).map(|(text, value)| {
+                    structured = Some(value);
+                    text
+                })
             }
+            "get_architecture" => tools::get_architecture(projects, &arguments),
+            "get_related_files" => tools::get_related_files(
+                projects,
+                &discovery.workspace,
+                &discovery.file_index,
+                &arguments,
+            ),
+            "list_skills" => tools::list_skills(projects, &arguments),
+            "get_skill" => tools::get_skill(projects, &arguments),
+            "get_conventions" => tools::get_conventions(projects, &discovery.workspace, &arguments),
+            "get_docs" => tools::get_docs(&self.root, projects, &discovery.workspace, &arguments),
+            "get_context_health" => tools::get_context_health(projects, &arguments),
+            "verify_context" => tools::verify_context(projects, &arguments),
+            "get_workspace_overview" => tools::get_workspace_overview(
+                &self.root,
+                &discovery.workspace,
+                projects,
+                &arguments,
+            ),
             "get_workspace_conventions" => {
-                tools::get_workspace_conventions(&self.workspace, &arguments)
+                tools::get_workspace_conventions(&discovery.workspace, &arguments)
             }
             "get_jumble_authoring_prompt" => tools::get_jumble_authoring_prompt(),
             "init_project" => tools::init_project(&self.root, &arguments),
-            "store_memory" => tools::store_memory(&self.projects, &arguments),
-            "get_memory" => tools::get_memory(&self.projects, &arguments),
-            "list_memories" => tools::list_memories(&self.projects, &arguments),
-            "search_memories" => tools::search_memories(&self.projects, &arguments),
-            "delete_memory" => tools::delete_memory(&self.projects, &arguments),
-            "clear_memories" => tools::clear_memories(&self.projects, &arguments),
-            _ => Err(format!("Unknown tool: {}", name)),
+            "store_memory" => tools::store_memory(projects, &arguments),
+            "get_memory" => tools::get_memory(projects, &arguments),
+            "list_memories" => tools::list_memories(projects, &arguments),
+            "search_memories" => tools::search_memories(projects, &discovery.workspace, &arguments),
+            "delete_memory" => tools::delete_memory(projects, &arguments),
+            "clear_memories" => tools::clear_memories(projects, &arguments),
+            "get_code_stats" => tools::get_code_stats(projects, &discovery.file_index, &arguments),
+            "get_compliance_info" => {
+                tools::get_compliance_info(projects, &discovery.workspace, &arguments)
+            }
+            "check_dependency" => {
+                tools::check_dependency(projects, &discovery.workspace, &arguments)
+            }
+            "get_data_assets" => {
+                tools::get_data_assets(projects, &discovery.file_index, &arguments)
+            }
+            "get_dev_endpoints" => tools::get_dev_endpoints(projects, &discovery.workspace),
+            "get_workflow" => tools::get_workflow(projects, &discovery.workspace, &arguments),
+            "get_performance_info" => tools::get_performance_info(projects, &arguments),
+            "get_known_issues" => {
+                tools::get_known_issues(projects, &discovery.workspace, &arguments)
+            }
+            "get_tests_for" => tools::get_tests_for(projects, &discovery.file_index, &arguments),
+            "prepare_for_task" => {
+                tools::prepare_for_task(projects, &discovery.workspace, &arguments)
+            }
+            "get_review_checklist" => {
+                tools::get_review_checklist(projects, &discovery.workspace, &arguments)
+            }
+            "get_messaging_info" => tools::get_messaging_info(projects, &arguments),
+            "get_api_examples" => tools::get_api_examples(projects, &arguments),
+            "get_format_settings" => tools::get_format_settings(&self.root, projects, &arguments),
+            "get_hooks" => tools::get_hooks(&self.root, projects, &arguments),
+            "get_ticket_link" => tools::get_ticket_link(projects, &discovery.workspace, &arguments),
+            "describe_tool" => tools::describe_tool(&arguments),
+            "record_command_result" => tools::record_command_result(projects, &arguments),
+            "get_last_run" => {
+                tools::get_last_run_structured(projects, &arguments).map(|(text, value)| {
+                    structured = Some(value);
+                    text
+                })
+            }
+            _ => context_diff
+                .or(code_owners)
+                .or(repo_activity)
+                .or(plugin_result)
+                .unwrap_or_else(|| Err(format!("Unknown tool: {}", name))),
         };
+        // JSON results must stay parseable.
+        let markdown = result
+            .as_ref()
+            .is_ok_and(|output| serde_json::from_str::<Value>(output).is_err());
+        let next_steps = discovery
+            .workspace
+            .as_ref()
+            .and_then(|ws| ws.next_steps.as_ref())
+            .filter(|_| markdown)
+            .and_then(|config| tools::next_steps(name, &arguments, config));
+        let mut output_style = discovery
+            .workspace
+            .as_ref()
+            .map(|ws| ws.output.for_client(client.as_deref()))
+            .unwrap_or_default();
+        output_style.paths = path_style.unwrap_or(output_style.paths);
+        // Relative paths are for agents working inside the project a call names.
+        let path_base = arguments
+            .get("project")
+            .and_then(|v| v.as_str())
+            .and_then(|project| projects.get(project))
+            .filter(|_| output_style.paths == PathStyle::Relative)
+            .map_or_else(|| self.root.clone(), |(path, _, _, _, _, _)| path.clone());
+        let no_projects = projects.is_empty();
+        let structured = structured.or_else(|| {
+            result.as_ref().ok()?;
+            tools::structured_output(name, projects, &discovery.workspace, &arguments)
+        });
+        drop(discovery);
+
+        if name == "get_workspace_overview" {
+            if let Ok(output) = &mut result {
+                self.append_project_changes(output);
+                if no_projects {
+                    self.append_first_run_hint(output);
+                }
+                if let Some(scope) = &self.options.scope {
+                    output.push_str(&format!(
+                        "\n*Scoped to `{}`: projects elsewhere in the workspace are not loaded.*\n",
+                        scope.display()
+                    ));
+                }
+            }
+        }
+        if let (Ok(output), Some(footer)) = (&mut result, next_steps) {
+            output.push_str(&footer);
+        }
+        if let Ok(output) = &mut result {
+            if markdown {
+                *output = format::apply_output_style(output, &output_style, &path_base);
+            }
+        }
+        let mut response = tool_result(result);
+        match structured {
+            _ if !structured_output => {}
+            Some(structured) => response["structuredContent"] = structured,
+            None if self.experimental(protocol::STRUCTURED_OUTPUT) => {
+                add_structured_content(&mut response)
+            }
+            None => {}
+        }
+        Ok(response)
+    }
+}
+
+/// Extract the required `uri` param of a `resources/*` request.
+fn resource_uri(params: &Value) -> Result<&str, JsonRpcError> {
+    params
+        .get("uri")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| JsonRpcError {
+            code: protocol::INVALID_PARAMS,
+            message: "Missing 'uri' parameter".to_string(),
+            data: None,
+        })
+}
+
+/// Wrap a tool's markdown output (or error) in an MCP `tools/call` result.
+/// Key for a JSON-RPC id in maps of requests: strings as they are, other ids
+/// as JSON.
+fn request_key(id: &Value) -> String {
+    match id {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn tool_result(result: Result<String, String>) -> Value {
+    match result {
+        Ok(content) => json!({
+            "content": [{
+                "type": "text",
+                "text": content
+            }]
+        }),
+        Err(msg) => json!({
+            "content": [{
+                "type": "text",
+                "text": format!("Error: {}", msg)
+            }],
+            "isError": true
+        }),
+    }
+}
+
+/// Repeat a successful result whose text is a JSON object as
+/// `structuredContent`, for tools that don't declare an `outputSchema`.
+fn add_structured_content(response: &mut Value) {
+    if response.get("isError").is_some() {
+        return;
+    }
+    let structured = response["content"][0]["text"]
+        .as_str()
+        .and_then(|text| serde_json::from_str::<Value>(text).ok())
+        .filter(Value::is_object);
+    if let Some(structured) = structured {
+        response["structuredContent"] = structured;
+    }
+}
+
+/// Guidance sent with `initialize` when workspace.toml doesn't opt out of it.
+const DEFAULT_INSTRUCTIONS: &str = "jumble describes the projects in this workspace. \
+Call get_workspace_overview first, then get_project_info(project) before working in a project. \
+Check get_conventions(project) before writing code and get_commands(project) before running builds or tests.";
+
+/// The `instructions` for `initialize`: the default guidance followed by
+/// the workspace's `[instructions] text`, or `None` when both are off.
+fn server_instructions(workspace: Option<&WorkspaceConfig>) -> Option<String> {
+    let config = workspace
+        .map(|w| w.instructions.clone())
+        .unwrap_or_default();
+    let parts: Vec<&str> = config
+        .include_default
+        .then_some(DEFAULT_INSTRUCTIONS)
+        .into_iter()
+        .chain(
+            config
+                .text
+                .as_deref()
+                .map(str::trim)
+                .filter(|t| !t.is_empty()),
+        )
+        .collect();
+    (!parts.is_empty()).then(|| parts.join("\n\n"))
+}
 
-        match result {
-            Ok(content) => Ok(json!({
-                "content": [{
-                    "type": "text",
-                    "text": content
-                }]
-            })),
-            Err(msg) => Ok(json!({
-                "content": [{
-                    "type": "text",
-                    "text": format!("Error: {}", msg)
-                }],
-                "isError": true
-            })),
+/// `author_project_config` without `confirm` must round-trip through sampling.
+fn needs_sampling(params: &Value) -> bool {
+    params.get("name").and_then(|v| v.as_str()) == Some(tools::AUTHOR_PROJECT_CONFIG)
+        && !params
+            .get("arguments")
+            .and_then(|a| a.get("confirm"))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+}
+
+/// Strict-mode checks that need to know the method being called.
+///
+/// Shape checks on the envelope itself happen in `protocol::parse_request`;
+/// this covers notification/id pairing and per-method params typing.
+fn validate_strict_request(request: &JsonRpcRequest) -> Result<(), JsonRpcError> {
+    let invalid_params = |message: &str| JsonRpcError {
+        code: protocol::INVALID_PARAMS,
+        message: message.to_string(),
+        data: None,
+    };
+
+    if request.jsonrpc != "2.0" {
+        return Err(JsonRpcError {
+            code: protocol::INVALID_REQUEST,
+            message: "Invalid Request: 'jsonrpc' must be \"2.0\"".to_string(),
+            data: None,
+        });
+    }
+
+    if protocol::is_notification_method(&request.method) && !request.is_notification() {
+        return Err(JsonRpcError {
+            code: protocol::INVALID_REQUEST,
+            message: format!(
+                "Invalid Request: notification '{}' must not include an id",
+                request.method
+            ),
+            data: None,
+        });
+    }
+
+    let params = &request.params;
+    match request.method.as_str() {
+        "initialize" => {
+            if !params.is_object() {
+                return Err(invalid_params("initialize params must be an object"));
+            }
+            if !params
+                .get("protocolVersion")
+                .map(|v| v.is_string())
+                .unwrap_or(false)
+            {
+                return Err(invalid_params(
+                    "initialize params must include a string 'protocolVersion'",
+                ));
+            }
+            if !params
+                .get("capabilities")
+                .map(|v| v.is_object())
+                .unwrap_or(false)
+            {
+                return Err(invalid_params(
+                    "initialize params must include a 'capabilities' object",
+                ));
+            }
         }
+        "tools/call" => {
+            if !params.is_object() {
+                return Err(invalid_params("tools/call params must be an object"));
+            }
+            if !params.get("name").map(|v| v.is_string()).unwrap_or(false) {
+                return Err(invalid_params(
+                    "tools/call params must include a string 'name'",
+                ));
+            }
+            if let Some(arguments) = params.get("arguments") {
+                if !arguments.is_object() {
+                    return Err(invalid_params("tools/call 'arguments' must be an object"));
+                }
+            }
+        }
+        "resources/read" | "resources/subscribe" | "resources/unsubscribe"
+            if !params.get("uri").map(|v| v.is_string()).unwrap_or(false) =>
+        {
+            return Err(invalid_params(&format!(
+                "{} params must include a string 'uri'",
+                request.method
+            )));
+        }
+        "prompts/get" if !params.get("name").map(|v| v.is_string()).unwrap_or(false) => {
+            return Err(invalid_params(
+                "prompts/get params must include a string 'name'",
+            ));
+        }
+        "tools/list" if !(params.is_null() || params.is_object()) => {
+            return Err(invalid_params("tools/list params must be an object"));
+        }
+        "tools/list" if params.get("cursor").is_some_and(|v| !v.is_string()) => {
+            return Err(invalid_params("tools/list 'cursor' must be a string"));
+        }
+        _ => {}
     }
+
+    Ok(())
 }
 
 /// Resolve the current user's home directory in a cross-platform way.
@@ -378,7 +2033,10 @@ fn load_jumble_config() -> Option<JumbleConfig> {
             return None;
         }
 
-        let default_content = "# Global configuration for the Jumble MCP server.\n\n[jumble]\n";
+        let default_content = format!(
+            "# Global configuration for the Jumble MCP server.\nconfig_version = {}\n\n[jumble]\n",
+            migrate::CURRENT_CONFIG_VERSION
+        );
         if let Err(e) = std::fs::write(&config_path, default_content) {
             eprintln!(
                 "jumble: failed to create default config at {}: {}",
@@ -389,7 +2047,7 @@ fn load_jumble_config() -> Option<JumbleConfig> {
         }
     }
 
-    let content = match std::fs::read_to_string(&config_path) {
+    let content = match text::read_text(&config_path) {
         Ok(c) => c,
         Err(e) => {
             eprintln!(
@@ -401,7 +2059,7 @@ fn load_jumble_config() -> Option<JumbleConfig> {
         }
     };
 
-    match toml::from_str::<JumbleConfig>(&content) {
+    match migrate::parse::<JumbleConfig>(ConfigKind::Global, &content) {
         Ok(cfg) => Some(cfg),
         Err(e) => {
             eprintln!(
@@ -436,7 +2094,7 @@ fn discover_structured_skills_in_dir(root: &Path, skills: &mut ProjectSkills) {
             continue;
         }
 
-        let (frontmatter, preview) = match std::fs::read_to_string(path) {
+        let (frontmatter, preview) = match text::read_text(path) {
             Ok(content) => extract_skill_frontmatter_and_preview(&content),
             Err(_) => (None, String::new()),
         };
@@ -494,28 +2152,110 @@ fn extract_skill_frontmatter_and_preview(content: &str) -> (Option<SkillFrontmat
             .join("\n")
     }
 
-    // Detect YAML frontmatter only if the file starts with `---` on the first line.
-    if let Some(rest) = content.strip_prefix("---\n") {
-        // Skip the initial `---\n`.
-        if let Some(end_idx) = rest.find("\n---\n") {
-            let frontmatter_str = &rest[..end_idx];
-            let body_start = end_idx + "\n---\n".len();
-            let body = &rest[body_start..];
+    // Without a valid frontmatter header the body is the whole file.
+    let (frontmatter, body) = crate::config::split_frontmatter(content);
+    let frontmatter =
+        frontmatter.and_then(|yaml| serde_yaml::from_str::<SkillFrontmatter>(yaml).ok());
+    (frontmatter, build_preview(body))
+}
+
+/// Longest summary derived from a doc, in characters.
+const DERIVED_SUMMARY_MAX_CHARS: usize = 200;
+
+/// Build a one-line summary from a markdown document's first heading and
+/// first paragraph, skipping any YAML frontmatter.
+fn summarize_markdown(content: &str) -> Option<String> {
+    let body = crate::config::split_frontmatter(content).1;
 
-            let frontmatter = serde_yaml::from_str::<SkillFrontmatter>(frontmatter_str).ok();
-            let preview = build_preview(body);
-            return (frontmatter, preview);
+    let mut heading = None;
+    let mut paragraph: Vec<&str> = Vec::new();
+    for line in body.lines().map(str::trim) {
+        if line.starts_with('#') {
+            if !paragraph.is_empty() {
+                break;
+            }
+            if heading.is_none() {
+                heading = Some(line.trim_start_matches('#').trim());
+            }
+            continue;
         }
+        if line.is_empty() || line.starts_with("```") {
+            if !paragraph.is_empty() {
+                break;
+            }
+            continue;
+        }
+        paragraph.push(line);
     }
 
-    // No valid frontmatter header found; fall back to using the first lines of the file.
-    (None, build_preview(content))
+    let paragraph = paragraph.join(" ");
+    let summary = match (heading.filter(|h| !h.is_empty()), paragraph.is_empty()) {
+        (Some(heading), false) => format!("{}: {}", heading, paragraph),
+        (Some(heading), true) => heading.to_string(),
+        (None, false) => paragraph,
+        (None, true) => return None,
+    };
+
+    if summary.chars().count() > DERIVED_SUMMARY_MAX_CHARS {
+        let truncated: String = summary
+            .chars()
+            .take(DERIVED_SUMMARY_MAX_CHARS - 1)
+            .collect();
+        Some(format!("{}…", truncated.trim_end()))
+    } else {
+        Some(summary)
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::collections::HashMap;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_markdown() {
+        let doc =
+            "---\ntitle: x\n---\n# Configuration\n\nAll options for\nthe proxy.\n\nMore text.\n";
+        assert_eq!(
+            summarize_markdown(doc).as_deref(),
+            Some("Configuration: All options for the proxy.")
+        );
+        assert_eq!(
+            summarize_markdown("# Only a title\n").as_deref(),
+            Some("Only a title")
+        );
+        assert_eq!(summarize_markdown("\n\n"), None);
+
+        let long = format!("# T\n\n{}", "word ".repeat(100));
+        let summary = summarize_markdown(&long).unwrap();
+        assert_eq!(summary.chars().count(), DERIVED_SUMMARY_MAX_CHARS);
+        assert!(summary.ends_with('…'));
+    }
+
+    #[test]
+    fn test_missing_doc_summaries_are_derived_on_load() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jumble_dir = temp.path().join(".jumble");
+        std::fs::create_dir_all(&jumble_dir).unwrap();
+        std::fs::create_dir_all(temp.path().join("docs")).unwrap();
+        std::fs::write(
+            temp.path().join("docs/setup.md"),
+            "# Setup\n\nInstall the toolchain first.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            jumble_dir.join("docs.toml"),
+            "[docs.setup]\npath = \"docs/setup.md\"\n\n[docs.api]\npath = \"docs/api.md\"\nsummary = \"API reference\"\n",
+        )
+        .unwrap();
+
+        let server = Server::bare(temp.path().to_path_buf(), ServerOptions::default(), None);
+        let docs = server.load_docs(&jumble_dir);
+        assert_eq!(
+            docs.docs["setup"].summary,
+            "Setup: Install the toolchain first."
+        );
+        assert_eq!(docs.docs["api"].summary, "API reference");
+    }
 
     #[test]
     fn test_extract_frontmatter_and_preview_with_valid_frontmatter() {
@@ -670,12 +2410,7 @@ mod tests {
         let global_conflict_path = global_skills_dir.join("local-first.md");
         std::fs::write(&global_conflict_path, "# Global Conflict\\nBody").unwrap();
 
-        let server = Server {
-            root: project_root.clone(),
-            workspace: None,
-            projects: HashMap::new(),
-            jumble_config: cfg,
-        };
+        let server = Server::bare(project_root.clone(), ServerOptions::default(), cfg);
 
         let skills = server.discover_skills(&jumble_dir);
 
@@ -714,4 +2449,1543 @@ mod tests {
             None => env::remove_var("HOMEPATH"),
         }
     }
+
+    fn test_server() -> Server {
+        Server::bare(PathBuf::from("/workspace"), ServerOptions::default(), None)
+    }
+
+    fn strict_server() -> Server {
+        let mut server = test_server();
+        server.options.strict = true;
+        server
+    }
+
+    fn notification(method: &str) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: None,
+            method: method.to_string(),
+            params: Value::Null,
+        }
+    }
+
+    fn request(id: i64, method: &str, params: Value) -> JsonRpcRequest {
+        JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(id)),
+            method: method.to_string(),
+            params,
+        }
+    }
+
+    #[test]
+    fn test_server_can_be_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Server>();
+
+        let (_temp, server) = server_with_project();
+        std::thread::scope(|scope| {
+            for id in 0..4 {
+                let server = &server;
+                scope.spawn(move || {
+                    let response = server
+                        .handle_request(request(
+                            id,
+                            "tools/call",
+                            json!({"name": "get_commands", "arguments": {"project": "app"}}),
+                        ))
+                        .unwrap();
+                    let text = response.result.unwrap()["content"][0]["text"]
+                        .as_str()
+                        .unwrap()
+                        .to_string();
+                    assert!(text.contains("cargo test"));
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_initialize_echoes_supported_protocol_version() {
+        let server = test_server();
+        let response = server
+            .handle_request(request(
+                1,
+                "initialize",
+                json!({"protocolVersion": "2025-03-26"}),
+            ))
+            .unwrap();
+
+        let result = response.result.expect("expected initialize result");
+        assert_eq!(result["protocolVersion"], "2025-03-26");
+        assert_eq!(server.protocol_version().as_deref(), Some("2025-03-26"));
+        assert!(result["capabilities"].get("completions").is_some());
+    }
+
+    #[test]
+    fn test_initialize_offers_capabilities_of_the_negotiated_revision() {
+        let server = test_server();
+        let response = server
+            .handle_request(request(
+                1,
+                "initialize",
+                json!({"protocolVersion": "2024-11-05"}),
+            ))
+            .unwrap();
+
+        let capabilities = &response.result.unwrap()["capabilities"];
+        assert!(capabilities.get("completions").is_none());
+        assert!(capabilities.get("tools").is_some());
+    }
+
+    #[test]
+    fn test_initialize_returns_instructions() {
+        let instructions = |server: &Server| {
+            let response = server
+                .handle_request(request(1, "initialize", json!({})))
+                .unwrap();
+            response.result.unwrap()["instructions"].clone()
+        };
+        let (temp, server) = server_with_project();
+        assert_eq!(instructions(&server), DEFAULT_INSTRUCTIONS);
+
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            "[instructions]\ntext = \"Never edit generated/.\"\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+        let text = instructions(&server);
+        let text = text.as_str().unwrap();
+        assert!(text.starts_with(DEFAULT_INSTRUCTIONS));
+        assert!(text.ends_with("\n\nNever edit generated/."));
+
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            "[instructions]\ninclude_default = false\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+        assert!(instructions(&server).is_null());
+    }
+
+    #[test]
+    fn test_initialize_records_client_info() {
+        let server = test_server();
+        assert!(server.health().get("client").is_none());
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({
+                "protocolVersion": "2025-06-18",
+                "capabilities": {},
+                "clientInfo": {"name": "zed", "version": "0.190.2"}
+            }),
+        ));
+        assert_eq!(server.health()["client"], "zed 0.190.2");
+
+        let response = server
+            .handle_request(request(
+                2,
+                "tools/call",
+                json!({"name": "get_session_summary", "arguments": {}}),
+            ))
+            .unwrap();
+        let text = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(
+            text.starts_with("# Session Summary\n\n**Client:** zed 0.190.2\n**Tool calls:** 0\n")
+        );
+
+        assert_eq!(
+            ClientInfo::parse(&json!({"clientInfo": {"name": " "}})),
+            None
+        );
+    }
+
+    #[test]
+    fn test_experimental_structured_output_is_opt_in() {
+        let (temp, server) = server_with_project();
+        let skills = temp.path().join("app/.jumble/skills");
+        std::fs::create_dir_all(&skills).unwrap();
+        std::fs::write(skills.join("debug.md"), "Read the logs first.\n").unwrap();
+        server.reload_workspace_and_projects().unwrap();
+        let init = |experimental: Value| {
+            server
+                .handle_request(request(
+                    1,
+                    "initialize",
+                    json!({
+                        "protocolVersion": "2025-06-18",
+                        "capabilities": {"experimental": experimental}
+                    }),
+                ))
+                .unwrap()
+                .result
+                .unwrap()
+        };
+        let call = |arguments: Value| {
+            server
+                .handle_request(request(
+                    2,
+                    "tools/call",
+                    json!({"name": "get_skill", "arguments": arguments}),
+                ))
+                .unwrap()
+                .result
+                .unwrap()
+        };
+        let messages = json!({"project": "app", "topic": "debug", "format": "messages"});
+
+        let result = init(json!({}));
+        assert!(result["capabilities"]["experimental"]
+            .get(protocol::STRUCTURED_OUTPUT)
+            .is_some());
+        assert!(call(messages.clone()).get("structuredContent").is_none());
+
+        init(json!({ protocol::STRUCTURED_OUTPUT: {}, "other/feature": {} }));
+        assert_eq!(
+            call(messages)["structuredContent"]["messages"][0]["role"],
+            "user"
+        );
+        // Markdown results stay text-only.
+        assert!(call(json!({"project": "app", "topic": "debug"}))
+            .get("structuredContent")
+            .is_none());
+    }
+
+    #[test]
+    fn test_tools_with_output_schemas_return_structured_content() {
+        let (temp, server) = server_with_project();
+        std::fs::write(
+            temp.path().join("app/.jumble/project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"App\"\n\n\
+             [commands]\ntest = \"cargo test\"\n\n\
+             [profiles.ci.commands]\ntest = \"cargo test --locked\"\n\n\
+             [dependencies]\ninternal = [\"shared\"]\n\n\
+             [concepts.auth]\nfiles = [\"src/auth.rs\"]\nsummary = \"Login\"\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+        let call = |name: &str, arguments: Value| {
+            server
+                .handle_tools_call(&json!({"name": name, "arguments": arguments}), None)
+                .unwrap()
+        };
+
+        let listed = server.handle_tools_list(&json!({})).unwrap();
+        let declared: Vec<&str> = listed["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|t| t.get("outputSchema").is_some())
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            declared,
+            [
+                "list_projects",
+                "get_project_info",
+                "get_commands",
+                "get_architecture",
+                "get_last_run"
+            ]
+        );
+
+        let listed = call("list_projects", json!({}));
+        assert_eq!(listed["structuredContent"]["total"], 1);
+        assert_eq!(listed["structuredContent"]["projects"][0]["name"], "app");
+
+        let info = call("get_project_info", json!({"project": "app"}));
+        assert!(info["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .starts_with("# app"));
+        assert_eq!(
+            info["structuredContent"]["dependencies"]["internal"],
+            json!(["shared"])
+        );
+
+        let commands = call("get_commands", json!({"project": "app", "profile": "ci"}));
+        assert_eq!(
+            commands["structuredContent"]["commands"][0],
+            json!({"name": "test", "command": "cargo test --locked", "source": "profile ci"})
+        );
+        let one = call(
+            "get_commands",
+            json!({"project": "app", "command_type": "test"}),
+        );
+        assert_eq!(
+            one["structuredContent"]["commands"],
+            json!([{"name": "test", "command": "cargo test", "source": ".jumble/project.toml"}])
+        );
+
+        let concept = call(
+            "get_architecture",
+            json!({"project": "app", "concept": "AUTH"}),
+        );
+        assert_eq!(concept["structuredContent"]["concept"], "auth");
+        assert_eq!(
+            concept["structuredContent"]["files"],
+            json!(["src/auth.rs"])
+        );
+
+        call(
+            "record_command_result",
+            json!({"project": "app", "command_type": "test", "exit_code": 1}),
+        );
+        let runs = call("get_last_run", json!({"project": "app"}));
+        assert_eq!(runs["structuredContent"]["runs"][0]["command_type"], "test");
+        assert_eq!(runs["structuredContent"]["runs"][0]["passed"], false);
+
+        // Errors carry no structured content.
+        let missing = call(
+            "get_architecture",
+            json!({"project": "app", "concept": "billing"}),
+        );
+        assert_eq!(missing["isError"], true);
+        assert!(missing.get("structuredContent").is_none());
+    }
+
+    #[test]
+    fn test_structured_output_is_left_out_before_2025_06_18() {
+        let (_temp, server) = server_with_project();
+        server
+            .handle_request(request(
+                1,
+                "initialize",
+                json!({"protocolVersion": "2025-03-26"}),
+            ))
+            .unwrap();
+
+        let listed = server.handle_tools_list(&json!({})).unwrap();
+        assert!(listed["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|t| t.get("outputSchema").is_none()));
+        let result = server
+            .handle_tools_call(
+                &json!({"name": "get_commands", "arguments": {"project": "app"}}),
+                None,
+            )
+            .unwrap();
+        assert!(result["content"][0]["text"].is_string());
+        assert!(result.get("structuredContent").is_none());
+    }
+
+    #[test]
+    fn test_subscribe_is_offered_only_with_a_watcher() {
+        let initialize = |server: &Server| {
+            let response = server
+                .handle_request(request(
+                    1,
+                    "initialize",
+                    json!({"protocolVersion": "2025-06-18"}),
+                ))
+                .unwrap();
+            response.result.unwrap()["capabilities"]["resources"]["subscribe"].clone()
+        };
+        let server = test_server();
+        assert_eq!(initialize(&server), false);
+        server.set_watching();
+        assert_eq!(initialize(&server.new_session()), true);
+    }
+
+    #[test]
+    fn test_initialize_rejects_incompatible_protocol_version() {
+        let server = test_server();
+        let response = server
+            .handle_request(request(
+                1,
+                "initialize",
+                json!({"protocolVersion": "2020-01-01"}),
+            ))
+            .unwrap();
+
+        let error = response.error.expect("expected initialize error");
+        assert_eq!(error.code, -32602);
+        assert!(error.message.contains("Unsupported protocol version"));
+        assert!(server.protocol_version().is_none());
+    }
+
+    #[test]
+    fn test_strict_mode_does_not_answer_notifications() {
+        let server = strict_server();
+        assert!(server.handle_request(notification("initialized")).is_none());
+        assert!(server
+            .handle_request(notification("notifications/unknown"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_lenient_mode_does_not_answer_notifications() {
+        let server = test_server();
+        assert!(server.handle_request(notification("initialized")).is_none());
+        assert!(server
+            .handle_request(notification("notifications/initialized"))
+            .is_none());
+        assert!(server
+            .handle_request(notification("notifications/unknown"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_cancelled_elicitation_is_abandoned() {
+        let (_temp, server) = server_with_project();
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2025-06-18", "capabilities": {"elicitation": {}}}),
+        ));
+        server.handle_request(request(2, "tools/call", json!({"name": "get_commands"})));
+        let outgoing = server.take_outgoing();
+
+        let mut cancel = notification("notifications/cancelled");
+        cancel.params = json!({"requestId": 2, "reason": "User gave up"});
+        assert!(server.handle_request(cancel).is_none());
+        assert!(server
+            .handle_message(IncomingMessage::Response(JsonRpcClientResponse {
+                id: outgoing[0].id.clone().unwrap(),
+                result: Some(json!({"action": "accept", "content": {"project": "app"}})),
+                error: None,
+            }))
+            .is_none());
+        assert!(server.session().in_flight.is_empty());
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "exec"))]
+    fn test_cancel_request_kills_a_running_custom_tool() {
+        let (temp, server) = server_with_project();
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            "[tools.wait]\ndescription = \"Wait\"\ncommand = \"sleep 5\"\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+        let server = Arc::new(server);
+
+        let started = Instant::now();
+        let running = {
+            let server = Arc::clone(&server);
+            std::thread::spawn(move || {
+                server.handle_request(request(
+                    3,
+                    "tools/call",
+                    json!({"name": "wait", "arguments": {}}),
+                ))
+            })
+        };
+        while server.session().in_flight.is_empty() {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        let mut cancel = notification("$/cancelRequest");
+        cancel.params = json!({"id": 3});
+        assert!(server.handle_request(cancel).is_none());
+        assert!(running.join().unwrap().is_none());
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_strict_mode_rejects_notification_with_id() {
+        let server = strict_server();
+        let response = server
+            .handle_request(request(7, "notifications/initialized", json!({})))
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, protocol::INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_strict_mode_enforces_params_typing() {
+        let server = strict_server();
+
+        let response = server
+            .handle_request(request(1, "initialize", json!({"protocolVersion": 1})))
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, protocol::INVALID_PARAMS);
+
+        let response = server
+            .handle_request(request(
+                2,
+                "tools/call",
+                json!({"name": "list_projects", "arguments": "oops"}),
+            ))
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, protocol::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_strict_mode_reports_unknown_tool_as_protocol_error() {
+        let server = strict_server();
+        let response = server
+            .handle_request(request(1, "tools/call", json!({"name": "no_such_tool"})))
+            .unwrap();
+        let error = response.error.expect("expected protocol error");
+        assert_eq!(error.code, protocol::INVALID_PARAMS);
+
+        let lenient = test_server();
+        let response = lenient
+            .handle_request(request(1, "tools/call", json!({"name": "no_such_tool"})))
+            .unwrap();
+        assert_eq!(response.result.unwrap()["isError"], true);
+    }
+
+    #[test]
+    fn test_author_project_config_round_trips_through_sampling() {
+        let temp = tempfile::TempDir::new().unwrap();
+        std::fs::write(temp.path().join("README.md"), "# Demo\nA demo app").unwrap();
+
+        let server = Server::bare(temp.path().to_path_buf(), ServerOptions::default(), None);
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2025-06-18", "capabilities": {"sampling": {}}}),
+        ));
+
+        let tools = server
+            .handle_request(request(2, "tools/list", json!({})))
+            .unwrap()
+            .result
+            .unwrap();
+        assert!(tools["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|t| t["name"] == "author_project_config"));
+
+        // The tool call is deferred until the client answers the sampling request.
+        let deferred = server.handle_request(request(
+            3,
+            "tools/call",
+            json!({"name": "author_project_config", "arguments": {"directory": "."}}),
+        ));
+        assert!(deferred.is_none());
+        let outgoing = server.take_outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].method, "sampling/createMessage");
+        let sampling_id = outgoing[0].id.clone().unwrap();
+
+        let reply = "```toml\n[project]\nname = \"demo\"\ndescription = \"Demo\"\n```";
+        let response = server
+            .handle_message(IncomingMessage::Response(JsonRpcClientResponse {
+                id: sampling_id,
+                result: Some(
+                    json!({"role": "assistant", "content": {"type": "text", "text": reply}}),
+                ),
+                error: None,
+            }))
+            .expect("expected the deferred tools/call response");
+        assert_eq!(response.id, Some(json!(3)));
+        let text = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.contains("name = \"demo\""));
+        assert!(!temp.path().join(".jumble/project.toml").exists());
+
+        let confirmed = server
+            .handle_request(request(
+                4,
+                "tools/call",
+                json!({"name": "author_project_config", "arguments": {"directory": ".", "confirm": true}}),
+            ))
+            .unwrap();
+        assert!(confirmed.result.unwrap().get("isError").is_none());
+        assert!(temp.path().join(".jumble/project.toml").is_file());
+    }
+
+    #[test]
+    fn test_author_project_config_stays_inside_the_workspace() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let root = temp.path().join("workspace");
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::write(root.join("app/README.md"), "# App").unwrap();
+        let server = Server::bare(root, ServerOptions::default(), None);
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2025-06-18", "capabilities": {"sampling": {}}}),
+        ));
+
+        let outside = temp.path().to_str().unwrap();
+        for (id, directory) in [(2, ".."), (3, "app/../.."), (4, outside)] {
+            let response = server
+                .handle_request(request(
+                    id,
+                    "tools/call",
+                    json!({"name": "author_project_config", "arguments": {"directory": directory}}),
+                ))
+                .unwrap();
+            let result = response.result.unwrap();
+            assert_eq!(result["isError"], true, "{}", directory);
+            assert!(result["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .contains("outside the workspace"));
+        }
+        assert!(server.take_outgoing().is_empty());
+
+        let deferred = server.handle_request(request(
+            5,
+            "tools/call",
+            json!({"name": "author_project_config", "arguments": {"directory": "app/../app"}}),
+        ));
+        assert!(deferred.is_none());
+    }
+
+    #[test]
+    fn test_author_project_config_requires_sampling_capability() {
+        let server = test_server();
+        let tools = server
+            .handle_request(request(1, "tools/list", json!({})))
+            .unwrap()
+            .result
+            .unwrap();
+        assert!(!tools["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|t| t["name"] == "author_project_config"));
+
+        let response = server
+            .handle_request(request(
+                2,
+                "tools/call",
+                json!({"name": "author_project_config", "arguments": {"directory": "."}}),
+            ))
+            .unwrap();
+        assert_eq!(response.result.unwrap()["isError"], true);
+        assert!(server.take_outgoing().is_empty());
+    }
+
+    fn server_with_project() -> (tempfile::TempDir, Server) {
+        let temp = tempfile::TempDir::new().unwrap();
+        let jumble_dir = temp.path().join("app/.jumble");
+        std::fs::create_dir_all(&jumble_dir).unwrap();
+        std::fs::write(
+            jumble_dir.join("project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"App\"\n\n[commands]\ntest = \"cargo test\"\n",
+        )
+        .unwrap();
+
+        let server = Server::bare(temp.path().to_path_buf(), ServerOptions::default(), None);
+        server.reload_workspace_and_projects().unwrap();
+        (temp, server)
+    }
+
+    #[test]
+    fn test_completion_complete_suggests_commands() {
+        let (_temp, server) = server_with_project();
+        let response = server
+            .handle_request(request(1, "initialize", json!({})))
+            .unwrap();
+        assert_eq!(
+            response.result.unwrap()["capabilities"]["completions"],
+            json!({})
+        );
+
+        let response = server
+            .handle_request(request(
+                2,
+                "completion/complete",
+                json!({
+                    "ref": {"type": "ref/tool", "name": "get_commands"},
+                    "argument": {"name": "command_type", "value": "t"},
+                    "context": {"arguments": {"project": "app"}}
+                }),
+            ))
+            .unwrap();
+        assert_eq!(
+            response.result.unwrap()["completion"]["values"],
+            json!(["test"])
+        );
+
+        let response = server
+            .handle_request(request(3, "resources/templates/list", json!({})))
+            .unwrap();
+        assert_eq!(
+            response.result.unwrap()["resourceTemplates"][0]["uriTemplate"],
+            resources::DOC_TEMPLATE
+        );
+    }
+
+    #[test]
+    fn test_watch_reloads_and_notifies_subscribers() {
+        let (temp, server) = server_with_project();
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2025-06-18", "capabilities": {}}),
+        ));
+        server.handle_request(request(
+            2,
+            "resources/subscribe",
+            json!({"uri": resources::WORKSPACE_OVERVIEW_URI}),
+        ));
+        let mut fingerprint = crate::watch::scan(&server);
+        assert!(!crate::watch::poll(&server, &mut fingerprint));
+
+        std::fs::write(
+            temp.path().join("app/.jumble/project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"Renamed app\"\n",
+        )
+        .unwrap();
+        assert!(crate::watch::poll(&server, &mut fingerprint));
+        let outgoing = server.take_outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].method, "notifications/resources/updated");
+        assert_eq!(
+            server.discovery().projects["app"].1.project.description,
+            "Renamed app"
+        );
+        assert!(!crate::watch::poll(&server, &mut fingerprint));
+    }
+
+    #[test]
+    fn test_watch_spawn_notifies_when_a_file_changes() {
+        let (temp, server) = server_with_project();
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2025-06-18", "capabilities": {}}),
+        ));
+        server.handle_request(request(
+            2,
+            "resources/subscribe",
+            json!({"uri": resources::WORKSPACE_OVERVIEW_URI}),
+        ));
+        let server = Arc::new(server);
+        let (tx, rx) = std::sync::mpsc::channel();
+        crate::watch::spawn(Arc::clone(&server), move |outgoing| {
+            let _ = tx.send(outgoing);
+        });
+        // Let the watcher register before the edit.
+        std::thread::sleep(std::time::Duration::from_millis(200));
+
+        std::fs::write(
+            temp.path().join("app/.jumble/project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"Watched app\"\n",
+        )
+        .unwrap();
+        let outgoing = rx
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("expected a notification after the edit");
+        assert_eq!(outgoing[0].method, "notifications/resources/updated");
+        assert_eq!(
+            server.discovery().projects["app"].1.project.description,
+            "Watched app"
+        );
+    }
+
+    #[test]
+    fn test_reload_sessions_notifies_each_subscriber() {
+        let (temp, server) = server_with_project();
+        let subscribed = Arc::new(server.new_session());
+        let other = Arc::new(server.new_session());
+        let (tx, rx) = std::sync::mpsc::channel();
+        for (name, session) in [("subscribed", &subscribed), ("other", &other)] {
+            session.handle_request(request(
+                1,
+                "initialize",
+                json!({"protocolVersion": "2025-06-18", "capabilities": {}}),
+            ));
+            let tx = tx.clone();
+            session.set_notifier(Box::new(move |message| {
+                let _ = tx.send((name, message.method.clone()));
+            }));
+        }
+        subscribed.handle_request(request(
+            2,
+            "resources/subscribe",
+            json!({"uri": resources::WORKSPACE_OVERVIEW_URI}),
+        ));
+
+        std::fs::write(
+            temp.path().join("app/.jumble/project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"Edited app\"\n",
+        )
+        .unwrap();
+        server
+            .reload_sessions(&[Arc::clone(&subscribed), Arc::clone(&other)])
+            .unwrap();
+        let sent: Vec<_> = rx.try_iter().collect();
+        assert_eq!(
+            sent,
+            [("subscribed", "notifications/resources/updated".to_string())]
+        );
+        assert_eq!(
+            other.discovery().projects["app"].1.project.description,
+            "Edited app"
+        );
+    }
+
+    #[test]
+    fn test_next_steps_footer_is_opt_in() {
+        let (temp, server) = server_with_project();
+        let call = |arguments: Value| -> String {
+            let response = server
+                .handle_request(request(
+                    1,
+                    "tools/call",
+                    json!({"name": "get_project_info", "arguments": arguments}),
+                ))
+                .unwrap();
+            response.result.unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        assert!(!call(json!({"project": "app"})).contains("Next steps"));
+
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(temp.path().join(".jumble/workspace.toml"), "[next_steps]\n").unwrap();
+        server.reload_workspace_and_projects().unwrap();
+        assert!(call(json!({"project": "app"}))
+            .ends_with("**Next steps:** `get_commands(project: \"app\")`, `get_conventions(project: \"app\")`, `get_architecture(project: \"app\")`\n"));
+        let json = call(json!({"project": "app", "as_json": true}));
+        assert!(serde_json::from_str::<Value>(&json).is_ok());
+    }
+
+    #[test]
+    fn test_reload_reports_progress_when_asked() {
+        let (_temp, server) = server_with_project();
+        let reload = |meta: Value| {
+            server.handle_request(request(
+                1,
+                "tools/call",
+                json!({"name": "reload_workspace", "arguments": {}, "_meta": meta}),
+            ))
+        };
+        reload(json!({}));
+        assert!(server.take_outgoing().is_empty());
+
+        // Without a notifier, progress is queued ahead of the response.
+        *server.last_reload.lock().unwrap() = None;
+        reload(json!({"progressToken": "scan"}));
+        let outgoing = server.take_outgoing();
+        assert!(outgoing.len() >= 2);
+        assert!(outgoing
+            .iter()
+            .all(|m| m.method == "notifications/progress" && m.params["progressToken"] == "scan"));
+        assert_eq!(outgoing[0].params["progress"], 0);
+        let last = &outgoing.last().unwrap().params;
+        assert_eq!(last["progress"], last["total"]);
+        assert_eq!(last["message"], "Found 1 project(s)");
+
+        // With one, it goes out while the call runs.
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&sent);
+        server.set_notifier(Box::new(move |message| {
+            sink.lock()
+                .unwrap()
+                .push(message.params["progressToken"].clone())
+        }));
+        *server.last_reload.lock().unwrap() = None;
+        reload(json!({"progressToken": 7}));
+        assert!(server.take_outgoing().is_empty());
+        assert!(sent.lock().unwrap().iter().all(|token| token == 7));
+        assert!(sent.lock().unwrap().len() >= 2);
+    }
+
+    #[test]
+    fn test_related_projects_resolve_in_other_workspaces() {
+        let (temp, server) = server_with_project();
+        let libs = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(libs.path().join("http/.jumble")).unwrap();
+        std::fs::write(
+            libs.path().join("http/.jumble/project.toml"),
+            "[project]\nname = \"http\"\ndescription = \"HTTP client\"\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            format!(
+                "[workspaces]\nlibs = {:?}\n",
+                libs.path().display().to_string()
+            ),
+        )
+        .unwrap();
+        std::fs::write(
+            temp.path().join("app/.jumble/project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"App\"\n\n[related_projects]\nupstream = [\"libs:http\", \"libs:missing\"]\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+
+        let response = server
+            .handle_request(request(
+                1,
+                "tools/call",
+                json!({"name": "get_project_info", "arguments": {"project": "app", "field": "related_projects"}}),
+            ))
+            .unwrap();
+        let text = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.contains(&format!(
+            "- libs:http: HTTP client ({})",
+            libs.path().join("http").display()
+        )));
+        assert!(text.contains("- libs:missing (not found"));
+        // Linked projects are not served as projects of this workspace.
+        assert!(!server.discovery().projects.contains_key("http"));
+    }
+
+    #[test]
+    fn test_output_section_restyles_tool_results() {
+        let (temp, server) = server_with_project();
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            "[output]\nheading_level = 2\nbullet = \"*\"\npaths = \"relative\"\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+        let response = server
+            .handle_request(request(
+                1,
+                "tools/call",
+                json!({"name": "get_project_info", "arguments": {"project": "app"}}),
+            ))
+            .unwrap();
+        let text = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.starts_with("## app\n"));
+        assert!(text.contains("**Path:** .\n"));
+    }
+
+    #[test]
+    fn test_output_clients_restyle_results_for_the_named_client() {
+        let (temp, server) = server_with_project();
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            "[output]\nheading_level = 2\n\n[output.clients.zed]\nheading_level = 3\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+        let heading = |client: &str| {
+            server.handle_request(request(
+                1,
+                "initialize",
+                json!({"protocolVersion": "2025-06-18", "clientInfo": {"name": client}}),
+            ));
+            let response = server
+                .handle_request(request(
+                    2,
+                    "tools/call",
+                    json!({"name": "get_project_info", "arguments": {"project": "app"}}),
+                ))
+                .unwrap();
+            let text = response.result.unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string();
+            text.lines().next().unwrap().to_string()
+        };
+        assert_eq!(heading("Zed"), "### app");
+        assert_eq!(heading("cursor"), "## app");
+    }
+
+    #[test]
+    fn test_path_style_argument_overrides_workspace_default() {
+        let (temp, server) = server_with_project();
+        let call = |arguments: Value| -> Value {
+            server
+                .handle_request(request(
+                    1,
+                    "tools/call",
+                    json!({"name": "get_project_info", "arguments": arguments}),
+                ))
+                .unwrap()
+                .result
+                .unwrap()
+        };
+        let app = temp.path().join("app");
+        let text = call(json!({"project": "app"}))["content"][0]["text"].clone();
+        assert!(text
+            .as_str()
+            .unwrap()
+            .contains(&format!("**Path:** {}\n", app.display())));
+
+        let text =
+            call(json!({"project": "app", "path_style": "uri"}))["content"][0]["text"].clone();
+        assert!(text
+            .as_str()
+            .unwrap()
+            .contains(&format!("**Path:** {}\n", crate::paths::file_uri(&app))));
+
+        let error = call(json!({"project": "app", "path_style": "posix"}));
+        assert_eq!(error["isError"], true);
+        assert_eq!(
+            error["content"][0]["text"],
+            "Error: Invalid path_style \"posix\": use \"relative\", \"absolute\" or \"uri\""
+        );
+    }
+
+    #[test]
+    fn test_scope_limits_discovery_to_subtree() {
+        let temp = tempfile::TempDir::new().unwrap();
+        for (dir, name) in [("services/billing", "billing"), ("web", "web")] {
+            let jumble_dir = temp.path().join(dir).join(".jumble");
+            std::fs::create_dir_all(&jumble_dir).unwrap();
+            std::fs::write(
+                jumble_dir.join("project.toml"),
+                format!("[project]\nname = \"{}\"\ndescription = \"x\"\n", name),
+            )
+            .unwrap();
+        }
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            "[conventions]\nerrors = \"Use thiserror\"\n",
+        )
+        .unwrap();
+
+        let options = ServerOptions {
+            scope: Some(PathBuf::from("services")),
+            ..Default::default()
+        };
+        let server = Server::with_options(temp.path().to_path_buf(), options).unwrap();
+        assert_eq!(
+            server.project_names().into_iter().collect::<Vec<_>>(),
+            ["billing"]
+        );
+        assert!(server.discovery().workspace.is_some());
+        assert_eq!(server.health()["scope"], "services");
+
+        for scope in ["../elsewhere", "missing"] {
+            let options = ServerOptions {
+                scope: Some(PathBuf::from(scope)),
+                ..Default::default()
+            };
+            assert!(Server::with_options(temp.path().to_path_buf(), options).is_err());
+        }
+    }
+
+    #[test]
+    fn test_discovery_from_canonical_root() {
+        // On Windows canonicalize yields a `\\?\C:\...` verbatim path.
+        let temp = tempfile::TempDir::new().unwrap();
+        let jumble_dir = temp.path().join("app").join(".jumble");
+        std::fs::create_dir_all(jumble_dir.join("skills")).unwrap();
+        std::fs::write(
+            jumble_dir.join("project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"App\"\n",
+        )
+        .unwrap();
+        std::fs::write(jumble_dir.join("skills").join("Review.MD"), "Review code").unwrap();
+
+        let root = std::fs::canonicalize(temp.path()).unwrap();
+        let server = Server::bare(root, ServerOptions::default(), None);
+        server.reload_workspace_and_projects().unwrap();
+
+        let discovery = server.discovery();
+        let (_, _, skills, _, _, _) = discovery.projects.get("app").unwrap();
+        assert!(skills.skills.contains_key("Review"));
+    }
+
+    #[test]
+    fn test_missing_project_is_elicited_when_supported() {
+        let (_temp, server) = server_with_project();
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2025-06-18", "capabilities": {"elicitation": {}}}),
+        ));
+
+        let deferred = server.handle_request(request(
+            2,
+            "tools/call",
+            json!({"name": "get_commands", "arguments": {"command_type": "test"}}),
+        ));
+        assert!(deferred.is_none());
+
+        let outgoing = server.take_outgoing();
+        assert_eq!(outgoing[0].method, "elicitation/create");
+        assert_eq!(
+            outgoing[0].params["requestedSchema"]["properties"]["project"]["enum"],
+            json!(["app"])
+        );
+
+        let response = server
+            .handle_message(IncomingMessage::Response(JsonRpcClientResponse {
+                id: outgoing[0].id.clone().unwrap(),
+                result: Some(json!({"action": "accept", "content": {"project": "app"}})),
+                error: None,
+            }))
+            .unwrap();
+        assert_eq!(response.id, Some(json!(2)));
+        let text = response.result.unwrap()["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.contains("cargo test"));
+    }
+
+    #[test]
+    fn test_declined_elicitation_returns_missing_argument_error() {
+        let (_temp, server) = server_with_project();
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2025-06-18", "capabilities": {"elicitation": {}}}),
+        ));
+        server.handle_request(request(2, "tools/call", json!({"name": "get_commands"})));
+        let outgoing = server.take_outgoing();
+
+        let response = server
+            .handle_message(IncomingMessage::Response(JsonRpcClientResponse {
+                id: outgoing[0].id.clone().unwrap(),
+                result: Some(json!({"action": "decline"})),
+                error: None,
+            }))
+            .unwrap();
+        let result = response.result.unwrap();
+        assert_eq!(result["isError"], true);
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("Missing 'project'"));
+    }
+
+    #[test]
+    fn test_subscribed_overview_is_updated_when_discovery_changes() {
+        let (temp, server) = server_with_project();
+        let response = server
+            .handle_request(request(
+                1,
+                "resources/subscribe",
+                json!({"uri": resources::WORKSPACE_OVERVIEW_URI}),
+            ))
+            .unwrap();
+        assert!(response.error.is_none());
+
+        // Nothing changed on disk: no notification.
+        server.handle_request(request(
+            2,
+            "tools/call",
+            json!({"name": "reload_workspace", "arguments": {}}),
+        ));
+        assert!(server.take_outgoing().is_empty());
+
+        let jumble_dir = temp.path().join("lib/.jumble");
+        std::fs::create_dir_all(&jumble_dir).unwrap();
+        std::fs::write(
+            jumble_dir.join("project.toml"),
+            "[project]\nname = \"lib\"\ndescription = \"Shared code\"\n",
+        )
+        .unwrap();
+        // Skip the reload throttle rather than sleeping.
+        *server.last_reload.lock().unwrap() = None;
+        server.handle_request(request(
+            3,
+            "tools/call",
+            json!({"name": "reload_workspace", "arguments": {}}),
+        ));
+        let outgoing = server.take_outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].method, "notifications/resources/updated");
+        assert_eq!(outgoing[0].params["uri"], resources::WORKSPACE_OVERVIEW_URI);
+
+        let read = server
+            .handle_request(request(
+                4,
+                "resources/read",
+                json!({"uri": resources::WORKSPACE_OVERVIEW_URI}),
+            ))
+            .unwrap();
+        let text = read.result.unwrap()["contents"][0]["text"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(text.contains("**lib**"));
+    }
+
+    #[test]
+    fn test_reload_reports_added_and_removed_projects() {
+        let (temp, server) = server_with_project();
+        server.handle_request(request(
+            1,
+            "initialize",
+            json!({"protocolVersion": "2025-06-18", "capabilities": {}}),
+        ));
+        let reload = |id: i64| {
+            *server.last_reload.lock().unwrap() = None;
+            server.handle_request(request(
+                id,
+                "tools/call",
+                json!({"name": "reload_workspace", "arguments": {}}),
+            ));
+        };
+        let overview = |id: i64| -> String {
+            let response = server
+                .handle_request(request(
+                    id,
+                    "tools/call",
+                    json!({"name": "get_workspace_overview", "arguments": {}}),
+                ))
+                .unwrap();
+            response.result.unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+
+        let jumble_dir = temp.path().join("lib/.jumble");
+        std::fs::create_dir_all(&jumble_dir).unwrap();
+        std::fs::write(
+            jumble_dir.join("project.toml"),
+            "[project]\nname = \"lib\"\ndescription = \"Shared code\"\n",
+        )
+        .unwrap();
+        reload(2);
+        let outgoing = server.take_outgoing();
+        assert_eq!(outgoing.len(), 1);
+        assert_eq!(outgoing[0].method, "notifications/message");
+        assert_eq!(outgoing[0].params["level"], "info");
+        assert!(outgoing[0].params["data"]
+            .as_str()
+            .unwrap()
+            .starts_with("New project 'lib' discovered at "));
+
+        assert!(overview(3).ends_with("\n**Recently added projects:** lib\n"));
+        assert!(!overview(4).contains("Recently added"));
+
+        // Warnings only: the removal is recorded but not logged.
+        server.handle_request(request(5, "logging/setLevel", json!({"level": "warning"})));
+        std::fs::remove_dir_all(temp.path().join("lib")).unwrap();
+        reload(6);
+        assert!(server.take_outgoing().is_empty());
+        assert!(overview(7).ends_with("\n**Recently removed projects:** lib\n"));
+
+        let response = server
+            .handle_request(request(8, "logging/setLevel", json!({"level": "loud"})))
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, protocol::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_empty_workspace_gets_getting_started_guidance_once() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let server =
+            Server::with_options(temp.path().to_path_buf(), ServerOptions::default()).unwrap();
+        assert!(server
+            .startup_hint()
+            .unwrap()
+            .contains(&format!("jumble init --root {}", temp.path().display())));
+
+        let overview = |id: i64| -> String {
+            let response = server
+                .handle_request(request(
+                    id,
+                    "tools/call",
+                    json!({"name": "get_workspace_overview", "arguments": {}}),
+                ))
+                .unwrap();
+            response.result.unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        let first = overview(1);
+        assert!(first.contains("No projects found."));
+        assert!(first.contains("## Getting started"));
+        assert!(first.contains("get_jumble_authoring_prompt"));
+        assert!(!overview(2).contains("Getting started"));
+
+        let (_temp, server) = server_with_project();
+        assert!(server.startup_hint().is_none());
+    }
+
+    #[test]
+    fn test_session_summary_tracks_successful_tool_calls() {
+        let (_temp, server) = server_with_project();
+        let call = |id: i64, name: &str, arguments: Value| -> String {
+            let response = server
+                .handle_request(request(
+                    id,
+                    "tools/call",
+                    json!({"name": name, "arguments": arguments}),
+                ))
+                .unwrap();
+            response.result.unwrap()["content"][0]["text"]
+                .as_str()
+                .unwrap()
+                .to_string()
+        };
+        call(1, "get_commands", json!({"project": "app"}));
+        call(2, "get_commands", json!({"project": "nope"}));
+
+        let summary = call(3, "get_session_summary", json!({}));
+        assert!(summary.contains("**Tool calls:** 1\n"));
+        assert!(summary.contains("- get_commands (app)\n"));
+        assert!(summary.contains("- **app**: project info (`get_project_info`)\n"));
+        assert!(call(4, "get_session_summary", json!({})).contains("**Tool calls:** 1\n"));
+    }
+
+    #[test]
+    fn test_metrics_count_requests_and_tool_calls() {
+        let (_temp, server) = server_with_project();
+        server.handle_request(request(1, "tools/list", json!({})));
+        server.handle_request(request(2, "no/such", json!({})));
+        server.handle_request(request(
+            3,
+            "tools/call",
+            json!({"name": "get_commands", "arguments": {"project": "app"}}),
+        ));
+        server.handle_request(request(
+            4,
+            "tools/call",
+            json!({"name": "not_a_tool", "arguments": {}}),
+        ));
+
+        let text = server.metrics_text();
+        for line in [
+            "jumble_requests_total{method=\"tools/list\"} 1",
+            "jumble_requests_total{method=\"tools/call\"} 2",
+            "jumble_requests_total{method=\"other\"} 1",
+            "jumble_tool_duration_seconds_count{tool=\"get_commands\"} 1",
+            "jumble_tool_duration_seconds_count{tool=\"other\"} 1",
+            "jumble_discovery_duration_seconds_count 1",
+            "jumble_projects 1",
+        ] {
+            assert!(
+                text.lines().any(|l| l == line),
+                "missing {}\n{}",
+                line,
+                text
+            );
+        }
+        assert_eq!(server.health(), json!({"status": "ok", "projects": 1}));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[cfg(feature = "exec")]
+    fn test_workspace_custom_tool_is_listed_and_runs() {
+        let (temp, server) = server_with_project();
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            "[tools.greet]\ndescription = \"Say hello\"\ncommand = \"echo hello {{name}}\"\n\n[tools.list_projects]\ndescription = \"Shadowed\"\ncommand = \"false\"\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+
+        let list = server.handle_tools_list(&json!({})).unwrap();
+        let tools = list["tools"].as_array().unwrap();
+        let greet = tools.iter().find(|t| t["name"] == "greet").unwrap();
+        assert_eq!(greet["inputSchema"]["required"], json!(["name"]));
+        assert_eq!(
+            tools
+                .iter()
+                .filter(|t| t["name"] == "list_projects")
+                .count(),
+            1
+        );
+
+        let result = server
+            .handle_tools_call(
+                &json!({"name": "greet", "arguments": {"name": "$(whoami)"}}),
+                None,
+            )
+            .unwrap();
+        assert!(result.get("isError").is_none());
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("hello $(whoami)"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[cfg(feature = "exec")]
+    fn test_run_skill_script_runs_from_the_project() {
+        let (temp, server) = server_with_project();
+        let skill_dir = temp.path().join("app/.claude/skills/greet");
+        std::fs::create_dir_all(skill_dir.join("scripts")).unwrap();
+        std::fs::write(skill_dir.join("SKILL.md"), "# Greet\n").unwrap();
+        std::fs::write(
+            skill_dir.join("scripts/hello.sh"),
+            "# Say hello from the project.\necho \"hello $1 from $(basename \"$PWD\")\"\n",
+        )
+        .unwrap();
+        server.reload_workspace_and_projects().unwrap();
+
+        let call = |script: &str| {
+            server
+                .handle_tools_call(
+                    &json!({
+                        "name": "run_skill_script",
+                        "arguments": {"project": "app", "topic": "greet", "script": script, "args": ["world"]}
+                    }),
+                    None,
+                )
+                .unwrap()
+        };
+        let result = call("hello.sh");
+        assert!(result.get("isError").is_none());
+        assert!(result["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("hello world from app"));
+        assert_eq!(call("missing.sh")["isError"], true);
+    }
+
+    #[test]
+    fn test_tools_list_is_paginated() {
+        let (temp, server) = server_with_project();
+        let custom: String = (0..tools::PAGE_SIZE)
+            .map(|i| {
+                format!(
+                    "[tools.custom_{:03}]\ndescription = \"Custom\"\ncommand = \"true\"\n\n",
+                    i
+                )
+            })
+            .collect();
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(temp.path().join(".jumble/workspace.toml"), custom).unwrap();
+        server.reload_workspace_and_projects().unwrap();
+
+        let first = server
+            .handle_request(request(1, "tools/list", json!({})))
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(first["tools"].as_array().unwrap().len(), tools::PAGE_SIZE);
+        let cursor = first["nextCursor"].as_str().unwrap().to_string();
+
+        let second = server
+            .handle_request(request(2, "tools/list", json!({ "cursor": cursor })))
+            .unwrap()
+            .result
+            .unwrap();
+        assert!(second.get("nextCursor").is_none());
+        let names: std::collections::HashSet<&str> = first["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .chain(second["tools"].as_array().unwrap())
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert!(names.contains("list_projects") && names.contains("custom_099"));
+        assert_eq!(
+            names.len(),
+            tools::tools_list()["tools"].as_array().unwrap().len() + tools::PAGE_SIZE
+        );
+
+        let response = server
+            .handle_request(request(3, "tools/list", json!({"cursor": "nonsense"})))
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, protocol::INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_requests_over_the_rate_limit_are_rejected() {
+        let options = ServerOptions {
+            max_requests_per_second: 2,
+            ..Default::default()
+        };
+        let server = Server::bare(PathBuf::from("/workspace"), options, None);
+
+        for id in 0..2 {
+            let response = server
+                .handle_request(request(id, "tools/list", json!({})))
+                .unwrap();
+            assert!(response.error.is_none());
+        }
+        let response = server
+            .handle_request(request(2, "tools/list", json!({})))
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, protocol::RATE_LIMITED);
+        assert!(server.handle_request(notification("initialized")).is_none());
+
+        // Pings are answered with an empty result even then.
+        let response = server
+            .handle_request(request(3, "ping", json!({})))
+            .unwrap();
+        assert!(response.error.is_none());
+        assert_eq!(response.result.unwrap(), json!({}));
+    }
+
+    #[test]
+    fn test_uncollected_messages_are_capped() {
+        let server = test_server();
+        for n in 0..limits::MAX_QUEUED_MESSAGES + 5 {
+            server.queue_outgoing(JsonRpcOutgoing::notification(
+                "notifications/message",
+                json!({ "n": n }),
+            ));
+        }
+        let outgoing = server.take_outgoing();
+        assert_eq!(outgoing.len(), limits::MAX_QUEUED_MESSAGES);
+        assert_eq!(outgoing[0].params, json!({"n": 5}));
+    }
+
+    #[test]
+    fn test_sessions_share_the_rate_limit() {
+        let options = ServerOptions {
+            max_requests_per_second: 2,
+            ..Default::default()
+        };
+        let server = Server::bare(PathBuf::from("/workspace"), options, None);
+        let session = server.new_session();
+        for (id, server) in [&server, &session].into_iter().enumerate() {
+            let response = server
+                .handle_request(request(id as i64, "tools/list", json!({})))
+                .unwrap();
+            assert!(response.error.is_none());
+        }
+        let response = session
+            .handle_request(request(2, "tools/list", json!({})))
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, protocol::RATE_LIMITED);
+    }
+
+    #[test]
+    fn test_back_to_back_reloads_are_throttled() {
+        let (_temp, server) = server_with_project();
+        let reload = json!({"name": "reload_workspace", "arguments": {}});
+        let first = server.handle_tools_call(&reload, None).unwrap();
+        assert!(first.get("isError").is_none());
+        let second = server.handle_tools_call(&reload, None).unwrap();
+        assert_eq!(second["isError"], true);
+        assert!(second["content"][0]["text"]
+            .as_str()
+            .unwrap()
+            .contains("reload_workspace called too frequently"));
+    }
+
+    #[test]
+    fn test_subscribe_to_unknown_resource_is_not_found() {
+        let server = test_server();
+        let response = server
+            .handle_request(request(
+                1,
+                "resources/subscribe",
+                json!({"uri": "jumble://nope"}),
+            ))
+            .unwrap();
+        assert_eq!(response.error.unwrap().code, protocol::RESOURCE_NOT_FOUND);
+    }
+
+    #[test]
+    fn test_missing_project_without_elicitation_is_an_error() {
+        let (_temp, server) = server_with_project();
+        let response = server
+            .handle_request(request(1, "tools/call", json!({"name": "get_commands"})))
+            .unwrap();
+        assert_eq!(response.result.unwrap()["isError"], true);
+        assert!(server.take_outgoing().is_empty());
+    }
 }
@@ -1,149 +1,466 @@
 //! Setup commands for configuring AI agents to use jumble effectively
 
 use anyhow::{Context, Result};
+use serde::Serialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::commands;
+use crate::generate::{self, WriteOutcome};
+use crate::migrate;
+use crate::paths;
+use crate::text;
+
+/// How setup commands report what they did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputMode {
+    /// Progress lines and next steps for a person at a terminal.
+    #[default]
+    Text,
+    /// Nothing on stdout; errors still reach stderr.
+    Quiet,
+    /// A single JSON report on stdout once the command finishes.
+    Json,
+}
+
+/// What a setup command did to a file or directory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Created,
+    Updated,
+    /// Left as it was: it already existed or was already up to date.
+    Skipped,
+}
+
+impl Action {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Action::Created => "created",
+            Action::Updated => "updated",
+            Action::Skipped => "skipped",
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub action: Action,
+    /// Unified diff of the change, reported in dry runs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub diff: Option<String>,
+}
+
+/// Collects what a setup command does, and performs its writes so a dry
+/// run can report them instead. Text mode prints as the command goes; JSON
+/// mode prints everything from [`Reporter::finish`].
+pub struct Reporter {
+    mode: OutputMode,
+    command: &'static str,
+    dry_run: bool,
+    files: Vec<FileReport>,
+    warnings: Vec<String>,
+}
+
+impl Reporter {
+    pub fn new(mode: OutputMode, command: &'static str) -> Self {
+        Self {
+            mode,
+            command,
+            dry_run: false,
+            files: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Report writes with a diff instead of performing them.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Record what happened to `path`; text mode prints `message`, or what
+    /// would happen in a dry run.
+    pub fn file(&mut self, path: &Path, action: Action, message: &str) {
+        self.record(path, action, message, None);
+    }
+
+    fn record(&mut self, path: &Path, action: Action, message: &str, diff: Option<String>) {
+        match action {
+            Action::Created if self.dry_run => {
+                self.say(&format!("Would create {}", path.display()))
+            }
+            Action::Updated if self.dry_run => {
+                self.say(&format!("Would update {}", path.display()))
+            }
+            _ => self.say(message),
+        }
+        if let Some(diff) = &diff {
+            self.say(diff.trim_end());
+        }
+        self.files.push(FileReport {
+            path: path.to_path_buf(),
+            action,
+            diff,
+        });
+    }
+
+    /// Create the directory `path` and record it as created.
+    pub fn create_dir(&mut self, path: &Path, message: &str) -> Result<()> {
+        if !self.dry_run {
+            fs::create_dir_all(path)
+                .with_context(|| format!("Failed to create {}", path.display()))?;
+        }
+        self.file(path, Action::Created, message);
+        Ok(())
+    }
+
+    /// Write `content` to `path` and record `action`. A dry run records a
+    /// diff against the current file instead.
+    pub fn write(
+        &mut self,
+        path: &Path,
+        content: &str,
+        action: Action,
+        message: &str,
+    ) -> Result<()> {
+        if self.dry_run {
+            let current = text::read_text(path).unwrap_or_default();
+            let shown = path.display().to_string();
+            let old = if path.exists() {
+                shown.as_str()
+            } else {
+                "/dev/null"
+            };
+            let diff = similar::TextDiff::from_lines(current.as_str(), content)
+                .unified_diff()
+                .header(old, &shown)
+                .to_string();
+            self.record(path, action, message, Some(diff).filter(|d| !d.is_empty()));
+            return Ok(());
+        }
+        fs::write(path, content).with_context(|| format!("Failed to write {}", path.display()))?;
+        self.file(path, action, message);
+        Ok(())
+    }
+
+    /// Record a warning; text mode prints it after a blank line.
+    pub fn warn(&mut self, message: &str) {
+        self.say("");
+        self.say(&format!("⚠️  {}", message));
+        self.warnings.push(message.to_string());
+    }
+
+    /// A line of guidance that only text mode shows.
+    pub fn say(&self, line: &str) {
+        if self.mode == OutputMode::Text {
+            println!("{}", line);
+        }
+    }
+
+    pub fn files(&self) -> &[FileReport] {
+        &self.files
+    }
+
+    /// Print the JSON report in JSON mode, including the error if the
+    /// command failed, and pass `result` through.
+    pub fn finish(self, result: Result<()>) -> Result<()> {
+        if self.mode == OutputMode::Json {
+            let mut report = serde_json::json!({
+                "command": self.command,
+                "dry_run": self.dry_run,
+                "ok": result.is_ok(),
+                "files": self.files,
+                "warnings": self.warnings,
+            });
+            if let Err(e) = &result {
+                report["error"] = format!("{:#}", e).into();
+            }
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        } else if self.dry_run && result.is_ok() {
+            self.say("");
+            self.say("Dry run: nothing was written. Run again without --dry-run to apply.");
+        }
+        result
+    }
+}
 
 /// Initialize a new jumble project by creating necessary directories and config files
-pub fn setup_init(workspace_root: &Path) -> Result<()> {
-    // Create .jumble directory
-    let jumble_dir = workspace_root.join(".jumble");
+pub fn setup_init(workspace_root: &Path, report: &mut Reporter) -> Result<()> {
+    // Create the config directory (.jumble unless --config-dir says otherwise)
+    let config_dir = paths::config_dir().display();
+    let jumble_dir = workspace_root.join(paths::config_dir());
     if jumble_dir.exists() {
-        println!("✓ .jumble directory already exists");
+        report.file(
+            &jumble_dir,
+            Action::Skipped,
+            &format!("✓ {} directory already exists", config_dir),
+        );
     } else {
-        fs::create_dir_all(&jumble_dir).context("Failed to create .jumble directory")?;
-        println!("✓ Created .jumble directory");
+        report.create_dir(&jumble_dir, &format!("✓ Created {} directory", config_dir))?;
     }
 
-    // Create .jumble/project.toml if it doesn't exist
+    // Create project.toml if it doesn't exist
     let project_toml = jumble_dir.join("project.toml");
     if project_toml.exists() {
-        println!("✓ .jumble/project.toml already exists");
+        report.file(
+            &project_toml,
+            Action::Skipped,
+            &format!("✓ {}/project.toml already exists", config_dir),
+        );
     } else {
-        let default_project = r#"[project]
-name = "my-project"
-description = "A brief description of your project"
-
-# [commands]
-# build = "cargo build"
-# test = "cargo test"
-# lint = "cargo clippy"
-
-# [entry_points]
-# main = "src/main.rs"
-"#;
-        fs::write(&project_toml, default_project)
-            .context("Failed to create .jumble/project.toml")?;
-        println!("✓ Created .jumble/project.toml (edit to configure)");
+        report.write(
+            &project_toml,
+            &project_template("my-project", None),
+            Action::Created,
+            &format!("✓ Created {}/project.toml (edit to configure)", config_dir),
+        )?;
     }
 
     // Create .ai directory
     let ai_dir = workspace_root.join(".ai");
     if ai_dir.exists() {
-        println!("✓ .ai directory already exists");
+        report.file(&ai_dir, Action::Skipped, "✓ .ai directory already exists");
     } else {
-        fs::create_dir_all(&ai_dir).context("Failed to create .ai directory")?;
-        println!("✓ Created .ai directory");
+        report.create_dir(&ai_dir, "✓ Created .ai directory")?;
     }
 
     // Create .ai/constitution.md if it doesn't exist
     let constitution = ai_dir.join("constitution.md");
     if constitution.exists() {
-        println!("✓ .ai/constitution.md already exists");
+        report.file(
+            &constitution,
+            Action::Skipped,
+            "✓ .ai/constitution.md already exists",
+        );
     } else {
-        fs::write(&constitution, "").context("Failed to create .ai/constitution.md")?;
-        println!("✓ Created .ai/constitution.md (blank for now)");
+        report.write(
+            &constitution,
+            "",
+            Action::Created,
+            "✓ Created .ai/constitution.md (blank for now)",
+        )?;
     }
 
     // Create docs directory
     let docs_dir = workspace_root.join("docs");
     if docs_dir.exists() {
-        println!("✓ docs directory already exists");
+        report.file(
+            &docs_dir,
+            Action::Skipped,
+            "✓ docs directory already exists",
+        );
     } else {
-        fs::create_dir_all(&docs_dir).context("Failed to create docs directory")?;
-        println!("✓ Created docs directory");
+        report.create_dir(&docs_dir, "✓ Created docs directory")?;
     }
 
     // Create AGENTS.md
     let agents_md = workspace_root.join("AGENTS.md");
     if agents_md.exists() {
-        println!("✓ AGENTS.md already exists");
+        report.file(&agents_md, Action::Skipped, "✓ AGENTS.md already exists");
     } else {
-        let agents_content = r#"# Using Jumble in This Project
-
-This project uses Jumble to provide AI agents with structured context about the codebase.
+        let body = generate::render_agents_md(workspace_root)?;
+        let content = generate::new_marked_file("# AGENTS.md", generate::AGENTS_MD, &body);
+        report.write(&agents_md, &content, Action::Created, "✓ Created AGENTS.md")?;
+    }
 
-## Getting Started
+    // Create .gitignore if it doesn't exist (empty by default)
+    let gitignore = workspace_root.join(".gitignore");
+    if gitignore.exists() {
+        report.file(&gitignore, Action::Skipped, "✓ .gitignore already exists");
+    } else {
+        report.write(&gitignore, "", Action::Created, "✓ Created .gitignore")?;
+    }
 
-1. **Always start by calling `get_workspace_overview()`** from the Jumble MCP server to understand the project structure.
+    report.say("");
+    report.say("✨ Project initialized!");
+    report.say("");
+    report.say("Next steps:");
+    report.say("1. Read AGENTS.md for guidance on using Jumble");
+    report.say("2. Add your project guidelines to .ai/constitution.md");
+    report.say(&format!(
+        "3. Edit {}/project.toml to configure your project",
+        config_dir
+    ));
+    report.say("4. Add project documentation to the docs/ directory");
+    report.say("5. Commit these files to version control");
 
-2. **Check the project constitution** in `.ai/constitution.md` for any project-specific guidelines and rules that should be followed.
+    Ok(())
+}
 
-3. **Use the Jumble tools** to access project metadata before making changes.
+/// Starter `.jumble/project.toml`, with `language` set when it is known.
+fn project_template(name: &str, language: Option<&str>) -> String {
+    let language = language
+        .map(|language| format!("language = \"{}\"\n", language))
+        .unwrap_or_default();
+    format!(
+        r#"config_version = {}
 
-## When to Use Jumble Tools
+[project]
+name = "{}"
+description = "A brief description of your project"
+{}
+# [commands]
+# build = "cargo build"
+# test = "cargo test"
+# lint = "cargo clippy"
 
-### Before suggesting commands
-- Call `get_commands(project, type)` to get exact build/test/lint/run commands
-- Never guess commands when Jumble can provide them
+# [entry_points]
+# main = "src/main.rs"
+"#,
+        migrate::CURRENT_CONFIG_VERSION,
+        name,
+        language
+    )
+}
 
-### Before making architectural changes
-- Call `get_architecture(project, concept)` to understand existing patterns
-- Use `get_related_files(project, query)` to find related code
+/// Starter `.jumble/workspace.toml`.
+fn workspace_template(name: &str) -> String {
+    format!(
+        r#"config_version = {}
 
-### Before writing new code
-- Call `get_conventions(project)` for project-specific patterns
-- Call `get_workspace_conventions()` for workspace-wide standards
-- Review both conventions AND gotchas
+[workspace]
+name = "{}"
+description = "A brief description of your workspace"
 
-### Before searching for documentation
-- Call `get_docs(project)` to see available documentation
-- Use topic names to get specific doc paths
+[conventions]
+# error_handling = "Libraries return typed errors; binaries use anyhow"
 
-## Project Guidelines
+[gotchas]
+# shared_schema = "Regenerate clients after changing the shared API schema"
+"#,
+        migrate::CURRENT_CONFIG_VERSION,
+        name
+    )
+}
 
-See `.ai/constitution.md` for project-specific guidelines, conventions, and any special instructions for AI agents working on this codebase.
+/// `jumble init --workspace`: create `.jumble/workspace.toml` at the root.
+/// With `projects`, also scaffold `.jumble/project.toml` in each immediate
+/// subdirectory that has a build manifest jumble recognizes.
+pub fn setup_init_workspace(
+    workspace_root: &Path,
+    projects: bool,
+    report: &mut Reporter,
+) -> Result<()> {
+    let config_dir = paths::config_dir().display();
+    let jumble_dir = workspace_root.join(paths::config_dir());
+    if jumble_dir.exists() {
+        report.file(
+            &jumble_dir,
+            Action::Skipped,
+            &format!("✓ {} directory already exists", config_dir),
+        );
+    } else {
+        report.create_dir(&jumble_dir, &format!("✓ Created {} directory", config_dir))?;
+    }
 
-## Available Jumble Tools
+    let workspace_toml = jumble_dir.join("workspace.toml");
+    if workspace_toml.exists() {
+        report.file(
+            &workspace_toml,
+            Action::Skipped,
+            &format!("✓ {}/workspace.toml already exists", config_dir),
+        );
+    } else {
+        let name = dir_name(workspace_root).unwrap_or_else(|| "my-workspace".to_string());
+        report.write(
+            &workspace_toml,
+            &workspace_template(&name),
+            Action::Created,
+            &format!(
+                "✓ Created {}/workspace.toml (edit to configure)",
+                config_dir
+            ),
+        )?;
+    }
 
-- `list_projects` - List all projects in workspace
-- `get_workspace_overview` - Workspace structure and dependencies
-- `get_workspace_conventions` - Workspace-level conventions/gotchas
-- `get_project_info` - Project metadata and structure
-- `get_commands` - Build/test/lint/run commands
-- `get_architecture` - Architectural concepts and files
-- `get_related_files` - Find files by concept
-- `get_conventions` - Project conventions and gotchas
-- `get_docs` - Documentation index
-- `list_skills` / `get_skill` - Task-specific guidance
-"#;
-        fs::write(&agents_md, agents_content)
-            .context("Failed to create AGENTS.md")?;
-        println!("✓ Created AGENTS.md");
+    let candidates = manifest_subdirectories(workspace_root)?;
+    if projects {
+        for (dir, language) in &candidates {
+            init_subproject(dir, language, report)?;
+        }
     }
 
-    // Create .gitignore if it doesn't exist (empty by default)
-    let gitignore = workspace_root.join(".gitignore");
-    if gitignore.exists() {
-        println!("✓ .gitignore already exists");
+    report.say("");
+    report.say("✨ Workspace initialized!");
+    report.say("");
+    report.say("Next steps:");
+    report.say(&format!(
+        "1. Edit {}/workspace.toml to describe the workspace and its conventions",
+        config_dir
+    ));
+    if candidates.is_empty() {
+        report.say("2. Run `jumble init` in each project directory");
+    } else if projects {
+        report.say(&format!(
+            "2. Fill in the description of each new {}/project.toml",
+            config_dir
+        ));
     } else {
-        fs::write(&gitignore, "").context("Failed to create .gitignore")?;
-        println!("✓ Created .gitignore");
+        report.say(&format!(
+            "2. Run `jumble init --workspace --projects` to add project files to the {} subdirectories with a build manifest",
+            candidates.len()
+        ));
     }
-
-    println!();
-    println!("✨ Project initialized!");
-    println!();
-    println!("Next steps:");
-    println!("1. Read AGENTS.md for guidance on using Jumble");
-    println!("2. Add your project guidelines to .ai/constitution.md");
-    println!("3. Edit .jumble/project.toml to configure your project");
-    println!("4. Add project documentation to the docs/ directory");
-    println!("5. Commit these files to version control");
+    report.say("3. Commit these files to version control");
 
     Ok(())
 }
 
+/// Immediate, non-hidden subdirectories of `root` with a recognizable
+/// manifest, and the language it implies, sorted by path.
+fn manifest_subdirectories(root: &Path) -> Result<Vec<(PathBuf, &'static str)>> {
+    // A dry run of `init` in a directory that does not exist yet.
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+    let entries =
+        fs::read_dir(root).with_context(|| format!("Failed to read {}", root.display()))?;
+    let mut found: Vec<(PathBuf, &'static str)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter(|path| !dir_name(path).is_some_and(|name| name.starts_with('.')))
+        .filter_map(|path| commands::detect_language(&path).map(|language| (path, language)))
+        .collect();
+    found.sort();
+    Ok(found)
+}
+
+/// Create `.jumble/project.toml` in `dir`, named after the directory.
+fn init_subproject(dir: &Path, language: &str, report: &mut Reporter) -> Result<()> {
+    let jumble_dir = dir.join(paths::config_dir());
+    let project_toml = jumble_dir.join("project.toml");
+    if project_toml.exists() {
+        report.file(
+            &project_toml,
+            Action::Skipped,
+            &format!("✓ {} already exists", project_toml.display()),
+        );
+        return Ok(());
+    }
+    if !jumble_dir.exists() {
+        report.create_dir(&jumble_dir, &format!("✓ Created {}", jumble_dir.display()))?;
+    }
+    let name = dir_name(dir).unwrap_or_else(|| "my-project".to_string());
+    report.write(
+        &project_toml,
+        &project_template(&name, Some(language)),
+        Action::Created,
+        &format!("✓ Created {} ({})", project_toml.display(), language),
+    )
+}
+
+/// The last component of `path`, resolving roots such as `.` first.
+fn dir_name(path: &Path) -> Option<String> {
+    let name = match path.file_name() {
+        Some(name) => name.to_os_string(),
+        None => fs::canonicalize(path).ok()?.file_name()?.to_os_string(),
+    };
+    name.to_str().map(str::to_string)
+}
+
 const JUMBLE_SECTION: &str = r#"## Using Jumble for Project Context
 
 ALWAYS start workspace exploration by calling `get_workspace_overview()` from the Jumble MCP server to understand the workspace structure, available projects, and their relationships.
@@ -175,7 +492,7 @@ ALWAYS start workspace exploration by calling `get_workspace_overview()` from th
 
 If jumble returns "No projects found":
 1. Call `get_jumble_authoring_prompt()` to get the creation prompt
-2. Offer to create `.jumble/project.toml` for the current project
+2. Offer to create `{config_dir}/project.toml` for the current project
 3. Follow the AUTHORING.md guide
 
 ### Workflow
@@ -187,10 +504,15 @@ If jumble returns "No projects found":
 5. **Running commands** → Use `get_commands(project, type)`
 "#;
 
+/// [`JUMBLE_SECTION`] naming the configured config directory.
+fn jumble_section() -> String {
+    JUMBLE_SECTION.replace("{config_dir}", &paths::config_dir().display().to_string())
+}
+
 const JUMBLE_SECTION_MARKER: &str = "## Using Jumble for Project Context";
 
 /// Setup Warp integration by creating/updating WARP.md
-pub fn setup_warp(workspace_root: &Path, force: bool) -> Result<()> {
+pub fn setup_warp(workspace_root: &Path, force: bool, report: &mut Reporter) -> Result<()> {
     let warp_md = workspace_root.join("WARP.md");
 
     if warp_md.exists() {
@@ -198,17 +520,25 @@ pub fn setup_warp(workspace_root: &Path, force: bool) -> Result<()> {
 
         if content.contains(JUMBLE_SECTION_MARKER) {
             if !force {
-                println!("✓ WARP.md already contains jumble rules");
-                println!();
-                println!("To update the jumble section, run with --force:");
-                println!("  jumble setup warp --force");
+                report.file(
+                    &warp_md,
+                    Action::Skipped,
+                    "✓ WARP.md already contains jumble rules",
+                );
+                report.say("");
+                report.say("To update the jumble section, run with --force:");
+                report.say("  jumble setup warp --force");
                 return Ok(());
             }
 
             // Replace existing section
             let updated = replace_jumble_section(&content)?;
-            fs::write(&warp_md, updated).context("Failed to update WARP.md")?;
-            println!("✓ Updated jumble rules in WARP.md");
+            report.write(
+                &warp_md,
+                &updated,
+                Action::Updated,
+                "✓ Updated jumble rules in WARP.md",
+            )?;
         } else {
             // Append jumble section
             let mut updated = content;
@@ -216,39 +546,47 @@ pub fn setup_warp(workspace_root: &Path, force: bool) -> Result<()> {
                 updated.push('\n');
             }
             updated.push('\n');
-            updated.push_str(JUMBLE_SECTION);
-
-            fs::write(&warp_md, updated).context("Failed to update WARP.md")?;
-            println!("✓ Added jumble rules to existing WARP.md");
+            updated.push_str(&jumble_section());
+
+            report.write(
+                &warp_md,
+                &updated,
+                Action::Updated,
+                "✓ Added jumble rules to existing WARP.md",
+            )?;
         }
     } else {
         // Create new WARP.md
         let content = format!(
             "# WARP.md\n\nThis file provides guidance to WARP (warp.dev) when working with code in this repository.\n\n{}",
-            JUMBLE_SECTION
+            jumble_section()
         );
 
-        fs::write(&warp_md, content).context("Failed to create WARP.md")?;
-        println!("✓ Created WARP.md with jumble rules");
+        report.write(
+            &warp_md,
+            &content,
+            Action::Created,
+            "✓ Created WARP.md with jumble rules",
+        )?;
     }
 
     // Check for .jumble directory
-    let jumble_dir = workspace_root.join(".jumble");
-    if !jumble_dir.exists() {
-        println!();
-        println!("⚠️  No .jumble directory found");
-        println!("   Create .jumble/project.toml to provide project context");
-        println!("   See: https://github.com/velvet-tiger/jumble/blob/main/AUTHORING.md");
-    }
-
-    println!();
-    println!("Next steps:");
-    println!("1. Ensure .jumble/project.toml exists (provides context to jumble)");
-    println!("2. Verify jumble MCP server is configured in Warp:");
-    println!("   - Open Warp settings → AI → MCP Servers");
-    println!("   - Add jumble with: --root {}", workspace_root.display());
-    println!("3. Restart Warp or reload the window to apply changes");
-    println!("4. Commit WARP.md to version control");
+    warn_if_no_jumble_dir(workspace_root, report);
+
+    report.say("");
+    report.say("Next steps:");
+    report.say(&format!(
+        "1. Ensure {}/project.toml exists (provides context to jumble)",
+        paths::config_dir().display()
+    ));
+    report.say("2. Verify jumble MCP server is configured in Warp:");
+    report.say("   - Open Warp settings → AI → MCP Servers");
+    report.say(&format!(
+        "   - Add jumble with: {}",
+        server_args(workspace_root).join(" ")
+    ));
+    report.say("3. Restart Warp or reload the window to apply changes");
+    report.say("4. Commit WARP.md to version control");
 
     Ok(())
 }
@@ -267,7 +605,8 @@ fn replace_jumble_section(content: &str) -> Result<String> {
 
         if in_jumble_section {
             // Check if we've hit another section at same or higher level
-            if line.starts_with("# ") || (line.starts_with("## ") && !line.contains("Using Jumble")) {
+            if line.starts_with("# ") || (line.starts_with("## ") && !line.contains("Using Jumble"))
+            {
                 in_jumble_section = false;
             }
         }
@@ -285,7 +624,8 @@ fn replace_jumble_section(content: &str) -> Result<String> {
         .unwrap_or(result.len());
 
     // Add the new jumble section
-    let jumble_lines: Vec<&str> = JUMBLE_SECTION.lines().collect();
+    let section = jumble_section();
+    let jumble_lines: Vec<&str> = section.lines().collect();
 
     // Insert with proper spacing
     if insert_pos < result.len() {
@@ -295,12 +635,17 @@ fn replace_jumble_section(content: &str) -> Result<String> {
         }
         result.insert(insert_pos + jumble_lines.len(), "");
     } else {
-        // Append to end
+        // Append to end, without stacking blank lines on repeated updates
+        while result.last() == Some(&"") {
+            result.pop();
+        }
         result.push("");
         result.extend(jumble_lines);
     }
 
-    Ok(result.join("\n"))
+    let mut updated = result.join("\n");
+    updated.push('\n');
+    Ok(updated)
 }
 
 const USAGE_GUIDE: &str = r#"# Using Jumble for Project Context
@@ -338,7 +683,7 @@ Jumble provides queryable, on-demand project context to help you work more effec
 
 If jumble returns "No projects found":
 1. Call `get_jumble_authoring_prompt()` to get the creation prompt
-2. Offer to create `.jumble/project.toml` for the current project
+2. Offer to create `{config_dir}/project.toml` for the current project
 3. Follow the AUTHORING.md guide
 
 ## Workflow
@@ -363,8 +708,13 @@ If jumble returns "No projects found":
 - `list_skills` / `get_skill` - Task-specific guidance
 "#;
 
+/// [`USAGE_GUIDE`] naming the configured config directory.
+fn usage_guide() -> String {
+    USAGE_GUIDE.replace("{config_dir}", &paths::config_dir().display().to_string())
+}
+
 /// Setup Claude Desktop integration
-pub fn setup_claude(workspace_root: &Path, global: bool) -> Result<()> {
+pub fn setup_claude(workspace_root: &Path, global: bool, report: &mut Reporter) -> Result<()> {
     let config_dir = if global {
         dirs::home_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
@@ -373,12 +723,10 @@ pub fn setup_claude(workspace_root: &Path, global: bool) -> Result<()> {
         workspace_root.join(".claude")
     };
 
-    fs::create_dir_all(&config_dir).context("Failed to create .claude directory")?;
-
-    let guide_path = config_dir.join("jumble-usage.md");
-    fs::write(&guide_path, USAGE_GUIDE).context("Failed to write usage guide")?;
-
-    println!("✓ Created {}", guide_path.display());
+    if !config_dir.exists() {
+        report.create_dir(&config_dir, &format!("✓ Created {}", config_dir.display()))?;
+    }
+    write_usage_guide(&config_dir, report)?;
 
     // Check MCP config
     let mcp_config = dirs::home_dir()
@@ -390,43 +738,26 @@ pub fn setup_claude(workspace_root: &Path, global: bool) -> Result<()> {
                 fs::read_to_string(&config_path).context("Failed to read Claude config")?;
 
             if content.contains("\"jumble\"") {
-                println!("✓ Jumble MCP server detected in Claude Desktop config");
+                report.say("✓ Jumble MCP server detected in Claude Desktop config");
             } else {
-                println!();
-                println!("⚠️  Jumble not found in Claude Desktop config");
-                println!("   Add to {}:", config_path.display());
-                println!();
-                println!("   {{");
-                println!("     \"mcpServers\": {{");
-                println!("       \"jumble\": {{");
-                let jumble_path = which::which("jumble")
-                    .map(|p| p.display().to_string())
-                    .unwrap_or_else(|_| "/path/to/jumble".to_string());
-                println!("         \"command\": \"{}\",", jumble_path);
-                println!(
-                    "         \"args\": [\"--root\", \"{}\"]",
-                    workspace_root.display()
-                );
-                println!("       }}");
-                println!("     }}");
-                println!("   }}");
-                println!();
-                println!("   Then restart Claude Desktop.");
+                report.warn("Jumble not found in Claude Desktop config");
+                say_json_config_instructions(report, &config_path, workspace_root);
+                report.say("");
+                report.say("   Then restart Claude Desktop.");
             }
         } else {
-            println!();
-            println!("⚠️  Claude Desktop config not found");
-            println!("   Expected: {}", config_path.display());
-            println!("   Configure jumble in Claude Desktop settings.");
+            report.warn("Claude Desktop config not found");
+            report.say(&format!("   Expected: {}", config_path.display()));
+            report.say("   Configure jumble in Claude Desktop settings.");
         }
     }
 
-    print_common_next_steps(workspace_root, "Claude Desktop");
+    say_common_next_steps(workspace_root, "Claude Desktop", report);
     Ok(())
 }
 
 /// Setup Cursor integration
-pub fn setup_cursor(workspace_root: &Path, global: bool) -> Result<()> {
+pub fn setup_cursor(workspace_root: &Path, global: bool, report: &mut Reporter) -> Result<()> {
     let config_dir = if global {
         dirs::home_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
@@ -435,12 +766,10 @@ pub fn setup_cursor(workspace_root: &Path, global: bool) -> Result<()> {
         workspace_root.join(".cursor")
     };
 
-    fs::create_dir_all(&config_dir).context("Failed to create .cursor directory")?;
-
-    let guide_path = config_dir.join("jumble-usage.md");
-    fs::write(&guide_path, USAGE_GUIDE).context("Failed to write usage guide")?;
-
-    println!("✓ Created {}", guide_path.display());
+    if !config_dir.exists() {
+        report.create_dir(&config_dir, &format!("✓ Created {}", config_dir.display()))?;
+    }
+    write_usage_guide(&config_dir, report)?;
 
     // Check/create MCP config
     let mcp_config_path = config_dir.join("mcp.json");
@@ -450,27 +779,26 @@ pub fn setup_cursor(workspace_root: &Path, global: bool) -> Result<()> {
             fs::read_to_string(&mcp_config_path).context("Failed to read Cursor MCP config")?;
 
         if content.contains("\"jumble\"") {
-            println!(
+            report.say(&format!(
                 "✓ Jumble already configured in {}",
                 mcp_config_path.display()
-            );
+            ));
         } else {
-            println!();
-            println!("⚠️  Jumble not found in Cursor MCP config");
-            print_cursor_config_instructions(&mcp_config_path, workspace_root);
+            report.warn("Jumble not found in Cursor MCP config");
+            say_json_config_instructions(report, &mcp_config_path, workspace_root);
         }
     } else {
-        println!();
-        println!("📝 Creating Cursor MCP config...");
-        print_cursor_config_instructions(&mcp_config_path, workspace_root);
+        report.say("");
+        report.say("📝 Creating Cursor MCP config...");
+        say_json_config_instructions(report, &mcp_config_path, workspace_root);
     }
 
-    print_common_next_steps(workspace_root, "Cursor");
+    say_common_next_steps(workspace_root, "Cursor", report);
     Ok(())
 }
 
 /// Setup Windsurf integration
-pub fn setup_windsurf(workspace_root: &Path, global: bool) -> Result<()> {
+pub fn setup_windsurf(workspace_root: &Path, global: bool, report: &mut Reporter) -> Result<()> {
     let config_dir = if global {
         dirs::home_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
@@ -479,12 +807,10 @@ pub fn setup_windsurf(workspace_root: &Path, global: bool) -> Result<()> {
         workspace_root.join(".windsurf")
     };
 
-    fs::create_dir_all(&config_dir).context("Failed to create windsurf config directory")?;
-
-    let guide_path = config_dir.join("jumble-usage.md");
-    fs::write(&guide_path, USAGE_GUIDE).context("Failed to write usage guide")?;
-
-    println!("✓ Created {}", guide_path.display());
+    if !config_dir.exists() {
+        report.create_dir(&config_dir, &format!("✓ Created {}", config_dir.display()))?;
+    }
+    write_usage_guide(&config_dir, report)?;
 
     // Check MCP config
     let mcp_config_path = dirs::home_dir().map(|h| h.join(".codeium/windsurf/mcp_config.json"));
@@ -495,26 +821,28 @@ pub fn setup_windsurf(workspace_root: &Path, global: bool) -> Result<()> {
                 fs::read_to_string(&config_path).context("Failed to read Windsurf config")?;
 
             if content.contains("\"jumble\"") {
-                println!("✓ Jumble MCP server detected in Windsurf config");
+                report.say("✓ Jumble MCP server detected in Windsurf config");
             } else {
-                println!();
-                println!("⚠️  Jumble not found in Windsurf config");
-                print_windsurf_config_instructions(&config_path, workspace_root);
+                report.warn("Jumble not found in Windsurf config");
+                say_json_config_instructions(report, &config_path, workspace_root);
+                report.say("");
+                report.say("   Then restart Windsurf.");
             }
         } else {
-            println!();
-            println!("⚠️  Windsurf config not found");
-            println!("   Expected: {}", config_path.display());
-            print_windsurf_config_instructions(&config_path, workspace_root);
+            report.warn("Windsurf config not found");
+            report.say(&format!("   Expected: {}", config_path.display()));
+            say_json_config_instructions(report, &config_path, workspace_root);
+            report.say("");
+            report.say("   Then restart Windsurf.");
         }
     }
 
-    print_common_next_steps(workspace_root, "Windsurf");
+    say_common_next_steps(workspace_root, "Windsurf", report);
     Ok(())
 }
 
 /// Setup Codex integration
-pub fn setup_codex(workspace_root: &Path, global: bool) -> Result<()> {
+pub fn setup_codex(workspace_root: &Path, global: bool, report: &mut Reporter) -> Result<()> {
     let config_dir = if global {
         dirs::home_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
@@ -523,12 +851,10 @@ pub fn setup_codex(workspace_root: &Path, global: bool) -> Result<()> {
         workspace_root.join(".codex")
     };
 
-    fs::create_dir_all(&config_dir).context("Failed to create .codex directory")?;
-
-    let guide_path = config_dir.join("jumble-usage.md");
-    fs::write(&guide_path, USAGE_GUIDE).context("Failed to write usage guide")?;
-
-    println!("✓ Created {}", guide_path.display());
+    if !config_dir.exists() {
+        report.create_dir(&config_dir, &format!("✓ Created {}", config_dir.display()))?;
+    }
+    write_usage_guide(&config_dir, report)?;
 
     // Check MCP config
     let config_path = dirs::home_dir().map(|h| h.join(".codex/config.toml"));
@@ -539,102 +865,214 @@ pub fn setup_codex(workspace_root: &Path, global: bool) -> Result<()> {
                 fs::read_to_string(&config_file).context("Failed to read Codex config")?;
 
             if content.contains("[mcp_servers.jumble]") {
-                println!("✓ Jumble MCP server detected in Codex config");
+                report.say("✓ Jumble MCP server detected in Codex config");
             } else {
-                println!();
-                println!("⚠️  Jumble not found in Codex config");
-                print_codex_config_instructions(&config_file, workspace_root);
+                report.warn("Jumble not found in Codex config");
+                say_codex_config_instructions(report, &config_file, workspace_root);
             }
         } else {
-            println!();
-            println!("⚠️  Codex config not found");
-            println!("   Expected: {}", config_file.display());
-            print_codex_config_instructions(&config_file, workspace_root);
+            report.warn("Codex config not found");
+            report.say(&format!("   Expected: {}", config_file.display()));
+            say_codex_config_instructions(report, &config_file, workspace_root);
         }
     }
 
-    print_common_next_steps(workspace_root, "Codex");
+    say_common_next_steps(workspace_root, "Codex", report);
     Ok(())
 }
 
-fn print_cursor_config_instructions(config_path: &Path, workspace_root: &Path) {
-    println!("   Add to {}:", config_path.display());
-    println!();
-    println!("   {{");
-    println!("     \"mcpServers\": {{");
-    println!("       \"jumble\": {{");
-    let jumble_path = which::which("jumble")
-        .map(|p| p.display().to_string())
-        .unwrap_or_else(|_| "/path/to/jumble".to_string());
-    println!("         \"command\": \"{}\",", jumble_path);
-    println!(
-        "         \"args\": [\"--root\", \"{}\"]",
-        workspace_root.display()
-    );
-    println!("       }}");
-    println!("     }}");
-    println!("   }}");
-}
-
-fn print_windsurf_config_instructions(config_path: &Path, workspace_root: &Path) {
-    println!("   Add to {}:", config_path.display());
-    println!();
-    println!("   {{");
-    println!("     \"mcpServers\": {{");
-    println!("       \"jumble\": {{");
-    let jumble_path = which::which("jumble")
-        .map(|p| p.display().to_string())
-        .unwrap_or_else(|_| "/path/to/jumble".to_string());
-    println!("         \"command\": \"{}\",", jumble_path);
-    println!(
-        "         \"args\": [\"--root\", \"{}\"]",
-        workspace_root.display()
-    );
-    println!("       }}");
-    println!("     }}");
-    println!("   }}");
-    println!();
-    println!("   Then restart Windsurf.");
-}
-
-fn print_codex_config_instructions(config_path: &Path, workspace_root: &Path) {
-    println!("   Add to {}:", config_path.display());
-    println!();
-    println!("   [mcp_servers.jumble]");
-    let jumble_path = which::which("jumble")
+/// Write `jumble-usage.md` into an agent's config directory.
+fn write_usage_guide(config_dir: &Path, report: &mut Reporter) -> Result<()> {
+    let guide_path = config_dir.join("jumble-usage.md");
+    let guide = usage_guide();
+    let (action, message) = if !guide_path.exists() {
+        (Action::Created, "✓ Created")
+    } else if text::read_text(&guide_path).is_ok_and(|content| content == guide) {
+        (Action::Skipped, "✓ Up to date:")
+    } else {
+        (Action::Updated, "✓ Updated")
+    };
+    let message = format!("{} {}", message, guide_path.display());
+    if action == Action::Skipped {
+        report.file(&guide_path, action, &message);
+        return Ok(());
+    }
+    report.write(&guide_path, &guide, action, &message)
+}
+
+/// Path of the installed jumble binary, for config snippets.
+fn jumble_command() -> String {
+    which::which("jumble")
         .map(|p| p.display().to_string())
-        .unwrap_or_else(|_| "/path/to/jumble".to_string());
-    println!("   command = \"{}\"", jumble_path);
-    println!("   args = [\"--root\", \"{}\"]", workspace_root.display());
-    println!();
-    println!("   Or use the CLI:");
-    println!(
-        "   codex mcp add jumble -- {} --root {}",
+        .unwrap_or_else(|_| "/path/to/jumble".to_string())
+}
+
+/// Arguments the MCP host starts `jumble` with: the root, and the config
+/// directory when it isn't `.jumble`.
+fn server_args(workspace_root: &Path) -> Vec<String> {
+    let mut args = vec!["--root".to_string(), workspace_root.display().to_string()];
+    if paths::config_dir() != Path::new(paths::JUMBLE_DIR) {
+        args.push("--config-dir".to_string());
+        args.push(paths::config_dir().display().to_string());
+    }
+    args
+}
+
+/// `args` as a JSON or TOML array of strings.
+fn quoted_args(args: &[String]) -> String {
+    let quoted: Vec<String> = args.iter().map(|arg| format!("\"{}\"", arg)).collect();
+    format!("[{}]", quoted.join(", "))
+}
+
+/// The `mcpServers` snippet used by Claude Desktop, Cursor and Windsurf.
+fn say_json_config_instructions(report: &Reporter, config_path: &Path, workspace_root: &Path) {
+    report.say(&format!("   Add to {}:", config_path.display()));
+    report.say("");
+    report.say("   {");
+    report.say("     \"mcpServers\": {");
+    report.say("       \"jumble\": {");
+    report.say(&format!("         \"command\": \"{}\",", jumble_command()));
+    report.say(&format!(
+        "         \"args\": {}",
+        quoted_args(&server_args(workspace_root))
+    ));
+    report.say("       }");
+    report.say("     }");
+    report.say("   }");
+}
+
+fn say_codex_config_instructions(report: &Reporter, config_path: &Path, workspace_root: &Path) {
+    let jumble_path = jumble_command();
+    report.say(&format!("   Add to {}:", config_path.display()));
+    report.say("");
+    report.say("   [mcp_servers.jumble]");
+    report.say(&format!("   command = \"{}\"", jumble_path));
+    report.say(&format!(
+        "   args = {}",
+        quoted_args(&server_args(workspace_root))
+    ));
+    report.say("");
+    report.say("   Or use the CLI:");
+    report.say(&format!(
+        "   codex mcp add jumble -- {} {}",
         jumble_path,
-        workspace_root.display()
-    );
-    println!();
-    println!("   Then restart Codex.");
+        server_args(workspace_root).join(" ")
+    ));
+    report.say("");
+    report.say("   Then restart Codex.");
 }
 
-fn print_common_next_steps(workspace_root: &Path, agent_name: &str) {
-    let jumble_dir = workspace_root.join(".jumble");
-    if !jumble_dir.exists() {
-        println!();
-        println!("⚠️  No .jumble directory found");
-        println!("   Create .jumble/project.toml to provide project context");
-        println!("   See: https://github.com/velvet-tiger/jumble/blob/main/AUTHORING.md");
+/// Project-local directories that `jumble setup <agent>` writes a usage guide to.
+const USAGE_GUIDE_DIRS: &[&str] = &[".claude", ".cursor", ".windsurf", ".codex"];
+
+/// Re-render every generated file that already exists under `workspace_root`
+/// and return each one with what happened to it. Files that were never
+/// generated are left alone.
+pub fn sync_artifacts(workspace_root: &Path) -> Result<Vec<(PathBuf, WriteOutcome)>> {
+    let mut results = Vec::new();
+
+    let warp_md = workspace_root.join("WARP.md");
+    if warp_md.is_file() {
+        let content = text::read_text(&warp_md).context("Failed to read WARP.md")?;
+        if content.contains(JUMBLE_SECTION_MARKER) {
+            let updated = replace_jumble_section(&content)?;
+            let outcome = write_if_changed(&warp_md, &content, &updated)?;
+            results.push((warp_md, outcome));
+        }
     }
 
-    println!();
-    println!("Next steps:");
-    println!("1. Ensure .jumble/project.toml exists");
-    println!(
+    for dir in USAGE_GUIDE_DIRS {
+        let guide_path = workspace_root.join(dir).join("jumble-usage.md");
+        if guide_path.is_file() {
+            let content = text::read_text(&guide_path)
+                .with_context(|| format!("Failed to read {}", guide_path.display()))?;
+            let outcome = write_if_changed(&guide_path, &content, &usage_guide())?;
+            results.push((guide_path, outcome));
+        }
+    }
+
+    let agents_md = workspace_root.join("AGENTS.md");
+    if agents_md.is_file() {
+        let content = text::read_text(&agents_md).context("Failed to read AGENTS.md")?;
+        if generate::has_marked_section(&content, generate::AGENTS_MD) {
+            let body = generate::render_agents_md(workspace_root)?;
+            let updated = generate::update_marked_section(&content, generate::AGENTS_MD, &body);
+            let outcome = write_if_changed(&agents_md, &content, &updated)?;
+            results.push((agents_md, outcome));
+        }
+    }
+
+    Ok(results)
+}
+
+fn write_if_changed(path: &Path, current: &str, updated: &str) -> Result<WriteOutcome> {
+    if current == updated {
+        return Ok(WriteOutcome::Unchanged);
+    }
+    fs::write(path, updated).with_context(|| format!("Failed to update {}", path.display()))?;
+    Ok(WriteOutcome::Updated)
+}
+
+/// `jumble sync`: bring generated agent guide files in line with the current
+/// configuration and report which ones changed.
+pub fn sync(workspace_root: &Path, report: &mut Reporter) -> Result<()> {
+    let results = sync_artifacts(workspace_root)?;
+    if results.is_empty() {
+        report.say("No generated files found. Run `jumble setup <agent>` or `jumble generate agents-md` first.");
+        return Ok(());
+    }
+
+    for (path, outcome) in &results {
+        let shown = path.strip_prefix(workspace_root).unwrap_or(path);
+        match outcome {
+            WriteOutcome::Unchanged => report.file(
+                path,
+                Action::Skipped,
+                &format!("  {} (up to date)", shown.display()),
+            ),
+            _ => report.file(
+                path,
+                Action::Updated,
+                &format!("✓ Updated {}", shown.display()),
+            ),
+        }
+    }
+    let changed = results
+        .iter()
+        .filter(|(_, outcome)| *outcome != WriteOutcome::Unchanged)
+        .count();
+    report.say("");
+    report.say(&format!("{} of {} file(s) changed", changed, results.len()));
+    Ok(())
+}
+
+fn warn_if_no_jumble_dir(workspace_root: &Path, report: &mut Reporter) {
+    let config_dir = paths::config_dir().display();
+    if !workspace_root.join(paths::config_dir()).exists() {
+        report.warn(&format!("No {} directory found", config_dir));
+        report.say(&format!(
+            "   Create {}/project.toml to provide project context",
+            config_dir
+        ));
+        report.say("   See: https://github.com/velvet-tiger/jumble/blob/main/AUTHORING.md");
+    }
+}
+
+fn say_common_next_steps(workspace_root: &Path, agent_name: &str, report: &mut Reporter) {
+    warn_if_no_jumble_dir(workspace_root, report);
+
+    report.say("");
+    report.say("Next steps:");
+    report.say(&format!(
+        "1. Ensure {}/project.toml exists",
+        paths::config_dir().display()
+    ));
+    report.say(&format!(
         "2. Verify jumble MCP server is configured in {}",
         agent_name
-    );
-    println!("3. Restart {} to apply changes", agent_name);
-    println!("4. Read the usage guide for best practices");
+    ));
+    report.say(&format!("3. Restart {} to apply changes", agent_name));
+    report.say("4. Read the usage guide for best practices");
 }
 
 #[cfg(test)]
@@ -643,12 +1081,16 @@ mod tests {
     use std::fs;
     use tempfile::TempDir;
 
+    fn quiet() -> Reporter {
+        Reporter::new(OutputMode::Quiet, "test")
+    }
+
     #[test]
     fn test_setup_init_creates_all_directories_and_files() {
         let temp = TempDir::new().unwrap();
         let workspace = temp.path();
 
-        setup_init(workspace).unwrap();
+        setup_init(workspace, &mut quiet()).unwrap();
 
         // Check all directories exist
         assert!(workspace.join(".jumble").is_dir());
@@ -665,9 +1107,14 @@ mod tests {
         let project_content = fs::read_to_string(workspace.join(".jumble/project.toml")).unwrap();
         assert!(project_content.contains("[project]"));
         assert!(project_content.contains("name = \"my-project\""));
+        assert!(project_content.starts_with(&format!(
+            "config_version = {}\n",
+            migrate::CURRENT_CONFIG_VERSION
+        )));
 
         // Check constitution.md is blank
-        let constitution_content = fs::read_to_string(workspace.join(".ai/constitution.md")).unwrap();
+        let constitution_content =
+            fs::read_to_string(workspace.join(".ai/constitution.md")).unwrap();
         assert_eq!(constitution_content, "");
 
         // Check AGENTS.md content
@@ -687,16 +1134,111 @@ mod tests {
         let workspace = temp.path();
 
         // Run twice
-        setup_init(workspace).unwrap();
-        let first_project_content = fs::read_to_string(workspace.join(".jumble/project.toml")).unwrap();
+        setup_init(workspace, &mut quiet()).unwrap();
+        let first_project_content =
+            fs::read_to_string(workspace.join(".jumble/project.toml")).unwrap();
 
-        setup_init(workspace).unwrap();
-        let second_project_content = fs::read_to_string(workspace.join(".jumble/project.toml")).unwrap();
+        setup_init(workspace, &mut quiet()).unwrap();
+        let second_project_content =
+            fs::read_to_string(workspace.join(".jumble/project.toml")).unwrap();
 
         // Content should be identical
         assert_eq!(first_project_content, second_project_content);
     }
 
+    #[test]
+    fn test_reporter_records_created_and_skipped_files() {
+        let temp = TempDir::new().unwrap();
+        let workspace = temp.path();
+        fs::write(workspace.join(".gitignore"), "target/\n").unwrap();
+
+        let mut report = quiet();
+        setup_init(workspace, &mut report).unwrap();
+        let action = |report: &Reporter, name: &str| {
+            report
+                .files()
+                .iter()
+                .find(|f| f.path.ends_with(name))
+                .map(|f| f.action)
+        };
+        assert_eq!(report.files().len(), 7);
+        assert_eq!(action(&report, "project.toml"), Some(Action::Created));
+        assert_eq!(action(&report, ".gitignore"), Some(Action::Skipped));
+
+        let mut again = quiet();
+        setup_init(workspace, &mut again).unwrap();
+        assert!(again.files().iter().all(|f| f.action == Action::Skipped));
+
+        let json = serde_json::to_value(&report.files()[0]).unwrap();
+        assert_eq!(json["action"], "created");
+        assert!(json["path"].as_str().unwrap().ends_with(".jumble"));
+    }
+
+    #[test]
+    fn test_dry_run_reports_diffs_without_writing() {
+        let temp = TempDir::new().unwrap();
+        let workspace = temp.path();
+
+        let mut report = quiet().with_dry_run(true);
+        setup_init(workspace, &mut report).unwrap();
+        assert!(!workspace.join(".jumble").exists());
+        assert!(!workspace.join("AGENTS.md").exists());
+        let agents = report
+            .files()
+            .iter()
+            .find(|f| f.path.ends_with("AGENTS.md"))
+            .unwrap();
+        assert_eq!(agents.action, Action::Created);
+        assert!(agents.diff.as_deref().unwrap().contains("+# AGENTS.md"));
+
+        let warp_md = workspace.join("WARP.md");
+        fs::write(&warp_md, "# Notes\n").unwrap();
+        let mut report = quiet().with_dry_run(true);
+        setup_warp(workspace, false, &mut report).unwrap();
+        assert_eq!(fs::read_to_string(&warp_md).unwrap(), "# Notes\n");
+        let diff = report.files()[0].diff.as_deref().unwrap();
+        assert!(diff.contains(" # Notes"));
+        assert!(diff.contains("+## Using Jumble"));
+    }
+
+    #[test]
+    fn test_init_workspace_scaffolds_manifest_subdirectories() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        for dir in ["api", "web", "notes", ".cache"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("api/Cargo.toml"), "[package]\n").unwrap();
+        fs::write(root.join("web/package.json"), "{}").unwrap();
+        fs::write(root.join("web/tsconfig.json"), "{}").unwrap();
+        fs::write(root.join(".cache/go.mod"), "module cache\n").unwrap();
+
+        setup_init_workspace(root, false, &mut quiet()).unwrap();
+        let workspace: crate::config::WorkspaceConfig =
+            toml::from_str(&fs::read_to_string(root.join(".jumble/workspace.toml")).unwrap())
+                .unwrap();
+        assert!(workspace.workspace.name.is_some());
+        assert!(!root.join("api/.jumble").exists());
+
+        let mut report = quiet();
+        setup_init_workspace(root, true, &mut report).unwrap();
+        let (_, projects) = crate::server::Server::discover_static(root).unwrap();
+        let mut names: Vec<&String> = projects.keys().collect();
+        names.sort();
+        assert_eq!(names, ["api", "web"]);
+        assert_eq!(
+            projects["web"].1.project.language.as_deref(),
+            Some("typescript")
+        );
+        assert!(!root.join("notes/.jumble").exists());
+        assert!(!root.join(".cache/.jumble").exists());
+        assert_eq!(
+            report.files()[1].action,
+            Action::Skipped,
+            "workspace.toml is left alone on a second run"
+        );
+    }
+
     #[test]
     fn test_setup_init_preserves_existing_gitignore() {
         let temp = TempDir::new().unwrap();
@@ -706,7 +1248,7 @@ mod tests {
         let gitignore_path = workspace.join(".gitignore");
         fs::write(&gitignore_path, "*.log\n*.tmp\n").unwrap();
 
-        setup_init(workspace).unwrap();
+        setup_init(workspace, &mut quiet()).unwrap();
 
         let gitignore_content = fs::read_to_string(&gitignore_path).unwrap();
         // Check original entries are preserved and unchanged
@@ -718,7 +1260,7 @@ mod tests {
         let temp = TempDir::new().unwrap();
         let workspace = temp.path();
 
-        setup_warp(workspace, false).unwrap();
+        setup_warp(workspace, false, &mut quiet()).unwrap();
 
         let warp_md = workspace.join("WARP.md");
         assert!(warp_md.exists());
@@ -741,7 +1283,7 @@ mod tests {
         )
         .unwrap();
 
-        setup_warp(workspace, false).unwrap();
+        setup_warp(workspace, false, &mut quiet()).unwrap();
 
         let content = fs::read_to_string(warp_md).unwrap();
         assert!(content.contains("## Existing Section"));
@@ -755,10 +1297,10 @@ mod tests {
         let warp_md = workspace.join("WARP.md");
 
         // Create WARP.md with jumble section
-        fs::write(&warp_md, format!("# WARP.md\n\n{}", JUMBLE_SECTION)).unwrap();
+        fs::write(&warp_md, format!("# WARP.md\n\n{}", jumble_section())).unwrap();
 
         // Should skip without --force
-        setup_warp(workspace, false).unwrap();
+        setup_warp(workspace, false, &mut quiet()).unwrap();
 
         let content = fs::read_to_string(warp_md).unwrap();
         // Should only have one occurrence
@@ -785,7 +1327,7 @@ Keep this.
         fs::write(&warp_md, old_content).unwrap();
 
         // Force update
-        setup_warp(workspace, true).unwrap();
+        setup_warp(workspace, true, &mut quiet()).unwrap();
 
         let content = fs::read_to_string(warp_md).unwrap();
         assert!(content.contains("get_workspace_overview()"));
@@ -813,5 +1355,39 @@ Keep this section.
         assert!(result.contains("get_workspace_overview()"));
         assert!(!result.contains("Old content here"));
         assert!(result.contains("## Another Section"));
+        assert_eq!(replace_jumble_section(&result).unwrap(), result);
+    }
+
+    #[test]
+    fn test_sync_updates_only_generated_files() {
+        let temp = TempDir::new().unwrap();
+        let workspace = temp.path();
+
+        setup_warp(workspace, false, &mut quiet()).unwrap();
+        fs::create_dir_all(workspace.join(".cursor")).unwrap();
+        fs::write(workspace.join(".cursor/jumble-usage.md"), "stale guide").unwrap();
+        fs::write(workspace.join("AGENTS.md"), "# Hand-written, no markers\n").unwrap();
+
+        let results = sync_artifacts(workspace).unwrap();
+        let outcome = |name: &str| {
+            results
+                .iter()
+                .find(|(path, _)| path.ends_with(name))
+                .map(|(_, outcome)| outcome)
+        };
+        assert_eq!(outcome("WARP.md"), Some(&WriteOutcome::Unchanged));
+        assert_eq!(outcome("jumble-usage.md"), Some(&WriteOutcome::Updated));
+        assert_eq!(outcome("AGENTS.md"), None);
+        assert_eq!(
+            fs::read_to_string(workspace.join(".cursor/jumble-usage.md")).unwrap(),
+            usage_guide()
+        );
+        assert!(usage_guide().contains("create `.jumble/project.toml`"));
+        assert!(!jumble_section().contains("{config_dir}"));
+
+        let again = sync_artifacts(workspace).unwrap();
+        assert!(again
+            .iter()
+            .all(|(_, outcome)| *outcome == WriteOutcome::Unchanged));
     }
 }
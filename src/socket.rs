@@ -0,0 +1,180 @@
+//! `jumble server --socket <path>`: one long-lived server per workspace that
+//! several local agents share over a unix domain socket.
+//!
+//! Each connection speaks the stdio protocol, framed as `--framing` says,
+//! and gets its own session from [`Server::new_session`]: clients don't see
+//! each other's subscriptions or queued messages, but the workspace is
+//! discovered once and a reload by any of them (or by the file watcher) is
+//! seen by all. They share one request rate limit and reload throttle.
+//! Connections are served on their own threads.
+//!
+//! MCP hosts that can only spawn a process use `jumble connect <path>`, which
+//! relays stdin and stdout to the socket.
+
+use anyhow::{bail, Context, Result};
+use std::io::{self, BufReader, Write};
+use std::os::unix::fs::FileTypeExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+
+use crate::framing::Framing;
+use crate::server::Server;
+use crate::stream;
+use crate::watch;
+
+/// Bind `path`, replacing a socket file left behind by a server that is no
+/// longer running. Anything else at `path` is left alone.
+fn bind(path: &Path) -> Result<UnixListener> {
+    if let Ok(metadata) = path.symlink_metadata() {
+        if !metadata.file_type().is_socket() {
+            bail!("{} exists and is not a socket", path.display());
+        }
+        if UnixStream::connect(path).is_ok() {
+            bail!("Another server is already listening on {}", path.display());
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
+    UnixListener::bind(path).with_context(|| format!("Failed to listen on {}", path.display()))
+}
+
+/// A connection's write half, shared by its session loop and its notifier
+/// so that messages written from both never interleave.
+#[derive(Clone)]
+struct SharedWriter(Arc<Mutex<UnixStream>>);
+
+impl SharedWriter {
+    fn lock(&self) -> MutexGuard<'_, UnixStream> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Write for SharedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.lock().write_all(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush()
+    }
+}
+
+/// Serve sessions of `server` on the socket at `path` until the process
+/// exits, reloading on file changes when `watch` is set.
+pub fn serve(path: &Path, server: Server, framing: Framing, watch: bool) -> Result<()> {
+    let listener = bind(path)?;
+    eprintln!("Serving MCP on unix socket {}", path.display());
+    let server = Arc::new(server);
+    let sessions: Arc<Mutex<Vec<Weak<Server>>>> = Arc::default();
+    if watch {
+        // Each session's client hears about the changes it subscribed to.
+        let sessions = Arc::clone(&sessions);
+        watch::spawn_with(Arc::clone(&server), move |server: &Server| {
+            let live: Vec<Arc<Server>> = {
+                let mut sessions = sessions.lock().unwrap_or_else(PoisonError::into_inner);
+                sessions.retain(|session| session.strong_count() > 0);
+                sessions.iter().filter_map(Weak::upgrade).collect()
+            };
+            server.reload_sessions(&live)
+        });
+    }
+    for stream in listener.incoming().flatten() {
+        let session = Arc::new(server.new_session());
+        sessions
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::downgrade(&session));
+        std::thread::spawn(move || {
+            let reader = match stream.try_clone() {
+                Ok(read_half) => BufReader::new(read_half),
+                Err(_) => return,
+            };
+            let mut writer = SharedWriter(Arc::new(Mutex::new(stream)));
+            let notify = writer.clone();
+            session.set_notifier(Box::new(move |message| {
+                let _ = framing.write(&mut notify.clone(), message);
+            }));
+            let _ = stream::serve_stream(reader, &mut writer, &session, framing, None);
+        });
+    }
+    Ok(())
+}
+
+/// Relay stdin and stdout to the server listening on `path`, for hosts that
+/// launch MCP servers as processes.
+pub fn connect(path: &Path) -> Result<()> {
+    let stream = UnixStream::connect(path)
+        .with_context(|| format!("Failed to connect to {}", path.display()))?;
+    let mut to_server = stream.try_clone()?;
+    std::thread::spawn(move || {
+        let _ = io::copy(&mut io::stdin().lock(), &mut to_server);
+        // Let the server see end of input, as it would from a closed stdin.
+        let _ = to_server.shutdown(std::net::Shutdown::Write);
+    });
+    io::copy(&mut BufReader::new(stream), &mut io::stdout().lock())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::ServerOptions;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sessions_share_the_workspace() {
+        let temp = TempDir::new().unwrap();
+        let jumble = temp.path().join("app/.jumble");
+        fs::create_dir_all(&jumble).unwrap();
+        fs::write(
+            jumble.join("project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"App\"\n",
+        )
+        .unwrap();
+        let path = temp.path().join("jumble.sock");
+        let server =
+            Server::with_options(temp.path().to_path_buf(), ServerOptions::default()).unwrap();
+        let listener = bind(&path).unwrap();
+        let first = Arc::new(server.new_session());
+        let second = Arc::new(server.new_session());
+        assert!(bind(&path).is_err());
+
+        let call = |session: &Arc<Server>, line: &str| -> String {
+            let mut output = Vec::new();
+            stream::serve_stream(
+                io::Cursor::new(line.to_string()),
+                &mut output,
+                session,
+                Framing::Lines,
+                None,
+            )
+            .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let list = "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"tools/call\",\"params\":{\"name\":\"list_projects\",\"arguments\":{}}}\n";
+        assert!(call(&first, list).contains("**app**"));
+
+        fs::write(
+            jumble.join("project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"Renamed\"\n",
+        )
+        .unwrap();
+        let reload = "{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"tools/call\",\"params\":{\"name\":\"reload_workspace\",\"arguments\":{}}}\n";
+        call(&first, reload);
+        assert!(call(&second, list).contains("Renamed"));
+
+        // The socket file of a server that is gone is replaced, but other
+        // files are not.
+        drop(listener);
+        assert!(bind(&path).is_ok());
+        let project = jumble.join("project.toml");
+        assert!(bind(&project).is_err());
+        assert!(project.is_file());
+    }
+}
@@ -0,0 +1,134 @@
+//! Results of project commands as reported by agents, kept in
+//! `.jumble/state.json` so a later run can be compared with an earlier one.
+//!
+//! Nothing is recorded unless an agent calls `record_command_result`; the
+//! file is created on the first record.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use crate::paths;
+
+/// File name of the state file inside `.jumble`.
+pub const STATE_FILE: &str = "state.json";
+
+/// Runs kept per command type; older ones are dropped.
+pub const MAX_RUNS: usize = 10;
+
+/// Serializes read-modify-write cycles, since tool calls may run in parallel.
+static UPDATE: Mutex<()> = Mutex::new(());
+
+/// One reported run of a project command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommandRun {
+    pub exit_code: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    /// ISO 8601 time the run was recorded.
+    pub timestamp: String,
+}
+
+impl CommandRun {
+    pub fn passed(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// Everything stored in `state.json` for one project.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ProjectState {
+    /// Runs per command type, oldest first.
+    #[serde(default)]
+    pub runs: BTreeMap<String, Vec<CommandRun>>,
+}
+
+impl ProjectState {
+    /// The most recent run of `command_type` and the one before it.
+    pub fn last_two(&self, command_type: &str) -> (Option<&CommandRun>, Option<&CommandRun>) {
+        let runs = self
+            .runs
+            .get(command_type)
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        let mut latest = runs.iter().rev();
+        (latest.next(), latest.next())
+    }
+}
+
+pub fn state_path(project_root: &Path) -> PathBuf {
+    project_root.join(paths::config_dir()).join(STATE_FILE)
+}
+
+/// Load the project's state; a missing file is an empty state.
+pub fn load(project_root: &Path) -> Result<ProjectState, String> {
+    let path = state_path(project_root);
+    if !path.exists() {
+        return Ok(ProjectState::default());
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Append `run` for `command_type` and save. Returns the run it follows, if
+/// any.
+pub fn record(
+    project_root: &Path,
+    command_type: &str,
+    run: CommandRun,
+) -> Result<Option<CommandRun>, String> {
+    let _guard = UPDATE.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut state = load(project_root)?;
+    let runs = state.runs.entry(command_type.to_string()).or_default();
+    let previous = runs.last().cloned();
+    runs.push(run);
+    if runs.len() > MAX_RUNS {
+        runs.drain(..runs.len() - MAX_RUNS);
+    }
+
+    let path = state_path(project_root);
+    let content = serde_json::to_string_pretty(&state).map_err(|e| e.to_string())?;
+    // Write then rename, so a crash never leaves a half-written file.
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, content + "\n")
+        .and_then(|_| fs::rename(&temp, &path))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn run(exit_code: i64) -> CommandRun {
+        CommandRun {
+            exit_code,
+            summary: None,
+            timestamp: format!("2026-01-01T00:00:0{}Z", exit_code),
+        }
+    }
+
+    #[test]
+    fn test_record_keeps_recent_runs_in_order() {
+        let temp = TempDir::new().unwrap();
+        fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        assert!(load(temp.path()).unwrap().runs.is_empty());
+
+        assert_eq!(record(temp.path(), "test", run(1)).unwrap(), None);
+        assert_eq!(record(temp.path(), "test", run(0)).unwrap(), Some(run(1)));
+        for _ in 0..MAX_RUNS {
+            record(temp.path(), "lint", run(0)).unwrap();
+        }
+        record(temp.path(), "lint", run(2)).unwrap();
+
+        let state = load(temp.path()).unwrap();
+        assert_eq!(state.last_two("test"), (Some(&run(0)), Some(&run(1))));
+        assert_eq!(state.runs["lint"].len(), MAX_RUNS);
+        assert_eq!(state.last_two("lint").0, Some(&run(2)));
+        assert_eq!(state.last_two("build"), (None, None));
+    }
+}
@@ -0,0 +1,406 @@
+//! Lines of code per language and per directory, for `get_code_stats`.
+//!
+//! Languages are recognized by file extension; files in other languages are
+//! not counted. A line is code when anything other than whitespace or a
+//! comment is on it. Comment markers inside double-quoted strings don't
+//! count, but other string forms (raw strings, docstrings) are read as
+//! code, so the counts are close rather than exact. A crate like `tokei`
+//! would be exact, but it brings a large dependency tree for a tool whose
+//! only use of the numbers is a rough size summary.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Files larger than this are skipped as generated or vendored.
+const MAX_FILE_BYTES: u64 = 2 * 1024 * 1024;
+
+/// How comments are written in a language.
+#[derive(Debug)]
+struct Syntax {
+    /// Markers that comment out the rest of the line.
+    line: &'static [&'static str],
+    /// `(open, close)` pairs of block comment delimiters.
+    block: &'static [(&'static str, &'static str)],
+}
+
+const C_LIKE: Syntax = Syntax {
+    line: &["//"],
+    block: &[("/*", "*/")],
+};
+const HASH: Syntax = Syntax {
+    line: &["#"],
+    block: &[],
+};
+const NONE: Syntax = Syntax {
+    line: &[],
+    block: &[],
+};
+
+/// `(extension, language, comment syntax)`.
+const LANGUAGES: &[(&str, &str, &Syntax)] = &[
+    ("rs", "Rust", &C_LIKE),
+    ("go", "Go", &C_LIKE),
+    ("py", "Python", &HASH),
+    ("js", "JavaScript", &C_LIKE),
+    ("jsx", "JavaScript", &C_LIKE),
+    ("mjs", "JavaScript", &C_LIKE),
+    ("cjs", "JavaScript", &C_LIKE),
+    ("ts", "TypeScript", &C_LIKE),
+    ("tsx", "TypeScript", &C_LIKE),
+    ("java", "Java", &C_LIKE),
+    ("kt", "Kotlin", &C_LIKE),
+    ("kts", "Kotlin", &C_LIKE),
+    ("swift", "Swift", &C_LIKE),
+    ("c", "C", &C_LIKE),
+    ("h", "C", &C_LIKE),
+    ("cc", "C++", &C_LIKE),
+    ("cpp", "C++", &C_LIKE),
+    ("hpp", "C++", &C_LIKE),
+    ("cs", "C#", &C_LIKE),
+    (
+        "php",
+        "PHP",
+        &Syntax {
+            line: &["//", "#"],
+            block: &[("/*", "*/")],
+        },
+    ),
+    (
+        "rb",
+        "Ruby",
+        &Syntax {
+            line: &["#"],
+            block: &[("=begin", "=end")],
+        },
+    ),
+    ("scala", "Scala", &C_LIKE),
+    ("ex", "Elixir", &HASH),
+    ("exs", "Elixir", &HASH),
+    (
+        "erl",
+        "Erlang",
+        &Syntax {
+            line: &["%"],
+            block: &[],
+        },
+    ),
+    (
+        "hs",
+        "Haskell",
+        &Syntax {
+            line: &["--"],
+            block: &[("{-", "-}")],
+        },
+    ),
+    (
+        "lua",
+        "Lua",
+        &Syntax {
+            line: &["--"],
+            block: &[("--[[", "]]")],
+        },
+    ),
+    (
+        "sql",
+        "SQL",
+        &Syntax {
+            line: &["--"],
+            block: &[("/*", "*/")],
+        },
+    ),
+    ("sh", "Shell", &HASH),
+    ("bash", "Shell", &HASH),
+    (
+        "ps1",
+        "PowerShell",
+        &Syntax {
+            line: &["#"],
+            block: &[("<#", "#>")],
+        },
+    ),
+    ("vue", "Vue", &C_LIKE),
+    ("svelte", "Svelte", &C_LIKE),
+    (
+        "css",
+        "CSS",
+        &Syntax {
+            line: &[],
+            block: &[("/*", "*/")],
+        },
+    ),
+    ("scss", "SCSS", &C_LIKE),
+    (
+        "html",
+        "HTML",
+        &Syntax {
+            line: &[],
+            block: &[("<!--", "-->")],
+        },
+    ),
+    ("proto", "Protobuf", &C_LIKE),
+    (
+        "tf",
+        "Terraform",
+        &Syntax {
+            line: &["#", "//"],
+            block: &[("/*", "*/")],
+        },
+    ),
+    ("md", "Markdown", &NONE),
+    ("toml", "TOML", &HASH),
+    ("yaml", "YAML", &HASH),
+    ("yml", "YAML", &HASH),
+];
+
+/// Totals for one language or directory.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Counts {
+    pub files: usize,
+    pub lines: usize,
+    pub code: usize,
+}
+
+impl Counts {
+    fn add(&mut self, other: Counts) {
+        self.files += other.files;
+        self.lines += other.lines;
+        self.code += other.code;
+    }
+}
+
+/// Counts for a project, each list sorted by code lines, largest first.
+#[derive(Debug, Default)]
+pub struct CodeStats {
+    pub languages: Vec<(String, Counts)>,
+    /// Directories up to two levels deep; `.` holds files at the root.
+    pub directories: Vec<(String, Counts)>,
+    pub total: Counts,
+}
+
+/// Count `files` (relative, `/`-separated) under `root`.
+pub fn collect(root: &Path, files: &[String]) -> CodeStats {
+    let mut languages: HashMap<&str, Counts> = HashMap::new();
+    let mut directories: HashMap<String, Counts> = HashMap::new();
+    let mut total = Counts::default();
+
+    for file in files {
+        let Some((language, syntax)) = language_of(file) else {
+            continue;
+        };
+        let path = root.join(file);
+        if path
+            .metadata()
+            .map_or(true, |meta| meta.len() > MAX_FILE_BYTES)
+        {
+            continue;
+        }
+        let Ok(bytes) = std::fs::read(&path) else {
+            continue;
+        };
+        let counts = count_lines(&String::from_utf8_lossy(&bytes), syntax);
+        languages.entry(language).or_default().add(counts);
+        directories
+            .entry(directory_of(file))
+            .or_default()
+            .add(counts);
+        total.add(counts);
+    }
+
+    CodeStats {
+        languages: sorted(languages.into_iter().map(|(k, v)| (k.to_string(), v))),
+        directories: sorted(directories.into_iter()),
+        total,
+    }
+}
+
+fn sorted(entries: impl Iterator<Item = (String, Counts)>) -> Vec<(String, Counts)> {
+    let mut entries: Vec<(String, Counts)> = entries.collect();
+    entries.sort_by(|a, b| b.1.code.cmp(&a.1.code).then_with(|| a.0.cmp(&b.0)));
+    entries
+}
+
+fn language_of(file: &str) -> Option<(&'static str, &'static Syntax)> {
+    let name = file.rsplit('/').next().unwrap_or(file);
+    let (_, extension) = name.rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|(ext, _, _)| *ext == extension)
+        .map(|(_, language, syntax)| (*language, *syntax))
+}
+
+/// File extensions of `language` (case-insensitive), e.g. `["ts", "tsx"]`
+/// for TypeScript.
+pub fn extensions(language: &str) -> Vec<&'static str> {
+    LANGUAGES
+        .iter()
+        .filter(|(_, name, _)| name.eq_ignore_ascii_case(language))
+        .map(|(ext, _, _)| *ext)
+        .collect()
+}
+
+/// `src/api` for `src/api/routes/users.rs`, `src` for `src/main.rs`.
+fn directory_of(file: &str) -> String {
+    let parts: Vec<&str> = file.split('/').collect();
+    match parts.len() {
+        1 => ".".to_string(),
+        2 => parts[0].to_string(),
+        _ => format!("{}/{}", parts[0], parts[1]),
+    }
+}
+
+fn count_lines(content: &str, syntax: &Syntax) -> Counts {
+    let mut counts = Counts {
+        files: 1,
+        ..Counts::default()
+    };
+    let mut open_block = None;
+    for line in content.lines() {
+        counts.lines += 1;
+        if has_code(line, syntax, &mut open_block) {
+            counts.code += 1;
+        }
+    }
+    counts
+}
+
+/// Whether `line` has anything besides whitespace and comments.
+/// `open_block` is the delimiter closing a block comment still open from
+/// an earlier line, and is updated for the next one.
+fn has_code(mut line: &str, syntax: &Syntax, open_block: &mut Option<&'static str>) -> bool {
+    let mut code = false;
+    loop {
+        if let Some(close) = *open_block {
+            let Some(end) = line.find(close) else {
+                return code;
+            };
+            line = &line[end + close.len()..];
+            *open_block = None;
+        }
+        line = line.trim_start();
+        if line.is_empty() {
+            return code;
+        }
+        // Checked before line markers: Lua's `--[[` starts with `--`.
+        if let Some((open, close)) = syntax.block.iter().find(|(open, _)| line.starts_with(open)) {
+            line = &line[open.len()..];
+            *open_block = Some(close);
+            continue;
+        }
+        if syntax.line.iter().any(|marker| line.starts_with(marker)) {
+            return code;
+        }
+        code = true;
+        line = match line.strip_prefix('"') {
+            Some(string) => after_string(string),
+            None => &line[line.chars().next().map_or(0, char::len_utf8)..],
+        };
+    }
+}
+
+/// What follows the double-quoted string `rest` starts inside; nothing if it
+/// doesn't end on this line.
+fn after_string(rest: &str) -> &str {
+    let mut chars = rest.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return &rest[i + 1..],
+            _ => {}
+        }
+    }
+    ""
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_collect_counts_code_per_language_and_directory() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src/api/routes")).unwrap();
+        fs::write(
+            root.join("src/api/routes/users.rs"),
+            "// Users\n\nfn a() {}\nfn b() {}\n",
+        )
+        .unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join("build.py"), "# build\nprint(1)\n").unwrap();
+        fs::write(root.join("logo.png"), [0u8, 1, 2]).unwrap();
+
+        let files: Vec<String> = [
+            "build.py",
+            "logo.png",
+            "src/api/routes/users.rs",
+            "src/main.rs",
+        ]
+        .iter()
+        .map(|f| f.to_string())
+        .collect();
+        let stats = collect(root, &files);
+
+        assert_eq!(
+            stats.languages[0],
+            (
+                "Rust".to_string(),
+                Counts {
+                    files: 2,
+                    lines: 5,
+                    code: 3
+                }
+            )
+        );
+        assert_eq!(stats.languages[1].0, "Python");
+        let directories: Vec<(&str, usize)> = stats
+            .directories
+            .iter()
+            .map(|(dir, counts)| (dir.as_str(), counts.code))
+            .collect();
+        assert_eq!(directories, [("src/api", 2), (".", 1), ("src", 1)]);
+        assert_eq!(stats.total.code, 4);
+    }
+
+    fn code_lines(content: &str, file: &str) -> usize {
+        count_lines(content, language_of(file).unwrap().1).code
+    }
+
+    #[test]
+    fn test_block_comments_are_not_code() {
+        let rust =
+            "/* Header\n * spanning lines\n */\nfn a() {} /* trailing */\n/* x */ fn b() {}\n";
+        assert_eq!(code_lines(rust, "lib.rs"), 2);
+        let html = "<!-- nav\n-->\n<nav></nav>\n";
+        assert_eq!(code_lines(html, "index.html"), 1);
+        let lua = "--[[ long\ncomment ]]\n-- short\nprint(1)\n";
+        assert_eq!(code_lines(lua, "main.lua"), 1);
+    }
+
+    #[test]
+    fn test_comment_markers_in_strings_are_code() {
+        let rust = "\"// not a comment\",\nlet s = \"/* nor this\";\nlet t = 1;\n";
+        assert_eq!(code_lines(rust, "lib.rs"), 3);
+        let python = "urls = [\n    \"#anchor\",\n]\n# comment\n";
+        assert_eq!(code_lines(python, "app.py"), 3);
+        let escaped = "let s = \"\\\" /*\";\nlet t = 1;\n";
+        assert_eq!(code_lines(escaped, "lib.rs"), 2);
+    }
+
+    #[test]
+    fn test_unknown_languages_are_not_counted() {
+        let temp = TempDir::new().unwrap();
+        fs::write(temp.path().join("notes.txt"), "hello\n").unwrap();
+        fs::write(temp.path().join("Makefile"), "all:\n").unwrap();
+        fs::write(temp.path().join("main.zig"), "const a = 1;\n").unwrap();
+        let files = ["notes.txt", "Makefile", "main.zig"].map(str::to_string);
+
+        let stats = collect(temp.path(), &files);
+        assert!(stats.languages.is_empty());
+        assert_eq!(stats.total, Counts::default());
+    }
+}
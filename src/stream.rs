@@ -0,0 +1,196 @@
+//! The loop serving one client over a byte stream, shared by stdio and the
+//! unix socket transport: read a framed message, handle it, then write the
+//! notifications it queued and its response.
+//!
+//! Messages are read on their own thread so that a cancellation reaches the
+//! request it names while that request is still running. Cancellations are
+//! applied as soon as they are read; everything else is handled in order.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc;
+use std::sync::Arc;
+
+use crate::framing::Framing;
+use crate::limits::LineRead;
+use crate::protocol::{self, IncomingMessage, JsonRpcError, JsonRpcResponse};
+use crate::server::Server;
+use crate::transcript::Recorder;
+
+/// What the reader thread passes on for one framed message.
+enum Input {
+    /// A message, or why it isn't one, with the bytes it was read from.
+    Message(Vec<u8>, Result<IncomingMessage, JsonRpcError>),
+    /// A cancellation, already applied.
+    Cancelled(Vec<u8>),
+    /// A message over the request size limit, discarded unread.
+    TooLong,
+}
+
+/// Whether `message` is a cancellation notification, which must not wait
+/// behind the request it cancels.
+fn is_cancel(message: &IncomingMessage) -> bool {
+    matches!(
+        message,
+        IncomingMessage::Request(request) if request.is_notification()
+            && matches!(request.method.as_str(), "notifications/cancelled" | "$/cancelRequest")
+    )
+}
+
+/// Read messages from `reader` until it ends, applying cancellations to
+/// `server` and sending everything else to `inputs`.
+fn read_inputs(
+    mut reader: impl BufRead,
+    server: &Server,
+    framing: Framing,
+    inputs: &mpsc::Sender<io::Result<Input>>,
+) {
+    let strict = server.options.strict;
+    let max_request_bytes = server.options.max_request_bytes;
+    loop {
+        let mut buf = Vec::new();
+        let input = match framing.read(&mut reader, &mut buf, max_request_bytes) {
+            Err(e) => Err(e),
+            Ok(LineRead::Eof) => return,
+            Ok(LineRead::TooLong) => Ok(Input::TooLong),
+            Ok(LineRead::Line) if buf.is_empty() => continue,
+            Ok(LineRead::Line) => {
+                let parsed = std::str::from_utf8(&buf)
+                    .map_err(|_| JsonRpcError {
+                        code: protocol::PARSE_ERROR,
+                        message: "Parse error: request is not valid UTF-8".to_string(),
+                        data: None,
+                    })
+                    .and_then(|line| protocol::parse_message(line, strict));
+                match parsed {
+                    Ok(message) if is_cancel(&message) => {
+                        server.handle_message(message);
+                        Ok(Input::Cancelled(buf))
+                    }
+                    parsed => Ok(Input::Message(buf, parsed)),
+                }
+            }
+        };
+        let failed = input.is_err();
+        if inputs.send(input).is_err() || failed {
+            return;
+        }
+    }
+}
+
+/// Serve `server` to the client on `reader` and `writer` until it closes its
+/// end, recording each exchange to `recorder` when given.
+pub fn serve_stream(
+    reader: impl BufRead + Send + 'static,
+    writer: &mut impl Write,
+    server: &Arc<Server>,
+    framing: Framing,
+    mut recorder: Option<&mut Recorder>,
+) -> Result<()> {
+    let strict = server.options.strict;
+    let max_request_bytes = server.options.max_request_bytes;
+    let (inputs, received) = mpsc::channel();
+    {
+        let server = Arc::clone(server);
+        // Left blocked on input if writing fails; it ends with the client.
+        std::thread::spawn(move || read_inputs(reader, &server, framing, &inputs));
+    }
+    for input in received {
+        let input = input.context("Failed to read a request")?;
+        // JSON-RPC requires `"id": null` when the id could not be determined.
+        let unknown_id = strict.then_some(serde_json::Value::Null);
+        let (request, response) = match input {
+            Input::TooLong => (
+                None,
+                Some(JsonRpcResponse::error(
+                    unknown_id,
+                    JsonRpcError {
+                        code: protocol::REQUEST_TOO_LARGE,
+                        message: "Request too large".to_string(),
+                        data: Some(json!({ "maxBytes": max_request_bytes })),
+                    },
+                )),
+            ),
+            Input::Cancelled(buf) => (Some(buf), None),
+            Input::Message(buf, parsed) => {
+                let response = match parsed {
+                    Ok(message) => server.handle_message(message),
+                    Err(error) => Some(JsonRpcResponse::error(unknown_id, error)),
+                };
+                (Some(buf), response)
+            }
+        };
+
+        for outgoing in server.take_outgoing() {
+            framing.write(writer, &outgoing)?;
+        }
+        if let (Some(recorder), Some(request)) = (recorder.as_deref_mut(), &request) {
+            recorder.record(request, response.as_ref())?;
+        }
+        if let Some(response) = response {
+            framing.write(writer, &response)?;
+        }
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix, feature = "exec"))]
+mod tests {
+    use super::*;
+    use crate::server::ServerOptions;
+    use std::os::unix::net::UnixStream;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_cancellation_reaches_a_running_tool() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        std::fs::write(
+            temp.path().join(".jumble/workspace.toml"),
+            "[tools.wait]\ndescription = \"Wait\"\ncommand = 'sh -c \"touch started; exec sleep 5\"'\n",
+        )
+        .unwrap();
+        let server = Arc::new(
+            Server::with_options(temp.path().to_path_buf(), ServerOptions::default()).unwrap(),
+        );
+
+        let (mut client, input) = UnixStream::pair().unwrap();
+        let started = temp.path().join("started");
+        let client = std::thread::spawn(move || {
+            let call = r#"{"jsonrpc":"2.0","id":3,"method":"tools/call","params":{"name":"wait","arguments":{}}}"#;
+            writeln!(client, "{}", call).unwrap();
+            while !started.exists() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let cancel =
+                r#"{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":3}}"#;
+            writeln!(client, "{}", cancel).unwrap();
+            writeln!(client, r#"{{"jsonrpc":"2.0","id":4,"method":"ping"}}"#).unwrap();
+        });
+
+        let begun = Instant::now();
+        let mut output = Vec::new();
+        serve_stream(
+            io::BufReader::new(input),
+            &mut output,
+            &server,
+            Framing::Lines,
+            None,
+        )
+        .unwrap();
+        client.join().unwrap();
+        assert!(begun.elapsed() < Duration::from_secs(5));
+
+        // The cancelled call gets no response; the next request is answered.
+        let output = String::from_utf8(output).unwrap();
+        let responses: Vec<serde_json::Value> = output
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 1);
+        assert_eq!(responses[0]["id"], 4);
+    }
+}
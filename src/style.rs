@@ -0,0 +1,404 @@
+//! Formatter settings from `.editorconfig` and tool configs, for
+//! `get_format_settings`.
+//!
+//! `.editorconfig` files are collected from the project directory up to the
+//! workspace root, stopping at one marked `root = true`. For rustfmt,
+//! prettier, black and ruff the nearest config wins, as the tools themselves
+//! resolve it. Only literal settings are read; configs written in
+//! JavaScript are listed without settings.
+
+use serde_yaml::Value as YamlValue;
+use std::path::Path;
+
+use crate::stats;
+use crate::text;
+
+const EDITORCONFIG: &str = ".editorconfig";
+const RUSTFMT_CONFIGS: &[&str] = &["rustfmt.toml", ".rustfmt.toml"];
+const PRETTIER_CONFIGS: &[&str] = &[
+    ".prettierrc",
+    ".prettierrc.json",
+    ".prettierrc.yaml",
+    ".prettierrc.yml",
+    ".prettierrc.toml",
+    ".prettierrc.js",
+    ".prettierrc.cjs",
+    ".prettierrc.mjs",
+    "prettier.config.js",
+    "prettier.config.cjs",
+    "prettier.config.mjs",
+];
+const RUFF_CONFIGS: &[&str] = &["ruff.toml", ".ruff.toml"];
+
+/// Languages prettier formats.
+const PRETTIER_LANGUAGES: &[&str] = &[
+    "javascript",
+    "typescript",
+    "css",
+    "scss",
+    "html",
+    "markdown",
+    "yaml",
+    "json",
+    "vue",
+    "svelte",
+];
+
+/// Settings from one config file.
+#[derive(Debug, PartialEq)]
+pub struct FormatSource {
+    pub tool: &'static str,
+    /// Path relative to the workspace root.
+    pub file: String,
+    pub sections: Vec<Section>,
+}
+
+/// A group of settings; `pattern` is the `.editorconfig` section glob.
+#[derive(Debug, PartialEq)]
+pub struct Section {
+    pub pattern: Option<String>,
+    pub settings: Vec<(String, String)>,
+}
+
+/// Formatter settings for the project at `project`, nearest first. With a
+/// language, only configs and `.editorconfig` sections that apply to it are
+/// returned.
+pub fn detect(project: &Path, workspace_root: &Path, language: Option<&str>) -> Vec<FormatSource> {
+    let dirs: Vec<&Path> = if project.starts_with(workspace_root) {
+        project
+            .ancestors()
+            .take_while(|dir| dir.starts_with(workspace_root))
+            .collect()
+    } else {
+        vec![project]
+    };
+    let relative = |path: &Path| {
+        path.strip_prefix(workspace_root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+            .replace('\\', "/")
+    };
+    let language = language.map(str::to_lowercase);
+    let applies = |languages: &[&str]| {
+        language
+            .as_deref()
+            .is_none_or(|language| languages.contains(&language))
+    };
+
+    let mut sources = Vec::new();
+    let extensions = language.as_deref().map(stats::extensions);
+    for dir in &dirs {
+        let path = dir.join(EDITORCONFIG);
+        let Ok(content) = text::read_text(&path) else {
+            continue;
+        };
+        let (is_root, mut sections) = parse_editorconfig(&content);
+        if let Some(extensions) = &extensions {
+            sections.retain(|section| {
+                section
+                    .pattern
+                    .as_deref()
+                    .is_some_and(|pattern| pattern_applies(pattern, extensions))
+            });
+        }
+        if !sections.is_empty() {
+            sources.push(FormatSource {
+                tool: "editorconfig",
+                file: relative(&path),
+                sections,
+            });
+        }
+        if is_root {
+            break;
+        }
+    }
+
+    let nearest = |files: &[&str]| {
+        dirs.iter()
+            .flat_map(|dir| files.iter().map(move |file| dir.join(file)))
+            .find(|path| path.is_file())
+    };
+    if applies(&["rust"]) {
+        if let Some(path) = nearest(RUSTFMT_CONFIGS) {
+            let settings = read_toml(&path)
+                .map(|table| toml_settings(&table, ""))
+                .unwrap_or_default();
+            sources.push(single("rustfmt", relative(&path), settings));
+        }
+    }
+    if applies(PRETTIER_LANGUAGES) {
+        if let Some((path, settings)) = prettier(&dirs) {
+            sources.push(single("prettier", relative(&path), settings));
+        }
+    }
+    if applies(&["python"]) {
+        if let Some((path, settings)) = python_tool(&dirs, "black", &[]) {
+            sources.push(single("black", relative(&path), settings));
+        }
+        if let Some((path, settings)) = python_tool(&dirs, "ruff", RUFF_CONFIGS) {
+            sources.push(single("ruff", relative(&path), settings));
+        }
+    }
+    sources
+}
+
+fn single(tool: &'static str, file: String, settings: Vec<(String, String)>) -> FormatSource {
+    FormatSource {
+        tool,
+        file,
+        sections: vec![Section {
+            pattern: None,
+            settings,
+        }],
+    }
+}
+
+/// `(root = true, sections)` of an `.editorconfig` file. Keys are
+/// lowercased, as EditorConfig treats them case-insensitively.
+fn parse_editorconfig(content: &str) -> (bool, Vec<Section>) {
+    let mut is_root = false;
+    let mut sections: Vec<Section> = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+        if let Some(pattern) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push(Section {
+                pattern: Some(pattern.to_string()),
+                settings: Vec::new(),
+            });
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim().to_lowercase(), value.trim().to_string());
+        match sections.last_mut() {
+            Some(section) => section.settings.push((key, value)),
+            None if key == "root" => is_root = value.eq_ignore_ascii_case("true"),
+            None => {}
+        }
+    }
+    sections.retain(|section| !section.settings.is_empty());
+    (is_root, sections)
+}
+
+/// Whether an `.editorconfig` section glob covers files with any of
+/// `extensions`. Only the file name part is considered, so `[src/**.rs]`
+/// applies to Rust and `[Makefile]` to no language.
+fn pattern_applies(pattern: &str, extensions: &[&str]) -> bool {
+    expand_braces(pattern).iter().any(|alternative| {
+        let name = alternative.rsplit('/').next().unwrap_or(alternative);
+        name.chars().all(|c| c == '*')
+            || extensions.iter().any(|ext| {
+                name.strip_suffix(ext)
+                    .and_then(|rest| rest.strip_suffix('.'))
+                    .is_some_and(|rest| rest.chars().all(|c| c == '*'))
+            })
+    })
+}
+
+/// `*.{js,ts}` as `["*.js", "*.ts"]`. Only the first brace group is
+/// expanded; nested groups are rare in practice.
+fn expand_braces(pattern: &str) -> Vec<String> {
+    let Some((prefix, rest)) = pattern.split_once('{') else {
+        return vec![pattern.to_string()];
+    };
+    let Some((choices, suffix)) = rest.split_once('}') else {
+        return vec![pattern.to_string()];
+    };
+    choices
+        .split(',')
+        .map(|choice| format!("{}{}{}", prefix, choice.trim(), suffix))
+        .collect()
+}
+
+fn read_toml(path: &Path) -> Option<toml::Table> {
+    text::read_text(path).ok()?.parse().ok()
+}
+
+/// Scalar settings of `table`, with nested tables as dotted keys. Arrays
+/// are skipped; they hold file lists and rule selections, not layout.
+fn toml_settings(table: &toml::Table, prefix: &str) -> Vec<(String, String)> {
+    let mut settings = Vec::new();
+    for (key, value) in table {
+        let key = format!("{}{}", prefix, key);
+        match value {
+            toml::Value::String(s) => settings.push((key, s.clone())),
+            toml::Value::Table(nested) => {
+                settings.extend(toml_settings(nested, &format!("{}.", key)))
+            }
+            toml::Value::Array(_) => {}
+            other => settings.push((key, other.to_string())),
+        }
+    }
+    settings
+}
+
+/// The nearest prettier config, including the `prettier` key of
+/// `package.json`.
+fn prettier(dirs: &[&Path]) -> Option<(std::path::PathBuf, Vec<(String, String)>)> {
+    for dir in dirs {
+        for file in PRETTIER_CONFIGS {
+            let path = dir.join(file);
+            if !path.is_file() {
+                continue;
+            }
+            let settings = if file.ends_with(".toml") {
+                read_toml(&path)
+                    .map(|table| toml_settings(&table, ""))
+                    .unwrap_or_default()
+            } else if file.ends_with("js") {
+                Vec::new()
+            } else {
+                // JSON is valid YAML, so one parser covers `.prettierrc`.
+                read_yaml(&path)
+                    .map(|config| yaml_settings(&config))
+                    .unwrap_or_default()
+            };
+            return Some((path, settings));
+        }
+        let package = dir.join("package.json");
+        if let Some(config) = read_yaml(&package).and_then(|p| p.get("prettier").cloned()) {
+            let settings = match config {
+                // A shared config package such as `@acme/prettier-config`.
+                YamlValue::String(shared) => vec![("extends".to_string(), shared)],
+                config => yaml_settings(&config),
+            };
+            return Some((package, settings));
+        }
+    }
+    None
+}
+
+fn read_yaml(path: &Path) -> Option<YamlValue> {
+    serde_yaml::from_str(&text::read_text(path).ok()?).ok()
+}
+
+fn yaml_settings(config: &YamlValue) -> Vec<(String, String)> {
+    let Some(mapping) = config.as_mapping() else {
+        return Vec::new();
+    };
+    mapping
+        .iter()
+        .filter_map(|(key, value)| {
+            let value = match value {
+                YamlValue::String(s) => s.clone(),
+                YamlValue::Number(n) => n.to_string(),
+                YamlValue::Bool(b) => b.to_string(),
+                _ => return None,
+            };
+            Some((key.as_str()?.to_string(), value))
+        })
+        .collect()
+}
+
+/// The nearest `[tool.<tool>]` table in `pyproject.toml` or dedicated
+/// config file. For ruff only top-level and `format` settings are kept.
+fn python_tool(
+    dirs: &[&Path],
+    tool: &str,
+    files: &[&str],
+) -> Option<(std::path::PathBuf, Vec<(String, String)>)> {
+    for dir in dirs {
+        let found = files
+            .iter()
+            .map(|file| dir.join(file))
+            .find_map(|path| Some((read_toml(&path)?, path)))
+            .or_else(|| {
+                let path = dir.join("pyproject.toml");
+                let table = read_toml(&path)?
+                    .get("tool")?
+                    .get(tool)?
+                    .as_table()?
+                    .clone();
+                Some((table, path))
+            });
+        if let Some((mut table, path)) = found {
+            if tool == "ruff" {
+                table.retain(|key, value| !value.is_table() || key == "format");
+            }
+            return Some((path, toml_settings(&table, "")));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pattern_applies() {
+        assert!(pattern_applies("*", &["rs"]));
+        assert!(pattern_applies("*.{js,ts}", &["ts", "tsx"]));
+        assert!(pattern_applies("src/**.rs", &["rs"]));
+        assert!(!pattern_applies("*.md", &["rs"]));
+        assert!(!pattern_applies("Makefile", &["rs"]));
+    }
+
+    #[test]
+    fn test_detect_walks_up_to_the_workspace_root() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let project = root.join("services/api");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(
+            root.join(EDITORCONFIG),
+            "root = true\n\n[*]\nindent_style = space\nindent_size = 2\n\n[Makefile]\nindent_style = tab\n",
+        )
+        .unwrap();
+        fs::write(
+            project.join(EDITORCONFIG),
+            "# Rust uses 4\n[*.rs]\nIndent_Size = 4\nmax_line_length = 100\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("rustfmt.toml"),
+            "max_width = 100\nhard_tabs = false\nignore = [\"generated\"]\n",
+        )
+        .unwrap();
+        fs::write(root.join(".prettierrc"), r#"{"singleQuote": true}"#).unwrap();
+
+        let sources = detect(&project, root, Some("Rust"));
+        let summary: Vec<String> = sources
+            .iter()
+            .flat_map(|source| {
+                source.sections.iter().map(|section| {
+                    let settings: Vec<String> = section
+                        .settings
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, value))
+                        .collect();
+                    format!(
+                        "{} {} {:?} {}",
+                        source.tool,
+                        source.file,
+                        section.pattern,
+                        settings.join(" ")
+                    )
+                })
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                "editorconfig services/api/.editorconfig Some(\"*.rs\") indent_size=4 max_line_length=100",
+                "editorconfig .editorconfig Some(\"*\") indent_style=space indent_size=2",
+                "rustfmt rustfmt.toml None hard_tabs=false max_width=100",
+            ]
+        );
+
+        let tools: Vec<&str> = detect(&project, root, None)
+            .iter()
+            .map(|s| s.tool)
+            .collect();
+        assert_eq!(
+            tools,
+            ["editorconfig", "editorconfig", "rustfmt", "prettier"]
+        );
+    }
+}
@@ -0,0 +1,465 @@
+//! `jumble init --from <repo> --template <name>`: start a project's context
+//! from a template kept in a shared repository.
+//!
+//! A template repository holds one directory per template. Everything in
+//! the chosen directory is copied into the project, with `{{variable}}`
+//! replaced in file contents and paths. `project_name` defaults to the
+//! project directory's name; `--var key=value` sets others. Files that
+//! already exist are left alone, as with a plain `jumble init`.
+//!
+//! `--rev <commit>` pins the template repository to a commit: it is checked
+//! out and `HEAD` must match before anything is copied.
+
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use walkdir::WalkDir;
+
+use crate::exec;
+use crate::remote;
+use crate::setup::{Action, Reporter};
+use crate::text;
+
+/// How long one git command may take while fetching a template.
+const CLONE_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// The user's variables git needs to reach a remote, passed on top of the
+/// exec sandbox's allow-list.
+const CREDENTIAL_ENV: &[&str] = &[
+    "SSH_AUTH_SOCK",
+    "GIT_SSH",
+    "GIT_SSH_COMMAND",
+    "GIT_ASKPASS",
+    "SSH_ASKPASS",
+    "XDG_CONFIG_HOME",
+    "HTTP_PROXY",
+    "HTTPS_PROXY",
+    "NO_PROXY",
+    "http_proxy",
+    "https_proxy",
+    "no_proxy",
+];
+
+/// The set entries of [`CREDENTIAL_ENV`].
+fn credential_env() -> HashMap<String, String> {
+    CREDENTIAL_ENV
+        .iter()
+        .filter_map(|key| Some((key.to_string(), std::env::var(key).ok()?)))
+        .collect()
+}
+
+/// A checked-out template repository, removed when dropped if it was cloned.
+pub struct TemplateSource {
+    path: PathBuf,
+    cloned: bool,
+}
+
+impl TemplateSource {
+    /// Use `source` directly when it is a local directory, otherwise
+    /// shallow-clone it with git. Git runs through the exec sandbox, with the
+    /// variables in [`CREDENTIAL_ENV`] passed on so SSH agents, credential
+    /// helpers and proxies work. With `rev`, the checkout must be at that
+    /// commit.
+    pub fn fetch(source: &str, rev: Option<&str>) -> Result<Self> {
+        if let Some(rev) = rev {
+            if !matches!(rev.len(), 40 | 64) || !rev.chars().all(|c| c.is_ascii_hexdigit()) {
+                anyhow::bail!("--rev must be a full commit hash, got '{}'", rev);
+            }
+        }
+        let local = Path::new(source);
+        if local.is_dir() {
+            let source = Self {
+                path: local.to_path_buf(),
+                cloned: false,
+            };
+            if let Some(rev) = rev {
+                source.verify_rev(rev)?;
+            }
+            return Ok(source);
+        }
+        remote::check_network(&format!("cloning {}", source)).map_err(anyhow::Error::msg)?;
+        let path = std::env::temp_dir().join(format!(
+            "jumble-template-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        let mut argv = vec!["git", "clone", "--depth", "1", "--quiet", "--", source]
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        argv.push(path.to_string_lossy().into_owned());
+        let policy = exec::ExecPolicy {
+            timeout: CLONE_TIMEOUT,
+            ..exec::ExecPolicy::default()
+        };
+        let output = exec::run_with_env(&argv, &std::env::temp_dir(), &credential_env(), &policy)
+            .map_err(anyhow::Error::msg)
+            .context("Failed to run git")?;
+        if !output.success() {
+            let _ = std::fs::remove_dir_all(&path);
+            if output.timed_out {
+                anyhow::bail!("Timed out cloning {}", source);
+            }
+            anyhow::bail!("Failed to clone {}: {}", source, output.stderr.trim());
+        }
+        let source = Self { path, cloned: true };
+        if let Some(rev) = rev {
+            if source.head()? != rev.to_lowercase() {
+                source.git(&["fetch", "--depth", "1", "--quiet", "origin", rev])?;
+                source.git(&["checkout", "--quiet", "FETCH_HEAD"])?;
+            }
+            source.verify_rev(rev)?;
+        }
+        Ok(source)
+    }
+
+    /// Run git in the checkout, returning its output.
+    fn git(&self, args: &[&str]) -> Result<String> {
+        let argv: Vec<String> = std::iter::once("git")
+            .chain(args.iter().copied())
+            .map(str::to_string)
+            .collect();
+        let policy = exec::ExecPolicy {
+            timeout: CLONE_TIMEOUT,
+            ..exec::ExecPolicy::default()
+        };
+        let output = exec::run_with_env(&argv, &self.path, &credential_env(), &policy)
+            .map_err(anyhow::Error::msg)
+            .context("Failed to run git")?;
+        if !output.success() {
+            anyhow::bail!("git {} failed: {}", args.join(" "), output.stderr.trim());
+        }
+        Ok(output.stdout.trim().to_string())
+    }
+
+    fn head(&self) -> Result<String> {
+        self.git(&["rev-parse", "HEAD"])
+    }
+
+    fn verify_rev(&self, rev: &str) -> Result<()> {
+        let head = self.head()?;
+        if head != rev.to_lowercase() {
+            anyhow::bail!(
+                "Template repository is at {}, but --rev {} was requested",
+                head,
+                rev
+            );
+        }
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Template names: top-level directories that aren't hidden.
+    pub fn templates(&self) -> Vec<String> {
+        let mut names: Vec<String> = std::fs::read_dir(&self.path)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| e.path().is_dir())
+                    .filter_map(|e| e.file_name().to_str().map(str::to_string))
+                    .filter(|name| !name.starts_with('.'))
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names
+    }
+}
+
+impl Drop for TemplateSource {
+    fn drop(&mut self) {
+        if self.cloned {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+}
+
+/// Parse `key=value` pairs from `--var`.
+pub fn parse_vars(pairs: &[String]) -> Result<HashMap<String, String>> {
+    pairs
+        .iter()
+        .map(|pair| {
+            let (key, value) = pair
+                .split_once('=')
+                .with_context(|| format!("Expected key=value, got '{}'", pair))?;
+            Ok((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Replace `{{key}}` for every variable. Returns the text and the names of
+/// placeholders left unreplaced.
+fn substitute(text: &str, vars: &HashMap<String, String>) -> (String, Vec<String>) {
+    let mut output = text.to_string();
+    for (key, value) in vars {
+        output = output.replace(&format!("{{{{{}}}}}", key), value);
+    }
+    let mut unresolved = Vec::new();
+    let mut rest = output.as_str();
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let name = &rest[start + 2..start + end];
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            unresolved.push(name.to_string());
+        }
+        rest = &rest[start + end + 2..];
+    }
+    (output, unresolved)
+}
+
+/// Whether `path` is non-empty and made only of plain names, so joining it
+/// onto a directory stays inside that directory.
+fn is_plain_relative(path: &Path) -> bool {
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+}
+
+/// Copy `template` from `source` into `project_root`, substituting `vars`
+/// (plus `project_name`, unless given).
+pub fn instantiate(
+    source: &TemplateSource,
+    template: &str,
+    project_root: &Path,
+    mut vars: HashMap<String, String>,
+    report: &mut Reporter,
+) -> Result<()> {
+    let template_dir = source.path().join(template);
+    if !is_plain_relative(Path::new(template)) || !template_dir.is_dir() {
+        anyhow::bail!(
+            "Template '{}' not found. Available templates: {}",
+            template,
+            source.templates().join(", ")
+        );
+    }
+    if !vars.contains_key("project_name") {
+        let name = project_root
+            .canonicalize()
+            .unwrap_or_else(|_| project_root.to_path_buf())
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("my-project")
+            .to_string();
+        vars.insert("project_name".to_string(), name);
+    }
+
+    let mut unresolved = Vec::new();
+    let mut entries: Vec<_> = WalkDir::new(&template_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .collect();
+    entries.sort_by(|a, b| a.path().cmp(b.path()));
+    // Render every path first, so a bad one fails before anything is written.
+    let mut files = Vec::new();
+    for entry in &entries {
+        let relative = entry
+            .path()
+            .strip_prefix(&template_dir)
+            .unwrap_or(entry.path());
+        let (relative, missing) = substitute(&relative.to_string_lossy(), &vars);
+        unresolved.extend(missing);
+        if !is_plain_relative(Path::new(&relative)) {
+            anyhow::bail!(
+                "Template file {} renders to '{}', which is outside the project",
+                entry.path().display(),
+                relative
+            );
+        }
+        files.push((entry.path(), relative));
+    }
+    for (path, relative) in files {
+        let target = project_root.join(&relative);
+
+        if target.exists() {
+            report.file(
+                &target,
+                Action::Skipped,
+                &format!("✓ {} already exists", relative),
+            );
+            continue;
+        }
+        let content =
+            text::read_text(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let (content, missing) = substitute(&content, &vars);
+        unresolved.extend(missing);
+        if let Some(parent) = target.parent().filter(|p| !p.exists()) {
+            let shown = parent.strip_prefix(project_root).unwrap_or(parent);
+            report.create_dir(parent, &format!("✓ Created {}", shown.display()))?;
+        }
+        report.write(
+            &target,
+            &content,
+            Action::Created,
+            &format!("✓ Created {}", relative),
+        )?;
+    }
+
+    unresolved.sort();
+    unresolved.dedup();
+    if !unresolved.is_empty() {
+        report.warn(&format!(
+            "No value for {}; pass --var name=value to fill them in",
+            unresolved
+                .iter()
+                .map(|name| format!("{{{{{}}}}}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// `jumble init --from <source> --template <name>`.
+pub fn run_init_from(
+    project_root: &Path,
+    source: &str,
+    rev: Option<&str>,
+    template: &str,
+    vars: &[String],
+    report: &mut Reporter,
+) -> Result<()> {
+    let vars = parse_vars(vars)?;
+    let source = TemplateSource::fetch(source, rev)?;
+    instantiate(&source, template, project_root, vars, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::setup::OutputMode;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_substitute_reports_unresolved_placeholders() {
+        let vars = HashMap::from([("project_name".to_string(), "billing".to_string())]);
+        let (text, unresolved) =
+            substitute("name = \"{{project_name}}\" # {{team}} {{ x }}", &vars);
+        assert_eq!(text, "name = \"billing\" # {{team}} {{ x }}");
+        assert_eq!(unresolved, ["team"]);
+    }
+
+    #[test]
+    fn test_instantiate_copies_template_with_variables() {
+        let repo = TempDir::new().unwrap();
+        let service = repo.path().join("service/.jumble");
+        fs::create_dir_all(service.join("skills")).unwrap();
+        fs::write(
+            service.join("project.toml"),
+            "[project]\nname = \"{{project_name}}\"\ndescription = \"Owned by {{team}}\"\n",
+        )
+        .unwrap();
+        fs::write(
+            service.join("skills/{{project_name}}-deploy.md"),
+            "Deploy\n",
+        )
+        .unwrap();
+        fs::create_dir_all(repo.path().join("library")).unwrap();
+
+        let project = TempDir::new().unwrap();
+        let root = project.path().join("billing");
+        fs::create_dir_all(root.join(".jumble")).unwrap();
+        let source = TemplateSource::fetch(repo.path().to_str().unwrap(), None).unwrap();
+        let vars = parse_vars(&["team=payments".to_string()]).unwrap();
+        let mut report = Reporter::new(OutputMode::Quiet, "init");
+        instantiate(&source, "service", &root, vars, &mut report).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(root.join(".jumble/project.toml")).unwrap(),
+            "[project]\nname = \"billing\"\ndescription = \"Owned by payments\"\n"
+        );
+        assert!(root.join(".jumble/skills/billing-deploy.md").is_file());
+
+        // Existing files are kept.
+        fs::write(root.join(".jumble/project.toml"), "edited").unwrap();
+        let mut report = Reporter::new(OutputMode::Quiet, "init");
+        instantiate(&source, "service", &root, HashMap::new(), &mut report).unwrap();
+        assert_eq!(
+            fs::read_to_string(root.join(".jumble/project.toml")).unwrap(),
+            "edited"
+        );
+
+        let err = instantiate(&source, "worker", &root, HashMap::new(), &mut report).unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("Available templates: library, service"));
+    }
+
+    #[test]
+    fn test_instantiate_stays_inside_the_project() {
+        let repo = TempDir::new().unwrap();
+        fs::create_dir_all(repo.path().join("service")).unwrap();
+        fs::write(repo.path().join("service/{{name}}.md"), "x").unwrap();
+        fs::write(repo.path().join("service/README.md"), "x").unwrap();
+        let source = TemplateSource::fetch(repo.path().to_str().unwrap(), None).unwrap();
+        let project = TempDir::new().unwrap();
+        let root = project.path().join("billing");
+        fs::create_dir_all(&root).unwrap();
+        let mut report = Reporter::new(OutputMode::Quiet, "init");
+
+        let outside = repo.path().join("service");
+        let err = instantiate(
+            &source,
+            outside.to_str().unwrap(),
+            &root,
+            HashMap::new(),
+            &mut report,
+        )
+        .unwrap_err();
+        assert!(err.to_string().starts_with("Template "));
+
+        let vars = parse_vars(&["name=../escaped".to_string()]).unwrap();
+        let err = instantiate(&source, "service", &root, vars, &mut report).unwrap_err();
+        assert!(err.to_string().ends_with("which is outside the project"));
+        assert!(!project.path().join("escaped.md").exists());
+        assert!(!root.join("README.md").exists());
+    }
+
+    #[test]
+    #[cfg(feature = "exec")]
+    fn test_fetch_verifies_pinned_rev() {
+        let repo = TempDir::new().unwrap();
+        fs::create_dir_all(repo.path().join("service")).unwrap();
+        fs::write(repo.path().join("service/README.md"), "x").unwrap();
+        let git = |args: &[&str]| {
+            let output = std::process::Command::new("git")
+                .arg("-C")
+                .arg(repo.path())
+                .args(["-c", "user.name=t", "-c", "user.email=t@example.com"])
+                .args(args)
+                .output()
+                .unwrap();
+            assert!(output.status.success());
+            String::from_utf8_lossy(&output.stdout).trim().to_string()
+        };
+        git(&["init", "--quiet"]);
+        git(&["add", "."]);
+        git(&["commit", "--quiet", "-m", "init"]);
+        let head = git(&["rev-parse", "HEAD"]);
+        let path = repo.path().to_str().unwrap();
+
+        assert!(TemplateSource::fetch(path, Some(&head.to_uppercase())).is_ok());
+        let err = TemplateSource::fetch(path, Some(&"a".repeat(40)))
+            .err()
+            .unwrap();
+        assert!(err.to_string().starts_with("Template repository is at "));
+        let err = TemplateSource::fetch(path, Some("main")).err().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "--rev must be a full commit hash, got 'main'"
+        );
+    }
+}
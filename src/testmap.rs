@@ -0,0 +1,283 @@
+//! Which tests cover a change, for `get_tests_for`.
+//!
+//! Sources matched by a `[test_map]` pattern get the tests listed for it.
+//! Otherwise a test file covers a source when their names agree once test
+//! affixes are removed: `billing.rs`, `tests/billing_test.rs`,
+//! `test_billing.py`, `billing.spec.ts` and `BillingTest.java` all name
+//! `billing`. Changed test files cover themselves.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::config::{ProjectConfig, TestMap};
+use crate::files;
+
+/// Directory names that hold tests.
+const TEST_DIRS: &[&str] = &["test", "tests", "__tests__", "spec", "specs"];
+
+/// Prefixes and suffixes that mark a file stem as a test.
+const TEST_PREFIXES: &[&str] = &["test_"];
+const TEST_SUFFIXES: &[&str] = &[
+    "_test", "_tests", "_spec", ".test", ".spec", "-test", "-spec",
+];
+
+/// Suffixes of CamelCase test classes, matched with their case.
+const CLASS_SUFFIXES: &[&str] = &["Test", "Tests", "Spec"];
+
+/// Stems that name their directory rather than themselves.
+const INDEX_STEMS: &[&str] = &["mod", "index", "__init__", "lib", "main"];
+
+fn stem(path: &str) -> &str {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    // `billing.spec.ts` -> `billing.spec`
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+fn in_test_dir(path: &str) -> bool {
+    let mut dirs = path.split('/').rev().skip(1);
+    dirs.any(|dir| TEST_DIRS.contains(&dir))
+}
+
+/// Whether `path` is a test file by its name or directory.
+pub fn is_test_file(path: &str) -> bool {
+    in_test_dir(path) || test_subject(path).is_some()
+}
+
+/// The name a test file covers: its stem without test affixes, or the whole
+/// stem for files in a test directory such as Rust's `tests/billing.rs`.
+fn covered_name(path: &str) -> Option<String> {
+    test_subject(path).or_else(|| in_test_dir(path).then(|| stem(path).to_lowercase()))
+}
+
+/// The lowercase name a test file's stem refers to, if it has test affixes.
+fn test_subject(path: &str) -> Option<String> {
+    let stem = stem(path);
+    let lower = stem.to_lowercase();
+    TEST_PREFIXES
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))
+        .or_else(|| {
+            TEST_SUFFIXES
+                .iter()
+                .find_map(|suffix| lower.strip_suffix(suffix))
+        })
+        .map(str::to_string)
+        .or_else(|| {
+            CLASS_SUFFIXES
+                .iter()
+                .find_map(|suffix| stem.strip_suffix(suffix))
+                .filter(|rest| rest.ends_with(|c: char| c.is_lowercase()))
+                .map(str::to_lowercase)
+        })
+        .filter(|subject| !subject.is_empty())
+}
+
+/// The lowercase name tests would use for a source file.
+fn source_subject(path: &str) -> String {
+    let stem = stem(path).to_lowercase();
+    if INDEX_STEMS.contains(&stem.as_str()) {
+        let mut parts = path.rsplit('/').skip(1);
+        if let Some(dir) = parts.next() {
+            return dir.to_lowercase();
+        }
+    }
+    stem
+}
+
+/// Test files covering `changed`, each with why it was picked, from the
+/// project's `files`.
+pub fn tests_for(
+    files: &[String],
+    changed: &[&str],
+    map: Option<&TestMap>,
+) -> BTreeMap<String, String> {
+    let tests: Vec<&String> = files.iter().filter(|f| is_test_file(f)).collect();
+    let mut found = BTreeMap::new();
+    for source in changed {
+        if is_test_file(source) {
+            found
+                .entry(source.to_string())
+                .or_insert_with(|| "changed".to_string());
+            continue;
+        }
+        let mapped: Vec<(&String, &Vec<String>)> = map
+            .map(|map| {
+                let mut paths: Vec<_> = map
+                    .paths
+                    .iter()
+                    .filter(|(pattern, _)| files::matches_globs(&[pattern.to_string()], source))
+                    .collect();
+                paths.sort();
+                paths
+            })
+            .unwrap_or_default();
+        if !mapped.is_empty() {
+            for (pattern, targets) in mapped {
+                for test in files.iter().filter(|f| files::matches_globs(targets, f)) {
+                    found
+                        .entry(test.clone())
+                        .or_insert_with(|| format!("`[test_map]` maps `{}` here", pattern));
+                }
+            }
+            continue;
+        }
+        let subject = source_subject(source);
+        for test in &tests {
+            if covered_name(test).as_deref() == Some(subject.as_str()) {
+                found
+                    .entry(test.to_string())
+                    .or_insert_with(|| format!("name matches `{}`", source));
+            }
+        }
+    }
+    found
+}
+
+/// `path` as one shell word: unchanged when it has no characters a shell
+/// treats specially, otherwise single-quoted.
+fn shell_quote(path: &str) -> String {
+    let plain = |c: char| c.is_ascii_alphanumeric() || "/._-+=:,@%".contains(c);
+    if !path.is_empty() && path.chars().all(plain) {
+        return path.to_string();
+    }
+    format!("'{}'", path.replace('\'', "'\\''"))
+}
+
+/// `tests` quoted for the shell and joined with spaces.
+fn file_args(tests: &[&str]) -> String {
+    let quoted: Vec<String> = tests.iter().map(|t| shell_quote(t)).collect();
+    quoted.join(" ")
+}
+
+/// A command running just `tests`, from `[test_map] run` or the project's
+/// language.
+pub fn run_command(config: &ProjectConfig, tests: &[&str]) -> Option<String> {
+    if tests.is_empty() {
+        return None;
+    }
+    if let Some(run) = config.test_map.as_ref().and_then(|map| map.run.as_ref()) {
+        return Some(run.replace("{files}", &file_args(tests)));
+    }
+    let language = config.project.language.as_deref()?.to_lowercase();
+    match language.as_str() {
+        "rust" => {
+            // Only integration tests can be selected by file.
+            let targets: Vec<String> = tests
+                .iter()
+                .filter(|t| t.starts_with("tests/") && t.matches('/').count() == 1)
+                .map(|t| format!("--test {}", shell_quote(stem(t))))
+                .collect();
+            (!targets.is_empty()).then(|| format!("cargo test {}", targets.join(" ")))
+        }
+        "python" => Some(format!("pytest {}", file_args(tests))),
+        "go" => {
+            let packages: BTreeSet<String> = tests
+                .iter()
+                .map(|t| match Path::new(t).parent().and_then(Path::to_str) {
+                    Some("") | None => "./".to_string(),
+                    Some(dir) => shell_quote(&format!("./{}", dir)),
+                })
+                .collect();
+            let packages: Vec<String> = packages.into_iter().collect();
+            Some(format!("go test {}", packages.join(" ")))
+        }
+        "typescript" | "javascript" => config
+            .commands
+            .get("test")
+            .map(|test| format!("{} -- {}", test, file_args(tests))),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn strings(items: &[&str]) -> Vec<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_is_test_file() {
+        assert!(is_test_file("tests/helpers.rs"));
+        assert!(is_test_file("pkg/api/handler_test.go"));
+        assert!(is_test_file("src/BillingTests.cs"));
+        assert!(!is_test_file("src/latest.rs"));
+        assert!(!is_test_file("src/Test.java"));
+    }
+
+    #[test]
+    fn test_tests_for_matches_names_and_map() {
+        let files = strings(&[
+            "src/billing/mod.rs",
+            "src/invoice.rs",
+            "src/ledger.py",
+            "tests/billing_test.rs",
+            "tests/invoice.rs",
+            "tests/test_ledger.py",
+            "tests/e2e/checkout.spec.ts",
+            "web/Invoice.test.ts",
+            "src/latest.rs",
+            "src/InvoiceTest.java",
+        ]);
+        let found = tests_for(&files, &["src/billing/mod.rs", "src/invoice.rs"], None);
+        assert_eq!(
+            found,
+            BTreeMap::from([
+                (
+                    "tests/billing_test.rs".to_string(),
+                    "name matches `src/billing/mod.rs`".to_string()
+                ),
+                (
+                    "src/InvoiceTest.java".to_string(),
+                    "name matches `src/invoice.rs`".to_string()
+                ),
+                (
+                    "tests/invoice.rs".to_string(),
+                    "name matches `src/invoice.rs`".to_string()
+                ),
+                (
+                    "web/Invoice.test.ts".to_string(),
+                    "name matches `src/invoice.rs`".to_string()
+                ),
+            ])
+        );
+
+        let map = TestMap {
+            run: None,
+            paths: HashMap::from([("src/*.py".to_string(), strings(&["tests/e2e/"]))]),
+        };
+        let found = tests_for(
+            &files,
+            &["src/ledger.py", "tests/test_ledger.py"],
+            Some(&map),
+        );
+        assert_eq!(
+            found.keys().collect::<Vec<_>>(),
+            ["tests/e2e/checkout.spec.ts", "tests/test_ledger.py"]
+        );
+        assert_eq!(found["tests/test_ledger.py"], "changed");
+    }
+
+    #[test]
+    fn test_run_command_quotes_and_dedups_files() {
+        let mut config: ProjectConfig =
+            toml::from_str("[project]\nname = \"app\"\ndescription = \"App\"\nlanguage = \"go\"\n")
+                .unwrap();
+        let tests = ["pkg/a/a_test.go", "pkg/b/b_test.go", "pkg/a/c_test.go"];
+        assert_eq!(
+            run_command(&config, &tests).unwrap(),
+            "go test ./pkg/a ./pkg/b"
+        );
+
+        config.test_map = Some(TestMap {
+            run: Some("pytest {files}".to_string()),
+            paths: Default::default(),
+        });
+        assert_eq!(
+            run_command(&config, &["tests/test_a.py", "tests/it's here.py"]).unwrap(),
+            "pytest tests/test_a.py 'tests/it'\\''s here.py'"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+//! Reading user-authored text files (TOML and markdown) leniently.
+//!
+//! Files written on Windows often start with a byte-order mark, use CRLF line
+//! endings, or are saved as UTF-16. Rather than failing to parse (and the
+//! file silently disappearing from discovery), decode what we can, normalize
+//! line endings, and print a diagnostic when bytes had to be replaced.
+
+use std::path::Path;
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+/// Text decoded from a file, with what had to be fixed along the way.
+#[derive(Debug)]
+pub struct Decoded {
+    pub text: String,
+    /// Invalid sequences were replaced with U+FFFD.
+    pub lossy: bool,
+}
+
+/// Decode bytes as UTF-8 (or UTF-16 with a BOM), dropping any BOM and
+/// converting CRLF/CR line endings to LF.
+pub fn decode(bytes: &[u8]) -> Decoded {
+    let (text, lossy) = if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+        decode_utf8(rest)
+    } else if let Some(rest) = bytes.strip_prefix(UTF16_LE_BOM) {
+        decode_utf16(rest, u16::from_le_bytes)
+    } else if let Some(rest) = bytes.strip_prefix(UTF16_BE_BOM) {
+        decode_utf16(rest, u16::from_be_bytes)
+    } else {
+        decode_utf8(bytes)
+    };
+
+    let text = if text.contains('\r') {
+        text.replace("\r\n", "\n").replace('\r', "\n")
+    } else {
+        text
+    };
+    Decoded { text, lossy }
+}
+
+fn decode_utf8(bytes: &[u8]) -> (String, bool) {
+    match String::from_utf8(bytes.to_vec()) {
+        Ok(text) => (text, false),
+        Err(_) => (String::from_utf8_lossy(bytes).into_owned(), true),
+    }
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> (String, bool) {
+    let units: Vec<u16> = bytes
+        .chunks(2)
+        .map(|pair| from_bytes([pair[0], *pair.get(1).unwrap_or(&0)]))
+        .collect();
+    let odd_length = !bytes.len().is_multiple_of(2);
+    match String::from_utf16(&units) {
+        Ok(text) => (text, odd_length),
+        Err(_) => (String::from_utf16_lossy(&units), true),
+    }
+}
+
+/// Read a text file leniently, warning on stderr if bytes were replaced.
+pub fn read_text(path: &Path) -> std::io::Result<String> {
+    let decoded = decode(&std::fs::read(path)?);
+    if decoded.lossy {
+        eprintln!(
+            "jumble: warning: {} is not valid UTF-8; invalid bytes were replaced",
+            path.display()
+        );
+    }
+    Ok(decoded.text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_strips_utf8_bom_and_crlf() {
+        let bytes = b"\xEF\xBB\xBF[project]\r\nname = \"win\"\r\n";
+        let decoded = decode(bytes);
+        assert_eq!(decoded.text, "[project]\nname = \"win\"\n");
+        assert!(!decoded.lossy);
+    }
+
+    #[test]
+    fn test_decode_utf16_le_with_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "name = \"é\"\r\n".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        let decoded = decode(&bytes);
+        assert_eq!(decoded.text, "name = \"é\"\n");
+        assert!(!decoded.lossy);
+    }
+
+    #[test]
+    fn test_decode_replaces_invalid_utf8() {
+        // Latin-1 "café" as saved by an old Windows editor.
+        let decoded = decode(b"description = \"caf\xE9\"\n");
+        assert!(decoded.lossy);
+        assert!(decoded.text.contains("caf\u{FFFD}"));
+    }
+}
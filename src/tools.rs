@@ -1,16 +1,36 @@
 //! MCP tool implementations.
 
+use crate::commands;
+use crate::compliance;
 use crate::config::{
-    Concept, ProjectConfig, ProjectConventions, ProjectDocs, ProjectSkills, WorkspaceConfig,
+    CommandProfile, Concept, CustomTool, DependencyPolicy, DocEntry, ExternalProject, JumbleConfig,
+    KnownIssue, KnownIssueStatus, LinkedProject, MessageTopic, NextStepsConfig, ProjectConfig,
+    ProjectConventions, ProjectDocs, ProjectGroup, ProjectSkills, SkillApplicability,
+    WorkspaceConfig,
 };
+use crate::dependencies;
+use crate::files::{self, FileIndex};
+use crate::forge;
 use crate::format::{
-    format_api, format_commands, format_concept, format_dependencies, format_entry_points,
-    format_related_projects,
+    format_api, format_commands, format_concept, format_dependencies, format_discovered_commands,
+    format_entry_points, format_file_entry, format_inferred_commands, format_related_projects,
 };
+use crate::freshness;
+use crate::health;
+use crate::hooks;
+use crate::i18n;
+use crate::local;
 use crate::memory::MemoryDatabase;
+use crate::owners;
+use crate::ports;
+use crate::state::{self, CommandRun};
+use crate::stats;
+use crate::style;
+use crate::testmap;
+use crate::tracker;
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Component, PathBuf};
 
 /// Type alias for project data stored in the server
 pub type ProjectData = (
@@ -24,339 +44,1101 @@ pub type ProjectData = (
 
 /// Returns the JSON schema for all available tools
 pub fn tools_list() -> Value {
-    json!({
-        "tools": [
-            {
-                "name": "list_projects",
-                "description": "Lists all projects with their descriptions. Use this to discover what projects exist in the workspace.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {},
-                    "required": []
-                }
-            },
-            {
-                "name": "get_project_info",
-                "description": "Returns metadata about a specific project including description, language, version, entry points, and dependencies.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "field": {
-                            "type": "string",
-                            "description": "Optional specific field to retrieve: 'commands', 'entry_points', 'dependencies', 'api', 'related_projects'",
-                            "enum": ["commands", "entry_points", "dependencies", "api", "related_projects"]
-                        }
+    let config_dir = crate::paths::config_dir().display();
+    // One `json!` per tool: a single literal for the whole list needs a
+    // raised recursion limit.
+    let mut tools = vec![
+        json!({
+            "name": "list_projects",
+            "description": "Lists all projects with their descriptions. Use this to discover what projects exist in the workspace. Long lists are paged: pass the cursor from the end of one page to get the next.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "group": {
+                        "type": "string",
+                        "description": "Optional: only list projects in this workspace group (see list_groups)"
                     },
-                    "required": ["project"]
-                }
-            },
-            {
-                "name": "get_commands",
-                "description": "Returns executable commands for a project (build, test, lint, run, dev, etc.)",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "command_type": {
-                            "type": "string",
-                            "description": "Optional specific command type: 'build', 'test', 'lint', 'run', 'dev'"
-                        }
+                    "cursor": {
+                        "type": "string",
+                        "description": "Optional: continue from the cursor given at the end of the previous page"
                     },
-                    "required": ["project"]
-                }
+                    "limit": {
+                        "type": "integer",
+                        "description": "Optional: maximum number of projects to return (default 100)"
+                    }
+                },
+                "required": []
             },
-            {
-                "name": "get_architecture",
-                "description": "Returns architectural info for a specific concept/area of a project, including relevant files and a summary.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "concept": {
-                            "type": "string",
-                            "description": "The architectural concept to look up (e.g., 'authentication', 'routing', 'database')"
+            "outputSchema": {
+                "type": "object",
+                "properties": {
+                    "projects": {
+                        "type": "array",
+                        "description": "This page of projects: local ones with their path, then [external] ones with their links",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "name": {"type": "string"},
+                                "description": {"type": "string"},
+                                "language": {"type": ["string", "null"]},
+                                "pinned": {"type": "boolean"},
+                                "path": {"type": "string"},
+                                "external": {"type": "boolean"},
+                                "repository": {"type": ["string", "null"]},
+                                "docs": {"type": ["string", "null"]}
+                            },
+                            "required": ["name", "description"]
                         }
                     },
-                    "required": ["project", "concept"]
-                }
-            },
-            {
-                "name": "get_related_files",
-                "description": "Finds files related to a concept or feature by searching through all defined concepts.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "query": {
-                            "type": "string",
-                            "description": "Search query to match against concept names and summaries"
-                        }
+                    "total": {"type": "integer", "description": "Projects in the whole list"},
+                    "next_cursor": {"type": ["string", "null"]}
+                },
+                "required": ["projects", "total"]
+            }
+        }),
+        json!({
+            "name": "list_groups",
+            "description": "Lists the workspace's project groups (domains or teams) with their descriptions and member projects, and the projects in no group. Use it to find your way around large workspaces before filtering list_projects or get_workspace_overview by group.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
+        json!({
+            "name": "get_project_info",
+            "description": "Returns metadata about a specific project including description, language, version, entry points, and dependencies. Pass several fields at once as an array, and `as_json` for the raw config instead of Markdown.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
                     },
-                    "required": ["project", "query"]
-                }
-            },
-            {
-                "name": "list_skills",
-                "description": "Lists available task-specific skills for a project. Skills provide focused context for specific tasks like adding endpoints, debugging, etc.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        }
+                    "field": {
+                        "description": "Optional field, or array of fields, to retrieve: 'commands', 'entry_points', 'dependencies', 'api', 'related_projects'",
+                        "anyOf": [
+                            {
+                                "type": "string",
+                                "enum": PROJECT_INFO_FIELDS
+                            },
+                            {
+                                "type": "array",
+                                "items": {"type": "string", "enum": PROJECT_INFO_FIELDS}
+                            }
+                        ]
                     },
-                    "required": ["project"]
-                }
+                    "as_json": {
+                        "type": "boolean",
+                        "description": "Return the raw config as JSON instead of Markdown: the whole project config, the subtree for a single field, or an object keyed by field for an array"
+                    }
+                },
+                "required": ["project"]
             },
-            {
-                "name": "get_skill",
-                "description": "Retrieves a task-specific skill containing focused context and instructions for a particular task.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
+            "outputSchema": {
+                "type": "object",
+                "description": "The project config. For an [external] project, only its name and description, with links under external.",
+                "properties": {
+                    "project": {
+                        "type": "object",
+                        "properties": {
+                            "name": {"type": "string"},
+                            "description": {"type": "string"},
+                            "language": {"type": ["string", "null"]},
+                            "version": {"type": ["string", "null"]}
                         },
-                        "topic": {
-                            "type": "string",
-                            "description": "The skill topic (e.g., 'add-endpoint', 'debug-auth')"
-                        }
+                        "required": ["name", "description"]
                     },
-                    "required": ["project", "topic"]
-                }
-            },
-            {
-                "name": "get_conventions",
-                "description": "Returns project-specific coding conventions and gotchas. Conventions are architectural patterns and standards; gotchas are common mistakes to avoid.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "category": {
-                            "type": "string",
-                            "description": "Optional: 'conventions' or 'gotchas' to filter results",
-                            "enum": ["conventions", "gotchas"]
-                        }
+                    "commands": {
+                        "type": "object",
+                        "additionalProperties": {"type": "string"}
                     },
-                    "required": ["project"]
-                }
-            },
-            {
-                "name": "get_docs",
-                "description": "Returns a documentation index for a project, listing available docs with summaries. Optionally retrieves the path to a specific doc.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "topic": {
-                            "type": "string",
-                            "description": "Optional: specific doc topic to get the path for"
-                        }
+                    "entry_points": {
+                        "type": "object",
+                        "additionalProperties": {"type": "string"}
                     },
-                    "required": ["project"]
-                }
-            },
-            {
-                "name": "get_workspace_overview",
-                "description": "Returns a high-level overview of the entire workspace: workspace info, all projects with descriptions, and their dependency relationships. Call this first to understand the workspace structure.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {},
-                    "required": []
-                }
-            },
-            {
-                "name": "get_workspace_conventions",
-                "description": "Returns workspace-level conventions and gotchas that apply across all projects in the workspace.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "category": {
-                            "type": "string",
-                            "description": "Optional: 'conventions' or 'gotchas' to filter results",
-                            "enum": ["conventions", "gotchas"]
+                    "dependencies": {
+                        "type": "object",
+                        "properties": {
+                            "internal": {"type": "array", "items": {"type": "string"}},
+                            "external": {"type": "array", "items": {"type": "string"}}
                         }
                     },
-                    "required": []
-                }
-            },
-            {
-                "name": "store_memory",
-                "description": "Stores a memory entry (key-value pair) for a project. AI agents can use this to persist learned information, preferences, or context over time.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "key": {
-                            "type": "string",
-                            "description": "The memory key (identifier)"
-                        },
-                        "value": {
-                            "type": "string",
-                            "description": "The memory value to store"
-                        },
-                        "source": {
-                            "type": "string",
-                            "description": "Optional: identifier for the agent/tool storing this memory"
-                        }
+                    "related_projects": {"type": "object"},
+                    "concepts": {"type": "object"},
+                    "external": {"type": "object"}
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_commands",
+            "description": "Returns executable commands for a project (build, test, lint, run, dev, etc.). Makefile targets, justfile recipes and package.json scripts are included and labelled with their file. Build, test and lint commands still missing are inferred from the project's language or build manifest and labelled as inferred.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
                     },
-                    "required": ["project", "key", "value"]
-                }
-            },
-            {
-                "name": "get_memory",
-                "description": "Retrieves a specific memory entry by key for a project.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "key": {
-                            "type": "string",
-                            "description": "The memory key to retrieve"
-                        }
+                    "command_type": {
+                        "type": "string",
+                        "description": "Optional specific command type: 'build', 'test', 'lint', 'run', 'dev'"
                     },
-                    "required": ["project", "key"]
-                }
+                    "profile": {
+                        "type": "string",
+                        "description": "Optional command profile from [profiles.<name>.commands], e.g. 'ci' or 'docker'. Its commands replace the defaults they name."
+                    }
+                },
+                "required": ["project"]
             },
-            {
-                "name": "list_memories",
-                "description": "Lists all stored memories for a project, optionally filtered by a key pattern.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "pattern": {
-                            "type": "string",
-                            "description": "Optional: filter keys by this substring (case-insensitive)"
+            "outputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {"type": "string"},
+                    "profile": {"type": ["string", "null"]},
+                    "commands": {
+                        "type": "array",
+                        "description": "In lookup order; with command_type, only the command that would run",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "name": {"type": "string"},
+                                "command": {"type": "string"},
+                                "source": {
+                                    "type": "string",
+                                    "description": format!("Where the command comes from: {0}/project.toml, {0}/local.toml, profile <name>, a task runner file, or inferred from <manifest>", config_dir)
+                                }
+                            },
+                            "required": ["name", "command", "source"]
                         }
+                    }
+                },
+                "required": ["project", "commands"]
+            }
+        }),
+        json!({
+            "name": "get_architecture",
+            "description": "Returns architectural info for a specific concept/area of a project, including relevant files, a summary and any skills listed as prompts for working in that area.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
                     },
-                    "required": ["project"]
-                }
+                    "concept": {
+                        "type": "string",
+                        "description": "The architectural concept to look up (e.g., 'authentication', 'routing', 'database')"
+                    }
+                },
+                "required": ["project", "concept"]
             },
-            {
-                "name": "search_memories",
-                "description": "Searches memory keys and values for a query string (case-insensitive substring match).",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "query": {
-                            "type": "string",
-                            "description": "Search query to match against keys and values"
-                        }
+            "outputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {"type": "string"},
+                    "project_path": {"type": "string"},
+                    "concept": {"type": "string", "description": "The concept matched, which may differ from the one asked for"},
+                    "summary": {"type": "string"},
+                    "files": {
+                        "type": "array",
+                        "description": "Paths and globs relative to project_path",
+                        "items": {"type": "string"}
                     },
-                    "required": ["project", "query"]
-                }
-            },
-            {
-                "name": "delete_memory",
-                "description": "Deletes a specific memory entry by key for a project.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "key": {
-                            "type": "string",
-                            "description": "The memory key to delete"
-                        }
+                    "prompts": {"type": "array", "items": {"type": "string"}}
+                },
+                "required": ["project", "project_path", "concept", "summary", "files"]
+            }
+        }),
+        json!({
+            "name": "get_related_files",
+            "description": "Finds files related to a concept or feature by searching through all defined concepts, best matches first. Query words are expanded with the workspace's `[synonyms]`. If no concept matches, falls back to project files whose names contain the query.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
                     },
-                    "required": ["project", "key"]
-                }
-            },
-            {
-                "name": "clear_memories",
-                "description": "Clears all memories for a project, optionally filtered by pattern or age. Use with caution!",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "project": {
-                            "type": "string",
-                            "description": "The project name"
-                        },
-                        "pattern": {
-                            "type": "string",
-                            "description": "Optional: only delete memories with keys matching this pattern (case-insensitive)"
-                        },
-                        "confirm": {
-                            "type": "boolean",
-                            "description": "Must be set to true to confirm deletion"
+                    "query": {
+                        "type": "string",
+                        "description": "Search query to match against concept names and summaries, or file names as a fallback"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Optional: maximum number of concepts to return, best matches first (default 5)"
+                    }
+                },
+                "required": ["project", "query"]
+            }
+        }),
+        json!({
+            "name": "list_skills",
+            "description": "Lists available task-specific skills for a project. Skills provide focused context for specific tasks like adding endpoints, debugging, etc. Skills whose applies_to doesn't match the project's language or the given context are left out.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "context": {
+                        "type": "object",
+                        "description": "Optional: what the agent is working on, to list only relevant skills",
+                        "properties": {
+                            "file": {
+                                "type": "string",
+                                "description": "Current file, relative to the project root"
+                            },
+                            "task": {
+                                "type": "string",
+                                "description": "Task type, e.g. 'bugfix' or 'migration'"
+                            }
                         }
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_skill",
+            "description": "Retrieves a task-specific skill containing focused context and instructions for a particular task.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
                     },
-                    "required": ["project", "confirm"]
-                }
-            },
-            {
-                "name": "reload_workspace",
-                "description": "Reloads workspace and project metadata from disk. Use this after editing .jumble files to pick up changes without restarting the server.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {},
-                    "required": []
-                }
-            },
-            {
-                "name": "get_jumble_authoring_prompt",
-                "description": "Returns a canonical prompt and guidance for creating .jumble context files (project, workspace, conventions, docs) in any project.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {},
-                    "required": []
-                }
+                    "topic": {
+                        "type": "string",
+                        "description": "The skill topic (e.g., 'add-endpoint', 'debug-auth')"
+                    },
+                    "format": {
+                        "type": "string",
+                        "description": "Optional: 'markdown' (default) returns the skill text; 'messages' returns an MCP prompt messages array split by the roles declared in the skill frontmatter",
+                        "enum": ["markdown", "messages"]
+                    }
+                },
+                "required": ["project", "topic"]
+            }
+        }),
+        json!({
+            "name": "run_skill_script",
+            "description": "Runs one of a skill's helper scripts, as listed by get_skill, from the project directory. No shell is involved: the script's interpreter gets the arguments as given, with a timeout and capped output.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "topic": {
+                        "type": "string",
+                        "description": "The skill topic"
+                    },
+                    "script": {
+                        "type": "string",
+                        "description": "The script's file name in the skill's scripts/ directory (e.g., 'scaffold.py')"
+                    },
+                    "args": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Optional: arguments passed to the script"
+                    }
+                },
+                "required": ["project", "topic", "script"]
+            }
+        }),
+        json!({
+            "name": "get_conventions",
+            "description": "Returns project-specific coding conventions and gotchas. Conventions are architectural patterns and standards; gotchas are common mistakes to avoid.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "category": {
+                        "type": "string",
+                        "description": "Optional: 'conventions' or 'gotchas' to filter results",
+                        "enum": ["conventions", "gotchas"]
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_docs",
+            "description": "Returns a documentation index for a project, listing available docs with summaries. Optionally retrieves the path to a specific doc.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "topic": {
+                        "type": "string",
+                        "description": "Optional: specific doc topic to get the path for"
+                    },
+                    "cursor": {
+                        "type": "string",
+                        "description": "Optional: continue the index from the cursor given at the end of the previous page"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Optional: maximum number of docs to list (default 100)"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_workspace_overview",
+            "description": "Returns a high-level overview of the entire workspace: workspace info, all projects with descriptions, and their dependency relationships. Call this first to understand the workspace structure. On large workspaces, narrow it with include and project_filter.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "include": {
+                        "type": "array",
+                        "items": {"type": "string", "enum": OVERVIEW_SECTIONS},
+                        "description": "Optional: sections to return. Defaults to [\"projects\", \"dependencies\"]"
+                    },
+                    "project_filter": {
+                        "type": "string",
+                        "description": "Optional: only list projects with this language or tag (case-insensitive)"
+                    },
+                    "group": {
+                        "type": "string",
+                        "description": "Optional: only list projects in this workspace group"
+                    }
+                },
+                "required": []
+            }
+        }),
+        json!({
+            "name": "get_workspace_conventions",
+            "description": "Returns workspace-level conventions and gotchas that apply across all projects in the workspace.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "category": {
+                        "type": "string",
+                        "description": "Optional: 'conventions' or 'gotchas' to filter results",
+                        "enum": ["conventions", "gotchas"]
+                    }
+                },
+                "required": []
+            }
+        }),
+        json!({
+            "name": "get_context_health",
+            "description": "Scores how completely each project's .jumble context is filled out (description, commands, at least 3 concepts, conventions, docs index, no dangling file references) and returns a 0-100 score with a checklist of what is missing.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "Optional: score only this project (default: all projects plus a workspace average)"
+                    }
+                },
+                "required": []
+            }
+        }),
+        json!({
+            "name": "store_memory",
+            "description": "Stores a memory entry (key-value pair) for a project. AI agents can use this to persist learned information, preferences, or context over time.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "key": {
+                        "type": "string",
+                        "description": "The memory key (identifier)"
+                    },
+                    "value": {
+                        "type": "string",
+                        "description": "The memory value to store"
+                    },
+                    "source": {
+                        "type": "string",
+                        "description": "Optional: identifier for the agent/tool storing this memory"
+                    }
+                },
+                "required": ["project", "key", "value"]
+            }
+        }),
+        json!({
+            "name": "get_memory",
+            "description": "Retrieves a specific memory entry by key for a project.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "key": {
+                        "type": "string",
+                        "description": "The memory key to retrieve"
+                    }
+                },
+                "required": ["project", "key"]
+            }
+        }),
+        json!({
+            "name": "list_memories",
+            "description": "Lists all stored memories for a project, optionally filtered by a key pattern.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "Optional: filter keys by this substring (case-insensitive)"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "search_memories",
+            "description": "Searches memory keys and values for a query string (case-insensitive substring match, expanded with the workspace's `[synonyms]`).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "query": {
+                        "type": "string",
+                        "description": "Search query to match against keys and values"
+                    }
+                },
+                "required": ["project", "query"]
+            }
+        }),
+        json!({
+            "name": "delete_memory",
+            "description": "Deletes a specific memory entry by key for a project.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "key": {
+                        "type": "string",
+                        "description": "The memory key to delete"
+                    }
+                },
+                "required": ["project", "key"]
+            }
+        }),
+        json!({
+            "name": "clear_memories",
+            "description": "Clears all memories for a project, optionally filtered by pattern or age. Use with caution!",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "pattern": {
+                        "type": "string",
+                        "description": "Optional: only delete memories with keys matching this pattern (case-insensitive)"
+                    },
+                    "confirm": {
+                        "type": "boolean",
+                        "description": "Must be set to true to confirm deletion"
+                    }
+                },
+                "required": ["project", "confirm"]
+            }
+        }),
+        json!({
+            "name": "get_code_stats",
+            "description": "Returns lines of code per language and the largest directories of a project, to gauge its scale and where most of the code lives before planning changes.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "limit": {
+                        "type": "integer",
+                        "description": "Optional: number of directories to list (default 10)"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_repo_activity",
+            "description": "Returns live repository state from GitHub or GitLab for a project with a repository URL: default branch, open pull/merge request count and recent releases. Needs a GITHUB_TOKEN or GITLAB_TOKEN; results are cached for 15 minutes.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "refresh": {
+                        "type": "boolean",
+                        "description": "Optional: bypass the cache and ask the forge again"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_compliance_info",
+            "description": "Returns a project's own license and the team's licensing policy: which dependency licenses are allowed or denied, and any export restrictions. Check this before adding a dependency.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "check_dependency",
+            "description": "Checks a proposed external dependency against the workspace's allow/deny list and reports which projects already use it or an equivalent. Call this before adding a dependency.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project that would add the dependency"
+                    },
+                    "name": {
+                        "type": "string",
+                        "description": "The package, crate or module name, e.g. 'reqwest'"
+                    }
+                },
+                "required": ["project", "name"]
+            }
+        }),
+        json!({
+            "name": "get_code_owners",
+            "description": "Returns who owns a path in a project, from CODEOWNERS or, if the project opts in with infer_code_owners, from git blame of recent changes. Use it to suggest reviewers.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Optional: file or directory relative to the project root (default: the whole project)"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_data_assets",
+            "description": "Returns a data project's datasets (location, format, schema), notebook directories with their notebooks, pipeline/DAG locations and schema registries from the [data] section.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_hooks",
+            "description": "Lists the git hooks a project configures through pre-commit, husky or lefthook: which checks run on commit and push, and which of them rewrite files (formatters, --fix). Use it to anticipate local hook failures and auto-formatting.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_format_settings",
+            "description": "Returns the formatting rules a project's files follow: .editorconfig sections (indentation, line length, line endings) and rustfmt, prettier, black or ruff settings. Use it to match indentation and line width without running the formatter.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "language": {
+                        "type": "string",
+                        "description": "Only return settings that apply to this language (e.g. 'rust', 'typescript')"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_api_examples",
+            "description": "Returns worked examples of calling a project's API (curl commands or client snippets with auth headers and payloads), from [api.examples] in project.toml. Use it before writing code that calls another service.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "endpoint": {
+                        "type": "string",
+                        "description": "Only return examples for this endpoint or example name (e.g. 'POST /users'; case-insensitive substring)"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_messaging_info",
+            "description": "Returns the queues, topics and webhooks a project publishes to and consumes from [messaging], with the other projects on each side. Pass a topic to see its schema, producers and consumers across the workspace.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "topic": {
+                        "type": "string",
+                        "description": "A queue, topic or webhook name to describe across the workspace"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_dev_endpoints",
+            "description": "Returns the workspace's registry of local development ports and URLs ([dev_ports] in workspace.toml), with any ports claimed by two services. Use it when wiring up local integration tests or adding a service, instead of picking a port that may collide.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {}
+            }
+        }),
+        json!({
+            "name": "get_session_summary",
+            "description": "Summarizes what context this session has already fetched and what it hasn't yet for the projects it used, e.g. gotchas or concepts not looked at. Useful in long sessions and when handing work to another agent.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "Optional: only report on this project, whether or not the session used it"
+                    }
+                },
+                "required": []
+            }
+        }),
+        json!({
+            "name": "get_context_diff",
+            "description": "Summarizes how .jumble files changed between two git refs: concepts, conventions, commands and other config keys added, removed or changed, and skills or docs edited. Use it when reviewing a pull request that touches project context.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "from": {
+                        "type": "string",
+                        "description": "The base git ref, e.g. main"
+                    },
+                    "to": {
+                        "type": "string",
+                        "description": "Optional: the ref to compare with (defaults to HEAD)"
+                    }
+                },
+                "required": ["from"]
+            }
+        }),
+        json!({
+            "name": "get_review_checklist",
+            "description": "Builds a review checklist for a change touching the given files: the project's and workspace's conventions, gotchas that mention those files or the concepts they belong to, the affected concepts, and the CI checks that must pass. Meant for review agents and for self-review before opening a pull request.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "files": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Changed files, relative to the project directory"
+                    }
+                },
+                "required": ["project", "files"]
+            }
+        }),
+        json!({
+            "name": "get_tests_for",
+            "description": "Returns the test files that cover a set of changed files, from the project's [test_map] or by matching test file names to sources, and a command that runs just those tests. Use it to run the relevant tests after a change instead of the whole suite.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "files": {
+                        "type": "array",
+                        "items": {"type": "string"},
+                        "description": "Changed files, relative to the project directory"
+                    }
+                },
+                "required": ["project", "files"]
+            }
+        }),
+        json!({
+            "name": "get_known_issues",
+            "description": "Returns the project's known flaky or failing tests with their tracking tickets and workarounds. Check this before investigating a test failure: it may be a known issue.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "test": {
+                        "type": "string",
+                        "description": "Optional: a failing test's name or file, to check whether it is known"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_performance_info",
+            "description": "Returns how to measure a project's performance: benchmark commands, the budgets changes must stay within, profiling how-tos and where baselines live. Use it before and after a performance-sensitive change.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_workflow",
+            "description": "Returns the team's contribution workflow for a project: branching model, base branch, pull request size norms, review requirements and merge strategy, from [workflow] in project.toml or workspace.toml. Check it before creating a branch or opening a pull request.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "describe_tool",
+            "description": "Explains one jumble tool in depth: what it returns, every parameter, example invocations and the tools usually called next. Use it before calling an unfamiliar tool instead of guessing arguments.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "description": "The tool name, as listed by tools/list"
+                    }
+                },
+                "required": ["name"]
+            }
+        }),
+        json!({
+            "name": "prepare_for_task",
+            "description": "Given a free-text task description, returns what to read and do before starting: the matching concepts and their files, gotchas, conventions, skills, docs and commands, best matches first. A single call to make before touching unfamiliar code, instead of querying each of those separately.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "description": {
+                        "type": "string",
+                        "description": "The task, in a sentence or a few keywords (e.g. 'add rate limiting to the login endpoint')"
+                    }
+                },
+                "required": ["project", "description"]
+            }
+        }),
+        json!({
+            "name": "verify_context",
+            "description": "Checks that a project's context is still current: concept files, entry points, docs and the OpenAPI spec must exist, and each command's program must resolve in the project or on PATH. Time-boxed and sampled, so it is cheap to run at the start of a session; treat anything it reports as out of date.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "Name of the project"
+                    }
+                },
+                "required": ["project"]
+            }
+        }),
+        json!({
+            "name": "get_ticket_link",
+            "description": "Formats a ticket or issue ID into its canonical reference and URL using the project's [tracker] section, along with the team's branch and commit message formats for referencing it.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "id": {
+                        "type": "string",
+                        "description": "The ticket ID, e.g. 'PAY-123', '123' or '#42'"
+                    }
+                },
+                "required": ["project", "id"]
+            }
+        }),
+        json!({
+            "name": "record_command_result",
+            "description": format!("Records the outcome of running a project command (e.g. tests) in {}/state.json, so a later get_last_run can tell whether it was already failing before a change.", config_dir),
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "command_type": {
+                        "type": "string",
+                        "description": "The command that was run, e.g. 'test', 'build' or 'lint'"
+                    },
+                    "exit_code": {
+                        "type": "integer",
+                        "description": "The command's exit code; 0 means it passed"
+                    },
+                    "summary": {
+                        "type": "string",
+                        "description": "Optional: short summary, e.g. '3 failed: test_login, ...'"
+                    }
+                },
+                "required": ["project", "command_type", "exit_code"]
+            }
+        }),
+        json!({
+            "name": "get_last_run",
+            "description": "Returns the most recently recorded result of a project command, and the run before it, as reported through record_command_result. Without command_type, lists the latest result of every recorded command.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {
+                        "type": "string",
+                        "description": "The project name"
+                    },
+                    "command_type": {
+                        "type": "string",
+                        "description": "Optional: the command, e.g. 'test'"
+                    }
+                },
+                "required": ["project"]
             },
-            {
-                "name": "init_project",
-                "description": "Initializes a new jumble project by creating the necessary directories and configuration files. Creates .jumble, .ai, docs directories and initializes project.toml, constitution.md, AGENTS.md, and .gitignore.",
-                "inputSchema": {
-                    "type": "object",
-                    "properties": {
-                        "directory": {
-                            "type": "string",
-                            "description": "The directory path to initialize the project in. Supports both absolute and relative paths."
+            "outputSchema": {
+                "type": "object",
+                "properties": {
+                    "project": {"type": "string"},
+                    "runs": {
+                        "type": "array",
+                        "description": "The latest run of each recorded command; with command_type, its latest run and the one before it",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "command_type": {"type": "string"},
+                                "exit_code": {"type": "integer"},
+                                "passed": {"type": "boolean"},
+                                "summary": {"type": ["string", "null"]},
+                                "timestamp": {"type": "string"}
+                            },
+                            "required": ["command_type", "exit_code", "passed", "timestamp"]
                         }
-                    },
-                    "required": ["directory"]
-                }
+                    }
+                },
+                "required": ["project", "runs"]
             }
-        ]
+        }),
+        json!({
+            "name": "reload_workspace",
+            "description": "Reloads workspace and project metadata from disk. Use this after editing .jumble files to pick up changes without restarting the server.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
+        json!({
+            "name": "get_jumble_authoring_prompt",
+            "description": "Returns a canonical prompt and guidance for creating .jumble context files (project, workspace, conventions, docs) in any project.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {},
+                "required": []
+            }
+        }),
+        json!({
+            "name": "init_project",
+            "description": format!("Initializes a new jumble project by creating the necessary directories and configuration files. Creates {}, .ai, docs directories and initializes project.toml, constitution.md, AGENTS.md, and .gitignore.", config_dir),
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "directory": {
+                        "type": "string",
+                        "description": "The directory path to initialize the project in. Supports both absolute and relative paths."
+                    }
+                },
+                "required": ["directory"]
+            }
+        }),
+    ];
+    // Without the `exec` feature, skill scripts can't be run.
+    tools.retain(|tool| cfg!(feature = "exec") || tool["name"] != "run_skill_script");
+    for tool in &mut tools {
+        tool["annotations"] = tool_annotations(tool["name"].as_str().unwrap_or_default());
+        if PATH_TOOLS.iter().any(|name| tool["name"] == *name) {
+            tool["inputSchema"]["properties"]["path_style"] = json!({
+                "type": "string",
+                "enum": ["relative", "absolute", "uri"],
+                "description": "How to write file paths: relative to the project (or workspace root), absolute, or as file:// URIs. Defaults to `paths` under [output] in workspace.toml."
+            });
+        }
+    }
+    json!({ "tools": tools })
+}
+
+/// Tools whose output includes file paths, which take `path_style`.
+const PATH_TOOLS: &[&str] = &[
+    "list_projects",
+    "get_project_info",
+    "get_architecture",
+    "get_related_files",
+    "get_skill",
+    "get_docs",
+    "get_workspace_overview",
+    "get_data_assets",
+    "get_api_examples",
+    "get_code_owners",
+    "init_project",
+    "prepare_for_task",
+];
+
+/// Tools that change state, as `(name, destructive, idempotent)`: memory,
+/// recorded runs, `.jumble` files and whatever skill scripts do. Every other
+/// built-in tool only reads.
+const WRITE_TOOLS: &[(&str, bool, bool)] = &[
+    ("store_memory", true, true),
+    ("delete_memory", true, true),
+    ("clear_memories", true, true),
+    ("record_command_result", false, true),
+    ("init_project", false, true),
+    (AUTHOR_PROJECT_CONFIG, true, false),
+    ("run_skill_script", true, false),
+];
+
+/// Tools that call services outside the workspace.
+const OPEN_WORLD_TOOLS: &[&str] = &["get_repo_activity"];
+
+/// MCP tool `annotations` for a built-in tool, so clients can auto-approve
+/// the read-only ones.
+fn tool_annotations(name: &str) -> Value {
+    let open_world = OPEN_WORLD_TOOLS.contains(&name);
+    match WRITE_TOOLS.iter().find(|(tool, _, _)| *tool == name) {
+        Some((_, destructive, idempotent)) => json!({
+            "readOnlyHint": false,
+            "destructiveHint": destructive,
+            "idempotentHint": idempotent,
+            "openWorldHint": open_world
+        }),
+        None => json!({
+            "readOnlyHint": true,
+            "idempotentHint": true,
+            "openWorldHint": open_world
+        }),
+    }
+}
+
+/// Name of the sampling-backed authoring tool, only listed for clients that
+/// advertise the `sampling` capability.
+pub const AUTHOR_PROJECT_CONFIG: &str = "author_project_config";
+
+/// Tool definition for `author_project_config`.
+pub fn author_project_config_tool() -> Value {
+    json!({
+        "name": AUTHOR_PROJECT_CONFIG,
+        "description": format!("Drafts {} and conventions.toml for a directory by asking your model (via sampling) to read its README and manifest files. Returns the draft for review; call again with confirm=true to write the files.", crate::paths::config_file("project.toml")),
+        "inputSchema": {
+            "type": "object",
+            "properties": {
+                "directory": {
+                    "type": "string",
+                    "description": "Project directory, absolute or relative to the workspace root"
+                },
+                "confirm": {
+                    "type": "boolean",
+                    "description": "Write the previously drafted files (after the user approved them)"
+                },
+                "overwrite": {
+                    "type": "boolean",
+                    "description": "Replace existing .jumble files when confirming"
+                }
+            },
+            "required": ["directory"]
+        },
+        "annotations": tool_annotations(AUTHOR_PROJECT_CONFIG)
     })
 }
 
+/// Returns true if the tool's input schema lists `argument` as required.
+pub fn requires_argument(tool: &str, argument: &str) -> bool {
+    tools_list()["tools"]
+        .as_array()
+        .and_then(|tools| tools.iter().find(|t| t["name"] == tool))
+        .and_then(|t| t["inputSchema"]["required"].as_array())
+        .map(|required| required.iter().any(|r| r == argument))
+        .unwrap_or(false)
+}
+
+/// Returns true if `name` is one of the tools this server can dispatch.
+pub fn is_known_tool(name: &str) -> bool {
+    name == AUTHOR_PROJECT_CONFIG
+        || tools_list()["tools"]
+            .as_array()
+            .map(|tools| tools.iter().any(|t| t["name"] == name))
+            .unwrap_or(false)
+}
+
 pub fn get_jumble_authoring_prompt() -> Result<String, String> {
     let prompt = r#"# Jumble authoring prompt
 
@@ -412,37 +1194,338 @@ For monorepos or multi-project workspaces, you can ask the AI to:
 Start with the most important projects. Use `related_projects` to show how they connect.
 "#;
 
-    Ok(prompt.to_string())
+    Ok(prompt.replace(".jumble/", &crate::paths::config_file("")))
 }
 
 // ============================================================================
 // Tool Implementations
 // ============================================================================
 
-pub fn list_projects(projects: &HashMap<String, ProjectData>) -> Result<String, String> {
-    if projects.is_empty() {
-        return Ok(
-            "No projects found. Make sure .jumble/project.toml files exist in your workspace."
-                .to_string(),
+/// `list_projects` in one call, for callers that only want its Markdown.
+#[cfg(test)]
+pub fn list_projects(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    list_projects_structured(projects, workspace, args).map(|(text, _)| text)
+}
+
+/// `list_projects` as Markdown, with its `structuredContent`: each entry of
+/// the page is built once as both.
+pub fn list_projects_structured(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<(String, Value), String> {
+    let has_external = workspace.as_ref().is_some_and(|ws| !ws.external.is_empty());
+    if projects.is_empty() && !has_external {
+        let text = format!(
+            "No projects found. Make sure {} files exist in your workspace.",
+            crate::paths::config_file("project.toml")
         );
+        return Ok((
+            text,
+            json!({"projects": [], "total": 0, "next_cursor": null}),
+        ));
     }
-
-    let mut output = String::new();
-    for (name, (path, config, _skills, _conventions, _docs, _memory)) in projects {
-        let lang = config.project.language.as_deref().unwrap_or("unknown");
-        output.push_str(&format!(
+    let group = group_filter(workspace, args)?;
+
+    let mut entries: Vec<(String, Value)> = Vec::new();
+    for name in ordered_project_names(projects, workspace)
+        .into_iter()
+        .filter(|name| group.is_none_or(|group| group.projects.contains(name)))
+    {
+        let (path, config, _skills, _conventions, _docs, _memory) = &projects[name];
+        let text = format!(
             "- **{}** ({}): {}\n  Path: {}\n",
             name,
-            lang,
+            project_label(workspace, name, config),
             config.project.description,
             path.display()
-        ));
+        );
+        let value = json!({
+            "name": name,
+            "description": config.project.description,
+            "language": config.project.language,
+            "pinned": is_pinned_project(workspace, name),
+            "path": path
+        });
+        entries.push((text, value));
+    }
+    for (name, external) in external_projects(projects, workspace, group) {
+        let mut text = format!("- **{}** (external): {}\n", name, external.description);
+        if let Some(repository) = &external.repository {
+            text.push_str(&format!("  Repository: {}\n", repository));
+        }
+        if let Some(docs) = &external.docs {
+            text.push_str(&format!("  Docs: {}\n", docs));
+        }
+        let value = json!({
+            "name": name,
+            "description": external.description,
+            "external": true,
+            "repository": external.repository,
+            "docs": external.docs
+        });
+        entries.push((text, value));
     }
-    Ok(output)
-}
 
-pub fn get_project_info(
+    let page = paginate(entries, args)?;
+    let mut output = String::new();
+    let mut listed = Vec::new();
+    for (text, value) in &page.items {
+        output.push_str(text);
+        listed.push(value.clone());
+    }
+    output.push_str(&page_footer(&page, "projects", "list_projects"));
+    let structured = json!({
+        "projects": listed,
+        "total": page.total,
+        "next_cursor": page.next_cursor
+    });
+    Ok((output, structured))
+}
+
+/// Items per page for `tools/list` and the list-style tools that take a
+/// `cursor`, unless the tool is given a `limit`.
+pub const PAGE_SIZE: usize = 100;
+
+/// One page of a longer list.
+#[derive(Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Index of the first item in the full list.
+    pub start: usize,
+    pub total: usize,
+    /// Where the next page starts, if there is one.
+    pub next_cursor: Option<String>,
+}
+
+/// The page of `items` starting at `cursor`, at most `limit` long.
+///
+/// Cursors are opaque to clients; here they are the index of the page's
+/// first item, so one taken from a list that has since shrunk past it is
+/// rejected rather than silently returning nothing.
+pub fn page_of<T>(items: Vec<T>, cursor: Option<&str>, limit: usize) -> Result<Page<T>, String> {
+    let total = items.len();
+    let start = match cursor {
+        None => 0,
+        Some(cursor) => cursor
+            .parse::<usize>()
+            .ok()
+            .filter(|start| *start <= total)
+            .ok_or_else(|| format!("Invalid cursor '{}'", cursor))?,
+    };
+    let end = start.saturating_add(limit.max(1)).min(total);
+    Ok(Page {
+        items: items.into_iter().skip(start).take(end - start).collect(),
+        start,
+        total,
+        next_cursor: (end < total).then(|| end.to_string()),
+    })
+}
+
+/// `page_of` driven by a tool's `cursor` and `limit` arguments.
+fn paginate<T>(items: Vec<T>, args: &Value) -> Result<Page<T>, String> {
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map_or(PAGE_SIZE, |n| n as usize);
+    page_of(items, args.get("cursor").and_then(|v| v.as_str()), limit)
+}
+
+/// A note saying which part of the list is shown and how to get the next
+/// page, or nothing when the whole list fits.
+fn page_footer<T>(page: &Page<T>, noun: &str, tool: &str) -> String {
+    if page.start == 0 && page.next_cursor.is_none() {
+        return String::new();
+    }
+    let mut footer = format!(
+        "\nShowing {}-{} of {} {}.",
+        page.start + 1,
+        page.start + page.items.len(),
+        page.total,
+        noun
+    );
+    if let Some(cursor) = &page.next_cursor {
+        footer.push_str(&format!(
+            " Call {} again with cursor \"{}\" for the next page.",
+            tool, cursor
+        ));
+    }
+    footer.push('\n');
+    footer
+}
+
+/// `[external]` projects in `group` (or all of them), sorted by name.
+/// A name that is also a local project is left to the local one.
+fn external_projects<'a>(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &'a Option<WorkspaceConfig>,
+    group: Option<&ProjectGroup>,
+) -> Vec<(&'a String, &'a ExternalProject)> {
+    let mut external: Vec<(&String, &ExternalProject)> = workspace
+        .as_ref()
+        .map(|ws| ws.external.iter().collect())
+        .unwrap_or_default();
+    external.retain(|(name, _)| {
+        !projects.contains_key(*name) && group.is_none_or(|group| group.projects.contains(name))
+    });
+    external.sort_by_key(|(name, _)| name.as_str());
+    external
+}
+
+/// Whether `[workspace]` lists `name` under `pinned_projects`.
+fn is_pinned_project(workspace: &Option<WorkspaceConfig>, name: &str) -> bool {
+    workspace
+        .as_ref()
+        .is_some_and(|ws| ws.workspace.pinned_projects.iter().any(|p| p == name))
+}
+
+/// Project names with pinned projects first, in the order they are pinned,
+/// then the rest alphabetically.
+fn ordered_project_names<'a>(
+    projects: &'a HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+) -> Vec<&'a String> {
+    let pinned: &[String] = workspace
+        .as_ref()
+        .map_or(&[], |ws| ws.workspace.pinned_projects.as_slice());
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort_by_key(|name| {
+        let rank = pinned
+            .iter()
+            .position(|p| p == *name)
+            .unwrap_or(pinned.len());
+        (rank, name.as_str())
+    });
+    names
+}
+
+/// `rust` or `rust, pinned`, for project list entries.
+/// The group named by the `group` argument, if any.
+fn group_filter<'a>(
+    workspace: &'a Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<Option<&'a ProjectGroup>, String> {
+    let Some(name) = args.get("group").and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    let groups = workspace.as_ref().map(|ws| &ws.groups);
+    match groups.and_then(|groups| groups.get(name)) {
+        Some(group) => Ok(Some(group)),
+        None => {
+            let mut known: Vec<&String> = groups.map(|g| g.keys().collect()).unwrap_or_default();
+            known.sort();
+            if known.is_empty() {
+                Err(format!(
+                    "Unknown group '{}'. No [groups] are defined in workspace.toml.",
+                    name
+                ))
+            } else {
+                Err(format!(
+                    "Unknown group '{}'. Known groups: {}",
+                    name,
+                    known
+                        .iter()
+                        .map(|g| g.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+            }
+        }
+    }
+}
+
+pub fn list_groups(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+) -> Result<String, String> {
+    let groups = workspace.as_ref().map(|ws| &ws.groups);
+    let Some(groups) = groups.filter(|groups| !groups.is_empty()) else {
+        return Ok(format!(
+            "No groups defined. Add [groups.<name>] with a `projects` list to {}.",
+            crate::paths::config_file("workspace.toml")
+        ));
+    };
+    let mut names: Vec<&String> = groups.keys().collect();
+    names.sort();
+
+    let mut output = "# Project Groups\n".to_string();
+    for name in names {
+        let group = &groups[name];
+        output.push_str(&format!("\n## {}\n\n", name));
+        if let Some(description) = &group.description {
+            output.push_str(&format!("{}\n\n", description));
+        }
+        let members: Vec<String> = group
+            .projects
+            .iter()
+            .map(|project| match projects.get(project) {
+                Some((_, config, _, _, _, _)) => {
+                    format!("- **{}**: {}", project, config.project.description)
+                }
+                None => match workspace.as_ref().and_then(|ws| ws.external.get(project)) {
+                    Some(external) => {
+                        format!("- **{}** (external): {}", project, external.description)
+                    }
+                    None => format!("- **{}** (not found in this workspace)", project),
+                },
+            })
+            .collect();
+        if members.is_empty() {
+            output.push_str("No projects.\n");
+        } else {
+            output.push_str(&members.join("\n"));
+            output.push('\n');
+        }
+    }
+
+    let mut ungrouped: Vec<&String> = projects
+        .keys()
+        .filter(|name| !groups.values().any(|group| group.projects.contains(name)))
+        .collect();
+    ungrouped.sort();
+    if !ungrouped.is_empty() {
+        output.push_str(&format!(
+            "\n**Not in any group:** {}\n",
+            ungrouped
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    output.push_str(
+        "\n*Pass `group` to list_projects or get_workspace_overview to see one group.*\n",
+    );
+    Ok(output)
+}
+
+fn project_label(
+    workspace: &Option<WorkspaceConfig>,
+    name: &str,
+    config: &ProjectConfig,
+) -> String {
+    let lang = config.project.language.as_deref().unwrap_or("unknown");
+    if is_pinned_project(workspace, name) {
+        format!("{}, pinned", lang)
+    } else {
+        lang.to_string()
+    }
+}
+
+/// Concepts with pinned ones first, each group sorted by name.
+fn ordered_concepts(config: &ProjectConfig) -> Vec<(&String, &Concept)> {
+    let mut concepts: Vec<(&String, &Concept)> = config.concepts.iter().collect();
+    concepts.sort_by_key(|(name, concept)| (!concept.pinned, name.as_str()));
+    concepts
+}
+
+pub fn get_project_info(
     projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
     args: &Value,
 ) -> Result<String, String> {
     let project_name = args
@@ -450,19 +1533,49 @@ pub fn get_project_info(
         .and_then(|v| v.as_str())
         .ok_or("Missing 'project' argument")?;
 
+    let external = workspace
+        .as_ref()
+        .and_then(|ws| ws.external.get(project_name))
+        .filter(|_| !projects.contains_key(project_name));
+    if let Some(external) = external {
+        return Ok(format_external_project(project_name, external));
+    }
     let (path, config, _skills, _conventions, _docs, _memory) = projects
         .get(project_name)
         .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+    let no_links = HashMap::new();
+    let linked = workspace
+        .as_ref()
+        .map_or(&no_links, |ws| &ws.linked_projects);
+
+    let as_json = args
+        .get("as_json")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let field = match args.get("field") {
+        None | Some(Value::Null) => None,
+        Some(Value::String(f)) => Some(f.as_str()),
+        Some(Value::Array(fields)) => {
+            let fields = fields
+                .iter()
+                .map(|f| f.as_str().ok_or("'field' array must contain only strings"))
+                .collect::<Result<Vec<&str>, _>>()?;
+            return project_info_fields(path, config, linked, &fields, as_json);
+        }
+        Some(_) => return Err("'field' must be a string or an array of strings".to_string()),
+    };
 
-    let field = args.get("field").and_then(|v| v.as_str());
+    if as_json {
+        let value = match field {
+            Some(f) => project_info_json(config, f)?,
+            None => serde_json::to_value(config).map_err(|e| e.to_string())?,
+        };
+        return serde_json::to_string_pretty(&value).map_err(|e| e.to_string());
+    }
 
     match field {
-        Some("commands") => Ok(format_commands(&config.commands)),
-        Some("entry_points") => Ok(format_entry_points(&config.entry_points)),
-        Some("dependencies") => Ok(format_dependencies(&config.dependencies)),
-        Some("api") => Ok(format_api(&config.api)),
-        Some("related_projects") => Ok(format_related_projects(&config.related_projects)),
-        Some(f) => Err(format!("Unknown field: {}", f)),
+        Some(f) => project_info_field(path, config, linked, f),
         None => {
             let mut output = format!("# {}\n\n", config.project.name);
             output.push_str(&format!(
@@ -478,6 +1591,9 @@ pub fn get_project_info(
             if let Some(repo) = &config.project.repository {
                 output.push_str(&format!("**Repository:** {}\n", repo));
             }
+            if !config.project.tags.is_empty() {
+                output.push_str(&format!("**Tags:** {}\n", config.project.tags.join(", ")));
+            }
             output.push_str(&format!("**Path:** {}\n", path.display()));
 
             if !config.entry_points.is_empty() {
@@ -487,43 +1603,377 @@ pub fn get_project_info(
 
             if !config.concepts.is_empty() {
                 output.push_str("\n## Concepts\n");
-                for (name, concept) in &config.concepts {
-                    output.push_str(&format!("- **{}**: {}\n", name, concept.summary));
+                for (name, concept) in ordered_concepts(config) {
+                    let pin = if concept.pinned { " (pinned)" } else { "" };
+                    output.push_str(&format!("- **{}**{}: {}\n", name, pin, concept.summary));
                 }
             }
 
+            if let Some(note) = local_overrides_note(&config.local_overrides, "") {
+                output.push_str(&format!("\n{}\n", note));
+            }
+
             Ok(output)
         }
     }
 }
 
+/// A line naming the keys under `prefix` that `.jumble/local.toml` set, so
+/// agents can tell machine-specific values from committed ones.
+fn local_overrides_note(overridden: &[String], prefix: &str) -> Option<String> {
+    let keys: Vec<String> = overridden
+        .iter()
+        .filter(|key| key.starts_with(prefix))
+        .map(|key| format!("`{}`", key))
+        .collect();
+    (!keys.is_empty()).then(|| {
+        format!(
+            "*Set locally in `{}` (not committed): {}*",
+            crate::paths::config_file(local::LOCAL_FILE),
+            keys.join(", ")
+        )
+    })
+}
+
+/// Fields `get_project_info` can return on their own.
+/// What `get_project_info` knows about a project that isn't checked out.
+fn format_external_project(name: &str, external: &ExternalProject) -> String {
+    let mut output = format!("# {}\n\n", name);
+    if !external.description.is_empty() {
+        output.push_str(&format!("{}\n\n", external.description));
+    }
+    output.push_str(
+        "External project: declared under [external] in workspace.toml and not checked out \
+         here, so there are no commands, conventions or files to look up.\n",
+    );
+    if let Some(repository) = &external.repository {
+        output.push_str(&format!("\n**Repository:** {}\n", repository));
+    }
+    if let Some(docs) = &external.docs {
+        output.push_str(&format!("**Docs:** {}\n", docs));
+    }
+    output
+}
+
+const PROJECT_INFO_FIELDS: [&str; 5] = [
+    "commands",
+    "entry_points",
+    "dependencies",
+    "api",
+    "related_projects",
+];
+
+fn project_info_field(
+    path: &std::path::Path,
+    config: &ProjectConfig,
+    linked: &HashMap<String, LinkedProject>,
+    field: &str,
+) -> Result<String, String> {
+    match field {
+        "commands" => Ok(ProjectCommands::collect(path, config, None).markdown(config)),
+        "entry_points" => Ok(format_entry_points(&config.entry_points)),
+        "dependencies" => Ok(format_dependencies(&config.dependencies)),
+        "api" => Ok(format_api(&config.api)),
+        "related_projects" => Ok(format_related_projects(&config.related_projects, linked)),
+        f => Err(format!("Unknown field: {}", f)),
+    }
+}
+
+/// The raw config subtree behind `field`, without inferred commands.
+fn project_info_json(config: &ProjectConfig, field: &str) -> Result<Value, String> {
+    let value = match field {
+        "commands" => serde_json::to_value(&config.commands),
+        "entry_points" => serde_json::to_value(&config.entry_points),
+        "dependencies" => serde_json::to_value(&config.dependencies),
+        "api" => serde_json::to_value(&config.api),
+        "related_projects" => serde_json::to_value(&config.related_projects),
+        f => return Err(format!("Unknown field: {}", f)),
+    };
+    value.map_err(|e| e.to_string())
+}
+
+/// Several fields in one response: a Markdown section per field, or a JSON
+/// object keyed by field name.
+fn project_info_fields(
+    path: &std::path::Path,
+    config: &ProjectConfig,
+    linked: &HashMap<String, LinkedProject>,
+    fields: &[&str],
+    as_json: bool,
+) -> Result<String, String> {
+    if fields.is_empty() {
+        return Err("'field' array is empty".to_string());
+    }
+
+    if as_json {
+        let mut object = serde_json::Map::new();
+        for field in fields {
+            object.insert(field.to_string(), project_info_json(config, field)?);
+        }
+        return serde_json::to_string_pretty(&object).map_err(|e| e.to_string());
+    }
+
+    let mut output = format!("# {}\n", config.project.name);
+    for field in fields {
+        output.push_str(&format!("\n## {}\n", field));
+        output.push_str(project_info_field(path, config, linked, field)?.trim_end());
+        output.push('\n');
+    }
+    Ok(output)
+}
+
+/// `get_commands` in one call, for callers that only want its Markdown.
+#[cfg(test)]
 pub fn get_commands(
     projects: &HashMap<String, ProjectData>,
     args: &Value,
 ) -> Result<String, String> {
+    get_commands_structured(projects, args).map(|(text, _)| text)
+}
+
+/// `get_commands` as Markdown, with its `structuredContent`: both are built
+/// from the same [`ProjectCommands`].
+pub fn get_commands_structured(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<(String, Value), String> {
     let project_name = args
         .get("project")
         .and_then(|v| v.as_str())
         .ok_or("Missing 'project' argument")?;
 
-    let (_, config, _, _, _, _) = projects
+    let (path, config, _, _, _, _) = projects
         .get(project_name)
         .ok_or_else(|| format!("Project '{}' not found", project_name))?;
 
     let command_type = args.get("command_type").and_then(|v| v.as_str());
 
+    let profile = match args.get("profile").and_then(|v| v.as_str()) {
+        Some(name) => {
+            let profile = config.profiles.get(name).ok_or_else(|| {
+                let mut names: Vec<&str> = config.profiles.keys().map(|n| n.as_str()).collect();
+                names.sort();
+                let available = if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                };
+                format!(
+                    "Profile '{}' not found for project '{}'. Available profiles: {}",
+                    name, project_name, available
+                )
+            })?;
+            Some((name, profile))
+        }
+        None => None,
+    };
+
+    let commands = ProjectCommands::collect(path, config, profile);
+    let structured = |entries: Vec<Value>| {
+        json!({
+            "project": project_name,
+            "profile": profile.map(|(name, _)| name),
+            "commands": entries
+        })
+    };
     match command_type {
-        Some(cmd_type) => config
-            .commands
-            .get(cmd_type)
-            .map(|cmd| format!("{}: {}", cmd_type, cmd))
-            .ok_or_else(|| {
+        Some(cmd_type) => {
+            let entry = commands
+                .entries()
+                .into_iter()
+                .find(|e| e["name"] == cmd_type);
+            let text = commands.describe(cmd_type).ok_or_else(|| {
                 format!(
                     "Command '{}' not found for project '{}'",
                     cmd_type, project_name
                 )
-            }),
-        None => Ok(format_commands(&config.commands)),
+            })?;
+            Ok((text, structured(entry.into_iter().collect())))
+        }
+        None => {
+            let mut output = commands.markdown(config);
+            match profile {
+                Some((name, profile)) => {
+                    let mut replaced: Vec<&str> =
+                        profile.commands.keys().map(|k| k.as_str()).collect();
+                    replaced.sort();
+                    output = format!(
+                        "**Profile:** {} (sets {})\n\n{}",
+                        name,
+                        replaced.join(", "),
+                        output
+                    );
+                }
+                None if !config.profiles.is_empty() => {
+                    let mut names: Vec<&str> = config.profiles.keys().map(|n| n.as_str()).collect();
+                    names.sort();
+                    output.push_str(&format!(
+                        "\n*Profiles: {} (pass `profile` for their commands)*\n",
+                        names.join(", ")
+                    ));
+                }
+                None => {}
+            }
+            Ok((output, structured(commands.entries())))
+        }
+    }
+}
+
+/// Where a configured command was set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ConfiguredIn {
+    ProjectFile,
+    LocalFile,
+    Profile,
+}
+
+/// Every command `get_commands` reports for a project, from each source in
+/// the order it looks them up.
+struct ProjectCommands<'a> {
+    /// `[commands]` with the profile's over them, sorted by name.
+    configured: Vec<(&'a str, &'a str, ConfiguredIn)>,
+    profile: Option<&'a str>,
+    /// Task runner commands `configured` doesn't name.
+    discovered: Vec<commands::DiscoveredCommands>,
+    /// Defaults for the standard kinds nothing else covers.
+    inferred: Option<commands::InferredCommands>,
+}
+
+impl<'a> ProjectCommands<'a> {
+    fn collect(
+        path: &std::path::Path,
+        config: &'a ProjectConfig,
+        profile: Option<(&'a str, &'a CommandProfile)>,
+    ) -> Self {
+        let profile_sets =
+            |name: &str| profile.is_some_and(|(_, profile)| profile.commands.contains_key(name));
+        let mut configured: Vec<(&str, &str, ConfiguredIn)> = config
+            .commands
+            .iter()
+            .filter(|(name, _)| !profile_sets(name))
+            .map(|(name, command)| {
+                let key = format!("commands.{}", name);
+                let set_in = if config.local_overrides.contains(&key) {
+                    ConfiguredIn::LocalFile
+                } else {
+                    ConfiguredIn::ProjectFile
+                };
+                (name.as_str(), command.as_str(), set_in)
+            })
+            .chain(profile.into_iter().flat_map(|(_, profile)| {
+                profile
+                    .commands
+                    .iter()
+                    .map(|(name, command)| (name.as_str(), command.as_str(), ConfiguredIn::Profile))
+            }))
+            .collect();
+        configured.sort_by_key(|(name, _, _)| *name);
+
+        let mut discovered = commands::discover(path, config);
+        for found in &mut discovered {
+            found.commands.retain(|(name, _)| !profile_sets(name));
+        }
+        discovered.retain(|found| !found.commands.is_empty());
+        let inferred = commands::infer(path, config).and_then(|mut inferred| {
+            inferred.commands.retain(|(kind, _)| {
+                !profile_sets(kind)
+                    && !discovered
+                        .iter()
+                        .any(|found| found.commands.iter().any(|(name, _)| name == kind))
+            });
+            (!inferred.commands.is_empty()).then_some(inferred)
+        });
+        Self {
+            configured,
+            profile: profile.map(|(name, _)| name),
+            discovered,
+            inferred,
+        }
+    }
+
+    /// `kind: command`, noting where it came from unless that is
+    /// project.toml.
+    fn describe(&self, kind: &str) -> Option<String> {
+        if let Some((_, command, set_in)) = self.configured.iter().find(|(n, _, _)| *n == kind) {
+            return Some(match set_in {
+                ConfiguredIn::ProjectFile => format!("{}: {}", kind, command),
+                ConfiguredIn::LocalFile => format!(
+                    "{}: {} (from {})",
+                    kind,
+                    command,
+                    crate::paths::config_file(local::LOCAL_FILE)
+                ),
+                ConfiguredIn::Profile => format!(
+                    "{}: {} (profile {})",
+                    kind,
+                    command,
+                    self.profile.unwrap_or_default()
+                ),
+            });
+        }
+        for found in &self.discovered {
+            if let Some((_, command)) = found.commands.iter().find(|(name, _)| name == kind) {
+                return Some(format!("{}: {} (from {})", kind, command, found.source));
+            }
+        }
+        let inferred = self.inferred.as_ref()?;
+        let (_, command) = inferred.commands.iter().find(|(name, _)| name == kind)?;
+        Some(format!(
+            "{}: {} (inferred from {})",
+            kind, command, inferred.source
+        ))
+    }
+
+    /// The commands as Markdown: configured ones, then labelled task runner
+    /// and inferred ones.
+    fn markdown(&self, config: &ProjectConfig) -> String {
+        let mut sections = Vec::new();
+        if !self.configured.is_empty() || (self.discovered.is_empty() && self.inferred.is_none()) {
+            let configured: HashMap<String, String> = self
+                .configured
+                .iter()
+                .map(|(name, command, _)| (name.to_string(), command.to_string()))
+                .collect();
+            sections.push(format_commands(&configured));
+        }
+        if !self.discovered.is_empty() {
+            sections.push(format_discovered_commands(&self.discovered));
+        }
+        if let Some(inferred) = &self.inferred {
+            sections.push(format_inferred_commands(inferred));
+        }
+        if let Some(note) = local_overrides_note(&config.local_overrides, "commands.") {
+            sections.push(format!("{}\n", note));
+        }
+        sections.join("\n")
+    }
+
+    /// Each command as `{name, command, source}`. `source` is the file the
+    /// command comes from, `profile <name>`, or `inferred from <manifest>`.
+    fn entries(&self) -> Vec<Value> {
+        let entry = |name: &str, command: &str, source: String| json!({"name": name, "command": command, "source": source});
+        let config_dir = crate::paths::config_dir().display();
+        let mut entries = Vec::new();
+        for (name, command, set_in) in &self.configured {
+            let source = match set_in {
+                ConfiguredIn::ProjectFile => format!("{}/project.toml", config_dir),
+                ConfiguredIn::LocalFile => format!("{}/{}", config_dir, local::LOCAL_FILE),
+                ConfiguredIn::Profile => format!("profile {}", self.profile.unwrap_or_default()),
+            };
+            entries.push(entry(name, command, source));
+        }
+        for found in &self.discovered {
+            for (name, command) in &found.commands {
+                entries.push(entry(name, command, found.source.clone()));
+            }
+        }
+        if let Some(inferred) = &self.inferred {
+            for (kind, command) in &inferred.commands {
+                let source = format!("inferred from {}", inferred.source);
+                entries.push(entry(kind, command, source));
+            }
+        }
+        entries
     }
 }
 
@@ -541,34 +1991,19 @@ pub fn get_architecture(
         .and_then(|v| v.as_str())
         .ok_or("Missing 'concept' argument")?;
 
-    let (path, config, _, _, _, _) = projects
+    let (path, config, skills, _, _, _) = projects
         .get(project_name)
         .ok_or_else(|| format!("Project '{}' not found", project_name))?;
 
-    // Try exact match first
-    if let Some(concept) = config.concepts.get(concept_name) {
-        return Ok(format_concept(path, concept_name, concept));
-    }
-
-    // Try case-insensitive match
-    let concept_lower = concept_name.to_lowercase();
-    for (name, concept) in &config.concepts {
-        if name.to_lowercase() == concept_lower {
-            return Ok(format_concept(path, name, concept));
-        }
-    }
-
-    // Try partial match
-    for (name, concept) in &config.concepts {
-        if name.to_lowercase().contains(&concept_lower)
-            || concept.summary.to_lowercase().contains(&concept_lower)
-        {
-            return Ok(format_concept(path, name, concept));
-        }
+    if let Some((name, concept)) = find_concept(config, concept_name) {
+        return Ok(concept_with_prompts(path, name, concept, skills));
     }
 
     // List available concepts
-    let available: Vec<&str> = config.concepts.keys().map(|s| s.as_str()).collect();
+    let available: Vec<&str> = ordered_concepts(config)
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect();
     Err(format!(
         "Concept '{}' not found. Available concepts: {}",
         concept_name,
@@ -576,8 +2011,112 @@ pub fn get_architecture(
     ))
 }
 
+/// The concept `query` names: an exact match, then a case-insensitive one,
+/// then the first whose name or summary contains it, pinned concepts first.
+fn find_concept<'a>(config: &'a ProjectConfig, query: &str) -> Option<(&'a str, &'a Concept)> {
+    if let Some((name, concept)) = config.concepts.get_key_value(query) {
+        return Some((name, concept));
+    }
+    let query = query.to_lowercase();
+    let concepts = ordered_concepts(config);
+    concepts
+        .iter()
+        .find(|(name, _)| name.to_lowercase() == query)
+        .or_else(|| {
+            concepts.iter().find(|(name, concept)| {
+                name.to_lowercase().contains(&query)
+                    || concept.summary.to_lowercase().contains(&query)
+            })
+        })
+        .map(|(name, concept)| (name.as_str(), *concept))
+}
+
+/// A concept followed by the skills it points to for tasks in that area.
+fn concept_with_prompts(
+    path: &std::path::Path,
+    name: &str,
+    concept: &Concept,
+    skills: &ProjectSkills,
+) -> String {
+    let mut output = format_concept(path, name, concept);
+    if concept.prompts.is_empty() {
+        return output;
+    }
+    output.push_str("\n**Prompts:**\n");
+    for topic in &concept.prompts {
+        match skills.skills.get(topic) {
+            Some(info) => {
+                let description = info
+                    .frontmatter
+                    .as_ref()
+                    .and_then(|fm| fm.description.as_deref())
+                    .filter(|d| !d.is_empty())
+                    .unwrap_or_else(|| info.preview.lines().next().unwrap_or("").trim());
+                if description.is_empty() {
+                    output.push_str(&format!("- `{}`\n", topic));
+                } else {
+                    output.push_str(&format!("- `{}`: {}\n", topic, description));
+                }
+            }
+            None => output.push_str(&format!(
+                "- `{}` (no such skill in {})\n",
+                topic,
+                crate::paths::config_file("skills")
+            )),
+        }
+    }
+    output.push_str("\nFetch one with `get_skill` before working on this area.\n");
+    output
+}
+
+/// `structuredContent` for the tools that declare an `outputSchema`, holding
+/// the same facts as their Markdown: the project config for
+/// `get_project_info` and a concept's files for `get_architecture`.
+/// `get_commands` builds its own in [`get_commands_structured`]. `None` for
+/// other tools and for failed calls.
+pub fn structured_output(
+    name: &str,
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Option<Value> {
+    let project_name = args.get("project").and_then(|v| v.as_str())?;
+    let project = projects.get(project_name);
+    match name {
+        "get_project_info" => match project {
+            Some((_, config, _, _, _, _)) => serde_json::to_value(config).ok(),
+            None => {
+                let external = workspace.as_ref()?.external.get(project_name)?;
+                Some(json!({
+                    "project": {"name": project_name, "description": external.description},
+                    "external": {"repository": external.repository, "docs": external.docs}
+                }))
+            }
+        },
+        "get_architecture" => {
+            let (path, config, _, _, _, _) = project?;
+            let query = args.get("concept").and_then(|v| v.as_str())?;
+            let (concept_name, concept) = find_concept(config, query)?;
+            Some(json!({
+                "project": project_name,
+                "project_path": path,
+                "concept": concept_name,
+                "summary": concept.summary,
+                "files": concept.files,
+                "prompts": concept.prompts
+            }))
+        }
+        _ => None,
+    }
+}
+
+/// File-name matches listed when no concept matches a query, at most.
+const MAX_FILE_NAME_MATCHES: usize = 50;
+
 pub fn get_related_files(
     projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    file_index: &FileIndex,
     args: &Value,
 ) -> Result<String, String> {
     let project_name = args
@@ -594,30 +2133,147 @@ pub fn get_related_files(
         .get(project_name)
         .ok_or_else(|| format!("Project '{}' not found", project_name))?;
 
-    let query_lower = query.to_lowercase();
-    let mut matched_files: Vec<(String, &str, &Concept)> = Vec::new();
-
-    for (name, concept) in &config.concepts {
-        if name.to_lowercase().contains(&query_lower)
-            || concept.summary.to_lowercase().contains(&query_lower)
-        {
-            matched_files.push((name.clone(), name.as_str(), concept));
-        }
-    }
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|n| n.max(1) as usize)
+        .unwrap_or(DEFAULT_RELATED_CONCEPTS);
+
+    let terms: Vec<String> = query.split_whitespace().map(|t| t.to_lowercase()).collect();
+    let synonyms = synonyms_for(workspace, &terms);
+    // Synonyms count for half, so the agent's own words rank first.
+    let mut ranked: Vec<(u32, &String, &Concept)> = config
+        .concepts
+        .iter()
+        .map(|(name, concept)| {
+            let score =
+                score_concept(name, concept, &terms) + score_concept(name, concept, &synonyms) / 2;
+            (score, name, concept)
+        })
+        .filter(|(score, _, _)| *score > 0)
+        .collect();
 
-    if matched_files.is_empty() {
-        return Err(format!("No concepts matching '{}' found", query));
+    if ranked.is_empty() {
+        return related_files_by_name(path, file_index, query, &synonyms);
     }
+    ranked.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| b.2.pinned.cmp(&a.2.pinned))
+            .then_with(|| a.1.cmp(b.1))
+    });
 
     let mut output = format!("Files related to '{}': \n\n", query);
-    for (_, name, concept) in &matched_files {
+    if !synonyms.is_empty() {
+        output.push_str(&format!("*Also searched: {}*\n\n", synonyms.join(", ")));
+    }
+    for (_, name, concept) in ranked.iter().take(limit) {
         output.push_str(&format!("## {}\n{}\n\nFiles:\n", name, concept.summary));
         for file in &concept.files {
-            output.push_str(&format!("- {}/{}\n", path.display(), file));
+            output.push_str(&format_file_entry(path, file));
         }
         output.push('\n');
     }
+    if ranked.len() > limit {
+        output.push_str(&format!(
+            "*{} more matching concept(s) omitted. Pass a higher `limit` or a more specific query.*\n",
+            ranked.len() - limit
+        ));
+    }
+
+    Ok(output)
+}
+
+/// Concepts shown by `get_related_files` unless `limit` says otherwise.
+const DEFAULT_RELATED_CONCEPTS: usize = 5;
+
+/// Relevance of a concept to the query terms: an exact name match outranks a
+/// name substring, which outranks a summary mention; each further occurrence
+/// of a term in the name or summary adds a point. Zero means no match.
+fn score_concept(name: &str, concept: &Concept, terms: &[String]) -> u32 {
+    let name = name.to_lowercase();
+    let summary = concept.summary.to_lowercase();
+    terms
+        .iter()
+        .map(|term| {
+            let base = if name == *term {
+                100
+            } else if name.contains(term.as_str()) {
+                50
+            } else if summary.contains(term.as_str()) {
+                10
+            } else {
+                return 0;
+            };
+            let frequency =
+                name.matches(term.as_str()).count() + summary.matches(term.as_str()).count();
+            base + frequency as u32
+        })
+        .sum()
+}
+
+/// Words from the workspace `[synonyms]` groups that contain any of `terms`,
+/// excluding the terms themselves. A group is its key plus its values, and
+/// matches in either direction: `login` finds `auth` and the rest of its
+/// group.
+fn synonyms_for(workspace: &Option<WorkspaceConfig>, terms: &[String]) -> Vec<String> {
+    let Some(ws) = workspace else {
+        return Vec::new();
+    };
+    let mut keys: Vec<&String> = ws.synonyms.keys().collect();
+    keys.sort();
+
+    let mut synonyms: Vec<String> = Vec::new();
+    for key in keys {
+        let group: Vec<String> = std::iter::once(key)
+            .chain(&ws.synonyms[key])
+            .map(|word| word.to_lowercase())
+            .collect();
+        if !group.iter().any(|word| terms.contains(word)) {
+            continue;
+        }
+        for word in group {
+            if !terms.contains(&word) && !synonyms.contains(&word) {
+                synonyms.push(word);
+            }
+        }
+    }
+    synonyms
+}
+
+/// Fallback for `get_related_files` when no curated concept matches.
+fn related_files_by_name(
+    path: &std::path::Path,
+    file_index: &FileIndex,
+    query: &str,
+    synonyms: &[String],
+) -> Result<String, String> {
+    let files = file_index.files(path);
+    let mut matches = files::match_basenames(&files, query);
+    for synonym in synonyms {
+        matches.extend(files::match_basenames(&files, synonym));
+    }
+    matches.sort();
+    matches.dedup();
+    if matches.is_empty() {
+        return Err(format!(
+            "No concepts or file names matching '{}' found",
+            query
+        ));
+    }
 
+    let mut output = format!(
+        "No concepts match '{}'. Files whose names contain it:\n\n",
+        query
+    );
+    for file in matches.iter().take(MAX_FILE_NAME_MATCHES) {
+        output.push_str(&format_file_entry(path, file));
+    }
+    if matches.len() > MAX_FILE_NAME_MATCHES {
+        output.push_str(&format!(
+            "\n...and {} more. Use a more specific query.\n",
+            matches.len() - MAX_FILE_NAME_MATCHES
+        ));
+    }
     Ok(output)
 }
 
@@ -630,23 +2286,33 @@ pub fn list_skills(
         .and_then(|v| v.as_str())
         .ok_or("Missing 'project' argument")?;
 
-    let (_, _, skills, _, _, _) = projects
+    let (_, config, skills, _, _, _) = projects
         .get(project_name)
         .ok_or_else(|| format!("Project '{}' not found", project_name))?;
 
     if skills.skills.is_empty() {
         return Ok(format!(
-            "No skills found for '{}'. Create .jumble/skills/*.md files to add task-specific context.",
-            project_name
+            "No skills found for '{}'. Create {} files to add task-specific context.",
+            project_name,
+            crate::paths::config_file("skills/*.md")
         ));
     }
 
+    let context = args.get("context").cloned().unwrap_or(Value::Null);
     let mut output = format!("Available skills for '{}':\n\n", project_name);
+    let mut hidden = Vec::new();
 
     // Include any available frontmatter description or, as a fallback, the first
     // line of the cached preview. This makes skill listings more informative
     // and exercises the cached metadata so it is not considered dead code.
     for (name, info) in &skills.skills {
+        if let Some(fm) = &info.frontmatter {
+            if !skill_applies(&fm.applies_to, config, &context) {
+                hidden.push(name.as_str());
+                continue;
+            }
+        }
+
         let mut line = format!("- {}", name);
 
         if let Some(fm) = &info.frontmatter {
@@ -669,10 +2335,37 @@ pub fn list_skills(
         output.push('\n');
     }
 
+    if !hidden.is_empty() {
+        hidden.sort();
+        output.push_str(&format!("\n*Not applicable here: {}*\n", hidden.join(", ")));
+    }
     output.push_str("\nUse get_skill(project, topic) to retrieve a specific skill.");
     Ok(output)
 }
 
+/// Whether a skill's `applies_to` matches the project's language and the
+/// `file` and `task` given in `context`. Conditions without a known value
+/// to compare with are skipped.
+fn skill_applies(applies: &SkillApplicability, config: &ProjectConfig, context: &Value) -> bool {
+    let any_eq = |list: &[String], value: &str| list.iter().any(|v| v.eq_ignore_ascii_case(value));
+    if let Some(language) = &config.project.language {
+        if !applies.languages.is_empty() && !any_eq(&applies.languages, language) {
+            return false;
+        }
+    }
+    if let Some(task) = context.get("task").and_then(|v| v.as_str()) {
+        if !applies.tags.is_empty() && !any_eq(&applies.tags, task) {
+            return false;
+        }
+    }
+    if let Some(file) = context.get("file").and_then(|v| v.as_str()) {
+        if !applies.files.is_empty() && !files::matches_globs(&applies.files, file) {
+            return false;
+        }
+    }
+    true
+}
+
 pub fn get_skill(projects: &HashMap<String, ProjectData>, args: &Value) -> Result<String, String> {
     let project_name = args
         .get("project")
@@ -701,21 +2394,156 @@ pub fn get_skill(projects: &HashMap<String, ProjectData>, args: &Value) -> Resul
         }
     })?;
 
+    let format = args
+        .get("format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("markdown");
+    if format != "markdown" && format != "messages" {
+        return Err(format!(
+            "Unknown format '{}'. Use 'markdown' or 'messages'.",
+            format
+        ));
+    }
+
     // Read the main skill file
-    let skill_content = std::fs::read_to_string(&skill_info.path)
+    let skill_content = crate::text::read_text(&skill_info.path)
         .map_err(|e| format!("Failed to read skill: {}", e))?;
 
-    // If this skill has a directory with companion files, include them
-    if let Some(skill_dir) = &skill_info.skill_dir {
-        let companions = discover_companion_files(skill_dir);
+    let companions = skill_info
+        .skill_dir
+        .as_ref()
+        .map(|dir| discover_companion_files(dir))
+        .unwrap_or_default();
+    let scripts = skill_info
+        .skill_dir
+        .as_ref()
+        .map(|dir| format_skill_scripts(dir))
+        .unwrap_or_default();
+
+    if format == "messages" {
+        let mut messages =
+            split_skill_messages(skill_body(&skill_content), skill_info.frontmatter.as_ref());
+        // Companion resources belong with whatever the last message asks for.
         if !companions.is_empty() {
-            return Ok(format_skill_with_companions(&skill_content, &companions));
+            if let Some(last) = messages.last_mut() {
+                let text = format_skill_with_companions(&last.1, &companions);
+                last.1 = text + &scripts;
+            }
         }
+        return Ok(format_skill_messages(
+            topic,
+            skill_info.frontmatter.as_ref(),
+            &messages,
+        ));
+    }
+
+    // If this skill has a directory with companion files, include them
+    if !companions.is_empty() {
+        return Ok(format_skill_with_companions(&skill_content, &companions) + &scripts);
     }
 
     Ok(skill_content)
 }
 
+/// Return the part of a skill file after its YAML frontmatter, if any.
+fn skill_body(content: &str) -> &str {
+    crate::config::split_frontmatter(content).1
+}
+
+/// Split a skill body into `(role, text)` messages.
+///
+/// A line that exactly matches one of the markers declared under `roles` in
+/// the frontmatter starts a new message with that role; the marker line
+/// itself is dropped. Text before the first marker (or the whole body when no
+/// roles are declared) becomes a `user` message.
+fn split_skill_messages(
+    body: &str,
+    frontmatter: Option<&crate::config::SkillFrontmatter>,
+) -> Vec<(String, String)> {
+    let markers: Vec<(&str, &str)> = frontmatter
+        .map(|fm| {
+            fm.roles
+                .iter()
+                .map(|(role, marker)| (marker.trim(), role.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut messages: Vec<(String, String)> = Vec::new();
+    let mut role = "user".to_string();
+    let mut text = String::new();
+
+    for line in body.lines() {
+        if let Some((_, next_role)) = markers.iter().find(|(m, _)| *m == line.trim()) {
+            if !text.trim().is_empty() {
+                messages.push((role, text.trim().to_string()));
+            }
+            role = next_role.to_string();
+            text = String::new();
+            continue;
+        }
+        text.push_str(line);
+        text.push('\n');
+    }
+    if !text.trim().is_empty() {
+        messages.push((role, text.trim().to_string()));
+    }
+
+    messages
+}
+
+/// Keep only the roles MCP prompt messages allow, `user` and `assistant`.
+/// Text under any other role (such as `system`) is put, in order, at the
+/// start of the first `user` message.
+fn prompt_roles(messages: &[(String, String)]) -> Vec<(String, String)> {
+    let (kept, folded): (Vec<_>, Vec<_>) = messages
+        .iter()
+        .cloned()
+        .partition(|(role, _)| role == "user" || role == "assistant");
+    if folded.is_empty() {
+        return kept;
+    }
+    let mut preamble: Vec<String> = folded.into_iter().map(|(_, text)| text).collect();
+    let mut kept = kept;
+    match kept.iter_mut().find(|(role, _)| role == "user") {
+        Some((_, text)) => {
+            preamble.push(std::mem::take(text));
+            *text = preamble.join("\n\n");
+        }
+        None => kept.insert(0, ("user".to_string(), preamble.join("\n\n"))),
+    }
+    kept
+}
+
+/// Render skill messages as an MCP `prompts/get`-style JSON result.
+fn format_skill_messages(
+    topic: &str,
+    frontmatter: Option<&crate::config::SkillFrontmatter>,
+    messages: &[(String, String)],
+) -> String {
+    let description = frontmatter
+        .and_then(|fm| fm.description.clone())
+        .unwrap_or_else(|| topic.to_string());
+    let messages: Vec<Value> = prompt_roles(messages)
+        .iter()
+        .map(|(role, text)| {
+            json!({
+                "role": role,
+                "content": {
+                    "type": "text",
+                    "text": text
+                }
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&json!({
+        "description": description,
+        "messages": messages
+    }))
+    .unwrap_or_default()
+}
+
 /// Companion file entry discovered in a skill directory
 #[derive(Debug)]
 struct CompanionFile {
@@ -768,6 +2596,117 @@ fn discover_companion_files(skill_dir: &std::path::Path) -> Vec<CompanionFile> {
     companions
 }
 
+/// Interpreters for helper scripts without a usable shebang, by extension.
+const SCRIPT_INTERPRETERS: &[(&str, &str)] = &[
+    ("sh", "sh"),
+    ("bash", "bash"),
+    ("py", "python3"),
+    ("js", "node"),
+    ("mjs", "node"),
+    ("ts", "npx tsx"),
+    ("rb", "ruby"),
+    ("pl", "perl"),
+    ("ps1", "pwsh -File"),
+];
+
+/// The command that runs the helper script at `path`: its shebang
+/// interpreter, or one picked by extension. `None` for files that don't
+/// look runnable.
+fn script_command(path: &std::path::Path) -> Option<String> {
+    let quoted = {
+        let display = path.display().to_string();
+        if display.contains(char::is_whitespace) {
+            format!("\"{}\"", display)
+        } else {
+            display
+        }
+    };
+    let content = crate::text::read_text(path).unwrap_or_default();
+    if let Some(shebang) = content.lines().next().and_then(|l| l.strip_prefix("#!")) {
+        // Flags after the interpreter, as in `#!/bin/bash -e`, are kept.
+        let mut parts = shebang.split_whitespace();
+        let interpreter: Vec<&str> = match parts.next() {
+            Some(program) if program.ends_with("/env") => {
+                parts.skip_while(|part| *part == "-S").collect()
+            }
+            Some(program) => std::iter::once(program.rsplit('/').next().unwrap_or(program))
+                .chain(parts)
+                .collect(),
+            None => Vec::new(),
+        };
+        if !interpreter.is_empty() {
+            return Some(format!("{} {}", interpreter.join(" "), quoted));
+        }
+    }
+    let extension = path.extension()?.to_str()?;
+    SCRIPT_INTERPRETERS
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, interpreter)| format!("{} {}", interpreter, quoted))
+}
+
+/// The first comment after a script's shebang, as its description.
+fn script_description(path: &std::path::Path) -> Option<String> {
+    let content = crate::text::read_text(path).ok()?;
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with("#!") && !line.is_empty())
+        .take(3)
+        .find_map(|line| {
+            let text = ["#", "//", "--"]
+                .iter()
+                .find_map(|marker| line.strip_prefix(marker))?
+                .trim();
+            (!text.is_empty()).then(|| text.to_string())
+        })
+}
+
+/// The runnable scripts in a skill's `scripts/` directory with the command
+/// for each, sorted by path.
+fn skill_scripts(skill_dir: &std::path::Path) -> Vec<(std::path::PathBuf, String)> {
+    let mut scripts: Vec<std::path::PathBuf> = std::fs::read_dir(skill_dir.join("scripts"))
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_file())
+                .collect()
+        })
+        .unwrap_or_default();
+    scripts.sort();
+    scripts
+        .into_iter()
+        .filter_map(|path| script_command(&path).map(|command| (path, command)))
+        .collect()
+}
+
+/// A "Helper Scripts" section with the exact command for each script in
+/// the skill's `scripts/` directory, or an empty string when it has none.
+fn format_skill_scripts(skill_dir: &std::path::Path) -> String {
+    let mut output = String::new();
+    for (path, command) in skill_scripts(skill_dir) {
+        let path = &path;
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+        match script_description(path) {
+            Some(description) => output.push_str(&format!(
+                "- **{}**: {}\n  `{}`\n",
+                name, description, command
+            )),
+            None => output.push_str(&format!("- **{}**\n  `{}`\n", name, command)),
+        }
+    }
+    if output.is_empty() {
+        return output;
+    }
+    let how = if cfg!(feature = "exec") {
+        "Run these from the project root, or call `run_skill_script` with the script's name; append arguments as the skill describes."
+    } else {
+        "Run these from the project root; append arguments as the skill describes."
+    };
+    format!("\n## Helper Scripts\n\n{}\n\n{}", how, output)
+}
+
 /// Format skill content with companion files listed at the end
 fn format_skill_with_companions(skill_content: &str, companions: &[CompanionFile]) -> String {
     let mut output = String::from(skill_content);
@@ -805,6 +2744,7 @@ fn format_skill_with_companions(skill_content: &str, companions: &[CompanionFile
 
 pub fn get_conventions(
     projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
     args: &Value,
 ) -> Result<String, String> {
     let project_name = args
@@ -818,49 +2758,81 @@ pub fn get_conventions(
         .get(project_name)
         .ok_or_else(|| format!("Project '{}' not found", project_name))?;
 
-    let has_conventions = !conventions.conventions.is_empty();
-    let has_gotchas = !conventions.gotchas.is_empty();
+    let group_conventions = group_entries(workspace, project_name, |g| &g.conventions);
+    let group_gotchas = group_entries(workspace, project_name, |g| &g.gotchas);
+    let has_conventions = !conventions.conventions.is_empty() || !group_conventions.is_empty();
+    let has_gotchas = !conventions.gotchas.is_empty() || !group_gotchas.is_empty();
 
     if !has_conventions && !has_gotchas {
         return Ok(format!(
-            "No conventions found for '{}'. Create .jumble/conventions.toml to add project-specific conventions and gotchas.",
-            project_name
+            "No conventions found for '{}'. Create {} to add project-specific conventions and gotchas.",
+            project_name,
+            crate::paths::config_file("conventions.toml")
         ));
     }
 
+    let strings = i18n::for_workspace(workspace);
+    let conventions_heading = i18n::fill(strings.conventions_for, project_name);
+    let gotchas_heading = i18n::fill(strings.gotchas_for, project_name);
     let mut output = String::new();
+    let section = |output: &mut String,
+                   heading: &str,
+                   own: &HashMap<String, String>,
+                   shared: &[(&String, &String, &String)]| {
+        output.push_str(&format!("# {}\n\n", heading));
+        for (name, desc) in own {
+            output.push_str(&format!("## {}\n{}\n\n", name, desc));
+        }
+        for (group, name, desc) in shared {
+            // The project's own entry of the same name replaces the group's.
+            if !own.contains_key(*name) {
+                output.push_str(&format!(
+                    "## {}\n{}\n*From group `{}`.*\n\n",
+                    name, desc, group
+                ));
+            }
+        }
+    };
 
     match category {
         Some("conventions") => {
             if !has_conventions {
                 return Ok("No conventions defined.".to_string());
             }
-            output.push_str(&format!("# Conventions for '{}'\n\n", project_name));
-            for (name, desc) in &conventions.conventions {
-                output.push_str(&format!("## {}\n{}\n\n", name, desc));
-            }
+            section(
+                &mut output,
+                &conventions_heading,
+                &conventions.conventions,
+                &group_conventions,
+            );
         }
         Some("gotchas") => {
             if !has_gotchas {
                 return Ok("No gotchas defined.".to_string());
             }
-            output.push_str(&format!("# Gotchas for '{}'\n\n", project_name));
-            for (name, desc) in &conventions.gotchas {
-                output.push_str(&format!("## {}\n{}\n\n", name, desc));
-            }
+            section(
+                &mut output,
+                &gotchas_heading,
+                &conventions.gotchas,
+                &group_gotchas,
+            );
         }
         None => {
             if has_conventions {
-                output.push_str(&format!("# Conventions for '{}'\n\n", project_name));
-                for (name, desc) in &conventions.conventions {
-                    output.push_str(&format!("## {}\n{}\n\n", name, desc));
-                }
+                section(
+                    &mut output,
+                    &conventions_heading,
+                    &conventions.conventions,
+                    &group_conventions,
+                );
             }
             if has_gotchas {
-                output.push_str(&format!("# Gotchas for '{}'\n\n", project_name));
-                for (name, desc) in &conventions.gotchas {
-                    output.push_str(&format!("## {}\n{}\n\n", name, desc));
-                }
+                section(
+                    &mut output,
+                    &gotchas_heading,
+                    &conventions.gotchas,
+                    &group_gotchas,
+                );
             }
         }
         Some(c) => {
@@ -874,7 +2846,33 @@ pub fn get_conventions(
     Ok(output)
 }
 
-pub fn get_docs(projects: &HashMap<String, ProjectData>, args: &Value) -> Result<String, String> {
+/// Entries that `project`'s groups share with it, as `(group, name, value)`
+/// sorted by group and name.
+fn group_entries<'a, T>(
+    workspace: &'a Option<WorkspaceConfig>,
+    project: &str,
+    entries: impl Fn(&'a ProjectGroup) -> &'a HashMap<String, T>,
+) -> Vec<(&'a String, &'a String, &'a T)> {
+    let mut found: Vec<(&String, &String, &T)> = workspace
+        .iter()
+        .flat_map(|ws| &ws.groups)
+        .filter(|(_, group)| group.projects.iter().any(|p| p == project))
+        .flat_map(|(group, members)| {
+            entries(members)
+                .iter()
+                .map(move |(name, value)| (group, name, value))
+        })
+        .collect();
+    found.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+    found
+}
+
+pub fn get_docs(
+    root: &std::path::Path,
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
     let project_name = args
         .get("project")
         .and_then(|v| v.as_str())
@@ -886,45 +2884,137 @@ pub fn get_docs(projects: &HashMap<String, ProjectData>, args: &Value) -> Result
         .get(project_name)
         .ok_or_else(|| format!("Project '{}' not found", project_name))?;
 
-    if docs.docs.is_empty() {
+    // Group docs live under the workspace root; the project's own win on a
+    // name clash.
+    let group_docs: Vec<(&String, &String, &DocEntry)> =
+        group_entries(workspace, project_name, |g| &g.docs)
+            .into_iter()
+            .filter(|(_, name, _)| !docs.docs.contains_key(*name))
+            .collect();
+
+    if docs.docs.is_empty() && group_docs.is_empty() {
         return Ok(format!(
-            "No documentation index found for '{}'. Create .jumble/docs.toml to index project documentation.",
-            project_name
+            "No documentation index found for '{}'. Create {} to index project documentation.",
+            project_name,
+            crate::paths::config_file("docs.toml")
         ));
     }
 
     match topic {
         Some(t) => {
             // Return path to specific doc
-            let doc = docs.docs.get(t).ok_or_else(|| {
-                let available: Vec<&str> = docs.docs.keys().map(|s| s.as_str()).collect();
-                format!("Doc '{}' not found. Available: {}", t, available.join(", "))
-            })?;
-            let full_path = path.join(&doc.path);
-            Ok(format!(
-                "## {}\n**Summary:** {}\n**Path:** {}",
+            let (full_path, doc, group) = match docs.docs.get(t) {
+                Some(doc) => (crate::paths::join_relative(path, &doc.path), doc, None),
+                None => {
+                    let (group, _, doc) = group_docs
+                        .iter()
+                        .find(|(_, name, _)| name.as_str() == t)
+                        .ok_or_else(|| {
+                            let available: Vec<&str> = docs
+                                .docs
+                                .keys()
+                                .chain(group_docs.iter().map(|(_, name, _)| *name))
+                                .map(|s| s.as_str())
+                                .collect();
+                            format!("Doc '{}' not found. Available: {}", t, available.join(", "))
+                        })?;
+                    (
+                        crate::paths::join_relative(root, &doc.path),
+                        *doc,
+                        Some(group),
+                    )
+                }
+            };
+            let mut output = format!(
+                "## {}\n**Summary:** {}\n**Path:** {}\n**URI:** {}",
                 t,
                 doc.summary,
-                full_path.display()
-            ))
+                full_path.display(),
+                crate::paths::file_uri(&full_path)
+            );
+            if let Some(group) = group {
+                output.push_str(&format!("\n*From group `{}`.*", group));
+            }
+            Ok(output)
         }
         None => {
             // List all docs with summaries
             let mut output = format!("# Documentation for '{}'\n\n", project_name);
-            for (name, doc) in &docs.docs {
-                output.push_str(&format!("- **{}**: {}\n", name, doc.summary));
+            // No summary and nothing to derive one from: the path is better than nothing.
+            let summary = |doc: &DocEntry| {
+                if doc.summary.is_empty() {
+                    doc.path.clone()
+                } else {
+                    doc.summary.clone()
+                }
+            };
+            let mut own: Vec<(&String, &DocEntry)> = docs.docs.iter().collect();
+            own.sort_by_key(|(name, _)| name.as_str());
+            let mut entries: Vec<String> = own
+                .into_iter()
+                .map(|(name, doc)| format!("- **{}**: {}\n", name, summary(doc)))
+                .collect();
+            for (group, name, doc) in &group_docs {
+                entries.push(format!(
+                    "- **{}**: {} *(group `{}`)*\n",
+                    name,
+                    summary(doc),
+                    group
+                ));
             }
+            let page = paginate(entries, args)?;
+            output.push_str(&page.items.concat());
+            output.push_str(&page_footer(&page, "docs", "get_docs"));
             output.push_str("\nUse get_docs(project, topic) to get the path to a specific doc.");
             Ok(output)
         }
     }
 }
 
+/// Sections `get_workspace_overview` can return.
+const OVERVIEW_SECTIONS: [&str; 3] = ["projects", "dependencies", "conventions_summary"];
+
+/// Sections returned when `include` is omitted.
+const DEFAULT_OVERVIEW_SECTIONS: [&str; 2] = ["projects", "dependencies"];
+
+/// Whether `project_filter` names the project's language or one of its tags.
+fn matches_project_filter(config: &ProjectConfig, filter: &str) -> bool {
+    config
+        .project
+        .language
+        .iter()
+        .chain(&config.project.tags)
+        .any(|value| value.eq_ignore_ascii_case(filter))
+}
+
 pub fn get_workspace_overview(
     root: &std::path::Path,
     workspace: &Option<WorkspaceConfig>,
     projects: &HashMap<String, ProjectData>,
+    args: &Value,
 ) -> Result<String, String> {
+    let sections: Vec<&str> = match args.get("include") {
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .filter(|section| OVERVIEW_SECTIONS.contains(section))
+                    .ok_or_else(|| {
+                        format!(
+                            "Unknown section: {}. Valid sections: {}",
+                            item,
+                            OVERVIEW_SECTIONS.join(", ")
+                        )
+                    })
+            })
+            .collect::<Result<_, _>>()?,
+        Some(_) => return Err("'include' must be an array of section names".to_string()),
+        None => DEFAULT_OVERVIEW_SECTIONS.to_vec(),
+    };
+    let project_filter = args.get("project_filter").and_then(|v| v.as_str());
+    let group = group_filter(workspace, args)?;
+
+    let strings = i18n::for_workspace(workspace);
     let mut output = String::new();
 
     // Workspace info
@@ -932,78 +3022,227 @@ pub fn get_workspace_overview(
         if let Some(name) = &ws.workspace.name {
             output.push_str(&format!("# {}\n\n", name));
         } else {
-            output.push_str("# Workspace Overview\n\n");
+            output.push_str(&format!("# {}\n\n", strings.workspace_overview));
         }
         if let Some(desc) = &ws.workspace.description {
             output.push_str(&format!("{}\n\n", desc));
         }
     } else {
-        output.push_str("# Workspace Overview\n\n");
+        output.push_str(&format!("# {}\n\n", strings.workspace_overview));
     }
 
-    output.push_str(&format!("**Root:** {}\n\n", root.display()));
-
-    // Projects list
-    if projects.is_empty() {
-        output.push_str("No projects found.\n");
-        return Ok(output);
+    output.push_str(&format!("**{}:** {}\n\n", strings.root, root.display()));
+    if let Some(note) = workspace
+        .as_ref()
+        .and_then(|ws| local_overrides_note(&ws.local_overrides, ""))
+    {
+        output.push_str(&format!("{}\n\n", note));
     }
 
-    output.push_str("## Projects\n\n");
+    // Pinned projects first, then the rest sorted for consistent output
+    let project_names: Vec<&String> = ordered_project_names(projects, workspace)
+        .into_iter()
+        .filter(|name| {
+            project_filter.is_none_or(|filter| matches_project_filter(&projects[*name].1, filter))
+        })
+        .filter(|name| group.is_none_or(|group| group.projects.contains(name)))
+        .collect();
+    // External projects have no language or tags to filter on.
+    let external = match project_filter {
+        Some(_) => Vec::new(),
+        None => external_projects(projects, workspace, group),
+    };
+    let is_external = |name: &String| external.iter().any(|(external, _)| *external == name);
 
-    // Collect and sort projects for consistent output
-    let mut project_names: Vec<&String> = projects.keys().collect();
-    project_names.sort();
+    let lists_projects = sections.contains(&"projects") || sections.contains(&"dependencies");
+    if lists_projects && project_names.is_empty() && external.is_empty() {
+        match project_filter {
+            Some(filter) if !projects.is_empty() => {
+                output.push_str(&format!("No projects match '{}'.\n", filter));
+            }
+            None if group.is_some() && !projects.is_empty() => {
+                output.push_str("No projects in this group.\n");
+            }
+            _ => output.push_str("No projects found.\n"),
+        }
+    }
 
-    for name in &project_names {
-        let (_, config, _, _, _, _) = projects.get(*name).unwrap();
-        let lang = config.project.language.as_deref().unwrap_or("unknown");
-        output.push_str(&format!(
-            "- **{}** ({}): {}\n",
-            name, lang, config.project.description
-        ));
+    // Projects list
+    if sections.contains(&"projects") && !(project_names.is_empty() && external.is_empty()) {
+        output.push_str(&format!("## {}\n\n", strings.projects));
+        for name in &project_names {
+            let (_, config, _, _, _, _) = projects.get(*name).unwrap();
+            output.push_str(&format!(
+                "- **{}** ({}): {}\n",
+                name,
+                project_label(workspace, name, config),
+                config.project.description
+            ));
+            for (concept_name, concept) in ordered_concepts(config) {
+                if !concept.pinned {
+                    break;
+                }
+                output.push_str(&format!("  - {}: {}\n", concept_name, concept.summary));
+            }
+        }
+        for (name, project) in &external {
+            output.push_str(&format!(
+                "- **{}** (external): {}\n",
+                name, project.description
+            ));
+        }
+        output.push('\n');
     }
 
     // Dependency graph
-    output.push_str("\n## Dependencies\n\n");
-    let mut has_deps = false;
-
-    for name in &project_names {
-        let (_, config, _, _, _, _) = projects.get(*name).unwrap();
-        let upstream = &config.related_projects.upstream;
-        let downstream = &config.related_projects.downstream;
+    if sections.contains(&"dependencies") && !(project_names.is_empty() && external.is_empty()) {
+        output.push_str(&format!("## {}\n\n", strings.dependencies));
+        let mut has_deps = false;
+        let linked = workspace.as_ref().map(|ws| &ws.linked_projects);
+        // `workspace:project` names resolve through `[workspaces]`.
+        let label = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| {
+                    if is_external(name) {
+                        format!("{} (external)", name)
+                    } else if linked.is_some_and(|linked| linked.contains_key(name)) {
+                        format!("{} (linked workspace)", name)
+                    } else if name.contains(':') {
+                        format!("{} (not found)", name)
+                    } else {
+                        name.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
 
-        if !upstream.is_empty() || !downstream.is_empty() {
-            has_deps = true;
-            output.push_str(&format!("**{}**:\n", name));
-            if !upstream.is_empty() {
-                output.push_str(&format!("  ← depends on: {}\n", upstream.join(", ")));
+        for name in &project_names {
+            let (_, config, _, _, _, _) = projects.get(*name).unwrap();
+            let upstream = &config.related_projects.upstream;
+            let downstream = &config.related_projects.downstream;
+
+            if !upstream.is_empty() || !downstream.is_empty() {
+                has_deps = true;
+                output.push_str(&format!("**{}**:\n", name));
+                if !upstream.is_empty() {
+                    output.push_str(&format!(
+                        "  ← {}: {}\n",
+                        strings.depends_on,
+                        label(upstream)
+                    ));
+                }
+                if !downstream.is_empty() {
+                    output.push_str(&format!("  → {}: {}\n", strings.used_by, label(downstream)));
+                }
+            }
+        }
+        for (name, _) in &external {
+            let users: Vec<&str> = project_names
+                .iter()
+                .filter(|user| projects[**user].1.related_projects.upstream.contains(name))
+                .map(|user| user.as_str())
+                .collect();
+            if !users.is_empty() {
+                has_deps = true;
+                output.push_str(&format!(
+                    "**{}** (external):\n  → {}: {}\n",
+                    name,
+                    strings.used_by,
+                    users.join(", ")
+                ));
             }
-            if !downstream.is_empty() {
-                output.push_str(&format!("  → used by: {}\n", downstream.join(", ")));
+        }
+
+        if !has_deps {
+            output.push_str("No cross-project dependencies defined.\n");
+        }
+        output.push('\n');
+    }
+
+    // Workspace conventions, one line each
+    if let Some(ws) = workspace {
+        if sections.contains(&"conventions_summary") {
+            let ws_name = ws.workspace.name.as_deref().unwrap_or("Workspace");
+            for (heading, entries) in [
+                (strings.workspace_conventions, &ws.conventions),
+                (strings.workspace_gotchas, &ws.gotchas),
+            ] {
+                if entries.is_empty() {
+                    continue;
+                }
+                output.push_str(&format!("## {}\n\n", i18n::fill(heading, ws_name)));
+                let mut entries: Vec<(&String, &String)> = entries.iter().collect();
+                entries.sort();
+                for (name, desc) in entries {
+                    let first_line = desc.lines().next().unwrap_or_default();
+                    output.push_str(&format!("- **{}**: {}\n", name, first_line));
+                }
+                output.push('\n');
             }
         }
+        output.push_str("*Use get_workspace_conventions() for workspace-wide coding standards.*");
+    }
+
+    Ok(output.trim_end().to_string() + "\n")
+}
+
+pub fn get_context_health(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    if let Some(project_name) = args.get("project").and_then(|v| v.as_str()) {
+        let (path, config, _, conventions, docs, _) = projects
+            .get(project_name)
+            .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+        let health = health::assess(path, config, conventions, docs);
+        return Ok(format!(
+            "# Context Health
+
+{}",
+            health::format_health(project_name, &health)
+        ));
     }
 
-    if !has_deps {
-        output.push_str("No cross-project dependencies defined.\n");
+    if projects.is_empty() {
+        return Ok(format!(
+            "No projects found. Make sure {} files exist in your workspace.",
+            crate::paths::config_file("project.toml")
+        ));
     }
 
-    // Note about workspace conventions
-    if workspace.is_some() {
-        output.push_str("\n*Use get_workspace_conventions() for workspace-wide coding standards.*");
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort();
+    let mut sections = Vec::new();
+    let mut total = 0;
+    for name in &names {
+        let (path, config, _, conventions, docs, _) = &projects[*name];
+        let health = health::assess(path, config, conventions, docs);
+        total += health.score();
+        sections.push(health::format_health(name, &health));
     }
 
-    Ok(output)
+    Ok(format!(
+        "# Context Health
+
+**Workspace score:** {}/100 across {} project(s)
+
+{}",
+        total / names.len() as u32,
+        names.len(),
+        sections.join("\n")
+    ))
 }
 
 pub fn get_workspace_conventions(
     workspace: &Option<WorkspaceConfig>,
     args: &Value,
 ) -> Result<String, String> {
-    let ws = workspace.as_ref().ok_or(
-        "No workspace.toml found. Create .jumble/workspace.toml at the workspace root to define workspace-level conventions."
-    )?;
+    let ws = workspace.as_ref().ok_or_else(|| format!(
+        "No workspace.toml found. Create {} at the workspace root to define workspace-level conventions.",
+        crate::paths::config_file("workspace.toml")
+    ))?;
 
     let category = args.get("category").and_then(|v| v.as_str());
 
@@ -1016,13 +3255,16 @@ pub fn get_workspace_conventions(
 
     let mut output = String::new();
     let ws_name = ws.workspace.name.as_deref().unwrap_or("Workspace");
+    let strings = i18n::for_workspace(workspace);
+    let conventions_heading = i18n::fill(strings.workspace_conventions, ws_name);
+    let gotchas_heading = i18n::fill(strings.workspace_gotchas, ws_name);
 
     match category {
         Some("conventions") => {
             if !has_conventions {
                 return Ok("No workspace conventions defined.".to_string());
             }
-            output.push_str(&format!("# {} Conventions\n\n", ws_name));
+            output.push_str(&format!("# {}\n\n", conventions_heading));
             for (name, desc) in &ws.conventions {
                 output.push_str(&format!("## {}\n{}\n\n", name, desc));
             }
@@ -1031,20 +3273,20 @@ pub fn get_workspace_conventions(
             if !has_gotchas {
                 return Ok("No workspace gotchas defined.".to_string());
             }
-            output.push_str(&format!("# {} Gotchas\n\n", ws_name));
+            output.push_str(&format!("# {}\n\n", gotchas_heading));
             for (name, desc) in &ws.gotchas {
                 output.push_str(&format!("## {}\n{}\n\n", name, desc));
             }
         }
         None => {
             if has_conventions {
-                output.push_str(&format!("# {} Conventions\n\n", ws_name));
+                output.push_str(&format!("# {}\n\n", conventions_heading));
                 for (name, desc) in &ws.conventions {
                     output.push_str(&format!("## {}\n{}\n\n", name, desc));
                 }
             }
             if has_gotchas {
-                output.push_str(&format!("# {} Gotchas\n\n", ws_name));
+                output.push_str(&format!("# {}\n\n", gotchas_heading));
                 for (name, desc) in &ws.gotchas {
                     output.push_str(&format!("## {}\n{}\n\n", name, desc));
                 }
@@ -1223,6 +3465,7 @@ pub fn list_memories(
 
 pub fn search_memories(
     projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
     args: &Value,
 ) -> Result<String, String> {
     let project_name = args
@@ -1247,11 +3490,17 @@ pub fn search_memories(
             }
 
             let query_lower = query.to_lowercase();
+            let terms: Vec<String> = query_lower.split_whitespace().map(String::from).collect();
+            let synonyms = synonyms_for(workspace, &terms);
+            let needles: Vec<&String> = std::iter::once(&query_lower).chain(&synonyms).collect();
             let mut matches: Vec<(&String, &crate::memory::MemoryEntry)> = db
                 .iter()
                 .filter(|(k, v)| {
-                    k.to_lowercase().contains(&query_lower)
-                        || v.value.to_lowercase().contains(&query_lower)
+                    let key = k.to_lowercase();
+                    let value = v.value.to_lowercase();
+                    needles.iter().any(|needle| {
+                        key.contains(needle.as_str()) || value.contains(needle.as_str())
+                    })
                 })
                 .collect();
 
@@ -1266,6 +3515,9 @@ pub fn search_memories(
             matches.sort_by_key(|(k, _)| *k);
 
             let mut output = format!("# Search results for '{}' in '{}'\n\n", query, project_name);
+            if !synonyms.is_empty() {
+                output.push_str(&format!("*Also searched: {}*\n\n", synonyms.join(", ")));
+            }
             output.push_str(&format!("Found {} match(es)\n\n", matches.len()));
 
             for (key, entry) in matches {
@@ -1392,294 +3644,4074 @@ pub fn clear_memories(
     }
 }
 
-pub fn init_project(_workspace_root: &std::path::PathBuf, args: &Value) -> Result<String, String> {
-    // Get the target directory from arguments (required)
-    let dir_str = args
-        .get("directory")
+/// `passed (exit 0)` or `failed (exit 1)`.
+fn run_status(run: &CommandRun) -> String {
+    let status = if run.passed() { "passed" } else { "failed" };
+    format!("{} (exit {})", status, run.exit_code)
+}
+
+pub fn record_command_result(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
         .and_then(|v| v.as_str())
-        .ok_or("Missing 'directory' argument")?;
-    
-    let target_dir = std::path::PathBuf::from(dir_str);
-    
-    // Ensure the directory exists or can be created
-    if !target_dir.exists() {
-        std::fs::create_dir_all(&target_dir)
-            .map_err(|e| format!("Failed to create directory '{}': {}", target_dir.display(), e))?;
-    }
-    
-    // Use the setup module's init function
-    match crate::setup::setup_init(&target_dir) {
-        Ok(()) => Ok(format!("Project initialized successfully in {}.", target_dir.display())),
-        Err(e) => Err(format!("Failed to initialize project: {}", e)),
+        .ok_or("Missing 'project' argument")?;
+
+    let command_type = args
+        .get("command_type")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .ok_or("Missing 'command_type' argument")?;
+
+    let exit_code = args
+        .get("exit_code")
+        .and_then(|v| v.as_i64())
+        .ok_or("Missing or non-integer 'exit_code' argument")?;
+
+    let summary = args.get("summary").and_then(|v| v.as_str());
+
+    let (path, _, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let run = CommandRun {
+        exit_code,
+        summary: summary.map(|s| s.to_string()),
+        timestamp: crate::memory::current_timestamp(),
+    };
+    let mut output = format!(
+        "Recorded `{}` for project '{}': {}",
+        command_type,
+        project_name,
+        run_status(&run)
+    );
+    if let Some(previous) = state::record(path, command_type, run.clone())? {
+        if previous.passed() != run.passed() {
+            output.push_str(&format!(
+                ". Previous run {} at {}",
+                run_status(&previous),
+                previous.timestamp
+            ));
+        }
     }
+    Ok(output)
 }
 
+/// `get_last_run` in one call, for callers that only want its Markdown.
 #[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::config::*;
-    use crate::memory;
-    use std::path::PathBuf;
-    use tempfile::TempDir;
+pub fn get_last_run(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    get_last_run_structured(projects, args).map(|(text, _)| text)
+}
 
-    fn create_test_project() -> (String, ProjectData) {
-        let config = ProjectConfig {
-            project: ProjectInfo {
-                name: "test-project".to_string(),
-                description: "A test project".to_string(),
-                language: Some("rust".to_string()),
-                version: Some("1.0.0".to_string()),
-                repository: None,
-            },
-            commands: {
-                let mut map = HashMap::new();
-                map.insert("build".to_string(), "cargo build".to_string());
-                map.insert("test".to_string(), "cargo test".to_string());
-                map
-            },
-            entry_points: {
-                let mut map = HashMap::new();
-                map.insert("main".to_string(), "src/main.rs".to_string());
-                map
-            },
-            dependencies: Dependencies {
-                internal: vec!["shared".to_string()],
-                external: vec!["serde".to_string()],
-            },
-            related_projects: RelatedProjects {
-                upstream: vec!["core".to_string()],
-                downstream: vec![],
-            },
-            api: None,
-            concepts: {
-                let mut map = HashMap::new();
-                map.insert(
-                    "authentication".to_string(),
-                    Concept {
-                        files: vec!["src/auth.rs".to_string()],
-                        summary: "JWT auth".to_string(),
-                    },
-                );
-                map
-            },
-        };
+/// `get_last_run` as Markdown, with its `structuredContent`: both list the
+/// runs read from one load of the state file.
+pub fn get_last_run_structured(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<(String, Value), String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
 
-        let skills = ProjectSkills::default();
-        let conventions = ProjectConventions {
+    let (path, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let state = state::load(path)?;
+    let run_value = |command_type: &str, run: &CommandRun| {
+        json!({
+            "command_type": command_type,
+            "exit_code": run.exit_code,
+            "passed": run.passed(),
+            "summary": run.summary,
+            "timestamp": run.timestamp
+        })
+    };
+    let structured = |runs: Vec<Value>| json!({"project": project_name, "runs": runs});
+
+    let Some(command_type) = args.get("command_type").and_then(|v| v.as_str()) else {
+        if state.runs.is_empty() {
+            let text = format!(
+                "No command results recorded for project '{}'. Call record_command_result after running a command.",
+                project_name
+            );
+            return Ok((text, structured(Vec::new())));
+        }
+        let mut output = format!("# Last runs for '{}'\n\n", project_name);
+        let mut runs = Vec::new();
+        for (command_type, recorded) in &state.runs {
+            if let Some(run) = recorded.last() {
+                output.push_str(&format!(
+                    "- **{}**: {} at {}\n",
+                    command_type,
+                    run_status(run),
+                    run.timestamp
+                ));
+                runs.push(run_value(command_type, run));
+            }
+        }
+        return Ok((output, structured(runs)));
+    };
+
+    let (latest, previous) = state.last_two(command_type);
+    let Some(latest) = latest else {
+        let text = format!(
+            "No recorded `{}` runs for project '{}'. Call record_command_result after running it.",
+            command_type, project_name
+        );
+        return Ok((text, structured(Vec::new())));
+    };
+
+    let mut output = format!("# Last `{}` run for '{}'\n\n", command_type, project_name);
+    if let Some(command) = config.commands.get(command_type) {
+        output.push_str(&format!("**Command:** `{}`\n", command));
+    }
+    let push_run = |output: &mut String, run: &CommandRun| {
+        output.push_str(&format!("**Status:** {}\n", run_status(run)));
+        output.push_str(&format!("**Recorded:** {}\n", run.timestamp));
+        if let Some(summary) = &run.summary {
+            output.push_str(&format!("**Summary:** {}\n", summary));
+        }
+    };
+    push_run(&mut output, latest);
+    let mut runs = vec![run_value(command_type, latest)];
+    if let Some(previous) = previous {
+        output.push_str("\n## Previous run\n\n");
+        push_run(&mut output, previous);
+        runs.push(run_value(command_type, previous));
+    }
+    Ok((output, structured(runs)))
+}
+
+/// Directories listed by `get_code_stats` unless `limit` says otherwise.
+const DEFAULT_STATS_DIRECTORIES: usize = 10;
+
+pub fn get_code_stats(
+    projects: &HashMap<String, ProjectData>,
+    file_index: &FileIndex,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|n| n.max(1) as usize)
+        .unwrap_or(DEFAULT_STATS_DIRECTORIES);
+
+    let (path, _, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let stats = stats::collect(path, &file_index.files(path));
+    if stats.total.files == 0 {
+        return Ok(format!(
+            "No source files found in project '{}'.",
+            project_name
+        ));
+    }
+
+    let mut output = format!("# Code statistics for '{}'\n\n", project_name);
+    output.push_str("| Language | Files | Lines | Code |\n|---|---:|---:|---:|\n");
+    for (language, counts) in &stats.languages {
+        output.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            language, counts.files, counts.lines, counts.code
+        ));
+    }
+    output.push_str(&format!(
+        "| **Total** | {} | {} | {} |\n",
+        stats.total.files, stats.total.lines, stats.total.code
+    ));
+
+    output.push_str("\n## Largest Directories\n\n");
+    for (directory, counts) in stats.directories.iter().take(limit) {
+        output.push_str(&format!(
+            "- `{}`: {} lines of code in {} file(s)\n",
+            directory, counts.code, counts.files
+        ));
+    }
+    output.push_str(
+        "\n*Code excludes blank lines and line comments. Ignored, hidden and very large files are not counted.*\n",
+    );
+    Ok(output)
+}
+
+/// The project directory and repository URL `get_repo_activity` reads. The
+/// forge is called without the discovery lock held, so this is looked up
+/// first.
+pub fn repo_activity_target(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<(PathBuf, String), String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let (path, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let repository = config.project.repository.clone().ok_or_else(|| {
+        format!(
+            "Project '{}' has no repository URL. Set repository under [project] in {}.",
+            project_name,
+            crate::paths::config_file("project.toml")
+        )
+    })?;
+    Ok((path.clone(), repository))
+}
+
+/// Activity for the target found by [`repo_activity_target`].
+pub fn get_repo_activity(
+    (path, repository): (PathBuf, String),
+    jumble_config: Option<&JumbleConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default();
+
+    let refresh = args
+        .get("refresh")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
+    let fetched = forge::activity(&path, &repository, jumble_config, refresh)?;
+    let activity = &fetched.activity;
+    let mut output = format!("# Repository activity for '{}'\n\n", project_name);
+    output.push_str(&format!("**Repository:** {}\n", repository));
+    output.push_str(&format!(
+        "**Default branch:** {}\n",
+        activity.default_branch
+    ));
+    output.push_str(&format!(
+        "**Open pull requests:** {}\n",
+        activity.open_pull_requests
+    ));
+
+    output.push_str("\n## Recent Releases\n");
+    if activity.releases.is_empty() {
+        output.push_str("No releases published.\n");
+    }
+    for release in &activity.releases {
+        output.push_str(&format!("- **{}**", release.tag));
+        if let Some(name) = release.name.as_ref().filter(|name| **name != release.tag) {
+            output.push_str(&format!(" {}", name));
+        }
+        if let Some(date) = &release.published_at {
+            output.push_str(&format!(" ({})", date.split('T').next().unwrap_or(date)));
+        }
+        if let Some(url) = &release.url {
+            output.push_str(&format!(" <{}>", url));
+        }
+        output.push('\n');
+    }
+
+    output.push_str(&format!("\n*Fetched {}", fetched.fetched_at));
+    if let Some(note) = &fetched.note {
+        output.push_str(&format!(" ({})", note));
+    }
+    output.push_str("*\n");
+    Ok(output)
+}
+
+pub fn get_ticket_link(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let id = args
+        .get("id")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'id' argument")?;
+
+    let (_, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let tracker_config = config
+        .tracker
+        .as_ref()
+        .or_else(|| workspace.as_ref().and_then(|ws| ws.tracker.as_ref()))
+        .ok_or_else(|| {
+            format!(
+                "No [tracker] configured for project '{}'. Add one to {} or {}.",
+                project_name,
+                crate::paths::config_file("project.toml"),
+                crate::paths::config_file("workspace.toml")
+            )
+        })?;
+
+    let ticket = tracker::ticket(tracker_config, id)?;
+    let mut output = format!("# {}\n\n", ticket.reference);
+    output.push_str(&format!("**URL:** {}\n", ticket.url));
+    if let Some(format) = &tracker_config.branch_format {
+        output.push_str(&format!(
+            "**Branch:** `{}`\n",
+            tracker::fill(format, &ticket)
+        ));
+    }
+    if let Some(format) = &tracker_config.commit_format {
+        output.push_str(&format!(
+            "**Commit:** `{}`\n",
+            tracker::fill(format, &ticket)
+        ));
+    }
+    output.push_str(&format!(
+        "\nWrite the reference exactly as `{}` in branches, commits and PR titles.\n",
+        ticket.reference
+    ));
+    Ok(output)
+}
+
+pub fn get_compliance_info(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let (path, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let mut output = format!("# Compliance for '{}'\n\n## License\n\n", project_name);
+    let licenses = compliance::detect(path);
+    if licenses.is_empty() {
+        output.push_str("No LICENSE file or manifest license found.\n");
+    }
+    for detected in &licenses {
+        match &detected.license {
+            Some(license) => output.push_str(&format!("- `{}`: {}\n", detected.source, license)),
+            None => output.push_str(&format!(
+                "- `{}`: not recognized; read the file\n",
+                detected.source
+            )),
+        }
+    }
+
+    let policy = config
+        .compliance
+        .as_ref()
+        .or_else(|| workspace.as_ref().and_then(|ws| ws.compliance.as_ref()));
+    let Some(policy) = policy else {
+        output.push_str(
+            "\nNo [compliance] policy configured. Ask before adding a dependency whose license differs from the project's.\n",
+        );
+        return Ok(output);
+    };
+
+    output.push_str("\n## Dependency Licenses\n\n");
+    if policy.allowed_licenses.is_empty() && policy.denied_licenses.is_empty() {
+        output.push_str("No license rules configured.\n");
+    }
+    if !policy.allowed_licenses.is_empty() {
+        output.push_str(&format!(
+            "**Allowed:** {}\n",
+            policy.allowed_licenses.join(", ")
+        ));
+    }
+    if !policy.denied_licenses.is_empty() {
+        output.push_str(&format!(
+            "**Denied:** {}\n",
+            policy.denied_licenses.join(", ")
+        ));
+    }
+    if !policy.allowed_licenses.is_empty() {
+        output.push_str(
+            "\nAsk before adding a dependency under any license not listed as allowed.\n",
+        );
+    }
+
+    if let Some(restrictions) = &policy.export_restrictions {
+        output.push_str(&format!("\n## Export Restrictions\n\n{}\n", restrictions));
+    }
+    Ok(output)
+}
+
+pub fn check_dependency(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|n| !n.is_empty())
+        .ok_or("Missing 'name' argument")?;
+
+    if !projects.contains_key(project_name) {
+        return Err(format!("Project '{}' not found", project_name));
+    }
+
+    let default_policy = DependencyPolicy::default();
+    let policy = workspace
+        .as_ref()
+        .map_or(&default_policy, |ws| &ws.dependency_policy);
+    let wanted = dependencies::normalize(name);
+    let in_use: Vec<(&String, BTreeSet<String>)> = ordered_project_names(projects, workspace)
+        .into_iter()
+        .map(|project| {
+            let (path, config, _, _, _, _) = &projects[project];
+            (project, dependencies::project_dependencies(path, config))
+        })
+        .collect();
+    let users_of = |dep: &str| -> Vec<&str> {
+        in_use
+            .iter()
+            .filter(|(project, deps)| *project != project_name && deps.contains(dep))
+            .map(|(project, _)| project.as_str())
+            .collect()
+    };
+    let already_used = in_use
+        .iter()
+        .any(|(project, deps)| *project == project_name && deps.contains(&wanted));
+
+    let mut output = format!("# Dependency check: `{}` for '{}'\n\n", name, project_name);
+    let denied = policy
+        .denied
+        .iter()
+        .find(|(dep, _)| dependencies::normalize(dep) == wanted);
+    if let Some((_, reason)) = denied {
+        output.push_str(&format!("**Denied:** {}\n", reason));
+    } else if already_used {
+        output.push_str(&format!(
+            "**Already used:** '{}' already depends on `{}`.\n",
+            project_name, name
+        ));
+    } else if policy.allowed.is_empty() {
+        output.push_str(
+            "**No allow list:** The workspace has no `[dependency_policy]` allow list. Prefer what other projects already use.\n",
+        );
+    } else if policy
+        .allowed
+        .iter()
+        .any(|dep| dependencies::normalize(dep) == wanted)
+    {
+        output.push_str("**Allowed** by the workspace dependency policy.\n");
+    } else {
+        output.push_str(&format!(
+            "**Not pre-approved:** `{}` is not on the workspace's allow list. Ask before adding it.\n",
+            name
+        ));
+    }
+
+    let users = users_of(&wanted);
+    if !users.is_empty() {
+        output.push_str(&format!(
+            "\n**Used by:** {}. Match the version they use.\n",
+            users.join(", ")
+        ));
+    }
+
+    if !already_used {
+        let alternatives: Vec<(String, Vec<&str>)> = dependencies::alternatives(policy, name)
+            .into_iter()
+            .filter_map(|alt| {
+                let users = users_of(&alt);
+                (!users.is_empty()).then_some((alt, users))
+            })
+            .collect();
+        if !alternatives.is_empty() {
+            output.push_str("\n## Alternatives Already in the Workspace\n\n");
+            for (alt, users) in &alternatives {
+                output.push_str(&format!("- `{}` (used by {})\n", alt, users.join(", ")));
+            }
+            output.push_str(&format!("\nPrefer one of these over adding `{}`.\n", name));
+        }
+    }
+    Ok(output)
+}
+
+/// Notebooks listed per notebook directory by `get_data_assets`.
+const MAX_LISTED_NOTEBOOKS: usize = 10;
+
+pub fn get_data_assets(
+    projects: &HashMap<String, ProjectData>,
+    file_index: &FileIndex,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let (path, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let files = file_index.files(path);
+    let notebooks_under = |dir: &str| -> Vec<&String> {
+        let prefix = format!("{}/", dir.trim_matches('/'));
+        files
+            .iter()
+            .filter(|f| {
+                (prefix == "/" || f.starts_with(&prefix))
+                    && crate::paths::has_extension(std::path::Path::new(f), "ipynb")
+            })
+            .collect()
+    };
+
+    let Some(data) = &config.data else {
+        let found = notebooks_under("").len();
+        let mut output = format!(
+            "No [data] section configured for project '{}'.",
+            project_name
+        );
+        if found > 0 {
+            output.push_str(&format!(
+                " Found {} notebook(s); describe them and the project's datasets under [data] in {}.",
+                found,
+                crate::paths::config_file("project.toml")
+            ));
+        }
+        return Ok(output);
+    };
+
+    let mut output = format!("# Data assets for '{}'\n", project_name);
+    if !data.datasets.is_empty() {
+        output.push_str("\n## Datasets\n\n");
+        let mut datasets: Vec<_> = data.datasets.iter().collect();
+        datasets.sort_by(|a, b| a.0.cmp(b.0));
+        for (name, dataset) in datasets {
+            output.push_str(&format!("### {}\n\n{}\n\n", name, dataset.summary));
+            output.push_str(&format!("- **Location:** `{}`\n", dataset.location));
+            if let Some(format) = &dataset.format {
+                output.push_str(&format!("- **Format:** {}\n", format));
+            }
+            if let Some(schema) = &dataset.schema {
+                output.push_str(&format!("- **Schema:** `{}`\n", schema));
+            }
+            output.push('\n');
+        }
+    }
+
+    if !data.notebooks.is_empty() {
+        output.push_str("\n## Notebooks\n\n");
+        for dir in &data.notebooks {
+            if !crate::paths::join_relative(path, dir).is_dir() {
+                output.push_str(&format!("- `{}` (missing)\n", dir));
+                continue;
+            }
+            let notebooks = notebooks_under(dir);
+            output.push_str(&format!("- `{}`: {} notebook(s)\n", dir, notebooks.len()));
+            for notebook in notebooks.iter().take(MAX_LISTED_NOTEBOOKS) {
+                output.push_str(&format!("  - `{}`\n", notebook));
+            }
+            if notebooks.len() > MAX_LISTED_NOTEBOOKS {
+                output.push_str(&format!(
+                    "  - ... and {} more\n",
+                    notebooks.len() - MAX_LISTED_NOTEBOOKS
+                ));
+            }
+        }
+    }
+
+    if !data.pipelines.is_empty() {
+        output.push_str("\n## Pipelines\n\n");
+        for pipeline in &data.pipelines {
+            let missing = if crate::paths::join_relative(path, pipeline).exists() {
+                ""
+            } else {
+                " (missing)"
+            };
+            output.push_str(&format!("- `{}`{}\n", pipeline, missing));
+        }
+    }
+
+    if !data.schema_registries.is_empty() {
+        output.push_str("\n## Schema Registries\n\n");
+        for registry in &data.schema_registries {
+            output.push_str(&format!("- {}\n", registry));
+        }
+    }
+    Ok(output.trim_end().to_string() + "\n")
+}
+
+pub fn get_hooks(
+    root: &std::path::Path,
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let (path, _, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    // Hooks are usually configured once at the repository root.
+    let mut configs = hooks::detect(path);
+    let mut location = "";
+    if configs.is_empty() && path.as_path() != root {
+        configs = hooks::detect(root);
+        location = " at the workspace root";
+    }
+    if configs.is_empty() {
+        return Ok(format!(
+            "No git hook configuration (pre-commit, husky or lefthook) found for project '{}'.",
+            project_name
+        ));
+    }
+
+    let mut output = format!("# Git hooks for '{}'\n", project_name);
+    for config in &configs {
+        output.push_str(&format!(
+            "\n## {} (`{}`{})\n",
+            config.tool, config.source, location
+        ));
+        if config.hooks.is_empty() {
+            output.push_str("\nNo hooks defined.\n");
+        }
+        let mut stages: Vec<&str> = Vec::new();
+        for hook in &config.hooks {
+            if !stages.contains(&hook.stage.as_str()) {
+                stages.push(&hook.stage);
+            }
+        }
+        for stage in stages {
+            output.push_str(&format!("\n**{}**\n", stage));
+            for hook in config.hooks.iter().filter(|h| h.stage == stage) {
+                let mut line = format!("- `{}`", hook.name);
+                if let Some(command) = hook.command.as_ref().filter(|c| **c != hook.name) {
+                    line.push_str(&format!(": `{}`", command));
+                }
+                if hook.fixes {
+                    line.push_str(" *(modifies files)*");
+                }
+                output.push_str(&line);
+                output.push('\n');
+            }
+        }
+    }
+
+    if configs.iter().flat_map(|c| &c.hooks).any(|h| h.fixes) {
+        output.push_str(
+            "\nHooks marked *(modifies files)* rewrite staged files when they run. Run the formatter before committing so the commit contains what you expect.\n",
+        );
+    }
+    output.push_str(
+        "\n*Hooks only run once installed locally (`pre-commit install`, `npm install` for husky, `lefthook install`).*\n",
+    );
+    Ok(output)
+}
+
+/// What a session has fetched so far, from its successful tool calls as
+/// `(tool, arguments)`, and the context it hasn't looked at yet for the
+/// projects it touched.
+pub fn get_session_summary(
+    calls: &[(String, Value)],
+    client: Option<&str>,
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let focus = args.get("project").and_then(|v| v.as_str());
+    if let Some(name) = focus {
+        if !projects.contains_key(name) {
+            return Err(format!("Project '{}' not found", name));
+        }
+    }
+    let arg = |args: &Value, key: &str| args.get(key).and_then(|v| v.as_str()).map(str::to_string);
+    let called = |tool: &str, project: Option<&str>| {
+        calls
+            .iter()
+            .any(|(name, args)| name == tool && arg(args, "project").as_deref() == project)
+    };
+
+    let mut output = "# Session Summary\n\n".to_string();
+    if let Some(client) = client {
+        output.push_str(&format!("**Client:** {}\n", client));
+    }
+    output.push_str(&format!("**Tool calls:** {}\n", calls.len()));
+
+    // Each distinct call once, in the order first made.
+    let mut fetched: Vec<String> = Vec::new();
+    for (tool, args) in calls {
+        let detail: Vec<String> = [
+            "project",
+            "concept",
+            "topic",
+            "category",
+            "query",
+            "command_type",
+        ]
+        .iter()
+        .filter_map(|key| arg(args, key))
+        .collect();
+        let line = if detail.is_empty() {
+            tool.clone()
+        } else {
+            format!("{} ({})", tool, detail.join(", "))
+        };
+        if !fetched.contains(&line) {
+            fetched.push(line);
+        }
+    }
+    if !fetched.is_empty() {
+        output.push_str("\n## Fetched\n\n");
+        for line in &fetched {
+            output.push_str(&format!("- {}\n", line));
+        }
+    }
+
+    let mut missing: Vec<String> = Vec::new();
+    if focus.is_none() && !called("get_workspace_overview", None) {
+        missing.push("Workspace overview (`get_workspace_overview`); call it first".to_string());
+    }
+    if let Some(ws) = workspace {
+        if (!ws.conventions.is_empty() || !ws.gotchas.is_empty())
+            && !called("get_workspace_conventions", None)
+        {
+            missing.push(
+                "Workspace conventions and gotchas (`get_workspace_conventions`)".to_string(),
+            );
+        }
+    }
+
+    let mut touched: Vec<&String> = match focus {
+        Some(name) => projects
+            .get_key_value(name)
+            .map(|(k, _)| k)
+            .into_iter()
+            .collect(),
+        None => projects
+            .keys()
+            .filter(|name| {
+                calls
+                    .iter()
+                    .any(|(_, args)| arg(args, "project").as_deref() == Some(name.as_str()))
+            })
+            .collect(),
+    };
+    touched.sort();
+    for name in touched {
+        let (_, config, skills, conventions, docs, _) = &projects[name];
+        let project = Some(name.as_str());
+        let read_category = |category: &str| {
+            calls.iter().any(|(tool, args)| {
+                tool == "get_conventions"
+                    && arg(args, "project").as_deref() == project
+                    && arg(args, "category").is_none_or(|c| c == category)
+            })
+        };
+        let mut gaps = Vec::new();
+        if !called("get_project_info", project) {
+            gaps.push("project info (`get_project_info`)".to_string());
+        }
+        if !config.commands.is_empty() && !called("get_commands", project) {
+            gaps.push("commands (`get_commands`)".to_string());
+        }
+        if !conventions.conventions.is_empty() && !read_category("conventions") {
+            gaps.push("conventions (`get_conventions`)".to_string());
+        }
+        if !conventions.gotchas.is_empty() && !read_category("gotchas") {
+            gaps.push("gotchas (`get_conventions`)".to_string());
+        }
+        let mut concepts: Vec<&String> = config
+            .concepts
+            .keys()
+            .filter(|concept| {
+                !calls.iter().any(|(tool, args)| {
+                    tool == "get_architecture"
+                        && arg(args, "project").as_deref() == project
+                        && arg(args, "concept").as_deref() == Some(concept.as_str())
+                })
+            })
+            .collect();
+        concepts.sort();
+        if !concepts.is_empty() {
+            let names: Vec<&str> = concepts.iter().map(|c| c.as_str()).collect();
+            gaps.push(format!(
+                "concepts {} (`get_architecture`)",
+                names.join(", ")
+            ));
+        }
+        if !skills.skills.is_empty() && !called("list_skills", project) {
+            gaps.push("skills (`list_skills`)".to_string());
+        }
+        if !docs.docs.is_empty() && !called("get_docs", project) {
+            gaps.push("docs index (`get_docs`)".to_string());
+        }
+        if !gaps.is_empty() {
+            missing.push(format!("**{}**: {}", name, gaps.join("; ")));
+        }
+    }
+
+    output.push_str("\n## Not Yet Fetched\n\n");
+    if missing.is_empty() {
+        output.push_str("Nothing: the session has seen all context for the projects it used.\n");
+    } else {
+        for line in &missing {
+            output.push_str(&format!("- {}\n", line));
+        }
+    }
+    Ok(output)
+}
+
+pub fn get_context_diff(root: &std::path::Path, args: &Value) -> Result<String, String> {
+    let from = args
+        .get("from")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'from' parameter")?;
+    let to = args.get("to").and_then(|v| v.as_str()).unwrap_or("HEAD");
+    crate::diff::context_diff(root, from, to)
+}
+
+/// Commands listed as checks when `[review] required_checks` is not set.
+const REVIEW_COMMANDS: &[&str] = &["build", "test", "lint", "typecheck", "format", "fmt"];
+
+/// Whether `text` mentions `term` as a whole word, ignoring case.
+fn mentions(text: &str, term: &str) -> bool {
+    let (text, term) = (text.to_lowercase(), term.to_lowercase());
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(&term).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + term.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+/// Words a gotcha might use to refer to `file`: its path, name, stem,
+/// extension and parent directory. Short stems and directories are left out
+/// so `src` or `mod` don't match everything.
+fn file_terms(file: &str) -> Vec<String> {
+    let path = std::path::Path::new(file);
+    let mut terms = vec![file.to_string()];
+    let name = |p: Option<&std::ffi::OsStr>| p.and_then(|n| n.to_str()).map(str::to_string);
+    terms.extend(name(path.file_name()));
+    terms.extend(name(path.file_stem()).filter(|stem| stem.len() >= 4));
+    terms.extend(name(path.extension()).map(|ext| format!(".{}", ext)));
+    terms.extend(name(path.parent().and_then(|p| p.file_name())).filter(|dir| dir.len() >= 4));
+    terms
+}
+
+pub fn get_review_checklist(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+    let files: Vec<&str> = args
+        .get("files")
+        .and_then(|v| v.as_array())
+        .ok_or("Missing 'files' argument")?
+        .iter()
+        .filter_map(|f| f.as_str())
+        .map(|f| f.trim_start_matches("./"))
+        .collect();
+    if files.is_empty() {
+        return Err("'files' must list at least one file".to_string());
+    }
+    let (_, config, _, conventions, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let mut output = format!("# Review Checklist: {}\n\n", project_name);
+    output.push_str(&format!(
+        "**Files:** {}\n",
+        files
+            .iter()
+            .map(|f| format!("`{}`", f))
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+
+    // Concepts whose files include a touched file.
+    let mut concepts: Vec<(&String, &Concept, Vec<&str>)> = config
+        .concepts
+        .iter()
+        .map(|(name, concept)| {
+            let touched: Vec<&str> = files
+                .iter()
+                .copied()
+                .filter(|file| files::matches_globs(&concept.files, file))
+                .collect();
+            (name, concept, touched)
+        })
+        .filter(|(_, _, touched)| !touched.is_empty())
+        .collect();
+    concepts.sort_by(|a, b| a.0.cmp(b.0));
+
+    // Project conventions win over workspace ones with the same key.
+    let mut all_conventions: BTreeMap<&String, &String> = BTreeMap::new();
+    let mut all_gotchas: BTreeMap<&String, &String> = BTreeMap::new();
+    if let Some(ws) = workspace {
+        all_conventions.extend(&ws.conventions);
+        all_gotchas.extend(&ws.gotchas);
+    }
+    all_conventions.extend(&conventions.conventions);
+    all_gotchas.extend(&conventions.gotchas);
+
+    if !all_conventions.is_empty() {
+        output.push_str("\n## Conventions\n\n");
+        for (name, text) in &all_conventions {
+            output.push_str(&format!("- [ ] **{}**: {}\n", name, text));
+        }
+    }
+
+    let mut terms: Vec<String> = files.iter().flat_map(|f| file_terms(f)).collect();
+    terms.extend(concepts.iter().map(|(name, _, _)| name.to_string()));
+    let (applicable, other): (Vec<_>, Vec<_>) = all_gotchas.iter().partition(|(name, text)| {
+        let text = format!("{} {}", name, text);
+        terms.iter().any(|term| mentions(&text, term))
+    });
+    if !applicable.is_empty() {
+        output.push_str("\n## Gotchas\n\n");
+        for (name, text) in &applicable {
+            output.push_str(&format!("- [ ] **{}**: {}\n", name, text));
+        }
+    }
+    if !other.is_empty() {
+        output.push_str(&format!(
+            "\n*{} other gotcha(s) don't mention these files; see `get_conventions`.*\n",
+            other.len()
+        ));
+    }
+
+    if !concepts.is_empty() {
+        output.push_str("\n## Affected Concepts\n\n");
+        for (name, concept, touched) in &concepts {
+            output.push_str(&format!(
+                "- [ ] **{}**: {} (touches {})\n",
+                name,
+                concept.summary,
+                touched
+                    .iter()
+                    .map(|f| format!("`{}`", f))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ));
+        }
+    }
+
+    output.push_str("\n## Required Checks\n\n");
+    let required = config
+        .review
+        .as_ref()
+        .map(|review| review.required_checks.as_slice())
+        .unwrap_or_default();
+    if !required.is_empty() {
+        for check in required {
+            output.push_str(&format!("- [ ] `{}` passes\n", check));
+        }
+    } else {
+        let commands: Vec<(&str, &String)> = REVIEW_COMMANDS
+            .iter()
+            .filter_map(|name| config.commands.get(*name).map(|cmd| (*name, cmd)))
+            .collect();
+        if commands.is_empty() {
+            output.push_str(
+                "None configured. Add `required_checks` under `[review]` in project.toml.\n",
+            );
+        }
+        for (name, command) in commands {
+            output.push_str(&format!("- [ ] {}: `{}` passes\n", name, command));
+        }
+    }
+    Ok(output)
+}
+
+pub fn get_tests_for(
+    projects: &HashMap<String, ProjectData>,
+    file_index: &FileIndex,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+    let changed: Vec<&str> = args
+        .get("files")
+        .and_then(|v| v.as_array())
+        .ok_or("Missing 'files' argument")?
+        .iter()
+        .filter_map(|f| f.as_str())
+        .map(|f| f.trim_start_matches("./"))
+        .collect();
+    if changed.is_empty() {
+        return Err("'files' must list at least one file".to_string());
+    }
+    let (path, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let found = testmap::tests_for(&file_index.files(path), &changed, config.test_map.as_ref());
+    let mut output = format!("# Tests for {}\n\n", project_name);
+    if found.is_empty() {
+        output.push_str("No test files found for these files.\n");
+    }
+    for (test, reason) in &found {
+        output.push_str(&format!("- `{}`: {}", test, reason));
+        let known: Vec<String> = known_issues_for(config, test)
+            .iter()
+            .map(|(name, _)| format!("`{}`", name))
+            .collect();
+        if !known.is_empty() {
+            output.push_str(&format!(" (known issue: {})", known.join(", ")));
+        }
+        output.push('\n');
+    }
+
+    let tests: Vec<&str> = found.keys().map(String::as_str).collect();
+    output.push_str("\n## Run\n\n");
+    match testmap::run_command(config, &tests) {
+        Some(command) => output.push_str(&format!("```\n{}\n```\n", command)),
+        None => match config.commands.get("test") {
+            Some(test) => output.push_str(&format!(
+                "No command for running single test files is known; run the whole suite with `{}`, or set `run` under `[test_map]`.\n",
+                test
+            )),
+            None => output.push_str(
+                "No test command configured. Add `test` under `[commands]` or `run` under `[test_map]`.\n",
+            ),
+        },
+    }
+    Ok(output)
+}
+
+/// Known issues whose `test` contains `test` or is contained in it, so a
+/// file path finds issues naming one test in it and vice versa. Blank names
+/// on either side match nothing.
+fn known_issues_for<'a>(
+    config: &'a ProjectConfig,
+    test: &str,
+) -> Vec<(&'a String, &'a KnownIssue)> {
+    let test = test.trim().to_lowercase();
+    if test.is_empty() {
+        return Vec::new();
+    }
+    let mut issues: Vec<(&String, &KnownIssue)> = config
+        .known_issues
+        .iter()
+        .filter(|(_, issue)| {
+            issue.test.as_ref().is_some_and(|known| {
+                let known = known.trim().to_lowercase();
+                !known.is_empty() && (known.contains(&test) || test.contains(&known))
+            })
+        })
+        .collect();
+    issues.sort_by(|a, b| a.0.cmp(b.0));
+    issues
+}
+
+pub fn get_known_issues(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let (_, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    if config.known_issues.is_empty() {
+        return Ok(format!(
+            "No [known_issues] recorded for project '{}'.",
+            project_name
+        ));
+    }
+
+    // An empty `test` is the same as none: every issue.
+    let test = args
+        .get("test")
+        .and_then(|v| v.as_str())
+        .filter(|test| !test.trim().is_empty());
+    let issues = match test {
+        Some(test) => {
+            let issues = known_issues_for(config, test);
+            if issues.is_empty() {
+                return Ok(format!(
+                    "'{}' is not a known issue in '{}'. Treat the failure as new.",
+                    test, project_name
+                ));
+            }
+            issues
+        }
+        None => {
+            let mut issues: Vec<(&String, &KnownIssue)> = config.known_issues.iter().collect();
+            issues.sort_by(|a, b| {
+                (a.1.status != KnownIssueStatus::Failing, a.0)
+                    .cmp(&(b.1.status != KnownIssueStatus::Failing, b.0))
+            });
+            issues
+        }
+    };
+
+    let tracker = config
+        .tracker
+        .as_ref()
+        .or_else(|| workspace.as_ref().and_then(|ws| ws.tracker.as_ref()));
+    let mut output = format!("# Known Issues for '{}'\n", project_name);
+    for (name, issue) in issues {
+        let status = match issue.status {
+            KnownIssueStatus::Flaky => "flaky",
+            KnownIssueStatus::Failing => "failing",
+        };
+        output.push_str(&format!(
+            "\n## {} ({})\n\n{}\n\n",
+            name, status, issue.summary
+        ));
+        if let Some(test) = &issue.test {
+            output.push_str(&format!("**Test:** `{}`\n", test));
+        }
+        if let Some(id) = &issue.ticket {
+            match tracker.and_then(|tracker| tracker::ticket(tracker, id).ok()) {
+                Some(ticket) => output.push_str(&format!(
+                    "**Ticket:** [{}]({})\n",
+                    ticket.reference, ticket.url
+                )),
+                None => output.push_str(&format!("**Ticket:** {}\n", id)),
+            }
+        }
+        if let Some(workaround) = &issue.workaround {
+            output.push_str(&format!("**Workaround:** {}\n", workaround));
+        }
+    }
+    Ok(output)
+}
+
+pub fn get_workflow(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let (_, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let workspace_workflow = workspace.as_ref().and_then(|ws| ws.workflow.as_ref());
+    let (workflow, source) = match (&config.workflow, workspace_workflow) {
+        (Some(workflow), _) => (workflow, "project.toml"),
+        (None, Some(workflow)) => (workflow, "workspace.toml"),
+        (None, None) => {
+            return Ok(format!(
+                "No [workflow] configured for project '{}'. Add one to {} or {}.",
+                project_name,
+                crate::paths::config_file("project.toml"),
+                crate::paths::config_file("workspace.toml")
+            ))
+        }
+    };
+
+    let mut output = format!("# Workflow for '{}'\n\n", project_name);
+    let fields = [
+        ("Branching", workflow.branching.clone()),
+        (
+            "Base branch",
+            workflow.base_branch.as_ref().map(|b| format!("`{}`", b)),
+        ),
+        ("Pull request size", workflow.pr_size.clone()),
+        (
+            "Required approvals",
+            workflow.required_approvals.map(|n| n.to_string()),
+        ),
+        ("Review", workflow.review.clone()),
+        ("Merge strategy", workflow.merge_strategy.clone()),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            output.push_str(&format!("**{}:** {}\n", label, value));
+        }
+    }
+    let tracker = config
+        .tracker
+        .as_ref()
+        .or_else(|| workspace.as_ref().and_then(|ws| ws.tracker.as_ref()));
+    if let Some(format) = tracker.and_then(|t| t.branch_format.as_ref()) {
+        output.push_str(&format!("**Branch names:** `{}`\n", format));
+    }
+    if let Some(format) = tracker.and_then(|t| t.commit_format.as_ref()) {
+        output.push_str(&format!("**Commit messages:** `{}`\n", format));
+    }
+    if !workflow.notes.is_empty() {
+        output.push_str("\n## Notes\n\n");
+        for note in &workflow.notes {
+            output.push_str(&format!("- {}\n", note));
+        }
+    }
+    output.push_str(&format!("\n*From `[workflow]` in {}.*\n", source));
+    Ok(output)
+}
+
+pub fn get_performance_info(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let (_, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let Some(performance) = &config.performance else {
+        return Ok(format!(
+            "No [performance] section configured for project '{}'.",
+            project_name
+        ));
+    };
+    fn sorted(map: &HashMap<String, String>) -> Vec<(&String, &String)> {
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort();
+        entries
+    }
+
+    let mut output = format!("# Performance for '{}'\n", project_name);
+    if !performance.budgets.is_empty() {
+        output.push_str("\n## Budgets\n\n");
+        for (metric, limit) in sorted(&performance.budgets) {
+            output.push_str(&format!("- **{}**: {}\n", metric, limit));
+        }
+    }
+    if !performance.benchmarks.is_empty() {
+        output.push_str("\n## Benchmarks\n\n");
+        for (name, command) in sorted(&performance.benchmarks) {
+            output.push_str(&format!("- **{}**: `{}`\n", name, command));
+        }
+    }
+    if !performance.profiling.is_empty() {
+        output.push_str("\n## Profiling\n\n");
+        for (kind, how) in sorted(&performance.profiling) {
+            output.push_str(&format!("- **{}**: {}\n", kind, how));
+        }
+    }
+    if let Some(baseline) = &performance.baseline {
+        output.push_str(&format!("\n**Baseline:** {}\n", baseline));
+    }
+    if !performance.benchmarks.is_empty() {
+        output.push_str(
+            "\n*Run the benchmarks before and after the change and compare against the budgets.*\n",
+        );
+    }
+    Ok(output)
+}
+
+/// The README, whose `#### tool` sections document each tool.
+const README: &str = include_str!("../README.md");
+
+/// Tools usually called after each tool.
+const FOLLOW_UPS: &[(&str, &[&str])] = &[
+    (
+        "get_workspace_overview",
+        &[
+            "get_project_info",
+            "list_groups",
+            "get_workspace_conventions",
+        ],
+    ),
+    ("list_projects", &["get_project_info"]),
+    ("list_groups", &["list_projects", "get_workspace_overview"]),
+    (
+        "get_project_info",
+        &["get_commands", "get_conventions", "get_architecture"],
+    ),
+    ("get_commands", &["record_command_result", "get_last_run"]),
+    ("get_architecture", &["get_related_files", "get_skill"]),
+    ("get_related_files", &["get_code_owners", "get_tests_for"]),
+    ("list_skills", &["get_skill"]),
+    ("get_conventions", &["get_review_checklist"]),
+    ("get_docs", &["get_related_files"]),
+    ("get_context_diff", &["get_review_checklist"]),
+    ("get_review_checklist", &["get_tests_for", "get_workflow"]),
+    (
+        "get_tests_for",
+        &["get_known_issues", "record_command_result"],
+    ),
+    ("get_known_issues", &["get_ticket_link"]),
+    ("record_command_result", &["get_last_run"]),
+    ("store_memory", &["search_memories"]),
+    ("list_memories", &["get_memory"]),
+    ("search_memories", &["get_memory"]),
+    ("reload_workspace", &["get_workspace_overview"]),
+    (
+        "get_session_summary",
+        &["get_project_info", "get_conventions"],
+    ),
+];
+
+/// Arguments carried over from a call into the follow-ups it suggests.
+const CARRIED_ARGS: &[&str] = &["project", "files"];
+
+/// A "Next steps" footer for a successful `tool` call made with `args`:
+/// the follow-ups from `[next_steps.tools]` or [`FOLLOW_UPS`], with the
+/// call's `project` and `files` filled in where the follow-up takes them.
+pub fn next_steps(tool: &str, args: &Value, config: &NextStepsConfig) -> Option<String> {
+    let follow_ups: Vec<&str> = match config.tools.get(tool) {
+        Some(tools) => tools.iter().map(String::as_str).collect(),
+        None => FOLLOW_UPS
+            .iter()
+            .find(|(name, _)| *name == tool)
+            .map(|(_, next)| next.to_vec())
+            .unwrap_or_default(),
+    };
+    if follow_ups.is_empty() {
+        return None;
+    }
+    let list = tools_list();
+    let calls: Vec<String> = follow_ups
+        .iter()
+        .map(|next| {
+            let properties = list["tools"]
+                .as_array()
+                .and_then(|tools| tools.iter().find(|t| t["name"] == *next))
+                .map(|t| t["inputSchema"]["properties"].clone())
+                .unwrap_or(Value::Null);
+            let carried: Vec<String> = CARRIED_ARGS
+                .iter()
+                .filter(|arg| properties.get(**arg).is_some())
+                .filter_map(|arg| args.get(*arg).map(|value| format!("{}: {}", arg, value)))
+                .collect();
+            format!("`{}({})`", next, carried.join(", "))
+        })
+        .collect();
+    Some(format!("\n---\n**Next steps:** {}\n", calls.join(", ")))
+}
+
+/// The body of the README section headed by `tool`, including headings
+/// that name several tools (`#### list_skills / get_skill`).
+fn readme_section(tool: &str) -> Option<String> {
+    let mut lines = README.lines();
+    lines.find(|line| {
+        line.strip_prefix("#### ")
+            .is_some_and(|heading| heading.split(" / ").any(|name| name.trim() == tool))
+    })?;
+    // A heading ends the section; `#` inside a code block doesn't.
+    let mut in_code = false;
+    let body: Vec<&str> = lines
+        .take_while(|line| {
+            if line.starts_with("```") {
+                in_code = !in_code;
+            }
+            in_code || !line.starts_with('#')
+        })
+        .collect();
+    let body = body.join("\n").trim().to_string();
+    (!body.is_empty()).then_some(body)
+}
+
+pub fn describe_tool(args: &Value) -> Result<String, String> {
+    let name = args
+        .get("name")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'name' argument")?;
+    let list = tools_list();
+    let definitions: Vec<&Value> = list["tools"]
+        .as_array()
+        .map(|tools| tools.iter().collect())
+        .unwrap_or_default();
+    let author = author_project_config_tool();
+    let description_of = |tool: &str| -> Option<String> {
+        definitions
+            .iter()
+            .copied()
+            .chain(std::iter::once(&author))
+            .find(|definition| definition["name"] == tool)
+            .and_then(|definition| definition["description"].as_str())
+            .map(str::to_string)
+    };
+    let definition = definitions
+        .iter()
+        .copied()
+        .chain(std::iter::once(&author))
+        .find(|definition| definition["name"] == name)
+        .ok_or_else(|| {
+            format!(
+                "Unknown tool '{}'. Only jumble's built-in tools can be described; see tools/list.",
+                name
+            )
+        })?;
+
+    let mut output = format!("# `{}`\n\n", name);
+    if let Some(description) = definition["description"].as_str() {
+        output.push_str(&format!("{}\n", description));
+    }
+
+    let schema = &definition["inputSchema"];
+    let required: Vec<&str> = schema["required"]
+        .as_array()
+        .map(|names| names.iter().filter_map(|n| n.as_str()).collect())
+        .unwrap_or_default();
+    if let Some(properties) = schema["properties"].as_object().filter(|p| !p.is_empty()) {
+        let mut params: Vec<(&String, &Value)> = properties.iter().collect();
+        params.sort_by_key(|(param, _)| (!required.contains(&param.as_str()), param.as_str()));
+        output.push_str("\n## Parameters\n\n");
+        for (param, spec) in params {
+            let kind = spec["type"].as_str().unwrap_or("any");
+            let flag = if required.contains(&param.as_str()) {
+                ", required"
+            } else {
+                ""
+            };
+            match spec["description"].as_str() {
+                Some(description) => output.push_str(&format!(
+                    "- `{}` ({}{}): {}\n",
+                    param, kind, flag, description
+                )),
+                None => output.push_str(&format!("- `{}` ({}{})\n", param, kind, flag)),
+            }
+        }
+    } else {
+        output.push_str("\nTakes no parameters.\n");
+    }
+
+    if let Some(properties) = definition["outputSchema"]["properties"].as_object() {
+        let fields: Vec<String> = properties.keys().map(|f| format!("`{}`", f)).collect();
+        output.push_str(&format!(
+            "\n## Structured Output\n\nResults also carry `structuredContent` with {}.\n",
+            fields.join(", ")
+        ));
+    }
+
+    if let Some(section) = readme_section(name) {
+        output.push_str(&format!("\n## Usage\n\n{}\n", section));
+    }
+
+    let follow_ups = FOLLOW_UPS
+        .iter()
+        .find(|(tool, _)| *tool == name)
+        .map(|(_, next)| *next)
+        .unwrap_or_default();
+    if !follow_ups.is_empty() {
+        output.push_str("\n## Typical Next Steps\n\n");
+        for next in follow_ups {
+            // The first sentence is enough to choose between them.
+            let summary = description_of(next)
+                .and_then(|d| {
+                    d.split(". ")
+                        .next()
+                        .map(|s| s.trim_end_matches('.').to_string())
+                })
+                .unwrap_or_default();
+            output.push_str(&format!("- `{}`: {}\n", next, summary));
+        }
+    }
+    Ok(output)
+}
+
+/// Items listed per section of `prepare_for_task`, at most.
+const MAX_TASK_ITEMS: usize = 5;
+
+/// Filler words left out when matching a task description.
+const TASK_STOP_WORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "into", "when", "what", "which", "should",
+    "before", "after", "need", "needs", "want", "some", "our", "are", "can", "not", "has", "have",
+    "will", "then", "also", "about", "any", "add", "fix", "make", "new", "use", "using", "change",
+];
+
+/// Words in a task description worth matching: lowercased, split on
+/// anything but letters, digits, `_` and `-`, without short words and
+/// filler.
+fn task_terms(description: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in description.split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-')) {
+        let word = word.trim_matches('-').to_lowercase();
+        if word.len() >= 3 && !TASK_STOP_WORDS.contains(&word.as_str()) && !terms.contains(&word) {
+            terms.push(word);
+        }
+    }
+    terms
+}
+
+/// How well `text` matches a task: two points per term it mentions, one
+/// per synonym. A plural term also matches its singular.
+fn score_task_text(text: &str, terms: &[String], synonyms: &[String]) -> u32 {
+    let hits = |words: &[String]| {
+        words
+            .iter()
+            .filter(|word| {
+                mentions(text, word)
+                    || word
+                        .strip_suffix('s')
+                        .is_some_and(|singular| singular.len() >= 3 && mentions(text, singular))
+            })
+            .count() as u32
+    };
+    hits(terms) * 2 + hits(synonyms)
+}
+
+/// The `(score, key, value)` entries of `entries` that match, best first.
+fn rank_task_entries<'a, T>(
+    entries: impl IntoIterator<Item = (&'a String, T, String)>,
+    terms: &[String],
+    synonyms: &[String],
+) -> Vec<(u32, &'a String, T)> {
+    let mut ranked: Vec<(u32, &String, T)> = entries
+        .into_iter()
+        .map(|(key, value, text)| (score_task_text(&text, terms, synonyms), key, value))
+        .filter(|(score, _, _)| *score > 0)
+        .collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+    ranked.truncate(MAX_TASK_ITEMS);
+    ranked
+}
+
+pub fn prepare_for_task(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+    let description = args
+        .get("description")
+        .and_then(|v| v.as_str())
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .ok_or("Missing 'description' argument")?;
+    let (path, config, skills, conventions, docs, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let terms = task_terms(description);
+    if terms.is_empty() {
+        return Err(
+            "'description' has no words to match; describe the task in a few words".to_string(),
+        );
+    }
+    let synonyms = synonyms_for(workspace, &terms);
+
+    // Concepts rank as in `get_related_files`.
+    let mut concepts: Vec<(u32, &String, &Concept)> = config
+        .concepts
+        .iter()
+        .map(|(name, concept)| {
+            let score =
+                score_concept(name, concept, &terms) + score_concept(name, concept, &synonyms) / 2;
+            (score, name, concept)
+        })
+        .filter(|(score, _, _)| *score > 0)
+        .collect();
+    concepts.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| b.2.pinned.cmp(&a.2.pinned))
+            .then_with(|| a.1.cmp(b.1))
+    });
+    concepts.truncate(MAX_TASK_ITEMS);
+
+    // Project conventions win over workspace ones with the same key.
+    let mut all_conventions: BTreeMap<&String, &String> = BTreeMap::new();
+    let mut all_gotchas: BTreeMap<&String, &String> = BTreeMap::new();
+    if let Some(ws) = workspace {
+        all_conventions.extend(&ws.conventions);
+        all_gotchas.extend(&ws.gotchas);
+    }
+    all_conventions.extend(&conventions.conventions);
+    all_gotchas.extend(&conventions.gotchas);
+    let gotchas = rank_task_entries(
+        all_gotchas
+            .into_iter()
+            .map(|(name, text)| (name, text, format!("{} {}", name, text))),
+        &terms,
+        &synonyms,
+    );
+    let conventions = rank_task_entries(
+        all_conventions
+            .into_iter()
+            .map(|(name, text)| (name, text, format!("{} {}", name, text))),
+        &terms,
+        &synonyms,
+    );
+
+    // Skills named by a matching concept come first, then ones that match
+    // on their own.
+    let mut skill_topics: Vec<&String> = concepts
+        .iter()
+        .flat_map(|(_, _, concept)| &concept.prompts)
+        .filter(|topic| skills.skills.contains_key(*topic))
+        .collect();
+    let skill_text = |topic: &String| {
+        let info = &skills.skills[topic];
+        let frontmatter = info.frontmatter.as_ref();
+        format!(
+            "{} {} {}",
+            topic.replace(['-', '_'], " "),
+            frontmatter
+                .and_then(|fm| fm.description.as_deref())
+                .unwrap_or(&info.preview),
+            frontmatter.map(|fm| fm.tags.join(" ")).unwrap_or_default()
+        )
+    };
+    let matching_skills = rank_task_entries(
+        skills
+            .skills
+            .keys()
+            .map(|topic| (topic, (), skill_text(topic))),
+        &terms,
+        &synonyms,
+    );
+    for (_, topic, _) in matching_skills {
+        if !skill_topics.contains(&topic) {
+            skill_topics.push(topic);
+        }
+    }
+    skill_topics.dedup();
+    skill_topics.truncate(MAX_TASK_ITEMS);
+
+    let doc_entries = rank_task_entries(
+        docs.docs.iter().map(|(name, doc)| {
+            let text = format!("{} {} {}", name, doc.summary, doc.path);
+            (name, doc, text)
+        }),
+        &terms,
+        &synonyms,
+    );
+    let commands = rank_task_entries(
+        config
+            .commands
+            .iter()
+            .map(|(name, command)| (name, command, format!("{} {}", name, command))),
+        &terms,
+        &synonyms,
+    );
+
+    if concepts.is_empty()
+        && gotchas.is_empty()
+        && conventions.is_empty()
+        && skill_topics.is_empty()
+        && doc_entries.is_empty()
+        && commands.is_empty()
+    {
+        return Err(format!(
+            "Nothing in {}'s context matches '{}'. Try `get_architecture` for an overview, or `get_related_files` with other words.",
+            project_name, description
+        ));
+    }
+
+    let mut output = format!(
+        "# Preparing: {}\n\n**Project:** {}\n",
+        description, project_name
+    );
+    if !synonyms.is_empty() {
+        output.push_str(&format!("*Also searched: {}*\n", synonyms.join(", ")));
+    }
+
+    if !concepts.is_empty() {
+        output.push_str("\n## Read First\n\n");
+        for (_, name, concept) in &concepts {
+            output.push_str(&format!("**{}**: {}\n", name, concept.summary));
+            for file in &concept.files {
+                output.push_str(&format_file_entry(path, file));
+            }
+            output.push('\n');
+        }
+        output.pop();
+    }
+    if !gotchas.is_empty() {
+        output.push_str("\n## Gotchas\n\n");
+        for (_, name, text) in &gotchas {
+            output.push_str(&format!("- **{}**: {}\n", name, text));
+        }
+    }
+    if !conventions.is_empty() {
+        output.push_str("\n## Conventions\n\n");
+        for (_, name, text) in &conventions {
+            output.push_str(&format!("- **{}**: {}\n", name, text));
+        }
+    }
+    if !skill_topics.is_empty() {
+        output.push_str("\n## Skills\n\n");
+        for topic in &skill_topics {
+            let info = &skills.skills[*topic];
+            match info
+                .frontmatter
+                .as_ref()
+                .and_then(|fm| fm.description.as_deref())
+                .filter(|d| !d.is_empty())
+            {
+                Some(description) => output.push_str(&format!("- `{}`: {}\n", topic, description)),
+                None => output.push_str(&format!("- `{}`\n", topic)),
+            }
+        }
+        output.push_str("\nFetch one with `get_skill` before starting.\n");
+    }
+    if !doc_entries.is_empty() {
+        output.push_str("\n## Docs\n\n");
+        for (_, name, doc) in &doc_entries {
+            output.push_str(&format!(
+                "- **{}** (`{}`): {}\n",
+                name, doc.path, doc.summary
+            ));
+        }
+    }
+    if !commands.is_empty() {
+        output.push_str("\n## Commands\n\n");
+        for (_, name, command) in &commands {
+            output.push_str(&format!("- {}: `{}`\n", name, command));
+        }
+    }
+    output.push_str(
+        "\n*Before finishing, `get_review_checklist` with the changed files lists the checks to run.*\n",
+    );
+    Ok(output)
+}
+
+pub fn verify_context(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+    let (path, config, _, _, docs, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    let report = freshness::probe(path, config, docs, &path_var, freshness::PROBE_BUDGET);
+    Ok(freshness::format_freshness(project_name, &report))
+}
+
+pub fn get_dev_endpoints(
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+) -> Result<String, String> {
+    let registry = match workspace {
+        Some(ws) if !ws.dev_ports.is_empty() => &ws.dev_ports,
+        _ => {
+            return Ok(format!(
+                "No [dev_ports] registry configured in {}.",
+                crate::paths::config_file("workspace.toml")
+            ))
+        }
+    };
+
+    let mut services: Vec<(&String, Option<u16>)> = registry
+        .iter()
+        .map(|(service, endpoint)| (service, ports::port_of(endpoint)))
+        .collect();
+    services.sort_by(|a, b| (a.1.is_none(), a.1, a.0).cmp(&(b.1.is_none(), b.1, b.0)));
+
+    let mut output = "# Local Development Endpoints\n\n".to_string();
+    output.push_str("| Service | Port | URL | Project | Notes |\n|---|---:|---|---|---|\n");
+    for (service, port) in &services {
+        let endpoint = &registry[*service];
+        let project = match endpoint.project.as_deref() {
+            Some(p) if projects.contains_key(p) => p.to_string(),
+            Some(p) => format!("{} (not in workspace)", p),
+            None => String::new(),
+        };
+        output.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            service,
+            port.map(|p| p.to_string()).unwrap_or_default(),
+            endpoint.url.as_deref().unwrap_or(""),
+            project,
+            endpoint.summary.as_deref().unwrap_or("")
+        ));
+    }
+
+    if let Some(note) = workspace
+        .as_ref()
+        .and_then(|ws| local_overrides_note(&ws.local_overrides, "dev_ports."))
+    {
+        output.push_str(&format!("\n{}\n", note));
+    }
+
+    let conflicts = ports::conflicts(registry);
+    if !conflicts.is_empty() {
+        output.push_str("\n## Port Conflicts\n\n");
+        for (port, services) in conflicts {
+            output.push_str(&format!("- **{}**: {}\n", port, services.join(", ")));
+        }
+    }
+    output.push_str(&format!(
+        "\n*Register new services under [dev_ports] in {} with a port not listed here.*\n",
+        crate::paths::config_file("workspace.toml")
+    ));
+    Ok(output)
+}
+
+/// Topics across the workspace: where each is described and which projects
+/// publish and subscribe to it.
+#[derive(Default)]
+struct MessagingCatalog<'a> {
+    definitions: HashMap<&'a str, (&'a str, &'a MessageTopic)>,
+    producers: HashMap<&'a str, Vec<&'a str>>,
+    consumers: HashMap<&'a str, Vec<&'a str>>,
+}
+
+impl<'a> MessagingCatalog<'a> {
+    fn new(projects: &'a HashMap<String, ProjectData>) -> Self {
+        let mut catalog = Self::default();
+        let mut names: Vec<&String> = projects.keys().collect();
+        names.sort();
+        for name in names {
+            let Some(messaging) = &projects[name].1.messaging else {
+                continue;
+            };
+            for (topic, definition) in &messaging.topics {
+                catalog
+                    .definitions
+                    .entry(topic)
+                    .or_insert((name, definition));
+            }
+            for topic in &messaging.publishes {
+                catalog.producers.entry(topic).or_default().push(name);
+            }
+            for topic in &messaging.subscribes {
+                catalog.consumers.entry(topic).or_default().push(name);
+            }
+        }
+        catalog
+    }
+
+    fn contains(&self, topic: &str) -> bool {
+        self.definitions.contains_key(topic)
+            || self.producers.contains_key(topic)
+            || self.consumers.contains_key(topic)
+    }
+
+    /// `` `topic` (kind): summary`` for a list entry.
+    fn describe(&self, topic: &str) -> String {
+        let mut line = format!("`{}`", topic);
+        if let Some((_, definition)) = self.definitions.get(topic) {
+            if let Some(kind) = &definition.kind {
+                line.push_str(&format!(" ({})", kind));
+            }
+            line.push_str(&format!(": {}", definition.summary));
+        }
+        line
+    }
+}
+
+fn project_list(projects: Option<&Vec<&str>>) -> String {
+    match projects {
+        Some(projects) if !projects.is_empty() => projects.join(", "),
+        _ => "none listed".to_string(),
+    }
+}
+
+pub fn get_messaging_info(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let (_, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+    let catalog = MessagingCatalog::new(projects);
+
+    if let Some(topic) = args.get("topic").and_then(|v| v.as_str()) {
+        if !catalog.contains(topic) {
+            let mut known: BTreeSet<&str> = catalog.definitions.keys().copied().collect();
+            known.extend(catalog.producers.keys());
+            known.extend(catalog.consumers.keys());
+            let known: Vec<&str> = known.into_iter().collect();
+            return Err(format!(
+                "Topic '{}' is not described or used by any project. Known topics: {}",
+                topic,
+                known.join(", ")
+            ));
+        }
+        let mut output = format!("# Topic `{}`\n\n", topic);
+        if let Some((owner, definition)) = catalog.definitions.get(topic) {
+            if let Some(kind) = &definition.kind {
+                output.push_str(&format!("**Kind:** {}\n", kind));
+            }
+            output.push_str(&format!("**Described in:** {}\n", owner));
+            if let Some(schema) = &definition.schema {
+                output.push_str(&format!("**Schema:** `{}` (in {})\n", schema, owner));
+            }
+            output.push_str(&format!("\n{}\n\n", definition.summary));
+        }
+        output.push_str(&format!(
+            "**Producers:** {}\n**Consumers:** {}\n",
+            project_list(catalog.producers.get(topic)),
+            project_list(catalog.consumers.get(topic))
+        ));
+        return Ok(output);
+    }
+
+    let Some(messaging) = &config.messaging else {
+        return Ok(format!(
+            "No [messaging] section configured for project '{}'.",
+            project_name
+        ));
+    };
+
+    let mut output = format!("# Messaging for '{}'\n", project_name);
+    if let Some(broker) = &messaging.broker {
+        output.push_str(&format!("\n**Broker:** {}\n", broker));
+    }
+    if !messaging.publishes.is_empty() {
+        output.push_str("\n## Publishes\n");
+        for topic in &messaging.publishes {
+            output.push_str(&format!(
+                "- {} — consumers: {}\n",
+                catalog.describe(topic),
+                project_list(catalog.consumers.get(topic.as_str()))
+            ));
+        }
+    }
+    if !messaging.subscribes.is_empty() {
+        output.push_str("\n## Subscribes\n");
+        for topic in &messaging.subscribes {
+            output.push_str(&format!(
+                "- {} — producers: {}\n",
+                catalog.describe(topic),
+                project_list(catalog.producers.get(topic.as_str()))
+            ));
+        }
+    }
+
+    let mut described: Vec<&String> = messaging
+        .topics
+        .keys()
+        .filter(|t| !messaging.publishes.contains(t) && !messaging.subscribes.contains(t))
+        .collect();
+    described.sort();
+    if !described.is_empty() {
+        output.push_str("\n## Also Described Here\n");
+        for topic in described {
+            output.push_str(&format!("- {}\n", catalog.describe(topic)));
+        }
+    }
+    output.push_str("\n*Pass `topic` for a topic's schema, producers and consumers.*\n");
+    Ok(output)
+}
+
+pub fn get_api_examples(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+    let filter = args
+        .get("endpoint")
+        .and_then(|v| v.as_str())
+        .map(str::to_lowercase);
+
+    let (path, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let examples = config
+        .api
+        .as_ref()
+        .map(|api| &api.examples)
+        .filter(|examples| !examples.is_empty())
+        .ok_or_else(|| {
+            format!(
+                "No API examples configured for project '{}'. Add them under [api.examples] in {}.",
+                project_name,
+                crate::paths::config_file("project.toml")
+            )
+        })?;
+
+    let mut names: Vec<&String> = examples
+        .iter()
+        .filter(|(name, example)| {
+            filter.as_deref().is_none_or(|filter| {
+                name.to_lowercase().contains(filter)
+                    || example
+                        .endpoint
+                        .as_deref()
+                        .is_some_and(|e| e.to_lowercase().contains(filter))
+            })
+        })
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    if names.is_empty() {
+        let mut endpoints: Vec<&str> = examples
+            .values()
+            .filter_map(|e| e.endpoint.as_deref())
+            .collect();
+        endpoints.sort();
+        endpoints.dedup();
+        return Err(format!(
+            "No API examples in project '{}' match '{}'. Endpoints with examples: {}",
+            project_name,
+            filter.unwrap_or_default(),
+            endpoints.join(", ")
+        ));
+    }
+
+    let mut output = format!("# API examples for '{}'\n", project_name);
+    for name in names {
+        let example = &examples[name];
+        output.push_str(&format!("\n## {}\n", name));
+        if let Some(endpoint) = &example.endpoint {
+            output.push_str(&format!("**Endpoint:** `{}`\n", endpoint));
+        }
+        if let Some(summary) = &example.summary {
+            output.push_str(&format!("{}\n", summary));
+        }
+        if let Some(snippet) = &example.snippet {
+            let lang = if snippet.trim_start().starts_with("curl ") {
+                "bash"
+            } else {
+                ""
+            };
+            output.push_str(&format!("\n```{}\n{}\n```\n", lang, snippet.trim()));
+        }
+        if let Some(file) = &example.file {
+            let file_path = crate::paths::join_relative(path, file);
+            match crate::text::read_text(&file_path) {
+                Ok(content) => {
+                    let lang = match file_path.extension().and_then(|e| e.to_str()) {
+                        Some("sh") => "bash",
+                        Some(ext) => ext,
+                        None => "",
+                    };
+                    output.push_str(&format!(
+                        "\n`{}`:\n```{}\n{}\n```\n",
+                        file,
+                        lang,
+                        content.trim_end()
+                    ));
+                }
+                Err(_) => output.push_str(&format!("\n*Example file `{}` not found.*\n", file)),
+            }
+        }
+    }
+    Ok(output)
+}
+
+pub fn get_format_settings(
+    root: &std::path::Path,
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<String, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+    let language = args.get("language").and_then(|v| v.as_str());
+
+    let (path, _, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let scope = language
+        .map(|language| format!(" ({})", language))
+        .unwrap_or_default();
+    let sources = style::detect(path, root, language);
+    if sources.is_empty() {
+        return Ok(format!(
+            "No formatter configuration (.editorconfig, rustfmt, prettier, black or ruff) found for project '{}'{}.",
+            project_name, scope
+        ));
+    }
+
+    let mut output = format!("# Format settings for '{}'{}\n", project_name, scope);
+    for source in &sources {
+        output.push_str(&format!("\n## {} (`{}`)\n", source.tool, source.file));
+        for section in &source.sections {
+            if let Some(pattern) = &section.pattern {
+                output.push_str(&format!("\n**`[{}]`**\n", pattern));
+            }
+            if section.settings.is_empty() {
+                output.push_str("\nNo settings could be read; check the file directly.\n");
+            }
+            for (key, value) in &section.settings {
+                output.push_str(&format!("- `{}`: {}\n", key, value));
+            }
+        }
+    }
+    if sources.iter().filter(|s| s.tool == "editorconfig").count() > 1 {
+        output.push_str(
+            "\n*Files are listed nearest first; nearer `.editorconfig` files and later sections take precedence.*\n",
+        );
+    }
+    Ok(output)
+}
+
+/// Authors listed by `get_code_owners` when inferring from git blame.
+const MAX_INFERRED_OWNERS: usize = 5;
+
+/// What `get_code_owners` found before running git blame.
+pub enum OwnersLookup {
+    /// CODEOWNERS answered, or blame is off: the finished output.
+    Answered(String),
+    /// Owners are inferred by blaming `files` in `dir`, after `output`.
+    Blame {
+        dir: PathBuf,
+        files: Vec<String>,
+        output: String,
+    },
+}
+
+/// [`look_up_code_owners`] and [`blame_code_owners`] in one call.
+#[cfg(test)]
+pub fn get_code_owners(
+    root: &std::path::Path,
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    file_index: &FileIndex,
+    args: &Value,
+) -> Result<String, String> {
+    look_up_code_owners(root, projects, workspace, file_index, args).and_then(blame_code_owners)
+}
+
+/// The part of `get_code_owners` that reads loaded context. Blame runs a
+/// process per file, so the server calls [`blame_code_owners`] once it has
+/// released the discovery lock.
+pub fn look_up_code_owners(
+    root: &std::path::Path,
+    projects: &HashMap<String, ProjectData>,
+    workspace: &Option<WorkspaceConfig>,
+    file_index: &FileIndex,
+    args: &Value,
+) -> Result<OwnersLookup, String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+
+    let relative = args
+        .get("path")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .replace('\\', "/");
+    let relative = relative.trim_end_matches('/').trim_start_matches("./");
+    let inside = std::path::Path::new(relative)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !inside {
+        return Err("'path' must stay inside the project".to_string());
+    }
+
+    let (path, config, _, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+
+    let target = if relative.is_empty() || relative == "." {
+        path.clone()
+    } else {
+        path.join(relative)
+    };
+    if !target.exists() {
+        return Err(format!(
+            "'{}' does not exist in project '{}'",
+            relative, project_name
+        ));
+    }
+    let shown = if target == *path { "." } else { relative };
+
+    let mut output = format!("# Code owners for `{}` in '{}'\n\n", shown, project_name);
+    if let Some(codeowners) = owners::find_codeowners(&[path.as_path(), root]) {
+        let content = crate::text::read_text(&codeowners.path)
+            .map_err(|e| format!("Failed to read {}: {}", codeowners.path.display(), e))?;
+        let file = codeowners
+            .path
+            .strip_prefix(&codeowners.base)
+            .unwrap_or(&codeowners.path)
+            .display()
+            .to_string()
+            .replace('\\', "/");
+        match owners::owners_for(&content, &codeowners.base, &target, target.is_dir()) {
+            Some((pattern, owners)) if !owners.is_empty() => output.push_str(&format!(
+                "`{}` assigns `{}` to: {}\n",
+                file,
+                pattern,
+                owners.join(", ")
+            )),
+            _ => output.push_str(&format!("`{}` assigns no owner to this path.\n", file)),
+        }
+        return Ok(OwnersLookup::Answered(output));
+    }
+
+    let enabled = config.project.infer_code_owners
+        || workspace
+            .as_ref()
+            .is_some_and(|ws| ws.workspace.infer_code_owners);
+    if !enabled {
+        output.push_str(
+            "No CODEOWNERS file found. Set `infer_code_owners = true` under [project] or [workspace] to infer owners from git blame.\n",
+        );
+        return Ok(OwnersLookup::Answered(output));
+    }
+
+    let prefix = if shown == "." {
+        String::new()
+    } else if target.is_dir() {
+        format!("{}/", relative)
+    } else {
+        relative.to_string()
+    };
+    let files: Vec<String> = file_index
+        .files(path)
+        .iter()
+        .filter(|f| {
+            prefix.is_empty() || **f == prefix || (prefix.ends_with('/') && f.starts_with(&prefix))
+        })
+        .take(owners::MAX_BLAME_FILES)
+        .cloned()
+        .collect();
+    Ok(OwnersLookup::Blame {
+        dir: path.clone(),
+        files,
+        output,
+    })
+}
+
+/// Finish a [`look_up_code_owners`] result, running git blame if it needs it.
+pub fn blame_code_owners(lookup: OwnersLookup) -> Result<String, String> {
+    let (dir, files, mut output) = match lookup {
+        OwnersLookup::Answered(output) => return Ok(output),
+        OwnersLookup::Blame { dir, files, output } => (dir, files, output),
+    };
+    let authors = owners::blame(&dir, &files, chrono::Utc::now().timestamp())?;
+    if authors.is_empty() {
+        output.push_str("No CODEOWNERS file found, and git blame found no committed lines here.\n");
+        return Ok(output);
+    }
+
+    output.push_str("No CODEOWNERS file found; inferred from git blame.\n\n");
+    output
+        .push_str("| Author | Lines | Changed in last year | Last change |\n|---|---:|---:|---|\n");
+    for author in authors.iter().take(MAX_INFERRED_OWNERS) {
+        let last_change = chrono::DateTime::from_timestamp(author.last_change, 0)
+            .map(|t| t.format("%Y-%m-%d").to_string())
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "| {} <{}> | {} | {} | {} |\n",
+            author.name, author.email, author.lines, author.recent_lines, last_change
+        ));
+    }
+    let capped = if files.len() == owners::MAX_BLAME_FILES {
+        format!(" (first {} files)", owners::MAX_BLAME_FILES)
+    } else {
+        String::new()
+    };
+    output.push_str(&format!(
+        "\n*Based on {} file(s){}. Suggest the top authors as reviewers.*\n",
+        files.len(),
+        capped
+    ));
+    Ok(output)
+}
+
+pub fn init_project(_workspace_root: &std::path::PathBuf, args: &Value) -> Result<String, String> {
+    // Get the target directory from arguments (required)
+    let dir_str = args
+        .get("directory")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'directory' argument")?;
+
+    let target_dir = std::path::PathBuf::from(dir_str);
+
+    // Ensure the directory exists or can be created
+    if !target_dir.exists() {
+        std::fs::create_dir_all(&target_dir).map_err(|e| {
+            format!(
+                "Failed to create directory '{}': {}",
+                target_dir.display(),
+                e
+            )
+        })?;
+    }
+
+    // Use the setup module's init function. Stdout carries the MCP stream, so it stays quiet.
+    let mut report = crate::setup::Reporter::new(crate::setup::OutputMode::Quiet, "init");
+    crate::setup::setup_init(&target_dir, &mut report)
+        .map_err(|e| format!("Failed to initialize project: {}", e))?;
+
+    let mut output = format!(
+        "Project initialized successfully in {}.\n\n",
+        target_dir.display()
+    );
+    for file in report.files() {
+        output.push_str(&format!(
+            "- {}: {}\n",
+            file.action.as_str(),
+            file.path.display()
+        ));
+    }
+    Ok(output)
+}
+
+/// Build the `tools/list` entry for a workspace-defined tool.
+pub fn custom_tool_definition(name: &str, tool: &CustomTool) -> Value {
+    let mut properties = serde_json::Map::new();
+    let names = crate::exec::placeholders(&tool.command);
+    for arg in &names {
+        let description = tool
+            .args
+            .get(arg)
+            .cloned()
+            .unwrap_or_else(|| format!("Value substituted for {{{{{}}}}}", arg));
+        properties.insert(
+            arg.clone(),
+            json!({
+                "type": "string",
+                "description": description
+            }),
+        );
+    }
+
+    json!({
+        "name": name,
+        "description": tool.description,
+        "inputSchema": {
+            "type": "object",
+            "properties": properties,
+            "required": names
+        }
+    })
+}
+
+/// Run a workspace-defined tool from the workspace root under the exec
+/// sandbox rules.
+pub fn run_custom_tool(
+    root: &std::path::Path,
+    name: &str,
+    tool: &CustomTool,
+    args: &Value,
+    cancel: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+) -> Result<String, String> {
+    let mut values = HashMap::new();
+    for arg in crate::exec::placeholders(&tool.command) {
+        let value = match args.get(&arg) {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Number(n)) => n.to_string(),
+            Some(Value::Bool(b)) => b.to_string(),
+            _ => return Err(format!("Missing '{}' argument", arg)),
+        };
+        values.insert(arg, value);
+    }
+
+    let argv = crate::exec::render_argv(&tool.command, &values)?;
+    let mut policy = crate::exec::ExecPolicy {
+        cancel,
+        ..Default::default()
+    };
+    if let Some(secs) = tool.timeout_secs {
+        policy.timeout = std::time::Duration::from_secs(secs);
+    }
+
+    let output = crate::exec::run(&argv, root, &policy)?;
+    let text = crate::exec::format_output(name, &output);
+    if output.success() {
+        Ok(text)
+    } else {
+        Err(text)
+    }
+}
+
+/// The project directory and argv for a `run_skill_script` call: the
+/// command `get_skill` lists for the named script, followed by `args`.
+/// Only scripts in the skill's own `scripts/` directory can be named.
+pub fn skill_script_invocation(
+    projects: &HashMap<String, ProjectData>,
+    args: &Value,
+) -> Result<(std::path::PathBuf, Vec<String>), String> {
+    let project_name = args
+        .get("project")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'project' argument")?;
+    let topic = args
+        .get("topic")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'topic' argument")?;
+    let script = args
+        .get("script")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing 'script' argument")?;
+
+    let (project_dir, _, skills, _, _, _) = projects
+        .get(project_name)
+        .ok_or_else(|| format!("Project '{}' not found", project_name))?;
+    let skill = skills
+        .skills
+        .get(topic)
+        .ok_or_else(|| format!("Skill '{}' not found in '{}'", topic, project_name))?;
+    let scripts = skill
+        .skill_dir
+        .as_deref()
+        .map(skill_scripts)
+        .unwrap_or_default();
+    let names: Vec<&str> = scripts
+        .iter()
+        .filter_map(|(path, _)| path.file_name()?.to_str())
+        .collect();
+    let Some((_, command)) = scripts
+        .iter()
+        .find(|(path, _)| path.file_name().and_then(|n| n.to_str()) == Some(script))
+    else {
+        return Err(if names.is_empty() {
+            format!("Skill '{}' has no helper scripts", topic)
+        } else {
+            format!(
+                "Script '{}' not found. Available: {}",
+                script,
+                names.join(", ")
+            )
+        });
+    };
+
+    let mut argv = crate::exec::split_command(command)?;
+    match args.get("args") {
+        None | Some(Value::Null) => {}
+        Some(Value::Array(values)) => {
+            for value in values {
+                argv.push(
+                    value
+                        .as_str()
+                        .ok_or("'args' must be an array of strings")?
+                        .to_string(),
+                );
+            }
+        }
+        Some(_) => return Err("'args' must be an array of strings".to_string()),
+    }
+    Ok((project_dir.clone(), argv))
+}
+
+/// Run a skill's helper script from its project directory under the exec
+/// sandbox rules.
+pub fn run_skill_script(
+    project_dir: &std::path::Path,
+    argv: &[String],
+    cancel: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+) -> Result<String, String> {
+    let policy = crate::exec::ExecPolicy {
+        cancel,
+        ..Default::default()
+    };
+    let output = crate::exec::run(argv, project_dir, &policy)?;
+    let text = crate::exec::format_output("run_skill_script", &output);
+    if output.success() {
+        Ok(text)
+    } else {
+        Err(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::*;
+    use crate::memory;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    fn create_test_project() -> (String, ProjectData) {
+        let config = ProjectConfig {
+            project: ProjectInfo {
+                name: "test-project".to_string(),
+                description: "A test project".to_string(),
+                language: Some("rust".to_string()),
+                version: Some("1.0.0".to_string()),
+                repository: None,
+                tags: Vec::new(),
+                infer_code_owners: false,
+            },
+            commands: {
+                let mut map = HashMap::new();
+                map.insert("build".to_string(), "cargo build".to_string());
+                map.insert("test".to_string(), "cargo test".to_string());
+                map
+            },
+            profiles: HashMap::new(),
+            entry_points: {
+                let mut map = HashMap::new();
+                map.insert("main".to_string(), "src/main.rs".to_string());
+                map
+            },
+            dependencies: Dependencies {
+                internal: vec!["shared".to_string()],
+                external: vec!["serde".to_string()],
+            },
+            related_projects: RelatedProjects {
+                upstream: vec!["core".to_string()],
+                downstream: vec![],
+            },
+            api: None,
+            concepts: {
+                let mut map = HashMap::new();
+                map.insert(
+                    "authentication".to_string(),
+                    Concept {
+                        files: vec!["src/auth.rs".to_string()],
+                        summary: "JWT auth".to_string(),
+                        pinned: false,
+                        prompts: Vec::new(),
+                    },
+                );
+                map
+            },
+            tracker: None,
+            compliance: None,
+            workflow: None,
+            data: None,
+            messaging: None,
+            review: None,
+            test_map: None,
+            performance: None,
+            known_issues: HashMap::new(),
+            local_overrides: Vec::new(),
+        };
+
+        let skills = ProjectSkills::default();
+        let conventions = ProjectConventions {
             conventions: {
                 let mut map = HashMap::new();
                 map.insert("naming".to_string(), "Use snake_case".to_string());
                 map
             },
-            gotchas: {
-                let mut map = HashMap::new();
-                map.insert("async".to_string(), "Avoid blocking".to_string());
-                map
+            gotchas: {
+                let mut map = HashMap::new();
+                map.insert("async".to_string(), "Avoid blocking".to_string());
+                map
+            },
+        };
+        let docs = ProjectDocs {
+            docs: {
+                let mut map = HashMap::new();
+                map.insert(
+                    "readme".to_string(),
+                    DocEntry {
+                        path: "README.md".to_string(),
+                        summary: "Project readme".to_string(),
+                    },
+                );
+                map
+            },
+        };
+
+        // Create a temporary memory database for testing
+        let temp_dir = TempDir::new().unwrap();
+        let test_path = temp_dir.path().to_path_buf();
+        let memory_db = memory::open_or_create_memory_db(&test_path).unwrap();
+
+        (
+            "test-project".to_string(),
+            (
+                test_path.clone(),
+                config,
+                skills,
+                conventions,
+                docs,
+                memory_db,
+            ),
+        )
+    }
+
+    fn create_test_projects() -> HashMap<String, ProjectData> {
+        let mut projects = HashMap::new();
+        let (name, data) = create_test_project();
+        projects.insert(name, data);
+        projects
+    }
+
+    #[test]
+    fn test_list_projects_empty() {
+        let projects = HashMap::new();
+        let result = list_projects(&projects, &None, &json!({})).unwrap();
+        assert!(result.contains("No projects found"));
+    }
+
+    #[test]
+    fn test_list_projects() {
+        let projects = create_test_projects();
+        let result = list_projects(&projects, &None, &json!({})).unwrap();
+        assert!(result.contains("test-project"));
+        assert!(result.contains("rust"));
+        assert!(result.contains("A test project"));
+    }
+
+    #[test]
+    fn test_list_projects_and_docs_are_paginated() {
+        let mut projects = create_test_projects();
+        for name in ["alpha", "beta"] {
+            let (_, mut data) = create_test_project();
+            data.1.project.name = name.to_string();
+            projects.insert(name.to_string(), data);
+        }
+
+        let first = list_projects(&projects, &None, &json!({"limit": 2})).unwrap();
+        assert!(first.contains("**alpha**") && first.contains("**beta**"));
+        assert!(first.ends_with(
+            "\nShowing 1-2 of 3 projects. Call list_projects again with cursor \"2\" for the next page.\n"
+        ));
+        let second = list_projects(&projects, &None, &json!({"limit": 2, "cursor": "2"})).unwrap();
+        assert!(second.starts_with("- **test-project**"));
+        assert!(second.ends_with("\nShowing 3-3 of 3 projects.\n"));
+        assert!(!list_projects(&projects, &None, &json!({}))
+            .unwrap()
+            .contains("Showing"));
+        assert_eq!(
+            list_projects(&projects, &None, &json!({"cursor": "4"})).unwrap_err(),
+            "Invalid cursor '4'"
+        );
+
+        let (_, _, _, _, docs, _) = projects.get_mut("test-project").unwrap();
+        docs.docs.insert(
+            "api".to_string(),
+            DocEntry {
+                path: "docs/api.md".to_string(),
+                summary: "API reference".to_string(),
+            },
+        );
+        let args = json!({"project": "test-project", "limit": 1, "cursor": "1"});
+        let result = get_docs(&PathBuf::from("/workspace"), &projects, &None, &args).unwrap();
+        assert!(result.contains("- **readme**: Project readme\n\nShowing 2-2 of 2 docs.\n"));
+        assert!(!result.contains("api"));
+    }
+
+    #[test]
+    fn test_groups_filter_projects() {
+        let mut projects = create_test_projects();
+        let (_, mut data) = create_test_project();
+        data.1.project.name = "billing".to_string();
+        data.1.project.description = "Billing".to_string();
+        projects.insert("billing".to_string(), data);
+        let workspace: Option<WorkspaceConfig> = Some(
+            toml::from_str(
+                "[groups.payments]\ndescription = \"Money\"\nprojects = [\"billing\", \"ledger\"]\n",
+            )
+            .unwrap(),
+        );
+
+        let result = list_groups(&projects, &workspace).unwrap();
+        assert!(result.contains(
+            "## payments\n\nMoney\n\n- **billing**: Billing\n- **ledger** (not found in this workspace)\n"
+        ));
+        assert!(result.contains("**Not in any group:** test-project\n"));
+        assert!(list_groups(&projects, &None)
+            .unwrap()
+            .starts_with("No groups defined"));
+
+        let args = json!({"group": "payments"});
+        let listed = list_projects(&projects, &workspace, &args).unwrap();
+        assert!(listed.contains("**billing**"));
+        assert!(!listed.contains("test-project"));
+        let root = PathBuf::from("/workspace");
+        let overview = get_workspace_overview(&root, &workspace, &projects, &args).unwrap();
+        assert!(overview.contains("**billing**"));
+        assert!(!overview.contains("test-project"));
+
+        let err = list_projects(&projects, &workspace, &json!({"group": "web"})).unwrap_err();
+        assert_eq!(err, "Unknown group 'web'. Known groups: payments");
+    }
+
+    #[test]
+    fn test_external_projects_are_listed_and_graphed() {
+        let projects = create_test_projects();
+        let workspace: Option<WorkspaceConfig> = Some(
+            toml::from_str(
+                "[external.core]\ndescription = \"Shared core library\"\nrepository = \"https://github.com/acme/core\"\ndocs = \"https://docs.acme.dev/core\"\n\n[groups.platform]\nprojects = [\"core\"]\n",
+            )
+            .unwrap(),
+        );
+        let groups = list_groups(&projects, &workspace).unwrap();
+        assert!(groups.contains("- **core** (external): Shared core library\n"));
+        let in_group = list_projects(&projects, &workspace, &json!({"group": "platform"})).unwrap();
+        assert!(in_group.starts_with("- **core** (external)"));
+
+        let listed = list_projects(&projects, &workspace, &json!({})).unwrap();
+        assert!(listed.ends_with(
+            "- **core** (external): Shared core library\n  Repository: https://github.com/acme/core\n  Docs: https://docs.acme.dev/core\n"
+        ));
+        assert!(list_projects(&HashMap::new(), &workspace, &json!({}))
+            .unwrap()
+            .starts_with("- **core** (external)"));
+
+        let root = PathBuf::from("/workspace");
+        let overview = get_workspace_overview(&root, &workspace, &projects, &json!({})).unwrap();
+        assert!(overview.contains("- **core** (external): Shared core library\n"));
+        assert!(overview.contains("  ← depends on: core (external)\n"));
+        assert!(overview.contains("**core** (external):\n  → used by: test-project\n"));
+
+        let info = get_project_info(&projects, &workspace, &json!({"project": "core"})).unwrap();
+        assert!(info.starts_with("# core\n\nShared core library\n\nExternal project"));
+        assert!(info.contains("**Repository:** https://github.com/acme/core\n"));
+    }
+
+    #[test]
+    fn test_get_project_info_not_found() {
+        let projects = create_test_projects();
+        let args = json!({"project": "nonexistent"});
+        let result = get_project_info(&projects, &None, &args);
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("not found"));
+    }
+
+    #[test]
+    fn test_get_project_info_full() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project"});
+        let result = get_project_info(&projects, &None, &args).unwrap();
+        assert!(result.contains("test-project"));
+        assert!(result.contains("A test project"));
+        assert!(result.contains("rust"));
+        assert!(result.contains("1.0.0"));
+    }
+
+    #[test]
+    fn test_get_project_info_commands_field() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "field": "commands"});
+        let result = get_project_info(&projects, &None, &args).unwrap();
+        assert!(result.contains("build"));
+        assert!(result.contains("cargo build"));
+    }
+
+    #[test]
+    fn test_get_project_info_field_array_and_json() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "field": ["commands", "dependencies"]});
+        let result = get_project_info(&projects, &None, &args).unwrap();
+        assert!(result.starts_with("# test-project\n"));
+        assert!(result.find("## commands").unwrap() < result.find("## dependencies").unwrap());
+        assert!(result.contains("`cargo build`"));
+
+        let args = json!({"project": "test-project", "field": "commands", "as_json": true});
+        let commands: Value =
+            serde_json::from_str(&get_project_info(&projects, &None, &args).unwrap()).unwrap();
+        assert_eq!(commands["build"], "cargo build");
+
+        let args = json!({
+            "project": "test-project",
+            "field": ["commands", "related_projects"],
+            "as_json": true
+        });
+        let bundle: Value =
+            serde_json::from_str(&get_project_info(&projects, &None, &args).unwrap()).unwrap();
+        assert_eq!(bundle["commands"]["build"], "cargo build");
+        assert!(bundle["related_projects"]["upstream"].is_array());
+        assert!(bundle.get("api").is_none());
+
+        let args = json!({"project": "test-project", "as_json": true});
+        let full: Value =
+            serde_json::from_str(&get_project_info(&projects, &None, &args).unwrap()).unwrap();
+        assert_eq!(full["project"]["name"], "test-project");
+
+        let args = json!({"project": "test-project", "field": ["commands", "bogus"]});
+        assert_eq!(
+            get_project_info(&projects, &None, &args).unwrap_err(),
+            "Unknown field: bogus"
+        );
+    }
+
+    #[test]
+    fn test_get_commands() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert!(result.contains("build"));
+        assert!(result.contains("test"));
+    }
+
+    #[test]
+    fn test_get_commands_specific() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "command_type": "build"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert!(result.contains("cargo build"));
+    }
+
+    #[test]
+    fn test_get_commands_not_found() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "command_type": "deploy"});
+        let result = get_commands(&projects, &args);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_get_commands_with_profile() {
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().1.profiles = toml::from_str(
+            "[ci.commands]\ntest = \"cargo test --locked\"\n\n[docker.commands]\nbuild = \"docker build .\"\n",
+        )
+        .unwrap();
+
+        let args = json!({"project": "test-project", "command_type": "test", "profile": "ci"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert_eq!(result, "test: cargo test --locked (profile ci)");
+        let args = json!({"project": "test-project", "command_type": "build", "profile": "ci"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert_eq!(result, "build: cargo build");
+
+        let args = json!({"project": "test-project", "profile": "docker"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert!(result.starts_with("**Profile:** docker (sets build)\n\n"));
+        assert!(result.contains("docker build ."));
+        assert!(!result.contains("cargo build"));
+
+        let args = json!({"project": "test-project"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert!(result.contains("*Profiles: ci, docker (pass `profile` for their commands)*"));
+
+        let args = json!({"project": "test-project", "profile": "nightly"});
+        let err = get_commands(&projects, &args).unwrap_err();
+        assert!(err.ends_with("Available profiles: ci, docker"));
+    }
+
+    #[test]
+    fn test_get_commands_marks_local_overrides() {
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().1.local_overrides =
+            vec!["commands.test".to_string()];
+
+        let args = json!({"project": "test-project", "command_type": "test"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert_eq!(result, "test: cargo test (from .jumble/local.toml)");
+
+        let args = json!({"project": "test-project"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert!(result
+            .ends_with("*Set locally in `.jumble/local.toml` (not committed): `commands.test`*\n"));
+    }
+
+    #[test]
+    fn test_get_commands_merges_discovered_commands() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(
+            temp.path().join("Makefile"),
+            "test:\n\tcargo nextest run\nbench:\n",
+        )
+        .unwrap();
+        let mut projects = create_test_projects();
+        let project = projects.get_mut("test-project").unwrap();
+        project.0 = temp.path().to_path_buf();
+        project.1.commands.remove("test");
+
+        let args = json!({"project": "test-project"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert!(result.contains("- **build**: `cargo build`\n"));
+        assert!(result.contains("- **test**: `make test` *(from Makefile)*\n"));
+        assert!(result.contains("- **bench**: `make bench` *(from Makefile)*\n"));
+        assert!(result.contains("- **lint**: `cargo clippy --all-targets` *(inferred)*"));
+        assert!(!result.contains("`cargo test`"));
+
+        let args = json!({"project": "test-project", "command_type": "test"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert_eq!(result, "test: make test (from Makefile)");
+    }
+
+    #[test]
+    fn test_get_commands_labels_inferred_defaults() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(temp.path().join("go.mod"), "module example.com/app\n").unwrap();
+        let mut projects = create_test_projects();
+        let project = projects.get_mut("test-project").unwrap();
+        project.0 = temp.path().to_path_buf();
+        let config = &mut project.1;
+        config.project.language = None;
+        config.commands.clear();
+        config
+            .commands
+            .insert("test".to_string(), "make test".to_string());
+
+        let args = json!({"project": "test-project"});
+        let result = get_commands(&projects, &args).unwrap();
+        assert!(result.contains("- **test**: `make test`\n"));
+        assert!(result.contains("- **build**: `go build ./...` *(inferred)*"));
+        assert!(result.contains("*Inferred from go.mod."));
+        assert!(!result.contains("go test"));
+
+        let args = json!({"project": "test-project", "command_type": "lint"});
+        assert_eq!(
+            get_commands(&projects, &args).unwrap(),
+            "lint: go vet ./... (inferred from go.mod)"
+        );
+    }
+
+    #[test]
+    fn test_get_architecture() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "concept": "authentication"});
+        let result = get_architecture(&projects, &args).unwrap();
+        assert!(result.contains("authentication"));
+        assert!(result.contains("JWT auth"));
+        assert!(result.contains("src/auth.rs"));
+    }
+
+    #[test]
+    fn test_get_architecture_lists_concept_prompts() {
+        let (name, mut data) = create_test_project();
+        data.1.concepts.get_mut("authentication").unwrap().prompts =
+            vec!["add-provider".to_string(), "rotate-keys".to_string()];
+        data.2.skills.insert(
+            "add-provider".to_string(),
+            SkillInfo {
+                path: PathBuf::from("/tmp/add-provider.md"),
+                skill_dir: None,
+                frontmatter: Some(SkillFrontmatter {
+                    description: Some("Add an OAuth provider".to_string()),
+                    ..Default::default()
+                }),
+                preview: String::new(),
+            },
+        );
+        let mut projects = HashMap::new();
+        projects.insert(name, data);
+
+        let args = json!({"project": "test-project", "concept": "authentication"});
+        let result = get_architecture(&projects, &args).unwrap();
+        assert!(result.contains(
+            "**Prompts:**\n- `add-provider`: Add an OAuth provider\n- `rotate-keys` (no such skill in .jumble/skills)\n"
+        ));
+        assert!(!get_architecture(&create_test_projects(), &args)
+            .unwrap()
+            .contains("Prompts"));
+    }
+
+    #[test]
+    fn test_get_architecture_case_insensitive() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "concept": "AUTHENTICATION"});
+        let result = get_architecture(&projects, &args).unwrap();
+        assert!(result.contains("JWT auth"));
+    }
+
+    #[test]
+    fn test_get_architecture_partial_match() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "concept": "auth"});
+        let result = get_architecture(&projects, &args).unwrap();
+        assert!(result.contains("JWT auth"));
+    }
+
+    #[test]
+    fn test_get_related_files() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "query": "auth"});
+        let result = get_related_files(&projects, &None, &FileIndex::default(), &args).unwrap();
+        assert!(result.contains("authentication"));
+        assert!(result.contains("src/auth.rs"));
+    }
+
+    #[test]
+    fn test_get_related_files_ranks_and_caps_matches() {
+        let mut projects = create_test_projects();
+        let config = &mut projects.get_mut("test-project").unwrap().1;
+        config.concepts.clear();
+        for (name, summary) in [
+            ("billing-auth", "Payment provider credentials"),
+            (
+                "sessions",
+                "Cookie sessions issued after auth, auth tokens refreshed",
+            ),
+            ("auth", "Login flow"),
+            ("logging", "Structured logs, mentions auth once"),
+        ] {
+            config.concepts.insert(
+                name.to_string(),
+                Concept {
+                    files: vec![format!("src/{}.rs", name)],
+                    summary: summary.to_string(),
+                    pinned: false,
+                    prompts: Vec::new(),
+                },
+            );
+        }
+
+        let args = json!({"project": "test-project", "query": "auth", "limit": 3});
+        let result = get_related_files(&projects, &None, &FileIndex::default(), &args).unwrap();
+        let position = |needle: &str| result.find(needle).unwrap();
+        assert!(position("## auth\n") < position("## billing-auth"));
+        assert!(position("## billing-auth") < position("## sessions"));
+        assert!(!result.contains("## logging"));
+        assert!(result.contains("1 more matching concept(s) omitted"));
+    }
+
+    #[test]
+    fn test_related_files_and_memory_search_expand_synonyms() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/oauth_client.rs"), "").unwrap();
+        let mut projects = create_test_projects();
+        let project = projects.get_mut("test-project").unwrap();
+        project.0 = temp.path().to_path_buf();
+        project.5 = memory::open_or_create_memory_db(temp.path()).unwrap();
+
+        let workspace: Option<WorkspaceConfig> =
+            Some(toml::from_str("[synonyms]\nauth = [\"Login\", \"oauth\"]\n").unwrap());
+
+        // "login" reaches the `auth` concept through its group.
+        let args = json!({"project": "test-project", "query": "login"});
+        let result =
+            get_related_files(&projects, &workspace, &FileIndex::default(), &args).unwrap();
+        assert!(result.contains("*Also searched: auth, oauth*"));
+        assert!(result.contains("src/auth.rs"));
+        assert!(get_related_files(&projects, &None, &FileIndex::default(), &args).is_err());
+
+        let args = json!({"project": "test-project", "query": "sso"});
+        assert!(get_related_files(&projects, &workspace, &FileIndex::default(), &args).is_err());
+
+        store_memory(
+            &projects,
+            &json!({"project": "test-project", "key": "sso", "value": "Login goes through Okta"}),
+        )
+        .unwrap();
+        let args = json!({"project": "test-project", "query": "Auth"});
+        let result = search_memories(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("## sso"));
+        let result = search_memories(&projects, &None, &args).unwrap();
+        assert!(result.contains("No memories matching"));
+    }
+
+    #[test]
+    fn test_get_related_files_falls_back_to_file_names() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("src/billing")).unwrap();
+        std::fs::write(temp.path().join("src/billing/invoice_service.rs"), "").unwrap();
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().0 = temp.path().to_path_buf();
+        let index = FileIndex::default();
+
+        let args = json!({"project": "test-project", "query": "invoice"});
+        let result = get_related_files(&projects, &None, &index, &args).unwrap();
+        assert!(result.contains("No concepts match 'invoice'"));
+        assert!(result.contains("src/billing/invoice_service.rs"));
+
+        let args = json!({"project": "test-project", "query": "nothing-like-this"});
+        assert!(get_related_files(&projects, &None, &index, &args).is_err());
+    }
+
+    #[test]
+    fn test_get_conventions() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project"});
+        let result = get_conventions(&projects, &None, &args).unwrap();
+        assert!(result.contains("naming"));
+        assert!(result.contains("async"));
+    }
+
+    #[test]
+    fn test_conventions_and_overview_follow_output_language() {
+        let projects = create_test_projects();
+        let workspace: Option<WorkspaceConfig> = Some(
+            toml::from_str(
+                "[workspace]\nname = \"Platform\"\noutput_language = \"ja\"\n\n[gotchas]\nlocks = \"Never hold locks across await\"\n",
+            )
+            .unwrap(),
+        );
+
+        let args = json!({"project": "test-project"});
+        let result = get_conventions(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("# 'test-project' の規約\n"));
+        assert!(result.contains("# 'test-project' の注意点\n"));
+        assert!(!result.contains("Conventions for"));
+
+        let result = get_workspace_conventions(&workspace, &json!({})).unwrap();
+        assert!(result.starts_with("# Platform の注意点\n"));
+
+        let root = PathBuf::from("/workspace");
+        let result = get_workspace_overview(&root, &workspace, &projects, &json!({})).unwrap();
+        assert!(result.contains("**ルート:** "));
+        assert!(result.contains("## プロジェクト\n"));
+    }
+
+    #[test]
+    fn test_get_conventions_filtered() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "category": "gotchas"});
+        let result = get_conventions(&projects, &None, &args).unwrap();
+        assert!(result.contains("async"));
+        assert!(!result.contains("naming"));
+    }
+
+    #[test]
+    fn test_get_docs() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project"});
+        let result = get_docs(&PathBuf::from("/workspace"), &projects, &None, &args).unwrap();
+        assert!(result.contains("readme"));
+        assert!(result.contains("Project readme"));
+    }
+
+    #[test]
+    fn test_get_docs_specific() {
+        let projects = create_test_projects();
+        let args = json!({"project": "test-project", "topic": "readme"});
+        let result = get_docs(&PathBuf::from("/workspace"), &projects, &None, &args).unwrap();
+        assert!(result.contains("README.md"));
+        assert!(result.contains("**URI:** file:///"));
+    }
+
+    #[test]
+    fn test_group_conventions_and_docs_reach_members() {
+        let mut projects = create_test_projects();
+        let (_, _, _, conventions, _, _) = projects.get_mut("test-project").unwrap();
+        conventions
+            .conventions
+            .insert("money".to_string(), "Project rule".to_string());
+        let workspace: Option<WorkspaceConfig> = Some(
+            toml::from_str(
+                "[groups.payments]\nprojects = [\"test-project\"]\n\n[groups.payments.conventions]\nmoney = \"Group rule\"\nids = \"Use ULIDs\"\n\n[groups.payments.gotchas]\nretries = \"Make handlers idempotent\"\n\n[groups.payments.docs.pci]\npath = \"docs/pci.md\"\nsummary = \"Card data rules\"\n\n[groups.web]\nprojects = [\"frontend\"]\n\n[groups.web.conventions]\ncss = \"Use modules\"\n",
+            )
+            .unwrap(),
+        );
+
+        let args = json!({"project": "test-project"});
+        let result = get_conventions(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("## ids\nUse ULIDs\n*From group `payments`.*\n"));
+        assert!(result.contains("## retries\nMake handlers idempotent\n*From group `payments`.*\n"));
+        assert!(result.contains("## money\nProject rule\n"));
+        assert!(!result.contains("Group rule"));
+        assert!(!result.contains("css"));
+
+        let root = PathBuf::from("/workspace");
+        let result = get_docs(&root, &projects, &workspace, &args).unwrap();
+        assert!(result.contains("- **readme**: Project readme\n"));
+        assert!(result.contains("- **pci**: Card data rules *(group `payments`)*\n"));
+
+        let args = json!({"project": "test-project", "topic": "pci"});
+        let result = get_docs(&root, &projects, &workspace, &args).unwrap();
+        let expected = root.join("docs").join("pci.md");
+        assert!(result.contains(&format!("**Path:** {}\n", expected.display())));
+        assert!(result.ends_with("*From group `payments`.*"));
+    }
+
+    #[test]
+    fn test_get_docs_accepts_windows_separators() {
+        let mut projects = create_test_projects();
+        let (path, _, _, _, docs, _) = projects.get_mut("test-project").unwrap();
+        docs.docs.insert(
+            "api".to_string(),
+            DocEntry {
+                path: "docs\\api\\index.md".to_string(),
+                summary: "API reference".to_string(),
             },
-        };
-        let docs = ProjectDocs {
-            docs: {
-                let mut map = HashMap::new();
-                map.insert(
-                    "readme".to_string(),
-                    DocEntry {
-                        path: "README.md".to_string(),
-                        summary: "Project readme".to_string(),
-                    },
-                );
-                map
+        );
+        let expected = path.join("docs").join("api").join("index.md");
+
+        let args = json!({"project": "test-project", "topic": "api"});
+        let result = get_docs(&PathBuf::from("/workspace"), &projects, &None, &args).unwrap();
+        assert!(result.contains(&expected.display().to_string()));
+    }
+
+    #[test]
+    fn test_get_context_health() {
+        let projects = create_test_projects();
+        let all = get_context_health(&projects, &json!({})).unwrap();
+        assert!(all.contains("**Workspace score:**"));
+        assert!(all.contains("## test-project"));
+
+        let one = get_context_health(&projects, &json!({"project": "test-project"})).unwrap();
+        assert!(one.contains("**Score:**"));
+        assert!(!one.contains("Workspace score"));
+        assert!(get_context_health(&projects, &json!({"project": "missing"})).is_err());
+    }
+
+    #[test]
+    fn test_verify_context() {
+        let projects = create_test_projects();
+        let result = verify_context(&projects, &json!({"project": "test-project"})).unwrap();
+        assert!(result.starts_with("# Context Freshness: test-project\n"));
+        assert!(result.contains("- `src/auth.rs` (concept `authentication`)\n"));
+        assert!(verify_context(&projects, &json!({"project": "missing"})).is_err());
+        assert!(verify_context(&projects, &json!({})).is_err());
+    }
+
+    #[test]
+    fn test_get_workspace_overview_no_workspace() {
+        let projects = create_test_projects();
+        let root = PathBuf::from("/workspace");
+        let result = get_workspace_overview(&root, &None, &projects, &json!({})).unwrap();
+        assert!(result.contains("Workspace Overview"));
+        assert!(result.contains("test-project"));
+    }
+
+    #[test]
+    fn test_get_workspace_overview_with_workspace() {
+        let projects = create_test_projects();
+        let root = PathBuf::from("/workspace");
+        let workspace = Some(WorkspaceConfig {
+            extends_url: None,
+            extends_sha256: None,
+            workspace: WorkspaceInfo {
+                name: Some("My Workspace".to_string()),
+                description: Some("A test workspace".to_string()),
+                output_language: None,
+                pinned_projects: Vec::new(),
+                infer_code_owners: false,
+            },
+            conventions: HashMap::new(),
+            gotchas: HashMap::new(),
+            tools: HashMap::new(),
+            servers: HashMap::new(),
+            synonyms: HashMap::new(),
+            tracker: None,
+            compliance: None,
+            workflow: None,
+            next_steps: None,
+            dependency_policy: Default::default(),
+            dev_ports: HashMap::new(),
+            groups: HashMap::new(),
+            external: HashMap::new(),
+            output: Default::default(),
+            lint: Default::default(),
+            instructions: Default::default(),
+            workspaces: HashMap::new(),
+            linked_projects: HashMap::new(),
+            local_overrides: Vec::new(),
+        });
+        let result = get_workspace_overview(&root, &workspace, &projects, &json!({})).unwrap();
+        assert!(result.contains("My Workspace"));
+        assert!(result.contains("A test workspace"));
+    }
+
+    #[test]
+    fn test_get_workspace_overview_include_and_filter() {
+        let mut projects = create_test_projects();
+        let (_, mut web) = create_test_project();
+        web.1.project.name = "web".to_string();
+        web.1.project.language = Some("typescript".to_string());
+        web.1.project.tags = vec!["Frontend".to_string()];
+        web.1.related_projects.upstream = vec!["test-project".to_string()];
+        projects.insert("web".to_string(), web);
+        let root = PathBuf::from("/workspace");
+        let workspace: Option<WorkspaceConfig> = Some(
+            toml::from_str(
+                "[workspace]\nname = \"Platform\"\n\n[conventions]\nerrors = \"Use anyhow\\nin binaries\"\n",
+            )
+            .unwrap(),
+        );
+
+        let args = json!({"project_filter": "frontend"});
+        let result = get_workspace_overview(&root, &workspace, &projects, &args).unwrap();
+        assert!(result.contains("- **web** (typescript)"));
+        assert!(!result.contains("- **test-project**"));
+        assert!(result.contains("← depends on: test-project"));
+
+        let web = &mut projects.get_mut("web").unwrap().1.related_projects;
+        web.upstream.push("billing:api".to_string());
+        web.downstream.push("billing:admin".to_string());
+        let mut linked = workspace.clone();
+        linked.as_mut().unwrap().linked_projects.insert(
+            "billing:api".to_string(),
+            crate::config::LinkedProject {
+                path: PathBuf::from("/billing/api"),
+                description: "Billing API".to_string(),
             },
+        );
+        let args = json!({"project_filter": "frontend"});
+        let result = get_workspace_overview(&root, &linked, &projects, &args).unwrap();
+        assert!(result.contains(
+            "← depends on: test-project, billing:api (linked workspace)\n  → used by: billing:admin (not found)\n"
+        ));
+
+        let args = json!({"include": ["conventions_summary"]});
+        let result = get_workspace_overview(&root, &workspace, &projects, &args).unwrap();
+        assert!(!result.contains("## Projects"));
+        assert!(!result.contains("## Dependencies"));
+        assert!(result.contains("## Platform Conventions\n\n- **errors**: Use anyhow\n"));
+
+        let args = json!({"project_filter": "go"});
+        let result = get_workspace_overview(&root, &None, &projects, &args).unwrap();
+        assert!(result.contains("No projects match 'go'."));
+
+        let args = json!({"include": ["everything"]});
+        assert!(get_workspace_overview(&root, &None, &projects, &args)
+            .unwrap_err()
+            .starts_with("Unknown section: \"everything\""));
+    }
+
+    #[test]
+    fn test_pinned_projects_and_concepts_are_listed_first() {
+        let mut projects = create_test_projects();
+        for name in ["alpha", "zeta"] {
+            let (_, mut data) = create_test_project();
+            data.1.project.name = name.to_string();
+            projects.insert(name.to_string(), data);
+        }
+        let config = &mut projects.get_mut("zeta").unwrap().1;
+        for (name, pinned) in [("auth", false), ("billing", true)] {
+            config.concepts.insert(
+                name.to_string(),
+                Concept {
+                    files: vec![format!("src/{}.rs", name)],
+                    summary: format!("{} module", name),
+                    pinned,
+                    prompts: Vec::new(),
+                },
+            );
+        }
+        let workspace: Option<WorkspaceConfig> =
+            Some(toml::from_str("[workspace]\npinned_projects = [\"zeta\"]\n").unwrap());
+
+        let listed = list_projects(&projects, &workspace, &json!({})).unwrap();
+        assert!(listed.starts_with("- **zeta** (rust, pinned)"));
+        assert!(listed.find("**alpha**").unwrap() < listed.find("**test-project**").unwrap());
+
+        let root = PathBuf::from("/workspace");
+        let overview = get_workspace_overview(&root, &workspace, &projects, &json!({})).unwrap();
+        assert!(overview.contains(
+            "## Projects\n\n- **zeta** (rust, pinned): A test project\n  - billing: billing module\n- **alpha**"
+        ));
+        assert!(!overview.contains("auth module"));
+
+        let info = get_project_info(&projects, &None, &json!({"project": "zeta"})).unwrap();
+        assert!(info.find("**billing** (pinned)").unwrap() < info.find("**auth**").unwrap());
+    }
+
+    #[test]
+    fn test_record_command_result_and_get_last_run() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join(".jumble")).unwrap();
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().0 = temp.path().to_path_buf();
+
+        let none = get_last_run(
+            &projects,
+            &json!({"project": "test-project", "command_type": "test"}),
+        )
+        .unwrap();
+        assert!(none.starts_with("No recorded `test` runs"));
+
+        let record = |exit_code: i64, summary: &str| {
+            record_command_result(
+                &projects,
+                &json!({"project": "test-project", "command_type": "test", "exit_code": exit_code, "summary": summary}),
+            )
+            .unwrap()
         };
+        assert_eq!(
+            record(1, "2 failed"),
+            "Recorded `test` for project 'test-project': failed (exit 1)"
+        );
+        assert!(record(0, "all green").contains(". Previous run failed (exit 1) at "));
 
-        // Create a temporary memory database for testing
-        let temp_dir = TempDir::new().unwrap();
-        let test_path = temp_dir.path().to_path_buf();
-        let memory_db = memory::open_or_create_memory_db(&test_path).unwrap();
+        let last = get_last_run(
+            &projects,
+            &json!({"project": "test-project", "command_type": "test"}),
+        )
+        .unwrap();
+        assert!(last.contains("**Command:** `cargo test`"));
+        assert!(last.contains("**Status:** passed (exit 0)\n"));
+        assert!(last.contains("## Previous run\n\n**Status:** failed (exit 1)"));
+        assert!(last.contains("**Summary:** 2 failed"));
+
+        let all = get_last_run(&projects, &json!({"project": "test-project"})).unwrap();
+        assert!(all.contains("- **test**: passed (exit 0) at "));
+        assert!(record_command_result(
+            &projects,
+            &json!({"project": "test-project", "command_type": "test", "exit_code": "0"})
+        )
+        .is_err());
+    }
 
-        (
-            "test-project".to_string(),
-            (
-                test_path.clone(),
-                config,
-                skills,
-                conventions,
-                docs,
-                memory_db,
-            ),
+    #[test]
+    fn test_get_ticket_link_prefers_project_tracker() {
+        let mut projects = create_test_projects();
+        let workspace: Option<WorkspaceConfig> = Some(
+            toml::from_str(
+                "[tracker]\ntype = \"jira\"\nbase_url = \"https://acme.atlassian.net\"\nproject_key = \"PAY\"\ncommit_format = \"{ticket}: {summary}\"\n",
+            )
+            .unwrap(),
+        );
+
+        let args = json!({"project": "test-project", "id": "pay-123"});
+        let result = get_ticket_link(&projects, &workspace, &args).unwrap();
+        assert!(
+            result.starts_with("# PAY-123\n\n**URL:** https://acme.atlassian.net/browse/PAY-123\n")
+        );
+        assert!(result.contains("**Commit:** `PAY-123: {summary}`"));
+        assert!(!result.contains("**Branch:**"));
+
+        projects.get_mut("test-project").unwrap().1.tracker = Some(
+            toml::from_str("type = \"github\"\nbase_url = \"https://github.com/acme/app\"\n")
+                .unwrap(),
+        );
+        let args = json!({"project": "test-project", "id": "17"});
+        let result = get_ticket_link(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("**URL:** https://github.com/acme/app/issues/17"));
+
+        let args = json!({"project": "test-project", "id": "17"});
+        assert!(get_ticket_link(&create_test_projects(), &None, &args)
+            .unwrap_err()
+            .starts_with("No [tracker] configured"));
+    }
+
+    #[test]
+    fn test_get_compliance_info_uses_workspace_policy() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join("LICENSE"),
+            "Permission is hereby granted, free of charge, to any person",
         )
+        .unwrap();
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().0 = temp.path().to_path_buf();
+
+        let args = json!({"project": "test-project"});
+        let result = get_compliance_info(&projects, &None, &args).unwrap();
+        assert!(result.contains("- `LICENSE`: MIT\n"));
+        assert!(result.contains("No [compliance] policy configured."));
+
+        let workspace: Option<WorkspaceConfig> = Some(
+            toml::from_str(
+                "[compliance]\nallowed_licenses = [\"MIT\", \"Apache-2.0\"]\ndenied_licenses = [\"GPL-3.0\"]\nexport_restrictions = \"EAR 5D002\"\n",
+            )
+            .unwrap(),
+        );
+        let result = get_compliance_info(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("**Allowed:** MIT, Apache-2.0\n**Denied:** GPL-3.0\n"));
+        assert!(result.ends_with("## Export Restrictions\n\nEAR 5D002\n"));
     }
 
-    fn create_test_projects() -> HashMap<String, ProjectData> {
-        let mut projects = HashMap::new();
-        let (name, data) = create_test_project();
-        projects.insert(name, data);
+    #[test]
+    fn test_check_dependency_points_at_existing_choices() {
+        let mut projects = create_test_projects();
+        let (_, mut web) = create_test_project();
+        web.1.dependencies.external = vec!["reqwest".to_string(), "serde".to_string()];
+        projects.insert("web".to_string(), web);
+        let workspace: Option<WorkspaceConfig> = Some(
+            toml::from_str(
+                "[dependency_policy]\nallowed = [\"reqwest\"]\n\n[dependency_policy.denied]\nopenssl = \"Use rustls\"\n\n[dependency_policy.groups]\nhttp = [\"reqwest\", \"ureq\"]\n",
+            )
+            .unwrap(),
+        );
+
+        let args = json!({"project": "test-project", "name": "ureq"});
+        let result = check_dependency(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("**Not pre-approved:**"));
+        assert!(result.contains("- `reqwest` (used by web)\n"));
+
+        let args = json!({"project": "test-project", "name": "reqwest"});
+        let result = check_dependency(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("**Allowed**"));
+        assert!(result.contains("**Used by:** web."));
+
+        let args = json!({"project": "test-project", "name": "OpenSSL"});
+        let result = check_dependency(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("**Denied:** Use rustls\n"));
+
+        let args = json!({"project": "web", "name": "serde"});
+        let result = check_dependency(&projects, &None, &args).unwrap();
+        assert!(result.contains("**Already used:** 'web' already depends on `serde`."));
+    }
+
+    #[test]
+    fn test_get_code_owners() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src/api")).unwrap();
+        std::fs::write(root.join("src/api/users.rs"), "fn users() {}\n").unwrap();
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().0 = root.to_path_buf();
+        let index = FileIndex::default();
+
+        let args = json!({"project": "test-project", "path": "src/api"});
+        let result = get_code_owners(root, &projects, &None, &index, &args).unwrap();
+        assert!(result.contains("Set `infer_code_owners = true`"));
+        let outside = json!({"project": "test-project", "path": std::env::temp_dir()});
+        assert_eq!(
+            get_code_owners(root, &projects, &None, &index, &outside).unwrap_err(),
+            "'path' must stay inside the project"
+        );
+        if !cfg!(feature = "exec") {
+            return;
+        }
+
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .args(["-c", "user.name=Ann", "-c", "user.email=ann@example.com"])
+                .args(args)
+                .current_dir(root)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "init"]);
         projects
+            .get_mut("test-project")
+            .unwrap()
+            .1
+            .project
+            .infer_code_owners = true;
+        let result = get_code_owners(root, &projects, &None, &index, &args).unwrap();
+        assert!(
+            result.contains("| Ann <ann@example.com> | 1 | 1 |"),
+            "{}",
+            result
+        );
+
+        std::fs::write(
+            root.join("CODEOWNERS"),
+            "* @acme/core\n/src/api/ @acme/api\n",
+        )
+        .unwrap();
+        let result = get_code_owners(root, &projects, &None, &index, &args).unwrap();
+        assert!(result.contains("`CODEOWNERS` assigns `/src/api/` to: @acme/api\n"));
+
+        let args = json!({"project": "test-project", "path": "../etc"});
+        assert!(get_code_owners(root, &projects, &None, &index, &args).is_err());
+    }
+
+    #[test]
+    fn test_get_data_assets() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("notebooks/eda")).unwrap();
+        std::fs::write(temp.path().join("notebooks/eda/churn.ipynb"), "{}").unwrap();
+        std::fs::write(temp.path().join("notebooks/README.md"), "").unwrap();
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().0 = temp.path().to_path_buf();
+
+        let args = json!({"project": "test-project"});
+        let result = get_data_assets(&projects, &FileIndex::default(), &args).unwrap();
+        assert!(result.contains("No [data] section configured"));
+        assert!(result.contains("Found 1 notebook(s)"));
+
+        projects.get_mut("test-project").unwrap().1.data = Some(
+            toml::from_str(
+                "notebooks = [\"notebooks\"]\npipelines = [\"dags\"]\n\n[datasets.orders]\nlocation = \"s3://lake/orders/\"\nformat = \"parquet\"\nsummary = \"One row per order\"\n",
+            )
+            .unwrap(),
+        );
+        let result = get_data_assets(&projects, &FileIndex::default(), &args).unwrap();
+        assert!(result.contains(
+            "### orders\n\nOne row per order\n\n- **Location:** `s3://lake/orders/`\n- **Format:** parquet\n"
+        ));
+        assert!(result.contains("- `notebooks`: 1 notebook(s)\n  - `notebooks/eda/churn.ipynb`\n"));
+        assert!(result.ends_with("## Pipelines\n\n- `dags` (missing)\n"));
     }
 
     #[test]
-    fn test_list_projects_empty() {
-        let projects = HashMap::new();
-        let result = list_projects(&projects).unwrap();
-        assert!(result.contains("No projects found"));
+    fn test_get_session_summary_lists_unfetched_context() {
+        let projects = create_test_projects();
+        let calls = vec![
+            ("get_workspace_overview".to_string(), json!({})),
+            (
+                "get_conventions".to_string(),
+                json!({"project": "test-project", "category": "conventions"}),
+            ),
+            (
+                "get_commands".to_string(),
+                json!({"project": "test-project"}),
+            ),
+            (
+                "get_commands".to_string(),
+                json!({"project": "test-project"}),
+            ),
+        ];
+
+        let result = get_session_summary(&calls, None, &projects, &None, &json!({})).unwrap();
+        assert!(result.contains("**Tool calls:** 4\n"));
+        assert!(result.contains(
+            "## Fetched\n\n- get_workspace_overview\n- get_conventions (test-project, conventions)\n- get_commands (test-project)\n\n"
+        ));
+        assert!(result.ends_with(
+            "## Not Yet Fetched\n\n- **test-project**: project info (`get_project_info`); gotchas (`get_conventions`); concepts authentication (`get_architecture`); docs index (`get_docs`)\n"
+        ));
+
+        let result = get_session_summary(&[], None, &projects, &None, &json!({})).unwrap();
+        assert!(!result.contains("## Fetched"));
+        assert!(result.contains("- Workspace overview (`get_workspace_overview`); call it first\n"));
+        assert!(!result.contains("test-project"));
+
+        let args = json!({"project": "test-project"});
+        let result = get_session_summary(&[], None, &projects, &None, &args).unwrap();
+        assert!(result.contains("- **test-project**: project info"));
+        assert!(!result.contains("Workspace overview"));
+
+        let args = json!({"project": "missing"});
+        assert!(get_session_summary(&[], None, &projects, &None, &args).is_err());
     }
 
     #[test]
-    fn test_list_projects() {
-        let projects = create_test_projects();
-        let result = list_projects(&projects).unwrap();
-        assert!(result.contains("test-project"));
-        assert!(result.contains("rust"));
-        assert!(result.contains("A test project"));
+    fn test_get_context_diff_arguments() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(
+            get_context_diff(temp.path(), &json!({})).unwrap_err(),
+            "Missing 'from' parameter"
+        );
+        if !cfg!(feature = "exec") {
+            return;
+        }
+        assert!(get_context_diff(temp.path(), &json!({"from": "main"}))
+            .unwrap_err()
+            .ends_with("is not in a git repository"));
     }
 
     #[test]
-    fn test_get_project_info_not_found() {
-        let projects = create_test_projects();
-        let args = json!({"project": "nonexistent"});
-        let result = get_project_info(&projects, &args);
-        assert!(result.is_err());
-        assert!(result.unwrap_err().contains("not found"));
+    fn test_get_review_checklist() {
+        let mut projects = create_test_projects();
+        let data = projects.get_mut("test-project").unwrap();
+        data.3.gotchas.insert(
+            "sessions".to_string(),
+            "Sessions end when the Authentication token expires".to_string(),
+        );
+        let args = json!({"project": "test-project", "files": ["./src/auth.rs", "README.md"]});
+        let result = get_review_checklist(&projects, &None, &args).unwrap();
+        assert!(result.contains("**Files:** `src/auth.rs`, `README.md`\n"));
+        assert!(result.contains("## Conventions\n\n- [ ] **naming**: Use snake_case\n"));
+        assert!(result.contains(
+            "## Gotchas\n\n- [ ] **sessions**: Sessions end when the Authentication token expires\n"
+        ));
+        assert!(result.contains("*1 other gotcha(s) don't mention these files"));
+        assert!(result.contains(
+            "## Affected Concepts\n\n- [ ] **authentication**: JWT auth (touches `src/auth.rs`)\n"
+        ));
+        assert!(
+            result.contains("- [ ] build: `cargo build` passes\n- [ ] test: `cargo test` passes\n")
+        );
+
+        projects.get_mut("test-project").unwrap().1.review = Some(ReviewConfig {
+            required_checks: vec!["ci / test".to_string()],
+        });
+        let result = get_review_checklist(&projects, &None, &args).unwrap();
+        assert!(result.ends_with("## Required Checks\n\n- [ ] `ci / test` passes\n"));
+
+        let args = json!({"project": "test-project", "files": []});
+        assert!(get_review_checklist(&projects, &None, &args).is_err());
     }
 
     #[test]
-    fn test_get_project_info_full() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project"});
-        let result = get_project_info(&projects, &args).unwrap();
-        assert!(result.contains("test-project"));
-        assert!(result.contains("A test project"));
-        assert!(result.contains("rust"));
-        assert!(result.contains("1.0.0"));
+    fn test_prepare_for_task() {
+        let mut projects = create_test_projects();
+        let data = projects.get_mut("test-project").unwrap();
+        data.1
+            .concepts
+            .get_mut("authentication")
+            .unwrap()
+            .prompts
+            .push("rotate-keys".to_string());
+        data.2.skills.insert(
+            "rotate-keys".to_string(),
+            SkillInfo {
+                path: PathBuf::from("/tmp/rotate-keys.md"),
+                skill_dir: None,
+                frontmatter: Some(SkillFrontmatter {
+                    description: Some("Rotate signing keys".to_string()),
+                    ..Default::default()
+                }),
+                preview: String::new(),
+            },
+        );
+        data.3.gotchas.insert(
+            "sessions".to_string(),
+            "Sessions end when the token expires".to_string(),
+        );
+        let args = json!({"project": "test-project", "description": "Add token refresh to authentication, with tests"});
+        let result = prepare_for_task(&projects, &None, &args).unwrap();
+        assert!(result.starts_with(
+            "# Preparing: Add token refresh to authentication, with tests\n\n**Project:** test-project\n"
+        ));
+        assert!(result.contains("## Read First\n\n**authentication**: JWT auth\n- /"));
+        assert!(
+            result.contains("## Gotchas\n\n- **sessions**: Sessions end when the token expires\n")
+        );
+        assert!(!result.contains("Avoid blocking"));
+        assert!(result.contains("## Skills\n\n- `rotate-keys`: Rotate signing keys\n"));
+        assert!(result.contains("## Commands\n\n- test: `cargo test`\n"));
+        assert!(!result.contains("## Docs"));
+
+        let args = json!({"project": "test-project", "description": "the and for"});
+        assert!(prepare_for_task(&projects, &None, &args).is_err());
+        let args = json!({"project": "test-project", "description": "billing exports"});
+        let err = prepare_for_task(&projects, &None, &args).unwrap_err();
+        assert!(err.starts_with("Nothing in test-project's context matches 'billing exports'"));
     }
 
     #[test]
-    fn test_get_project_info_commands_field() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project", "field": "commands"});
-        let result = get_project_info(&projects, &args).unwrap();
-        assert!(result.contains("build"));
-        assert!(result.contains("cargo build"));
+    fn test_get_tests_for() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("tests")).unwrap();
+        std::fs::write(root.join("src/auth.rs"), "").unwrap();
+        std::fs::write(root.join("tests/auth_test.rs"), "").unwrap();
+        let mut projects = create_test_projects();
+        let data = projects.get_mut("test-project").unwrap();
+        data.0 = root.to_path_buf();
+        data.1.project.language = Some("rust".to_string());
+        let index = FileIndex::default();
+
+        let args = json!({"project": "test-project", "files": ["src/auth.rs"]});
+        let result = get_tests_for(&projects, &index, &args).unwrap();
+        assert!(result.contains("- `tests/auth_test.rs`: name matches `src/auth.rs`\n"));
+        assert!(result.ends_with("```\ncargo test --test auth_test\n```\n"));
+
+        let args = json!({"project": "test-project", "files": ["README.md"]});
+        let result = get_tests_for(&projects, &index, &args).unwrap();
+        assert!(result.contains("No test files found"));
+        assert!(result.contains("run the whole suite with `cargo test`"));
     }
 
     #[test]
-    fn test_get_commands() {
-        let projects = create_test_projects();
+    fn test_get_known_issues() {
+        let mut projects = create_test_projects();
         let args = json!({"project": "test-project"});
-        let result = get_commands(&projects, &args).unwrap();
-        assert!(result.contains("build"));
-        assert!(result.contains("test"));
-    }
+        assert!(get_known_issues(&projects, &None, &args)
+            .unwrap()
+            .starts_with("No [known_issues]"));
+
+        let config = &mut projects.get_mut("test-project").unwrap().1;
+        config.known_issues.insert(
+            "checkout_timeout".to_string(),
+            KnownIssue {
+                summary: "Times out on CI".to_string(),
+                test: Some("tests/e2e/checkout.spec.ts".to_string()),
+                status: KnownIssueStatus::Flaky,
+                ticket: Some("pay-812".to_string()),
+                workaround: Some("Re-run the job".to_string()),
+            },
+        );
+        config.known_issues.insert(
+            "locale".to_string(),
+            KnownIssue {
+                summary: "Fails outside en_US".to_string(),
+                test: None,
+                status: KnownIssueStatus::Failing,
+                ticket: Some("PAY-9".to_string()),
+                workaround: None,
+            },
+        );
+        let workspace = Some(WorkspaceConfig {
+            tracker: Some(TrackerConfig {
+                kind: TrackerKind::Jira,
+                base_url: "https://acme.atlassian.net".to_string(),
+                project_key: None,
+                branch_format: None,
+                commit_format: None,
+            }),
+            ..WorkspaceConfig::default()
+        });
 
-    #[test]
-    fn test_get_commands_specific() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project", "command_type": "build"});
-        let result = get_commands(&projects, &args).unwrap();
-        assert!(result.contains("cargo build"));
+        let result = get_known_issues(&projects, &workspace, &args).unwrap();
+        let failing = result.find("## locale (failing)").unwrap();
+        assert!(failing < result.find("## checkout_timeout (flaky)").unwrap());
+        assert!(result.contains(
+            "**Test:** `tests/e2e/checkout.spec.ts`\n**Ticket:** [PAY-812](https://acme.atlassian.net/browse/PAY-812)\n**Workaround:** Re-run the job\n"
+        ));
+
+        let args = json!({"project": "test-project", "test": "checkout.spec.ts"});
+        let result = get_known_issues(&projects, &None, &args).unwrap();
+        assert!(result.contains("## checkout_timeout (flaky)"));
+        assert!(result.contains("**Ticket:** pay-812\n"));
+        assert!(!result.contains("locale"));
+
+        let args = json!({"project": "test-project", "test": "tests/auth_test.rs"});
+        assert!(get_known_issues(&projects, &None, &args)
+            .unwrap()
+            .contains("is not a known issue"));
+
+        let args = json!({"project": "test-project", "test": " "});
+        let result = get_known_issues(&projects, &None, &args).unwrap();
+        assert!(result.contains("## locale (failing)"));
+        assert!(known_issues_for(&projects["test-project"].1, "").is_empty());
     }
 
     #[test]
-    fn test_get_commands_not_found() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project", "command_type": "deploy"});
-        let result = get_commands(&projects, &args);
-        assert!(result.is_err());
+    fn test_get_workflow() {
+        let mut projects = create_test_projects();
+        let args = json!({"project": "test-project"});
+        assert!(get_workflow(&projects, &None, &args)
+            .unwrap()
+            .starts_with("No [workflow] configured"));
+
+        let workspace = Some(WorkspaceConfig {
+            workflow: Some(WorkflowConfig {
+                branching: Some("Trunk-based".to_string()),
+                base_branch: Some("main".to_string()),
+                required_approvals: Some(2),
+                merge_strategy: Some("squash".to_string()),
+                notes: vec!["Link the ticket in the PR description".to_string()],
+                ..WorkflowConfig::default()
+            }),
+            ..WorkspaceConfig::default()
+        });
+        let result = get_workflow(&projects, &workspace, &args).unwrap();
+        assert!(result.contains(
+            "**Branching:** Trunk-based\n**Base branch:** `main`\n**Required approvals:** 2\n**Merge strategy:** squash\n"
+        ));
+        assert!(result.contains("## Notes\n\n- Link the ticket in the PR description\n"));
+        assert!(result.ends_with("*From `[workflow]` in workspace.toml.*\n"));
+
+        projects.get_mut("test-project").unwrap().1.workflow = Some(WorkflowConfig {
+            pr_size: Some("Small".to_string()),
+            ..WorkflowConfig::default()
+        });
+        let result = get_workflow(&projects, &workspace, &args).unwrap();
+        assert!(result.contains("**Pull request size:** Small\n"));
+        assert!(!result.contains("Trunk-based"));
+        assert!(result.ends_with("*From `[workflow]` in project.toml.*\n"));
     }
 
     #[test]
-    fn test_get_architecture() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project", "concept": "authentication"});
-        let result = get_architecture(&projects, &args).unwrap();
-        assert!(result.contains("authentication"));
-        assert!(result.contains("JWT auth"));
-        assert!(result.contains("src/auth.rs"));
+    fn test_get_performance_info() {
+        let mut projects = create_test_projects();
+        let args = json!({"project": "test-project"});
+        assert!(get_performance_info(&projects, &args)
+            .unwrap()
+            .starts_with("No [performance] section"));
+
+        projects.get_mut("test-project").unwrap().1.performance = Some(PerformanceConfig {
+            benchmarks: HashMap::from([("api".to_string(), "cargo bench --bench api".to_string())]),
+            budgets: HashMap::from([("p99 GET /invoices".to_string(), "150 ms".to_string())]),
+            profiling: HashMap::from([(
+                "cpu".to_string(),
+                "`cargo flamegraph --bin server`".to_string(),
+            )]),
+            baseline: Some("https://grafana.example.com/d/api".to_string()),
+        });
+        let result = get_performance_info(&projects, &args).unwrap();
+        assert!(result.contains("## Budgets\n\n- **p99 GET /invoices**: 150 ms\n"));
+        assert!(result.contains("## Benchmarks\n\n- **api**: `cargo bench --bench api`\n"));
+        assert!(result.contains("## Profiling\n\n- **cpu**: `cargo flamegraph --bin server`\n"));
+        assert!(result.contains("**Baseline:** https://grafana.example.com/d/api\n"));
     }
 
     #[test]
-    fn test_get_architecture_case_insensitive() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project", "concept": "AUTHENTICATION"});
-        let result = get_architecture(&projects, &args).unwrap();
-        assert!(result.contains("JWT auth"));
+    fn test_describe_tool() {
+        let result = describe_tool(&json!({"name": "get_tests_for"})).unwrap();
+        assert!(result.starts_with("# `get_tests_for`\n\n"));
+        assert!(result.contains("\n## Parameters\n\n- `files` (array, required): "));
+        assert!(result.contains("\n## Usage\n\n"));
+        assert!(result.contains("get_tests_for(project: "));
+        assert!(result.contains("\n## Typical Next Steps\n\n- `get_known_issues`: "));
+
+        let result = describe_tool(&json!({"name": "get_skill"})).unwrap();
+        assert!(result.contains("## Usage"));
+        let result = describe_tool(&json!({"name": "list_groups"})).unwrap();
+        assert!(result.contains("Takes no parameters."));
+        assert!(!result.contains("Structured Output"));
+        let result = describe_tool(&json!({"name": "get_commands"})).unwrap();
+        assert!(result
+            .contains("\n## Structured Output\n\nResults also carry `structuredContent` with "));
+        assert!(result.contains("`commands`"));
+
+        // Every follow-up names a real tool.
+        for (_, next) in FOLLOW_UPS {
+            for tool in *next {
+                assert!(describe_tool(&json!({ "name": tool })).is_ok(), "{}", tool);
+            }
+        }
+        assert!(describe_tool(&json!({"name": "nope"})).is_err());
     }
 
     #[test]
-    fn test_get_architecture_partial_match() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project", "concept": "auth"});
-        let result = get_architecture(&projects, &args).unwrap();
-        assert!(result.contains("JWT auth"));
+    fn test_next_steps_fill_in_arguments() {
+        let config = NextStepsConfig::default();
+        let args = json!({"project": "api"});
+        assert_eq!(
+            next_steps("get_project_info", &args, &config).unwrap(),
+            "\n---\n**Next steps:** `get_commands(project: \"api\")`, `get_conventions(project: \"api\")`, `get_architecture(project: \"api\")`\n"
+        );
+        let args = json!({"project": "api", "files": ["src/a.rs"]});
+        assert!(next_steps("get_review_checklist", &args, &config)
+            .unwrap()
+            .contains("`get_tests_for(project: \"api\", files: [\"src/a.rs\"])`"));
+        assert!(next_steps("get_code_stats", &args, &config).is_none());
+
+        let config: NextStepsConfig = toml::from_str(
+            "[tools]\nget_project_info = []\nget_code_stats = [\"list_projects\"]\n",
+        )
+        .unwrap();
+        assert!(next_steps("get_project_info", &args, &config).is_none());
+        assert_eq!(
+            next_steps("get_code_stats", &args, &config).unwrap(),
+            "\n---\n**Next steps:** `list_projects()`\n"
+        );
     }
 
     #[test]
-    fn test_get_related_files() {
+    fn test_get_dev_endpoints_reports_conflicts() {
         let projects = create_test_projects();
-        let args = json!({"project": "test-project", "query": "auth"});
-        let result = get_related_files(&projects, &args).unwrap();
-        assert!(result.contains("authentication"));
-        assert!(result.contains("src/auth.rs"));
+        let workspace: WorkspaceConfig = toml::from_str(
+            "[dev_ports.web]\nport = 3000\nproject = \"test-project\"\n\n[dev_ports.api]\nurl = \"http://localhost:8080\"\nsummary = \"REST API\"\n\n[dev_ports.admin]\nport = 8080\n",
+        )
+        .unwrap();
+
+        let result = get_dev_endpoints(&projects, &Some(workspace)).unwrap();
+        assert!(result.contains(
+            "| web | 3000 |  | test-project |  |\n| admin | 8080 |  |  |  |\n| api | 8080 | http://localhost:8080 |  | REST API |\n"
+        ));
+        assert!(result.contains("## Port Conflicts\n\n- **8080**: admin, api\n"));
+
+        let result = get_dev_endpoints(&projects, &None).unwrap();
+        assert!(result.starts_with("No [dev_ports] registry"));
     }
 
     #[test]
-    fn test_get_conventions() {
-        let projects = create_test_projects();
+    fn test_get_messaging_info_links_producers_and_consumers() {
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().1.messaging = Some(
+            toml::from_str(
+                "broker = \"kafka\"\npublishes = [\"orders.created\"]\n\n[topics.\"orders.created\"]\nkind = \"topic\"\nsummary = \"An order was committed\"\nschema = \"schemas/order.avsc\"\n",
+            )
+            .unwrap(),
+        );
+        let (_, mut billing) = create_test_project();
+        billing.1.messaging =
+            Some(toml::from_str("subscribes = [\"orders.created\", \"refunds\"]\n").unwrap());
+        projects.insert("billing".to_string(), billing);
+
         let args = json!({"project": "test-project"});
-        let result = get_conventions(&projects, &args).unwrap();
-        assert!(result.contains("naming"));
-        assert!(result.contains("async"));
-    }
+        let result = get_messaging_info(&projects, &args).unwrap();
+        assert!(result.contains("**Broker:** kafka\n"));
+        assert!(result.contains(
+            "## Publishes\n- `orders.created` (topic): An order was committed — consumers: billing\n"
+        ));
 
-    #[test]
-    fn test_get_conventions_filtered() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project", "category": "gotchas"});
-        let result = get_conventions(&projects, &args).unwrap();
-        assert!(result.contains("async"));
-        assert!(!result.contains("naming"));
+        let args = json!({"project": "billing"});
+        let result = get_messaging_info(&projects, &args).unwrap();
+        assert!(result.contains(
+            "- `orders.created` (topic): An order was committed — producers: test-project\n"
+        ));
+        assert!(result.contains("- `refunds` — producers: none listed\n"));
+
+        let args = json!({"project": "billing", "topic": "orders.created"});
+        let result = get_messaging_info(&projects, &args).unwrap();
+        assert!(result.contains("**Schema:** `schemas/order.avsc` (in test-project)\n"));
+        assert!(result.contains("**Producers:** test-project\n**Consumers:** billing\n"));
+
+        let args = json!({"project": "billing", "topic": "users.deleted"});
+        assert!(get_messaging_info(&projects, &args).is_err());
     }
 
     #[test]
-    fn test_get_docs() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project"});
-        let result = get_docs(&projects, &args).unwrap();
-        assert!(result.contains("readme"));
-        assert!(result.contains("Project readme"));
+    fn test_get_api_examples() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("examples")).unwrap();
+        std::fs::write(
+            temp.path().join("examples/login.sh"),
+            "curl -X POST \"$API/auth/login\" -d @creds.json\n",
+        )
+        .unwrap();
+        let mut projects = create_test_projects();
+        let project = projects.get_mut("test-project").unwrap();
+        project.0 = temp.path().to_path_buf();
+        project.1.api = Some(
+            toml::from_str(
+                "endpoints = [\"GET /users\"]\n\n[examples.list-users]\nendpoint = \"GET /users\"\nsummary = \"Requires a service token\"\nsnippet = \"curl -H 'Authorization: Bearer $TOKEN' $API/users\"\n\n[examples.login]\nendpoint = \"POST /auth/login\"\nfile = \"examples/login.sh\"\n",
+            )
+            .unwrap(),
+        );
+
+        let result = get_api_examples(&projects, &json!({"project": "test-project"})).unwrap();
+        assert!(result.contains(
+            "## list-users\n**Endpoint:** `GET /users`\nRequires a service token\n\n```bash\ncurl -H 'Authorization: Bearer $TOKEN' $API/users\n```\n"
+        ));
+        assert!(result.contains("`examples/login.sh`:\n```bash\ncurl -X POST"));
+
+        let args = json!({"project": "test-project", "endpoint": "post /auth"});
+        let result = get_api_examples(&projects, &args).unwrap();
+        assert!(result.contains("## login"));
+        assert!(!result.contains("## list-users"));
+
+        let args = json!({"project": "test-project", "endpoint": "DELETE"});
+        let err = get_api_examples(&projects, &args).unwrap_err();
+        assert!(err.ends_with("Endpoints with examples: GET /users, POST /auth/login"));
     }
 
     #[test]
-    fn test_get_docs_specific() {
-        let projects = create_test_projects();
-        let args = json!({"project": "test-project", "topic": "readme"});
-        let result = get_docs(&projects, &args).unwrap();
-        assert!(result.contains("README.md"));
+    fn test_get_format_settings() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::write(
+            root.join(".editorconfig"),
+            "root = true\n[*]\nindent_style = space\n[*.md]\ntrim_trailing_whitespace = false\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("app/rustfmt.toml"), "max_width = 80\n").unwrap();
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().0 = root.join("app");
+
+        let args = json!({"project": "test-project", "language": "rust"});
+        let result = get_format_settings(root, &projects, &args).unwrap();
+        assert!(result.starts_with("# Format settings for 'test-project' (rust)\n"));
+        assert!(result
+            .contains("## editorconfig (`.editorconfig`)\n\n**`[*]`**\n- `indent_style`: space\n"));
+        assert!(!result.contains("*.md"));
+        assert!(result.contains("## rustfmt (`app/rustfmt.toml`)\n- `max_width`: 80\n"));
+
+        let args = json!({"project": "test-project", "language": "python"});
+        let result = get_format_settings(&root.join("app"), &projects, &args).unwrap();
+        assert!(result.starts_with("No formatter configuration"));
     }
 
     #[test]
-    fn test_get_workspace_overview_no_workspace() {
-        let projects = create_test_projects();
-        let root = PathBuf::from("/workspace");
-        let result = get_workspace_overview(&root, &None, &projects).unwrap();
-        assert!(result.contains("Workspace Overview"));
-        assert!(result.contains("test-project"));
+    fn test_get_hooks_falls_back_to_workspace_root() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        std::fs::create_dir_all(root.join("app")).unwrap();
+        std::fs::write(
+            root.join(".pre-commit-config.yaml"),
+            "repos:\n  - repo: local\n    hooks:\n      - id: cargo-fmt\n        entry: cargo fmt\n      - id: cargo-test\n        entry: cargo test\n        stages: [pre-push]\n",
+        )
+        .unwrap();
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().0 = root.join("app");
+
+        let args = json!({"project": "test-project"});
+        let result = get_hooks(root, &projects, &args).unwrap();
+        assert!(
+            result.contains("## pre-commit (`.pre-commit-config.yaml` at the workspace root)\n")
+        );
+        assert!(result.contains(
+            "**pre-commit**\n- `cargo-fmt`: `cargo fmt` *(modifies files)*\n\n**pre-push**\n- `cargo-test`: `cargo test`\n"
+        ));
+
+        let result = get_hooks(&root.join("app"), &projects, &args).unwrap();
+        assert!(result.starts_with("No git hook configuration"));
     }
 
     #[test]
-    fn test_get_workspace_overview_with_workspace() {
-        let projects = create_test_projects();
-        let root = PathBuf::from("/workspace");
-        let workspace = Some(WorkspaceConfig {
-            workspace: WorkspaceInfo {
-                name: Some("My Workspace".to_string()),
-                description: Some("A test workspace".to_string()),
-            },
-            conventions: HashMap::new(),
-            gotchas: HashMap::new(),
-        });
-        let result = get_workspace_overview(&root, &workspace, &projects).unwrap();
-        assert!(result.contains("My Workspace"));
-        assert!(result.contains("A test workspace"));
+    fn test_get_code_stats() {
+        let temp = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/lib.rs"), "// lib\npub fn a() {}\n").unwrap();
+        let mut projects = create_test_projects();
+        projects.get_mut("test-project").unwrap().0 = temp.path().to_path_buf();
+
+        let args = json!({"project": "test-project"});
+        let result = get_code_stats(&projects, &FileIndex::default(), &args).unwrap();
+        assert!(result.contains("| Rust | 1 | 2 | 1 |\n"));
+        assert!(result.contains("- `src`: 1 lines of code in 1 file(s)\n"));
     }
 
     #[test]
@@ -1707,8 +7739,58 @@ mod tests {
         assert!(tool_names.contains(&"get_docs"));
         assert!(tool_names.contains(&"get_workspace_overview"));
         assert!(tool_names.contains(&"get_workspace_conventions"));
+        assert!(tool_names.contains(&"get_context_health"));
         assert!(tool_names.contains(&"reload_workspace"));
         assert!(tool_names.contains(&"get_jumble_authoring_prompt"));
+        assert_eq!(
+            tool_names.contains(&"run_skill_script"),
+            cfg!(feature = "exec")
+        );
+    }
+
+    #[test]
+    fn test_every_tool_is_annotated() {
+        let list = tools_list();
+        let tools = list["tools"].as_array().unwrap();
+        for tool in tools.iter().chain([&author_project_config_tool()]) {
+            let annotations = &tool["annotations"];
+            for hint in ["readOnlyHint", "idempotentHint", "openWorldHint"] {
+                assert!(annotations[hint].is_boolean(), "{} {}", tool["name"], hint);
+            }
+        }
+        let annotations = |name: &str| {
+            tools
+                .iter()
+                .find(|t| t["name"] == name)
+                .map(|t| t["annotations"].clone())
+                .unwrap()
+        };
+        assert_eq!(annotations("get_commands")["readOnlyHint"], true);
+        assert_eq!(annotations("reload_workspace")["readOnlyHint"], true);
+        assert_eq!(annotations("get_repo_activity")["openWorldHint"], true);
+        assert_eq!(
+            annotations("clear_memories"),
+            json!({"readOnlyHint": false, "destructiveHint": true, "idempotentHint": true, "openWorldHint": false})
+        );
+
+        // Every listed writer is a real tool.
+        for (name, _, _) in WRITE_TOOLS {
+            assert!(
+                *name == AUTHOR_PROJECT_CONFIG
+                    || (*name == "run_skill_script" && !cfg!(feature = "exec"))
+                    || tools.iter().any(|t| t["name"] == *name),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_requires_argument() {
+        assert!(requires_argument("get_commands", "project"));
+        assert!(!requires_argument("get_commands", "command_type"));
+        assert!(!requires_argument("list_projects", "project"));
+        assert!(!requires_argument("no_such_tool", "project"));
     }
 
     #[test]
@@ -1796,6 +7878,227 @@ mod tests {
             .any(|c| c.relative_path.contains("template.json")));
     }
 
+    #[test]
+    fn test_list_skills_filters_by_applies_to() {
+        let (name, mut data) = create_test_project();
+        for (topic, frontmatter) in [
+            (
+                "add-endpoint",
+                "description: Add an endpoint\napplies_to:\n  files: [\"src/api/**\"]\n",
+            ),
+            (
+                "fix-flaky-test",
+                "description: Fix a flaky test\napplies_to:\n  tags: [bugfix]\n",
+            ),
+            (
+                "django-migration",
+                "description: Write a migration\napplies_to:\n  languages: [python]\n",
+            ),
+            ("style", "description: Style guide\n"),
+        ] {
+            data.2.skills.insert(
+                topic.to_string(),
+                SkillInfo {
+                    path: PathBuf::from(format!("/tmp/{}.md", topic)),
+                    skill_dir: None,
+                    frontmatter: Some(serde_yaml::from_str(frontmatter).unwrap()),
+                    preview: String::new(),
+                },
+            );
+        }
+        let mut projects = HashMap::new();
+        projects.insert(name, data);
+
+        let result = list_skills(&projects, &json!({"project": "test-project"})).unwrap();
+        assert!(result.contains("- add-endpoint: Add an endpoint\n"));
+        assert!(result.contains("- fix-flaky-test: Fix a flaky test\n"));
+        assert!(result.contains("*Not applicable here: django-migration*\n"));
+
+        let args = json!({
+            "project": "test-project",
+            "context": {"file": "src/db/pool.rs", "task": "feature"}
+        });
+        let result = list_skills(&projects, &args).unwrap();
+        assert!(result.contains("- style: Style guide\n"));
+        assert!(result
+            .contains("*Not applicable here: add-endpoint, django-migration, fix-flaky-test*\n"));
+
+        let args = json!({
+            "project": "test-project",
+            "context": {"file": "./src/api/users.rs", "task": "BugFix"}
+        });
+        let result = list_skills(&projects, &args).unwrap();
+        assert!(result.contains("- add-endpoint"));
+        assert!(result.contains("- fix-flaky-test"));
+    }
+
+    #[test]
+    fn test_split_skill_messages_by_declared_roles() {
+        let mut roles = HashMap::new();
+        roles.insert("system".to_string(), "## Context".to_string());
+        roles.insert("user".to_string(), "## Task".to_string());
+        let frontmatter = SkillFrontmatter {
+            roles,
+            ..Default::default()
+        };
+        let body = "Intro line\n## Context\nYou are reviewing code.\n## Task\nReview the diff.\n";
+
+        let messages = split_skill_messages(body, Some(&frontmatter));
+        assert_eq!(
+            messages,
+            vec![
+                ("user".to_string(), "Intro line".to_string()),
+                ("system".to_string(), "You are reviewing code.".to_string()),
+                ("user".to_string(), "Review the diff.".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_prompt_roles_fold_other_roles_into_the_first_user_message() {
+        let message = |role: &str, text: &str| (role.to_string(), text.to_string());
+        let messages = [
+            message("system", "Be strict."),
+            message("assistant", "Ready."),
+            message("user", "Review the diff."),
+            message("user", "Then summarise."),
+        ];
+        assert_eq!(
+            prompt_roles(&messages),
+            [
+                message("assistant", "Ready."),
+                message("user", "Be strict.\n\nReview the diff."),
+                message("user", "Then summarise."),
+            ]
+        );
+        assert_eq!(
+            prompt_roles(&[message("system", "Be strict.")]),
+            [message("user", "Be strict.")]
+        );
+    }
+
+    #[test]
+    fn test_skill_body_skips_frontmatter_with_crlf() {
+        let content = "---\r\ndescription: Review\r\n---\r\n## Task\r\nReview it.\r\n";
+        assert_eq!(skill_body(content), "## Task\r\nReview it.\r\n");
+    }
+
+    #[test]
+    fn test_split_skill_messages_without_roles_is_single_user_message() {
+        let messages = split_skill_messages("# Title\nBody\n", None);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].0, "user");
+        assert!(messages[0].1.contains("Body"));
+    }
+
+    #[test]
+    fn test_get_skill_messages_format() {
+        let temp = TempDir::new().unwrap();
+        let skill_path = temp.path().join("review.md");
+        std::fs::write(
+            &skill_path,
+            "---\ndescription: Review code\nroles:\n  system: \"## Context\"\n---\n## Context\nBe strict.\n",
+        )
+        .unwrap();
+
+        let mut roles = HashMap::new();
+        roles.insert("system".to_string(), "## Context".to_string());
+        let (name, mut data) = create_test_project();
+        data.2.skills.insert(
+            "review".to_string(),
+            SkillInfo {
+                path: skill_path,
+                skill_dir: None,
+                frontmatter: Some(SkillFrontmatter {
+                    description: Some("Review code".to_string()),
+                    roles,
+                    ..Default::default()
+                }),
+                preview: String::new(),
+            },
+        );
+        let mut projects = HashMap::new();
+        projects.insert(name, data);
+
+        let args = json!({"project": "test-project", "topic": "review", "format": "messages"});
+        let result = get_skill(&projects, &args).unwrap();
+        let parsed: Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(parsed["description"], "Review code");
+        assert_eq!(parsed["messages"][0]["role"], "user");
+        assert_eq!(parsed["messages"][0]["content"]["text"], "Be strict.");
+
+        let args = json!({"project": "test-project", "topic": "review", "format": "xml"});
+        assert!(get_skill(&projects, &args).is_err());
+    }
+
+    #[test]
+    fn test_format_skill_scripts_gives_invocations() {
+        let temp = TempDir::new().unwrap();
+        let scripts = temp.path().join("scripts");
+        std::fs::create_dir_all(&scripts).unwrap();
+        std::fs::write(
+            scripts.join("scaffold.py"),
+            "#!/usr/bin/env python3\n# Create a handler and its test.\nimport sys\n",
+        )
+        .unwrap();
+        std::fs::write(scripts.join("check.sh"), "set -e\ncargo test\n").unwrap();
+        std::fs::write(scripts.join("strict.sh"), "#!/bin/bash -eu\nexit 0\n").unwrap();
+        std::fs::write(scripts.join("task.ts"), "#!/usr/bin/env -S deno run -A\n").unwrap();
+        std::fs::write(scripts.join("notes.txt"), "not a script\n").unwrap();
+
+        let result = format_skill_scripts(temp.path());
+        let dir = scripts.display();
+        assert!(result.starts_with("\n## Helper Scripts\n"));
+        assert!(result.contains(&format!("- **check.sh**\n  `sh {}/check.sh`\n", dir)));
+        assert!(result.contains(&format!(
+            "- **scaffold.py**: Create a handler and its test.\n  `python3 {}/scaffold.py`\n",
+            dir
+        )));
+        assert!(result.contains(&format!("`bash -eu {}/strict.sh`", dir)));
+        assert!(result.contains(&format!("`deno run -A {}/task.ts`", dir)));
+        assert!(!result.contains("notes.txt"));
+
+        assert_eq!(format_skill_scripts(&temp.path().join("missing")), "");
+    }
+
+    #[test]
+    fn test_skill_script_invocation_only_runs_listed_scripts() {
+        let temp = TempDir::new().unwrap();
+        let skill_dir = temp.path().join("scaffold");
+        std::fs::create_dir_all(skill_dir.join("scripts")).unwrap();
+        std::fs::write(skill_dir.join("SKILL.md"), "# Scaffold\n").unwrap();
+        std::fs::write(skill_dir.join("scripts/new.sh"), "echo new\n").unwrap();
+        std::fs::write(skill_dir.join("notes.sh"), "echo outside\n").unwrap();
+
+        let (name, mut data) = create_test_project();
+        data.2.skills.insert(
+            "scaffold".to_string(),
+            SkillInfo {
+                path: skill_dir.join("SKILL.md"),
+                skill_dir: Some(skill_dir.clone()),
+                frontmatter: None,
+                preview: String::new(),
+            },
+        );
+        let mut projects = HashMap::new();
+        projects.insert(name, data);
+        let call = |script: &str, args: Value| {
+            skill_script_invocation(
+                &projects,
+                &json!({"project": "test-project", "topic": "scaffold", "script": script, "args": args}),
+            )
+        };
+
+        let (_, argv) = call("new.sh", json!(["handler", "$(whoami)"])).unwrap();
+        let script = skill_dir.join("scripts/new.sh").display().to_string();
+        assert_eq!(argv, ["sh", script.as_str(), "handler", "$(whoami)"]);
+
+        let err = call("../notes.sh", Value::Null).unwrap_err();
+        assert_eq!(err, "Script '../notes.sh' not found. Available: new.sh");
+        assert!(call("new.sh", json!([1])).is_err());
+        assert!(call("new.sh", json!("handler")).is_err());
+    }
+
     #[test]
     fn test_format_skill_with_companions() {
         let skill_content = "# My Skill\n\nThis is a test skill.";
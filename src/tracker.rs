@@ -0,0 +1,132 @@
+//! Ticket references and links for the `[tracker]` section.
+//!
+//! Jira and Linear tickets are written `KEY-123`; GitHub and GitLab issues
+//! are `#123`. IDs are normalized to that form before building URLs, so an
+//! agent passing `pay-123` or a bare `123` gets the reference the team uses.
+
+use crate::config::{TrackerConfig, TrackerKind};
+
+/// A normalized ticket reference and its URL.
+#[derive(Debug, PartialEq)]
+pub struct Ticket {
+    /// `PAY-123` or `#123`.
+    pub reference: String,
+    pub url: String,
+}
+
+/// Normalize `id` for `tracker` and build its URL.
+pub fn ticket(tracker: &TrackerConfig, id: &str) -> Result<Ticket, String> {
+    let id = id.trim().trim_start_matches('#');
+    let base = tracker.base_url.trim_end_matches('/');
+    match tracker.kind {
+        TrackerKind::Jira | TrackerKind::Linear => {
+            let reference = keyed_reference(tracker, id)?;
+            let path = if tracker.kind == TrackerKind::Jira {
+                "browse"
+            } else {
+                "issue"
+            };
+            Ok(Ticket {
+                url: format!("{}/{}/{}", base, path, reference),
+                reference,
+            })
+        }
+        TrackerKind::Github | TrackerKind::Gitlab => {
+            if !is_number(id) {
+                return Err(format!("'{}' is not an issue number", id));
+            }
+            let path = if tracker.kind == TrackerKind::Github {
+                "issues"
+            } else {
+                "-/issues"
+            };
+            Ok(Ticket {
+                reference: format!("#{}", id),
+                url: format!("{}/{}/{}", base, path, id),
+            })
+        }
+    }
+}
+
+/// `KEY-123` from `123` (using `project_key`) or any casing of `key-123`.
+fn keyed_reference(tracker: &TrackerConfig, id: &str) -> Result<String, String> {
+    if is_number(id) {
+        let key = tracker.project_key.as_deref().ok_or_else(|| {
+            format!(
+                "'{}' has no project key. Pass an ID like PAY-{} or set project_key under [tracker]",
+                id, id
+            )
+        })?;
+        return Ok(format!("{}-{}", key.to_uppercase(), id));
+    }
+    match id.rsplit_once(['-', '_', ' ']) {
+        Some((key, number))
+            if is_number(number)
+                && key.starts_with(|c: char| c.is_ascii_alphabetic())
+                && key.chars().all(|c| c.is_ascii_alphanumeric()) =>
+        {
+            Ok(format!("{}-{}", key.to_uppercase(), number))
+        }
+        _ => Err(format!("'{}' is not a ticket ID like PAY-123", id)),
+    }
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Fill `{ticket}` in a branch or commit pattern.
+pub fn fill(pattern: &str, ticket: &Ticket) -> String {
+    pattern.replace("{ticket}", &ticket.reference)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracker(toml: &str) -> TrackerConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_keyed_trackers_normalize_ids() {
+        let jira = tracker(
+            "type = \"jira\"\nbase_url = \"https://acme.atlassian.net/\"\nproject_key = \"pay\"\n",
+        );
+        for id in ["123", "PAY-123", "pay-123", "pay_123", "#PAY-123"] {
+            assert_eq!(
+                ticket(&jira, id).unwrap(),
+                Ticket {
+                    reference: "PAY-123".to_string(),
+                    url: "https://acme.atlassian.net/browse/PAY-123".to_string(),
+                },
+                "{}",
+                id
+            );
+        }
+        assert!(ticket(&jira, "fix login").is_err());
+
+        let linear = tracker("type = \"linear\"\nbase_url = \"https://linear.app/acme\"\n");
+        assert_eq!(
+            ticket(&linear, "eng-7").unwrap().url,
+            "https://linear.app/acme/issue/ENG-7"
+        );
+        assert!(ticket(&linear, "7").unwrap_err().contains("project_key"));
+    }
+
+    #[test]
+    fn test_forge_trackers_use_issue_numbers() {
+        let github = tracker("type = \"github\"\nbase_url = \"https://github.com/acme/api\"\n");
+        let issue = ticket(&github, "#42").unwrap();
+        assert_eq!(issue.reference, "#42");
+        assert_eq!(issue.url, "https://github.com/acme/api/issues/42");
+        assert!(ticket(&github, "PAY-42").is_err());
+
+        let gitlab = tracker("type = \"gitlab\"\nbase_url = \"https://gitlab.com/acme/api\"\n");
+        assert_eq!(
+            ticket(&gitlab, "42").unwrap().url,
+            "https://gitlab.com/acme/api/-/issues/42"
+        );
+        assert_eq!(fill("{ticket}: {summary}", &issue), "#42: {summary}");
+    }
+}
@@ -0,0 +1,263 @@
+//! Request/response transcripts: `jumble --record <file>` appends each
+//! exchange to a JSONL file and `jumble replay <file>` runs the requests
+//! again against the current workspace, diffing the answers.
+//!
+//! Values under keys that look like credentials are replaced before
+//! anything is written, so transcripts can be attached to bug reports.
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::protocol::{self, JsonRpcResponse};
+use crate::server::{Server, ServerOptions};
+use crate::text;
+
+/// Keys whose values are never written to a transcript, lowercase and
+/// without `_` or `-`. Whole names only, so `progressToken` and `max_tokens`
+/// stay readable.
+const SECRET_KEYS: &[&str] = &[
+    "token",
+    "secret",
+    "password",
+    "passwd",
+    "authorization",
+    "apikey",
+    "accesstoken",
+    "authtoken",
+    "bearertoken",
+    "refreshtoken",
+    "clientsecret",
+    "privatekey",
+];
+
+const REDACTED: &str = "[redacted]";
+
+/// Appends exchanges to a transcript file.
+pub struct Recorder {
+    file: File,
+}
+
+impl Recorder {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open transcript {}", path.display()))?;
+        Ok(Self { file })
+    }
+
+    /// Record the raw request `line` and the response written for it, if any.
+    /// Lines that aren't JSON are kept as strings.
+    pub fn record(&mut self, line: &[u8], response: Option<&JsonRpcResponse>) -> Result<()> {
+        let line = String::from_utf8_lossy(line);
+        let request = serde_json::from_str(&line).unwrap_or(Value::String(line.into_owned()));
+        let response = match response {
+            Some(response) => serde_json::to_value(response)?,
+            None => Value::Null,
+        };
+        let entry = json!({
+            "request": sanitize(request),
+            "response": sanitize(response),
+        });
+        writeln!(self.file, "{}", entry)?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// `value` with anything under a credential-like key replaced.
+pub fn sanitize(value: Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| {
+                    let name: String = key
+                        .chars()
+                        .filter(|c| !matches!(c, '_' | '-'))
+                        .collect::<String>()
+                        .to_lowercase();
+                    if SECRET_KEYS.contains(&name.as_str()) {
+                        (key, Value::String(REDACTED.to_string()))
+                    } else {
+                        (key, sanitize(value))
+                    }
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.into_iter().map(sanitize).collect()),
+        other => other,
+    }
+}
+
+/// A replayed request whose response changed.
+pub struct Mismatch {
+    /// 1-based line in the transcript.
+    pub line: usize,
+    /// Method, and tool name for tool calls.
+    pub label: String,
+    pub diff: String,
+}
+
+/// Replay the transcript at `path` against the workspace at `root`. Returns
+/// the number of exchanges replayed and those whose response differs.
+pub fn replay(root: &Path, path: &Path) -> Result<(usize, Vec<Mismatch>)> {
+    let content = text::read_text(path)
+        .with_context(|| format!("Failed to read transcript {}", path.display()))?;
+    let options = ServerOptions {
+        // A transcript is replayed as fast as it can be read.
+        max_requests_per_second: u32::MAX,
+        ..ServerOptions::default()
+    };
+    let server = Server::with_options(root.to_path_buf(), options)?;
+
+    let mut replayed = 0;
+    let mut mismatches = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry: Value = serde_json::from_str(line)
+            .with_context(|| format!("{}:{}: not a transcript entry", path.display(), index + 1))?;
+        let request = &entry["request"];
+        let expected = &entry["response"];
+        let actual = match protocol::parse_message(&request.to_string(), false) {
+            Ok(message) => server.handle_message(message),
+            Err(error) => Some(JsonRpcResponse::error(None, error)),
+        };
+        server.take_outgoing();
+        let actual = sanitize(match actual {
+            Some(response) => serde_json::to_value(&response)?,
+            None => Value::Null,
+        });
+        replayed += 1;
+
+        if &actual != expected {
+            let (expected, actual) = (response_text(expected), response_text(&actual));
+            let diff = similar::TextDiff::from_lines(&expected, &actual)
+                .unified_diff()
+                .header("recorded", "replayed")
+                .to_string();
+            mismatches.push(Mismatch {
+                line: index + 1,
+                label: label(request),
+                diff,
+            });
+        }
+    }
+    Ok((replayed, mismatches))
+}
+
+/// `tools/call get_commands`, or just the method for other requests.
+fn label(request: &Value) -> String {
+    let method = request["method"].as_str().unwrap_or("?");
+    match request["params"]["name"].as_str() {
+        Some(tool) if method == "tools/call" => format!("{} {}", method, tool),
+        _ => method.to_string(),
+    }
+}
+
+/// Tool output as plain text so markdown diffs line by line; anything else
+/// as pretty JSON.
+fn response_text(response: &Value) -> String {
+    let texts: Option<Vec<&str>> = response["result"]["content"]
+        .as_array()
+        .and_then(|content| content.iter().map(|c| c["text"].as_str()).collect());
+    match texts {
+        Some(texts) => format!("{}\n", texts.join("\n")),
+        None => format!(
+            "{}\n",
+            serde_json::to_string_pretty(response).unwrap_or_default()
+        ),
+    }
+}
+
+/// `jumble replay <file>`.
+pub fn run_replay(root: &Path, path: PathBuf) -> Result<()> {
+    let (replayed, mismatches) = replay(root, &path)?;
+    if mismatches.is_empty() {
+        println!("✓ {} response(s) match the transcript", replayed);
+        return Ok(());
+    }
+    for mismatch in &mismatches {
+        println!("✗ line {}: {}", mismatch.line, mismatch.label);
+        print!("{}", mismatch.diff);
+    }
+    anyhow::bail!(
+        "{} of {} response(s) differ from the transcript",
+        mismatches.len(),
+        replayed
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sanitize_redacts_credential_keys() {
+        let value = json!({
+            "arguments": {"project": "api", "apiKey": "abc", "headers": [{"Authorization": "Bearer x"}]},
+            "access_token": "x",
+            "_meta": {"progressToken": 7},
+            "max_tokens": 100,
+        });
+        assert_eq!(
+            sanitize(value),
+            json!({
+                "arguments": {"project": "api", "apiKey": REDACTED, "headers": [{"Authorization": REDACTED}]},
+                "access_token": REDACTED,
+                "_meta": {"progressToken": 7},
+                "max_tokens": 100,
+            })
+        );
+    }
+
+    #[test]
+    fn test_replay_diffs_changed_responses() {
+        let temp = TempDir::new().unwrap();
+        let jumble_dir = temp.path().join("app/.jumble");
+        fs::create_dir_all(&jumble_dir).unwrap();
+        let project = jumble_dir.join("project.toml");
+        fs::write(
+            &project,
+            "[project]\nname = \"app\"\ndescription = \"App\"\n\n[commands]\ntest = \"cargo test\"\n",
+        )
+        .unwrap();
+
+        let transcript = temp.path().join("session.jsonl");
+        let server =
+            Server::with_options(temp.path().to_path_buf(), ServerOptions::default()).unwrap();
+        let mut recorder = Recorder::open(&transcript).unwrap();
+        for line in [
+            r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#,
+            r#"{"jsonrpc":"2.0","id":2,"method":"tools/call","params":{"name":"get_commands","arguments":{"project":"app"}}}"#,
+        ] {
+            let message = protocol::parse_message(line, false).unwrap();
+            let response = server.handle_message(message);
+            recorder.record(line.as_bytes(), response.as_ref()).unwrap();
+        }
+
+        let (replayed, mismatches) = replay(temp.path(), &transcript).unwrap();
+        assert_eq!((replayed, mismatches.len()), (2, 0));
+
+        fs::write(
+            &project,
+            "[project]\nname = \"app\"\ndescription = \"App\"\n\n[commands]\ntest = \"cargo nextest run\"\n",
+        )
+        .unwrap();
+        let (_, mismatches) = replay(temp.path(), &transcript).unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].line, 2);
+        assert_eq!(mismatches[0].label, "tools/call get_commands");
+        assert!(mismatches[0].diff.contains("-- **test**: `cargo test`\n"));
+        assert!(mismatches[0]
+            .diff
+            .contains("+- **test**: `cargo nextest run`\n"));
+    }
+}
@@ -0,0 +1,575 @@
+//! `jumble tui`: browse the workspace's context as a tree of projects and
+//! what each one declares, to see the map an agent gets and where it has
+//! holes.
+//!
+//! Each project opens into concepts, docs, skills, conventions and gotchas.
+//! Selecting a project, concept or skill shows what the matching tool
+//! returns (`get_project_info`, `get_architecture`, `get_skill`); docs,
+//! conventions and gotchas show their text. Empty sections and projects
+//! without a description are highlighted, with a note on what to add.
+//!
+//! `.jumble` files and indexed docs are fingerprinted every poll interval the
+//! server's watcher falls back to, and the tree is rebuilt when they change,
+//! keeping what was open.
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use crate::paths;
+use crate::protocol::{self, JsonRpcRequest};
+use crate::server::{Server, ServerOptions};
+use crate::tools::ProjectData;
+use crate::watch;
+
+/// How long to wait for a key before checking for file changes.
+const TICK: Duration = Duration::from_millis(250);
+
+const KEYS: &str = "↑↓ move  → open  ← close  PgUp/PgDn scroll  r reload  q quit";
+
+/// What the right-hand pane shows for a node.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Detail {
+    /// The text result of calling a tool with these arguments.
+    Tool(&'static str, Value),
+    Text(String),
+}
+
+/// One entry in the tree.
+#[derive(Debug, Clone)]
+pub struct Node {
+    /// Identifies the node among its siblings, across reloads.
+    pub name: String,
+    pub label: String,
+    pub detail: Detail,
+    pub children: Vec<Node>,
+    /// Marks context that is missing, such as an empty section.
+    pub gap: bool,
+}
+
+impl Node {
+    fn leaf(label: &str, detail: Detail) -> Self {
+        Node {
+            name: label.to_string(),
+            label: label.to_string(),
+            detail,
+            children: Vec::new(),
+            gap: false,
+        }
+    }
+}
+
+/// A section of a project, flagged as a gap when it has nothing in it.
+fn section(name: &str, mut children: Vec<Node>, missing: &str) -> Node {
+    children.sort_by(|a, b| a.label.cmp(&b.label));
+    let gap = children.is_empty();
+    let detail = if gap {
+        Detail::Text(format!("No {}. {}", name.to_lowercase(), missing))
+    } else {
+        let names: Vec<&str> = children.iter().map(|c| c.label.as_str()).collect();
+        Detail::Text(format!(
+            "{} ({}):\n\n{}",
+            name,
+            names.len(),
+            names.join("\n")
+        ))
+    };
+    Node {
+        name: name.to_string(),
+        label: format!("{} ({})", name, children.len()),
+        detail,
+        children,
+        gap,
+    }
+}
+
+/// The tree for `projects`, sorted by name.
+pub fn outline(projects: &HashMap<String, ProjectData>) -> Vec<Node> {
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| {
+            let (_, config, skills, conventions, docs, _) = &projects[name];
+            let concepts = config
+                .concepts
+                .keys()
+                .map(|concept| {
+                    let args = json!({"project": name, "concept": concept});
+                    Node::leaf(concept, Detail::Tool("get_architecture", args))
+                })
+                .collect();
+            let docs = docs
+                .docs
+                .iter()
+                .map(|(topic, doc)| {
+                    let text = format!("{}\n\n{}", doc.path, doc.summary);
+                    Node::leaf(topic, Detail::Text(text.trim_end().to_string()))
+                })
+                .collect();
+            let skills = skills
+                .skills
+                .keys()
+                .map(|topic| {
+                    let args = json!({"project": name, "topic": topic});
+                    Node::leaf(topic, Detail::Tool("get_skill", args))
+                })
+                .collect();
+            let text_nodes = |entries: &HashMap<String, String>| {
+                entries
+                    .iter()
+                    .map(|(key, text)| Node::leaf(key, Detail::Text(text.clone())))
+                    .collect()
+            };
+            let children = vec![
+                section(
+                    "Concepts",
+                    concepts,
+                    &format!(
+                        "Add [concepts.<name>] with files and a summary to {}.",
+                        paths::config_file("project.toml")
+                    ),
+                ),
+                section(
+                    "Docs",
+                    docs,
+                    &format!(
+                        "Index documentation under [docs.<topic>] in {}.",
+                        paths::config_file("docs.toml")
+                    ),
+                ),
+                section(
+                    "Skills",
+                    skills,
+                    &format!(
+                        "Write task guides as {}.",
+                        paths::config_file("skills/<topic>.md")
+                    ),
+                ),
+                section(
+                    "Conventions",
+                    text_nodes(&conventions.conventions),
+                    &format!(
+                        "Add a [conventions] table to {}.",
+                        paths::config_file("conventions.toml")
+                    ),
+                ),
+                section(
+                    "Gotchas",
+                    text_nodes(&conventions.gotchas),
+                    &format!(
+                        "Add a [gotchas] table to {}.",
+                        paths::config_file("conventions.toml")
+                    ),
+                ),
+            ];
+            let undescribed = config.project.description.trim().is_empty();
+            Node {
+                gap: undescribed || children.iter().any(|c| c.gap),
+                name: name.clone(),
+                label: name.clone(),
+                detail: Detail::Tool("get_project_info", json!({ "project": name })),
+                children,
+            }
+        })
+        .collect()
+}
+
+/// A visible line of the tree.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    /// Node names from the top of the tree down to this one.
+    key: Vec<String>,
+    depth: usize,
+    label: String,
+    detail: Detail,
+    expandable: bool,
+    gap: bool,
+}
+
+/// The rows shown when the nodes whose keys are in `expanded` are open.
+fn visible(nodes: &[Node], expanded: &HashSet<Vec<String>>) -> Vec<Row> {
+    fn walk(
+        nodes: &[Node],
+        expanded: &HashSet<Vec<String>>,
+        parent: &[String],
+        rows: &mut Vec<Row>,
+    ) {
+        for node in nodes {
+            let mut key = parent.to_vec();
+            key.push(node.name.clone());
+            rows.push(Row {
+                key: key.clone(),
+                depth: parent.len(),
+                label: node.label.clone(),
+                detail: node.detail.clone(),
+                expandable: !node.children.is_empty(),
+                gap: node.gap,
+            });
+            if expanded.contains(&key) {
+                walk(&node.children, expanded, &key, rows);
+            }
+        }
+    }
+    let mut rows = Vec::new();
+    walk(nodes, expanded, &[], &mut rows);
+    rows
+}
+
+struct App {
+    server: Server,
+    next_id: u64,
+    nodes: Vec<Node>,
+    expanded: HashSet<Vec<String>>,
+    rows: Vec<Row>,
+    list: ListState,
+    /// Text for the selected row, and how far it is scrolled.
+    detail: String,
+    scroll: u16,
+    status: String,
+    fingerprint: watch::Fingerprint,
+}
+
+impl App {
+    fn request(&mut self, method: &str, params: Value) -> Result<Value, String> {
+        self.next_id += 1;
+        let response = self.server.handle_request(JsonRpcRequest {
+            jsonrpc: "2.0".to_string(),
+            id: Some(json!(self.next_id)),
+            method: method.to_string(),
+            params,
+        });
+        let response = response.ok_or("The server sent no response")?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(error.message),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err("The server sent an empty response".to_string()),
+        }
+    }
+
+    fn selected(&self) -> Option<&Row> {
+        self.list.selected().and_then(|i| self.rows.get(i))
+    }
+
+    /// Rebuild the rows, keeping the selection on the same key if it's
+    /// still there.
+    fn refresh(&mut self) {
+        let key = self.selected().map(|row| row.key.clone());
+        self.rows = visible(&self.nodes, &self.expanded);
+        let index = key
+            .and_then(|key| self.rows.iter().position(|row| row.key == key))
+            .unwrap_or(0)
+            .min(self.rows.len().saturating_sub(1));
+        self.list.select((!self.rows.is_empty()).then_some(index));
+        self.show_selected();
+    }
+
+    fn show_selected(&mut self) {
+        self.scroll = 0;
+        let Some(detail) = self.selected().map(|row| row.detail.clone()) else {
+            self.detail =
+                "No projects found. Run `jumble init` in a project to describe it.".to_string();
+            return;
+        };
+        self.detail = match detail {
+            Detail::Text(text) => text,
+            Detail::Tool(tool, arguments) => {
+                match self.request("tools/call", json!({"name": tool, "arguments": arguments})) {
+                    Ok(result) => result["content"][0]["text"]
+                        .as_str()
+                        .unwrap_or_default()
+                        .to_string(),
+                    Err(message) => format!("✗ {}", message),
+                }
+            }
+        };
+    }
+
+    fn reload(&mut self) {
+        self.status = match self.server.reload() {
+            Ok(()) => "Reloaded".to_string(),
+            Err(e) => format!("Reload failed: {:#}", e),
+        };
+        // Nobody subscribes here, so notifications the reload queued are dropped.
+        self.server.take_outgoing();
+        self.fingerprint = watch::scan(&self.server);
+        self.nodes = self.server.outline();
+        self.refresh();
+    }
+
+    fn move_by(&mut self, delta: isize) {
+        if self.rows.is_empty() {
+            return;
+        }
+        let current = self.list.selected().unwrap_or(0) as isize;
+        let next = (current + delta).clamp(0, self.rows.len() as isize - 1) as usize;
+        if Some(next) != self.list.selected() {
+            self.list.select(Some(next));
+            self.show_selected();
+        }
+    }
+
+    /// Open the selected node, or step into it when it's already open.
+    fn open(&mut self) {
+        let Some(row) = self.selected().cloned() else {
+            return;
+        };
+        if !row.expandable {
+            return;
+        }
+        if self.expanded.insert(row.key) {
+            self.refresh();
+        } else {
+            self.move_by(1);
+        }
+    }
+
+    /// Close the selected node, or move to its parent when it's closed.
+    fn close(&mut self) {
+        let Some(row) = self.selected().cloned() else {
+            return;
+        };
+        if self.expanded.remove(&row.key) {
+            self.refresh();
+        } else if let Some(parent) = self.rows.iter().position(|r| r.key == row.key[..row.depth]) {
+            self.list.select(Some(parent));
+            self.show_selected();
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [tree, detail] =
+            Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)])
+                .areas(main);
+
+        let items: Vec<ListItem> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let marker = match (row.expandable, self.expanded.contains(&row.key)) {
+                    (false, _) => " ",
+                    (true, true) => "▾",
+                    (true, false) => "▸",
+                };
+                let text = format!("{}{} {}", "  ".repeat(row.depth), marker, row.label);
+                let style = if row.gap {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default()
+                };
+                ListItem::new(Line::styled(text, style))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Workspace "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, tree, &mut self.list);
+
+        let title = self
+            .selected()
+            .map(|row| format!(" {} ", row.key.join(" › ")))
+            .unwrap_or_default();
+        let text = Paragraph::new(self.detail.as_str())
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        frame.render_widget(text, detail);
+
+        let status = if self.status.is_empty() {
+            KEYS.to_string()
+        } else {
+            format!("{}  ·  {}", self.status, KEYS)
+        };
+        frame.render_widget(
+            Paragraph::new(status).style(Style::default().add_modifier(Modifier::DIM)),
+            footer,
+        );
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let mut last_poll = Instant::now();
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind != KeyEventKind::Press {
+                        continue;
+                    }
+                    match key.code {
+                        KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                        KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+                        KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+                        KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') => self.open(),
+                        KeyCode::Left | KeyCode::Char('h') => self.close(),
+                        KeyCode::PageDown => self.scroll = self.scroll.saturating_add(10),
+                        KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(10),
+                        KeyCode::Char('r') => self.reload(),
+                        _ => {}
+                    }
+                }
+            }
+            if last_poll.elapsed() >= watch::POLL_INTERVAL {
+                last_poll = Instant::now();
+                if watch::scan(&self.server) != self.fingerprint {
+                    self.reload();
+                }
+            }
+        }
+    }
+}
+
+/// `jumble tui`
+pub fn run_tui(root: &Path) -> Result<()> {
+    let server = Server::with_options(root.to_path_buf(), ServerOptions::default())?;
+    let mut app = App {
+        fingerprint: watch::scan(&server),
+        nodes: server.outline(),
+        server,
+        next_id: 0,
+        expanded: HashSet::new(),
+        rows: Vec::new(),
+        list: ListState::default(),
+        detail: String::new(),
+        scroll: 0,
+        status: String::new(),
+    };
+    app.request(
+        "initialize",
+        json!({
+            "protocolVersion": protocol::LATEST_PROTOCOL_VERSION,
+            "capabilities": {},
+            "clientInfo": {"name": "jumble-tui", "version": env!("CARGO_PKG_VERSION")}
+        }),
+    )
+    .map_err(anyhow::Error::msg)?;
+    app.refresh();
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{
+        Concept, DocEntry, ProjectConfig, ProjectConventions, ProjectDocs, ProjectSkills,
+    };
+    use crate::memory;
+    use tempfile::TempDir;
+
+    fn projects(temp: &TempDir) -> HashMap<String, ProjectData> {
+        let mut config: ProjectConfig =
+            toml::from_str("[project]\nname = \"api\"\ndescription = \"The API\"\n").unwrap();
+        config.concepts.insert(
+            "auth".to_string(),
+            Concept {
+                files: vec!["src/auth.rs".to_string()],
+                summary: "Login".to_string(),
+                pinned: false,
+                prompts: Vec::new(),
+            },
+        );
+        let mut docs = ProjectDocs::default();
+        docs.docs.insert(
+            "setup".to_string(),
+            DocEntry {
+                path: "docs/setup.md".to_string(),
+                summary: "Getting started".to_string(),
+            },
+        );
+        let mut conventions = ProjectConventions::default();
+        conventions
+            .conventions
+            .insert("errors".to_string(), "Use anyhow".to_string());
+        let data = (
+            temp.path().to_path_buf(),
+            config,
+            ProjectSkills::default(),
+            conventions,
+            docs,
+            memory::open_or_create_memory_db(temp.path()).unwrap(),
+        );
+        HashMap::from([("api".to_string(), data)])
+    }
+
+    #[test]
+    fn test_outline_sections_and_gaps() {
+        let temp = TempDir::new().unwrap();
+        let nodes = outline(&projects(&temp));
+        assert_eq!(nodes.len(), 1);
+        let api = &nodes[0];
+        assert_eq!(api.label, "api");
+        assert_eq!(
+            api.detail,
+            Detail::Tool("get_project_info", json!({"project": "api"}))
+        );
+        let labels: Vec<&str> = api.children.iter().map(|c| c.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            [
+                "Concepts (1)",
+                "Docs (1)",
+                "Skills (0)",
+                "Conventions (1)",
+                "Gotchas (0)"
+            ]
+        );
+        assert_eq!(
+            api.children[0].children[0].detail,
+            Detail::Tool(
+                "get_architecture",
+                json!({"project": "api", "concept": "auth"})
+            )
+        );
+        assert_eq!(
+            api.children[1].children[0].detail,
+            Detail::Text("docs/setup.md\n\nGetting started".to_string())
+        );
+
+        // Empty sections are gaps, and so is the project holding them.
+        let gaps: Vec<bool> = api.children.iter().map(|c| c.gap).collect();
+        assert_eq!(gaps, [false, false, true, false, true]);
+        assert!(api.gap);
+        let Detail::Text(hint) = &api.children[2].detail else {
+            panic!("expected a hint");
+        };
+        assert!(hint.starts_with("No skills."));
+    }
+
+    #[test]
+    fn test_visible_follows_expanded_keys() {
+        let temp = TempDir::new().unwrap();
+        let nodes = outline(&projects(&temp));
+        assert_eq!(visible(&nodes, &HashSet::new()).len(), 1);
+
+        let mut expanded = HashSet::from([vec!["api".to_string()]]);
+        let rows = visible(&nodes, &expanded);
+        assert_eq!(rows.len(), 6);
+        assert_eq!(rows[1].key, ["api", "Concepts"]);
+        assert_eq!(rows[1].depth, 1);
+        assert!(rows[1].expandable);
+        assert!(!rows[3].expandable);
+
+        expanded.insert(vec!["api".to_string(), "Concepts".to_string()]);
+        let rows = visible(&nodes, &expanded);
+        assert_eq!(rows.len(), 7);
+        assert_eq!(rows[2].key, ["api", "Concepts", "auth"]);
+        assert_eq!(rows[2].depth, 2);
+
+        // A child stays hidden while its parent is closed.
+        expanded.remove(&vec!["api".to_string()]);
+        assert_eq!(visible(&nodes, &expanded).len(), 1);
+    }
+}
@@ -0,0 +1,398 @@
+//! `jumble validate`: check `.jumble` files without starting the server.
+//!
+//! Every `.jumble/*.toml` file under the root is parsed with the same loader
+//! the server uses. With `--links`, indexed docs, concept files and API
+//! example files are also checked for references to files that no longer
+//! exist.
+
+use anyhow::Result;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::config::{
+    JumbleConfig, ProjectConfig, ProjectConventions, ProjectDocs, WorkspaceConfig,
+};
+use crate::health::{self, Referrer};
+use crate::i18n;
+use crate::local;
+use crate::migrate::{self, ConfigKind};
+use crate::paths;
+use crate::ports;
+use crate::server::Server;
+use crate::text;
+
+/// A problem found in a file.
+#[derive(Debug, PartialEq)]
+pub struct Issue {
+    pub path: PathBuf,
+    /// 1-based line, when the problem is tied to one.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.message),
+            None => write!(f, "{}: {}", self.path.display(), self.message),
+        }
+    }
+}
+
+/// Parse every `.jumble/*.toml` file under `root`.
+pub fn check_configs(root: &Path) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    for entry in WalkDir::new(root)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        let kind = match path.file_name().and_then(|n| n.to_str()) {
+            Some(name) if paths::is_in_jumble_dir(path) => ConfigKind::from_file_name(name),
+            _ => None,
+        };
+        let result = match kind {
+            Some(ConfigKind::Project) => {
+                local::load::<ProjectConfig>(ConfigKind::Project, path).map(|_| ())
+            }
+            Some(ConfigKind::Workspace) => {
+                local::load::<WorkspaceConfig>(ConfigKind::Workspace, path).map(|(config, _)| {
+                    if let Some(message) = unsupported_language(&config) {
+                        issues.push(Issue {
+                            path: path.to_path_buf(),
+                            line: None,
+                            message,
+                        });
+                    }
+                    let client_levels =
+                        config.output.clients.iter().filter_map(|(name, client)| {
+                            Some((
+                                format!("output.clients.\"{}\"", name),
+                                client.heading_level?,
+                            ))
+                        });
+                    for (key, level) in
+                        std::iter::once(("output".to_string(), config.output.heading_level))
+                            .chain(client_levels)
+                    {
+                        if !(1..=6).contains(&level) {
+                            issues.push(Issue {
+                                path: path.to_path_buf(),
+                                line: None,
+                                message: format!(
+                                    "{}.heading_level {} is outside 1 to 6",
+                                    key, level
+                                ),
+                            });
+                        }
+                    }
+                    for (port, services) in ports::conflicts(&config.dev_ports) {
+                        issues.push(Issue {
+                            path: path.to_path_buf(),
+                            line: None,
+                            message: format!(
+                                "dev port {} is registered for {}",
+                                port,
+                                services.join(", ")
+                            ),
+                        });
+                    }
+                })
+            }
+            Some(ConfigKind::Conventions) => {
+                migrate::load::<ProjectConventions>(ConfigKind::Conventions, path).map(|_| ())
+            }
+            Some(ConfigKind::Docs) => {
+                migrate::load::<ProjectDocs>(ConfigKind::Docs, path).map(|_| ())
+            }
+            Some(ConfigKind::Global) => {
+                migrate::load::<JumbleConfig>(ConfigKind::Global, path).map(|_| ())
+            }
+            None => continue,
+        };
+        if let Err(e) = result {
+            issues.push(Issue {
+                path: path.to_path_buf(),
+                line: None,
+                message: format!("{:#}", e),
+            });
+        }
+    }
+    issues
+}
+
+/// A message when `output_language` names a language without translations.
+fn unsupported_language(config: &WorkspaceConfig) -> Option<String> {
+    let language = config.workspace.output_language.as_deref()?;
+    if i18n::is_supported(language) {
+        return None;
+    }
+    Some(format!(
+        "output_language '{}' has no translations, English is used (supported: {})",
+        language,
+        i18n::SUPPORTED.join(", ")
+    ))
+}
+
+/// Check doc paths, concept files, API example files and relative links
+/// inside indexed markdown docs for every discovered project.
+pub fn check_links(root: &Path) -> Result<Vec<Issue>> {
+    let (_, projects) = Server::discover_static(root)?;
+    let mut names: Vec<&String> = projects.keys().collect();
+    names.sort();
+
+    let mut issues = Vec::new();
+    for name in names {
+        let (project_dir, config, _, _, docs, _) = &projects[name];
+        let jumble_dir = project_dir.join(paths::config_dir());
+
+        for (referrer, file) in health::dangling_references(project_dir, config, docs) {
+            issues.push(match referrer {
+                Referrer::Concept(concept) => Issue {
+                    path: jumble_dir.join("project.toml"),
+                    line: None,
+                    message: format!("concept '{}' lists missing file '{}'", concept, file),
+                },
+                Referrer::Doc(topic) => Issue {
+                    path: jumble_dir.join("docs.toml"),
+                    line: None,
+                    message: format!("doc '{}' points at missing file '{}'", topic, file),
+                },
+            });
+        }
+
+        if let Some(api) = &config.api {
+            let mut examples: Vec<_> = api.examples.iter().collect();
+            examples.sort_by(|a, b| a.0.cmp(b.0));
+            for (example, entry) in examples {
+                let Some(file) = &entry.file else {
+                    continue;
+                };
+                if !paths::join_relative(project_dir, file).is_file() {
+                    issues.push(Issue {
+                        path: jumble_dir.join("project.toml"),
+                        line: None,
+                        message: format!(
+                            "api example '{}' points at missing file '{}'",
+                            example, file
+                        ),
+                    });
+                }
+            }
+        }
+
+        let mut entries: Vec<_> = docs.docs.iter().collect();
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+        for (_, doc) in entries {
+            let doc_path = paths::join_relative(project_dir, &doc.path);
+            // Missing docs were reported above.
+            if !doc_path.is_file() || !paths::has_extension(&doc_path, "md") {
+                continue;
+            }
+            let Ok(content) = text::read_text(&doc_path) else {
+                continue;
+            };
+            let base = doc_path.parent().unwrap_or(project_dir);
+            for (line, target) in relative_links(&content) {
+                let resolved = match target.strip_prefix('/') {
+                    Some(from_root) => paths::join_relative(project_dir, from_root),
+                    None => paths::join_relative(base, &target),
+                };
+                if !resolved.exists() {
+                    issues.push(Issue {
+                        path: doc_path.clone(),
+                        line: Some(line),
+                        message: format!("broken link to '{}'", target),
+                    });
+                }
+            }
+        }
+    }
+    Ok(issues)
+}
+
+/// Relative link targets in markdown, with their 1-based line numbers.
+/// Inline links, images and reference definitions are recognised; code
+/// blocks, inline code, URLs and same-page anchors are skipped.
+fn relative_links(content: &str) -> Vec<(usize, String)> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let line = strip_inline_code(line);
+        let mut targets = Vec::new();
+
+        // Reference definition: `[id]: target "title"`
+        if let Some(rest) = trimmed.strip_prefix('[') {
+            if let Some((_, target)) = rest.split_once("]:") {
+                targets.push(target.trim().to_string());
+            }
+        }
+
+        // Inline links and images: `[text](target "title")`
+        let mut rest = line.as_str();
+        while let Some(start) = rest.find("](") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find(')') else { break };
+            targets.push(after[..end].to_string());
+            rest = &after[end + 1..];
+        }
+
+        for target in targets {
+            if let Some(target) = clean_target(&target) {
+                links.push((index + 1, target));
+            }
+        }
+    }
+    links
+}
+
+fn strip_inline_code(line: &str) -> String {
+    line.split('`')
+        .enumerate()
+        .filter(|(i, _)| i % 2 == 0)
+        .map(|(_, part)| part)
+        .collect()
+}
+
+/// Reduce a link target to a local file path, or `None` if it isn't one.
+fn clean_target(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let target = match raw.strip_prefix('<') {
+        Some(rest) => rest.split('>').next().unwrap_or_default(),
+        None => raw.split_whitespace().next().unwrap_or_default(),
+    };
+    let target = target.split(['#', '?']).next().unwrap_or_default();
+
+    // `mailto:`, `https://` etc.; a single letter is a Windows drive.
+    let is_url = target.split_once(':').is_some_and(|(scheme, _)| {
+        scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphabetic())
+    });
+    if target.is_empty() || is_url {
+        return None;
+    }
+    Some(target.replace("%20", " "))
+}
+
+/// `jumble validate [--links]`
+pub fn run_validate(root: &Path, links: bool) -> Result<()> {
+    let mut issues = check_configs(root);
+    if links {
+        issues.extend(check_links(root)?);
+    }
+
+    if issues.is_empty() {
+        println!("✓ No problems found");
+        return Ok(());
+    }
+    for issue in &issues {
+        let path = issue.path.strip_prefix(root).unwrap_or(&issue.path);
+        let shown = Issue {
+            path: path.to_path_buf(),
+            line: issue.line,
+            message: issue.message.clone(),
+        };
+        println!("✗ {}", shown);
+    }
+    anyhow::bail!("{} problem(s) found", issues.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_relative_links() {
+        let doc = "\
+See [setup](setup.md#install) and ![diagram](<img/arch diagram.png>).
+Visit [site](https://example.com) or [mail](mailto:a@b.c) or [top](#top).
+Inline `[not](a-link.md)` code.
+```
+[also not](fenced.md)
+```
+[ref]: ../README.md \"Readme\"
+";
+        assert_eq!(
+            relative_links(doc),
+            vec![
+                (1, "setup.md".to_string()),
+                (1, "img/arch diagram.png".to_string()),
+                (7, "../README.md".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_links_flags_missing_targets() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path();
+        let jumble_dir = root.join(".jumble");
+        fs::create_dir_all(&jumble_dir).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(
+            jumble_dir.join("project.toml"),
+            "[project]\nname = \"app\"\ndescription = \"App\"\n\n[concepts.core]\nfiles = [\"src/gone.rs\"]\nsummary = \"Core\"\n",
+        )
+        .unwrap();
+        fs::write(
+            jumble_dir.join("docs.toml"),
+            "[docs.guide]\npath = \"docs/guide.md\"\nsummary = \"Guide\"\n\n[docs.old]\npath = \"docs/moved.md\"\nsummary = \"Old\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("docs/other.md"), "# Other\n").unwrap();
+        fs::write(
+            root.join("docs/guide.md"),
+            "# Guide\n\nSee [other](other.md) and [missing](missing.md).\n",
+        )
+        .unwrap();
+
+        let messages: Vec<String> = check_links(root)
+            .unwrap()
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        assert_eq!(messages.len(), 3, "{:?}", messages);
+        assert!(messages[0].ends_with("concept 'core' lists missing file 'src/gone.rs'"));
+        assert!(messages[1].ends_with("doc 'old' points at missing file 'docs/moved.md'"));
+        assert!(messages[2].contains("guide.md:3: broken link to 'missing.md'"));
+    }
+
+    #[test]
+    fn test_check_configs_reports_parse_errors() {
+        let temp = TempDir::new().unwrap();
+        let jumble_dir = temp.path().join(".jumble");
+        fs::create_dir_all(&jumble_dir).unwrap();
+        fs::write(
+            jumble_dir.join("project.toml"),
+            "[project]\nname = \"app\"\n",
+        )
+        .unwrap();
+
+        fs::write(
+            jumble_dir.join("workspace.toml"),
+            "[workspace]\noutput_language = \"pt-BR\"\n",
+        )
+        .unwrap();
+
+        let mut issues = check_configs(temp.path());
+        issues.sort_by(|a, b| a.path.cmp(&b.path));
+        assert_eq!(issues.len(), 2);
+        assert!(issues[0].message.contains("description"));
+        assert!(issues[1]
+            .message
+            .starts_with("output_language 'pt-BR' has no translations"));
+    }
+}
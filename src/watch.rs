@@ -0,0 +1,252 @@
+//! Reload the workspace when context files change, so long-lived sessions
+//! see edits without calling `reload_workspace`.
+//!
+//! A background thread watches the files under the workspace's and each
+//! project's `.jumble` directory (except `cache/` and the memory and state
+//! files jumble writes itself), plus every indexed doc,
+//! through the platform's file notifications. Events are debounced, then the
+//! files are fingerprinted: when one was added, removed or modified the
+//! server rediscovers, which sends `notifications/resources/updated` for
+//! subscribed resources whose contents changed and log messages for projects
+//! that came or went. Where notifications are unavailable (or the watch
+//! limit is reached) the thread falls back to polling every
+//! [`POLL_INTERVAL`]. New project directories are picked up by
+//! `reload_workspace`, not here.
+
+use notify_debouncer_mini::new_debouncer;
+use notify_debouncer_mini::notify::{RecursiveMode, Watcher};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use crate::protocol::JsonRpcOutgoing;
+use crate::server::Server;
+use crate::{memory, paths, state};
+
+/// Time between two scans when polling.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long file events must settle before the files are rescanned, so an
+/// editor's save (often a write, a rename and a metadata change) reloads once.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Files in a `.jumble` directory that tools write as they run. Changes to
+/// them are not edits to the context, and must not trigger a reload.
+const RUNTIME_FILES: [&str; 2] = [memory::MEMORY_FILE, state::STATE_FILE];
+
+/// Modification time and length of each watched file.
+pub type Fingerprint = BTreeMap<PathBuf, (Option<SystemTime>, u64)>;
+
+/// Fingerprint the files under `jumble_dirs` (skipping `cache/` and
+/// [`RUNTIME_FILES`]) and `files`.
+pub fn fingerprint(jumble_dirs: &[PathBuf], files: &[PathBuf]) -> Fingerprint {
+    let mut found = Fingerprint::new();
+    let mut add = |path: &Path| {
+        if let Ok(metadata) = path.metadata() {
+            if metadata.is_file() {
+                found.insert(
+                    path.to_path_buf(),
+                    (metadata.modified().ok(), metadata.len()),
+                );
+            }
+        }
+    };
+    for dir in jumble_dirs {
+        let entries = WalkDir::new(dir)
+            .into_iter()
+            .filter_entry(|e| !(e.file_type().is_dir() && e.file_name() == "cache"))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .filter(|e| !(e.depth() == 1 && RUNTIME_FILES.iter().any(|f| e.file_name() == *f)));
+        for entry in entries {
+            add(entry.path());
+        }
+    }
+    for file in files {
+        add(file);
+    }
+    found
+}
+
+/// The current fingerprint of `server`'s context files.
+pub fn scan(server: &Server) -> Fingerprint {
+    let (roots, docs) = server.watched_paths();
+    let dirs: Vec<PathBuf> = roots
+        .iter()
+        .map(|root| root.join(paths::config_dir()))
+        .collect();
+    fingerprint(&dirs, &docs)
+}
+
+/// Rescan, reloading `server` when anything changed since `previous`.
+/// Returns whether it reloaded.
+#[cfg(test)]
+pub fn poll(server: &Server, previous: &mut Fingerprint) -> bool {
+    rescan(server, previous, &|server: &Server| server.reload())
+}
+
+/// Rescan, calling `reload` when anything changed since `previous`.
+fn rescan<R>(server: &Server, previous: &mut Fingerprint, reload: &R) -> bool
+where
+    R: Fn(&Server) -> anyhow::Result<()>,
+{
+    let current = scan(server);
+    if current == *previous {
+        return false;
+    }
+    if let Err(e) = reload(server) {
+        eprintln!("Failed to reload after a file change: {:#}", e);
+    }
+    // Rescan: the reload may have found projects with files of their own.
+    *previous = scan(server);
+    true
+}
+
+/// Directories to watch for `server`, and how: each `.jumble` directory
+/// with everything under it, and on their own the directories holding them
+/// and the indexed docs, so that a new `.jumble` directory or a doc an
+/// editor replaced is still seen.
+fn watch_targets(server: &Server) -> BTreeMap<PathBuf, RecursiveMode> {
+    let (roots, docs) = server.watched_paths();
+    let mut targets = BTreeMap::new();
+    for root in &roots {
+        let dir = root.join(paths::config_dir());
+        if dir.is_dir() {
+            targets.insert(dir, RecursiveMode::Recursive);
+        }
+    }
+    let parents = docs.iter().filter_map(|doc| doc.parent());
+    for dir in roots.iter().map(PathBuf::as_path).chain(parents) {
+        if dir.is_dir() {
+            targets
+                .entry(dir.to_path_buf())
+                .or_insert(RecursiveMode::NonRecursive);
+        }
+    }
+    targets
+}
+
+/// Point `watcher` at `targets`, dropping the directories in `watched` that
+/// are no longer wanted. Directories removed in the meantime are skipped.
+fn sync_watches(
+    watcher: &mut dyn Watcher,
+    watched: &mut BTreeMap<PathBuf, RecursiveMode>,
+    targets: BTreeMap<PathBuf, RecursiveMode>,
+) -> notify_debouncer_mini::notify::Result<()> {
+    for (dir, mode) in watched.iter() {
+        if targets.get(dir) != Some(mode) {
+            let _ = watcher.unwatch(dir);
+        }
+    }
+    watched.retain(|dir, mode| targets.get(dir) == Some(mode));
+    for (dir, mode) in targets {
+        if watched.contains_key(&dir) {
+            continue;
+        }
+        match watcher.watch(&dir, mode) {
+            Ok(()) => {
+                watched.insert(dir, mode);
+            }
+            Err(_) if !dir.exists() => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+/// Watch in a background thread, passing the notifications a reload queued
+/// to `emit`.
+pub fn spawn<F>(server: Arc<Server>, emit: F)
+where
+    F: Fn(Vec<JsonRpcOutgoing>) + Send + 'static,
+{
+    spawn_with(server, move |server: &Server| {
+        let result = server.reload();
+        let outgoing = server.take_outgoing();
+        if !outgoing.is_empty() {
+            emit(outgoing);
+        }
+        result
+    });
+}
+
+/// Watch in a background thread, calling `reload` after a change, for
+/// servers whose clients are told about it some other way.
+pub fn spawn_with<R>(server: Arc<Server>, reload: R)
+where
+    R: Fn(&Server) -> anyhow::Result<()> + Send + 'static,
+{
+    server.set_watching();
+    std::thread::spawn(move || {
+        let mut previous = scan(&server);
+        let (tx, events) = mpsc::channel();
+        let mut watched = BTreeMap::new();
+        let watching = new_debouncer(DEBOUNCE, tx).and_then(|mut debouncer| {
+            sync_watches(debouncer.watcher(), &mut watched, watch_targets(&server))?;
+            Ok(debouncer)
+        });
+        match watching {
+            Ok(mut debouncer) => {
+                // Ends if the debouncer's thread does.
+                for _ in events {
+                    if rescan(&server, &mut previous, &reload) {
+                        let targets = watch_targets(&server);
+                        if let Err(e) = sync_watches(debouncer.watcher(), &mut watched, targets) {
+                            eprintln!("Failed to watch new context directories: {}", e);
+                        }
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "File notifications unavailable ({}); checking for changes every {} seconds",
+                    e,
+                    POLL_INTERVAL.as_secs()
+                );
+                loop {
+                    std::thread::sleep(POLL_INTERVAL);
+                    rescan(&server, &mut previous, &reload);
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_fingerprint_sees_context_changes_only() {
+        let temp = TempDir::new().unwrap();
+        let jumble = temp.path().join(".jumble");
+        fs::create_dir_all(jumble.join("cache")).unwrap();
+        fs::create_dir_all(jumble.join("skills")).unwrap();
+        fs::write(jumble.join("project.toml"), "a").unwrap();
+        let doc = temp.path().join("README.md");
+        fs::write(&doc, "readme").unwrap();
+        let dirs = [jumble.clone()];
+        let docs = [doc.clone(), temp.path().join("missing.md")];
+
+        let before = fingerprint(&dirs, &docs);
+        assert_eq!(before.len(), 2);
+
+        fs::write(jumble.join("cache/extends.json"), "{}").unwrap();
+        assert_eq!(fingerprint(&dirs, &docs), before);
+        fs::write(jumble.join(memory::MEMORY_FILE), "{}").unwrap();
+        fs::write(jumble.join(state::STATE_FILE), "{}").unwrap();
+        assert_eq!(fingerprint(&dirs, &docs), before);
+
+        fs::write(jumble.join("skills/deploy.md"), "Deploy").unwrap();
+        assert_ne!(fingerprint(&dirs, &docs), before);
+
+        let before = fingerprint(&dirs, &docs);
+        fs::write(&doc, "longer readme").unwrap();
+        assert_ne!(fingerprint(&dirs, &docs), before);
+    }
+}
@@ -0,0 +1,357 @@
+//! `jumble server --ws <addr>`: JSON-RPC over WebSocket, for agents running
+//! in browsers or Electron apps that can't spawn a stdio process.
+//!
+//! Clients connect to `/mcp` and exchange one JSON-RPC message per text
+//! message, exactly as the stdio transport exchanges lines. Each connection
+//! gets its own session ([`Server::new_session`]) over the workspace
+//! discovered at startup, and lasts until either side closes it. The
+//! `Origin` and auth token checks are the ones the HTTP transport uses;
+//! browsers can't set `Authorization` on a WebSocket, so they pass the token
+//! as `?access_token=`.
+//!
+//! The upgrade request is read and checked here; framing, fragmentation and
+//! control frames are `tungstenite`'s. Binary messages close the connection,
+//! and one over `--max-request-bytes` is answered with `REQUEST_TOO_LARGE`
+//! before the connection is closed, since the rest of it can't be skipped.
+
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::io::{self, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tungstenite::handshake::derive_accept_key;
+use tungstenite::protocol::frame::coding::CloseCode;
+use tungstenite::protocol::{CloseFrame, Role, WebSocket, WebSocketConfig};
+use tungstenite::{Error as WsError, Message};
+
+use crate::http::{self, HttpRequest, HttpResponse, MCP_PATH};
+use crate::protocol::{self, JsonRpcError, JsonRpcResponse};
+use crate::server::{Server, ServerOptions};
+
+/// Seconds to wait for a slow client to finish the handshake.
+const HANDSHAKE_TIMEOUT_SECS: u64 = 30;
+
+/// How long a read waits before letting notifications from other threads
+/// (progress, log messages) take the socket.
+const READ_POLL: Duration = Duration::from_millis(100);
+
+/// The only WebSocket version there is, from RFC 6455.
+const WEBSOCKET_VERSION: &str = "13";
+
+/// A session's socket, shared with its notifier.
+type Socket<S> = Arc<Mutex<WebSocket<S>>>;
+
+/// Check an upgrade request, returning its accept key or the response
+/// refusing it.
+fn handshake(
+    request: &HttpRequest,
+    allowed_origins: &[String],
+    auth_token: Option<&str>,
+) -> Result<String, HttpResponse> {
+    if request.path.split('?').next() != Some(MCP_PATH) {
+        return Err(HttpResponse::text("404 Not Found", "Not found"));
+    }
+    if !http::origin_allowed(request, allowed_origins) {
+        return Err(HttpResponse::text("403 Forbidden", "Origin not allowed"));
+    }
+    http::authorize(request, auth_token)?;
+    let upgrade = request
+        .header("upgrade")
+        .is_some_and(|v| v.eq_ignore_ascii_case("websocket"));
+    let version = request.header("sec-websocket-version");
+    match request.header("sec-websocket-key") {
+        Some(key) if request.method == "GET" && upgrade && version == Some(WEBSOCKET_VERSION) => {
+            Ok(derive_accept_key(key.trim().as_bytes()))
+        }
+        _ => {
+            let mut response =
+                HttpResponse::text("426 Upgrade Required", "Expected a WebSocket upgrade");
+            response.headers.push(("Upgrade", "websocket".to_string()));
+            response
+                .headers
+                .push(("Sec-WebSocket-Version", WEBSOCKET_VERSION.to_string()));
+            Err(response)
+        }
+    }
+}
+
+fn lock<S>(socket: &Socket<S>) -> std::sync::MutexGuard<'_, WebSocket<S>> {
+    socket.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn send_json<S: io::Read + Write>(
+    socket: &Socket<S>,
+    message: &impl serde::Serialize,
+) -> Result<(), WsError> {
+    let text = serde_json::to_string(message).unwrap_or_default();
+    lock(socket).send(Message::text(text))
+}
+
+/// Start closing the connection with `code`, then flush the close frame.
+fn close<S: io::Read + Write>(socket: &Socket<S>, code: CloseCode) -> Result<(), WsError> {
+    let mut socket = lock(socket);
+    let frame = CloseFrame {
+        code,
+        reason: "".into(),
+    };
+    match socket.close(Some(frame)).and_then(|()| socket.flush()) {
+        Err(WsError::ConnectionClosed | WsError::AlreadyClosed) => Ok(()),
+        result => result,
+    }
+}
+
+/// Whether a read gave up only because [`READ_POLL`] passed.
+fn timed_out(error: &WsError) -> bool {
+    matches!(error, WsError::Io(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut))
+}
+
+/// Exchange messages with one client until the connection closes. The
+/// socket's stream should time out reads, so that the lock is released for
+/// notifications while the client is quiet.
+fn run_session<S: io::Read + Write>(
+    socket: &Socket<S>,
+    server: &Server,
+    options: &ServerOptions,
+) -> Result<(), WsError> {
+    // JSON-RPC requires `"id": null` when the id could not be determined.
+    let unknown_id = options.strict.then_some(serde_json::Value::Null);
+    loop {
+        let read = lock(socket).read();
+        let text = match read {
+            Ok(Message::Text(text)) => text,
+            // Pings are answered by tungstenite on the next read or write.
+            Ok(Message::Ping(_) | Message::Pong(_) | Message::Frame(_)) => continue,
+            Ok(Message::Binary(_)) => return close(socket, CloseCode::Unsupported),
+            Err(e) if timed_out(&e) => continue,
+            // The close handshake was completed, by the client or by us.
+            Ok(Message::Close(_)) | Err(WsError::ConnectionClosed) => {
+                return match lock(socket).flush() {
+                    Err(WsError::ConnectionClosed | WsError::AlreadyClosed) => Ok(()),
+                    result => result,
+                }
+            }
+            Err(WsError::Capacity(_)) => {
+                let error = JsonRpcResponse::error(
+                    unknown_id.clone(),
+                    JsonRpcError {
+                        code: protocol::REQUEST_TOO_LARGE,
+                        message: "Request too large".to_string(),
+                        data: Some(json!({ "maxBytes": options.max_request_bytes })),
+                    },
+                );
+                send_json(socket, &error)?;
+                return close(socket, CloseCode::Size);
+            }
+            Err(WsError::Utf8(_)) => return close(socket, CloseCode::Invalid),
+            Err(WsError::Protocol(_)) => return close(socket, CloseCode::Protocol),
+            Err(e) => return Err(e),
+        };
+
+        let response = match protocol::parse_message(text.as_str(), options.strict) {
+            Ok(message) => server.handle_message(message),
+            Err(error) => Some(JsonRpcResponse::error(unknown_id.clone(), error)),
+        };
+        for outgoing in server.take_outgoing() {
+            send_json(socket, &outgoing)?;
+        }
+        if let Some(response) = response {
+            send_json(socket, &response)?;
+        }
+    }
+}
+
+/// Handshake with one connection and serve it.
+fn handle_connection(
+    mut stream: TcpStream,
+    workspace: &Server,
+    allowed_origins: &[String],
+    auth_token: Option<&str>,
+) -> Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(HANDSHAKE_TIMEOUT_SECS)))?;
+    let request = http::read_request(&stream, 0)?;
+    let accept = match handshake(&request, allowed_origins, auth_token) {
+        Ok(accept) => accept,
+        Err(response) => return Ok(response.write_to(&mut stream)?),
+    };
+    let server = workspace.new_session();
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept
+    )?;
+    stream.flush()?;
+    // Sessions sit idle between messages for as long as the client likes;
+    // reads only time out to let notifications through.
+    stream.set_read_timeout(Some(READ_POLL))?;
+    let max = workspace.options.max_request_bytes;
+    let config = WebSocketConfig::default()
+        .max_message_size(Some(max))
+        .max_frame_size(Some(max));
+    let socket = Arc::new(Mutex::new(WebSocket::from_raw_socket(
+        stream,
+        Role::Server,
+        Some(config),
+    )));
+    let notify = Arc::clone(&socket);
+    server.set_notifier(Box::new(move |message| {
+        let _ = send_json(&notify, message);
+    }));
+    run_session(&socket, &server, &workspace.options)?;
+    Ok(())
+}
+
+/// Serve WebSocket sessions of `server`'s workspace on `addr` until the
+/// process exits.
+pub fn serve(
+    addr: &str,
+    server: Server,
+    allowed_origins: Vec<String>,
+    auth_token: Option<String>,
+) -> Result<()> {
+    let listener =
+        TcpListener::bind(addr).with_context(|| format!("Failed to listen on {}", addr))?;
+    eprintln!(
+        "Serving MCP over WebSocket on ws://{}{}",
+        listener.local_addr()?,
+        MCP_PATH
+    );
+    let shared = Arc::new((server, allowed_origins, auth_token));
+    for stream in listener.incoming().flatten() {
+        let shared = Arc::clone(&shared);
+        std::thread::spawn(move || {
+            let (server, allowed_origins, auth_token) = &*shared;
+            let _ = handle_connection(stream, server, allowed_origins, auth_token.as_deref());
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A server on a loopback port taking `connections` connections, and a
+    /// client for each.
+    fn connect(server: Server, connections: usize) -> impl FnMut() -> WebSocket<TcpStream> {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming().take(connections).flatten() {
+                let _ = handle_connection(stream, &server, &[], None);
+            }
+        });
+        move || {
+            let stream = TcpStream::connect(addr).unwrap();
+            stream
+                .set_read_timeout(Some(Duration::from_secs(10)))
+                .unwrap();
+            let url = format!("ws://{}{}", addr, MCP_PATH);
+            tungstenite::client(url.as_str(), stream).unwrap().0
+        }
+    }
+
+    fn read_json(client: &mut WebSocket<TcpStream>) -> serde_json::Value {
+        loop {
+            match client.read().unwrap() {
+                Message::Text(text) => return serde_json::from_str(text.as_str()).unwrap(),
+                Message::Ping(_) | Message::Pong(_) => continue,
+                other => panic!("expected a text message, got {:?}", other),
+            }
+        }
+    }
+
+    fn close_code(client: &mut WebSocket<TcpStream>) -> Option<CloseCode> {
+        loop {
+            match client.read() {
+                Ok(Message::Close(frame)) => return frame.map(|f| f.code),
+                Ok(_) => continue,
+                Err(e) => panic!("expected a close frame, got {}", e),
+            }
+        }
+    }
+
+    #[test]
+    fn test_session_over_websocket() {
+        let temp = TempDir::new().unwrap();
+        let options = ServerOptions {
+            max_request_bytes: 200,
+            ..ServerOptions::default()
+        };
+        let server = Server::with_options(temp.path().to_path_buf(), options).unwrap();
+        let mut connect = connect(server, 3);
+
+        let mut client = connect();
+        let init = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-06-18","capabilities":{}}}"#;
+        client.send(Message::text(init)).unwrap();
+        assert_eq!(
+            read_json(&mut client)["result"]["protocolVersion"],
+            "2025-06-18"
+        );
+        client.send(Message::Ping(b"hi".to_vec().into())).unwrap();
+        assert_eq!(client.read().unwrap(), Message::Pong(b"hi".to_vec().into()));
+
+        client.send(Message::text(" ".repeat(300))).unwrap();
+        assert_eq!(
+            read_json(&mut client)["error"]["code"],
+            protocol::REQUEST_TOO_LARGE
+        );
+        assert_eq!(close_code(&mut client), Some(CloseCode::Size));
+
+        let mut client = connect();
+        client.send(Message::binary(b"{}".to_vec())).unwrap();
+        assert_eq!(close_code(&mut client), Some(CloseCode::Unsupported));
+
+        // Control frames may not carry more than 125 bytes.
+        let mut client = connect();
+        let mut ping = vec![0x89, 0x80 | 126];
+        ping.extend_from_slice(&130u16.to_be_bytes());
+        ping.extend_from_slice(&[0; 4]);
+        ping.extend_from_slice(&[0; 130]);
+        client.get_mut().write_all(&ping).unwrap();
+        assert_eq!(close_code(&mut client), Some(CloseCode::Protocol));
+    }
+
+    #[test]
+    fn test_handshake_checks_upgrade_and_origin() {
+        let mut request = HttpRequest {
+            method: "GET".to_string(),
+            path: MCP_PATH.to_string(),
+            headers: vec![
+                ("upgrade".to_string(), "websocket".to_string()),
+                ("sec-websocket-version".to_string(), "13".to_string()),
+                (
+                    "sec-websocket-key".to_string(),
+                    "dGhlIHNhbXBsZSBub25jZQ==".to_string(),
+                ),
+            ],
+            body: Vec::new(),
+        };
+        assert_eq!(
+            handshake(&request, &[], None).unwrap(),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert_eq!(
+            handshake(&request, &[], Some("secret")).unwrap_err().status,
+            "401 Unauthorized"
+        );
+        request.path = format!("{}?access_token=secret", MCP_PATH);
+        assert!(handshake(&request, &[], Some("secret")).is_ok());
+
+        request
+            .headers
+            .push(("origin".to_string(), "https://evil.example.com".to_string()));
+        assert_eq!(
+            handshake(&request, &[], None).unwrap_err().status,
+            "403 Forbidden"
+        );
+
+        request.headers.remove(0);
+        request.headers.pop();
+        assert_eq!(
+            handshake(&request, &[], None).unwrap_err().status,
+            "426 Upgrade Required"
+        );
+    }
+}